@@ -2,8 +2,73 @@ use crate::constants::{LAST_COLUMN, LAST_ROW};
 use crate::expressions::parser::stringify::{
     to_localized_string, to_string_displaced, DisplaceData,
 };
-use crate::expressions::types::CellReferenceRC;
+use crate::expressions::types::{Area, CellReferenceRC};
+use crate::expressions::utils::{
+    is_valid_column_number, is_valid_row, number_to_column, parse_reference_a1,
+};
 use crate::model::Model;
+use crate::types::MergedRange;
+
+/// The direction in which cells are shifted when cells are inserted or deleted
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum CellShift {
+    Down,
+    Right,
+    Up,
+    Left,
+}
+
+/// Returns the block of cells that moves when inserting (or deleting) the cells of `area`
+/// and the displacement. The block might go past the end of the sheet.
+pub(crate) fn get_shifted_block(area: &Area, shift: CellShift) -> (Area, i32, i32) {
+    match shift {
+        // Cells from the area to the end of the columns move down
+        CellShift::Down => (
+            Area {
+                height: LAST_ROW - area.row + 1,
+                ..*area
+            },
+            area.height,
+            0,
+        ),
+        CellShift::Right => (
+            Area {
+                width: LAST_COLUMN - area.column + 1,
+                ..*area
+            },
+            0,
+            area.width,
+        ),
+        // Cells below the area move up over it
+        CellShift::Up => (
+            Area {
+                row: area.row + area.height,
+                height: LAST_ROW - area.row + 1,
+                ..*area
+            },
+            -area.height,
+            0,
+        ),
+        CellShift::Left => (
+            Area {
+                column: area.column + area.width,
+                width: LAST_COLUMN - area.column + 1,
+                ..*area
+            },
+            0,
+            -area.width,
+        ),
+    }
+}
+
+fn area_to_range(area: &Area) -> MergedRange {
+    MergedRange {
+        first_row: area.row,
+        first_column: area.column,
+        last_row: area.row + area.height - 1,
+        last_column: area.column + area.width - 1,
+    }
+}
 
 // NOTE: There is a difference with Excel behaviour when deleting cells/rows/columns
 // In Excel if the whole range is deleted then it will substitute for #REF!
@@ -201,7 +266,7 @@ impl<'a> Model<'a> {
 
         worksheet.cols = new_columns;
 
-        Ok(())
+        self.insert_merged_columns(sheet, column, column_count)
     }
 
     /// Deletes one or more columns from the model starting at the specified index.
@@ -318,7 +383,7 @@ impl<'a> Model<'a> {
         }
         worksheet.cols = new_columns;
 
-        Ok(())
+        self.delete_merged_columns(sheet, column, column_count)
     }
 
     /// Inserts one or more new rows into the model at the specified index.
@@ -384,7 +449,7 @@ impl<'a> Model<'a> {
             }),
         )?;
 
-        Ok(())
+        self.insert_merged_rows(sheet, row, row_count)
     }
 
     /// Deletes one or more rows from the model starting at the specified index.
@@ -451,7 +516,7 @@ impl<'a> Model<'a> {
                 delta: -row_count,
             }),
         )?;
-        Ok(())
+        self.delete_merged_rows(sheet, row, row_count)
     }
 
     /// Displaces cells due to a move column action
@@ -485,6 +550,9 @@ impl<'a> Model<'a> {
             return Ok(());
         }
 
+        // Merged cells go first, this fails if the move would split a merged block
+        self.move_merged_column(sheet, column, delta)?;
+
         // Preserve cell contents, width and style of the column being moved
         let original_refs = self
             .workbook
@@ -582,6 +650,9 @@ impl<'a> Model<'a> {
             return Ok(());
         }
 
+        // Merged cells go first, this fails if the move would split a merged block
+        self.move_merged_row(sheet, row, delta)?;
+
         let original_cols = self.get_columns_for_row(sheet, row, false)?;
         let mut original_cells = Vec::new();
         for c in &original_cols {
@@ -647,4 +718,198 @@ impl<'a> Model<'a> {
 
         Ok(())
     }
+
+    /// Moves the cells of `area` `row_delta` rows down and `column_delta` columns to the right,
+    /// like cut and paste.
+    ///
+    /// Values, styles, comments and merged cells go with the cells, the cells they land on are
+    /// overwritten. All the formulas in the workbook pointing to the moved cells are updated and
+    /// the references to the overwritten cells become `#REF!`. Other references are left untouched.
+    /// Fails without changing anything if a merged block would be split, the cells would land on part
+    /// of a merged block or a cell would leave the sheet.
+    pub fn move_cells(
+        &mut self,
+        area: &Area,
+        row_delta: i32,
+        column_delta: i32,
+    ) -> Result<(), String> {
+        let sheet = area.sheet;
+        if area.width <= 0 || area.height <= 0 {
+            return Err("Invalid area".to_string());
+        }
+        // The block can go past the end of the sheet, there are no cells there
+        if area.row < 1 || area.column < 1 {
+            return Err("Invalid area".to_string());
+        }
+        if row_delta == 0 && column_delta == 0 {
+            return Ok(());
+        }
+        let block = area_to_range(area);
+        let target = area_to_range(&Area {
+            row: area.row + row_delta,
+            column: area.column + column_delta,
+            ..*area
+        });
+        let move_range = |range: &MergedRange| MergedRange {
+            first_row: range.first_row + row_delta,
+            first_column: range.first_column + column_delta,
+            last_row: range.last_row + row_delta,
+            last_column: range.last_column + column_delta,
+        };
+        let outside_error = "Cannot move cells outside of the sheet".to_string();
+
+        let worksheet = self.workbook.worksheet(sheet)?;
+        // Merged blocks go with the cells, the ones that are overwritten are removed
+        let mut merge_cells = Vec::new();
+        for range in &worksheet.merge_cells {
+            if block.contains_range(range) {
+                let moved = move_range(range);
+                if !is_valid_row(moved.first_row)
+                    || !is_valid_row(moved.last_row)
+                    || !is_valid_column_number(moved.first_column)
+                    || !is_valid_column_number(moved.last_column)
+                {
+                    return Err(outside_error);
+                }
+                merge_cells.push(moved);
+            } else if range.intersects(&block) {
+                return Err("Cannot move part of a merged cell".to_string());
+            } else if !range.intersects(&target) {
+                merge_cells.push(*range);
+            } else if !target.contains_range(range) {
+                return Err("Cannot move cells to part of a merged cell".to_string());
+            }
+        }
+
+        // The cells in the block. Formulas are kept as text that points to the same cells anywhere.
+        let sheet_name = worksheet.get_name();
+        let mut cells = Vec::new();
+        for (row, row_data) in &worksheet.sheet_data {
+            for (column, cell) in row_data {
+                if !block.contains(*row, *column) {
+                    continue;
+                }
+                let (new_row, new_column) = (row + row_delta, column + column_delta);
+                if !is_valid_row(new_row) || !is_valid_column_number(new_column) {
+                    return Err(outside_error);
+                }
+                let formula = match cell.get_formula() {
+                    Some(index) => {
+                        let node = self.parsed_formulas[sheet as usize]
+                            .get(index as usize)
+                            .ok_or("missing formula")?;
+                        let cell_reference = CellReferenceRC {
+                            sheet: sheet_name.clone(),
+                            row: *row,
+                            column: *column,
+                        };
+                        Some(to_string_displaced(
+                            node,
+                            &cell_reference,
+                            &DisplaceData::None,
+                        ))
+                    }
+                    None => None,
+                };
+                cells.push((new_row, new_column, cell.clone(), formula));
+            }
+        }
+        // We do not need this, but it eliminates sources of randomness in the algorithm
+        cells.sort_by_key(|(row, column, _, _)| (*row, *column));
+
+        // Comments go with the cells too
+        let mut comments = Vec::new();
+        for comment in &worksheet.comments {
+            let cell_reference = match parse_reference_a1(&comment.cell_ref) {
+                Some(cell_reference) => cell_reference,
+                None => {
+                    comments.push(comment.clone());
+                    continue;
+                }
+            };
+            let (row, column) = (cell_reference.row, cell_reference.column);
+            if block.contains(row, column) {
+                let (row, column) = (row + row_delta, column + column_delta);
+                if !is_valid_row(row) {
+                    return Err(outside_error);
+                }
+                let column_name = number_to_column(column).ok_or(outside_error.clone())?;
+                let mut comment = comment.clone();
+                comment.cell_ref = format!("{column_name}{row}");
+                comments.push(comment);
+            } else if !target.contains(row, column) {
+                comments.push(comment.clone());
+            }
+        }
+
+        let worksheet = self.workbook.worksheet_mut(sheet)?;
+        for (row, row_data) in worksheet.sheet_data.iter_mut() {
+            row_data.retain(|column, _| {
+                !block.contains(*row, *column) && !target.contains(*row, *column)
+            });
+        }
+        worksheet
+            .sheet_data
+            .retain(|_, row_data| !row_data.is_empty());
+        worksheet.merge_cells = merge_cells;
+        worksheet.comments = comments;
+
+        for (row, column, cell, formula) in cells {
+            match formula {
+                Some(formula) => {
+                    self.update_cell_with_formula(sheet, row, column, format!("={formula}"))?;
+                    self.workbook.worksheet_mut(sheet)?.set_cell_style(
+                        row,
+                        column,
+                        cell.get_style(),
+                    )?;
+                }
+                None => self
+                    .workbook
+                    .worksheet_mut(sheet)?
+                    .update_cell(row, column, cell)?,
+            }
+        }
+
+        // Update all formulas in the workbook
+        self.displace_cells(&DisplaceData::RangeMove {
+            sheet,
+            row: area.row,
+            column: area.column,
+            width: area.width,
+            height: area.height,
+            row_delta,
+            column_delta,
+        })
+    }
+
+    /// Inserts blank cells in `area` shifting the cells below it down
+    pub fn insert_cells_shift_down(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Down)
+    }
+
+    /// Inserts blank cells in `area` shifting the cells on its right to the right
+    pub fn insert_cells_shift_right(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Right)
+    }
+
+    /// Deletes the cells of `area` shifting the cells below it up.
+    /// References to the deleted cells become `#REF!`.
+    pub fn delete_cells_shift_up(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Up)
+    }
+
+    /// Deletes the cells of `area` shifting the cells on its right to the left.
+    /// References to the deleted cells become `#REF!`.
+    pub fn delete_cells_shift_left(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Left)
+    }
+
+    fn shift_cells(&mut self, area: &Area, shift: CellShift) -> Result<(), String> {
+        if area.width <= 0 || area.height <= 0 {
+            return Err("Invalid area".to_string());
+        }
+        let (block, row_delta, column_delta) = get_shifted_block(area, shift);
+        self.move_cells(&block, row_delta, column_delta)
+    }
 }
@@ -0,0 +1,337 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use crate::{
+    cell::CellValue,
+    expressions::parser::parse_range,
+    formatter::dates::{date_to_serial_number, from_excel_date},
+    functions::util::from_wildcard_to_regex,
+    model::Model,
+    types::{
+        AutoFilter, Color, ColumnFilter, CustomFilter, DateSystem, DynamicFilterType,
+        FilterOperator, PatternType, Theme,
+    },
+    utils::is_valid_hex_color,
+};
+
+// NOTE: Like in Excel filters are non dynamic. They are applied once when the criteria changes
+// (or when the user asks to reapply them) by hiding the rows that do not match.
+
+/// First and last date (serial numbers, both included) of the period that includes `date`.
+/// `offset` moves the period forward (1) or backwards (-1).
+fn get_period(
+    date: NaiveDate,
+    months_in_period: u32,
+    offset: i32,
+    date_system: DateSystem,
+) -> Option<(i32, i32)> {
+    let month0 = date.month0() / months_in_period * months_in_period;
+    let start = NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1)?;
+    let shift = Months::new(months_in_period * offset.unsigned_abs());
+    let start = if offset < 0 {
+        start.checked_sub_months(shift)?
+    } else {
+        start.checked_add_months(shift)?
+    };
+    let end = start
+        .checked_add_months(Months::new(months_in_period))?
+        .checked_sub_days(Days::new(1))?;
+    Some((to_serial(start, date_system)?, to_serial(end, date_system)?))
+}
+
+fn to_serial(date: NaiveDate, date_system: DateSystem) -> Option<i32> {
+    date_to_serial_number(date.day(), date.month(), date.year(), date_system).ok()
+}
+
+/// Returns the range of dates (serial numbers, both included) of a date filter relative to `today`
+fn get_date_range(
+    filter_type: &DynamicFilterType,
+    today: i32,
+    date_system: DateSystem,
+) -> Option<(i32, i32)> {
+    let date = from_excel_date(today as i64, date_system).ok()?;
+    // Weeks start on Sunday
+    let week_start = today - date.weekday().num_days_from_sunday() as i32;
+    match filter_type {
+        DynamicFilterType::Tomorrow => Some((today + 1, today + 1)),
+        DynamicFilterType::Today => Some((today, today)),
+        DynamicFilterType::Yesterday => Some((today - 1, today - 1)),
+        DynamicFilterType::NextWeek => Some((week_start + 7, week_start + 13)),
+        DynamicFilterType::ThisWeek => Some((week_start, week_start + 6)),
+        DynamicFilterType::LastWeek => Some((week_start - 7, week_start - 1)),
+        DynamicFilterType::NextMonth => get_period(date, 1, 1, date_system),
+        DynamicFilterType::ThisMonth => get_period(date, 1, 0, date_system),
+        DynamicFilterType::LastMonth => get_period(date, 1, -1, date_system),
+        DynamicFilterType::NextQuarter => get_period(date, 3, 1, date_system),
+        DynamicFilterType::ThisQuarter => get_period(date, 3, 0, date_system),
+        DynamicFilterType::LastQuarter => get_period(date, 3, -1, date_system),
+        DynamicFilterType::NextYear => get_period(date, 12, 1, date_system),
+        DynamicFilterType::ThisYear => get_period(date, 12, 0, date_system),
+        DynamicFilterType::LastYear => get_period(date, 12, -1, date_system),
+        DynamicFilterType::YearToDate => {
+            let (start, _) = get_period(date, 12, 0, date_system)?;
+            Some((start, today))
+        }
+        DynamicFilterType::AboveAverage
+        | DynamicFilterType::BelowAverage
+        | DynamicFilterType::Quarter(_)
+        | DynamicFilterType::Month(_) => None,
+    }
+}
+
+fn compare_text(operator: &FilterOperator, text: &str, value: &str) -> bool {
+    let text = text.to_uppercase();
+    let value = value.to_uppercase();
+    match operator {
+        FilterOperator::Equal | FilterOperator::NotEqual => {
+            let is_equal = match from_wildcard_to_regex(&value, true) {
+                Ok(re) => re.is_match(&text),
+                Err(_) => text == value,
+            };
+            is_equal == (operator == &FilterOperator::Equal)
+        }
+        FilterOperator::LessThan => text < value,
+        FilterOperator::LessThanOrEqual => text <= value,
+        FilterOperator::GreaterThanOrEqual => text >= value,
+        FilterOperator::GreaterThan => text > value,
+    }
+}
+
+fn compare_number(operator: &FilterOperator, number: f64, value: f64) -> bool {
+    match operator {
+        FilterOperator::Equal => (number - value).abs() < f64::EPSILON,
+        FilterOperator::NotEqual => (number - value).abs() >= f64::EPSILON,
+        FilterOperator::LessThan => number < value,
+        FilterOperator::LessThanOrEqual => number <= value,
+        FilterOperator::GreaterThanOrEqual => number >= value,
+        FilterOperator::GreaterThan => number > value,
+    }
+}
+
+fn matches_custom_filter(filter: &CustomFilter, value: &CellValue, text: &str) -> bool {
+    match (value, filter.value.parse::<f64>()) {
+        (CellValue::Number(number), Ok(criteria)) => {
+            compare_number(&filter.operator, *number, criteria)
+        }
+        // Numbers are not equal to text, but any number is different from a text
+        (CellValue::Number(_), Err(_)) => filter.operator == FilterOperator::NotEqual,
+        (CellValue::None, _) => match filter.operator {
+            FilterOperator::Equal => filter.value.is_empty(),
+            FilterOperator::NotEqual => !filter.value.is_empty(),
+            _ => false,
+        },
+        _ => compare_text(&filter.operator, text, &filter.value),
+    }
+}
+
+fn same_color(color: &Option<Color>, criteria: &str, theme: &Theme) -> bool {
+    match color {
+        Some(color) => color.to_rgb(theme).eq_ignore_ascii_case(criteria),
+        None => false,
+    }
+}
+
+impl<'a> Model<'a> {
+    /// Sets the AutoFilter of a table. A table has filters if any of the columns has criteria.
+    pub(crate) fn set_table_auto_filter(
+        &mut self,
+        table_name: &str,
+        auto_filter: Option<AutoFilter>,
+    ) -> Result<(), String> {
+        let table = self
+            .workbook
+            .tables
+            .get_mut(table_name)
+            .ok_or_else(|| format!("Table not found: '{table_name}'"))?;
+        table.has_filters = auto_filter
+            .as_ref()
+            .is_some_and(|f| !f.filter_columns.is_empty());
+        table.auto_filter = auto_filter;
+        Ok(())
+    }
+
+    /// Returns the rows in the range of the `auto_filter` (excluding the header row) that do not
+    /// pass the criteria of the filter.
+    pub(crate) fn get_auto_filter_hidden_rows(
+        &self,
+        sheet: u32,
+        auto_filter: &AutoFilter,
+    ) -> Result<Vec<i32>, String> {
+        let (first_column, first_row, last_column, last_row) = parse_range(&auto_filter.reference)?;
+        let worksheet = self.workbook.worksheet(sheet)?;
+        let mut hidden_rows = Vec::new();
+        for filter_column in &auto_filter.filter_columns {
+            let column = first_column + filter_column.col_id as i32;
+            if column > last_column {
+                return Err(format!("Invalid filter column: {}", filter_column.col_id));
+            }
+            // values of the column. Cells with an empty string are considered blank
+            let mut values = Vec::new();
+            for row in first_row + 1..=last_row {
+                let value = match worksheet.cell(row, column) {
+                    Some(cell) => match cell.value(&self.workbook.shared_strings, self.language) {
+                        CellValue::String(s) if s.is_empty() => CellValue::None,
+                        value => value,
+                    },
+                    None => CellValue::None,
+                };
+                values.push((row, value));
+            }
+            let filter = &filter_column.filter;
+            let numbers: Vec<f64> = values
+                .iter()
+                .filter_map(|(_, value)| match value {
+                    CellValue::Number(number) => Some(*number),
+                    _ => None,
+                })
+                .collect();
+            // The Top10 filter and the average filters are relative to the values in the column
+            let threshold = match filter {
+                ColumnFilter::Top10 {
+                    top,
+                    percent,
+                    value,
+                } => {
+                    let mut sorted = numbers.clone();
+                    sorted.sort_by(|a, b| a.total_cmp(b));
+                    if *top {
+                        sorted.reverse();
+                    }
+                    let count = if *percent {
+                        (sorted.len() as f64 * value / 100.0).floor() as usize
+                    } else {
+                        value.floor() as usize
+                    };
+                    sorted.get(count.max(1) - 1).or(sorted.last()).copied()
+                }
+                ColumnFilter::Dynamic {
+                    filter_type: DynamicFilterType::AboveAverage | DynamicFilterType::BelowAverage,
+                } => {
+                    if numbers.is_empty() {
+                        None
+                    } else {
+                        Some(numbers.iter().sum::<f64>() / numbers.len() as f64)
+                    }
+                }
+                _ => None,
+            };
+            let today = self
+                .current_excel_serial_with_timezone(self.tz)
+                .map(|serial| serial.floor() as i32);
+            let date_system = self.workbook.settings.date_system;
+            for (row, value) in values {
+                let is_visible = match filter {
+                    ColumnFilter::Values {
+                        values: criteria,
+                        blank,
+                    } => match value {
+                        CellValue::None => *blank,
+                        _ => {
+                            let text = self.get_formatted_cell_value(sheet, row, column)?;
+                            let text = text.to_uppercase();
+                            criteria.iter().any(|c| c.to_uppercase() == text)
+                        }
+                    },
+                    ColumnFilter::Custom { and, filters } => {
+                        let text = self.get_formatted_cell_value(sheet, row, column)?;
+                        let mut results = filters
+                            .iter()
+                            .map(|f| matches_custom_filter(f, &value, &text));
+                        if *and {
+                            results.all(|r| r)
+                        } else {
+                            results.any(|r| r)
+                        }
+                    }
+                    ColumnFilter::Top10 { top, .. } => match (value, threshold) {
+                        (CellValue::Number(number), Some(threshold)) => {
+                            if *top {
+                                number >= threshold
+                            } else {
+                                number <= threshold
+                            }
+                        }
+                        _ => false,
+                    },
+                    ColumnFilter::Dynamic { filter_type } => match value {
+                        CellValue::Number(number) => match filter_type {
+                            DynamicFilterType::AboveAverage => {
+                                threshold.is_some_and(|average| number > average)
+                            }
+                            DynamicFilterType::BelowAverage => {
+                                threshold.is_some_and(|average| number < average)
+                            }
+                            DynamicFilterType::Quarter(quarter) => {
+                                from_excel_date(number.floor() as i64, date_system)
+                                    .is_ok_and(|date| date.month0() / 3 + 1 == *quarter)
+                            }
+                            DynamicFilterType::Month(month) => {
+                                from_excel_date(number.floor() as i64, date_system)
+                                    .is_ok_and(|date| date.month() == *month)
+                            }
+                            _ => {
+                                let day = number.floor() as i32;
+                                match today
+                                    .and_then(|t| get_date_range(filter_type, t, date_system))
+                                {
+                                    Some((start, end)) => start <= day && day <= end,
+                                    None => false,
+                                }
+                            }
+                        },
+                        _ => false,
+                    },
+                    ColumnFilter::Color { cell_color, color } => {
+                        let style = self.get_style_for_cell(sheet, row, column)?;
+                        let theme = self.get_theme();
+                        if *cell_color {
+                            style.fill.pattern_type != PatternType::None
+                                && same_color(&style.fill.fg_color, color, theme)
+                        } else {
+                            same_color(&style.font.color, color, theme)
+                        }
+                    }
+                };
+                if !is_visible && !hidden_rows.contains(&row) {
+                    hidden_rows.push(row);
+                }
+            }
+        }
+        hidden_rows.sort_unstable();
+        Ok(hidden_rows)
+    }
+}
+
+/// Checks that the criteria of a filter are valid
+pub(crate) fn validate_column_filter(filter: &ColumnFilter) -> Result<(), String> {
+    match filter {
+        ColumnFilter::Values { .. } => Ok(()),
+        ColumnFilter::Custom { filters, .. } => {
+            if filters.is_empty() || filters.len() > 2 {
+                return Err("A custom filter needs one or two criteria".to_string());
+            }
+            Ok(())
+        }
+        ColumnFilter::Top10 { percent, value, .. } => {
+            let max = if *percent { 100.0 } else { 500.0 };
+            if *value < 1.0 || *value > max {
+                return Err(format!("Invalid top 10 value: {value}"));
+            }
+            Ok(())
+        }
+        ColumnFilter::Dynamic { filter_type } => match filter_type {
+            DynamicFilterType::Quarter(q) if !(1..=4).contains(q) => {
+                Err(format!("Invalid quarter: {q}"))
+            }
+            DynamicFilterType::Month(m) if !(1..=12).contains(m) => {
+                Err(format!("Invalid month: {m}"))
+            }
+            _ => Ok(()),
+        },
+        ColumnFilter::Color { color, .. } => {
+            if !is_valid_hex_color(color) {
+                return Err(format!("Invalid color: '{color}'."));
+            }
+            Ok(())
+        }
+    }
+}
@@ -25,7 +25,8 @@ impl<'a> Model<'a> {
                     currencies.push(currency);
                 }
                 // Try to parse as a formatted number (e.g., dates, currencies, percentages)
-                if let Ok((v, _number_format)) = parse_formatted_number(s, &currencies, self.locale)
+                if let Ok((v, _number_format)) =
+                    parse_formatted_number(s, &currencies, self.locale, self.get_date_system())
                 {
                     return Some(v);
                 }
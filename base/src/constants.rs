@@ -12,6 +12,9 @@ pub(crate) const DEFAULT_WINDOW_WIDTH: i64 = 800;
 pub(crate) const LAST_COLUMN: i32 = 16_384;
 pub(crate) const LAST_ROW: i32 = 1_048_576;
 
+// Rows and columns can be grouped up to seven levels deep
+pub(crate) const MAX_OUTLINE_LEVEL: u8 = 7;
+
 // Excel uses 15 significant digits of precision for all numeric calculations.
 pub(crate) const EXCEL_PRECISION: usize = 15;
 
@@ -26,3 +29,7 @@ pub(crate) const MINIMUM_DATE_SERIAL_NUMBER: i32 = 1;
 // Excel can handle dates until the year 9999-12-31
 // 2958465 is the number of days from 1900-01-01 to 9999-12-31
 pub(crate) const MAXIMUM_DATE_SERIAL_NUMBER: i32 = 2_958_465;
+
+// A date in the 1904 date system has a serial number 1462 smaller than in the 1900 date system
+// 1462 is the serial number of 1904-01-01 in the 1900 date system
+pub(crate) const DATE_1904_OFFSET: i32 = 1_462;
@@ -238,6 +238,7 @@ impl<'a> Lexer<'a> {
 
     /// Consumes a reference in R1C1 style like:
     /// R12C3, R[2]C[-2], R3C[6], R[-3]C4, RC1, R[-2]C
+    /// The letters are the ones of the language, i.e. Z12S3 in German
     pub(super) fn consume_reference_r1c1(&mut self) -> Result<ParsedReference> {
         // R12C3, R[2]C[-2], R3C[6], R[-3]C4, RC1, R[-2]C
         let absolute_column;
@@ -245,7 +246,7 @@ impl<'a> Lexer<'a> {
         let position = self.position;
         let row;
         let column;
-        self.expect_char('R')?;
+        self.expect_char(self.language.r1c1.row)?;
         match self.peek_char() {
             Some('[') => {
                 absolute_row = false;
@@ -278,7 +279,7 @@ impl<'a> Lexer<'a> {
                 return Err(self.set_error("Expected row number or '['", position));
             }
         }
-        self.expect_char('C')?;
+        self.expect_char(self.language.r1c1.column)?;
         match self.peek_char() {
             Some('[') => {
                 self.expect_char('[')?;
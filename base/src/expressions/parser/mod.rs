@@ -273,27 +273,32 @@ impl<'a> Parser<'a> {
         self.defined_names = defined_names;
     }
 
+    pub fn set_tables(&mut self, tables: HashMap<String, Table>) {
+        self.tables = tables;
+    }
+
     pub fn parse(&mut self, formula: &str, context: &CellReferenceRC) -> Node {
         self.lexer.set_formula(formula);
         self.context = context.clone();
         self.parse_expr()
     }
 
-    // Returns the token used to separate arguments in functions and arrays
+    // Returns the token used to separate arguments in functions and columns in arrays
     // If the locale decimal separator is '.', then it is a comma ','
     // Otherwise, it is a semicolon ';'
     fn get_argument_separator_token(&self) -> TokenType {
-        if self.locale.numbers.symbols.decimal == "." {
+        if self.locale.argument_separator() == ',' {
             TokenType::Comma
         } else {
             TokenType::Semicolon
         }
     }
 
-    // Returns the token used to separate columns in arrays
+    // Returns the token used to separate rows in arrays
     // If the locale decimal separator is '.', then it is a semicolon ';'
-    fn get_column_separator_token(&self) -> TokenType {
-        if self.locale.numbers.symbols.decimal == "." {
+    // Otherwise, it is a backslash '\'
+    fn get_row_separator_token(&self) -> TokenType {
+        if self.locale.array_row_separator() == ';' {
             TokenType::Semicolon
         } else {
             TokenType::Backslash
@@ -599,7 +604,7 @@ impl<'a> Parser<'a> {
             TokenType::String(s) => Node::StringKind(s),
             TokenType::LeftBrace => {
                 // It's an array. It's a collection of rows all of the same dimension
-                let column_separator_token = self.get_column_separator_token();
+                let row_separator_token = self.get_row_separator_token();
 
                 let first_row = match self.parse_array_row() {
                     Ok(s) => s,
@@ -610,7 +615,7 @@ impl<'a> Parser<'a> {
                 let mut matrix = Vec::new();
                 matrix.push(first_row);
                 let mut next_token = self.lexer.peek_token();
-                while next_token == column_separator_token {
+                while next_token == row_separator_token {
                     self.lexer.advance_token();
                     let row = match self.parse_array_row() {
                         Ok(s) => s,
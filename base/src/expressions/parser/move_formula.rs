@@ -58,11 +58,13 @@ fn move_function(
 ) -> String {
     let mut first = true;
     let mut arguments = "".to_string();
+    let arg_separator = locale.argument_separator();
     for el in args {
         if !first {
             arguments = format!(
-                "{},{}",
+                "{}{}{}",
                 arguments,
+                arg_separator,
                 to_string_moved(el, move_context, locale, language)
             );
         } else {
@@ -98,7 +100,7 @@ pub(crate) fn to_string_array_node(
         }
         ArrayNode::Number(number) => format_number_locale(*number, locale),
         ArrayNode::String(value) => format!("\"{value}\""),
-        ArrayNode::Error(kind) => format!("{kind}"),
+        ArrayNode::Error(kind) => kind.to_localized_error_string(language),
     }
 }
 
@@ -110,7 +112,13 @@ fn to_string_moved(
 ) -> String {
     use self::Node::*;
     match node {
-        BooleanKind(value) => format!("{value}").to_uppercase(),
+        BooleanKind(value) => {
+            if *value {
+                language.booleans.r#true.to_string()
+            } else {
+                language.booleans.r#false.to_string()
+            }
+        }
         NumberKind(number) => format_number_locale(*number, locale),
         StringKind(value) => format!("\"{value}\""),
         ReferenceKind {
@@ -174,6 +182,7 @@ fn to_string_moved(
                 },
                 false,
                 false,
+                language,
             )
         }
         RangeKind {
@@ -268,6 +277,7 @@ fn to_string_moved(
                 },
                 full_row,
                 full_column,
+                language,
             );
             let s2 = stringify_reference(
                 Some(&context),
@@ -282,6 +292,7 @@ fn to_string_moved(
                 },
                 full_row,
                 full_column,
+                language,
             );
             format!("{s1}:{s2}")
         }
@@ -314,6 +325,7 @@ fn to_string_moved(
                 },
                 false,
                 false,
+                language,
             )
         }
         WrongRangeKind {
@@ -352,6 +364,7 @@ fn to_string_moved(
                 },
                 full_row,
                 full_column,
+                language,
             );
             let s2 = stringify_reference(
                 Some(&context),
@@ -366,6 +379,7 @@ fn to_string_moved(
                 },
                 full_row,
                 full_column,
+                language,
             );
             format!("{s1}:{s2}")
         }
@@ -435,42 +449,24 @@ fn to_string_moved(
         ArrayKind(args) => {
             let mut first_row = true;
             let mut matrix_string = String::new();
-
-            // Each element in `args` is assumed to be one "row" (itself a `Vec<T>`).
-            let row_separator = if locale.numbers.symbols.decimal == "." {
-                ';'
-            } else {
-                '/'
-            };
-            let col_separator = if row_separator == ';' { ',' } else { ';' };
+            let row_separator = locale.array_row_separator();
+            let col_separator = locale.argument_separator();
             for row in args {
                 if !first_row {
-                    matrix_string.push(col_separator);
+                    matrix_string.push(row_separator);
                 } else {
                     first_row = false;
                 }
-
-                // Build the string for the current row
                 let mut first_col = true;
-                let mut row_string = String::new();
                 for el in row {
                     if !first_col {
-                        row_string.push(row_separator);
+                        matrix_string.push(col_separator);
                     } else {
                         first_col = false;
                     }
-
-                    // Reuse your existing element-stringification function
-                    row_string.push_str(&to_string_array_node(el, locale, language));
+                    matrix_string.push_str(&to_string_array_node(el, locale, language));
                 }
-
-                // Enclose the row in braces
-                matrix_string.push('{');
-                matrix_string.push_str(&row_string);
-                matrix_string.push('}');
             }
-
-            // Enclose the whole matrix in braces
             format!("{{{matrix_string}}}")
         }
         DefinedNameKind((name, ..)) => name.to_string(),
@@ -492,7 +488,7 @@ fn to_string_moved(
                 to_string_moved(right, move_context, locale, language)
             ),
         },
-        ErrorKind(kind) => format!("{kind}"),
+        ErrorKind(kind) => kind.to_localized_error_string(language),
         ParseErrorKind {
             formula,
             message: _,
@@ -5,7 +5,10 @@ use crate::expressions::parser::static_analysis::add_implicit_intersection;
 use crate::expressions::token::{OpSum, OpUnary};
 use crate::language::{get_language, Language};
 use crate::locale::{get_locale, Locale};
-use crate::{expressions::types::CellReferenceRC, number_format::to_excel_precision_str};
+use crate::{
+    expressions::types::{Area, CellReferenceRC},
+    number_format::to_excel_precision_str,
+};
 
 pub enum DisplaceData {
     Column {
@@ -40,6 +43,16 @@ pub enum DisplaceData {
         column: i32,
         delta: i32,
     },
+    /// The block of cells starting at (row, column) is moved, the cells it lands on are overwritten
+    RangeMove {
+        sheet: u32,
+        row: i32,
+        column: i32,
+        width: i32,
+        height: i32,
+        row_delta: i32,
+        column_delta: i32,
+    },
     None,
 }
 
@@ -70,6 +83,12 @@ pub fn to_localized_string(
     )
 }
 
+/// This is the mode used to display the formula in R1C1 notation in the UI
+/// References are relative to the cell, i.e. `R[-1]C` is the cell above (`Z(-1)S` in German)
+pub fn to_localized_r1c1_string(node: &Node, locale: &Locale, language: &Language) -> String {
+    stringify(node, None, &DisplaceData::None, false, locale, language)
+}
+
 /// This is the mode used to export the formula to Excel
 /// Internally the locale and language are always "en"
 pub fn to_excel_string(node: &Node, context: &CellReferenceRC) -> String {
@@ -99,16 +118,91 @@ pub fn to_string_displaced(
     stringify(node, Some(context), displace_data, false, locale, language)
 }
 
+/// Same as [to_string_displaced] but in the locale and language of the user
+pub(crate) fn to_localized_string_displaced(
+    node: &Node,
+    context: &CellReferenceRC,
+    displace_data: &DisplaceData,
+    locale: &Locale,
+    language: &Language,
+) -> String {
+    stringify(node, Some(context), displace_data, false, locale, language)
+}
+
+// When a block of cells is moved a range follows the cells at its corners only if it spans the
+// same columns (rows) as the block in the direction of the move, or if it is inside the block.
+// Otherwise the range is left untouched.
+fn range_follows_move(
+    context: Option<&CellReferenceRC>,
+    displace_data: &DisplaceData,
+    sheet_index: u32,
+    (row1, absolute_row1): (i32, bool),
+    (column1, absolute_column1): (i32, bool),
+    (row2, absolute_row2): (i32, bool),
+    (column2, absolute_column2): (i32, bool),
+) -> bool {
+    let (context, (sheet, row, column, width, height, row_delta, column_delta)) =
+        match (context, displace_data) {
+            (
+                Some(context),
+                DisplaceData::RangeMove {
+                    sheet,
+                    row,
+                    column,
+                    width,
+                    height,
+                    row_delta,
+                    column_delta,
+                },
+            ) => (
+                context,
+                (
+                    *sheet,
+                    *row,
+                    *column,
+                    *width,
+                    *height,
+                    *row_delta,
+                    *column_delta,
+                ),
+            ),
+            _ => return true,
+        };
+    if sheet_index != sheet {
+        return true;
+    }
+    let resolve = |value: i32, absolute: bool, offset: i32| {
+        if absolute {
+            value
+        } else {
+            value + offset
+        }
+    };
+    let first_row = resolve(row1, absolute_row1, context.row);
+    let last_row = resolve(row2, absolute_row2, context.row);
+    let first_column = resolve(column1, absolute_column1, context.column);
+    let last_column = resolve(column2, absolute_column2, context.column);
+    let rows_inside = first_row >= row && last_row < row + height;
+    let columns_inside = first_column >= column && last_column < column + width;
+    match (row_delta != 0, column_delta != 0) {
+        (true, false) => columns_inside,
+        (false, true) => rows_inside,
+        _ => rows_inside && columns_inside,
+    }
+}
+
 /// Converts a local reference to a string applying some displacement if needed.
 /// It uses A1 style if context is not None. If context is None it uses R1C1 style
 /// If full_row is true then the row details will be omitted in the A1 case
 /// If full_column is true then column details will be omitted.
+/// The letters of R1C1 references are the ones of the language.
 pub(crate) fn stringify_reference(
     context: Option<&CellReferenceRC>,
     displace_data: &DisplaceData,
     reference: &Reference,
     full_row: bool,
     full_column: bool,
+    language: &Language,
 ) -> String {
     let sheet_name = reference.sheet_name;
     let sheet_index = reference.sheet_index;
@@ -245,6 +339,34 @@ pub(crate) fn stringify_reference(
                         }
                     }
                 }
+                DisplaceData::RangeMove {
+                    sheet,
+                    row: move_row,
+                    column: move_column,
+                    width,
+                    height,
+                    row_delta,
+                    column_delta,
+                } => {
+                    let in_block = |r: i32, c: i32| {
+                        r >= *move_row
+                            && r < move_row + height
+                            && c >= *move_column
+                            && c < move_column + width
+                    };
+                    if sheet_index == *sheet && !full_row && !full_column {
+                        if in_block(row, column) {
+                            row += *row_delta;
+                            column += *column_delta;
+                            if row > LAST_ROW || column > LAST_COLUMN {
+                                return "#REF!".to_string();
+                            }
+                        } else if in_block(row - row_delta, column - column_delta) {
+                            // The cell was overwritten by the moved block
+                            return "#REF!".to_string();
+                        }
+                    }
+                }
                 DisplaceData::None => {}
             }
             if row < 1 {
@@ -280,15 +402,17 @@ pub(crate) fn stringify_reference(
             }
         }
         None => {
+            let row_letter = language.r1c1.row;
+            let column_letter = language.r1c1.column;
             let row_abs = if absolute_row {
-                format!("R{row}")
+                format!("{row_letter}{row}")
             } else {
-                format!("R[{row}]")
+                format!("{row_letter}[{row}]")
             };
             let col_abs = if absolute_column {
-                format!("C{column}")
+                format!("{column_letter}{column}")
             } else {
-                format!("C[{column}]")
+                format!("{column_letter}[{column}]")
             };
             match &sheet_name {
                 Some(name) => {
@@ -313,11 +437,7 @@ fn format_function(
 ) -> String {
     let mut first = true;
     let mut arguments = "".to_string();
-    let arg_separator = if locale.numbers.symbols.decimal == "." {
-        ','
-    } else {
-        ';'
-    };
+    let arg_separator = locale.argument_separator();
     for el in args {
         if !first {
             arguments = format!(
@@ -418,6 +538,7 @@ fn stringify(
             },
             false,
             false,
+            language,
         ),
         ReferenceKind {
             sheet_name,
@@ -439,6 +560,7 @@ fn stringify(
             },
             false,
             false,
+            language,
         ),
         RangeKind {
             sheet_name,
@@ -460,6 +582,19 @@ fn stringify(
                 && *absolute_column2
                 && (*column1 == 1)
                 && (*column2 == LAST_COLUMN);
+            let displace_data = if range_follows_move(
+                context,
+                displace_data,
+                *sheet_index,
+                (*row1, *absolute_row1),
+                (*column1, *absolute_column1),
+                (*row2, *absolute_row2),
+                (*column2, *absolute_column2),
+            ) {
+                displace_data
+            } else {
+                &DisplaceData::None
+            };
             let s1 = stringify_reference(
                 context,
                 displace_data,
@@ -473,6 +608,7 @@ fn stringify(
                 },
                 full_row,
                 full_column,
+                language,
             );
             let s2 = stringify_reference(
                 context,
@@ -487,6 +623,7 @@ fn stringify(
                 },
                 full_row,
                 full_column,
+                language,
             );
             format!("{s1}:{s2}")
         }
@@ -522,6 +659,7 @@ fn stringify(
                 },
                 full_row,
                 full_column,
+                language,
             );
             let s2 = stringify_reference(
                 context,
@@ -536,6 +674,7 @@ fn stringify(
                 },
                 full_row,
                 full_column,
+                language,
             );
             format!("{s1}:{s2}")
         }
@@ -796,12 +935,8 @@ fn stringify(
         ArrayKind(args) => {
             let mut first_row = true;
             let mut matrix_string = String::new();
-            let row_separator = if locale.numbers.symbols.decimal == "." {
-                ';'
-            } else {
-                '/'
-            };
-            let col_separator = if row_separator == ';' { ',' } else { ';' };
+            let row_separator = locale.array_row_separator();
+            let col_separator = locale.argument_separator();
 
             for row in args {
                 if !first_row {
@@ -893,7 +1028,7 @@ fn stringify(
                 )
             }
         },
-        ErrorKind(kind) => format!("{kind}"),
+        ErrorKind(kind) => kind.to_localized_error_string(language),
         ParseErrorKind {
             formula,
             position: _,
@@ -1138,3 +1273,143 @@ pub(crate) fn rename_defined_name_in_node(
         Node::WrongVariableKind(_) => {}
     }
 }
+
+// Renames the tables in the node, `tables` are pairs of (old name, new name)
+pub(crate) fn rename_tables_in_node(node: &mut Node, tables: &[(String, String)]) {
+    match node {
+        // Rename
+        Node::TableNameKind(name) => {
+            let lower = name.to_lowercase();
+            if let Some((_, new_name)) = tables
+                .iter()
+                .find(|(old_name, _)| old_name.to_lowercase() == lower)
+            {
+                *name = new_name.to_string();
+            }
+        }
+        // Go next level
+        Node::OpRangeKind { left, right }
+        | Node::OpConcatenateKind { left, right }
+        | Node::OpSumKind { left, right, .. }
+        | Node::OpProductKind { left, right, .. }
+        | Node::OpPowerKind { left, right }
+        | Node::CompareKind { left, right, .. } => {
+            rename_tables_in_node(left, tables);
+            rename_tables_in_node(right, tables);
+        }
+        Node::FunctionKind { args, .. } | Node::InvalidFunctionKind { args, .. } => {
+            for arg in args {
+                rename_tables_in_node(arg, tables);
+            }
+        }
+        Node::UnaryKind { right, .. } => rename_tables_in_node(right, tables),
+        Node::ImplicitIntersection { child, .. } => rename_tables_in_node(child, tables),
+
+        // Do nothing
+        Node::BooleanKind(_)
+        | Node::NumberKind(_)
+        | Node::StringKind(_)
+        | Node::ErrorKind(_)
+        | Node::ParseErrorKind { .. }
+        | Node::ArrayKind(_)
+        | Node::EmptyArgKind
+        | Node::ReferenceKind { .. }
+        | Node::RangeKind { .. }
+        | Node::WrongReferenceKind { .. }
+        | Node::WrongRangeKind { .. }
+        | Node::DefinedNameKind(_)
+        | Node::WrongVariableKind(_) => {}
+    }
+}
+
+/// Returns true if a reference or a range of the formula in (`row`, `column`) has a cell in one
+/// of the areas
+pub(crate) fn node_references_areas(node: &Node, row: i32, column: i32, areas: &[Area]) -> bool {
+    let resolve = |value: i32, absolute: bool, offset: i32| {
+        if absolute {
+            value
+        } else {
+            value + offset
+        }
+    };
+    let intersects = |sheet: u32, (row1, column1): (i32, i32), (row2, column2): (i32, i32)| {
+        let (first_row, last_row) = (row1.min(row2), row1.max(row2));
+        let (first_column, last_column) = (column1.min(column2), column1.max(column2));
+        areas.iter().any(|area| {
+            area.sheet == sheet
+                && first_row < area.row + area.height
+                && last_row >= area.row
+                && first_column < area.column + area.width
+                && last_column >= area.column
+        })
+    };
+    match node {
+        Node::ReferenceKind {
+            sheet_index,
+            absolute_row,
+            absolute_column,
+            row: reference_row,
+            column: reference_column,
+            ..
+        } => {
+            let cell = (
+                resolve(*reference_row, *absolute_row, row),
+                resolve(*reference_column, *absolute_column, column),
+            );
+            intersects(*sheet_index, cell, cell)
+        }
+        Node::RangeKind {
+            sheet_index,
+            absolute_row1,
+            absolute_column1,
+            row1,
+            column1,
+            absolute_row2,
+            absolute_column2,
+            row2,
+            column2,
+            ..
+        } => intersects(
+            *sheet_index,
+            (
+                resolve(*row1, *absolute_row1, row),
+                resolve(*column1, *absolute_column1, column),
+            ),
+            (
+                resolve(*row2, *absolute_row2, row),
+                resolve(*column2, *absolute_column2, column),
+            ),
+        ),
+        // Go next level
+        Node::OpRangeKind { left, right }
+        | Node::OpConcatenateKind { left, right }
+        | Node::OpSumKind { left, right, .. }
+        | Node::OpProductKind { left, right, .. }
+        | Node::OpPowerKind { left, right }
+        | Node::CompareKind { left, right, .. } => {
+            node_references_areas(left, row, column, areas)
+                || node_references_areas(right, row, column, areas)
+        }
+        Node::FunctionKind { args, .. } | Node::InvalidFunctionKind { args, .. } => args
+            .iter()
+            .any(|arg| node_references_areas(arg, row, column, areas)),
+        Node::UnaryKind { right, .. } => node_references_areas(right, row, column, areas),
+        Node::ImplicitIntersection { child, .. } => {
+            node_references_areas(child, row, column, areas)
+        }
+
+        // No references
+        Node::BooleanKind(_)
+        | Node::NumberKind(_)
+        | Node::StringKind(_)
+        | Node::ErrorKind(_)
+        | Node::ParseErrorKind { .. }
+        | Node::ArrayKind(_)
+        | Node::EmptyArgKind
+        | Node::WrongReferenceKind { .. }
+        | Node::WrongRangeKind { .. }
+        | Node::DefinedNameKind(_)
+        | Node::TableNameKind(_)
+        | Node::WrongVariableKind(_) => false,
+    }
+}
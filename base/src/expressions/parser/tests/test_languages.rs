@@ -7,7 +7,7 @@ use crate::expressions::types::CellReferenceRC;
 
 use crate::expressions::parser::stringify::{to_localized_string, to_rc_format};
 use crate::functions::Function;
-use crate::language::get_language;
+use crate::language::{get_language, get_supported_languages};
 use crate::locale::get_locale;
 use crate::types::Table;
 
@@ -59,3 +59,79 @@ fn simple_language() {
     assert_eq!(to_string(&t, &cell_reference), "VERDADERO()".to_string());
     assert_eq!(to_rc_format(&t), "TRUE()");
 }
+
+#[test]
+fn every_function_in_every_language() {
+    let locale = get_locale("en").unwrap();
+    let english = get_language("en").unwrap();
+    let cell_reference = CellReferenceRC {
+        sheet: "Sheet1".to_string(),
+        row: 1,
+        column: 1,
+    };
+    for code in get_supported_languages() {
+        let language = get_language(&code).unwrap();
+        let worksheets = vec!["Sheet1".to_string()];
+        let mut parser = Parser::new(worksheets, vec![], HashMap::new(), locale, language);
+        for function in Function::into_iter() {
+            let formula = format!("{}(1)", function.to_localized_name(language));
+            let t = parser.parse(&formula, &cell_reference);
+            assert!(
+                matches!(&t, Node::FunctionKind { kind, args: _ } if kind == &function),
+                "'{formula}' in '{code}'"
+            );
+            assert_eq!(
+                to_localized_string(&t, &cell_reference, locale, language),
+                formula
+            );
+            assert_eq!(
+                to_rc_format(&t),
+                format!("{}(1)", function.to_localized_name(english))
+            );
+        }
+    }
+}
+
+#[test]
+fn errors_and_booleans_in_every_language() {
+    let locale = get_locale("en").unwrap();
+    let cell_reference = CellReferenceRC {
+        sheet: "Sheet1".to_string(),
+        row: 1,
+        column: 1,
+    };
+    for code in get_supported_languages() {
+        let language = get_language(&code).unwrap();
+        let worksheets = vec!["Sheet1".to_string()];
+        let mut parser = Parser::new(worksheets, vec![], HashMap::new(), locale, language);
+        let errors = &language.errors;
+        for error in [
+            &errors.r#ref,
+            &errors.name,
+            &errors.value,
+            &errors.div,
+            &errors.na,
+            &errors.num,
+            &errors.nimpl,
+            &errors.spill,
+            &errors.calc,
+            &errors.circ,
+            &errors.error,
+            &errors.null,
+        ] {
+            let t = parser.parse(error, &cell_reference);
+            assert!(matches!(t, Node::ErrorKind(_)), "'{error}' in '{code}'");
+            assert_eq!(
+                to_localized_string(&t, &cell_reference, locale, language),
+                *error
+            );
+        }
+        let booleans = &language.booleans;
+        let t = parser.parse(&booleans.r#true, &cell_reference);
+        assert!(matches!(t, Node::BooleanKind(true)));
+        assert_eq!(to_rc_format(&t), "TRUE");
+        let t = parser.parse(&booleans.r#false, &cell_reference);
+        assert!(matches!(t, Node::BooleanKind(false)));
+        assert_eq!(to_rc_format(&t), "FALSE");
+    }
+}
@@ -0,0 +1,110 @@
+//! Helpers for front ends that paint fills.
+//!
+//! A front end that can not draw patterns or gradients can use [Fill::get_color], an
+//! approximation of the fill by a single colour. Colours are resolved against the theme of the
+//! workbook.
+
+use crate::{
+    theme::{hex_to_rgb, rgb_to_hex},
+    types::{Fill, GradientFill, PatternType, Theme},
+};
+
+// Colours of the pattern when they are not given
+const DEFAULT_FOREGROUND: &str = "#000000";
+const DEFAULT_BACKGROUND: &str = "#FFFFFF";
+
+fn mix_colors(color1: &str, color2: &str, ratio: f64) -> String {
+    let rgb1 = hex_to_rgb(color1);
+    let rgb2 = hex_to_rgb(color2);
+    let mut rgb = [0; 3];
+    for (index, value) in rgb.iter_mut().enumerate() {
+        *value = (rgb1[index] as f64 * ratio + rgb2[index] as f64 * (1.0 - ratio)).round() as i32;
+    }
+    rgb_to_hex(rgb)
+}
+
+impl PatternType {
+    /// The fraction of the cell painted with the foreground colour
+    pub fn get_foreground_ratio(&self) -> f64 {
+        match self {
+            PatternType::None => 0.0,
+            PatternType::Solid => 1.0,
+            PatternType::DarkGray => 0.75,
+            PatternType::MediumGray => 0.5,
+            PatternType::LightGray => 0.25,
+            PatternType::Gray125 => 0.125,
+            PatternType::Gray0625 => 0.0625,
+            PatternType::DarkTrellis => 0.75,
+            PatternType::DarkHorizontal
+            | PatternType::DarkVertical
+            | PatternType::DarkDown
+            | PatternType::DarkUp
+            | PatternType::DarkGrid
+            | PatternType::LightTrellis => 0.5,
+            PatternType::LightGrid => 0.4375,
+            PatternType::LightHorizontal
+            | PatternType::LightVertical
+            | PatternType::LightDown
+            | PatternType::LightUp => 0.25,
+        }
+    }
+}
+
+impl GradientFill {
+    /// Returns the colour of the gradient at `position` (0.0 to 1.0), interpolating between
+    /// the two nearest stops
+    pub fn get_color_at(&self, position: f64, theme: &Theme) -> Option<String> {
+        let mut stops: Vec<(f64, String)> = self
+            .stops
+            .iter()
+            .filter_map(|stop| {
+                let color = stop.color.as_ref()?;
+                Some((stop.position, color.to_rgb(theme)))
+            })
+            .collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (first, last) = (stops.first()?, stops.last()?);
+        if position <= first.0 {
+            return Some(first.1.clone());
+        }
+        if position >= last.0 {
+            return Some(last.1.clone());
+        }
+        for pair in stops.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            if position <= end.0 {
+                let width = end.0 - start.0;
+                if width <= 0.0 {
+                    return Some(end.1.clone());
+                }
+                let ratio = (position - start.0) / width;
+                return Some(mix_colors(&end.1, &start.1, ratio));
+            }
+        }
+        Some(last.1.clone())
+    }
+}
+
+impl Fill {
+    /// Returns a single colour that approximates the fill, or None if the cell has no fill
+    pub fn get_color(&self, theme: &Theme) -> Option<String> {
+        if let Some(gradient) = &self.gradient {
+            return gradient.get_color_at(0.5, theme);
+        }
+        let foreground = self.fg_color.as_ref().map(|color| color.to_rgb(theme));
+        let background = self.bg_color.as_ref().map(|color| color.to_rgb(theme));
+        match self.pattern_type {
+            PatternType::None => None,
+            PatternType::Solid => foreground.or(background),
+            pattern_type => {
+                let foreground = foreground.as_deref().unwrap_or(DEFAULT_FOREGROUND);
+                let background = background.as_deref().unwrap_or(DEFAULT_BACKGROUND);
+                Some(mix_colors(
+                    foreground,
+                    background,
+                    pattern_type.get_foreground_ratio(),
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+#[cfg(feature = "use_regex_lite")]
+use regex_lite as regex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    expressions::{types::Area, utils::parse_reference_a1},
+    functions::util::wildcard_to_regex_pattern,
+    model::Model,
+};
+
+/// What the text is searched in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FindLookIn {
+    /// The value of the cell as displayed
+    #[default]
+    Values,
+    /// The content of the cell, that is the formula for cells with formulas
+    Formulas,
+    /// The comments (notes) of the cells
+    Comments,
+}
+
+/// The order in which the cells are visited
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FindOrder {
+    /// Row after row, left to right
+    #[default]
+    ByRows,
+    /// Column after column, top to bottom
+    ByColumns,
+}
+
+/// The cells that are searched
+#[derive(Serialize, Deserialize, Default)]
+pub enum FindScope {
+    /// Only the cells in the area
+    Selection(Area),
+    /// All the cells of a sheet
+    Sheet(u32),
+    /// All the cells of all the sheets, in order
+    #[default]
+    Workbook,
+}
+
+/// Options of a search.
+///
+/// Unless `regex` is set the text is an Excel wildcard, `*` matches any text, `?` matches any
+/// character and `~` escapes the next character.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FindOptions {
+    /// Upper and lower case letters are different
+    pub match_case: bool,
+    /// The text must match the whole value, not just a part of it
+    pub whole_cell: bool,
+    /// The text is a regular expression
+    pub regex: bool,
+    /// What the text is searched in
+    pub look_in: FindLookIn,
+    /// The order in which the cells are visited
+    pub order: FindOrder,
+    /// The cells that are searched
+    pub scope: FindScope,
+}
+
+/// A cell where the text was found
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FindMatch {
+    /// The sheet index
+    pub sheet: u32,
+    /// The row of the cell
+    pub row: i32,
+    /// The column of the cell
+    pub column: i32,
+    /// The text where the search text was found (value, formula or comment)
+    pub text: String,
+}
+
+impl FindOptions {
+    /// Returns the regular expression matching `text` with these options
+    pub(crate) fn get_regex(&self, text: &str) -> Result<regex::Regex, String> {
+        if text.is_empty() {
+            return Err("Nothing to find".to_string());
+        }
+        let mut pattern = if self.regex {
+            text.to_string()
+        } else {
+            wildcard_to_regex_pattern(text)
+        };
+        if self.whole_cell {
+            pattern = format!("^(?:{pattern})$");
+        }
+        if !self.match_case {
+            pattern = format!("(?i){pattern}");
+        }
+        regex::Regex::new(&pattern).map_err(|e| format!("Invalid regular expression: {e}"))
+    }
+}
+
+impl<'a> Model<'a> {
+    // The cells in the scope that have content, in order
+    fn get_find_cells(&self, options: &FindOptions) -> Result<Vec<(u32, i32, i32)>, String> {
+        let (sheets, area) = match &options.scope {
+            FindScope::Selection(area) => (vec![area.sheet], Some(area)),
+            FindScope::Sheet(sheet) => (vec![*sheet], None),
+            FindScope::Workbook => ((0..self.workbook.worksheets.len() as u32).collect(), None),
+        };
+        let in_area = |row: i32, column: i32| match area {
+            Some(area) => {
+                row >= area.row
+                    && row < area.row + area.height
+                    && column >= area.column
+                    && column < area.column + area.width
+            }
+            None => true,
+        };
+        let mut result = Vec::new();
+        for sheet in sheets {
+            let worksheet = self.workbook.worksheet(sheet)?;
+            let mut cells: Vec<(i32, i32)> = match options.look_in {
+                FindLookIn::Comments => worksheet
+                    .comments
+                    .iter()
+                    .filter_map(|comment| parse_reference_a1(&comment.cell_ref))
+                    .map(|reference| (reference.row, reference.column))
+                    .collect(),
+                _ => worksheet
+                    .sheet_data
+                    .iter()
+                    .flat_map(|(row, data)| data.keys().map(move |column| (*row, *column)))
+                    .collect(),
+            };
+            cells.retain(|(row, column)| in_area(*row, *column));
+            match options.order {
+                FindOrder::ByRows => cells.sort(),
+                FindOrder::ByColumns => cells.sort_by_key(|(row, column)| (*column, *row)),
+            }
+            cells.dedup();
+            result.extend(cells.into_iter().map(|(row, column)| (sheet, row, column)));
+        }
+        Ok(result)
+    }
+
+    // The text of the cell that is searched
+    fn get_find_text(&self, look_in: FindLookIn, sheet: u32, row: i32, column: i32) -> String {
+        match look_in {
+            FindLookIn::Values => self
+                .get_formatted_cell_value(sheet, row, column)
+                .unwrap_or_default(),
+            FindLookIn::Formulas => {
+                // The formulas of hidden cells are not shown on protected sheets
+                if self
+                    .is_cell_formula_hidden(sheet, row, column)
+                    .unwrap_or(false)
+                {
+                    return "".to_string();
+                }
+                self.get_localized_cell_content(sheet, row, column)
+                    .unwrap_or_default()
+            }
+            FindLookIn::Comments => match self.workbook.worksheet(sheet) {
+                Ok(worksheet) => worksheet
+                    .comments
+                    .iter()
+                    .filter(|comment| {
+                        parse_reference_a1(&comment.cell_ref)
+                            .is_some_and(|r| r.row == row && r.column == column)
+                    })
+                    .map(|comment| comment.text.clone())
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+                Err(_) => "".to_string(),
+            },
+        }
+    }
+
+    /// Returns the cells where `text` is found, in the order given by the options.
+    ///
+    /// The values are the ones of [Model::get_formatted_cell_value], so the model should be
+    /// evaluated first.
+    pub fn find<'b>(
+        &'b self,
+        text: &str,
+        options: &FindOptions,
+    ) -> Result<impl Iterator<Item = FindMatch> + 'b, String> {
+        let regex = options.get_regex(text)?;
+        let look_in = options.look_in;
+        let cells = self.get_find_cells(options)?;
+        Ok(cells.into_iter().filter_map(move |(sheet, row, column)| {
+            let text = self.get_find_text(look_in, sheet, row, column);
+            if text.is_empty() || !regex.is_match(&text) {
+                return None;
+            }
+            Some(FindMatch {
+                sheet,
+                row,
+                column,
+                text,
+            })
+        }))
+    }
+}
@@ -4,46 +4,86 @@ use chrono::Duration;
 use chrono::Months;
 use chrono::NaiveDate;
 
+use crate::constants::DATE_1904_OFFSET;
 use crate::constants::EXCEL_DATE_BASE;
 use crate::constants::MAXIMUM_DATE_SERIAL_NUMBER;
 use crate::constants::MINIMUM_DATE_SERIAL_NUMBER;
+use crate::types::DateSystem;
 
 pub const DATE_OUT_OF_RANGE_MESSAGE: &str = "Out of range parameters for date";
 
+/// Number of days to add to a serial number in `date_system` to get the serial number in the 1900 date system
 #[inline]
-fn convert_to_serial_number(date: NaiveDate) -> i32 {
-    date.num_days_from_ce() - EXCEL_DATE_BASE
+fn date_system_offset(date_system: DateSystem) -> i32 {
+    match date_system {
+        DateSystem::Excel1900 => 0,
+        DateSystem::Excel1904 => DATE_1904_OFFSET,
+    }
+}
+
+/// Returns the serial number of the first date supported in `date_system`
+pub fn minimum_date_serial_number(date_system: DateSystem) -> i32 {
+    match date_system {
+        DateSystem::Excel1900 => MINIMUM_DATE_SERIAL_NUMBER,
+        DateSystem::Excel1904 => 0,
+    }
+}
+
+/// Returns the serial number of 9999-12-31 in `date_system`
+pub fn maximum_date_serial_number(date_system: DateSystem) -> i32 {
+    MAXIMUM_DATE_SERIAL_NUMBER - date_system_offset(date_system)
+}
+
+/// Converts a serial number from one date system to another
+pub fn convert_date_serial_number(value: f64, from: DateSystem, to: DateSystem) -> f64 {
+    value + (date_system_offset(from) - date_system_offset(to)) as f64
+}
+
+/// Returns the serial number of `date` in `date_system`
+#[inline]
+pub(crate) fn convert_to_serial_number(date: NaiveDate, date_system: DateSystem) -> i32 {
+    date.num_days_from_ce() - EXCEL_DATE_BASE - date_system_offset(date_system)
 }
 
-fn is_date_within_range(date: NaiveDate) -> bool {
-    convert_to_serial_number(date) >= MINIMUM_DATE_SERIAL_NUMBER
-        && convert_to_serial_number(date) <= MAXIMUM_DATE_SERIAL_NUMBER
+fn is_date_within_range(date: NaiveDate, date_system: DateSystem) -> bool {
+    let serial_number = convert_to_serial_number(date, date_system);
+    serial_number >= minimum_date_serial_number(date_system)
+        && serial_number <= maximum_date_serial_number(date_system)
 }
 
-pub fn from_excel_date(days: i64) -> Result<NaiveDate, String> {
-    if days < MINIMUM_DATE_SERIAL_NUMBER as i64 {
-        return Err(format!(
-            "Excel date must be greater than {MINIMUM_DATE_SERIAL_NUMBER}"
-        ));
+pub fn from_excel_date(days: i64, date_system: DateSystem) -> Result<NaiveDate, String> {
+    let minimum = minimum_date_serial_number(date_system);
+    let maximum = maximum_date_serial_number(date_system);
+    if days < minimum as i64 {
+        return Err(format!("Excel date must be greater than {minimum}"));
     };
-    if days > MAXIMUM_DATE_SERIAL_NUMBER as i64 {
-        return Err(format!(
-            "Excel date must be less than {MAXIMUM_DATE_SERIAL_NUMBER}"
-        ));
+    if days > maximum as i64 {
+        return Err(format!("Excel date must be less than {maximum}"));
     };
+    let days = days + date_system_offset(date_system) as i64;
     #[allow(clippy::expect_used)]
     let dt = NaiveDate::from_ymd_opt(1900, 1, 1).expect("problem with chrono::NaiveDate");
     Ok(dt + Duration::days(days - 2))
 }
 
-pub fn date_to_serial_number(day: u32, month: u32, year: i32) -> Result<i32, String> {
+pub fn date_to_serial_number(
+    day: u32,
+    month: u32,
+    year: i32,
+    date_system: DateSystem,
+) -> Result<i32, String> {
     match NaiveDate::from_ymd_opt(year, month, day) {
-        Some(native_date) => Ok(convert_to_serial_number(native_date)),
+        Some(native_date) => Ok(convert_to_serial_number(native_date, date_system)),
         None => Err(DATE_OUT_OF_RANGE_MESSAGE.to_string()),
     }
 }
 
-pub fn permissive_date_to_serial_number(day: i32, month: i32, year: i32) -> Result<i32, String> {
+pub fn permissive_date_to_serial_number(
+    day: i32,
+    month: i32,
+    year: i32,
+    date_system: DateSystem,
+) -> Result<i32, String> {
     // Excel parses `DATE` very permissively. It allows not just for valid date values, but it
     // allows for invalid dates as well. If you for example enter `DATE(1900, 1, 32)` it will
     // return the date `1900-02-01`. Despite giving a day that is out of range it will just
@@ -53,7 +93,7 @@ pub fn permissive_date_to_serial_number(day: i32, month: i32, year: i32) -> Resu
     // possible.
 
     // Special case for the minimum date
-    if year == 1899 && month == 12 && day == 31 {
+    if date_system == DateSystem::Excel1900 && year == 1899 && month == 12 && day == 31 {
         return Ok(MINIMUM_DATE_SERIAL_NUMBER);
     }
     let Some(mut date) = NaiveDate::from_ymd_opt(year, 1, 1) else {
@@ -69,7 +109,7 @@ pub fn permissive_date_to_serial_number(day: i32, month: i32, year: i32) -> Resu
     //
     // As a result, we have to run range checks as we parse the date from the biggest unit to the
     // smallest unit.
-    if !is_date_within_range(date) {
+    if !is_date_within_range(date, date_system) {
         return Err(DATE_OUT_OF_RANGE_MESSAGE.to_string());
     }
 
@@ -81,7 +121,7 @@ pub fn permissive_date_to_serial_number(day: i32, month: i32, year: i32) -> Resu
         } else {
             date = date + Months::new(abs_month);
         }
-        if !is_date_within_range(date) {
+        if !is_date_within_range(date, date_system) {
             return Err(DATE_OUT_OF_RANGE_MESSAGE.to_string());
         }
         date
@@ -95,13 +135,13 @@ pub fn permissive_date_to_serial_number(day: i32, month: i32, year: i32) -> Resu
         } else {
             date = date + Days::new(abs_day);
         }
-        if !is_date_within_range(date) {
+        if !is_date_within_range(date, date_system) {
             return Err(DATE_OUT_OF_RANGE_MESSAGE.to_string());
         }
         date
     };
 
-    Ok(convert_to_serial_number(date))
+    Ok(convert_to_serial_number(date, date_system))
 }
 
 #[cfg(test)]
@@ -111,48 +151,72 @@ mod tests {
     #[test]
     fn test_permissive_date_to_serial_number() {
         assert_eq!(
-            permissive_date_to_serial_number(42, 42, 2002),
-            date_to_serial_number(12, 7, 2005)
+            permissive_date_to_serial_number(42, 42, 2002, DateSystem::Excel1900),
+            date_to_serial_number(12, 7, 2005, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(1, 42, 2002),
-            date_to_serial_number(1, 6, 2005)
+            permissive_date_to_serial_number(1, 42, 2002, DateSystem::Excel1900),
+            date_to_serial_number(1, 6, 2005, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(1, 15, 2000),
-            date_to_serial_number(1, 3, 2001)
+            permissive_date_to_serial_number(1, 15, 2000, DateSystem::Excel1900),
+            date_to_serial_number(1, 3, 2001, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(1, 49, 2000),
-            date_to_serial_number(1, 1, 2004)
+            permissive_date_to_serial_number(1, 49, 2000, DateSystem::Excel1900),
+            date_to_serial_number(1, 1, 2004, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(1, 49, 2000),
-            date_to_serial_number(1, 1, 2004)
+            permissive_date_to_serial_number(1, 49, 2000, DateSystem::Excel1900),
+            date_to_serial_number(1, 1, 2004, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(31, 49, 2000),
-            date_to_serial_number(31, 1, 2004)
+            permissive_date_to_serial_number(31, 49, 2000, DateSystem::Excel1900),
+            date_to_serial_number(31, 1, 2004, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(256, 49, 2000),
-            date_to_serial_number(12, 9, 2004)
+            permissive_date_to_serial_number(256, 49, 2000, DateSystem::Excel1900),
+            date_to_serial_number(12, 9, 2004, DateSystem::Excel1900)
         );
         assert_eq!(
-            permissive_date_to_serial_number(256, 1, 2004),
-            date_to_serial_number(12, 9, 2004)
+            permissive_date_to_serial_number(256, 1, 2004, DateSystem::Excel1900),
+            date_to_serial_number(12, 9, 2004, DateSystem::Excel1900)
         );
     }
 
     #[test]
     fn test_max_and_min_dates() {
         assert_eq!(
-            permissive_date_to_serial_number(31, 12, 9999),
+            permissive_date_to_serial_number(31, 12, 9999, DateSystem::Excel1900),
             Ok(MAXIMUM_DATE_SERIAL_NUMBER),
         );
         assert_eq!(
-            permissive_date_to_serial_number(31, 12, 1899),
+            permissive_date_to_serial_number(31, 12, 1899, DateSystem::Excel1900),
             Ok(MINIMUM_DATE_SERIAL_NUMBER),
         );
     }
+
+    #[test]
+    fn test_1904_date_system() {
+        let date_system = DateSystem::Excel1904;
+        assert_eq!(date_to_serial_number(1, 1, 1904, date_system), Ok(0));
+        assert_eq!(
+            from_excel_date(0, date_system).ok(),
+            NaiveDate::from_ymd_opt(1904, 1, 1)
+        );
+        assert_eq!(
+            from_excel_date(43831 - 1462, date_system),
+            from_excel_date(43831, DateSystem::Excel1900)
+        );
+        assert!(from_excel_date(-1, date_system).is_err());
+        assert!(permissive_date_to_serial_number(31, 12, 1903, date_system).is_err());
+        assert_eq!(
+            permissive_date_to_serial_number(31, 12, 9999, date_system),
+            Ok(maximum_date_serial_number(date_system))
+        );
+        assert_eq!(
+            convert_date_serial_number(1462.5, DateSystem::Excel1900, date_system),
+            0.5
+        );
+    }
 }
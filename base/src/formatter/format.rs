@@ -1,10 +1,11 @@
 use chrono::Datelike;
 
-use crate::{locale::Locale, number_format::to_precision};
+use crate::{locale::Locale, number_format::to_precision, types::DateSystem};
 
 use super::{
     dates::{date_to_serial_number, from_excel_date},
-    parser::{ParsePart, Parser, TextToken},
+    locale_codes::{get_format_locale, get_system_time_format, FormatLocale},
+    parser::{NumberPart, ParsePart, Parser, TextToken},
 };
 
 pub struct Formatted {
@@ -41,22 +42,231 @@ fn get_fract_part(value: f64, precision: i32, int_len: usize) -> Vec<char> {
 
 /// Return true if we need to add a separator in position digit_index
 /// It normally happens if if digit_index -1 is 3, 6, 9,... digit_index ≡ 1 mod 3
+/// With the Indian grouping (lakh) it happens at 4, 6, 8,...
 fn use_group_separator(use_thousands: bool, digit_index: i32, group_sizes: &str) -> bool {
     if use_thousands {
         if group_sizes == "#,##0.###" {
             if digit_index > 1 && (digit_index - 1) % 3 == 0 {
                 return true;
             }
-        } else if group_sizes == "#,##,##0.###"
-            && (digit_index == 3 || (digit_index > 3 && digit_index % 2 == 0))
-        {
+        } else if group_sizes == "#,##,##0.###" && digit_index > 3 && digit_index % 2 == 0 {
             return true;
         }
     }
     false
 }
 
-pub fn format_number(value_original: f64, format: &str, locale: &Locale) -> Formatted {
+/// Returns the best rational approximation num/den of a positive `value` with den <= max_denominator.
+/// It uses the convergents (and the last semiconvergent) of the continued fraction of `value`.
+fn approximate_fraction(value: f64, max_denominator: u64) -> (u64, u64) {
+    // p1/q1 is the last convergent and p0/q0 the one before
+    let (mut p0, mut q0, mut p1, mut q1) = (0_u64, 1_u64, 1_u64, 0_u64);
+    let mut x = value;
+    loop {
+        let a = x.floor();
+        if a * (q1 as f64) + (q0 as f64) > max_denominator as f64 {
+            break;
+        }
+        let a = a as u64;
+        (p0, q0, p1, q1) = (p1, q1, a * p1 + p0, a * q1 + q0);
+        let remainder = x - x.floor();
+        if remainder < 1e-12 || (value - p1 as f64 / q1 as f64).abs() < 1e-12 {
+            break;
+        }
+        x = 1.0 / remainder;
+    }
+    if q1 == 0 {
+        // max_denominator is 0
+        return (value.round() as u64, 1);
+    }
+    // The semiconvergent with the largest possible denominator might be closer
+    let k = (max_denominator - q0) / q1;
+    let (p2, q2) = (k * p1 + p0, k * q1 + q0);
+    let error1 = (value - p1 as f64 / q1 as f64).abs();
+    let error2 = (value - p2 as f64 / q2 as f64).abs();
+    if error2 < error1 {
+        (p2, q2)
+    } else {
+        (p1, q1)
+    }
+}
+
+// Writes `number` using the digit tokens of a section with `digit_count` digits.
+// Missing digits are written as '0' for '0', a space for '?' and nothing for '#'
+// (a space for '0' if aligned to the left).
+// The first digit token writes all the extra digits of the number.
+fn format_fraction_digit(
+    number: &[char],
+    kind: char,
+    index: i32,
+    digit_count: i32,
+    align_left: bool,
+) -> String {
+    let len = number.len() as i32;
+    if align_left {
+        // Denominators: 4 in ??/?? is written as "4 "
+        if index == 0 && len > digit_count {
+            return number[..(len - digit_count + 1) as usize].iter().collect();
+        }
+        let number_index = if len > digit_count {
+            index + len - digit_count
+        } else {
+            index
+        };
+        if number_index < len {
+            return number[number_index as usize].to_string();
+        }
+        // Trailing zeros would change the denominator
+        if kind == '0' {
+            return " ".to_string();
+        }
+    } else {
+        let number_index = len - digit_count + index;
+        if index == 0 && number_index >= 0 {
+            return number[..=number_index as usize].iter().collect();
+        }
+        if number_index >= 0 {
+            return number[number_index as usize].to_string();
+        }
+    }
+    match kind {
+        '0' => "0".to_string(),
+        '?' => " ".to_string(),
+        _ => "".to_string(),
+    }
+}
+
+fn format_fraction(value: f64, p: &NumberPart, locale: &Locale) -> Formatted {
+    let value = value * 100.0_f64.powi(p.percent) / (1000.0_f64.powi(p.comma));
+    let value_abs = value.abs();
+    // A mixed fraction has an integer part (# ?/?), otherwise it is improper (?/?)
+    let is_mixed = p.digit_count > 0;
+    let (mut integer, fraction) = if is_mixed {
+        (value_abs.floor(), value_abs - value_abs.floor())
+    } else {
+        (0.0, value_abs)
+    };
+    let (mut numerator, denominator) = match p.fixed_denominator {
+        Some(denominator) => (
+            (fraction * denominator as f64).round() as u64,
+            denominator as u64,
+        ),
+        None => {
+            let max_denominator = 10_u64.pow(p.denominator_digit_count.clamp(1, 9) as u32) - 1;
+            approximate_fraction(fraction, max_denominator)
+        }
+    };
+    if is_mixed && numerator == denominator {
+        integer += 1.0;
+        numerator = 0;
+    }
+    // In a mixed fraction with a zero numerator only the integer part is displayed
+    let hide_fraction = is_mixed && numerator == 0;
+    let integer_part: Vec<char> = if integer == 0.0 {
+        if hide_fraction {
+            vec!['0']
+        } else {
+            vec![]
+        }
+    } else {
+        format!("{integer}").chars().collect()
+    };
+    let numerator_part: Vec<char> = numerator.to_string().chars().collect();
+    let denominator_part: Vec<char> = denominator.to_string().chars().collect();
+
+    let group_sizes = &locale.numbers.decimal_formats.standard;
+    let group_separator = &locale.numbers.symbols.group;
+    let mut text = "".to_string();
+    let mut needs_sign = value < 0.0;
+    for token in &p.tokens {
+        match token {
+            TextToken::Literal(c) => text.push(*c),
+            TextToken::Text(t) => text.push_str(t),
+            TextToken::Ghost(_) | TextToken::Spacer(_) => text.push(' '),
+            TextToken::Raw => text.push_str(&value.to_string()),
+            TextToken::Period => {}
+            TextToken::FractionSlash => text.push(if hide_fraction { ' ' } else { '/' }),
+            TextToken::FixedDenominator(d) => {
+                let d = d.to_string();
+                if hide_fraction {
+                    text.push_str(&" ".repeat(d.len()));
+                } else {
+                    text.push_str(&d);
+                }
+            }
+            TextToken::Digit(digit) => {
+                let kind = digit.kind;
+                let index = digit.index;
+                let (digits, digit_count) = if digit.number.is_integer() {
+                    (
+                        format_fraction_digit(&integer_part, kind, index, p.digit_count, false),
+                        p.digit_count,
+                    )
+                } else if hide_fraction {
+                    let space = if kind == '#' { "" } else { " " };
+                    (space.to_string(), 0)
+                } else if digit.number.is_numerator() {
+                    (
+                        format_fraction_digit(
+                            &numerator_part,
+                            kind,
+                            index,
+                            p.numerator_digit_count,
+                            false,
+                        ),
+                        p.numerator_digit_count,
+                    )
+                } else {
+                    (
+                        format_fraction_digit(
+                            &denominator_part,
+                            kind,
+                            index,
+                            p.denominator_digit_count,
+                            true,
+                        ),
+                        p.denominator_digit_count,
+                    )
+                };
+                // the number of digits still to be written after these
+                let remaining = digit_count - index - 1;
+                for (i, c) in digits.chars().enumerate() {
+                    if c.is_ascii_digit() && needs_sign {
+                        // The sign goes right before the first digit
+                        text.push('-');
+                        needs_sign = false;
+                    }
+                    text.push(c);
+                    let digit_index = remaining + (digits.len() - i) as i32;
+                    if c.is_ascii_digit()
+                        && digit.number.is_integer()
+                        && use_group_separator(p.use_thousands, digit_index, group_sizes)
+                    {
+                        text.push_str(group_separator);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Formatted {
+        text,
+        color: p.color,
+        error: None,
+    }
+}
+
+pub fn format_number(value: f64, format: &str, locale: &Locale) -> Formatted {
+    format_number_with_date_system(value, format, locale, DateSystem::Excel1900)
+}
+
+/// Formats `value_original` with `format`, dates are interpreted in `date_system`
+pub fn format_number_with_date_system(
+    value_original: f64,
+    format: &str,
+    locale: &Locale,
+    date_system: DateSystem,
+) -> Formatted {
     let mut parser = Parser::new(format);
     parser.parse();
     let parts = parser.parts;
@@ -115,6 +325,60 @@ pub fn format_number(value_original: f64, format: &str, locale: &Locale) -> Form
             };
         }
     }
+    // The locale code of the format overrides the locale
+    let locale = match part.locale_code().and_then(get_format_locale) {
+        Some(FormatLocale::Locale(format_locale)) => format_locale,
+        Some(FormatLocale::SystemLongDate) => {
+            return format_number_with_date_system(
+                value,
+                &locale.dates.date_formats.full,
+                locale,
+                date_system,
+            );
+        }
+        Some(FormatLocale::SystemTime) => {
+            let time_format = get_system_time_format(&locale.dates.time_formats.medium);
+            return format_number_with_date_system(value, &time_format, locale, date_system);
+        }
+        None => locale,
+    };
+    let formatted = format_part(value, part, locale, date_system);
+    match &locale.numbers.native_digits {
+        Some(digits) => Formatted {
+            text: to_native_digits(&formatted.text, digits),
+            ..formatted
+        },
+        None => formatted,
+    }
+}
+
+// Replaces the latin digits by the digits of the locale, i.e. "12" => "١٢" in Arabic
+fn to_native_digits(text: &str, digits: &str) -> String {
+    let digits: Vec<char> = digits.chars().collect();
+    text.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => digits.get(d as usize).copied().unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+// Replaces the digits of the locale by the latin ones, i.e. "١٢" => "12" in Arabic
+fn from_native_digits(text: &str, digits: &str) -> String {
+    text.chars()
+        .map(|c| match digits.chars().position(|d| d == c) {
+            Some(index) => char::from_digit(index as u32, 10).unwrap_or(c),
+            None => c,
+        })
+        .collect()
+}
+
+fn format_part(
+    mut value: f64,
+    part: &ParsePart,
+    locale: &Locale,
+    date_system: DateSystem,
+) -> Formatted {
     match part {
         ParsePart::Error(..) => Formatted {
             text: "#VALUE!".to_owned(),
@@ -167,11 +431,13 @@ pub fn format_number(value_original: f64, format: &str, locale: &Locale) -> Form
         ParsePart::Date(p) => {
             let tokens = &p.tokens;
             let mut text = "".to_string();
+            // Like Excel, times are rounded to the second (14:00 might be stored as 13:59:59.99)
+            let value = (value * 86_400.0).round() / 86_400.0;
             let time_fract = value.fract();
             let hours = (time_fract * 24.0).floor();
             let minutes = ((time_fract * 24.0 - hours) * 60.0).floor();
             let seconds = ((((time_fract * 24.0 - hours) * 60.0) - minutes) * 60.0).round();
-            let date = from_excel_date(value as i64).ok();
+            let date = from_excel_date(value as i64, date_system).ok();
             for token in tokens {
                 match token {
                     TextToken::Literal(c) => {
@@ -195,6 +461,8 @@ pub fn format_number(value_original: f64, format: &str, locale: &Locale) -> Form
                     }
                     TextToken::Digit(_) => {}
                     TextToken::Period => {}
+                    TextToken::FractionSlash => {}
+                    TextToken::FixedDenominator(_) => {}
                     TextToken::Day => match date {
                         Some(date) => {
                             let day = date.day() as usize;
@@ -427,11 +695,9 @@ pub fn format_number(value_original: f64, format: &str, locale: &Locale) -> Form
                 error: None,
             }
         }
+        ParsePart::Number(p) if p.is_fraction => format_fraction(value, p, locale),
         ParsePart::Number(p) => {
             let mut text = "".to_string();
-            if let Some(c) = p.currency {
-                text = format!("{c}");
-            }
             let tokens = &p.tokens;
             value = value * 100.0_f64.powi(p.percent) / (1000.0_f64.powi(p.comma));
             // p.precision is the number of significant digits _after_ the decimal point
@@ -619,6 +885,9 @@ pub fn format_number(value_original: f64, format: &str, locale: &Locale) -> Form
                             }
                         }
                     }
+                    // Fraction tokens are only present in fraction formats
+                    TextToken::FractionSlash => {}
+                    TextToken::FixedDenominator(_) => {}
                     // Date tokens should not be present
                     TextToken::Day => {}
                     TextToken::DayPadded => {}
@@ -745,7 +1014,11 @@ fn parse_year(year_str: &str) -> Result<(i32, String), String> {
 // NOTE 1: The separator has to be the same
 // NOTE 2: In some engines "2/3" is implemented ad "2/March of the present year"
 // NOTE 3: I did not implement the "short date"
-fn parse_date(value: &str, locale: &Locale) -> Result<(i32, String), String> {
+fn parse_date(
+    value: &str,
+    locale: &Locale,
+    date_system: DateSystem,
+) -> Result<(i32, String), String> {
     let separator = if value.contains('/') {
         '/'
     } else if value.contains('-') {
@@ -786,7 +1059,7 @@ fn parse_date(value: &str, locale: &Locale) -> Result<(i32, String), String> {
     let (day, day_format) = parse_day(day_str)?;
     let (month, month_format) = parse_month(month_str, locale)?;
     let (year, year_format) = parse_year(year_str)?;
-    let serial_number = match date_to_serial_number(day, month, year) {
+    let serial_number = match date_to_serial_number(day, month, year, date_system) {
         Ok(n) => n,
         Err(_) => return Err("Not a valid date".to_string()),
     };
@@ -808,16 +1081,52 @@ fn parse_date(value: &str, locale: &Locale) -> Result<(i32, String), String> {
     }
 }
 
+// Parses a mixed fraction: an integer, a space and a fraction, i.e. "1 3/4" or "-0 1/2".
+// The format has as many '?' as digits in the denominator: "1 3/4" => (1.75, "# ?/?")
+fn parse_fraction(value: &str) -> Result<(f64, String), String> {
+    let error = || "Not a valid fraction".to_string();
+    let (integer, fraction) = value.split_once(' ').ok_or_else(error)?;
+    let (numerator, denominator) = fraction.trim_start().split_once('/').ok_or_else(error)?;
+    let (sign, integer) = match integer.strip_prefix('-') {
+        Some(integer) => (-1.0, integer),
+        None => (1.0, integer),
+    };
+    let is_number =
+        |s: &str| !s.is_empty() && s.len() <= 9 && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(integer) || !is_number(numerator) || !is_number(denominator) {
+        return Err(error());
+    }
+    let integer = integer.parse::<f64>().map_err(|_| error())?;
+    let numerator = numerator.parse::<f64>().map_err(|_| error())?;
+    let denominator = denominator.parse::<f64>().map_err(|_| error())?;
+    if denominator == 0.0 {
+        return Err(error());
+    }
+    let digits = "?".repeat(denominator.to_string().len().min(3));
+    Ok((
+        sign * (integer + numerator / denominator),
+        format!("# {digits}/{digits}"),
+    ))
+}
+
 /// Parses a formatted number, returning the numeric value together with the format
 /// Uses heuristics to guess the format string
 /// "$ 123,345.678" => (123345.678, "$#,##0.00")
 /// "30.34%" => (0.3034, "0.00%")
 /// 100€ => (100, "100€")
+/// "1 3/4" => (1.75, "# ?/?")
+/// Dates are returned as serial numbers in `date_system`
 pub(crate) fn parse_formatted_number(
     original: &str,
     currencies: &[&str],
     locale: &Locale,
+    date_system: DateSystem,
 ) -> Result<(f64, Option<String>), String> {
+    // Numbers might be written with the digits of the locale
+    let original = match &locale.numbers.native_digits {
+        Some(digits) => from_native_digits(original, digits),
+        None => original.to_string(),
+    };
     let value = original.trim();
     let scientific_format = "0.00E+00";
 
@@ -876,8 +1185,13 @@ pub(crate) fn parse_formatted_number(
         }
     }
 
+    // check if it is a mixed fraction like "1 3/4"
+    if let Ok((f, format)) = parse_fraction(value) {
+        return Ok((f, Some(format)));
+    }
+
     // check if it is a date. NOTE: we don't trim the original here
-    if let Ok((serial_number, format)) = parse_date(original, locale) {
+    if let Ok((serial_number, format)) = parse_date(&original, locale, date_system) {
         return Ok((serial_number as f64, Some(format)));
     }
 
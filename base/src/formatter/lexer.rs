@@ -8,9 +8,10 @@ pub struct Lexer {
 
 #[derive(PartialEq, Debug)]
 pub enum Token {
-    Color(i32),              // [Red] or [Color 23]
-    Condition(Compare, f64), // [<=100] (Comparator, number)
-    Currency(char),          // [$€] ($ currency symbol)
+    Color(i32),                       // [Red] or [Color 23]
+    Condition(Compare, f64),          // [<=100] (Comparator, number)
+    Currency(String, Option<String>), // [$€], [$USD-409] or [$-409] (currency and locale code)
+    DbNum(u32),                       // [DBNum1] (Asian numerals)
     Literal(char), // €, $, (, ), /, :, +, -, ^, ', {, }, <, =, !, ~, > and space or escaped \X
     Spacer(char),  // *X
     Ghost(char),   // _X
@@ -23,6 +24,7 @@ pub enum Token {
     Sharp,         // #
     Zero,          // 0
     QuestionMark,  // ?
+    Number(u32),   // 1-9 followed by any digits (fixed denominator of a fraction)
     Scientific,    // E+
     ScientificMinus, // E-
     General,       // General
@@ -80,6 +82,28 @@ impl Token {
             || self == &Token::YearShort
             || self == &Token::Year
     }
+
+    pub fn is_time(&self) -> bool {
+        self.is_hour()
+            || self.is_second()
+            || self == &Token::ElapsedMinute
+            || self == &Token::ElapsedMinutePadded
+            || self == &Token::AMPM
+    }
+
+    fn is_hour(&self) -> bool {
+        self == &Token::Hour
+            || self == &Token::HourPadded
+            || self == &Token::ElapsedHour
+            || self == &Token::ElapsedHourPadded
+    }
+
+    fn is_second(&self) -> bool {
+        self == &Token::Second
+            || self == &Token::SecondPadded
+            || self == &Token::ElapsedSecond
+            || self == &Token::ElapsedSecondPadded
+    }
 }
 
 impl Lexer {
@@ -283,6 +307,44 @@ impl Lexer {
         Some(token)
     }
 
+    // Consumes the currency and the optional locale code after '[$'
+    // [$€] => ("€", None), [$USD-409] => ("USD", Some("409")), [$-ja-JP-x-gannen] => ("", Some("ja-JP-x-gannen"))
+    fn consume_currency(&mut self) -> Option<(String, Option<String>)> {
+        let mut chars = "".to_string();
+        while let Some(ch) = self.read_next_char() {
+            if ch == ']' {
+                if chars.is_empty() {
+                    return None;
+                }
+                return match chars.split_once('-') {
+                    Some((currency, locale_code)) => {
+                        Some((currency.to_string(), Some(locale_code.to_string())))
+                    }
+                    None => Some((chars, None)),
+                };
+            }
+            chars.push(ch);
+        }
+        None
+    }
+
+    // Consumes [DBNum1], [DBNum2],... We are called right after '['
+    fn consume_db_num(&mut self) -> Option<u32> {
+        let start_pos = self.position;
+        for c in "dbnum".chars() {
+            if self.read_next_char().map(|x| x.to_ascii_lowercase()) != Some(c) {
+                self.position = start_pos;
+                return None;
+            }
+        }
+        let n = self.read_next_char().and_then(|c| c.to_digit(10));
+        if n.is_none() || self.read_next_char() != Some(']') {
+            self.position = start_pos;
+            return None;
+        }
+        n
+    }
+
     fn consume_color(&mut self) -> Option<i32> {
         let colors = [
             "black", "white", "red", "green", "blue", "yellow", "magenta",
@@ -326,6 +388,14 @@ impl Lexer {
                 '$' | '€' | '(' | ')' | '/' | ':' | '+' | '-' | '^' | '\'' | '{' | '}' | '<'
                 | '=' | '!' | '~' | '>' | ' ' => Token::Literal(x),
                 '?' => Token::QuestionMark,
+                '1'..='9' => {
+                    let mut number = x.to_digit(10).unwrap_or(0);
+                    while let Some(digit) = self.peek_char().and_then(|c| c.to_digit(10)) {
+                        number = number.saturating_mul(10).saturating_add(digit);
+                        self.read_next_char();
+                    }
+                    Token::Number(number)
+                }
                 ';' => Token::Separator,
                 '#' => Token::Sharp,
                 ',' => Token::Comma,
@@ -344,16 +414,17 @@ impl Lexer {
                                 Token::ILLEGAL
                             }
                         } else if c == '$' {
-                            // currency
+                            // currency and locale code
                             self.read_next_char();
-                            if let Some(currency) = self.read_next_char() {
-                                if self.read_next_char() == Some(']') {
-                                    return Token::Currency(currency);
-                                }
+                            if let Some((currency, locale_code)) = self.consume_currency() {
+                                return Token::Currency(currency, locale_code);
                             }
                             self.set_error("Failed to parse currency");
                             Token::ILLEGAL
                         } else {
+                            if let Some(n) = self.consume_db_num() {
+                                return Token::DbNum(n);
+                            }
                             // try bracketed (elapsed) time
                             if let Some(token) = self.consume_elapsed_time() {
                                 return token;
@@ -538,6 +609,44 @@ impl Lexer {
     }
 }
 
+// Date and time tokens of the format, an `m` is a month or a minute depending on its neighbours
+fn get_date_time_tokens(format: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(format);
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        if token == Token::EOF || token == Token::ILLEGAL {
+            return tokens;
+        }
+        if token.is_date() || token.is_time() {
+            tokens.push(token);
+        }
+    }
+}
+
+// As in Excel an `m` right after hours or right before seconds are minutes, like in `h:mm` or `mm:ss`
+fn is_minute(tokens: &[Token], index: usize) -> bool {
+    if !matches!(tokens[index], Token::Month | Token::MonthPadded) {
+        return false;
+    }
+    let after_hours = index > 0 && tokens[index - 1].is_hour();
+    let before_seconds = tokens.get(index + 1).is_some_and(|token| token.is_second());
+    after_hours || before_seconds
+}
+
+/// Returns true if the format shows a calendar date (a day, a month or a year).
+/// Unlike [is_likely_date_number_format] time formats like `h:mm` are not dates.
+pub fn is_date_number_format(format: &str) -> bool {
+    let tokens = get_date_time_tokens(format);
+    (0..tokens.len()).any(|index| tokens[index].is_date() && !is_minute(&tokens, index))
+}
+
+/// Returns true if the format shows a time and no calendar date, like `h:mm` or `[mm]:ss`
+pub fn is_time_number_format(format: &str) -> bool {
+    let tokens = get_date_time_tokens(format);
+    !tokens.is_empty() && !is_date_number_format(format)
+}
+
 pub fn is_likely_date_number_format(format: &str) -> bool {
     let mut lexer = Lexer::new(format);
     loop {
@@ -0,0 +1,129 @@
+//! Locale codes in number formats: `[$-409]`, `[$€-407]`, `[$-F800]` or `[$-ja-JP-x-gannen]`.
+//!
+//! The code is either a hexadecimal number or a language tag. The lower 16 bits of a hexadecimal
+//! code are the Windows LCID, the higher bits select the calendar and the numeral system. We only
+//! use the language to select month and day names and the number separators. Unsupported
+//! languages and calendars are formatted with the workbook locale and the Gregorian calendar.
+
+use crate::locale::{get_locale, Locale};
+
+pub enum FormatLocale {
+    /// Month and day names and separators come from this locale
+    Locale(&'static Locale),
+    /// [$-F800]: the long date format of the workbook locale
+    SystemLongDate,
+    /// [$-F400]: the time format of the workbook locale
+    SystemTime,
+}
+
+// Windows LCIDs of the most common languages and regions
+const LCIDS: &[(u32, &str)] = &[
+    (0x0401, "ar-SA"),
+    (0x0402, "bg-BG"),
+    (0x0403, "ca-ES"),
+    (0x0404, "zh-TW"),
+    (0x0405, "cs-CZ"),
+    (0x0406, "da-DK"),
+    (0x0407, "de-DE"),
+    (0x0408, "el-GR"),
+    (0x0409, "en-US"),
+    (0x040A, "es-ES"),
+    (0x040B, "fi-FI"),
+    (0x040C, "fr-FR"),
+    (0x040D, "he-IL"),
+    (0x040E, "hu-HU"),
+    (0x040F, "is-IS"),
+    (0x0410, "it-IT"),
+    (0x0411, "ja-JP"),
+    (0x0412, "ko-KR"),
+    (0x0413, "nl-NL"),
+    (0x0414, "nb-NO"),
+    (0x0415, "pl-PL"),
+    (0x0416, "pt-BR"),
+    (0x0418, "ro-RO"),
+    (0x0419, "ru-RU"),
+    (0x041A, "hr-HR"),
+    (0x041B, "sk-SK"),
+    (0x041D, "sv-SE"),
+    (0x041E, "th-TH"),
+    (0x041F, "tr-TR"),
+    (0x0421, "id-ID"),
+    (0x0422, "uk-UA"),
+    (0x0424, "sl-SI"),
+    (0x0425, "et-EE"),
+    (0x0426, "lv-LV"),
+    (0x0427, "lt-LT"),
+    (0x042A, "vi-VN"),
+    (0x0439, "hi-IN"),
+    (0x0804, "zh-CN"),
+    (0x0807, "de-CH"),
+    (0x0809, "en-GB"),
+    (0x080A, "es-MX"),
+    (0x080C, "fr-BE"),
+    (0x0810, "it-CH"),
+    (0x0813, "nl-BE"),
+    (0x0816, "pt-PT"),
+    (0x0C07, "de-AT"),
+    (0x0C09, "en-AU"),
+    (0x0C0A, "es-ES"),
+    (0x0C0C, "fr-CA"),
+    (0x1009, "en-CA"),
+    (0x100C, "fr-CH"),
+    (0x1409, "en-NZ"),
+    (0x1809, "en-IE"),
+    (0x1C09, "en-ZA"),
+    (0x2C0A, "es-AR"),
+    (0x4009, "en-IN"),
+];
+
+const SYSTEM_LONG_DATE: u32 = 0xF800;
+const SYSTEM_TIME: u32 = 0xF400;
+
+// Finds the locale of a language tag: "de-DE" => "de-DE" or "de".
+// Private use subtags are ignored: "ja-JP-x-gannen" => "ja-JP"
+fn get_tag_locale(tag: &str) -> Option<&'static Locale> {
+    let tag = tag.split("-x-").next().unwrap_or(tag);
+    if let Ok(locale) = get_locale(tag) {
+        return Some(locale);
+    }
+    let language = tag.split('-').next()?;
+    get_locale(language).ok()
+}
+
+/// Returns the locale selected by the locale code of a number format, if we support it
+pub fn get_format_locale(code: &str) -> Option<FormatLocale> {
+    match code.to_ascii_lowercase().as_str() {
+        "x-sysdate" => return Some(FormatLocale::SystemLongDate),
+        "x-systime" => return Some(FormatLocale::SystemTime),
+        _ => {}
+    }
+    let value = match u32::from_str_radix(code, 16) {
+        Ok(value) => value,
+        Err(_) => return get_tag_locale(code).map(FormatLocale::Locale),
+    };
+    let lcid = value & 0xFFFF;
+    match lcid {
+        SYSTEM_LONG_DATE => Some(FormatLocale::SystemLongDate),
+        SYSTEM_TIME => Some(FormatLocale::SystemTime),
+        _ => match LCIDS.iter().find(|(id, _)| *id == lcid) {
+            Some((_, tag)) => get_tag_locale(tag).map(FormatLocale::Locale),
+            // i.e. [$-de]
+            None => get_tag_locale(code).map(FormatLocale::Locale),
+        },
+    }
+}
+
+/// Converts a CLDR time pattern ("h:mm:ss a" or "HH:mm:ss") into a number format
+pub fn get_system_time_format(pattern: &str) -> String {
+    let mut format = "".to_string();
+    for c in pattern.chars() {
+        match c {
+            'a' => format.push_str("AM/PM"),
+            // time zone
+            'z' => {}
+            '\u{202f}' | '\u{a0}' => format.push(' '),
+            c => format.push(c),
+        }
+    }
+    format.trim().to_string()
+}
@@ -1,6 +1,7 @@
 pub mod dates;
 pub mod format;
 pub mod lexer;
+pub mod locale_codes;
 pub mod parser;
 
 #[cfg(test)]
@@ -66,15 +67,14 @@ mod test;
 //  | $      234 |
 //  | $     1234 |
 // We can't do this easily in IronCalc
-//
-// 3.- You can use ?/? to format fractions in Excel (this is probably not too hard)
 
 // TOKENs
 // ======
 //
 // * Color [Red] or [Color 23] or [Color23]
 // * Conditions [<100]
-// * Currency [$€]
+// * Currency and locale [$€], [$USD-409], [$-407] (see locale_codes.rs)
+// * Asian numerals [DBNum1] (ignored)
 // * Space _X when X is any given char
 // * A spacer of chars: *X where X is repeated as much as possible
 // * Literals: $, (, ), :, +, - and space
@@ -87,6 +87,7 @@ mod test;
 // * @ inserts raw text
 // * Scientific literals E+, E-, e+, e-
 // * . period. First one is the decimal point, subsequent are literals.
+// * / in between digit characters is a fraction: # ?/?, # ??/??, ?/8 or 0/100
 
 // d day of the month
 // dd day of the month (padded i.e 05)
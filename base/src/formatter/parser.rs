@@ -3,7 +3,7 @@ use super::lexer::{Compare, Lexer, Token};
 pub struct Digit {
     pub kind: char, // '#' | '?' | '0'
     pub index: i32,
-    pub number: NumberState, // 'i' | 'd' | 'e' (integer, decimal or exponent)
+    pub number: NumberState, // integer, decimal, exponent, numerator or denominator
 }
 
 pub enum TextToken {
@@ -15,6 +15,9 @@ pub enum TextToken {
     Raw,
     Digit(Digit),
     Period,
+    // Fractions
+    FractionSlash,
+    FixedDenominator(u32),
     // Dates
     Day,
     DayPadded,
@@ -53,11 +56,16 @@ pub struct NumberPart {
     pub is_scientific: bool,
     pub scientific_minus: bool,
     pub exponent_digit_count: i32,
-    pub currency: Option<char>,
+    pub locale_code: Option<String>, // i.e. 409 in [$-409]
+    pub is_fraction: bool,
+    pub numerator_digit_count: i32,
+    pub denominator_digit_count: i32,
+    pub fixed_denominator: Option<u32>, // i.e. 100 in `# ?/100`
 }
 
 pub struct DatePart {
     pub color: Option<i32>,
+    pub locale_code: Option<String>,
     pub use_ampm: bool,
     pub tokens: Vec<TextToken>,
 }
@@ -83,6 +91,8 @@ pub enum NumberState {
     Integer,
     Decimal,
     Exponent,
+    Numerator,
+    Denominator,
 }
 
 impl NumberState {
@@ -97,6 +107,14 @@ impl NumberState {
     pub fn is_exponent(&self) -> bool {
         matches!(self, NumberState::Exponent)
     }
+
+    pub fn is_numerator(&self) -> bool {
+        matches!(self, NumberState::Numerator)
+    }
+
+    pub fn is_denominator(&self) -> bool {
+        matches!(self, NumberState::Denominator)
+    }
 }
 
 impl ParsePart {
@@ -108,6 +126,14 @@ impl ParsePart {
             ParsePart::General(..) => false,
         }
     }
+    pub fn locale_code(&self) -> Option<&str> {
+        match &self {
+            ParsePart::Date(p) => p.locale_code.as_deref(),
+            ParsePart::Number(p) => p.locale_code.as_deref(),
+            ParsePart::Error(..) => None,
+            ParsePart::General(..) => None,
+        }
+    }
     pub fn is_date(&self) -> bool {
         match &self {
             ParsePart::Date(..) => true,
@@ -121,6 +147,12 @@ impl ParsePart {
 // Numbers:
 // [integer section][decimal point][fractional section][optional exponent]
 // So #,##0.00 is valid but 0.00#,## is not.
+//
+// Fractions:
+// [optional integer section][numerator]/[denominator]
+// The numerator is the digits right before the '/', the denominator is either digits
+// (the maximum number of digits of the denominator) or a fixed number:
+// # ?/?, # ??/??, ?/8 or 0/100
 
 impl Parser {
     pub fn new(format: &str) -> Self {
@@ -155,8 +187,12 @@ impl Parser {
         let mut exponent_digit_count = 0;
         let mut number = NumberState::Integer;
         let mut index = 0;
-        let mut currency = None;
+        let mut locale_code = None;
         let mut is_time = false;
+        let mut is_fraction = false;
+        let mut numerator_digit_count = 0;
+        let mut denominator_digit_count = 0;
+        let mut fixed_denominator = None;
 
         while token != Token::EOF && token != Token::Separator {
             let next_token = self.lexer.next_token();
@@ -166,6 +202,8 @@ impl Parser {
             if token_is_digit {
                 if is_scientific {
                     exponent_digit_count += 1;
+                } else if number.is_denominator() {
+                    denominator_digit_count += 1;
                 } else if found_decimal_dot {
                     precision += 1;
                 } else {
@@ -213,8 +251,16 @@ impl Parser {
                 Token::Condition(cmp, value) => {
                     condition = Some((cmp, value));
                 }
-                Token::Currency(c) => {
-                    currency = Some(c);
+                Token::Currency(currency, code) => {
+                    if !currency.is_empty() {
+                        tokens.push(TextToken::Text(currency));
+                    }
+                    if code.is_some() {
+                        locale_code = code;
+                    }
+                }
+                Token::DbNum(_) => {
+                    // We always use western digits
                 }
                 Token::QuestionMark => {
                     tokens.push(TextToken::Digit(Digit {
@@ -240,6 +286,46 @@ impl Parser {
                     }));
                     index += 1;
                 }
+                Token::Literal('/')
+                    if last_token_is_digit
+                        && !is_fraction
+                        && !found_decimal_dot
+                        && !is_scientific
+                        && (next_token_is_digit || matches!(next_token, Token::Number(_))) =>
+                {
+                    // The digits right before the slash are the numerator
+                    is_fraction = true;
+                    for token in tokens.iter_mut().rev() {
+                        match token {
+                            TextToken::Digit(digit) => {
+                                digit.number = NumberState::Numerator;
+                                numerator_digit_count += 1;
+                            }
+                            _ => break,
+                        }
+                    }
+                    let numerator_start = tokens.len() - numerator_digit_count as usize;
+                    for (index, token) in tokens[numerator_start..].iter_mut().enumerate() {
+                        if let TextToken::Digit(digit) = token {
+                            digit.index = index as i32;
+                        }
+                    }
+                    digit_count -= numerator_digit_count;
+                    tokens.push(TextToken::FractionSlash);
+                    number = NumberState::Denominator;
+                    index = 0;
+                }
+                Token::Number(value) => {
+                    if number.is_denominator()
+                        && denominator_digit_count == 0
+                        && fixed_denominator.is_none()
+                    {
+                        fixed_denominator = Some(value);
+                        tokens.push(TextToken::FixedDenominator(value));
+                    } else {
+                        tokens.push(TextToken::Text(value.to_string()));
+                    }
+                }
                 Token::Literal(value) => {
                     if value == ':' {
                         is_time = true;
@@ -397,6 +483,7 @@ impl Parser {
             }
             ParsePart::Date(DatePart {
                 color,
+                locale_code,
                 use_ampm,
                 tokens,
             })
@@ -413,7 +500,11 @@ impl Parser {
                 is_scientific,
                 scientific_minus,
                 exponent_digit_count,
-                currency,
+                locale_code,
+                is_fraction,
+                numerator_digit_count,
+                denominator_digit_count,
+                fixed_denominator,
             })
         }
     }
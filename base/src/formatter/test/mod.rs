@@ -3,7 +3,10 @@ mod test_dates;
 mod test_de_examples;
 mod test_en_examples;
 mod test_fr_examples;
+mod test_fractions;
 mod test_general;
+mod test_locale_codes;
+mod test_locales;
 mod test_parse_formatted_number;
 mod test_scientific;
 mod test_time;
@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::{
+    formatter::format::{format_number, parse_formatted_number},
+    locale::{get_locale, Locale},
+    types::DateSystem,
+};
+
+fn get_default_locale() -> &'static Locale {
+    get_locale("en").unwrap()
+}
+
+fn format(value: f64, format_code: &str) -> String {
+    format_number(value, format_code, get_default_locale()).text
+}
+
+#[test]
+fn mixed_fractions() {
+    assert_eq!(format(1.75, "# ?/?"), "1 3/4");
+    assert_eq!(format(0.75, "# ?/?"), " 3/4");
+    assert_eq!(format(-1.75, "# ?/?"), "-1 3/4");
+    assert_eq!(format(1234.5, "#,##0 ?/?"), "1,234 1/2");
+    // Only the integer part is displayed if there is no fraction
+    assert_eq!(format(2.0, "# ?/?"), "2    ");
+    assert_eq!(format(1.999, "# ?/?"), "2    ");
+    assert_eq!(format(0.0, "# ?/?"), "0    ");
+}
+
+#[test]
+fn best_approximation() {
+    assert_eq!(format(std::f64::consts::PI, "# ?/?"), "3 1/7");
+    assert_eq!(format(std::f64::consts::PI, "# ??/??"), "3 14/99");
+    assert_eq!(format(std::f64::consts::PI, "# ???/???"), "3  16/113");
+    assert_eq!(format(0.333333, "# ?/?"), " 1/3");
+    assert_eq!(format(0.6875, "# ??/??"), " 11/16");
+}
+
+#[test]
+fn padding() {
+    // The numerator is aligned to the right and the denominator to the left
+    assert_eq!(format(1.5, "# ??/??"), "1  1/2 ");
+    assert_eq!(format(1.5, "# ???/???"), "1   1/2  ");
+    assert_eq!(format(1.5, "# 00/00"), "1 01/2 ");
+}
+
+#[test]
+fn improper_fractions() {
+    assert_eq!(format(1.75, "?/?"), "7/4");
+    assert_eq!(format(0.5, "?/?"), "1/2");
+    assert_eq!(format(0.0, "?/?"), "0/1");
+    assert_eq!(format(3.0, "?/?"), "3/1");
+}
+
+#[test]
+fn fixed_denominators() {
+    assert_eq!(format(0.3, "# ?/8"), " 2/8");
+    assert_eq!(format(0.3, "0/100"), "30/100");
+    assert_eq!(format(1.25, "# ?/4"), "1 1/4");
+    assert_eq!(format(2.0, "# ?/4"), "2    ");
+    assert_eq!(format(1.5, "# ?/8"), "1 4/8");
+}
+
+#[test]
+fn slash_is_a_literal_otherwise() {
+    assert_eq!(format(3.0, "0 \"m\"/\"s\""), "3 m/s");
+    assert_eq!(format(0.5, "0.0/"), "0.5/");
+}
+
+#[test]
+fn parse_mixed_fractions() {
+    let locale = get_default_locale();
+    assert_eq!(
+        parse_formatted_number("1 3/4", &["$"], locale, DateSystem::Excel1900),
+        Ok((1.75, Some("# ?/?".to_string())))
+    );
+    assert_eq!(
+        parse_formatted_number("-2 1/2", &["$"], locale, DateSystem::Excel1900),
+        Ok((-2.5, Some("# ?/?".to_string())))
+    );
+    assert_eq!(
+        parse_formatted_number("0 13/16", &["$"], locale, DateSystem::Excel1900),
+        Ok((0.8125, Some("# ??/??".to_string())))
+    );
+    assert!(parse_formatted_number("1 3/0", &["$"], locale, DateSystem::Excel1900).is_err());
+    assert!(parse_formatted_number("1 a/4", &["$"], locale, DateSystem::Excel1900).is_err());
+}
@@ -0,0 +1,70 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::{
+    formatter::format::format_number,
+    locale::{get_locale, Locale},
+};
+
+fn get_default_locale() -> &'static Locale {
+    get_locale("en").unwrap()
+}
+
+fn format(value: f64, format_code: &str) -> String {
+    format_number(value, format_code, get_default_locale()).text
+}
+
+// 14 March 2012
+const DATE: f64 = 40982.0;
+
+#[test]
+fn lcid_month_and_day_names() {
+    assert_eq!(format(DATE, "[$-409]mmmm d, yyyy"), "March 14, 2012");
+    assert_eq!(format(DATE, "[$-407]d. mmmm yyyy"), "14. März 2012");
+    assert_eq!(format(DATE, "[$-40C]dddd d mmmm"), "mercredi 14 mars");
+    // Unknown regions use the language
+    assert_eq!(format(DATE, "[$-C07]mmmm"), "März");
+    assert_eq!(format(DATE, "[$-de-AT]mmmm"), "März");
+    // The calendar and numeral system bits are ignored
+    assert_eq!(format(DATE, "[$-1010407]mmmm"), "März");
+}
+
+#[test]
+fn lcid_separators() {
+    assert_eq!(format(1234.5, "[$-407]#,##0.00"), "1.234,50");
+    assert_eq!(format(1234.5, "#,##0.00 [$€-407]"), "1.234,50 €");
+    assert_eq!(format(-1234.5, "[$€-407] #,##0.00"), "-€ 1.234,50");
+}
+
+#[test]
+fn long_currencies() {
+    assert_eq!(format(1234.5, "[$USD-409] #,##0.00"), "USD 1,234.50");
+    assert_eq!(format(42.0, "[$CHF] 0"), "CHF 42");
+    assert_eq!(format(42.0, "0 [$kr.-406]"), "42 kr.");
+}
+
+#[test]
+fn system_formats() {
+    assert_eq!(
+        format(DATE, "[$-F800]dddd, mmmm dd, yyyy"),
+        "Wednesday, March 14, 2012"
+    );
+    assert_eq!(format(0.75, "[$-F400]h:mm:ss AM/PM"), "6:00:00 PM");
+    let de = get_locale("de").unwrap();
+    assert_eq!(
+        format_number(DATE, "[$-F800]dddd, mmmm dd, yyyy", de).text,
+        "Mittwoch, 14. März 2012"
+    );
+    assert_eq!(
+        format_number(0.75, "[$-x-systime]h:mm:ss AM/PM", de).text,
+        "18:00:00"
+    );
+}
+
+#[test]
+fn unsupported_calendars() {
+    // Formatted with the Gregorian calendar rather than failing
+    assert_eq!(format(DATE, "[$-ja-JP-x-gannen]yyyy/m/d"), "2012/3/14");
+    assert_eq!(format(DATE, "[$-3000411]yyyy/m/d"), "2012/3/14");
+    assert_eq!(format(42.0, "[DBNum1]0"), "42");
+    assert_eq!(format(42.0, "[DBNum2][$-804]General"), "42");
+}
@@ -0,0 +1,69 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::{
+    formatter::format::{format_number, parse_formatted_number},
+    locale::get_locale,
+    types::DateSystem,
+};
+
+#[test]
+fn indian_grouping() {
+    let locale = get_locale("en-IN").unwrap();
+    assert_eq!(
+        format_number(1234567.89, "#,##0.00", locale).text,
+        "12,34,567.89"
+    );
+    let locale = get_locale("hi").unwrap();
+    assert_eq!(
+        format_number(123456789.0, "#,##0", locale).text,
+        "12,34,56,789"
+    );
+}
+
+#[test]
+fn separators() {
+    let locale = get_locale("pt").unwrap();
+    assert_eq!(format_number(1234.5, "#,##0.00", locale).text, "1.234,50");
+    let locale = get_locale("nl").unwrap();
+    assert_eq!(format_number(1234.5, "#,##0.00", locale).text, "1.234,50");
+    let locale = get_locale("pl").unwrap();
+    assert_eq!(
+        format_number(1234.5, "#,##0.00", locale).text,
+        "1\u{a0}234,50"
+    );
+    let locale = get_locale("sv").unwrap();
+    assert_eq!(
+        format_number(-1234.5, "#,##0.00", locale).text,
+        "-1\u{a0}234,50"
+    );
+}
+
+#[test]
+fn cjk_dates() {
+    // 2024-03-05
+    let locale = get_locale("ja").unwrap();
+    assert_eq!(
+        format_number(45356.0, &locale.dates.date_formats.long, locale).text,
+        "2024年3月5日"
+    );
+    let locale = get_locale("zh").unwrap();
+    assert_eq!(
+        format_number(45356.0, &locale.dates.date_formats.long, locale).text,
+        "2024年3月5日"
+    );
+}
+
+#[test]
+fn native_digits() {
+    let locale = get_locale("ar").unwrap();
+    assert_eq!(format_number(1234.5, "0.00", locale).text, "١٢٣٤.٥٠");
+    assert_eq!(
+        parse_formatted_number("١٢٣", &[], locale, DateSystem::Excel1900),
+        Ok((123.0, None))
+    );
+    // latin digits are also accepted
+    assert_eq!(
+        parse_formatted_number("123", &[], locale, DateSystem::Excel1900),
+        Ok((123.0, None))
+    );
+}
@@ -1,12 +1,14 @@
 #![allow(clippy::unwrap_used)]
 
-use crate::{formatter::format::parse_formatted_number, locale::get_default_locale};
+use crate::{
+    formatter::format::parse_formatted_number, locale::get_default_locale, types::DateSystem,
+};
 
 const PARSE_ERROR_MSG: &str = "Could not parse number";
 
 fn parse(input: &str, currencies: &[&str]) -> Result<(f64, Option<String>), String> {
     let locale = get_default_locale();
-    parse_formatted_number(input, currencies, locale)
+    parse_formatted_number(input, currencies, locale, DateSystem::Excel1900)
 }
 
 #[test]
@@ -70,3 +70,32 @@ fn elapsed_hour_padded() {
     let formatted = format_number(value, format, locale);
     assert_eq!(formatted.text, "02:24:00");
 }
+
+#[test]
+fn time_formats_are_not_dates() {
+    use crate::formatter::lexer::{is_date_number_format, is_time_number_format};
+    for format in ["h:mm", "hh:mm:ss", "mm:ss", "[h]:mm", "h:mm AM/PM"] {
+        assert!(!is_date_number_format(format), "{format}");
+        assert!(is_time_number_format(format), "{format}");
+    }
+    for format in ["dd/mm/yyyy", "mmm-yy", "yyyy-mm-dd h:mm", "d-mmm"] {
+        assert!(is_date_number_format(format), "{format}");
+        assert!(!is_time_number_format(format), "{format}");
+    }
+    assert!(!is_time_number_format("General"));
+}
+
+#[test]
+fn times_are_rounded_to_the_second() {
+    let locale = get_default_locale();
+    // 14/24 to 15 significant digits is a bit less than 14:00
+    assert_eq!(
+        format_number(0.583333333333333, "h:mm", locale).text,
+        "14:00"
+    );
+    // The date moves to the next day
+    assert_eq!(
+        format_number(45_000.999_999_99, "yyyy-mm-dd hh:mm:ss", locale).text,
+        format_number(45_001.0, "yyyy-mm-dd hh:mm:ss", locale).text
+    );
+}
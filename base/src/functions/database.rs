@@ -762,7 +762,8 @@ impl<'a> Model<'a> {
             }
         }
 
-        let formatted_number = parse_formatted_number(&criteria, &[], self.locale);
+        let formatted_number =
+            parse_formatted_number(&criteria, &[], self.locale, self.get_date_system());
 
         match op {
             ">" | ">=" | "<" | "<=" => {
@@ -80,21 +80,22 @@ macro_rules! time_part_fn {
     };
 }
 
-use crate::constants::MAXIMUM_DATE_SERIAL_NUMBER;
-use crate::constants::MINIMUM_DATE_SERIAL_NUMBER;
 use crate::expressions::types::CellReferenceIndex;
+use crate::formatter::dates::convert_to_serial_number;
 use crate::formatter::dates::date_to_serial_number;
+use crate::formatter::dates::maximum_date_serial_number;
+use crate::formatter::dates::minimum_date_serial_number;
 use crate::formatter::dates::permissive_date_to_serial_number;
 use crate::formatter::dates::DATE_OUT_OF_RANGE_MESSAGE;
 use crate::model::get_milliseconds_since_epoch;
 use crate::number_format::to_precision;
 use crate::{
     calc_result::CalcResult,
-    constants::EXCEL_DATE_BASE,
     expressions::parser::{ArrayNode, Node},
     expressions::token::Error,
     formatter::dates::from_excel_date,
     model::Model,
+    types::DateSystem,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -404,7 +405,7 @@ fn parse_year_simple(year_str: &str) -> Result<i32, String> {
     }
 }
 
-fn parse_datevalue_text(value: &str) -> Result<i32, String> {
+fn parse_datevalue_text(value: &str, date_system: DateSystem) -> Result<i32, String> {
     // Trim whitespace and discard any time component (e.g., "2024-02-29 06:00" -> "2024-02-29")
     let mut date_str = value.trim();
     if let Some(idx) = date_str.find('T') {
@@ -468,11 +469,11 @@ fn parse_datevalue_text(value: &str) -> Result<i32, String> {
     let year = parse_year_simple(year_str)?;
 
     // Excel 1900 leap-year bug: 29-Feb-1900 is treated as serial 60
-    if year == 1900 && month == 2 && day == 29 {
+    if date_system == DateSystem::Excel1900 && year == 1900 && month == 2 && day == 29 {
         return Ok(60);
     }
 
-    match date_to_serial_number(day, month, year) {
+    match date_to_serial_number(day, month, year, date_system) {
         Ok(n) => Ok(n),
         Err(_) => Err("Not a valid date".to_string()),
     }
@@ -487,7 +488,7 @@ impl<'a> Model<'a> {
         let result = self.evaluate_node_in_context(node, cell);
         match result {
             CalcResult::Number(f) => Ok(f.floor() as i64),
-            CalcResult::String(s) => match parse_datevalue_text(&s) {
+            CalcResult::String(s) => match parse_datevalue_text(&s, self.get_date_system()) {
                 Ok(n) => Ok(n as i64),
                 Err(_) => Err(CalcResult::Error {
                     error: Error::VALUE,
@@ -547,7 +548,7 @@ impl<'a> Model<'a> {
             Ok(d) => d,
             Err(e) => return e,
         };
-        if serial_number > MAXIMUM_DATE_SERIAL_NUMBER as i64 {
+        if serial_number > maximum_date_serial_number(self.get_date_system()) as i64 {
             return CalcResult::Error {
                 error: Error::NUM,
                 origin: cell,
@@ -579,7 +580,7 @@ impl<'a> Model<'a> {
             month = 1;
             year += 1;
         }
-        match date_to_serial_number(1, month, year) {
+        match date_to_serial_number(1, month, year, self.get_date_system()) {
             Ok(serial_number) => CalcResult::Number(serial_number as f64 - 1.0),
             Err(message) => CalcResult::Error {
                 error: Error::NUM,
@@ -623,7 +624,7 @@ impl<'a> Model<'a> {
             }
             Err(s) => return s,
         };
-        match permissive_date_to_serial_number(day, month, year) {
+        match permissive_date_to_serial_number(day, month, year, self.get_date_system()) {
             Ok(serial_number) => CalcResult::Number(serial_number as f64),
             Err(message) => CalcResult::Error {
                 error: Error::NUM,
@@ -663,8 +664,9 @@ impl<'a> Model<'a> {
             date - Months::new(months_abs)
         };
 
-        let serial_number = native_date.num_days_from_ce() - EXCEL_DATE_BASE;
-        if serial_number < MINIMUM_DATE_SERIAL_NUMBER {
+        let date_system = self.get_date_system();
+        let serial_number = convert_to_serial_number(native_date, date_system);
+        if serial_number < minimum_date_serial_number(date_system) {
             return CalcResult::Error {
                 error: Error::NUM,
                 origin: cell,
@@ -785,9 +787,9 @@ impl<'a> Model<'a> {
         let seconds = get_milliseconds_since_epoch() / 1000;
         DateTime::from_timestamp(seconds, 0).map(|dt| {
             let local_time = dt.with_timezone(&tz);
-            let days_from_1900 = local_time.num_days_from_ce() - EXCEL_DATE_BASE;
+            let days = convert_to_serial_number(local_time.date_naive(), self.get_date_system());
             let fraction = (local_time.num_seconds_from_midnight() as f64) / (60.0 * 60.0 * 24.0);
-            days_from_1900 as f64 + fraction
+            days as f64 + fraction
         })
     }
 
@@ -839,7 +841,7 @@ impl<'a> Model<'a> {
         serial: i64,
         cell: CellReferenceIndex,
     ) -> Result<chrono::NaiveDate, CalcResult> {
-        match from_excel_date(serial) {
+        match from_excel_date(serial, self.get_date_system()) {
             Ok(date) => Ok(date),
             Err(_) => Err(CalcResult::Error {
                 error: Error::NUM,
@@ -1101,7 +1103,7 @@ impl<'a> Model<'a> {
             return CalcResult::new_args_number_error(cell);
         }
         match self.evaluate_node_in_context(&args[0], cell) {
-            CalcResult::String(s) => match parse_datevalue_text(&s) {
+            CalcResult::String(s) => match parse_datevalue_text(&s, self.get_date_system()) {
                 Ok(n) => CalcResult::Number(n as f64),
                 Err(_) => CalcResult::Error {
                     error: Error::VALUE,
@@ -1503,7 +1505,7 @@ impl<'a> Model<'a> {
                 }
             }
         }
-        let serial = date.num_days_from_ce() - EXCEL_DATE_BASE;
+        let serial = convert_to_serial_number(date, self.get_date_system());
         CalcResult::Number(serial as f64)
     }
 
@@ -1515,16 +1517,19 @@ impl<'a> Model<'a> {
         let mut holiday_set = std::collections::HashSet::new();
 
         if let Some(arg) = arg_option {
-            self.collect_serial_numbers(arg, cell, |serial| match from_excel_date(serial) {
-                Ok(date) => {
-                    holiday_set.insert(date);
-                    Ok(())
+            let date_system = self.get_date_system();
+            self.collect_serial_numbers(arg, cell, |serial| {
+                match from_excel_date(serial, date_system) {
+                    Ok(date) => {
+                        holiday_set.insert(date);
+                        Ok(())
+                    }
+                    Err(_) => Err(CalcResult::Error {
+                        error: Error::NUM,
+                        origin: cell,
+                        message: "Invalid holiday date".to_string(),
+                    }),
                 }
-                Err(_) => Err(CalcResult::Error {
-                    error: Error::NUM,
-                    origin: cell,
-                    message: "Invalid holiday date".to_string(),
-                }),
             })?;
         }
 
@@ -1584,7 +1589,7 @@ impl<'a> Model<'a> {
                 }
             }
         }
-        let serial = date.num_days_from_ce() - EXCEL_DATE_BASE;
+        let serial = convert_to_serial_number(date, self.get_date_system());
         CalcResult::Number(serial as f64)
     }
 
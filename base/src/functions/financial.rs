@@ -2,10 +2,11 @@ use chrono::Datelike;
 
 use crate::{
     calc_result::CalcResult,
-    constants::{LAST_COLUMN, LAST_ROW, MAXIMUM_DATE_SERIAL_NUMBER, MINIMUM_DATE_SERIAL_NUMBER},
+    constants::{LAST_COLUMN, LAST_ROW},
     expressions::{parser::Node, token::Error, types::CellReferenceIndex},
-    formatter::dates::from_excel_date,
+    formatter::dates::{from_excel_date, maximum_date_serial_number, minimum_date_serial_number},
     model::Model,
+    types::DateSystem,
 };
 
 use super::financial_util::{compute_irr, compute_npv, compute_rate, compute_xirr, compute_xnpv};
@@ -13,9 +14,13 @@ use super::financial_util::{compute_irr, compute_npv, compute_rate, compute_xirr
 // See:
 // https://github.com/apache/openoffice/blob/c014b5f2b55cff8d4b0c952d5c16d62ecde09ca1/main/scaddins/source/analysis/financial.cxx
 
-fn is_less_than_one_year(start_date: i64, end_date: i64) -> Result<bool, String> {
-    let end = from_excel_date(end_date)?;
-    let start = from_excel_date(start_date)?;
+fn is_less_than_one_year(
+    start_date: i64,
+    end_date: i64,
+    date_system: DateSystem,
+) -> Result<bool, String> {
+    let end = from_excel_date(end_date, date_system)?;
+    let start = from_excel_date(start_date, date_system)?;
     if end_date - start_date < 365 {
         return Ok(true);
     }
@@ -917,10 +922,11 @@ impl<'a> Model<'a> {
             return CalcResult::new_error(Error::NUM, cell, "Not enough values".to_string());
         }
         let first_date = dates[0];
+        let date_system = self.get_date_system();
+        let minimum_date = minimum_date_serial_number(date_system) as f64;
+        let maximum_date = maximum_date_serial_number(date_system) as f64;
         for date in &dates {
-            if *date < MINIMUM_DATE_SERIAL_NUMBER as f64
-                || *date > MAXIMUM_DATE_SERIAL_NUMBER as f64
-            {
+            if *date < minimum_date || *date > maximum_date {
                 // Excel docs claim that if any number in dates is not a valid date,
                 // XNPV returns the #VALUE! error value, but it seems to return #VALUE!
                 return CalcResult::new_error(
@@ -985,10 +991,11 @@ impl<'a> Model<'a> {
             return CalcResult::new_error(Error::NUM, cell, "Not enough values".to_string());
         }
         let first_date = dates[0];
+        let date_system = self.get_date_system();
+        let minimum_date = minimum_date_serial_number(date_system) as f64;
+        let maximum_date = maximum_date_serial_number(date_system) as f64;
         for date in &dates {
-            if *date < MINIMUM_DATE_SERIAL_NUMBER as f64
-                || *date > MAXIMUM_DATE_SERIAL_NUMBER as f64
-            {
+            if *date < minimum_date || *date > maximum_date {
                 return CalcResult::new_error(
                     Error::NUM,
                     cell,
@@ -1372,10 +1379,14 @@ impl<'a> Model<'a> {
             Ok(f) => f,
             Err(s) => return s,
         };
-        let less_than_one_year = match is_less_than_one_year(settlement as i64, maturity as i64) {
-            Ok(f) => f,
-            Err(_) => return CalcResult::new_error(Error::NUM, cell, "Invalid date".to_string()),
-        };
+        let less_than_one_year =
+            match is_less_than_one_year(settlement as i64, maturity as i64, self.get_date_system())
+            {
+                Ok(f) => f,
+                Err(_) => {
+                    return CalcResult::new_error(Error::NUM, cell, "Invalid date".to_string())
+                }
+            };
         if settlement > maturity {
             return CalcResult::new_error(
                 Error::NUM,
@@ -1437,10 +1448,14 @@ impl<'a> Model<'a> {
             Ok(f) => f,
             Err(s) => return s,
         };
-        let less_than_one_year = match is_less_than_one_year(settlement as i64, maturity as i64) {
-            Ok(f) => f,
-            Err(_) => return CalcResult::new_error(Error::NUM, cell, "Invalid date".to_string()),
-        };
+        let less_than_one_year =
+            match is_less_than_one_year(settlement as i64, maturity as i64, self.get_date_system())
+            {
+                Ok(f) => f,
+                Err(_) => {
+                    return CalcResult::new_error(Error::NUM, cell, "Invalid date".to_string())
+                }
+            };
         if settlement > maturity {
             return CalcResult::new_error(
                 Error::NUM,
@@ -1488,10 +1503,14 @@ impl<'a> Model<'a> {
             Ok(f) => f,
             Err(s) => return s,
         };
-        let less_than_one_year = match is_less_than_one_year(settlement as i64, maturity as i64) {
-            Ok(f) => f,
-            Err(_) => return CalcResult::new_error(Error::NUM, cell, "Invalid date".to_string()),
-        };
+        let less_than_one_year =
+            match is_less_than_one_year(settlement as i64, maturity as i64, self.get_date_system())
+            {
+                Ok(f) => f,
+                Err(_) => {
+                    return CalcResult::new_error(Error::NUM, cell, "Invalid date".to_string())
+                }
+            };
         if settlement > maturity {
             return CalcResult::new_error(
                 Error::NUM,
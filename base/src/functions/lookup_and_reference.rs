@@ -840,8 +840,8 @@ impl<'a> Model<'a> {
     }
 
     // FORMULATEXT(reference)
-    // Returns a formula as a string. Two differences with Excel:
-    // - It returns the formula in English
+    // Returns a formula as a string in the language and locale of the workbook.
+    // One difference with Excel:
     // - It formats the formula without spaces between elements
     pub(crate) fn fn_formulatext(&mut self, args: &[Node], cell: CellReferenceIndex) -> CalcResult {
         if args.len() != 1 {
@@ -864,7 +864,7 @@ impl<'a> Model<'a> {
                     message: "argument must be a reference to a single cell".to_string(),
                 };
             }
-            if let Ok(Some(f)) = self.get_english_cell_formula(left.sheet, left.row, left.column) {
+            if let Ok(Some(f)) = self.get_cell_formula(left.sheet, left.row, left.column) {
                 CalcResult::String(f)
             } else {
                 CalcResult::Error {
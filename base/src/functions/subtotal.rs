@@ -17,6 +17,8 @@ use crate::{
 /// In Excel filters are non-dynamic. Once you apply filters in a table (say value in column 1 should > 20) they
 /// stay that way, even if you change the value of the values in the table after the fact.
 /// If you try to hide rows in a table with filtered rows they will behave as if filtered
+/// Rows hidden by collapsing an outline group are just hidden rows.
+/// Hidden columns are never ignored, SUBTOTAL is meant for vertical ranges.
 /// // Also subtotals ignore subtotals
 ///
 #[derive(PartialEq)]
@@ -38,6 +40,15 @@ impl<'a> Model<'a> {
             Ok(ws) => ws,
             Err(_) => return false,
         };
+        // The sheet AutoFilter behaves like a table
+        if let Some(auto_filter) = &worksheet.auto_filter {
+            if let Ok((column1, row1, column2, row2)) = parse_range(&auto_filter.reference) {
+                if ((column >= column1) && (column <= column2)) && ((row >= row1) && (row <= row2))
+                {
+                    return !auto_filter.filter_columns.is_empty();
+                }
+            }
+        }
         for table in self.workbook.tables.values() {
             if worksheet.name != table.sheet_name {
                 continue;
@@ -60,14 +71,7 @@ impl<'a> Model<'a> {
         column: i32,
     ) -> Result<CellTableStatus, String> {
         let worksheet = self.workbook.worksheet(sheet_index)?;
-        let mut hidden = false;
-        for row_style in &worksheet.rows {
-            if row_style.r == row {
-                hidden = row_style.hidden;
-                break;
-            }
-        }
-        if !hidden {
+        if !worksheet.is_row_hidden(row)? {
             return Ok(CellTableStatus::Normal);
         }
         // The row is hidden we need to know if the table has filters
@@ -2,7 +2,7 @@ use crate::{
     calc_result::CalcResult,
     constants::{LAST_COLUMN, LAST_ROW},
     expressions::{parser::Node, token::Error, types::CellReferenceIndex},
-    formatter::format::{format_number, parse_formatted_number},
+    formatter::format::{format_number_with_date_system, parse_formatted_number},
     model::Model,
     number_format::to_precision,
 };
@@ -149,7 +149,12 @@ impl<'a> Model<'a> {
                 Ok(s) => s,
                 Err(s) => return s,
             };
-            let d = format_number(value, &format_code, self.locale);
+            let d = format_number_with_date_system(
+                value,
+                &format_code,
+                self.locale,
+                self.get_date_system(),
+            );
             if let Some(_e) = d.error {
                 return CalcResult::Error {
                     error: Error::VALUE,
@@ -1210,7 +1215,9 @@ impl<'a> Model<'a> {
         match self.evaluate_node_in_context(&args[0], cell) {
             CalcResult::String(text) => {
                 let currencies = vec!["$", "€"];
-                if let Ok((value, _)) = parse_formatted_number(&text, &currencies, self.locale) {
+                if let Ok((value, _)) =
+                    parse_formatted_number(&text, &currencies, self.locale, self.get_date_system())
+                {
                     return CalcResult::Number(value);
                 };
                 CalcResult::Error {
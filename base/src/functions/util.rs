@@ -91,6 +91,15 @@ pub(crate) fn from_wildcard_to_regex(
     wildcard: &str,
     exact: bool,
 ) -> Result<regex::Regex, regex::Error> {
+    let reg = &wildcard_to_regex_pattern(wildcard);
+    if exact {
+        return regex::Regex::new(&format!("^{reg}$"));
+    }
+    regex::Regex::new(reg)
+}
+
+/// Converts an Excel wildcard into the source of an equivalent regex
+pub(crate) fn wildcard_to_regex_pattern(wildcard: &str) -> String {
     // 1. Escape all
     let reg = &regex::escape(wildcard);
 
@@ -111,13 +120,10 @@ pub(crate) fn from_wildcard_to_regex(
     // 7. Remove all the others '\\~d' --> 'd'
     let reg = &reg.replace("\\~", "");
     // 8. Put back the '\\~\\~'  as '\\~'
-    let reg = &reg.replace("??", "\\~");
+    let reg = reg.replace("??", "\\~");
 
     // And we have a valid Perl regex! (As Kim Kardashian said before me: "I know, right?")
-    if exact {
-        return regex::Regex::new(&format!("^{reg}$"));
-    }
-    regex::Regex::new(reg)
+    reg
 }
 
 // NUMBERS ///
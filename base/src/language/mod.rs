@@ -1,14 +1,22 @@
 use std::{collections::HashMap, sync::OnceLock};
 
 use bitcode::{Decode, Encode};
+use serde::Deserialize;
 
-#[derive(Encode, Decode)]
+#[derive(Encode, Decode, Deserialize)]
 pub struct Booleans {
     pub r#true: String,
     pub r#false: String,
 }
 
-#[derive(Encode, Decode)]
+/// The letters of the rows and the columns in R1C1 references, i.e. Z1S1 in German
+#[derive(Encode, Decode, Deserialize)]
+pub struct R1C1Letters {
+    pub row: char,
+    pub column: char,
+}
+
+#[derive(Encode, Decode, Deserialize)]
 pub struct Errors {
     pub r#ref: String,
     pub name: String,
@@ -24,7 +32,7 @@ pub struct Errors {
     pub null: String,
 }
 
-#[derive(Encode, Decode)]
+#[derive(Encode, Decode, Deserialize)]
 pub struct Functions {
     pub and: String,
     pub r#false: String,
@@ -373,11 +381,12 @@ pub struct Functions {
     pub small: String,
 }
 
-#[derive(Encode, Decode)]
+#[derive(Encode, Decode, Deserialize)]
 pub struct Language {
     pub name: String,
     pub code: String,
     pub booleans: Booleans,
+    pub r1c1: R1C1Letters,
     pub errors: Errors,
     pub functions: Functions,
 }
@@ -396,6 +405,11 @@ fn get_languages() -> &'static HashMap<String, Language> {
     })
 }
 
+/// Get all available language IDs.
+pub fn get_supported_languages() -> Vec<String> {
+    get_languages().keys().cloned().collect()
+}
+
 pub fn get_language(id: &str) -> Result<&'static Language, String> {
     get_languages()
         .get(id)
@@ -40,12 +40,20 @@ pub mod worksheet;
 
 mod actions;
 mod arithmetic;
+mod auto_filter;
 mod cast;
 mod constants;
+mod fill;
+mod find;
 mod functions;
 mod implicit_intersection;
+mod merged_cells;
 mod model;
+mod page_setup;
+mod protection;
+mod sort;
 mod styles;
+mod theme;
 mod units;
 mod user_model;
 mod utils;
@@ -57,11 +65,21 @@ mod test;
 #[cfg(test)]
 pub mod mock_time;
 
+pub use find::{FindLookIn, FindMatch, FindOptions, FindOrder, FindScope};
+pub use language::get_supported_languages;
 pub use locale::get_supported_locales;
 pub use model::get_milliseconds_since_epoch;
 pub use model::FmtSettings;
 pub use model::Model;
+pub use protection::ProtectionError;
+pub use styles::get_builtin_named_styles;
+pub use user_model::diffs;
+pub use user_model::AutoFillMode;
 pub use user_model::BorderArea;
 pub use user_model::ClipboardData;
+pub use user_model::CollaborationServer;
 pub use user_model::UserModel;
+pub use user_model::{
+    ClipboardPayload, CopiedCell, CopiedValue, PasteMode, PasteOperation, PasteOptions,
+};
 pub use utils::get_all_timezones;
@@ -9,6 +9,27 @@ pub struct Locale {
     pub currency: Currency,
 }
 
+impl Locale {
+    /// Separator of the arguments of a function and of the columns of an array in formulas.
+    /// It is ',' unless the decimal separator is a comma, in which case it is ';' (the list separator of those locales).
+    pub fn argument_separator(&self) -> char {
+        if self.numbers.symbols.decimal == "," {
+            ';'
+        } else {
+            ','
+        }
+    }
+
+    /// Separator of the rows of an array in formulas: `{1,2;3,4}` in English, `{1;2\3;4}` in German.
+    pub fn array_row_separator(&self) -> char {
+        if self.numbers.symbols.decimal == "," {
+            '\\'
+        } else {
+            ';'
+        }
+    }
+}
+
 #[derive(Encode, Decode)]
 pub struct Currency {
     pub iso: String,
@@ -20,6 +41,9 @@ pub struct NumbersProperties {
     pub symbols: NumbersSymbols,
     pub decimal_formats: DecimalFormats,
     pub currency_formats: CurrencyFormats,
+    /// Digits used to display numbers if they are not the latin ones, i.e. "٠١٢٣٤٥٦٧٨٩" in Arabic.
+    /// Formulas always use the latin digits and symbols.
+    pub native_digits: Option<String>,
 }
 
 #[derive(Encode, Decode)]
@@ -0,0 +1,324 @@
+use std::fmt::Display;
+
+use crate::{
+    constants::{LAST_COLUMN, LAST_ROW},
+    expressions::utils::{
+        is_valid_column_number, is_valid_row, number_to_column, parse_reference_a1,
+    },
+    model::Model,
+    types::MergedRange,
+};
+
+impl MergedRange {
+    /// Parses a reference like `K7:L10`
+    pub fn parse(reference: &str) -> Result<MergedRange, String> {
+        let error = || format!("Invalid merged range: '{reference}'");
+        let (first, last) = reference.split_once(':').ok_or_else(error)?;
+        let first = parse_reference_a1(first).ok_or_else(error)?;
+        let last = parse_reference_a1(last).ok_or_else(error)?;
+        Ok(MergedRange {
+            first_row: first.row.min(last.row),
+            first_column: first.column.min(last.column),
+            last_row: first.row.max(last.row),
+            last_column: first.column.max(last.column),
+        })
+    }
+
+    /// Returns true if the cell is in the block
+    pub fn contains(&self, row: i32, column: i32) -> bool {
+        (self.first_row..=self.last_row).contains(&row)
+            && (self.first_column..=self.last_column).contains(&column)
+    }
+
+    /// Returns true if `other` is completely inside the block
+    pub fn contains_range(&self, other: &MergedRange) -> bool {
+        self.contains(other.first_row, other.first_column)
+            && self.contains(other.last_row, other.last_column)
+    }
+
+    /// Returns true if both blocks have at least one cell in common
+    pub fn intersects(&self, other: &MergedRange) -> bool {
+        self.first_row <= other.last_row
+            && other.first_row <= self.last_row
+            && self.first_column <= other.last_column
+            && other.first_column <= self.last_column
+    }
+
+    fn is_single_cell(&self) -> bool {
+        self.first_row == self.last_row && self.first_column == self.last_column
+    }
+
+    fn is_valid(&self) -> bool {
+        is_valid_row(self.first_row)
+            && is_valid_row(self.last_row)
+            && is_valid_column_number(self.first_column)
+            && is_valid_column_number(self.last_column)
+            && self.first_row <= self.last_row
+            && self.first_column <= self.last_column
+    }
+}
+
+impl Display for MergedRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let first_column = number_to_column(self.first_column).unwrap_or_default();
+        let last_column = number_to_column(self.last_column).unwrap_or_default();
+        write!(
+            f,
+            "{first_column}{}:{last_column}{}",
+            self.first_row, self.last_row
+        )
+    }
+}
+
+// The functions below compute the new [first, last] interval of a merged block along one axis
+// (rows or columns) after a structural change.
+
+/// Inserting `count` lines at `index`. Returns `None` if the block is pushed off the sheet.
+fn insert_lines((first, last): (i32, i32), index: i32, count: i32, max: i32) -> Option<(i32, i32)> {
+    if index <= first {
+        if first + count > max {
+            return None;
+        }
+        Some((first + count, (last + count).min(max)))
+    } else if index <= last {
+        Some((first, (last + count).min(max)))
+    } else {
+        Some((first, last))
+    }
+}
+
+/// Deleting `count` lines starting at `index`. Returns `None` if all the lines are deleted.
+fn delete_lines((first, last): (i32, i32), index: i32, count: i32) -> Option<(i32, i32)> {
+    let end = index + count - 1;
+    let new_first = if first < index {
+        first
+    } else if first > end {
+        first - count
+    } else {
+        index
+    };
+    let new_last = if last < index {
+        last
+    } else if last > end {
+        last - count
+    } else {
+        index - 1
+    };
+    if new_last < new_first {
+        return None;
+    }
+    Some((new_first, new_last))
+}
+
+/// Moving line `index` to `index + delta`, the lines in between are shifted one position.
+/// Returns `None` if the lines of the block would not be contiguous anymore.
+fn move_line((first, last): (i32, i32), index: i32, delta: i32) -> Option<(i32, i32)> {
+    let target = index + delta;
+    let (low, high) = (index.min(target), index.max(target));
+    let (shift_start, shift_end, shift) = if delta > 0 {
+        (low + 1, high, -1)
+    } else {
+        (low, high - 1, 1)
+    };
+    // Every piece of [first, last] is moved as a whole
+    let mut pieces = vec![
+        (first, last.min(low - 1), 0),
+        (first.max(high + 1), last, 0),
+        (first.max(shift_start), last.min(shift_end), shift),
+    ];
+    if (first..=last).contains(&index) {
+        pieces.push((index, index, delta));
+    }
+    let mut new_first = i32::MAX;
+    let mut new_last = i32::MIN;
+    for (start, end, shift) in pieces {
+        if start <= end {
+            new_first = new_first.min(start + shift);
+            new_last = new_last.max(end + shift);
+        }
+    }
+    if new_last - new_first != last - first {
+        return None;
+    }
+    Some((new_first, new_last))
+}
+
+impl<'a> Model<'a> {
+    /// Returns the blocks of merged cells of the sheet
+    pub fn get_merged_cells(&self, sheet: u32) -> Result<Vec<MergedRange>, String> {
+        Ok(self.workbook.worksheet(sheet)?.merge_cells.clone())
+    }
+
+    /// Returns the block of merged cells that contains the cell, if any
+    pub fn get_merged_range(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<Option<MergedRange>, String> {
+        Ok(self
+            .workbook
+            .worksheet(sheet)?
+            .merge_cells
+            .iter()
+            .find(|range| range.contains(row, column))
+            .copied())
+    }
+
+    /// Merges the cells of `range`. Blocks completely inside `range` are replaced.
+    /// Fails if `range` partially overlaps another block.
+    /// Note that this does not clear the cells of the block.
+    pub(crate) fn merge_range(&mut self, sheet: u32, range: MergedRange) -> Result<(), String> {
+        if !range.is_valid() {
+            return Err(format!("Invalid merged range: '{range}'"));
+        }
+        if range.is_single_cell() {
+            return Err("Cannot merge a single cell".to_string());
+        }
+        let worksheet = self.workbook.worksheet_mut(sheet)?;
+        if worksheet
+            .merge_cells
+            .iter()
+            .any(|other| other.intersects(&range) && !range.contains_range(other))
+        {
+            return Err("Cannot merge a range that partially overlaps merged cells".to_string());
+        }
+        worksheet
+            .merge_cells
+            .retain(|other| !range.contains_range(other));
+        worksheet.merge_cells.push(range);
+        Ok(())
+    }
+
+    /// Unmerges all the blocks that have at least one cell in `range`
+    pub(crate) fn unmerge_range(&mut self, sheet: u32, range: &MergedRange) -> Result<(), String> {
+        self.workbook
+            .worksheet_mut(sheet)?
+            .merge_cells
+            .retain(|other| !other.intersects(range));
+        Ok(())
+    }
+
+    fn update_merged_cells(
+        &mut self,
+        sheet: u32,
+        update: impl Fn(&MergedRange) -> Option<MergedRange>,
+    ) -> Result<(), String> {
+        let worksheet = self.workbook.worksheet_mut(sheet)?;
+        worksheet.merge_cells = worksheet
+            .merge_cells
+            .iter()
+            .filter_map(update)
+            .filter(|range| !range.is_single_cell())
+            .collect();
+        Ok(())
+    }
+
+    fn update_merged_rows(
+        &mut self,
+        sheet: u32,
+        update: impl Fn((i32, i32)) -> Option<(i32, i32)>,
+    ) -> Result<(), String> {
+        self.update_merged_cells(sheet, |range| {
+            let (first_row, last_row) = update((range.first_row, range.last_row))?;
+            Some(MergedRange {
+                first_row,
+                last_row,
+                ..*range
+            })
+        })
+    }
+
+    fn update_merged_columns(
+        &mut self,
+        sheet: u32,
+        update: impl Fn((i32, i32)) -> Option<(i32, i32)>,
+    ) -> Result<(), String> {
+        self.update_merged_cells(sheet, |range| {
+            let (first_column, last_column) = update((range.first_column, range.last_column))?;
+            Some(MergedRange {
+                first_column,
+                last_column,
+                ..*range
+            })
+        })
+    }
+
+    /// Shifts (or expands) the merged blocks after inserting rows
+    pub(crate) fn insert_merged_rows(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        row_count: i32,
+    ) -> Result<(), String> {
+        self.update_merged_rows(sheet, |rows| insert_lines(rows, row, row_count, LAST_ROW))
+    }
+
+    /// Shifts (or shrinks) the merged blocks after deleting rows
+    pub(crate) fn delete_merged_rows(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        row_count: i32,
+    ) -> Result<(), String> {
+        self.update_merged_rows(sheet, |rows| delete_lines(rows, row, row_count))
+    }
+
+    /// Shifts (or expands) the merged blocks after inserting columns
+    pub(crate) fn insert_merged_columns(
+        &mut self,
+        sheet: u32,
+        column: i32,
+        column_count: i32,
+    ) -> Result<(), String> {
+        self.update_merged_columns(sheet, |columns| {
+            insert_lines(columns, column, column_count, LAST_COLUMN)
+        })
+    }
+
+    /// Shifts (or shrinks) the merged blocks after deleting columns
+    pub(crate) fn delete_merged_columns(
+        &mut self,
+        sheet: u32,
+        column: i32,
+        column_count: i32,
+    ) -> Result<(), String> {
+        self.update_merged_columns(sheet, |columns| delete_lines(columns, column, column_count))
+    }
+
+    /// Moves the merged blocks along with a row.
+    /// Fails without changing anything if the move would split a block.
+    pub(crate) fn move_merged_row(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        delta: i32,
+    ) -> Result<(), String> {
+        let worksheet = self.workbook.worksheet(sheet)?;
+        if worksheet
+            .merge_cells
+            .iter()
+            .any(|range| move_line((range.first_row, range.last_row), row, delta).is_none())
+        {
+            return Err("Cannot move part of a merged cell".to_string());
+        }
+        self.update_merged_rows(sheet, |rows| move_line(rows, row, delta))
+    }
+
+    /// Moves the merged blocks along with a column.
+    /// Fails without changing anything if the move would split a block.
+    pub(crate) fn move_merged_column(
+        &mut self,
+        sheet: u32,
+        column: i32,
+        delta: i32,
+    ) -> Result<(), String> {
+        let worksheet = self.workbook.worksheet(sheet)?;
+        if worksheet.merge_cells.iter().any(|range| {
+            move_line((range.first_column, range.last_column), column, delta).is_none()
+        }) {
+            return Err("Cannot move part of a merged cell".to_string());
+        }
+        self.update_merged_columns(sheet, |columns| move_line(columns, column, delta))
+    }
+}
@@ -11,7 +11,10 @@ use crate::{
         lexer::LexerMode,
         parser::{
             move_formula::{move_formula, MoveContext},
-            stringify::{rename_defined_name_in_node, to_localized_string, to_rc_format},
+            stringify::{
+                rename_defined_name_in_node, to_localized_r1c1_string, to_localized_string,
+                to_rc_format,
+            },
             Node, Parser,
         },
         token::{get_error_by_name, Error, OpCompare, OpProduct, OpSum, OpUnary},
@@ -19,13 +22,16 @@ use crate::{
         utils::{self, is_valid_column_number, is_valid_identifier, is_valid_row},
     },
     formatter::{
-        format::{format_number, parse_formatted_number},
-        lexer::is_likely_date_number_format,
+        dates::{
+            convert_date_serial_number, maximum_date_serial_number, minimum_date_serial_number,
+        },
+        format::{format_number, format_number_with_date_system, parse_formatted_number},
+        lexer::{is_date_number_format, is_likely_date_number_format},
     },
     functions::util::compare_values,
     implicit_intersection::implicit_intersection,
     language::{get_default_language, get_language, Language},
-    locale::{get_locale, Locale},
+    locale::{get_default_locale, get_locale, Locale},
     types::*,
     utils as common,
 };
@@ -941,7 +947,10 @@ impl<'a> Model<'a> {
         );
         let mut shared_strings = HashMap::new();
         for (index, s) in workbook.shared_strings.iter().enumerate() {
-            shared_strings.insert(s.to_string(), index);
+            // Rich text strings are never reused for plain text
+            if !workbook.rich_text.contains_key(&(index as u32)) {
+                shared_strings.insert(s.to_string(), index);
+            }
         }
 
         let mut model = Model {
@@ -1265,11 +1274,26 @@ impl<'a> Model<'a> {
         }
     }
 
-    /// Returns the text for the formula in (`sheet`, `row`, `column`) in English if any
+    /// Returns the text for the formula in (`sheet`, `row`, `column`) in R1C1 notation if any.
+    /// Function names, separators and the letters of the references are localized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ironcalc_base::Model;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut model = Model::new_empty("model", "en", "UTC", "en")?;
+    /// model.set_user_input(0, 2, 2, "=SUM(A1:A3, $C$1)".to_string())?;
+    /// let result = model.get_cell_formula_r1c1(0, 2, 2)?;
+    /// assert_eq!(result, Some("=SUM(R[-1]C[-1]:R[1]C[-1],R1C3)".to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
     ///
     /// See also:
-    /// * [Model::get_localized_cell_content()]
-    pub(crate) fn get_english_cell_formula(
+    /// * [Model::get_cell_formula()]
+    /// * [Model::set_user_input_r1c1()]
+    pub fn get_cell_formula_r1c1(
         &self,
         sheet: u32,
         row: i32,
@@ -1285,15 +1309,9 @@ impl<'a> Model<'a> {
                         .ok_or("missing sheet")?
                         .get(formula_index as usize)
                         .ok_or("missing formula")?;
-                    let cell_ref = CellReferenceRC {
-                        sheet: worksheet.get_name(),
-                        row,
-                        column,
-                    };
-                    let language_en = get_default_language();
                     Ok(Some(format!(
                         "={}",
-                        to_localized_string(formula, &cell_ref, self.locale, language_en)
+                        to_localized_r1c1_string(formula, self.locale, self.language)
                     )))
                 }
                 None => Ok(None),
@@ -1302,6 +1320,61 @@ impl<'a> Model<'a> {
         }
     }
 
+    /// Translates a formula in R1C1 notation typed in (`sheet`, `row`, `column`) to A1 notation,
+    /// both in the language and locale of the model. Other values are returned as they are.
+    pub(crate) fn r1c1_input_to_a1(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        value: &str,
+    ) -> Result<String, String> {
+        let formula = match value.strip_prefix('=') {
+            Some(formula) => formula,
+            None => return Ok(value.to_string()),
+        };
+        let cell_reference = CellReferenceRC {
+            sheet: self.workbook.worksheet(sheet)?.get_name(),
+            row,
+            column,
+        };
+        self.parser.set_lexer_mode(LexerMode::R1C1);
+        let node = self.parser.parse(formula, &cell_reference);
+        self.parser.set_lexer_mode(LexerMode::A1);
+        if let Node::ParseErrorKind { message, .. } = &node {
+            return Err(message.clone());
+        }
+        Ok(format!(
+            "={}",
+            to_localized_string(&node, &cell_reference, self.locale, self.language)
+        ))
+    }
+
+    /// Sets a value like [Model::set_user_input()] but formulas are in R1C1 notation,
+    /// i.e. `=SUM(R[-3]C:R[-1]C)` adds the three cells above.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ironcalc_base::Model;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut model = Model::new_empty("model", "de", "UTC", "de")?;
+    /// model.set_user_input_r1c1(0, 3, 1, "=SUMME(Z[-2]S[0]:Z[-1]S[0];1,5)".to_string())?;
+    /// assert_eq!(model.get_cell_formula(0, 3, 1)?, Some("=SUMME(A1:A2;1,5)".to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_user_input_r1c1(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        value: String,
+    ) -> Result<(), String> {
+        let value = self.r1c1_input_to_a1(sheet, row, column, &value)?;
+        self.set_user_input(sheet, row, column, value)
+    }
+
     /// Updates the value of a cell with some text
     /// It does not change the style unless needs to add "quoting"
     ///
@@ -1557,16 +1630,11 @@ impl<'a> Model<'a> {
                     self.set_cell_style(sheet, row, column, &style)?
                 }
             } else {
-                // The list of currencies is '$', '€' and the local currency
-                let mut currencies = vec!["$", "€"];
-                let currency = &self.locale.currency.symbol;
-                if !currencies.iter().any(|e| e == currency) {
-                    currencies.push(currency);
-                }
+                let currencies = self.get_input_currencies();
 
                 //  We try to parse as number
                 if let Ok((v, number_format)) =
-                    parse_formatted_number(&value, &currencies, self.locale)
+                    parse_formatted_number(&value, &currencies, self.locale, self.get_date_system())
                 {
                     if let Some(num_fmt) = number_format {
                         // Should not apply the format in the following cases:
@@ -1585,14 +1653,20 @@ impl<'a> Model<'a> {
                     worksheet.set_cell_with_number(row, column, v, new_style_index)?;
                     return Ok(());
                 }
-                // We try to parse as boolean
-                if let Ok(v) = value.to_lowercase().parse::<bool>() {
+                // We try to parse as boolean (in the language of the model)
+                let upper = value.to_uppercase();
+                let booleans = &self.language.booleans;
+                if upper == booleans.r#true || upper == booleans.r#false {
                     let worksheet = self.workbook.worksheet_mut(sheet)?;
-                    worksheet.set_cell_with_boolean(row, column, v, new_style_index)?;
+                    worksheet.set_cell_with_boolean(
+                        row,
+                        column,
+                        upper == booleans.r#true,
+                        new_style_index,
+                    )?;
                     return Ok(());
                 }
                 // Check is it is error value
-                let upper = value.to_uppercase();
                 let worksheet = self.workbook.worksheet_mut(sheet)?;
                 match get_error_by_name(&upper, self.language) {
                     Some(error) => {
@@ -1607,6 +1681,138 @@ impl<'a> Model<'a> {
         Ok(())
     }
 
+    // The list of currencies the user input understands is '$', '€' and the local currency
+    fn get_input_currencies(&self) -> Vec<&str> {
+        let mut currencies = vec!["$", "€"];
+        let currency = self.locale.currency.symbol.as_str();
+        if !currencies.contains(&currency) {
+            currencies.push(currency);
+        }
+        currencies
+    }
+
+    /// Returns true if `value` typed in a cell would not be read as text, that is, if it needs a
+    /// leading `'` to be kept as text
+    pub(crate) fn input_needs_quoting(&self, value: &str) -> bool {
+        if value.starts_with('=') || value.starts_with('\'') {
+            return true;
+        }
+        let currencies = self.get_input_currencies();
+        if parse_formatted_number(value, &currencies, self.locale, self.get_date_system()).is_ok() {
+            return true;
+        }
+        let upper = value.to_uppercase();
+        let booleans = &self.language.booleans;
+        upper == booleans.r#true
+            || upper == booleans.r#false
+            || get_error_by_name(&upper, self.language).is_some()
+    }
+
+    /// Sets a rich text string in a cell, keeping the style of the cell.
+    /// The value of the cell is the text of all the runs together, formulas see it as plain text.
+    ///
+    /// See also:
+    /// * [Model::get_cell_rich_text()]
+    pub fn set_cell_rich_text(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        runs: &[TextRun],
+    ) -> Result<(), String> {
+        if runs.is_empty() {
+            return Err("Rich text needs at least one run".to_string());
+        }
+        let style_index = self.get_cell_style_index(sheet, row, column)?;
+        let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+        let string_index = self.get_shared_string_index_or_create(&text, Some(runs));
+        self.workbook.worksheet_mut(sheet)?.set_cell_with_string(
+            row,
+            column,
+            string_index,
+            style_index,
+        )
+    }
+
+    /// Returns the index of the shared string `text`, with the rich text `runs` if any, adding
+    /// it if it does not exist. Runs without formatting are stored as plain text.
+    pub(crate) fn get_shared_string_index_or_create(
+        &mut self,
+        text: &str,
+        runs: Option<&[TextRun]>,
+    ) -> i32 {
+        let runs = match runs {
+            Some(runs) if runs.iter().any(|run| run.font.is_some()) => runs,
+            _ => {
+                if let Some(index) = self.shared_strings.get(text) {
+                    return *index as i32;
+                }
+                let index = self.workbook.shared_strings.len();
+                self.workbook.shared_strings.push(text.to_string());
+                self.shared_strings.insert(text.to_string(), index);
+                return index as i32;
+            }
+        };
+        match self
+            .workbook
+            .rich_text
+            .iter()
+            .find(|(_, other)| other.as_slice() == runs)
+        {
+            Some((index, _)) => *index as i32,
+            None => {
+                let index = self.workbook.shared_strings.len() as u32;
+                self.workbook.shared_strings.push(text.to_string());
+                self.workbook.rich_text.insert(index, runs.to_vec());
+                index as i32
+            }
+        }
+    }
+
+    /// Returns the index of the internal formula `formula` (in R1C1 notation) of the sheet,
+    /// adding it if it does not exist
+    pub(crate) fn get_shared_formula_index_or_create(
+        &mut self,
+        sheet: u32,
+        formula: &str,
+    ) -> Result<i32, String> {
+        let worksheet = self.workbook.worksheet_mut(sheet)?;
+        if let Some(index) = worksheet.shared_formulas.iter().position(|f| f == formula) {
+            return Ok(index as i32);
+        }
+        let cell_reference = CellReferenceRC {
+            sheet: worksheet.get_name(),
+            row: 1,
+            column: 1,
+        };
+        // Internal formulas are R1C1 in the default language and locale
+        self.parser.set_locale(get_default_locale());
+        self.parser.set_language(get_default_language());
+        self.parser.set_lexer_mode(LexerMode::R1C1);
+        let node = self.parser.parse(formula, &cell_reference);
+        self.parser.set_lexer_mode(LexerMode::A1);
+        self.parser.set_locale(self.locale);
+        self.parser.set_language(self.language);
+        worksheet.shared_formulas.push(formula.to_string());
+        self.parsed_formulas[sheet as usize].push(node);
+        Ok(worksheet.shared_formulas.len() as i32 - 1)
+    }
+
+    /// Returns the runs of a cell with rich text, or `None` if the cell is not rich text
+    pub fn get_cell_rich_text(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<Option<Vec<TextRun>>, String> {
+        match self.workbook.worksheet(sheet)?.cell(row, column) {
+            Some(Cell::SharedString { si, .. }) => {
+                Ok(self.workbook.rich_text.get(&(*si as u32)).cloned())
+            }
+            _ => Ok(None),
+        }
+    }
+
     fn set_cell_with_formula(
         &mut self,
         sheet: u32,
@@ -1655,28 +1861,10 @@ impl<'a> Model<'a> {
         value: &str,
         style: i32,
     ) -> Result<(), String> {
-        match self.shared_strings.get(value) {
-            Some(string_index) => {
-                self.workbook.worksheet_mut(sheet)?.set_cell_with_string(
-                    row,
-                    column,
-                    *string_index as i32,
-                    style,
-                )?;
-            }
-            None => {
-                let string_index = self.workbook.shared_strings.len();
-                self.workbook.shared_strings.push(value.to_string());
-                self.shared_strings.insert(value.to_string(), string_index);
-                self.workbook.worksheet_mut(sheet)?.set_cell_with_string(
-                    row,
-                    column,
-                    string_index as i32,
-                    style,
-                )?;
-            }
-        }
-        Ok(())
+        let string_index = self.get_shared_string_index_or_create(value, None);
+        self.workbook
+            .worksheet_mut(sheet)?
+            .set_cell_with_string(row, column, string_index, style)
     }
 
     fn set_cell_with_boolean(
@@ -1806,9 +1994,11 @@ impl<'a> Model<'a> {
         match self.workbook.worksheet(sheet_index)?.cell(row, column) {
             Some(cell) => {
                 let format = self.get_style_for_cell(sheet_index, row, column)?.num_fmt;
+                let date_system = self.get_date_system();
                 let formatted_value =
                     cell.formatted_value(&self.workbook.shared_strings, self.language, |value| {
-                        format_number(value, &format, self.locale).text
+                        format_number_with_date_system(value, &format, self.locale, date_system)
+                            .text
                     });
                 Ok(formatted_value)
             }
@@ -2274,7 +2464,9 @@ impl<'a> Model<'a> {
                     // We need to rename the name in every formula:
 
                     // Parse all formulas with the old name
-                    // All internal formulas are R1C1
+                    // All internal formulas are R1C1 in the default language and locale
+                    self.parser.set_locale(get_default_locale());
+                    self.parser.set_language(get_default_language());
                     self.parser.set_lexer_mode(LexerMode::R1C1);
                     let worksheets = &mut self.workbook.worksheets;
                     for worksheet in worksheets {
@@ -2293,6 +2485,8 @@ impl<'a> Model<'a> {
                     }
                     // Se the mode back to A1
                     self.parser.set_lexer_mode(LexerMode::A1);
+                    self.parser.set_locale(self.locale);
+                    self.parser.set_language(self.language);
                 }
                 df.name = new_name.to_string();
                 df.sheet_id = new_sheet_id;
@@ -2397,6 +2591,56 @@ impl<'a> Model<'a> {
         Ok(())
     }
 
+    /// Sets the date system of the model.
+    /// Numbers in cells with a date format are shifted so that they keep showing the same date.
+    /// Times (numbers with a format like `h:mm`) and numbers that are not a valid date in the
+    /// current date system are not changed.
+    pub fn set_date_system(&mut self, date_system: DateSystem) -> Result<(), String> {
+        let old_date_system = self.workbook.settings.date_system;
+        if old_date_system == date_system {
+            return Ok(());
+        }
+        for CellReferenceIndex { sheet, row, column } in self.get_date_cells()? {
+            let worksheet = self.workbook.worksheet_mut(sheet)?;
+            if let Some(Cell::NumberCell { v, .. }) = worksheet.cell_mut(row, column) {
+                *v = convert_date_serial_number(*v, old_date_system, date_system);
+            }
+        }
+        self.workbook.settings.date_system = date_system;
+        self.evaluate();
+        Ok(())
+    }
+
+    /// Returns the cells holding a date of the current date system.
+    /// Those are the cells that change when the date system is set.
+    pub(crate) fn get_date_cells(&self) -> Result<Vec<CellReferenceIndex>, String> {
+        let date_system = self.workbook.settings.date_system;
+        let minimum = minimum_date_serial_number(date_system) as f64;
+        // The last date ends right before the next day
+        let maximum = (maximum_date_serial_number(date_system) + 1) as f64;
+        let styles = &self.workbook.styles;
+        let mut cells = Vec::new();
+        for (sheet, worksheet) in self.workbook.worksheets.iter().enumerate() {
+            for (row, row_data) in &worksheet.sheet_data {
+                for (column, cell) in row_data {
+                    if let Cell::NumberCell { v, s } = cell {
+                        if *v >= minimum
+                            && *v < maximum
+                            && is_date_number_format(&styles.get_style(*s)?.num_fmt)
+                        {
+                            cells.push(CellReferenceIndex {
+                                sheet: sheet as u32,
+                                row: *row,
+                                column: *column,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(cells)
+    }
+
     /// Sets the language
     pub fn set_language(&mut self, language_id: &str) -> Result<(), String> {
         let language = match get_language(language_id) {
@@ -2418,6 +2662,31 @@ impl<'a> Model<'a> {
         self.workbook.settings.tz.clone()
     }
 
+    /// Gets the date system of the model
+    pub fn get_date_system(&self) -> DateSystem {
+        self.workbook.settings.date_system
+    }
+
+    /// Sets the user defined lists that autofill extends.
+    /// Each list needs at least two items and items can't be empty.
+    pub fn set_custom_lists(&mut self, lists: Vec<Vec<String>>) -> Result<(), String> {
+        for list in &lists {
+            if list.len() < 2 {
+                return Err("A custom list needs at least two items".to_string());
+            }
+            if list.iter().any(|item| item.trim().is_empty()) {
+                return Err("Custom list items can't be empty".to_string());
+            }
+        }
+        self.workbook.settings.custom_lists = lists;
+        Ok(())
+    }
+
+    /// Gets the user defined lists that autofill extends
+    pub fn get_custom_lists(&self) -> &Vec<Vec<String>> {
+        &self.workbook.settings.custom_lists
+    }
+
     /// Gets the locale of the model
     pub fn get_locale(&self) -> String {
         self.workbook.settings.locale.clone()
@@ -8,7 +8,9 @@ use crate::{
     expressions::{
         lexer::LexerMode,
         parser::{
-            stringify::{rename_sheet_in_node, to_localized_string, to_rc_format},
+            stringify::{
+                rename_sheet_in_node, rename_tables_in_node, to_localized_string, to_rc_format,
+            },
             Parser,
         },
         types::CellReferenceRC,
@@ -16,9 +18,10 @@ use crate::{
     language::{get_default_language, get_language},
     locale::{get_default_locale, get_locale},
     model::{get_milliseconds_since_epoch, Model, ParsedDefinedName},
+    page_setup::{is_print_defined_name, split_references},
     types::{
-        DefinedName, Metadata, SheetState, Workbook, WorkbookSettings, WorkbookView, Worksheet,
-        WorksheetView,
+        DateSystem, DefinedName, Metadata, SheetState, Table, Workbook, WorkbookSettings,
+        WorkbookView, Worksheet, WorksheetView,
     },
     utils::ParsedReference,
 };
@@ -37,6 +40,48 @@ fn is_valid_sheet_name(name: &str) -> bool {
     !name.is_empty() && name.chars().count() <= 31 && !name.contains(&invalid[..])
 }
 
+/// Returns the index of the sheet at `index` after the sheet at `from` is moved to `to`
+pub(crate) fn get_moved_sheet_index(index: u32, from: u32, to: u32) -> u32 {
+    if index == from {
+        to
+    } else if from < to && index > from && index <= to {
+        index - 1
+    } else if to < from && index >= to && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+// Returns the formula of the defined name with the references to `sheet_index` renamed.
+// The parser must be in A1 mode and in the default language and locale.
+fn rename_sheet_in_defined_name(
+    parser: &mut Parser,
+    defined_name: &DefinedName,
+    sheet_index: u32,
+    new_name: &str,
+    cell_reference: &CellReferenceRC,
+) -> String {
+    // The print area and print titles can be lists of references
+    let references = if is_print_defined_name(&defined_name.name) {
+        split_references(&defined_name.formula)
+    } else {
+        vec![defined_name.formula.as_str()]
+    };
+    let mut formulas = Vec::new();
+    for reference in references {
+        let mut t = parser.parse(reference, cell_reference);
+        rename_sheet_in_node(&mut t, sheet_index, new_name);
+        formulas.push(to_localized_string(
+            &t,
+            cell_reference,
+            get_default_locale(),
+            get_default_language(),
+        ));
+    }
+    formulas.join(",")
+}
+
 impl<'a> Model<'a> {
     /// Creates a new worksheet. Note that it does not check if the name or the sheet_id exists
     fn new_empty_worksheet(name: &str, sheet_id: u32, view_ids: &[&u32]) -> Worksheet {
@@ -69,6 +114,12 @@ impl<'a> Model<'a> {
             frozen_rows: 0,
             show_grid_lines: true,
             views,
+            auto_filter: None,
+            summary_below: true,
+            summary_right: true,
+            page_setup: Default::default(),
+            protection: None,
+            opaque_parts: Default::default(),
         }
     }
 
@@ -157,6 +208,7 @@ impl<'a> Model<'a> {
         let defined_names = self.workbook.get_defined_names_with_scope();
         self.parser
             .set_worksheets_and_names(self.workbook.get_worksheet_names(), defined_names);
+        self.parser.set_tables(self.workbook.tables.clone());
         self.parsed_formulas = vec![];
         self.parse_formulas();
         self.parsed_defined_names = HashMap::new();
@@ -279,7 +331,10 @@ impl<'a> Model<'a> {
         let old_name = self.workbook.worksheet(sheet_index)?.get_name();
 
         // Parse all formulas with the old name
-        // All internal formulas are R1C1
+        // All internal formulas are R1C1 in the default language and locale
+        let (locale, language) = (self.locale, self.language);
+        self.parser.set_locale(get_default_locale());
+        self.parser.set_language(get_default_language());
         self.parser.set_lexer_mode(LexerMode::R1C1);
 
         for worksheet in &mut self.workbook.worksheets {
@@ -301,7 +356,7 @@ impl<'a> Model<'a> {
         // Set the mode back to A1
         self.parser.set_lexer_mode(LexerMode::A1);
 
-        // We reparse all the defined names formulas
+        // We reparse all the defined names formulas (also stored in the default language and locale)
         let mut defined_names = Vec::new();
         // Defined names do not have a context, we can use anything
         let cell_reference = &CellReferenceRC {
@@ -309,10 +364,14 @@ impl<'a> Model<'a> {
             row: 1,
             column: 1,
         };
-        for defined_name in &mut self.workbook.defined_names {
-            let mut t = self.parser.parse(&defined_name.formula, cell_reference);
-            rename_sheet_in_node(&mut t, sheet_index, new_name);
-            let formula = to_localized_string(&t, cell_reference, self.locale, self.language);
+        for defined_name in &self.workbook.defined_names {
+            let formula = rename_sheet_in_defined_name(
+                &mut self.parser,
+                defined_name,
+                sheet_index,
+                new_name,
+                cell_reference,
+            );
             defined_names.push(DefinedName {
                 name: defined_name.name.clone(),
                 formula,
@@ -320,6 +379,8 @@ impl<'a> Model<'a> {
             });
         }
         self.workbook.defined_names = defined_names;
+        self.parser.set_locale(locale);
+        self.parser.set_language(language);
 
         // Update the name of the worksheet
         self.workbook.worksheet_mut(sheet_index)?.set_name(new_name);
@@ -327,6 +388,140 @@ impl<'a> Model<'a> {
         Ok(())
     }
 
+    /// Inserts a copy of a sheet right after it, with all its cells, styles, merged cells,
+    /// comments, tables and local defined names. Returns the index of the new sheet.
+    ///
+    /// References in the copied formulas to the sheet itself point to the new sheet.
+    /// Tables in the new sheet get new unique names.
+    /// Drawings and pictures kept from an xlsx file are not copied.
+    /// It can fail if:
+    ///   * The sheet does not exist
+    ///   * The new sheet name already exists or is invalid
+    pub fn duplicate_sheet(&mut self, sheet_index: u32, new_name: &str) -> Result<u32, String> {
+        if !is_valid_sheet_name(new_name) {
+            return Err(format!("Invalid name for a sheet: '{new_name}'."));
+        }
+        if self.get_sheet_index_by_name(new_name).is_some() {
+            return Err(format!("Sheet already exists: '{new_name}'."));
+        }
+        let worksheet = self.workbook.worksheet(sheet_index)?;
+        let old_name = worksheet.get_name();
+        let old_sheet_id = worksheet.sheet_id;
+        let mut new_worksheet = worksheet.clone();
+        new_worksheet.set_name(new_name);
+        new_worksheet.sheet_id = self.get_new_sheet_id();
+        // The parts of the xlsx file (drawings, pictures,...) belong to the original sheet
+        new_worksheet.opaque_parts = Default::default();
+
+        // The tables of the sheet are copied with new names
+        let mut tables: Vec<Table> = self
+            .workbook
+            .tables
+            .values()
+            .filter(|table| table.sheet_name == old_name)
+            .cloned()
+            .collect();
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut table_names = Vec::new();
+        for mut table in tables {
+            let name = self.get_new_table_name(&table.name);
+            table_names.push((table.name.clone(), name.clone()));
+            table.display_name = name.clone();
+            table.name = name.clone();
+            table.sheet_name = new_name.to_string();
+            self.workbook.tables.insert(name, table);
+        }
+
+        // All internal formulas are R1C1 in the default language and locale
+        let (locale, language) = (self.locale, self.language);
+        self.parser.set_locale(get_default_locale());
+        self.parser.set_language(get_default_language());
+        self.parser.set_lexer_mode(LexerMode::R1C1);
+        let cell_reference = &CellReferenceRC {
+            sheet: old_name.clone(),
+            row: 1,
+            column: 1,
+        };
+        let mut formulas = Vec::new();
+        for formula in &new_worksheet.shared_formulas {
+            let mut t = self.parser.parse(formula, cell_reference);
+            rename_sheet_in_node(&mut t, sheet_index, new_name);
+            rename_tables_in_node(&mut t, &table_names);
+            formulas.push(to_rc_format(&t));
+        }
+        new_worksheet.shared_formulas = formulas;
+        self.parser.set_lexer_mode(LexerMode::A1);
+
+        let mut defined_names = Vec::new();
+        for defined_name in &self.workbook.defined_names {
+            if defined_name.sheet_id == Some(old_sheet_id) {
+                let formula = rename_sheet_in_defined_name(
+                    &mut self.parser,
+                    defined_name,
+                    sheet_index,
+                    new_name,
+                    cell_reference,
+                );
+                defined_names.push(DefinedName {
+                    name: defined_name.name.clone(),
+                    formula,
+                    sheet_id: Some(new_worksheet.sheet_id),
+                });
+            }
+        }
+        self.parser.set_locale(locale);
+        self.parser.set_language(language);
+        self.workbook.defined_names.extend(defined_names);
+
+        let new_index = sheet_index + 1;
+        self.workbook
+            .worksheets
+            .insert(new_index as usize, new_worksheet);
+        self.reset_parsed_structures();
+        Ok(new_index)
+    }
+
+    // Returns an unused table name of the form `{name}_{n}`
+    fn get_new_table_name(&self, name: &str) -> String {
+        let is_used = |candidate: &str| {
+            let candidate = candidate.to_lowercase();
+            self.workbook
+                .tables
+                .keys()
+                .any(|key| key.to_lowercase() == candidate)
+                || self
+                    .workbook
+                    .defined_names
+                    .iter()
+                    .any(|defined_name| defined_name.name.to_lowercase() == candidate)
+        };
+        let mut index = 2;
+        while is_used(&format!("{name}_{index}")) {
+            index += 1;
+        }
+        format!("{name}_{index}")
+    }
+
+    /// Moves the sheet at index `from` to index `to`, shifting the sheets in between.
+    /// The selected sheet of every view follows the move.
+    pub fn move_sheet(&mut self, from: u32, to: u32) -> Result<(), String> {
+        let sheet_count = self.workbook.worksheets.len() as u32;
+        if from >= sheet_count || to >= sheet_count {
+            return Err("Sheet index out of range".to_string());
+        }
+        if from == to {
+            return Ok(());
+        }
+        let worksheet = self.workbook.worksheets.remove(from as usize);
+        self.workbook.worksheets.insert(to as usize, worksheet);
+        for view in self.workbook.views.values_mut() {
+            view.sheet = get_moved_sheet_index(view.sheet, from, to);
+        }
+        // Formulas reference sheets by name, parsing them again updates the sheet indices
+        self.reset_parsed_structures();
+        Ok(())
+    }
+
     /// Deletes a sheet by index. Fails if:
     ///   * The sheet does not exists
     ///   * It is the last sheet
@@ -427,6 +622,7 @@ impl<'a> Model<'a> {
         // String versions of the locale are added here to simplify the serialize/deserialize logic
         let workbook = Workbook {
             shared_strings: vec![],
+            rich_text: HashMap::new(),
             defined_names: vec![],
             worksheets: vec![Model::new_empty_worksheet(&sheet_name, 1, &[&0])],
             styles: Default::default(),
@@ -434,6 +630,8 @@ impl<'a> Model<'a> {
             settings: WorkbookSettings {
                 tz: timezone.to_string(),
                 locale: locale_id.to_string(),
+                date_system: DateSystem::Excel1900,
+                custom_lists: vec![],
             },
             metadata: Metadata {
                 application: APPLICATION.to_string(),
@@ -445,6 +643,9 @@ impl<'a> Model<'a> {
             },
             tables: HashMap::new(),
             views,
+            protection: None,
+            opaque_parts: Default::default(),
+            theme: Default::default(),
         };
         let parsed_formulas = Vec::new();
         let worksheets = &workbook.worksheets;
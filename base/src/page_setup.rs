@@ -0,0 +1,200 @@
+use crate::{
+    constants::{LAST_COLUMN, LAST_ROW},
+    expressions::{
+        types::Area,
+        utils::{
+            column_to_number, is_valid_column_number, is_valid_row, number_to_column, quote_name,
+        },
+    },
+    model::Model,
+    types::{DefinedName, PageSetup, PrintTitles},
+};
+
+// The print area and print titles are stored in Excel as defined names local to the sheet
+pub(crate) const PRINT_AREA: &str = "_xlnm.Print_Area";
+pub(crate) const PRINT_TITLES: &str = "_xlnm.Print_Titles";
+
+/// Checks that the page setup values are in the ranges Excel accepts
+pub(crate) fn validate_page_setup(page_setup: &PageSetup) -> Result<(), String> {
+    if !(10..=400).contains(&page_setup.scale) {
+        return Err(format!("Invalid scale: {}", page_setup.scale));
+    }
+    if page_setup.paper_size == 0 {
+        return Err("Invalid paper size: 0".to_string());
+    }
+    let margins = &page_setup.margins;
+    for margin in [
+        margins.left,
+        margins.right,
+        margins.top,
+        margins.bottom,
+        margins.header,
+        margins.footer,
+    ] {
+        if !margin.is_finite() || margin < 0.0 {
+            return Err(format!("Invalid margin: {margin}"));
+        }
+    }
+    for row in &page_setup.row_breaks {
+        if !is_valid_row(*row) {
+            return Err(format!("Invalid page break row: {row}"));
+        }
+    }
+    for column in &page_setup.column_breaks {
+        if !is_valid_column_number(*column) {
+            return Err(format!("Invalid page break column: {column}"));
+        }
+    }
+    Ok(())
+}
+
+/// Returns true for the defined names of the print area and print titles
+pub(crate) fn is_print_defined_name(name: &str) -> bool {
+    name.eq_ignore_ascii_case(PRINT_AREA) || name.eq_ignore_ascii_case(PRINT_TITLES)
+}
+
+/// Splits a list of references by commas that are not in a quoted sheet name
+pub(crate) fn split_references(formula: &str) -> Vec<&str> {
+    let mut references = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (index, c) in formula.char_indices() {
+        match c {
+            '\'' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                references.push(&formula[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    references.push(&formula[start..]);
+    references
+}
+
+/// Parses a print titles formula like `Sheet1!$A:$B,Sheet1!$1:$2`
+pub(crate) fn parse_print_titles(formula: &str) -> PrintTitles {
+    let mut print_titles = PrintTitles::default();
+    for reference in split_references(formula) {
+        let range = match reference.rsplit_once('!') {
+            Some((_, range)) => range,
+            None => reference,
+        };
+        let range = range.replace('$', "");
+        let (first, last) = match range.split_once(':') {
+            Some((first, last)) => (first.trim().to_string(), last.trim().to_string()),
+            None => continue,
+        };
+        if let (Ok(first), Ok(last)) = (first.parse::<i32>(), last.parse::<i32>()) {
+            print_titles.rows = Some((first, last));
+        } else if let (Ok(first), Ok(last)) = (column_to_number(&first), column_to_number(&last)) {
+            print_titles.columns = Some((first, last));
+        }
+    }
+    print_titles
+}
+
+impl<'a> Model<'a> {
+    /// Returns the formula of the defined name `name` local to the sheet
+    pub(crate) fn get_local_defined_name(
+        &self,
+        sheet: u32,
+        name: &str,
+    ) -> Result<Option<String>, String> {
+        let sheet_id = self.workbook.worksheet(sheet)?.sheet_id;
+        Ok(self
+            .workbook
+            .defined_names
+            .iter()
+            .find(|df| df.sheet_id == Some(sheet_id) && df.name.eq_ignore_ascii_case(name))
+            .map(|df| df.formula.clone()))
+    }
+
+    /// Sets (or deletes if `formula` is `None`) the defined name `name` local to the sheet.
+    /// The formula is not validated.
+    pub(crate) fn set_local_defined_name(
+        &mut self,
+        sheet: u32,
+        name: &str,
+        formula: Option<String>,
+    ) -> Result<(), String> {
+        let sheet_id = self.workbook.worksheet(sheet)?.sheet_id;
+        let defined_names = &mut self.workbook.defined_names;
+        let index = defined_names
+            .iter()
+            .position(|df| df.sheet_id == Some(sheet_id) && df.name.eq_ignore_ascii_case(name));
+        match (index, formula) {
+            (Some(index), Some(formula)) => defined_names[index].formula = formula,
+            (Some(index), None) => {
+                defined_names.remove(index);
+            }
+            (None, Some(formula)) => defined_names.push(DefinedName {
+                name: name.to_string(),
+                formula,
+                sheet_id: Some(sheet_id),
+            }),
+            (None, None) => return Ok(()),
+        }
+        self.reset_parsed_structures();
+        Ok(())
+    }
+
+    /// Returns the print area of the sheet, i.e. `Sheet1!$A$1:$F$40`
+    pub fn get_print_area(&self, sheet: u32) -> Result<Option<String>, String> {
+        self.get_local_defined_name(sheet, PRINT_AREA)
+    }
+
+    /// Returns the formula of the print area `area`
+    pub(crate) fn get_print_area_formula(&self, area: &Area) -> Result<String, String> {
+        let sheet_name = quote_name(&self.workbook.worksheet(area.sheet)?.name);
+        let last_row = area.row + area.height - 1;
+        if area.height < 1 || !is_valid_row(area.row) || !is_valid_row(last_row) {
+            return Err("Invalid rows in print area".to_string());
+        }
+        let first_column = number_to_column(area.column);
+        let last_column = number_to_column(area.column + area.width - 1);
+        match (first_column, last_column) {
+            (Some(first_column), Some(last_column)) if area.width > 0 => Ok(format!(
+                "{sheet_name}!${first_column}${}:${last_column}${last_row}",
+                area.row
+            )),
+            _ => Err("Invalid columns in print area".to_string()),
+        }
+    }
+
+    /// Returns the rows and columns printed on every page
+    pub fn get_print_titles(&self, sheet: u32) -> Result<PrintTitles, String> {
+        Ok(match self.get_local_defined_name(sheet, PRINT_TITLES)? {
+            Some(formula) => parse_print_titles(&formula),
+            None => PrintTitles::default(),
+        })
+    }
+
+    /// Returns the formula of the print titles, `None` if there are no print titles
+    pub(crate) fn get_print_titles_formula(
+        &self,
+        sheet: u32,
+        print_titles: &PrintTitles,
+    ) -> Result<Option<String>, String> {
+        let sheet_name = quote_name(&self.workbook.worksheet(sheet)?.name);
+        let mut references = Vec::new();
+        if let Some((first, last)) = print_titles.columns {
+            if first > last || first < 1 || last > LAST_COLUMN {
+                return Err("Invalid print title columns".to_string());
+            }
+            let first = number_to_column(first).ok_or("Invalid column")?;
+            let last = number_to_column(last).ok_or("Invalid column")?;
+            references.push(format!("{sheet_name}!${first}:${last}"));
+        }
+        if let Some((first, last)) = print_titles.rows {
+            if first > last || first < 1 || last > LAST_ROW {
+                return Err("Invalid print title rows".to_string());
+            }
+            references.push(format!("{sheet_name}!${first}:${last}"));
+        }
+        if references.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(references.join(",")))
+    }
+}
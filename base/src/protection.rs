@@ -0,0 +1,257 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::{
+    expressions::types::Area,
+    model::Model,
+    types::{ProtectionPassword, SheetProtection},
+};
+
+/// Error of an action that the protection of a sheet or of the workbook does not allow.
+///
+/// The methods of the models fail with its message, [ProtectionError::from_message] tells it
+/// apart from other errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectionError {
+    /// The sheet is protected
+    ProtectedSheet,
+    /// The workbook structure is protected
+    ProtectedWorkbook,
+}
+
+impl ProtectionError {
+    /// Returns the protection error with the message `message`, if any
+    pub fn from_message(message: &str) -> Option<ProtectionError> {
+        [
+            ProtectionError::ProtectedSheet,
+            ProtectionError::ProtectedWorkbook,
+        ]
+        .into_iter()
+        .find(|error| error.to_string() == message)
+    }
+}
+
+impl fmt::Display for ProtectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtectionError::ProtectedSheet => write!(f, "The sheet is protected"),
+            ProtectionError::ProtectedWorkbook => write!(f, "The workbook structure is protected"),
+        }
+    }
+}
+
+impl std::error::Error for ProtectionError {}
+
+impl From<ProtectionError> for String {
+    fn from(error: ProtectionError) -> String {
+        error.to_string()
+    }
+}
+
+// Excel uses SHA-512 with a random 16 bytes salt and 100000 iterations
+const ALGORITHM_NAME: &str = "SHA-512";
+const SALT_LENGTH: usize = 16;
+const SPIN_COUNT: u32 = 100_000;
+
+// ECMA-376-1:2016 section 18.2.29 (algorithmName, hashValue, saltValue and spinCount).
+// The first hash is computed from the salt followed by the UTF-16LE password, each iteration
+// hashes the previous hash followed by the iteration number as a 32 bit little endian integer.
+fn iterate_hash<D: Digest>(password: &str, salt: &[u8], spin_count: u32) -> Vec<u8> {
+    let mut hasher = D::new();
+    hasher.update(salt);
+    for unit in password.encode_utf16() {
+        hasher.update(unit.to_le_bytes());
+    }
+    let mut hash = hasher.finalize().to_vec();
+    for iteration in 0..spin_count {
+        let mut hasher = D::new();
+        hasher.update(&hash);
+        hasher.update(iteration.to_le_bytes());
+        hash = hasher.finalize().to_vec();
+    }
+    hash
+}
+
+fn hash_password(
+    algorithm_name: &str,
+    password: &str,
+    salt: &[u8],
+    spin_count: u32,
+) -> Result<Vec<u8>, String> {
+    match algorithm_name {
+        "SHA-512" => Ok(iterate_hash::<Sha512>(password, salt, spin_count)),
+        "SHA-384" => Ok(iterate_hash::<Sha384>(password, salt, spin_count)),
+        "SHA-256" => Ok(iterate_hash::<Sha256>(password, salt, spin_count)),
+        _ => Err(format!("Unsupported hash algorithm: '{algorithm_name}'")),
+    }
+}
+
+// The 16 bit hash of the `password` attribute (ECMA-376-4:2016 section 14.7.1)
+fn legacy_hash(password: &str) -> String {
+    let rotate = |hash: u16| ((hash >> 14) & 0x01) | ((hash << 1) & 0x7fff);
+    let bytes: Vec<u16> = password.chars().map(|c| (c as u32 & 0xff) as u16).collect();
+    let mut hash: u16 = 0;
+    for byte in bytes.iter().rev() {
+        hash = rotate(hash) ^ byte;
+    }
+    hash = rotate(hash) ^ (bytes.len() as u16) ^ 0xce4b;
+    format!("{hash:04X}")
+}
+
+impl ProtectionPassword {
+    /// Hashes the password the way Excel does, with a random salt
+    pub fn new(password: &str) -> Result<ProtectionPassword, String> {
+        let mut salt = [0u8; SALT_LENGTH];
+        getrandom::getrandom(&mut salt).map_err(|e| format!("Could not create a salt: {e}"))?;
+        Ok(ProtectionPassword::with_salt(password, &salt))
+    }
+
+    pub(crate) fn with_salt(password: &str, salt: &[u8]) -> ProtectionPassword {
+        // SHA-512 is always supported
+        let hash = hash_password(ALGORITHM_NAME, password, salt, SPIN_COUNT).unwrap_or_default();
+        ProtectionPassword::Hashed {
+            algorithm_name: ALGORITHM_NAME.to_string(),
+            hash_value: STANDARD.encode(hash),
+            salt_value: STANDARD.encode(salt),
+            spin_count: SPIN_COUNT,
+        }
+    }
+
+    /// Returns true if `password` matches the hash
+    pub fn verify(&self, password: &str) -> Result<bool, String> {
+        match self {
+            ProtectionPassword::Legacy(hash) => {
+                Ok(hash.eq_ignore_ascii_case(&legacy_hash(password)))
+            }
+            ProtectionPassword::Hashed {
+                algorithm_name,
+                hash_value,
+                salt_value,
+                spin_count,
+            } => {
+                let salt = STANDARD
+                    .decode(salt_value)
+                    .map_err(|_| format!("Invalid salt value: '{salt_value}'"))?;
+                let hash = hash_password(algorithm_name, password, &salt, *spin_count)?;
+                Ok(STANDARD.encode(hash) == *hash_value)
+            }
+        }
+    }
+}
+
+// Checks the password of a protected sheet or workbook.
+// A protection without a password can be removed with any password.
+pub(crate) fn check_password(
+    protection_password: &Option<ProtectionPassword>,
+    password: Option<&str>,
+) -> Result<(), String> {
+    if let Some(protection_password) = protection_password {
+        if !protection_password.verify(password.unwrap_or(""))? {
+            return Err("The password you supplied is not correct".to_string());
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Model<'a> {
+    /// Returns the protection of the sheet, if the sheet is protected
+    pub fn get_sheet_protection(&self, sheet: u32) -> Result<Option<&SheetProtection>, String> {
+        Ok(self.workbook.worksheet(sheet)?.protection.as_ref())
+    }
+
+    /// Returns true if the workbook structure is protected
+    pub fn is_workbook_structure_locked(&self) -> bool {
+        self.workbook
+            .protection
+            .as_ref()
+            .is_some_and(|protection| protection.lock_structure)
+    }
+
+    // Returns true if the style has the locked protection flag (the default)
+    fn is_style_locked(&self, style_index: i32) -> bool {
+        self.workbook
+            .styles
+            .cell_xfs
+            .get(style_index as usize)
+            .and_then(|cell_xf| cell_xf.protection.as_ref())
+            .is_none_or(|protection| protection.locked)
+    }
+
+    /// Returns true if the cell formula is hidden by the sheet protection
+    pub fn is_cell_formula_hidden(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<bool, String> {
+        if self.workbook.worksheet(sheet)?.protection.is_none() {
+            return Ok(false);
+        }
+        let style_index = self.get_cell_style_index(sheet, row, column)?;
+        Ok(self
+            .workbook
+            .styles
+            .cell_xfs
+            .get(style_index as usize)
+            .and_then(|cell_xf| cell_xf.protection.as_ref())
+            .is_some_and(|protection| protection.hidden))
+    }
+
+    /// Returns true if the sheet is protected and at least one cell in the area is locked
+    pub fn is_area_locked(&self, area: &Area) -> Result<bool, String> {
+        let worksheet = self.workbook.worksheet(area.sheet)?;
+        if worksheet.protection.is_none() {
+            return Ok(false);
+        }
+        let first_row = area.row;
+        let last_row = area.row + area.height - 1;
+        let first_column = area.column;
+        let last_column = area.column + area.width - 1;
+        let row_styles: HashMap<i32, i32> = worksheet
+            .rows
+            .iter()
+            .filter(|r| r.custom_format && first_row <= r.r && r.r <= last_row)
+            .map(|r| (r.r, r.s))
+            .collect();
+        // Columns whose empty cells are locked in rows without a style
+        let mut locked_columns = Vec::new();
+        for column in first_column..=last_column {
+            let style_index = worksheet.get_column_style(column)?.unwrap_or(0);
+            if self.is_style_locked(style_index) {
+                locked_columns.push(column);
+            }
+        }
+        for row in first_row..=last_row {
+            let row_data = worksheet.sheet_data.get(&row);
+            let mut columns_with_cells = HashSet::new();
+            if let Some(row_data) = row_data {
+                for (column, cell) in row_data {
+                    if first_column <= *column && *column <= last_column {
+                        if self.is_style_locked(cell.get_style()) {
+                            return Ok(true);
+                        }
+                        columns_with_cells.insert(*column);
+                    }
+                }
+            }
+            if columns_with_cells.len() as i32 == area.width {
+                continue;
+            }
+            // The row has empty cells in the area
+            if let Some(style_index) = row_styles.get(&row) {
+                if self.is_style_locked(*style_index) {
+                    return Ok(true);
+                }
+            } else if locked_columns
+                .iter()
+                .any(|column| !columns_with_cells.contains(column))
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
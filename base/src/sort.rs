@@ -0,0 +1,224 @@
+use std::cmp::Ordering;
+
+use crate::{
+    cell::CellValue,
+    expressions::{
+        types::Area,
+        utils::{is_valid_column_number, is_valid_row},
+    },
+    model::Model,
+    types::{Cell, SortKey},
+};
+
+// Sorting a range moves the cells (with their styles) to their new rows. Because formulas are stored
+// in R1C1 notation, moving a cell "rewrites" its relative references the same way copy/paste would do.
+// That is exactly what Excel does.
+
+// A letter that sorts after 'z' but before any non latin letter
+const AFTER_Z: char = '\u{10FFF0}';
+
+/// Base letters of latin letters with diacritics
+fn fold_letter(c: char) -> Option<&'static str> {
+    let s = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(s)
+}
+
+/// Some languages treat letters with diacritics as separate letters of the alphabet.
+/// Returns the letter they are sorted after and their position.
+fn tailored_letter(c: char, language: &str) -> Option<(char, u32)> {
+    match (language, c) {
+        ("sv" | "fi", 'å') => Some(('z', 1)),
+        ("sv" | "fi", 'ä' | 'æ') => Some(('z', 2)),
+        ("sv" | "fi", 'ö' | 'ø') => Some(('z', 3)),
+        ("da" | "nb" | "nn" | "no", 'æ' | 'ä') => Some(('z', 1)),
+        ("da" | "nb" | "nn" | "no", 'ø' | 'ö') => Some(('z', 2)),
+        ("da" | "nb" | "nn" | "no", 'å') => Some(('z', 3)),
+        ("es", 'ñ') => Some(('n', 1)),
+        _ => None,
+    }
+}
+
+/// Returns a key to compare texts in the given locale.
+/// Texts are compared by their letters ignoring case and accents, then by their accents.
+/// Letters sorted after the base letter in the locale alphabet are respected (i.e. 'ñ' in Spanish).
+pub(crate) fn collation_key(text: &str, locale_id: &str) -> (Vec<char>, String) {
+    let language = locale_id.split('-').next().unwrap_or_default();
+    let lower = text.to_lowercase();
+    let mut primary = Vec::new();
+    for c in lower.chars() {
+        if let Some((base, position)) = tailored_letter(c, language) {
+            primary.push(base);
+            primary.push(char::from_u32(AFTER_Z as u32 + position).unwrap_or(AFTER_Z));
+        } else if let Some(base) = fold_letter(c) {
+            primary.extend(base.chars());
+        } else {
+            primary.push(c);
+        }
+    }
+    (primary, lower)
+}
+
+// Ascending order in Excel is:
+// numbers < text < logical values < errors. Blank cells are always last.
+#[derive(PartialEq)]
+enum SortValue {
+    Number(f64),
+    Text((Vec<char>, String)),
+    Boolean(bool),
+    Error,
+    Blank,
+}
+
+impl SortValue {
+    fn rank(&self) -> u8 {
+        match self {
+            SortValue::Number(_) => 0,
+            SortValue::Text(_) => 1,
+            SortValue::Boolean(_) => 2,
+            SortValue::Error => 3,
+            SortValue::Blank => 4,
+        }
+    }
+
+    fn compare(&self, other: &SortValue, ascending: bool) -> Ordering {
+        let ordering = match (self, other) {
+            (SortValue::Blank, SortValue::Blank) => return Ordering::Equal,
+            (SortValue::Blank, _) => return Ordering::Greater,
+            (_, SortValue::Blank) => return Ordering::Less,
+            (SortValue::Number(a), SortValue::Number(b)) => a.total_cmp(b),
+            (SortValue::Text(a), SortValue::Text(b)) => a.cmp(b),
+            (SortValue::Boolean(a), SortValue::Boolean(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        };
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+}
+
+impl<'a> Model<'a> {
+    fn get_sort_value(&self, cell: Option<&Cell>, locale_id: &str) -> SortValue {
+        let cell = match cell {
+            Some(cell) => cell,
+            None => return SortValue::Blank,
+        };
+        if matches!(cell, Cell::ErrorCell { .. } | Cell::CellFormulaError { .. }) {
+            return SortValue::Error;
+        }
+        match cell.value(&self.workbook.shared_strings, self.language) {
+            CellValue::None => SortValue::Blank,
+            CellValue::String(value) if value.is_empty() => SortValue::Blank,
+            CellValue::Number(value) => SortValue::Number(value),
+            CellValue::Boolean(value) => SortValue::Boolean(value),
+            CellValue::String(value) => SortValue::Text(collation_key(&value, locale_id)),
+        }
+    }
+
+    /// Sorts the rows of the `area` by the `keys`.
+    /// Cells are moved with their styles. Relative references in formulas are displaced
+    /// as if the formula was copied to its new position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ironcalc_base::Model;
+    /// # use ironcalc_base::expressions::types::Area;
+    /// # use ironcalc_base::types::SortKey;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut model = Model::new_empty("model", "en", "UTC", "en")?;
+    /// model.set_user_input(0, 1, 1, "3".to_string())?;
+    /// model.set_user_input(0, 2, 1, "1".to_string())?;
+    /// model.set_user_input(0, 3, 1, "2".to_string())?;
+    /// let area = Area { sheet: 0, row: 1, column: 1, width: 1, height: 3 };
+    /// model.sort_range(&area, &[SortKey { column: 1, ascending: true }])?;
+    /// model.evaluate();
+    /// assert_eq!(model.get_formatted_cell_value(0, 1, 1)?, "1");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sort_range(&mut self, area: &Area, keys: &[SortKey]) -> Result<(), String> {
+        let sheet = area.sheet;
+        let last_row = area.row + area.height - 1;
+        let last_column = area.column + area.width - 1;
+        if !is_valid_row(area.row) || !is_valid_row(last_row) || area.height < 1 {
+            return Err("Invalid rows in sort range".to_string());
+        }
+        if !is_valid_column_number(area.column)
+            || !is_valid_column_number(last_column)
+            || area.width < 1
+        {
+            return Err("Invalid columns in sort range".to_string());
+        }
+        if keys.is_empty() {
+            return Err("At least one sort key is needed".to_string());
+        }
+        for key in keys {
+            if key.column < area.column || key.column > last_column {
+                return Err(format!("Sort column {} is outside the range", key.column));
+            }
+        }
+        let locale_id = self.workbook.settings.locale.clone();
+        let worksheet = self.workbook.worksheet(sheet)?;
+        let mut rows = Vec::new();
+        for row in area.row..=last_row {
+            let cells: Vec<Option<Cell>> = (area.column..=last_column)
+                .map(|column| worksheet.cell(row, column).cloned())
+                .collect();
+            let values: Vec<SortValue> = keys
+                .iter()
+                .map(|key| self.get_sort_value(worksheet.cell(row, key.column), &locale_id))
+                .collect();
+            rows.push((cells, values));
+        }
+        // NB: sort_by is stable, rows that compare equal keep their order
+        rows.sort_by(|(_, a), (_, b)| {
+            for (index, key) in keys.iter().enumerate() {
+                let ordering = a[index].compare(&b[index], key.ascending);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+        let worksheet = self.workbook.worksheet_mut(sheet)?;
+        for (row, (cells, _)) in (area.row..=last_row).zip(rows) {
+            for (column, cell) in (area.column..=last_column).zip(cells) {
+                match cell {
+                    Some(cell) => worksheet.update_cell(row, column, cell)?,
+                    None => {
+                        if let Some(row_data) = worksheet.sheet_data.get_mut(&row) {
+                            row_data.remove(&column);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{
     model::Model,
     number_format::{get_default_num_fmt_id, get_new_num_fmt_index, get_num_fmt},
-    types::{Border, CellStyles, CellXfs, Fill, Font, NumFmt, Style, Styles},
+    types::{
+        Border, BorderItem, BorderStyle, CellStyleXfs, CellStyles, CellXfs, Color, Fill, Font,
+        FontScheme, NamedStyle, NumFmt, PatternType, Style, Styles,
+    },
 };
 
 impl Styles {
@@ -41,41 +44,78 @@ impl Styles {
         None
     }
 
-    pub fn create_new_style(&mut self, style: &Style) -> i32 {
-        let font = &style.font;
-        let font_id = if let Some(index) = self.get_font_index(font) {
+    fn get_or_create_font_index(&mut self, font: &Font) -> i32 {
+        if let Some(index) = self.get_font_index(font) {
             index
         } else {
             self.fonts.push(font.clone());
             self.fonts.len() as i32 - 1
-        };
-        let fill = &style.fill;
-        let fill_id = if let Some(index) = self.get_fill_index(fill) {
+        }
+    }
+
+    fn get_or_create_fill_index(&mut self, fill: &Fill) -> i32 {
+        if let Some(index) = self.get_fill_index(fill) {
             index
         } else {
             self.fills.push(fill.clone());
             self.fills.len() as i32 - 1
-        };
-        let border = &style.border;
-        let border_id = if let Some(index) = self.get_border_index(border) {
+        }
+    }
+
+    fn get_or_create_border_index(&mut self, border: &Border) -> i32 {
+        if let Some(index) = self.get_border_index(border) {
             index
         } else {
             self.borders.push(border.clone());
             self.borders.len() as i32 - 1
-        };
-        let num_fmt = &style.num_fmt;
-        let num_fmt_id;
+        }
+    }
+
+    fn get_or_create_num_fmt_index(&mut self, num_fmt: &str) -> i32 {
         if let Some(index) = self.get_num_fmt_index(num_fmt) {
-            num_fmt_id = index;
+            index
         } else {
-            num_fmt_id = get_new_num_fmt_index(&self.num_fmts);
+            let num_fmt_id = get_new_num_fmt_index(&self.num_fmts);
             self.num_fmts.push(NumFmt {
                 format_code: num_fmt.to_string(),
                 num_fmt_id,
             });
+            num_fmt_id
         }
-        self.cell_xfs.push(CellXfs {
-            xf_id: 0,
+    }
+
+    // Index in `cell_style_xfs` of a named style. The Normal style (and any unknown style) is 0.
+    fn get_named_style_xf_id(&self, named_style: &Option<String>) -> i32 {
+        match named_style {
+            Some(name) => self
+                .cell_styles
+                .iter()
+                .find(|cell_style| &cell_style.name == name)
+                .map(|cell_style| cell_style.xf_id)
+                .unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    // Name of the named style at `xf_id` or None for the Normal style
+    fn get_named_style_name(&self, xf_id: i32) -> Option<String> {
+        if xf_id == 0 {
+            return None;
+        }
+        self.cell_styles
+            .iter()
+            .find(|cell_style| cell_style.xf_id == xf_id)
+            .map(|cell_style| cell_style.name.clone())
+    }
+
+    pub fn create_new_style(&mut self, style: &Style) -> i32 {
+        let font_id = self.get_or_create_font_index(&style.font);
+        let fill_id = self.get_or_create_fill_index(&style.fill);
+        let border_id = self.get_or_create_border_index(&style.border);
+        let num_fmt_id = self.get_or_create_num_fmt_index(&style.num_fmt);
+        let xf_id = self.get_named_style_xf_id(&style.named_style);
+        let mut cell_xf = CellXfs {
+            xf_id,
             num_fmt_id,
             font_id,
             fill_id,
@@ -88,7 +128,12 @@ impl Styles {
             apply_fill: false,
             quote_prefix: style.quote_prefix,
             alignment: style.alignment.clone(),
-        });
+            protection: style.protection.clone(),
+        };
+        if let Some(named_xf) = self.cell_style_xfs.get(xf_id as usize) {
+            set_apply_flags(&mut cell_xf, named_xf);
+        }
+        self.cell_xfs.push(cell_xf);
         self.cell_xfs.len() as i32 - 1
     }
 
@@ -107,6 +152,8 @@ impl Styles {
                     font: self.fonts[font_id].clone(),
                     border: self.borders[border_id].clone(),
                     quote_prefix,
+                    protection: cell_xf.protection.clone(),
+                    named_style: self.get_named_style_name(cell_xf.xf_id),
                 })
             {
                 return Some(index as i32);
@@ -124,42 +171,216 @@ impl Styles {
         }
     }
 
-    /// Adds a named cell style from an existing index
+    /// Adds a named cell style from an existing index. The cells with that style become cells of
+    /// the named style.
     /// Fails if the named style already exists or if there is not a style with that index
     pub fn add_named_cell_style(
         &mut self,
         style_name: &str,
         style_index: i32,
     ) -> Result<(), String> {
-        if self.get_style_index_by_name(style_name).is_ok() {
-            return Err("A style with that name already exists".to_string());
-        }
-        if self.cell_xfs.len() < style_index as usize {
-            return Err("There is no style with that index".to_string());
-        }
-        let cell_style = CellStyles {
-            name: style_name.to_string(),
-            xf_id: style_index,
-            builtin_id: 0,
-        };
-        self.cell_styles.push(cell_style);
+        let style = self
+            .get_style(style_index)
+            .map_err(|_| "There is no style with that index".to_string())?;
+        self.create_named_style(style_name, &style)?;
+        self.cell_xfs[style_index as usize].xf_id =
+            self.get_named_style_xf_id(&Some(style_name.to_string()));
         Ok(())
     }
 
     // Returns the index of the style or fails.
     // NB: this method is case sensitive
     pub fn get_style_index_by_name(&self, style_name: &str) -> Result<i32, String> {
-        for cell_style in &self.cell_styles {
-            if cell_style.name == style_name {
-                return Ok(cell_style.xf_id);
+        let style = self.get_named_style(style_name)?;
+        self.get_style_index(&style)
+            .ok_or_else(|| format!("Style '{style_name}' not found"))
+    }
+
+    // Same as [Styles::get_style_index_by_name] but it adds the style if there are no cells with it
+    pub(crate) fn get_style_index_by_name_or_create(
+        &mut self,
+        style_name: &str,
+    ) -> Result<i32, String> {
+        let style = self.get_named_style(style_name)?;
+        Ok(self.get_style_index_or_create(&style))
+    }
+
+    /// Creates a new custom named style.
+    /// Fails if a style with that name already exists
+    pub fn create_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
+        self.add_named_style(style_name, style, None)
+    }
+
+    pub(crate) fn add_named_style(
+        &mut self,
+        style_name: &str,
+        style: &Style,
+        builtin_id: Option<i32>,
+    ) -> Result<(), String> {
+        if style_name.trim().is_empty() {
+            return Err("Invalid style name".to_string());
+        }
+        if self.cell_styles.iter().any(|s| s.name == style_name) {
+            return Err("A style with that name already exists".to_string());
+        }
+        let font_id = self.get_or_create_font_index(&style.font);
+        let fill_id = self.get_or_create_fill_index(&style.fill);
+        let border_id = self.get_or_create_border_index(&style.border);
+        let num_fmt_id = self.get_or_create_num_fmt_index(&style.num_fmt);
+        self.cell_style_xfs.push(CellStyleXfs {
+            num_fmt_id,
+            font_id,
+            fill_id,
+            border_id,
+            alignment: style.alignment.clone(),
+            protection: style.protection.clone(),
+            ..Default::default()
+        });
+        self.cell_styles.push(CellStyles {
+            name: style_name.to_string(),
+            xf_id: self.cell_style_xfs.len() as i32 - 1,
+            builtin_id: builtin_id.unwrap_or(-1),
+        });
+        Ok(())
+    }
+
+    /// Returns the named style `style_name`
+    pub fn get_named_style(&self, style_name: &str) -> Result<Style, String> {
+        let cell_style = self
+            .cell_styles
+            .iter()
+            .find(|cell_style| cell_style.name == style_name)
+            .ok_or_else(|| format!("Style '{style_name}' not found"))?;
+        self.get_named_style_by_xf_id(cell_style.xf_id)
+    }
+
+    fn get_named_style_by_xf_id(&self, xf_id: i32) -> Result<Style, String> {
+        let xf = self
+            .cell_style_xfs
+            .get(xf_id as usize)
+            .ok_or("Invalid index provided".to_string())?;
+        Ok(Style {
+            alignment: xf.alignment.clone(),
+            num_fmt: get_num_fmt(xf.num_fmt_id, &self.num_fmts),
+            fill: self.fills[xf.fill_id as usize].clone(),
+            font: self.fonts[xf.font_id as usize].clone(),
+            border: self.borders[xf.border_id as usize].clone(),
+            quote_prefix: false,
+            protection: xf.protection.clone(),
+            named_style: self.get_named_style_name(xf_id),
+        })
+    }
+
+    /// Returns the list of all named styles in the workbook
+    pub fn get_named_styles(&self) -> Vec<NamedStyle> {
+        self.cell_styles
+            .iter()
+            .filter_map(|cell_style| {
+                self.get_named_style_by_xf_id(cell_style.xf_id)
+                    .ok()
+                    .map(|style| NamedStyle {
+                        name: cell_style.name.clone(),
+                        builtin_id: cell_style.get_builtin_id(),
+                        style,
+                    })
+            })
+            .collect()
+    }
+
+    /// Changes the named style `style_name`.
+    /// The properties of the cells linked to the style that were not formatted directly
+    /// are updated too.
+    pub fn update_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
+        self.get_named_style(style_name)?;
+        let xf_id = self.get_named_style_xf_id(&Some(style_name.to_string()));
+        let font_id = self.get_or_create_font_index(&style.font);
+        let fill_id = self.get_or_create_fill_index(&style.fill);
+        let border_id = self.get_or_create_border_index(&style.border);
+        let num_fmt_id = self.get_or_create_num_fmt_index(&style.num_fmt);
+        for cell_xf in self.cell_xfs.iter_mut().filter(|xf| xf.xf_id == xf_id) {
+            if !cell_xf.apply_font {
+                cell_xf.font_id = font_id;
+            }
+            if !cell_xf.apply_fill {
+                cell_xf.fill_id = fill_id;
+            }
+            if !cell_xf.apply_border {
+                cell_xf.border_id = border_id;
+            }
+            if !cell_xf.apply_number_format {
+                cell_xf.num_fmt_id = num_fmt_id;
+            }
+            if !cell_xf.apply_alignment {
+                cell_xf.alignment = style.alignment.clone();
+            }
+            if !cell_xf.apply_protection {
+                cell_xf.protection = style.protection.clone();
             }
         }
-        Err(format!("Style '{style_name}' not found"))
+        let xf = &mut self.cell_style_xfs[xf_id as usize];
+        xf.font_id = font_id;
+        xf.fill_id = fill_id;
+        xf.border_id = border_id;
+        xf.num_fmt_id = num_fmt_id;
+        xf.alignment = style.alignment.clone();
+        xf.protection = style.protection.clone();
+        Ok(())
     }
 
-    pub fn create_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
-        let style_index = self.create_new_style(style);
-        self.add_named_cell_style(style_name, style_index)
+    /// Deletes the named style `style_name`. The cells linked to it keep their formatting
+    /// but are linked to the Normal style.
+    /// Returns the indexes of the styles that were linked to the named style.
+    pub fn delete_named_style(&mut self, style_name: &str) -> Result<Vec<i32>, String> {
+        let index = self
+            .cell_styles
+            .iter()
+            .position(|cell_style| cell_style.name == style_name)
+            .ok_or_else(|| format!("Style '{style_name}' not found"))?;
+        let xf_id = self.cell_styles[index].xf_id;
+        if xf_id == 0 {
+            return Err("The Normal style can not be deleted".to_string());
+        }
+        self.cell_styles.remove(index);
+        let normal_xf = self.cell_style_xfs[0].clone();
+        let mut linked_styles = Vec::new();
+        for (style_index, cell_xf) in self.cell_xfs.iter_mut().enumerate() {
+            if cell_xf.xf_id == xf_id {
+                cell_xf.xf_id = 0;
+                set_apply_flags(cell_xf, &normal_xf);
+                linked_styles.push(style_index as i32);
+            }
+        }
+        // The format of the style is removed unless another style shares it
+        if self.cell_styles.iter().all(|s| s.xf_id != xf_id) {
+            self.cell_style_xfs.remove(xf_id as usize);
+            for cell_xf in self.cell_xfs.iter_mut().filter(|xf| xf.xf_id > xf_id) {
+                cell_xf.xf_id -= 1;
+            }
+            for cell_style in self.cell_styles.iter_mut().filter(|s| s.xf_id > xf_id) {
+                cell_style.xf_id -= 1;
+            }
+        }
+        Ok(linked_styles)
+    }
+
+    // Links the styles at the given indexes to the named style
+    pub(crate) fn link_named_style(
+        &mut self,
+        style_name: &str,
+        style_indexes: &[i32],
+    ) -> Result<(), String> {
+        let xf_id = self.get_named_style_xf_id(&Some(style_name.to_string()));
+        if xf_id == 0 {
+            return Err(format!("Style '{style_name}' not found"));
+        }
+        let named_xf = self.cell_style_xfs[xf_id as usize].clone();
+        for &style_index in style_indexes {
+            if let Some(cell_xf) = self.cell_xfs.get_mut(style_index as usize) {
+                cell_xf.xf_id = xf_id;
+                set_apply_flags(cell_xf, &named_xf);
+            }
+        }
+        Ok(())
     }
 
     pub(crate) fn get_style_with_quote_prefix(&mut self, index: i32) -> Result<i32, String> {
@@ -200,6 +421,7 @@ impl Styles {
         let num_fmt_id = cell_xf.num_fmt_id;
         let quote_prefix = cell_xf.quote_prefix;
         let alignment = cell_xf.alignment.clone();
+        let protection = cell_xf.protection.clone();
 
         Ok(Style {
             alignment,
@@ -208,10 +430,154 @@ impl Styles {
             font: self.fonts[font_id].clone(),
             border: self.borders[border_id].clone(),
             quote_prefix,
+            protection,
+            named_style: self.get_named_style_name(cell_xf.xf_id),
         })
     }
 }
 
+// Marks the properties of a cell style that are formatted directly, those that are not the ones
+// of its named style. Only those are kept when the named style changes.
+fn set_apply_flags(cell_xf: &mut CellXfs, named_xf: &CellStyleXfs) {
+    cell_xf.apply_number_format = cell_xf.num_fmt_id != named_xf.num_fmt_id;
+    cell_xf.apply_font = cell_xf.font_id != named_xf.font_id;
+    cell_xf.apply_fill = cell_xf.fill_id != named_xf.fill_id;
+    cell_xf.apply_border = cell_xf.border_id != named_xf.border_id;
+    cell_xf.apply_alignment = cell_xf.alignment != named_xf.alignment;
+    cell_xf.apply_protection = cell_xf.protection != named_xf.protection;
+}
+
+fn builtin_style(name: &str, builtin_id: i32, update: impl FnOnce(&mut Style)) -> NamedStyle {
+    let mut style = Style {
+        named_style: Some(name.to_string()),
+        ..Default::default()
+    };
+    update(&mut style);
+    NamedStyle {
+        name: name.to_string(),
+        builtin_id: Some(builtin_id),
+        style,
+    }
+}
+
+fn solid_fill(color: &str) -> Fill {
+    Fill {
+        pattern_type: PatternType::Solid,
+        fg_color: Some(Color::Rgb(color.to_string())),
+        ..Default::default()
+    }
+}
+
+fn border_item(style: BorderStyle, color: &str) -> Option<BorderItem> {
+    Some(BorderItem {
+        style,
+        color: Some(Color::Rgb(color.to_string())),
+    })
+}
+
+fn box_border(style: BorderStyle, color: &str) -> Border {
+    Border {
+        left: border_item(style.clone(), color),
+        right: border_item(style.clone(), color),
+        top: border_item(style.clone(), color),
+        bottom: border_item(style, color),
+        ..Default::default()
+    }
+}
+
+/// Returns the Excel built-in cell styles with the colors of the default Office theme.
+/// The ids are the `builtinId` values in 18.8.7 cellStyle
+pub fn get_builtin_named_styles() -> Vec<NamedStyle> {
+    let text2 = "#44546A";
+    let accent1 = "#4472C4";
+    vec![
+        builtin_style("Comma", 3, |s| s.num_fmt = "#,##0.00".to_string()),
+        builtin_style("Currency", 4, |s| s.num_fmt = "$#,##0.00".to_string()),
+        builtin_style("Percent", 5, |s| s.num_fmt = "0%".to_string()),
+        builtin_style("Note", 10, |s| {
+            s.fill = solid_fill("#FFFFCC");
+            s.border = box_border(BorderStyle::Thin, "#B2B2B2");
+        }),
+        builtin_style("Warning Text", 11, |s| {
+            s.font.color = Some(Color::Rgb("#FF0000".to_string()))
+        }),
+        builtin_style("Title", 15, |s| {
+            s.font.sz = 18;
+            s.font.name = "Calibri Light".to_string();
+            s.font.scheme = FontScheme::Major;
+            s.font.color = Some(Color::Rgb(text2.to_string()));
+        }),
+        builtin_style("Heading 1", 16, |s| {
+            s.font.b = true;
+            s.font.sz = 15;
+            s.font.color = Some(Color::Rgb(text2.to_string()));
+            s.border.bottom = border_item(BorderStyle::Thick, accent1);
+        }),
+        builtin_style("Heading 2", 17, |s| {
+            s.font.b = true;
+            s.font.color = Some(Color::Rgb(text2.to_string()));
+            s.border.bottom = border_item(BorderStyle::Thick, "#A2B8E1");
+        }),
+        builtin_style("Heading 3", 18, |s| {
+            s.font.b = true;
+            s.font.color = Some(Color::Rgb(text2.to_string()));
+            s.border.bottom = border_item(BorderStyle::Medium, "#8EA9DB");
+        }),
+        builtin_style("Heading 4", 19, |s| {
+            s.font.b = true;
+            s.font.color = Some(Color::Rgb(text2.to_string()));
+        }),
+        builtin_style("Input", 20, |s| {
+            s.fill = solid_fill("#FFCC99");
+            s.font.color = Some(Color::Rgb("#3F3F76".to_string()));
+            s.border = box_border(BorderStyle::Thin, "#7F7F7F");
+        }),
+        builtin_style("Output", 21, |s| {
+            s.fill = solid_fill("#F2F2F2");
+            s.font.b = true;
+            s.font.color = Some(Color::Rgb("#3F3F3F".to_string()));
+            s.border = box_border(BorderStyle::Thin, "#3F3F3F");
+        }),
+        builtin_style("Calculation", 22, |s| {
+            s.fill = solid_fill("#F2F2F2");
+            s.font.b = true;
+            s.font.color = Some(Color::Rgb("#FA7D00".to_string()));
+            s.border = box_border(BorderStyle::Thin, "#7F7F7F");
+        }),
+        builtin_style("Check Cell", 23, |s| {
+            s.fill = solid_fill("#A5A5A5");
+            s.font.b = true;
+            s.font.color = Some(Color::Rgb("#FFFFFF".to_string()));
+            s.border = box_border(BorderStyle::Double, "#3F3F3F");
+        }),
+        builtin_style("Linked Cell", 24, |s| {
+            s.font.color = Some(Color::Rgb("#FA7D00".to_string()));
+            s.border.bottom = border_item(BorderStyle::Double, "#FF8001");
+        }),
+        builtin_style("Total", 25, |s| {
+            s.font.b = true;
+            s.border.top = border_item(BorderStyle::Thin, accent1);
+            s.border.bottom = border_item(BorderStyle::Double, accent1);
+        }),
+        builtin_style("Good", 26, |s| {
+            s.fill = solid_fill("#C6EFCE");
+            s.font.color = Some(Color::Rgb("#006100".to_string()));
+        }),
+        builtin_style("Bad", 27, |s| {
+            s.fill = solid_fill("#FFC7CE");
+            s.font.color = Some(Color::Rgb("#9C0006".to_string()));
+        }),
+        builtin_style("Neutral", 28, |s| {
+            s.fill = solid_fill("#FFEB9C");
+            s.font.color = Some(Color::Rgb("#9C5700".to_string()));
+        }),
+        builtin_style("Explanatory Text", 53, |s| {
+            s.font.i = true;
+            s.font.color = Some(Color::Rgb("#7F7F7F".to_string()));
+        }),
+    ]
+}
+
 // TODO: Try to find a better spot for styles setters
 impl<'a> Model<'a> {
     pub fn set_cell_style(
@@ -250,14 +616,20 @@ impl<'a> Model<'a> {
         column: i32,
         style_name: &str,
     ) -> Result<(), String> {
-        let style_index = self.workbook.styles.get_style_index_by_name(style_name)?;
+        let style_index = self
+            .workbook
+            .styles
+            .get_style_index_by_name_or_create(style_name)?;
         self.workbook
             .worksheet_mut(sheet)?
             .set_cell_style(row, column, style_index)
     }
 
     pub fn set_sheet_style(&mut self, sheet: u32, style_name: &str) -> Result<(), String> {
-        let style_index = self.workbook.styles.get_style_index_by_name(style_name)?;
+        let style_index = self
+            .workbook
+            .styles
+            .get_style_index_by_name_or_create(style_name)?;
         self.workbook.worksheet_mut(sheet)?.set_style(style_index)?;
         Ok(())
     }
@@ -268,7 +640,10 @@ impl<'a> Model<'a> {
         row: i32,
         style_name: &str,
     ) -> Result<(), String> {
-        let style_index = self.workbook.styles.get_style_index_by_name(style_name)?;
+        let style_index = self
+            .workbook
+            .styles
+            .get_style_index_by_name_or_create(style_name)?;
         self.workbook
             .worksheet_mut(sheet)?
             .set_row_style(row, style_index)?;
@@ -281,10 +656,47 @@ impl<'a> Model<'a> {
         column: i32,
         style_name: &str,
     ) -> Result<(), String> {
-        let style_index = self.workbook.styles.get_style_index_by_name(style_name)?;
+        let style_index = self
+            .workbook
+            .styles
+            .get_style_index_by_name_or_create(style_name)?;
         self.workbook
             .worksheet_mut(sheet)?
             .set_column_style(column, style_index)?;
         Ok(())
     }
+
+    /// Returns the named styles of the workbook
+    pub fn get_named_styles(&self) -> Vec<NamedStyle> {
+        self.workbook.styles.get_named_styles()
+    }
+
+    /// Creates a new custom named style
+    pub fn create_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
+        self.workbook.styles.create_named_style(style_name, style)
+    }
+
+    /// Adds the built-in style `style_name` to the workbook if it is not there yet
+    pub fn add_builtin_named_style(&mut self, style_name: &str) -> Result<(), String> {
+        if self.workbook.styles.get_named_style(style_name).is_ok() {
+            return Ok(());
+        }
+        let builtin = get_builtin_named_styles()
+            .into_iter()
+            .find(|named_style| named_style.name == style_name)
+            .ok_or_else(|| format!("Style '{style_name}' not found"))?;
+        self.workbook
+            .styles
+            .add_named_style(style_name, &builtin.style, builtin.builtin_id)
+    }
+
+    /// Changes a named style and the cells that use it
+    pub fn update_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
+        self.workbook.styles.update_named_style(style_name, style)
+    }
+
+    /// Deletes a named style. The cells that used it keep their formatting.
+    pub fn delete_named_style(&mut self, style_name: &str) -> Result<Vec<i32>, String> {
+        self.workbook.styles.delete_named_style(style_name)
+    }
 }
@@ -0,0 +1,52 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::test::util::new_empty_model;
+use crate::types::DateSystem;
+
+#[test]
+fn switching_date_system_keeps_dates() {
+    let mut model = new_empty_model();
+    model
+        .set_user_input(0, 1, 1, "2024-01-05".to_string())
+        .unwrap();
+    model.set_user_input(0, 2, 1, "7".to_string()).unwrap();
+    model.evaluate();
+    assert_eq!(model._get_text("A1"), "2024-01-05");
+    let serial_1900 = model.get_cell_value_by_ref("Sheet1!A1").unwrap();
+
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    assert_eq!(model.get_date_system(), DateSystem::Excel1904);
+    // Dates keep the same calendar date, plain numbers are untouched
+    assert_eq!(model._get_text("A1"), "2024-01-05");
+    assert_eq!(model._get_text("A2"), "7");
+    let serial_1904 = model.get_cell_value_by_ref("Sheet1!A1").unwrap();
+    assert_eq!(serial_1900, crate::cell::CellValue::Number(45296.0),);
+    assert_eq!(
+        serial_1904,
+        crate::cell::CellValue::Number(45296.0 - 1462.0),
+    );
+
+    model.set_date_system(DateSystem::Excel1900).unwrap();
+    assert_eq!(model.get_cell_value_by_ref("Sheet1!A1"), Ok(serial_1900));
+}
+
+#[test]
+fn functions_in_1904() {
+    let mut model = new_empty_model();
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    model._set("A1", "=DATE(1904,1,1)");
+    model._set("A2", "=YEAR(0)");
+    model._set("A3", "=TEXT(0,\"yyyy-mm-dd\")");
+    model._set("A4", "=DATEVALUE(\"1904-01-02\")");
+    model._set("A5", "=DATE(1903,12,31)");
+    model._set("A6", "=DAY(366)");
+    model.evaluate();
+
+    assert_eq!(model._get_text("A1"), "1/1/1904");
+    assert_eq!(model._get_text("A2"), "1904");
+    assert_eq!(model._get_text("A3"), "1904-01-01");
+    assert_eq!(model._get_text("A4"), "1");
+    assert_eq!(model._get_text("A5"), "#NUM!");
+    // 1904 is a leap year
+    assert_eq!(model._get_text("A6"), "1");
+}
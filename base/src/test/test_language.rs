@@ -1,6 +1,13 @@
 #![allow(clippy::unwrap_used)]
 
-use crate::{test::util::new_empty_model, Model};
+use crate::{
+    expressions::types::{Area, CellReferenceIndex},
+    functions::Function,
+    language::{get_language, get_supported_languages},
+    locale::get_locale,
+    test::util::new_empty_model,
+    Model,
+};
 
 pub fn new_german_empty_model<'a>() -> Model<'a> {
     Model::new_empty("model", "en", "UTC", "de").unwrap()
@@ -50,3 +57,170 @@ fn spanish() {
     assert_eq!(model._get_text("A1"), *"VERDADERO");
     assert_eq!(model._get_text("B1"), *"#¿NOMBRE?");
 }
+
+#[test]
+fn german_round_trip() {
+    let mut model = Model::new_empty("model", "de", "UTC", "de").unwrap();
+    model._set("A1", "=SUMME(1,5;2;WENN(WAHR;1;0))");
+    model._set("B1", "=SUMME({1;2\\3;4})");
+    model._set("C1", "=WENN(FALSCH;#BEZUG!;1,25)");
+    model.evaluate();
+
+    assert_eq!(model._get_formula("A1"), *"=SUMME(1,5;2;WENN(WAHR;1;0))");
+    assert_eq!(model._get_formula("B1"), *"=SUMME({1;2\\3;4})");
+    assert_eq!(model._get_formula("C1"), *"=WENN(FALSCH;#BEZUG!;1,25)");
+    assert_eq!(model._get_text("A1"), *"4,5");
+    assert_eq!(model._get_text("B1"), *"10");
+    assert_eq!(model._get_text("C1"), *"1,25");
+
+    model.set_language("en").unwrap();
+    model.set_locale("en").unwrap();
+    assert_eq!(model._get_formula("A1"), *"=SUM(1.5,2,IF(TRUE,1,0))");
+    assert_eq!(model._get_formula("B1"), *"=SUM({1,2;3,4})");
+    assert_eq!(model._get_formula("C1"), *"=IF(FALSE,#REF!,1.25)");
+}
+
+#[test]
+fn round_trip_every_language() {
+    for code in get_supported_languages() {
+        let mut model = Model::new_empty("model", &code, "UTC", &code).unwrap();
+        let language = get_language(&code).unwrap();
+        let locale = get_locale(&code).unwrap();
+        let name = |f: Function| f.to_localized_name(language);
+        let sep = locale.argument_separator();
+        let row = locale.array_row_separator();
+        let dec = &locale.numbers.symbols.decimal;
+        let formula = format!(
+            "={}(1{dec}5{sep}{{1{sep}2{row}3{sep}4}}{sep}{}({}{sep}1{sep}{}))",
+            name(Function::Sum),
+            name(Function::If),
+            language.booleans.r#true,
+            language.errors.r#ref,
+        );
+        model._set("A1", &formula);
+        model.evaluate();
+        assert_eq!(model._get_formula("A1"), formula, "language '{code}'");
+        assert_eq!(
+            model._get_text("A1"),
+            format!("12{dec}5"),
+            "language '{code}'"
+        );
+
+        model.set_language("en").unwrap();
+        model.set_locale("en").unwrap();
+        assert_eq!(
+            model._get_formula("A1"),
+            *"=SUM(1.5,{1,2;3,4},IF(TRUE,1,#REF!))",
+            "language '{code}'"
+        );
+    }
+}
+
+#[test]
+fn typed_booleans_and_errors() {
+    let mut model = Model::new_empty("model", "de", "UTC", "de").unwrap();
+    model._set("A1", "wahr");
+    model._set("A2", "#BEZUG!");
+    model._set("A3", "TRUE");
+    model._set("A4", "=A1");
+    model.evaluate();
+
+    assert_eq!(model._get_text("A1"), *"WAHR");
+    assert_eq!(model._get_text("A2"), *"#BEZUG!");
+    assert_eq!(model._get_text("A3"), *"TRUE");
+    assert_eq!(model._get_text("A4"), *"WAHR");
+}
+
+#[test]
+fn rename_sheet_in_other_locale() {
+    let mut model = Model::new_empty("model", "de", "UTC", "de").unwrap();
+    model.add_sheet("Daten").unwrap();
+    model._set("A1", "=SUMME(Daten!A1;1,5)");
+    model.new_defined_name("Zins", None, "Daten!$A$1").unwrap();
+    model.rename_sheet("Daten", "Werte").unwrap();
+    model.evaluate();
+
+    assert_eq!(model._get_formula("A1"), *"=SUMME(Werte!A1;1,5)");
+    assert_eq!(model._get_text("A1"), *"1,5");
+    assert_eq!(model.workbook.defined_names[0].formula, *"Werte!$A$1");
+}
+
+#[test]
+fn move_formula_in_other_locale() {
+    let mut model = Model::new_empty("model", "de", "UTC", "de").unwrap();
+    let source = &CellReferenceIndex {
+        sheet: 0,
+        row: 1,
+        column: 1,
+    };
+    let target = &CellReferenceIndex {
+        sheet: 0,
+        row: 2,
+        column: 2,
+    };
+    let area = &Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    let value = "=SUMME(A1;{1;2\\3;4};WAHR;0,5)+#BEZUG!";
+    let t = model.move_cell_value_to_area(value, source, target, area);
+    assert_eq!(t, Ok("=SUMME(B2;{1;2\\3;4};WAHR;0,5)+#BEZUG!".to_string()));
+}
+
+#[test]
+fn formulatext_in_other_locale() {
+    let mut model = Model::new_empty("model", "de", "UTC", "de").unwrap();
+    model._set("A1", "=SUMME(1,5;{1;2\\3;4};WAHR)");
+    model._set("B1", "=FORMELTEXT(A1)");
+    model.evaluate();
+    assert_eq!(model._get_text("B1"), *"=SUMME(1,5;{1;2\\3;4};WAHR)");
+
+    model.set_language("fr").unwrap();
+    model.set_locale("fr").unwrap();
+    model.evaluate();
+    assert_eq!(model._get_text("B1"), *"=SOMME(1,5;{1;2\\3;4};VRAI)");
+}
+
+#[test]
+fn r1c1_in_other_locale() {
+    let mut model = Model::new_empty("model", "de", "UTC", "de").unwrap();
+    model
+        .set_user_input_r1c1(
+            0,
+            3,
+            2,
+            "=SUMME(Z[-2]S[-1]:Z[-1]S[-1];Z1S1;1,5)".to_string(),
+        )
+        .unwrap();
+    model._set("A1", "1");
+    model._set("A2", "2");
+    model.evaluate();
+    assert_eq!(model._get_formula("B3"), *"=SUMME(A1:A2;$A$1;1,5)");
+    assert_eq!(model._get_text("B3"), *"5,5");
+    assert_eq!(
+        model.get_cell_formula_r1c1(0, 3, 2).unwrap(),
+        Some("=SUMME(Z[-2]S[-1]:Z[-1]S[-1];Z1S1;1,5)".to_string())
+    );
+    // English letters are not references in German
+    assert!(model
+        .set_user_input_r1c1(0, 4, 2, "=R1C1+1".to_string())
+        .is_err());
+
+    model.set_language("fr").unwrap();
+    model.set_locale("fr").unwrap();
+    assert_eq!(
+        model.get_cell_formula_r1c1(0, 3, 2).unwrap(),
+        Some("=SOMME(L[-2]C[-1]:L[-1]C[-1];L1C1;1,5)".to_string())
+    );
+
+    model.set_language("en").unwrap();
+    model.set_locale("en").unwrap();
+    assert_eq!(
+        model.get_cell_formula_r1c1(0, 3, 2).unwrap(),
+        Some("=SUM(R[-2]C[-1]:R[-1]C[-1],R1C1,1.5)".to_string())
+    );
+    assert_eq!(model.get_cell_formula_r1c1(0, 1, 1).unwrap(), None);
+}
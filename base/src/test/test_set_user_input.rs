@@ -92,6 +92,29 @@ fn test_percentage() {
     assert_eq!(model._get_text("A11"), "55.76%");
 }
 
+#[test]
+fn test_fractions() {
+    let mut model = new_empty_model();
+    model.set_user_input(0, 1, 1, "1 3/4".to_string()).unwrap();
+    model
+        .set_user_input(0, 2, 1, "-0 5/16".to_string())
+        .unwrap();
+    model.set_user_input(0, 3, 1, "=A1+A2".to_string()).unwrap();
+    model.evaluate();
+
+    assert_eq!(
+        model.get_cell_value_by_ref("Sheet1!A1"),
+        Ok(CellValue::Number(1.75))
+    );
+    assert_eq!(model._get_text("A1"), "1 3/4");
+    assert_eq!(model.get_style_for_cell(0, 1, 1).unwrap().num_fmt, "# ?/?");
+    assert_eq!(model._get_text("A2"), "  -5/16");
+    assert_eq!(
+        model.get_cell_value_by_ref("Sheet1!A3"),
+        Ok(CellValue::Number(1.4375))
+    );
+}
+
 #[test]
 fn test_percentage_ops() {
     let mut model = new_empty_model();
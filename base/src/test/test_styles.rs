@@ -1,7 +1,7 @@
 #![allow(clippy::unwrap_used)]
 
 use crate::test::util::new_empty_model;
-use crate::types::Style;
+use crate::types::{PatternType, Style};
 
 #[test]
 fn test_model_set_cells_with_values_styles() {
@@ -69,10 +69,10 @@ fn empty_models_have_two_fills() {
     assert_eq!(model.workbook.styles.fills.len(), 2);
     assert_eq!(
         model.workbook.styles.fills[0].pattern_type,
-        "none".to_string()
+        PatternType::None
     );
     assert_eq!(
         model.workbook.styles.fills[1].pattern_type,
-        "gray125".to_string()
+        PatternType::Gray125
     );
 }
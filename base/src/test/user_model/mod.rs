@@ -1,29 +1,50 @@
 mod test_add_delete_sheets;
+mod test_auto_filter;
 mod test_autofill_columns;
 mod test_autofill_rows;
+mod test_autofill_series;
 mod test_batch_row_column_diff;
 mod test_border;
 mod test_clear_cells;
+mod test_collaboration;
 mod test_column_style;
+mod test_date_system;
 mod test_defined_names;
 mod test_delete_row_column_formatting;
+mod test_diff_format;
 mod test_diff_queue;
+mod test_duplicate_move_sheet;
 mod test_evaluation;
+mod test_fills;
+mod test_find;
+mod test_flash_fill;
 mod test_general;
 mod test_grid_lines;
+mod test_history;
 mod test_keyboard_navigation;
 mod test_last_empty_cell;
+mod test_merge_cells;
+mod test_move_range;
 mod test_multi_row_column;
+mod test_named_styles;
 mod test_on_area_selection;
 mod test_on_expand_selected_range;
 mod test_on_paste_styles;
+mod test_outline;
+mod test_page_setup;
 mod test_paste_csv;
+mod test_paste_html;
+mod test_paste_special;
+mod test_protection;
 mod test_recursive;
 mod test_rename_sheet;
+mod test_rich_text;
 mod test_row_column;
 mod test_sheet_state;
 mod test_sheets_undo_redo;
+mod test_sort_range;
 mod test_styles;
+mod test_theme;
 mod test_to_from_bytes;
 mod test_undo_redo;
 mod test_view;
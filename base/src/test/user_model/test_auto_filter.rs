@@ -0,0 +1,435 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{
+    AutoFilter, ColumnFilter, CustomFilter, DynamicFilterType, FilterColumn, FilterOperator, Table,
+    TableColumn, TableStyleInfo,
+};
+use crate::UserModel;
+
+fn new_model_with_data<'a>() -> UserModel<'a> {
+    let mut model = new_empty_user_model();
+    let data = [
+        ("Name", "Amount", "Date"),
+        ("Apples", "10", "44873"),
+        ("Pears", "20", "44872"),
+        ("apples", "30", "44874"),
+        ("Plums", "40", "44850"),
+        ("Oranges", "50", "44800"),
+        ("", "60", "44880"),
+    ];
+    for (index, (name, amount, date)) in data.iter().enumerate() {
+        let row = index as i32 + 1;
+        model.set_user_input(0, row, 1, name).unwrap();
+        model.set_user_input(0, row, 2, amount).unwrap();
+        model.set_user_input(0, row, 3, date).unwrap();
+    }
+    model
+        .set_auto_filter(&Area {
+            sheet: 0,
+            row: 1,
+            column: 1,
+            width: 3,
+            height: 7,
+        })
+        .unwrap();
+    model
+}
+
+fn hidden_rows(model: &UserModel) -> Vec<i32> {
+    let worksheet = model.get_model().workbook.worksheet(0).unwrap();
+    (1..=7)
+        .filter(|row| worksheet.is_row_hidden(*row).unwrap())
+        .collect()
+}
+
+#[test]
+fn set_and_delete_auto_filter() {
+    let mut model = new_model_with_data();
+    assert_eq!(
+        model.get_auto_filter(0).unwrap(),
+        Some(AutoFilter {
+            reference: "A1:C7".to_string(),
+            filter_columns: vec![],
+        })
+    );
+    assert_eq!(hidden_rows(&model), Vec::<i32>::new());
+
+    model
+        .set_auto_filter_column(
+            0,
+            0,
+            ColumnFilter::Values {
+                values: vec!["apples".to_string()],
+                blank: false,
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![3, 5, 6, 7]);
+
+    model.delete_auto_filter(0).unwrap();
+    assert_eq!(model.get_auto_filter(0).unwrap(), None);
+    assert_eq!(hidden_rows(&model), Vec::<i32>::new());
+
+    model.undo().unwrap();
+    assert_eq!(hidden_rows(&model), vec![3, 5, 6, 7]);
+    assert_eq!(
+        model
+            .get_auto_filter(0)
+            .unwrap()
+            .unwrap()
+            .filter_columns
+            .len(),
+        1
+    );
+
+    model.undo().unwrap();
+    assert_eq!(hidden_rows(&model), Vec::<i32>::new());
+
+    model.redo().unwrap();
+    assert_eq!(hidden_rows(&model), vec![3, 5, 6, 7]);
+
+    // The diffs can be applied to another model
+    let send_queue = model.flush_send_queue();
+    let mut model2 = new_empty_user_model();
+    model2.apply_external_diffs(&send_queue).unwrap();
+    assert_eq!(hidden_rows(&model2), vec![3, 5, 6, 7]);
+    assert_eq!(model2.get_auto_filter(0), model.get_auto_filter(0));
+}
+
+#[test]
+fn values_filter_with_blanks() {
+    let mut model = new_model_with_data();
+    model
+        .set_auto_filter_column(
+            0,
+            0,
+            ColumnFilter::Values {
+                values: vec!["Pears".to_string()],
+                blank: true,
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 4, 5, 6]);
+}
+
+#[test]
+fn custom_filters() {
+    let mut model = new_model_with_data();
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Custom {
+                and: true,
+                filters: vec![
+                    CustomFilter {
+                        operator: FilterOperator::GreaterThan,
+                        value: "20".to_string(),
+                    },
+                    CustomFilter {
+                        operator: FilterOperator::LessThanOrEqual,
+                        value: "50".to_string(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 3, 7]);
+
+    // Wildcards
+    model
+        .set_auto_filter_column(
+            0,
+            0,
+            ColumnFilter::Custom {
+                and: false,
+                filters: vec![CustomFilter {
+                    operator: FilterOperator::Equal,
+                    value: "p*".to_string(),
+                }],
+            },
+        )
+        .unwrap();
+    // Both filters apply
+    assert_eq!(hidden_rows(&model), vec![2, 3, 4, 6, 7]);
+
+    model.clear_auto_filter_column(0, 1).unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 4, 6, 7]);
+}
+
+#[test]
+fn top10_filters() {
+    let mut model = new_model_with_data();
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Top10 {
+                top: true,
+                percent: false,
+                value: 2.0,
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 3, 4, 5]);
+
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Top10 {
+                top: false,
+                percent: true,
+                value: 50.0,
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![5, 6, 7]);
+}
+
+#[test]
+fn dynamic_filters() {
+    // Mock time is Tuesday 8 November 2022 (serial number 44873)
+    let mut model = new_model_with_data();
+    let cases = [
+        (DynamicFilterType::Today, vec![3, 4, 5, 6, 7]),
+        (DynamicFilterType::Yesterday, vec![2, 4, 5, 6, 7]),
+        (DynamicFilterType::Tomorrow, vec![2, 3, 5, 6, 7]),
+        // Sunday 6 November to Saturday 12 November
+        (DynamicFilterType::ThisWeek, vec![5, 6, 7]),
+        (DynamicFilterType::NextWeek, vec![2, 3, 4, 5, 6]),
+        (DynamicFilterType::ThisMonth, vec![5, 6]),
+        (DynamicFilterType::LastMonth, vec![2, 3, 4, 6, 7]),
+        (DynamicFilterType::Month(10), vec![2, 3, 4, 6, 7]),
+        (DynamicFilterType::ThisQuarter, vec![6]),
+        (DynamicFilterType::Quarter(3), vec![2, 3, 4, 5, 7]),
+        (DynamicFilterType::YearToDate, vec![4, 7]),
+        (DynamicFilterType::LastYear, vec![2, 3, 4, 5, 6, 7]),
+    ];
+    for (filter_type, expected) in cases {
+        model
+            .set_auto_filter_column(0, 2, ColumnFilter::Dynamic { filter_type })
+            .unwrap();
+        assert_eq!(hidden_rows(&model), expected);
+    }
+
+    // average of the amounts is 35
+    model.clear_auto_filter_column(0, 2).unwrap();
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Dynamic {
+                filter_type: DynamicFilterType::AboveAverage,
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 3, 4]);
+}
+
+#[test]
+fn color_filters() {
+    let mut model = new_model_with_data();
+    let area = Area {
+        sheet: 0,
+        row: 3,
+        column: 2,
+        width: 1,
+        height: 2,
+    };
+    model
+        .update_range_style(&area, "fill.fg_color", "#FF0000")
+        .unwrap();
+    let area = Area {
+        sheet: 0,
+        row: 6,
+        column: 2,
+        width: 1,
+        height: 1,
+    };
+    model
+        .update_range_style(&area, "font.color", "#00FF00")
+        .unwrap();
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Color {
+                cell_color: true,
+                color: "#FF0000".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 5, 6, 7]);
+
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Color {
+                cell_color: false,
+                color: "#00ff00".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 3, 4, 5, 7]);
+}
+
+#[test]
+fn filters_are_not_dynamic() {
+    let mut model = new_model_with_data();
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Custom {
+                and: false,
+                filters: vec![CustomFilter {
+                    operator: FilterOperator::GreaterThan,
+                    value: "40".to_string(),
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 3, 4, 5]);
+
+    model.set_user_input(0, 2, 2, "100").unwrap();
+    assert_eq!(hidden_rows(&model), vec![2, 3, 4, 5]);
+
+    model.reapply_auto_filter(0).unwrap();
+    assert_eq!(hidden_rows(&model), vec![3, 4, 5]);
+}
+
+#[test]
+fn subtotal_ignores_filtered_rows() {
+    let mut model = new_model_with_data();
+    model
+        .set_user_input(0, 10, 2, "=SUBTOTAL(9, B2:B7)")
+        .unwrap();
+    model
+        .set_user_input(0, 11, 2, "=SUBTOTAL(109, B2:B7)")
+        .unwrap();
+    model
+        .set_auto_filter_column(
+            0,
+            1,
+            ColumnFilter::Top10 {
+                top: true,
+                percent: false,
+                value: 3.0,
+            },
+        )
+        .unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 10, 2).unwrap(), "150");
+    assert_eq!(model.get_formatted_cell_value(0, 11, 2).unwrap(), "150");
+
+    model.undo().unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 10, 2).unwrap(), "210");
+    assert_eq!(model.get_formatted_cell_value(0, 11, 2).unwrap(), "210");
+
+    model.redo().unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 11, 2).unwrap(), "150");
+
+    model.delete_auto_filter(0).unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 11, 2).unwrap(), "210");
+}
+
+#[test]
+fn errors() {
+    let mut model = new_model_with_data();
+    let values = ColumnFilter::Values {
+        values: vec![],
+        blank: true,
+    };
+    assert_eq!(
+        model.set_auto_filter_column(0, 3, values.clone()),
+        Err("Invalid filter column: 3".to_string())
+    );
+    assert_eq!(
+        model.set_auto_filter_column(
+            0,
+            0,
+            ColumnFilter::Dynamic {
+                filter_type: DynamicFilterType::Month(13)
+            }
+        ),
+        Err("Invalid month: 13".to_string())
+    );
+    model.new_sheet().unwrap();
+    assert_eq!(
+        model.set_auto_filter_column(1, 0, values),
+        Err("The sheet has no AutoFilter".to_string())
+    );
+}
+
+#[test]
+fn table_filters() {
+    let mut model = new_model_with_data();
+    model.delete_auto_filter(0).unwrap();
+    let columns = ["Name", "Amount", "Date"]
+        .iter()
+        .enumerate()
+        .map(|(index, name)| TableColumn {
+            id: index as u32 + 1,
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    model.model.workbook.tables.insert(
+        "Fruits".to_string(),
+        Table {
+            name: "Fruits".to_string(),
+            display_name: "Fruits".to_string(),
+            sheet_name: "Sheet1".to_string(),
+            reference: "A1:C8".to_string(),
+            totals_row_count: 1,
+            header_row_count: 1,
+            header_row_dxf_id: None,
+            data_dxf_id: None,
+            totals_row_dxf_id: None,
+            columns,
+            style_info: TableStyleInfo {
+                ..Default::default()
+            },
+            has_filters: false,
+            auto_filter: None,
+        },
+    );
+    model
+        .set_table_filter_column(
+            "Fruits",
+            1,
+            ColumnFilter::Custom {
+                and: false,
+                filters: vec![CustomFilter {
+                    operator: FilterOperator::LessThan,
+                    value: "30".to_string(),
+                }],
+            },
+        )
+        .unwrap();
+    let auto_filter = model.get_table_auto_filter("Fruits").unwrap().unwrap();
+    // The totals row is not part of the filter
+    assert_eq!(auto_filter.reference, "A1:C7");
+    assert_eq!(auto_filter.filter_columns[0].col_id, 1);
+    assert_eq!(hidden_rows(&model), vec![4, 5, 6, 7]);
+    assert!(model.get_model().workbook.tables["Fruits"].has_filters);
+
+    model.undo().unwrap();
+    assert_eq!(hidden_rows(&model), Vec::<i32>::new());
+    assert_eq!(model.get_table_auto_filter("Fruits").unwrap(), None);
+    assert!(!model.get_model().workbook.tables["Fruits"].has_filters);
+
+    model.redo().unwrap();
+    model.clear_table_filter_column("Fruits", 1).unwrap();
+    assert_eq!(hidden_rows(&model), Vec::<i32>::new());
+    assert_eq!(
+        model.get_table_auto_filter("Fruits").unwrap(),
+        Some(AutoFilter {
+            reference: "A1:C7".to_string(),
+            filter_columns: Vec::<FilterColumn>::new(),
+        })
+    );
+}
@@ -3,6 +3,7 @@
 use crate::constants::{LAST_COLUMN, LAST_ROW};
 use crate::expressions::types::Area;
 use crate::test::util::new_empty_model;
+use crate::types::Color;
 use crate::UserModel;
 
 #[test]
@@ -188,7 +189,7 @@ fn styles() {
     assert!(style.font.i);
     // A6 would have the style of A3
     let style = model.get_cell_style(0, 1, 6).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#334455".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#334455".to_string())));
 
     model.undo().unwrap();
 
@@ -210,7 +211,7 @@ fn styles() {
     assert!(style.font.i);
     // A6 would have the style of A3
     let style = model.get_cell_style(0, 1, 6).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#334455".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#334455".to_string())));
 }
 
 #[test]
@@ -0,0 +1,272 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::{AutoFillMode, UserModel};
+
+fn column_area(row: i32, column: i32, height: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width: 1,
+        height,
+    }
+}
+
+fn fill_down(model: &mut UserModel, area: &Area, to_row: i32, mode: AutoFillMode) {
+    model.auto_fill_rows_with_mode(area, to_row, mode).unwrap();
+}
+
+fn values(model: &UserModel, column: i32, rows: std::ops::RangeInclusive<i32>) -> Vec<String> {
+    rows.map(|row| model.get_formatted_cell_value(0, row, column).unwrap())
+        .collect()
+}
+
+#[test]
+fn dates_by_day() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "2024-01-30").unwrap();
+    // A single date is extended by one day
+    fill_down(&mut model, &column_area(1, 1, 1), 3, AutoFillMode::Auto);
+    assert_eq!(
+        values(&model, 1, 1..=3),
+        vec!["2024-01-30", "2024-01-31", "2024-02-01"]
+    );
+
+    // Two dates give the step
+    model.set_user_input(0, 1, 2, "2024-01-01").unwrap();
+    model.set_user_input(0, 2, 2, "2024-01-08").unwrap();
+    fill_down(&mut model, &column_area(1, 2, 2), 4, AutoFillMode::Auto);
+    assert_eq!(values(&model, 2, 3..=4), vec!["2024-01-15", "2024-01-22"]);
+}
+
+#[test]
+fn dates_by_month_and_year() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "2024-01-31").unwrap();
+    model.set_user_input(0, 2, 1, "2024-02-29").unwrap();
+    // Same day of the month is not required when the mode is explicit
+    fill_down(&mut model, &column_area(1, 1, 1), 4, AutoFillMode::Months);
+    assert_eq!(
+        values(&model, 1, 1..=4),
+        vec!["2024-01-31", "2024-02-29", "2024-03-31", "2024-04-30"]
+    );
+
+    model.set_user_input(0, 1, 2, "2020-03-15").unwrap();
+    model.set_user_input(0, 2, 2, "2020-06-15").unwrap();
+    fill_down(&mut model, &column_area(1, 2, 2), 4, AutoFillMode::Auto);
+    assert_eq!(values(&model, 2, 3..=4), vec!["2020-09-15", "2020-12-15"]);
+
+    model.set_user_input(0, 1, 3, "2020-02-29").unwrap();
+    fill_down(&mut model, &column_area(1, 3, 1), 3, AutoFillMode::Years);
+    assert_eq!(
+        values(&model, 3, 1..=3),
+        vec!["2020-02-29", "2021-02-28", "2022-02-28"]
+    );
+}
+
+#[test]
+fn times_by_hour() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "0.5").unwrap();
+    model
+        .update_range_style(&column_area(1, 1, 1), "num_fmt", "h:mm")
+        .unwrap();
+    // A single time is extended by one hour
+    fill_down(&mut model, &column_area(1, 1, 1), 3, AutoFillMode::Auto);
+    assert_eq!(values(&model, 1, 1..=3), vec!["12:00", "13:00", "14:00"]);
+
+    // And up by one hour before
+    model.set_user_input(0, 5, 2, "0.5").unwrap();
+    model
+        .update_range_style(&column_area(5, 2, 1), "num_fmt", "h:mm")
+        .unwrap();
+    model
+        .auto_fill_rows_with_mode(&column_area(5, 2, 1), 4, AutoFillMode::Auto)
+        .unwrap();
+    assert_eq!(values(&model, 2, 4..=5), vec!["11:00", "12:00"]);
+}
+
+#[test]
+fn times_by_step() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "0.5").unwrap();
+    model.set_user_input(0, 2, 1, "0.520833333333333").unwrap();
+    model
+        .update_range_style(&column_area(1, 1, 2), "num_fmt", "h:mm")
+        .unwrap();
+    assert_eq!(values(&model, 1, 1..=2), vec!["12:00", "12:30"]);
+    // Two times give the step, not whole days
+    fill_down(&mut model, &column_area(1, 1, 2), 4, AutoFillMode::Auto);
+    assert_eq!(values(&model, 1, 3..=4), vec!["13:00", "13:30"]);
+}
+
+#[test]
+fn dates_by_weekday() {
+    let mut model = new_empty_user_model();
+    // A Friday
+    model.set_user_input(0, 1, 1, "2024-03-08").unwrap();
+    fill_down(&mut model, &column_area(1, 1, 1), 4, AutoFillMode::Weekdays);
+    assert_eq!(
+        values(&model, 1, 2..=4),
+        vec!["2024-03-11", "2024-03-12", "2024-03-13"]
+    );
+}
+
+#[test]
+fn copy_mode() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(0, 2, 1, "2").unwrap();
+    fill_down(&mut model, &column_area(1, 1, 2), 4, AutoFillMode::Copy);
+    assert_eq!(values(&model, 1, 1..=4), vec!["1", "2", "1", "2"]);
+}
+
+#[test]
+fn quarters_and_names() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Q3").unwrap();
+    model.set_user_input(0, 1, 2, "Monday").unwrap();
+    model.set_user_input(0, 1, 3, "Jan").unwrap();
+    model
+        .auto_fill_rows(
+            &Area {
+                sheet: 0,
+                row: 1,
+                column: 1,
+                width: 3,
+                height: 1,
+            },
+            4,
+        )
+        .unwrap();
+    assert_eq!(values(&model, 1, 1..=4), vec!["Q3", "Q4", "Q1", "Q2"]);
+    assert_eq!(
+        values(&model, 2, 2..=4),
+        vec!["Tuesday", "Wednesday", "Thursday"]
+    );
+    assert_eq!(values(&model, 3, 2..=4), vec!["Feb", "Mar", "Apr"]);
+}
+
+#[test]
+fn month_names_in_the_locale() {
+    let mut model = UserModel::new_empty("model", "de", "UTC", "de").unwrap();
+    model.set_user_input(0, 1, 1, "Januar").unwrap();
+    fill_down(&mut model, &column_area(1, 1, 1), 3, AutoFillMode::Auto);
+    assert_eq!(values(&model, 1, 2..=3), vec!["Februar", "März"]);
+}
+
+#[test]
+fn custom_lists() {
+    let mut model = new_empty_user_model();
+    assert_eq!(
+        model.set_custom_lists(vec![vec!["Only".to_string()]]),
+        Err("A custom list needs at least two items".to_string())
+    );
+    let list = vec![
+        "North".to_string(),
+        "East".to_string(),
+        "South".to_string(),
+        "West".to_string(),
+    ];
+    model.set_custom_lists(vec![list.clone()]).unwrap();
+    assert_eq!(model.get_custom_lists(), vec![list.clone()]);
+
+    model.set_user_input(0, 1, 1, "south").unwrap();
+    fill_down(&mut model, &column_area(1, 1, 1), 4, AutoFillMode::Auto);
+    assert_eq!(values(&model, 1, 2..=4), vec!["West", "North", "East"]);
+
+    // The lists are saved with the workbook
+    let bytes = model.to_bytes();
+    let other = UserModel::from_bytes(&bytes, "en").unwrap();
+    assert_eq!(other.get_custom_lists(), vec![list]);
+
+    // The fill, the input and the lists
+    model.undo().unwrap();
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert!(model.get_custom_lists().is_empty());
+    model.redo().unwrap();
+    assert_eq!(model.get_custom_lists().len(), 1);
+}
+
+#[test]
+fn trends() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(0, 2, 1, "3").unwrap();
+    model.set_user_input(0, 3, 1, "4").unwrap();
+    fill_down(
+        &mut model,
+        &column_area(1, 1, 3),
+        5,
+        AutoFillMode::LinearTrend,
+    );
+    // Least squares fit: 1.5 * x - 0.333...
+    assert_eq!(
+        model.get_cell_content(0, 4, 1),
+        Ok("5.66666666666667".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(0, 5, 1),
+        Ok("7.16666666666667".to_string())
+    );
+
+    model.set_user_input(0, 1, 2, "2").unwrap();
+    model.set_user_input(0, 2, 2, "6").unwrap();
+    fill_down(
+        &mut model,
+        &column_area(1, 2, 2),
+        4,
+        AutoFillMode::GrowthTrend,
+    );
+    assert_eq!(values(&model, 2, 3..=4), vec!["18", "54"]);
+
+    // Growth needs positive numbers, otherwise the cells are copied
+    model.set_user_input(0, 1, 3, "-2").unwrap();
+    model.set_user_input(0, 2, 3, "6").unwrap();
+    fill_down(
+        &mut model,
+        &column_area(1, 3, 2),
+        4,
+        AutoFillMode::GrowthTrend,
+    );
+    assert_eq!(values(&model, 3, 3..=4), vec!["-2", "6"]);
+}
+
+#[test]
+fn fill_up_and_left() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 5, 1, "2024-01-10").unwrap();
+    model.set_user_input(0, 6, 1, "2024-01-12").unwrap();
+    fill_down(&mut model, &column_area(5, 1, 2), 3, AutoFillMode::Auto);
+    assert_eq!(values(&model, 1, 3..=4), vec!["2024-01-06", "2024-01-08"]);
+
+    model.set_user_input(0, 10, 5, "Q2").unwrap();
+    model
+        .auto_fill_columns(
+            &Area {
+                sheet: 0,
+                row: 10,
+                column: 5,
+                width: 1,
+                height: 1,
+            },
+            3,
+        )
+        .unwrap();
+    assert_eq!(
+        model.get_formatted_cell_value(0, 10, 4),
+        Ok("Q1".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 10, 3),
+        Ok("Q4".to_string())
+    );
+
+    // A single date goes back one day at a time
+    model.set_user_input(0, 20, 1, "2024-03-01").unwrap();
+    fill_down(&mut model, &column_area(20, 1, 1), 18, AutoFillMode::Auto);
+    assert_eq!(values(&model, 1, 18..=19), vec!["2024-02-28", "2024-02-29"]);
+}
@@ -1,18 +1,16 @@
 #![allow(clippy::unwrap_used)]
 #![allow(clippy::panic)]
 
-use bitcode::decode;
-
 use crate::{
     constants::{LAST_COLUMN, LAST_ROW},
+    diffs::{self, Diff},
     test::util::new_empty_model,
-    user_model::history::{Diff, QueueDiffs},
     UserModel,
 };
 
 fn last_diff_list(model: &mut UserModel) -> Vec<Diff> {
     let bytes = model.flush_send_queue();
-    let queue: Vec<QueueDiffs> = decode(&bytes).unwrap();
+    let queue = diffs::from_bytes(&bytes).unwrap().changes;
     // Get the last operation's diff list
     queue.last().unwrap().list.clone()
 }
@@ -112,6 +110,7 @@ fn diff_order_delete_rows() {
             row,
             count,
             old_data,
+            old_merged_cells: _,
         } => {
             assert_eq!(*sheet, 0);
             assert_eq!(*row, 5);
@@ -188,6 +187,7 @@ fn delete_empty_rows() {
             row,
             count,
             old_data,
+            old_merged_cells: _,
         } => {
             assert_eq!(*sheet, 0);
             assert_eq!(*row, 5);
@@ -231,6 +231,7 @@ fn delete_mixed_empty_and_filled_rows() {
             row,
             count,
             old_data,
+            old_merged_cells: _,
         } => {
             assert_eq!(*sheet, 0);
             assert_eq!(*row, 5);
@@ -364,6 +365,7 @@ fn bulk_delete_rows_round_trip() {
             row,
             count,
             old_data,
+            old_merged_cells: _,
         } => {
             assert_eq!(*sheet, 0);
             assert_eq!(*row, 3);
@@ -431,6 +433,7 @@ fn bulk_delete_columns_round_trip() {
             column,
             count,
             old_data,
+            old_merged_cells: _,
         } => {
             assert_eq!(*sheet, 0);
             assert_eq!(*column, 3);
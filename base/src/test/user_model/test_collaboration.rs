@@ -0,0 +1,567 @@
+#![allow(clippy::unwrap_used)]
+
+use std::collections::VecDeque;
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::DateSystem;
+use crate::{diffs, CollaborationServer, UserModel};
+
+struct Session<'a> {
+    server: CollaborationServer,
+    clients: Vec<UserModel<'a>>,
+    inboxes: Vec<VecDeque<Vec<u8>>>,
+}
+
+impl<'a> Session<'a> {
+    fn new(base: &UserModel, client_count: u32) -> Session<'a> {
+        let bytes = base.to_bytes();
+        let clients = (0..client_count)
+            .map(|client_id| {
+                let mut client = UserModel::from_bytes(&bytes, "en").unwrap();
+                client.start_collaboration(client_id, 0);
+                client
+            })
+            .collect();
+        let mut server = CollaborationServer::new(0);
+        for client_id in 0..client_count {
+            server.add_client(client_id);
+        }
+        Session {
+            server,
+            clients,
+            inboxes: (0..client_count).map(|_| VecDeque::new()).collect(),
+        }
+    }
+
+    // Sends the pending operation of the client to the server, if any
+    fn send(&mut self, client: usize) {
+        if let Some(operation) = self.clients[client].take_pending_operation() {
+            let committed = self.server.commit(&operation).unwrap();
+            for inbox in self.inboxes.iter_mut() {
+                inbox.push_back(committed.clone());
+            }
+        }
+    }
+
+    // Applies the next committed operation in the inbox of the client, if any
+    fn receive(&mut self, client: usize) {
+        if let Some(committed) = self.inboxes[client].pop_front() {
+            self.clients[client]
+                .apply_committed_operation(&committed)
+                .unwrap();
+        }
+    }
+
+    fn sync(&mut self) {
+        loop {
+            for client in 0..self.clients.len() {
+                self.send(client);
+                while !self.inboxes[client].is_empty() {
+                    self.receive(client);
+                }
+            }
+            if self.clients.iter().all(|c| !c.has_pending_changes())
+                && self.inboxes.iter().all(|inbox| inbox.is_empty())
+            {
+                break;
+            }
+        }
+    }
+
+    fn assert_converged(&self) {
+        let revision = self.server.get_revision();
+        let first = contents(&self.clients[0]);
+        for client in &self.clients {
+            assert_eq!(client.get_revision(), Some(revision));
+            assert_eq!(contents(client), first);
+        }
+    }
+}
+
+fn contents(model: &UserModel) -> Vec<String> {
+    let mut result = Vec::new();
+    for (sheet, properties) in model.get_worksheets_properties().iter().enumerate() {
+        let sheet = sheet as u32;
+        result.push(properties.name.clone());
+        for row in 1..=30 {
+            for column in 1..=10 {
+                result.push(model.get_cell_content(sheet, row, column).unwrap());
+                result.push(model.get_formatted_cell_value(sheet, row, column).unwrap());
+            }
+        }
+    }
+    result
+}
+
+fn base_model<'a>() -> UserModel<'a> {
+    let mut model = new_empty_user_model();
+    for row in 1..=5 {
+        model.set_user_input(0, row, 1, &row.to_string()).unwrap();
+    }
+    model
+}
+
+// Sheet1, Sheet2 and Sheet3 with the index of the sheet plus one in A1
+fn sheets_model<'a>() -> UserModel<'a> {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.new_sheet().unwrap();
+    for sheet in 0..3 {
+        model
+            .set_user_input(sheet, 1, 1, &(sheet + 1).to_string())
+            .unwrap();
+    }
+    model
+}
+
+#[test]
+fn insert_rows_and_set_value() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].insert_rows(0, 2, 2).unwrap();
+    session.clients[1].set_user_input(0, 3, 2, "=A4*2").unwrap();
+    session.clients[1]
+        .set_user_input(0, 1, 2, "Header")
+        .unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    assert_eq!(client.get_cell_content(0, 5, 2), Ok("=A6*2".to_string()));
+    assert_eq!(
+        client.get_formatted_cell_value(0, 5, 2),
+        Ok("8".to_string())
+    );
+    assert_eq!(client.get_cell_content(0, 1, 2), Ok("Header".to_string()));
+}
+
+#[test]
+fn delete_columns_drops_edits_in_deleted_cells() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].delete_columns(0, 2, 2).unwrap();
+    session.clients[1].set_user_input(0, 1, 3, "Gone").unwrap();
+    session.clients[1].set_user_input(0, 1, 5, "=A1+1").unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[1];
+    assert_eq!(client.get_cell_content(0, 1, 3), Ok("=A1+1".to_string()));
+    assert_eq!(client.get_cell_content(0, 1, 5), Ok("".to_string()));
+    assert_eq!(
+        client.get_formatted_cell_value(0, 1, 3),
+        Ok("2".to_string())
+    );
+}
+
+#[test]
+fn move_row_and_set_value() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].move_row_action(0, 1, 3).unwrap();
+    session.clients[1].set_user_input(0, 1, 2, "One").unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    assert_eq!(client.get_cell_content(0, 4, 1), Ok("1".to_string()));
+    assert_eq!(client.get_cell_content(0, 4, 2), Ok("One".to_string()));
+}
+
+#[test]
+fn move_range_and_insert_rows() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    let source = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 2,
+    };
+    // Rows inserted between the block and where it lands, committed before the move
+    session.clients[0].insert_rows(0, 3, 2).unwrap();
+    session.clients[0].set_user_input(0, 6, 4, "next").unwrap();
+    session.clients[1].move_range(&source, 4, 3).unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    assert_eq!(client.get_cell_content(0, 1, 1), Ok("".to_string()));
+    assert_eq!(client.get_cell_content(0, 6, 3), Ok("1".to_string()));
+    assert_eq!(client.get_cell_content(0, 7, 3), Ok("2".to_string()));
+    assert_eq!(client.get_cell_content(0, 6, 4), Ok("next".to_string()));
+}
+
+#[test]
+fn concurrent_insertions_at_the_same_place() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].insert_rows(0, 2, 1).unwrap();
+    session.clients[0].set_user_input(0, 2, 1, "first").unwrap();
+    session.clients[1].insert_rows(0, 2, 2).unwrap();
+    session.clients[1]
+        .set_user_input(0, 2, 1, "second")
+        .unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    assert_eq!(client.get_cell_content(0, 1, 1), Ok("1".to_string()));
+    assert_eq!(client.get_cell_content(0, 2, 1), Ok("first".to_string()));
+    assert_eq!(client.get_cell_content(0, 3, 1), Ok("second".to_string()));
+    assert_eq!(client.get_cell_content(0, 5, 1), Ok("2".to_string()));
+}
+
+#[test]
+fn local_changes_survive_remote_operations() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].set_user_input(0, 10, 1, "mine").unwrap();
+    session.send(0);
+    // A change made while the first one is in flight
+    session.clients[0]
+        .set_user_input(0, 11, 1, "also mine")
+        .unwrap();
+    session.clients[1].insert_rows(0, 1, 1).unwrap();
+    session.send(1);
+
+    // Client 0 receives its own operation and the remote insertion
+    session.receive(0);
+    session.receive(0);
+    let client = &session.clients[0];
+    assert_eq!(client.get_cell_content(0, 11, 1), Ok("mine".to_string()));
+    assert_eq!(
+        client.get_cell_content(0, 12, 1),
+        Ok("also mine".to_string())
+    );
+    assert!(client.has_pending_changes());
+
+    session.sync();
+    session.assert_converged();
+
+    // Undo still targets the right cell
+    session.clients[0].undo().unwrap();
+    assert_eq!(
+        session.clients[0].get_cell_content(0, 12, 1),
+        Ok("".to_string())
+    );
+    session.sync();
+    session.assert_converged();
+}
+
+#[test]
+fn out_of_order_operation() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].set_user_input(0, 1, 1, "a").unwrap();
+    session.send(0);
+    session.clients[0].set_user_input(0, 1, 1, "b").unwrap();
+    session.receive(0);
+    session.send(0);
+    let first = session.inboxes[1].pop_front().unwrap();
+    let second = session.inboxes[1].pop_front().unwrap();
+    assert_eq!(
+        session.clients[1].apply_committed_operation(&second),
+        Err("Expected revision 1, got 2".to_string())
+    );
+    session.clients[1]
+        .apply_committed_operation(&first)
+        .unwrap();
+    session.clients[1]
+        .apply_committed_operation(&second)
+        .unwrap();
+    session.sync();
+    session.assert_converged();
+}
+
+#[test]
+fn operations_use_the_diff_wire_format() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].set_user_input(0, 1, 2, "x").unwrap();
+    let operation = session.clients[0].take_pending_operation().unwrap();
+    // Client id and base revision, then the cell tables and the diffs
+    assert_eq!(&operation[0..12], &[0; 12]);
+    let tables_len = u32::from_le_bytes(operation[12..16].try_into().unwrap()) as usize;
+    let tables: serde_json::Value =
+        serde_json::from_slice(&operation[16..16 + tables_len]).unwrap();
+    assert!(tables.get("strings").is_some());
+    let decoded = diffs::from_bytes(&operation[16 + tables_len..]).unwrap();
+    assert_eq!(decoded.changes.len(), 1);
+
+    let committed = session.server.commit(&operation).unwrap();
+    // Revision and oldest base revision, then the operation
+    assert_eq!(&committed[0..8], &1u64.to_le_bytes());
+    assert_eq!(&committed[8..16], &0u64.to_le_bytes());
+    assert_eq!(&committed[16..], &operation[..]);
+
+    assert!(session.server.commit(&operation[0..8]).is_err());
+    assert!(session.server.commit(&[0; 20]).is_err());
+    assert_eq!(session.server.get_revision(), 1);
+}
+
+#[test]
+fn undo_restores_cells_by_content() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    // Each client adds the strings, formulas and styles in a different order
+    session.clients[0].set_user_input(0, 1, 2, "first").unwrap();
+    session.clients[0].set_user_input(0, 2, 2, "=A1*2").unwrap();
+    session.clients[1]
+        .set_user_input(0, 1, 3, "second")
+        .unwrap();
+    session.clients[1].set_user_input(0, 2, 3, "=A1*3").unwrap();
+    session.clients[1].set_user_input(0, 3, 3, "50%").unwrap();
+    session.clients[0].set_user_input(0, 3, 2, "$5").unwrap();
+    session.sync();
+
+    for row in 1..=3 {
+        session.clients[0]
+            .set_user_input(0, row, 2, "other")
+            .unwrap();
+        session.clients[0].undo().unwrap();
+    }
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[1];
+    assert_eq!(client.get_cell_content(0, 1, 2), Ok("first".to_string()));
+    assert_eq!(client.get_cell_content(0, 2, 2), Ok("=A1*2".to_string()));
+    assert_eq!(
+        client.get_formatted_cell_value(0, 3, 2),
+        Ok("$5".to_string())
+    );
+}
+
+#[test]
+fn move_sheet_and_set_value() {
+    let base = sheets_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].move_sheet(2, 0).unwrap();
+    session.clients[1]
+        .set_user_input(2, 2, 1, "=A1*10")
+        .unwrap();
+    session.clients[1].set_user_input(0, 2, 1, "first").unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[1];
+    assert_eq!(client.get_worksheets_properties()[0].name, "Sheet3");
+    assert_eq!(
+        client.get_formatted_cell_value(0, 2, 1),
+        Ok("30".to_string())
+    );
+    assert_eq!(client.get_cell_content(1, 2, 1), Ok("first".to_string()));
+}
+
+#[test]
+fn delete_sheet_drops_its_edits() {
+    let base = sheets_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].delete_sheet(1).unwrap();
+    session.clients[1].set_user_input(1, 2, 1, "gone").unwrap();
+    session.clients[1].set_user_input(2, 2, 1, "kept").unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    assert_eq!(client.get_worksheets_properties().len(), 2);
+    assert_eq!(client.get_worksheets_properties()[1].name, "Sheet3");
+    assert_eq!(client.get_cell_content(1, 2, 1), Ok("kept".to_string()));
+    assert_eq!(client.get_cell_content(0, 2, 1), Ok("".to_string()));
+}
+
+#[test]
+fn duplicate_sheet_and_edit_the_next_sheet() {
+    let base = sheets_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].duplicate_sheet(0, "Copy").unwrap();
+    session.clients[1].set_user_input(1, 2, 1, "two").unwrap();
+    session.clients[1].new_sheet().unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    let names: Vec<String> = client
+        .get_worksheets_properties()
+        .into_iter()
+        .map(|properties| properties.name)
+        .collect();
+    assert_eq!(names, vec!["Sheet1", "Copy", "Sheet2", "Sheet3", "Sheet4"]);
+    assert_eq!(client.get_cell_content(2, 2, 1), Ok("two".to_string()));
+
+    // Undoing the duplicate removes the copy
+    session.clients[0].undo().unwrap();
+    session.sync();
+    session.assert_converged();
+    assert_eq!(session.clients[1].get_worksheets_properties().len(), 4);
+}
+
+#[test]
+fn insert_rows_and_undo_date_system() {
+    let mut base = new_empty_user_model();
+    base.set_user_input(0, 1, 1, "1901-03-15").unwrap();
+    let serial_number = base.get_cell_content(0, 1, 1).unwrap();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].insert_rows(0, 1, 1).unwrap();
+    session.clients[1]
+        .set_date_system(DateSystem::Excel1904)
+        .unwrap();
+    session.sync();
+    session.assert_converged();
+
+    // The shifted date moved down with its row
+    session.clients[1].undo().unwrap();
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[0];
+    assert_eq!(client.get_date_system(), DateSystem::Excel1900);
+    assert_eq!(client.get_cell_content(0, 2, 1), Ok(serial_number));
+    assert_eq!(
+        client.get_formatted_cell_value(0, 2, 1),
+        Ok("1901-03-15".to_string())
+    );
+}
+
+#[test]
+fn rename_sheet_and_type_formula() {
+    let base = sheets_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].rename_sheet(1, "Data").unwrap();
+    session.clients[1]
+        .set_user_input(0, 2, 1, "=Sheet2!A1*10")
+        .unwrap();
+
+    session.sync();
+    session.assert_converged();
+    let client = &session.clients[1];
+    assert_eq!(
+        client.get_cell_content(0, 2, 1),
+        Ok("=Data!A1*10".to_string())
+    );
+    assert_eq!(
+        client.get_formatted_cell_value(0, 2, 1),
+        Ok("20".to_string())
+    );
+}
+
+#[test]
+fn old_revisions_are_dropped() {
+    let base = base_model();
+    let mut session = Session::new(&base, 2);
+    session.clients[0].set_user_input(0, 1, 2, "a").unwrap();
+    session.send(0);
+    session.receive(0);
+    session.receive(1);
+    session.clients[1].set_user_input(0, 1, 3, "b").unwrap();
+    session.send(1);
+    session.clients[0].set_user_input(0, 1, 4, "c").unwrap();
+    session.send(0);
+    session.sync();
+    session.assert_converged();
+    assert_eq!(session.server.get_revision(), 3);
+
+    // A client that joined at revision 0 and was never added can't send anything now
+    let mut late = UserModel::from_bytes(&base.to_bytes(), "en").unwrap();
+    late.start_collaboration(7, 0);
+    late.set_user_input(0, 1, 5, "late").unwrap();
+    let operation = late.take_pending_operation().unwrap();
+    assert_eq!(
+        session.server.commit(&operation),
+        Err("Operation based on revision 0 that is no longer available".to_string())
+    );
+
+    // Clients that leave do not hold the revisions back
+    session.server.remove_client(1);
+    session.clients[0].set_user_input(0, 1, 6, "d").unwrap();
+    session.send(0);
+    session.receive(0);
+    assert_eq!(session.clients[0].get_revision(), Some(4));
+}
+
+// A small deterministic pseudo random generator, so the test is reproducible
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, max: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % max
+    }
+}
+
+#[test]
+fn random_sessions_converge() {
+    for seed in 0..20 {
+        let base = base_model();
+        let mut session = Session::new(&base, 3);
+        let mut random = Random(seed);
+        for step in 0..60 {
+            let client = random.next(3) as usize;
+            match random.next(4) {
+                0 | 1 => {
+                    let model = &mut session.clients[client];
+                    let row = random.next(12) as i32 + 1;
+                    let column = random.next(5) as i32 + 1;
+                    let _ = match random.next(12) {
+                        0 => model.insert_rows(0, row, random.next(2) as i32 + 1),
+                        1 => model.delete_rows(0, row, random.next(2) as i32 + 1),
+                        2 => model.insert_columns(0, column, 1),
+                        3 => model.delete_columns(0, column, 1),
+                        4 => model.move_row_action(0, row, random.next(5) as i32 - 2),
+                        5 => model.move_column_action(0, column, random.next(3) as i32 - 1),
+                        6 => model.undo(),
+                        7 => model.redo(),
+                        8 => model.set_user_input(0, row, column, "=A1+B2"),
+                        9 => model.set_user_input(0, row, column, &format!("=SUM(A{row}:C5)")),
+                        _ => model.set_user_input(0, row, column, &step.to_string()),
+                    };
+                }
+                2 => session.send(client),
+                _ => session.receive(client),
+            }
+        }
+        session.sync();
+        session.assert_converged();
+    }
+}
+
+#[test]
+fn random_sheet_sessions_converge() {
+    for seed in 0..20 {
+        let base = sheets_model();
+        let mut session = Session::new(&base, 3);
+        let mut random = Random(seed);
+        for step in 0..60 {
+            let client = random.next(3) as usize;
+            match random.next(4) {
+                0 | 1 => {
+                    let model = &mut session.clients[client];
+                    let sheet_count = model.get_worksheets_properties().len() as u64;
+                    let sheet = random.next(sheet_count) as u32;
+                    let other = random.next(sheet_count) as u32;
+                    let row = random.next(5) as i32 + 1;
+                    let _ = match random.next(10) {
+                        0 => model.new_sheet(),
+                        1 => model.delete_sheet(sheet),
+                        2 => model.move_sheet(sheet, other),
+                        3 => model.duplicate_sheet(sheet, &format!("Copy{seed}_{step}")),
+                        4 => model.rename_sheet(sheet, &format!("Name{seed}_{step}")),
+                        5 => model.insert_rows(sheet, row, 1),
+                        6 => model.undo(),
+                        7 => model.redo(),
+                        8 => model.set_user_input(sheet, row, 2, "=Sheet1!A1+1"),
+                        _ => model.set_user_input(sheet, row, 1, &step.to_string()),
+                    };
+                }
+                2 => session.send(client),
+                _ => session.receive(client),
+            }
+        }
+        session.sync();
+        session.assert_converged();
+    }
+}
@@ -3,6 +3,7 @@
 use crate::constants::{DEFAULT_COLUMN_WIDTH, DEFAULT_ROW_HEIGHT, LAST_COLUMN, LAST_ROW};
 use crate::expressions::types::Area;
 use crate::test::user_model::util::new_empty_user_model;
+use crate::types::Color;
 
 #[test]
 fn column_width() {
@@ -18,9 +19,9 @@ fn column_width() {
     let style = model.get_cell_style(0, 1, 1).unwrap();
     assert!(!style.font.i);
     assert!(!style.font.b);
-    assert!(!style.font.u);
+    assert!(!style.font.is_underlined());
     assert!(!style.font.strike);
-    assert_eq!(style.font.color, Some("#000000".to_owned()));
+    assert_eq!(style.font.color, Some(Color::Rgb("#000000".to_string())));
 
     // Set the whole column style and check it works
     model.update_range_style(&range, "font.b", "true").unwrap();
@@ -77,19 +78,19 @@ fn existing_style() {
 
     // Get the style of G123
     let style = model.get_cell_style(0, 123, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 
     model.undo().unwrap();
 
     // Check the style of G123 is now what it was before
     let style = model.get_cell_style(0, 123, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 
     model.redo().unwrap();
 
     // Check G123 has the column style now
     let style = model.get_cell_style(0, 123, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 }
 
 #[test]
@@ -125,12 +126,12 @@ fn row_column() {
 
     // Check G3 has the column style
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 
     // undo twice. Color must be default
     model.undo().unwrap();
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
     model.undo().unwrap();
     let style = model.get_cell_style(0, 3, 7).unwrap();
     assert_eq!(style.fill.bg_color, None);
@@ -170,13 +171,13 @@ fn column_row() {
 
     // Check G3 has the row style
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 
     model.undo().unwrap();
 
     // Check G3 has the column style
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 
     model.undo().unwrap();
 
@@ -324,11 +325,11 @@ fn cell_row_undo() {
         .unwrap();
 
     let style = model.get_cell_style(0, 12, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#CCC111".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#CCC111".to_string())));
     model.undo().unwrap();
 
     let style = model.get_cell_style(0, 12, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 }
 
 #[test]
@@ -362,7 +363,7 @@ fn set_column_style_then_cell() {
         .unwrap();
 
     let style = model.get_cell_style(0, 12, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 
     model.undo().unwrap();
     model.undo().unwrap();
@@ -401,7 +402,7 @@ fn set_row_style_then_cell() {
         .unwrap();
 
     let style = model.get_cell_style(0, 12, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 }
 
 #[test]
@@ -429,7 +430,7 @@ fn column_style_then_row_alignment() {
         .unwrap();
     // check the row alignment does not affect the column style
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 }
 
 #[test]
@@ -500,5 +501,5 @@ fn test_row_column_column() {
 
     // test E5 has the column style
     let style = model.get_cell_style(0, 5, 5).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#CCC111".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#CCC111".to_string())));
 }
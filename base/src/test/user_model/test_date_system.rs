@@ -0,0 +1,113 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::util::new_empty_model;
+use crate::types::DateSystem;
+use crate::UserModel;
+
+#[test]
+fn undo_redo_date_system() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_user_input(0, 1, 1, "2024-01-05").unwrap();
+    assert_eq!(model.get_date_system(), DateSystem::Excel1900);
+
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    assert_eq!(model.get_date_system(), DateSystem::Excel1904);
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("2024-01-05".to_string())
+    );
+
+    model.undo().unwrap();
+    assert_eq!(model.get_date_system(), DateSystem::Excel1900);
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("2024-01-05".to_string())
+    );
+
+    model.redo().unwrap();
+    assert_eq!(model.get_date_system(), DateSystem::Excel1904);
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("2024-01-05".to_string())
+    );
+
+    let bytes = model.to_bytes();
+    let model = UserModel::from_bytes(&bytes, "en").unwrap();
+    assert_eq!(model.get_date_system(), DateSystem::Excel1904);
+}
+
+#[test]
+fn times_are_not_shifted() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_user_input(0, 1, 1, "0.5").unwrap();
+    model.set_user_input(0, 2, 1, "1.5").unwrap();
+    model.set_user_input(0, 3, 1, "2024-01-05 12:00").unwrap();
+    for row in 1..=2 {
+        let area = Area {
+            sheet: 0,
+            row,
+            column: 1,
+            width: 1,
+            height: 1,
+        };
+        model.update_range_style(&area, "num_fmt", "h:mm").unwrap();
+    }
+    let date_time = model.get_formatted_cell_value(0, 3, 1).unwrap();
+
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("12:00".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("0.5".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("1.5".to_string()));
+    assert_eq!(model.get_formatted_cell_value(0, 3, 1), Ok(date_time));
+}
+
+#[test]
+fn same_date_system_is_not_an_undo_step() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_date_system(DateSystem::Excel1900).unwrap();
+    assert!(!model.can_undo());
+}
+
+#[test]
+fn undo_keeps_dates_before_1904() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_user_input(0, 1, 1, "1901-03-15").unwrap();
+    let serial_number = model.get_cell_content(0, 1, 1).unwrap();
+
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok(serial_number.clone()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("1901-03-15".to_string())
+    );
+
+    model.redo().unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok(serial_number));
+}
+
+#[test]
+fn first_day_of_1904_is_shifted() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    model.set_user_input(0, 1, 1, "1904-01-01").unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("0".to_string()));
+
+    model.set_date_system(DateSystem::Excel1900).unwrap();
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("1904-01-01".to_string())
+    );
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("0".to_string()));
+}
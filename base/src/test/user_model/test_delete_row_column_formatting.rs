@@ -4,6 +4,7 @@ use crate::{
     constants::{DEFAULT_COLUMN_WIDTH, DEFAULT_ROW_HEIGHT, LAST_COLUMN, LAST_ROW},
     expressions::types::Area,
     test::user_model::util::new_empty_user_model,
+    types::Color,
 };
 
 #[test]
@@ -60,7 +61,7 @@ fn delete_column_formatting() {
 
     // Check the style of the whole row is still there
     let style = model.get_cell_style(0, 3, 1).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 
     // Check the style of the whole column is now gone
     let style = model.get_cell_style(0, 3, 7).unwrap();
@@ -73,15 +74,15 @@ fn delete_column_formatting() {
 
     // Check the style of G123 is now what it was before
     let style = model.get_cell_style(0, 123, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#FF5533".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#FF5533".to_string())));
 
     // Check G3 is the row style
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 
     // Check G40 is the column style
     let style = model.get_cell_style(0, 40, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 
     model.redo().unwrap();
 
@@ -91,7 +92,7 @@ fn delete_column_formatting() {
 
     // Check the style of the whole row is still there
     let style = model.get_cell_style(0, 3, 1).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#333444".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#333444".to_string())));
 
     // Check the style of the whole column is now gone
     let style = model.get_cell_style(0, 3, 7).unwrap();
@@ -192,7 +193,7 @@ fn column_row_style_undo() {
 
     // G123 has the row style
     let style = model.get_cell_style(0, 123, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#111222".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#111222".to_string())));
 
     // undo twice
     model.undo().unwrap();
@@ -239,5 +240,5 @@ fn column_row_row_height_undo() {
 
     // check G3 has the column style
     let style = model.get_cell_style(0, 3, 7).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#555666".to_string()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#555666".to_string())));
 }
@@ -0,0 +1,212 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::diffs::{self, Diff, DiffType, QueueDiffs, FORMAT_VERSION};
+use crate::test::user_model::util::new_empty_user_model;
+use crate::UserModel;
+
+fn sample_changes() -> Vec<QueueDiffs> {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Hello").unwrap();
+    model.set_user_input(0, 2, 1, "=1/0").unwrap();
+    model.set_user_input(0, 3, 1, "42.5").unwrap();
+    model.set_user_input(0, 3, 2, "TRUE").unwrap();
+    model.set_rows_height(0, 2, 2, 40.0).unwrap();
+    model.delete_rows(0, 2, 2).unwrap();
+    model.undo().unwrap();
+    model.new_sheet().unwrap();
+    model.set_user_input(1, 5, 5, "=Sheet1!A1").unwrap();
+    model.delete_sheet(1).unwrap();
+    let bytes = model.flush_send_queue();
+    diffs::from_bytes(&bytes).unwrap().changes
+}
+
+#[test]
+fn json_round_trip() {
+    let changes = sample_changes();
+    let json = diffs::to_json(&changes);
+    let decoded = diffs::from_json(&json).unwrap();
+    assert_eq!(decoded.version, FORMAT_VERSION);
+    assert!(decoded.unknown.is_empty());
+    assert_eq!(decoded.changes, changes);
+}
+
+#[test]
+fn binary_round_trip() {
+    let changes = sample_changes();
+    let bytes = diffs::to_bytes(&changes);
+    assert_eq!(&bytes[0..4], b"ICDF");
+    // The binary form is much smaller than the JSON one
+    assert!(bytes.len() < diffs::to_json(&changes).len());
+    let decoded = diffs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.version, FORMAT_VERSION);
+    assert_eq!(decoded.changes, changes);
+}
+
+#[test]
+fn integer_keys_are_not_in_the_key_table() {
+    let mut model = new_empty_user_model();
+    for column in 1..=300 {
+        model
+            .set_user_input(0, 2, column, &column.to_string())
+            .unwrap();
+    }
+    model.delete_rows(0, 2, 1).unwrap();
+    let changes = diffs::from_bytes(&model.flush_send_queue())
+        .unwrap()
+        .changes;
+    let bytes = diffs::to_bytes(&changes);
+    assert_eq!(diffs::from_bytes(&bytes).unwrap().changes, changes);
+
+    // The cells of the deleted row are keyed by column
+    let (key_count, mut position) = (bytes[5] as usize, 6);
+    let mut keys = Vec::new();
+    for _ in 0..key_count {
+        let length = bytes[position] as usize;
+        keys.push(String::from_utf8(bytes[position + 1..position + 1 + length].to_vec()).unwrap());
+        position += 1 + length;
+    }
+    assert!(keys.contains(&"old_data".to_string()));
+    assert!(keys.iter().all(|key| key.parse::<i64>().is_err()));
+}
+
+#[test]
+fn json_shape() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 2, "=A1*2").unwrap();
+    model.undo().unwrap();
+    let changes = diffs::from_bytes(&model.flush_send_queue())
+        .unwrap()
+        .changes;
+    let json: serde_json::Value = serde_json::from_str(&diffs::to_json(&changes)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "version": 1,
+            "changes": [
+                {
+                    "type": "redo",
+                    "diffs": [{
+                        "op": "set_cell_value",
+                        "sheet": 0,
+                        "row": 1,
+                        "column": 2,
+                        "new_value": "=A1*2",
+                        "old_value": null
+                    }]
+                },
+                {
+                    "type": "undo",
+                    "diffs": [{
+                        "op": "set_cell_value",
+                        "sheet": 0,
+                        "row": 1,
+                        "column": 2,
+                        "new_value": "=A1*2",
+                        "old_value": null
+                    }]
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn unknown_diffs_are_reported() {
+    // A document written by a newer version, with a new kind of diff and a new field
+    let json = r#"{
+        "version": 7,
+        "changes": [{
+            "type": "redo",
+            "diffs": [
+                {"op": "set_cell_value", "sheet": 0, "row": 1, "column": 1, "new_value": "1", "old_value": null, "author": "Ada"},
+                {"op": "insert_chart", "sheet": 0},
+                {"op": "insert_rows", "sheet": 0, "row": 2},
+                {"sheet": 0}
+            ]
+        }]
+    }"#;
+    let decoded = diffs::from_json(json).unwrap();
+    assert_eq!(decoded.version, 7);
+    assert_eq!(
+        decoded.changes,
+        vec![QueueDiffs {
+            r#type: DiffType::Redo,
+            list: vec![Diff::SetCellValue {
+                sheet: 0,
+                row: 1,
+                column: 1,
+                new_value: "1".to_string(),
+                old_value: Box::new(None),
+            }]
+        }]
+    );
+    let unknown: Vec<(usize, &str)> = decoded
+        .unknown
+        .iter()
+        .map(|u| (u.index, u.op.as_str()))
+        .collect();
+    assert_eq!(
+        unknown,
+        vec![(1, "insert_chart"), (2, "insert_rows"), (3, "")]
+    );
+    assert!(decoded.unknown[1].reason.contains("count"));
+}
+
+#[test]
+fn invalid_documents() {
+    assert!(diffs::from_json("[]").is_err());
+    assert!(diffs::from_json(r#"{"changes": []}"#).is_err());
+    assert!(
+        diffs::from_json(r#"{"version": 1, "changes": [{"type": "sideways", "diffs": []}]}"#)
+            .is_err()
+    );
+
+    let bytes = diffs::to_bytes(&sample_changes());
+    assert!(diffs::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(diffs::from_bytes(&bytes[1..]).is_err());
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert!(diffs::from_bytes(&extra).is_err());
+}
+
+#[test]
+fn external_diffs_with_unknown_ops_are_not_applied() {
+    // [{"type": "redo", "diffs": [{"op": "insert_chart", "sheet": 0}]}] written by hand
+    let mut bytes = b"ICDF\x02\x04".to_vec();
+    for key in ["type", "diffs", "op", "sheet"] {
+        bytes.push(key.len() as u8);
+        bytes.extend(key.as_bytes());
+    }
+    bytes.extend([7, 1, 8, 2, 0, 6, 4]);
+    bytes.extend(b"redo");
+    bytes.extend([1, 7, 1, 8, 2, 2, 6, 12]);
+    bytes.extend(b"insert_chart");
+    bytes.extend([3, 3, 0]);
+
+    let decoded = diffs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.version, 2);
+    assert_eq!(decoded.unknown.len(), 1);
+    assert_eq!(decoded.unknown[0].op, "insert_chart");
+
+    let mut model = new_empty_user_model();
+    assert_eq!(
+        model.apply_external_diffs(&bytes),
+        Err("Unknown diffs: insert_chart".to_string())
+    );
+}
+
+#[test]
+fn external_diffs_round_trip() {
+    let mut model = UserModel::from_model(crate::test::util::new_empty_model());
+    let changes = sample_changes();
+    model
+        .apply_external_diffs(&diffs::to_bytes(&changes))
+        .unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("Hello".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 2, 1),
+        Ok("#DIV/0!".to_string())
+    );
+    assert_eq!(model.get_row_height(0, 2), Ok(40.0));
+    assert_eq!(model.get_worksheets_properties().len(), 1);
+}
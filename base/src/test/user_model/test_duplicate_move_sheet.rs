@@ -0,0 +1,270 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{Comment, Table, TableColumn, TableStyleInfo};
+use crate::{ProtectionError, UserModel};
+
+fn sheet_names(model: &UserModel) -> Vec<String> {
+    model
+        .get_worksheets_properties()
+        .into_iter()
+        .map(|p| p.name)
+        .collect()
+}
+
+fn add_table(model: &mut UserModel, name: &str, sheet_name: &str) {
+    model.model.workbook.tables.insert(
+        name.to_string(),
+        Table {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            sheet_name: sheet_name.to_string(),
+            reference: "A1:B3".to_string(),
+            totals_row_count: 0,
+            header_row_count: 1,
+            header_row_dxf_id: None,
+            data_dxf_id: None,
+            totals_row_dxf_id: None,
+            columns: vec![TableColumn {
+                id: 1,
+                name: "Name".to_string(),
+                ..Default::default()
+            }],
+            style_info: TableStyleInfo {
+                ..Default::default()
+            },
+            has_filters: false,
+            auto_filter: None,
+        },
+    );
+}
+
+#[test]
+fn duplicate_sheet() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.set_user_input(0, 1, 1, "21").unwrap();
+    model.set_user_input(0, 1, 2, "=A1*2").unwrap();
+    model.set_user_input(0, 1, 3, "=Sheet1!A1+1").unwrap();
+    model.set_user_input(0, 1, 4, "=Sheet2!A1").unwrap();
+    model.set_user_input(1, 1, 1, "=Sheet1!A1").unwrap();
+    model
+        .update_range_style(
+            &Area {
+                sheet: 0,
+                row: 1,
+                column: 1,
+                width: 1,
+                height: 1,
+            },
+            "font.b",
+            "true",
+        )
+        .unwrap();
+    model
+        .merge_cells(&Area {
+            sheet: 0,
+            row: 5,
+            column: 1,
+            width: 2,
+            height: 2,
+        })
+        .unwrap();
+    model.model.workbook.worksheets[0].comments.push(Comment {
+        text: "Check".to_string(),
+        author_name: "Ada".to_string(),
+        author_id: None,
+        cell_ref: "A1".to_string(),
+    });
+    model
+        .new_defined_name("Rate", Some(0), "Sheet1!$A$1")
+        .unwrap();
+    model
+        .new_defined_name("Total", None, "Sheet1!$B$1")
+        .unwrap();
+    add_table(&mut model, "Sales", "Sheet1");
+
+    model.duplicate_sheet(0, "Copy").unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet1", "Copy", "Sheet2"]);
+    assert_eq!(model.get_selected_sheet(), 1);
+
+    assert_eq!(
+        model.get_formatted_cell_value(1, 1, 2),
+        Ok("42".to_string())
+    );
+    // References to the sheet itself point to the copy
+    assert_eq!(
+        model.get_cell_content(1, 1, 3),
+        Ok("=Copy!A1+1".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(1, 1, 4),
+        Ok("=Sheet2!A1".to_string())
+    );
+    assert!(model.get_cell_style(1, 1, 1).unwrap().font.b);
+    assert_eq!(model.get_merged_cells(1).unwrap().len(), 1);
+    assert_eq!(model.model.workbook.worksheets[1].comments.len(), 1);
+    // Sheet2 moved, its formulas too
+    assert_eq!(
+        model.get_cell_content(2, 1, 1),
+        Ok("=Sheet1!A1".to_string())
+    );
+
+    // Local defined names are copied, global ones are not
+    let mut names = model.get_defined_name_list();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            ("Rate".to_string(), Some(0), "Sheet1!$A$1".to_string()),
+            ("Rate".to_string(), Some(1), "Copy!$A$1".to_string()),
+            ("Total".to_string(), None, "Sheet1!$B$1".to_string()),
+        ]
+    );
+
+    // Tables get a new name
+    let table = &model.model.workbook.tables["Sales_2"];
+    assert_eq!(table.sheet_name, "Copy");
+    assert_eq!(table.display_name, "Sales_2");
+    assert_eq!(model.model.workbook.tables["Sales"].sheet_name, "Sheet1");
+
+    // Changes in the copy don't change the original
+    model.set_user_input(1, 1, 1, "1").unwrap();
+    assert_eq!(model.get_formatted_cell_value(1, 1, 2), Ok("2".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 2),
+        Ok("42".to_string())
+    );
+    model.undo().unwrap();
+
+    model.undo().unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet1", "Sheet2"]);
+    // Sheet2 was selected before the copy
+    assert_eq!(model.get_selected_sheet(), 1);
+    assert_eq!(model.get_defined_name_list().len(), 2);
+    assert_eq!(model.model.workbook.tables.len(), 1);
+
+    model.redo().unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet1", "Copy", "Sheet2"]);
+    assert!(model.model.workbook.tables.contains_key("Sales_2"));
+    assert_eq!(
+        model.get_cell_content(1, 1, 3),
+        Ok("=Copy!A1+1".to_string())
+    );
+}
+
+#[test]
+fn duplicate_sheet_errors() {
+    let mut model = new_empty_user_model();
+    assert_eq!(
+        model.duplicate_sheet(0, "sheet1"),
+        Err("Sheet already exists: 'sheet1'.".to_string())
+    );
+    assert!(model.duplicate_sheet(0, "a:b").is_err());
+    assert!(model.duplicate_sheet(3, "Copy").is_err());
+    assert!(!model.can_undo());
+}
+
+#[test]
+fn move_sheet() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.new_sheet().unwrap();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(1, 1, 1, "2").unwrap();
+    model
+        .set_user_input(2, 1, 1, "=Sheet1!A1+Sheet2!A1")
+        .unwrap();
+    model.set_user_input(2, 1, 2, "=Rate*10").unwrap();
+    model
+        .new_defined_name("Rate", Some(2), "Sheet2!$A$1")
+        .unwrap();
+    model.set_selected_sheet(1).unwrap();
+
+    model.move_sheet(2, 0).unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet3", "Sheet1", "Sheet2"]);
+    // The selection follows the sheet
+    assert_eq!(model.get_selected_sheet(), 2);
+    assert_eq!(model.get_formatted_cell_value(0, 1, 1), Ok("3".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 2),
+        Ok("20".to_string())
+    );
+    assert_eq!(
+        model.get_defined_name_list(),
+        vec![("Rate".to_string(), Some(0), "Sheet2!$A$1".to_string())]
+    );
+
+    // The formulas still reference the right sheets
+    model.set_user_input(2, 1, 1, "5").unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 1, 1), Ok("6".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 2),
+        Ok("50".to_string())
+    );
+    model.undo().unwrap();
+
+    model.undo().unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet1", "Sheet2", "Sheet3"]);
+    assert_eq!(model.get_selected_sheet(), 1);
+    assert_eq!(
+        model.get_formatted_cell_value(2, 1, 2),
+        Ok("20".to_string())
+    );
+
+    model.redo().unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet3", "Sheet1", "Sheet2"]);
+
+    model.move_sheet(0, 2).unwrap();
+    assert_eq!(sheet_names(&model), vec!["Sheet1", "Sheet2", "Sheet3"]);
+    assert_eq!(model.get_formatted_cell_value(2, 1, 1), Ok("3".to_string()));
+
+    assert_eq!(
+        model.move_sheet(0, 3),
+        Err("Sheet index out of range".to_string())
+    );
+}
+
+#[test]
+fn locked_workbook_structure() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.protect_workbook(true, false, None).unwrap();
+    let error = Err(ProtectionError::ProtectedWorkbook.to_string());
+    assert_eq!(model.duplicate_sheet(0, "Copy"), error);
+    assert_eq!(model.move_sheet(0, 1), error);
+}
+
+#[test]
+fn duplicate_sheet_with_table_references() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    add_table(&mut model, "Sales", "Sheet1");
+    model.model.reset_parsed_structures();
+    model.set_user_input(0, 2, 1, "1").unwrap();
+    model.set_user_input(0, 3, 1, "2").unwrap();
+    model.set_user_input(0, 5, 1, "=SUM(Sales[Name])").unwrap();
+    model.set_user_input(0, 6, 1, "=ROWS(Sales)").unwrap();
+    model.set_user_input(1, 1, 1, "=SUM(Sales[Name])").unwrap();
+
+    model.duplicate_sheet(0, "Copy").unwrap();
+    // The references to the tables of the sheet point to the tables of the copy
+    assert_eq!(
+        model.get_cell_content(1, 6, 1),
+        Ok("=ROWS(Sales_2)".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(0, 6, 1),
+        Ok("=ROWS(Sales)".to_string())
+    );
+
+    model.set_user_input(1, 2, 1, "10").unwrap();
+    assert_eq!(
+        model.get_formatted_cell_value(1, 5, 1),
+        Ok("12".to_string())
+    );
+    assert_eq!(model.get_formatted_cell_value(0, 5, 1), Ok("3".to_string()));
+    // Other sheets keep pointing to the original table
+    assert_eq!(model.get_formatted_cell_value(2, 1, 1), Ok("3".to_string()));
+}
@@ -0,0 +1,128 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{Color, Fill, GradientFill, GradientStop, GradientType, PatternType, Theme};
+
+fn stop(position: f64, color: &str) -> GradientStop {
+    GradientStop {
+        position,
+        color: Some(Color::Rgb(color.to_string())),
+    }
+}
+
+#[test]
+fn pattern_type_path() {
+    let mut model = new_empty_user_model();
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    model
+        .update_range_style(&range, "fill.pattern_type", "darkTrellis")
+        .unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.fill.pattern_type, PatternType::DarkTrellis);
+
+    assert_eq!(
+        model.update_range_style(&range, "fill.pattern_type", "stripes"),
+        Err("Invalid pattern type: 'stripes'.".to_string())
+    );
+
+    model.undo().unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.fill.pattern_type, PatternType::None);
+}
+
+#[test]
+fn fill_colors() {
+    let theme = Theme::default();
+    assert_eq!(Fill::default().get_color(&theme), None);
+
+    let solid = Fill {
+        pattern_type: PatternType::Solid,
+        fg_color: Some(Color::Rgb("#FF0000".to_string())),
+        ..Default::default()
+    };
+    assert_eq!(solid.get_color(&theme), Some("#FF0000".to_string()));
+
+    // Half of the cell is black, half white
+    let pattern = Fill {
+        pattern_type: PatternType::MediumGray,
+        ..Default::default()
+    };
+    assert_eq!(pattern.get_color(&theme), Some("#808080".to_string()));
+
+    let gradient = GradientFill {
+        degree: 90.0,
+        stops: vec![stop(0.0, "#FFFFFF"), stop(1.0, "#0000FF")],
+        ..Default::default()
+    };
+    assert_eq!(
+        gradient.get_color_at(0.0, &theme),
+        Some("#FFFFFF".to_string())
+    );
+    assert_eq!(
+        gradient.get_color_at(0.25, &theme),
+        Some("#BFBFFF".to_string())
+    );
+    assert_eq!(
+        gradient.get_color_at(1.0, &theme),
+        Some("#0000FF".to_string())
+    );
+    let fill = Fill {
+        gradient: Some(gradient),
+        ..Default::default()
+    };
+    assert_eq!(fill.get_color(&theme), Some("#8080FF".to_string()));
+}
+
+#[test]
+fn gradient_follows_theme() {
+    let mut model = new_empty_user_model();
+    let mut style = model.get_cell_style(0, 1, 1).unwrap();
+    style.fill.gradient = Some(GradientFill {
+        gradient_type: GradientType::Path,
+        left: 0.5,
+        right: 0.5,
+        top: 0.5,
+        bottom: 0.5,
+        stops: vec![
+            stop(0.0, "#FFFFFF"),
+            GradientStop {
+                position: 1.0,
+                color: Some(Color::theme(4, 0.0)),
+            },
+        ],
+        ..Default::default()
+    });
+    model.on_paste_styles(&[vec![style]]).unwrap();
+
+    let mut theme = Theme::default();
+    theme.colors[4] = "#00FF00".to_string();
+    model.set_theme(theme).unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    let gradient = style.fill.gradient.unwrap();
+    assert_eq!(
+        gradient.stops[1].color,
+        Some(Color::Rgb("#00FF00".to_string()))
+    );
+
+    // Setting a colour replaces the gradient
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    model
+        .update_range_style(&range, "fill.fg_color", "#FF0000")
+        .unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.fill.gradient, None);
+    assert_eq!(style.fill.pattern_type, PatternType::Solid);
+}
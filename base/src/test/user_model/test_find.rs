@@ -0,0 +1,318 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{Comment, SheetProtection};
+use crate::{FindLookIn, FindOptions, FindOrder, FindScope, UserModel};
+
+fn cells(model: &UserModel, text: &str, options: &FindOptions) -> Vec<(u32, i32, i32)> {
+    model
+        .find(text, options)
+        .unwrap()
+        .map(|m| (m.sheet, m.row, m.column))
+        .collect()
+}
+
+fn sample_model() -> UserModel<'static> {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Apple").unwrap();
+    model.set_user_input(0, 1, 2, "pineapple").unwrap();
+    model.set_user_input(0, 2, 1, "=UPPER(A1)").unwrap();
+    model.set_user_input(0, 3, 1, "Banana").unwrap();
+    model.new_sheet().unwrap();
+    model.set_user_input(1, 1, 1, "apple pie").unwrap();
+    model
+}
+
+#[test]
+fn find_values() {
+    let model = sample_model();
+    let options = FindOptions::default();
+    assert_eq!(
+        cells(&model, "apple", &options),
+        vec![(0, 1, 1), (0, 1, 2), (0, 2, 1), (1, 1, 1)]
+    );
+
+    let options = FindOptions {
+        match_case: true,
+        ..Default::default()
+    };
+    assert_eq!(cells(&model, "apple", &options), vec![(0, 1, 2), (1, 1, 1)]);
+
+    let options = FindOptions {
+        whole_cell: true,
+        ..Default::default()
+    };
+    assert_eq!(cells(&model, "apple", &options), vec![(0, 1, 1), (0, 2, 1)]);
+
+    let matches: Vec<String> = model
+        .find("apple", &options)
+        .unwrap()
+        .map(|m| m.text)
+        .collect();
+    assert_eq!(matches, vec!["Apple", "APPLE"]);
+}
+
+#[test]
+fn find_formulas() {
+    let model = sample_model();
+    let options = FindOptions {
+        look_in: FindLookIn::Formulas,
+        ..Default::default()
+    };
+    assert_eq!(cells(&model, "upper", &options), vec![(0, 2, 1)]);
+    assert_eq!(
+        cells(&model, "apple", &options),
+        vec![(0, 1, 1), (0, 1, 2), (1, 1, 1)]
+    );
+}
+
+#[test]
+fn find_comments() {
+    let mut model = sample_model();
+    model.model.workbook.worksheets[0].comments.push(Comment {
+        text: "Check this apple".to_string(),
+        author_name: "Ada".to_string(),
+        author_id: None,
+        cell_ref: "C5".to_string(),
+    });
+    let options = FindOptions {
+        look_in: FindLookIn::Comments,
+        ..Default::default()
+    };
+    let matches: Vec<_> = model.find("apple", &options).unwrap().collect();
+    assert_eq!(matches.len(), 1);
+    assert_eq!((matches[0].row, matches[0].column), (5, 3));
+    assert_eq!(matches[0].text, "Check this apple");
+}
+
+#[test]
+fn wildcards_and_regex() {
+    let model = sample_model();
+    let options = FindOptions {
+        whole_cell: true,
+        ..Default::default()
+    };
+    assert_eq!(cells(&model, "b?n*", &options), vec![(0, 3, 1)]);
+    assert_eq!(cells(&model, "*pie", &options), vec![(1, 1, 1)]);
+    // Regular expression characters are literal in wildcards
+    assert!(cells(&model, "a.ple", &options).is_empty());
+
+    let options = FindOptions {
+        regex: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        cells(&model, "^(apple|banana)$", &options),
+        vec![(0, 1, 1), (0, 2, 1), (0, 3, 1)]
+    );
+    assert!(model.find("(", &options).is_err());
+    assert_eq!(
+        model.find("", &options).err(),
+        Some("Nothing to find".to_string())
+    );
+}
+
+#[test]
+fn order_and_scope() {
+    let model = sample_model();
+    let options = FindOptions {
+        order: FindOrder::ByColumns,
+        ..Default::default()
+    };
+    assert_eq!(
+        cells(&model, "a", &options),
+        vec![(0, 1, 1), (0, 2, 1), (0, 3, 1), (0, 1, 2), (1, 1, 1)]
+    );
+
+    let options = FindOptions {
+        scope: FindScope::Sheet(1),
+        ..Default::default()
+    };
+    assert_eq!(cells(&model, "apple", &options), vec![(1, 1, 1)]);
+
+    let options = FindOptions {
+        scope: FindScope::Selection(Area {
+            sheet: 0,
+            row: 1,
+            column: 2,
+            width: 1,
+            height: 3,
+        }),
+        ..Default::default()
+    };
+    assert_eq!(cells(&model, "apple", &options), vec![(0, 1, 2)]);
+
+    let options = FindOptions {
+        scope: FindScope::Sheet(5),
+        ..Default::default()
+    };
+    assert!(model.find("apple", &options).is_err());
+}
+
+#[test]
+fn options_from_json() {
+    let options: FindOptions =
+        serde_json::from_str(r#"{"match_case": true, "scope": {"Sheet": 1}}"#).unwrap();
+    assert!(options.match_case);
+    assert!(!options.whole_cell);
+    assert!(matches!(options.scope, FindScope::Sheet(1)));
+}
+
+#[test]
+fn replace_all() {
+    let mut model = sample_model();
+    let options = FindOptions {
+        look_in: FindLookIn::Formulas,
+        ..Default::default()
+    };
+    assert_eq!(model.replace_all("apple", "pear", &options), Ok(3));
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("pear".to_string()));
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("pinepear".to_string()));
+    assert_eq!(model.get_cell_content(1, 1, 1), Ok("pear pie".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 2, 1),
+        Ok("PEAR".to_string())
+    );
+
+    // All the changes are undone at once
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("Apple".to_string()));
+    assert_eq!(model.get_cell_content(1, 1, 1), Ok("apple pie".to_string()));
+    model.redo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("pinepear".to_string()));
+
+    // Not in comments
+    let options = FindOptions {
+        look_in: FindLookIn::Comments,
+        ..Default::default()
+    };
+    assert_eq!(
+        model.replace_all("pear", "apple", &options),
+        Err("Replace does not work in comments".to_string())
+    );
+}
+
+#[test]
+fn replace_all_values() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "red apple").unwrap();
+    model.set_user_input(0, 2, 1, "=A1").unwrap();
+    model.set_user_input(0, 3, 1, "=\"apple\"").unwrap();
+    // Looking in values finds the cells by their value but only replaces in constants
+    assert_eq!(
+        model.replace_all("apple", "pear", &FindOptions::default()),
+        Ok(1)
+    );
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("red pear".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("=A1".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 3, 1),
+        Ok("=\"apple\"".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 2, 1),
+        Ok("red pear".to_string())
+    );
+}
+
+#[test]
+fn replace_all_formatted_values() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1000").unwrap();
+    model.set_user_input(0, 2, 1, "2024-01-05").unwrap();
+    let area = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    model.update_range_style(&area, "num_fmt", "#,##0").unwrap();
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("1,000".to_string())
+    );
+
+    // Numbers are replaced as they are shown
+    assert_eq!(
+        cells(&model, ",0", &FindOptions::default()),
+        vec![(0, 1, 1)]
+    );
+    assert_eq!(
+        model.replace_all(",0", ",5", &FindOptions::default()),
+        Ok(1)
+    );
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("1500".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1),
+        Ok("1,500".to_string())
+    );
+
+    assert_eq!(
+        model.replace_all("-01-", "-03-", &FindOptions::default()),
+        Ok(1)
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 2, 1),
+        Ok("2024-03-05".to_string())
+    );
+}
+
+#[test]
+fn replace_with_groups() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Smith, John").unwrap();
+    let options = FindOptions {
+        look_in: FindLookIn::Formulas,
+        regex: true,
+        ..Default::default()
+    };
+    assert_eq!(model.replace_all(r"(\w+), (\w+)", "$2 $1", &options), Ok(1));
+    assert_eq!(
+        model.get_cell_content(0, 1, 1),
+        Ok("John Smith".to_string())
+    );
+
+    // Without regex `$` is literal
+    let options = FindOptions {
+        look_in: FindLookIn::Formulas,
+        ..Default::default()
+    };
+    assert_eq!(model.replace_all("John", "$1", &options), Ok(1));
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("$1 Smith".to_string()));
+}
+
+#[test]
+fn replace_skips_invalid_formulas_and_locked_cells() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "=SUM(1,2)").unwrap();
+    model.set_user_input(0, 2, 1, "SUM").unwrap();
+    let options = FindOptions {
+        look_in: FindLookIn::Formulas,
+        ..Default::default()
+    };
+    // "=SUM(1(2)" is not a valid formula
+    assert_eq!(model.replace_all(",", "(", &options), Ok(0));
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("=SUM(1,2)".to_string()));
+
+    model
+        .update_range_style(
+            &Area {
+                sheet: 0,
+                row: 2,
+                column: 1,
+                width: 1,
+                height: 1,
+            },
+            "protection.locked",
+            "false",
+        )
+        .unwrap();
+    model
+        .protect_sheet(0, &SheetProtection::default(), None)
+        .unwrap();
+    assert_eq!(model.replace_all("SUM", "PRODUCT", &options), Ok(1));
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("=SUM(1,2)".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("PRODUCT".to_string()));
+}
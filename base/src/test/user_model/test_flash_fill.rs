@@ -0,0 +1,138 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::CellType;
+use crate::UserModel;
+
+fn cell(row: i32, column: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width: 1,
+        height: 1,
+    }
+}
+
+fn set_column(model: &mut UserModel, column: i32, values: &[&str]) {
+    for (index, value) in values.iter().enumerate() {
+        model
+            .set_user_input(0, index as i32 + 1, column, value)
+            .unwrap();
+    }
+}
+
+fn column(model: &UserModel, column: i32, rows: std::ops::RangeInclusive<i32>) -> Vec<String> {
+    rows.map(|row| model.get_cell_content(0, row, column).unwrap())
+        .collect()
+}
+
+#[test]
+fn names_and_initials() {
+    let mut model = new_empty_user_model();
+    set_column(
+        &mut model,
+        1,
+        &["John Smith", "Ada Lovelace", "alan turing", "Grace Hopper"],
+    );
+    model.set_user_input(0, 1, 2, "Smith, J.").unwrap();
+    model.flash_fill(&cell(1, 2)).unwrap();
+    assert_eq!(
+        column(&model, 2, 1..=4),
+        vec!["Smith, J.", "Lovelace, A.", "turing, a.", "Hopper, G."]
+    );
+
+    // All the changes are undone at once
+    model.undo().unwrap();
+    assert_eq!(column(&model, 2, 2..=4), vec!["", "", ""]);
+    model.redo().unwrap();
+    assert_eq!(
+        model.get_cell_content(0, 4, 2),
+        Ok("Hopper, G.".to_string())
+    );
+}
+
+#[test]
+fn change_case() {
+    let mut model = new_empty_user_model();
+    set_column(&mut model, 1, &["john", "ADA", "grace"]);
+    set_column(&mut model, 2, &["SMITH", "lovelace", "hopper"]);
+    model.set_user_input(0, 1, 3, "John Smith").unwrap();
+    model.flash_fill(&cell(1, 3)).unwrap();
+    assert_eq!(
+        column(&model, 3, 1..=3),
+        vec!["John Smith", "Ada Lovelace", "Grace Hopper"]
+    );
+}
+
+#[test]
+fn several_examples() {
+    let mut model = new_empty_user_model();
+    set_column(
+        &mut model,
+        2,
+        &[
+            "jsmith@example.com",
+            "ada.lovelace@math.org",
+            "grace@navy.mil",
+            "alan@bletchley.uk",
+        ],
+    );
+    // The first example alone is ambiguous (first word or whole user name)
+    model.set_user_input(0, 1, 1, "jsmith").unwrap();
+    model.set_user_input(0, 2, 1, "ada.lovelace").unwrap();
+    model.flash_fill(&cell(1, 1)).unwrap();
+    assert_eq!(column(&model, 1, 3..=4), vec!["grace", "alan"]);
+}
+
+#[test]
+fn numbers_from_text() {
+    let mut model = new_empty_user_model();
+    set_column(
+        &mut model,
+        1,
+        &["(555) 123-4567", "(212) 555-0199", "(415) 000-1234"],
+    );
+    model.set_user_input(0, 1, 2, "555-123-4567").unwrap();
+    model.flash_fill(&cell(1, 2)).unwrap();
+    assert_eq!(
+        column(&model, 2, 2..=3),
+        vec!["212-555-0199", "415-000-1234"]
+    );
+
+    // Results that look like numbers are kept as text
+    model.set_user_input(0, 1, 3, "555").unwrap();
+    model.flash_fill(&cell(1, 3)).unwrap();
+    assert_eq!(column(&model, 3, 2..=3), vec!["212", "415"]);
+    assert_eq!(model.get_cell_type(0, 2, 3), Ok(CellType::Text));
+}
+
+#[test]
+fn errors() {
+    let mut model = new_empty_user_model();
+    assert_eq!(
+        model.flash_fill(&cell(1, 2)),
+        Err("There is no data next to the column".to_string())
+    );
+    set_column(&mut model, 1, &["abc", "def"]);
+    assert_eq!(
+        model.flash_fill(&cell(1, 2)),
+        Err("Type an example of the result first".to_string())
+    );
+    model.set_user_input(0, 1, 2, "xyz").unwrap();
+    assert_eq!(
+        model.flash_fill(&cell(1, 2)),
+        Err("Could not find a pattern in the examples".to_string())
+    );
+    assert_eq!(
+        model.flash_fill(&Area {
+            sheet: 0,
+            row: 1,
+            column: 2,
+            width: 2,
+            height: 2,
+        }),
+        Err("Flash fill works on a single column".to_string())
+    );
+}
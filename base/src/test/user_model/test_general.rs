@@ -138,3 +138,33 @@ fn get_and_set_name() {
     model.set_name("Another name");
     assert_eq!(model.get_name(), "Another name");
 }
+
+#[test]
+fn r1c1_input_and_content() {
+    let mut model = UserModel::new_empty("model", "de", "UTC", "de").unwrap();
+    model.set_user_input(0, 1, 1, "2").unwrap();
+    model
+        .set_user_input_r1c1(0, 2, 1, "=SUMME(Z[-1]S[0];0,5)")
+        .unwrap();
+    assert_eq!(
+        model.get_cell_content(0, 2, 1),
+        Ok("=SUMME(A1;0,5)".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content_r1c1(0, 2, 1),
+        Ok("=SUMME(Z[-1]S[0];0,5)".to_string())
+    );
+    assert_eq!(model.get_cell_content_r1c1(0, 1, 1), Ok("2".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 2, 1),
+        Ok("2,5".to_string())
+    );
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("".to_string()));
+    model.redo().unwrap();
+    assert_eq!(
+        model.get_cell_content(0, 2, 1),
+        Ok("=SUMME(A1;0,5)".to_string())
+    );
+}
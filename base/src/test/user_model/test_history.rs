@@ -0,0 +1,167 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::diffs::{self, Diff};
+use crate::mock_time::{get_milliseconds_since_epoch, set_mock_time};
+use crate::test::user_model::util::new_empty_user_model;
+
+#[test]
+fn undo_restores_the_view() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.set_selected_sheet(0).unwrap();
+    model.set_selected_cell(3, 3).unwrap();
+    model.set_top_left_visible_cell(2, 2).unwrap();
+    model.set_user_input(0, 3, 3, "Hello").unwrap();
+    let view = model.get_selected_view();
+
+    // The user goes somewhere else
+    model.set_selected_sheet(1).unwrap();
+    model.set_selected_cell(10, 5).unwrap();
+    model.set_top_left_visible_cell(8, 4).unwrap();
+
+    model.undo().unwrap();
+    assert_eq!(model.get_selected_view(), view);
+    assert_eq!(model.get_cell_content(0, 3, 3), Ok("".to_string()));
+
+    model.set_selected_cell(1, 1).unwrap();
+    model.redo().unwrap();
+    assert_eq!(model.get_selected_view(), view);
+    assert_eq!(model.get_cell_content(0, 3, 3), Ok("Hello".to_string()));
+}
+
+#[test]
+fn undo_restores_the_view_before_the_change() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.set_selected_sheet(0).unwrap();
+    model.set_selected_cell(4, 2).unwrap();
+    let before = model.get_selected_view();
+
+    // Duplicating a sheet selects the copy
+    model.duplicate_sheet(0, "Copy").unwrap();
+    let after = model.get_selected_view();
+    assert_eq!(after.sheet, 1);
+
+    model.undo().unwrap();
+    assert_eq!(model.get_selected_view(), before);
+    model.redo().unwrap();
+    assert_eq!(model.get_selected_view(), after);
+
+    // The selected sheet moves with the sheets
+    model.set_selected_sheet(2).unwrap();
+    let before = model.get_selected_view();
+    model.move_sheet(2, 0).unwrap();
+    assert_eq!(model.get_selected_sheet(), 0);
+    model.undo().unwrap();
+    assert_eq!(model.get_selected_view(), before);
+}
+
+#[test]
+fn views_are_not_sent() {
+    let mut model = new_empty_user_model();
+    model.set_selected_cell(3, 3).unwrap();
+    model.set_user_input(0, 3, 3, "Hello").unwrap();
+    model.set_selected_cell(7, 7).unwrap();
+    model.undo().unwrap();
+    let changes = diffs::from_bytes(&model.flush_send_queue())
+        .unwrap()
+        .changes;
+    assert_eq!(changes.len(), 2);
+    for change in changes {
+        assert!(matches!(change.list[..], [Diff::SetCellValue { .. }]));
+    }
+
+    // Remote models keep their own selection
+    let mut model2 = new_empty_user_model();
+    model2.set_selected_cell(5, 5).unwrap();
+    model.set_user_input(0, 1, 1, "World").unwrap();
+    model2
+        .apply_external_diffs(&model.flush_send_queue())
+        .unwrap();
+    assert_eq!(model2.get_selected_cell(), (0, 5, 5));
+}
+
+#[test]
+fn consecutive_typing_is_one_change() {
+    let start = get_milliseconds_since_epoch();
+    let mut model = new_empty_user_model();
+    model.set_typing_group_interval(1000);
+
+    set_mock_time(start);
+    model.set_user_input(0, 1, 1, "a").unwrap();
+    set_mock_time(start + 500);
+    model.set_user_input(0, 2, 1, "b").unwrap();
+    set_mock_time(start + 1400);
+    model.set_user_input(0, 3, 1, "c").unwrap();
+    // Too late, this is a new change
+    set_mock_time(start + 3000);
+    model.set_user_input(0, 4, 1, "d").unwrap();
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("c".to_string()));
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("".to_string()));
+    assert!(!model.can_undo());
+
+    model.redo().unwrap();
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("c".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("".to_string()));
+
+    // Typing right after a redo is a new change
+    model.set_user_input(0, 5, 1, "e").unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("c".to_string()));
+
+    // Other changes break the group
+    set_mock_time(start + 3100);
+    model.set_user_input(0, 6, 1, "f").unwrap();
+    model.insert_rows(0, 10, 1).unwrap();
+    model.set_user_input(0, 7, 1, "g").unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 6, 1), Ok("f".to_string()));
+    set_mock_time(start);
+}
+
+#[test]
+fn typing_is_not_grouped_by_default() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "a").unwrap();
+    model.set_user_input(0, 2, 1, "b").unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("a".to_string()));
+}
+
+#[test]
+fn history_depth_limit() {
+    let mut model = new_empty_user_model();
+    for row in 1..=5 {
+        model.set_user_input(0, row, 1, "x").unwrap();
+    }
+    model.set_max_history_depth(Some(3));
+    model.set_user_input(0, 6, 1, "x").unwrap();
+    for _ in 0..3 {
+        model.undo().unwrap();
+    }
+    assert!(!model.can_undo());
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("x".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("".to_string()));
+
+    // Redo is not limited by the depth
+    for _ in 0..3 {
+        model.redo().unwrap();
+    }
+    assert_eq!(model.get_cell_content(0, 6, 1), Ok("x".to_string()));
+
+    model.set_max_history_depth(None);
+    for row in 7..=12 {
+        model.set_user_input(0, row, 1, "x").unwrap();
+    }
+    let mut count = 0;
+    while model.can_undo() {
+        model.undo().unwrap();
+        count += 1;
+    }
+    assert_eq!(count, 9);
+}
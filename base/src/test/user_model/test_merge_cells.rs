@@ -0,0 +1,274 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{HorizontalAlignment, MergedRange, SheetProtection};
+use crate::ProtectionError;
+
+fn area(row: i32, column: i32, height: i32, width: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width,
+        height,
+    }
+}
+
+fn range(reference: &str) -> MergedRange {
+    MergedRange::parse(reference).unwrap()
+}
+
+#[test]
+fn parse_and_display() {
+    let merged = range("K7:L10");
+    assert_eq!(
+        merged,
+        MergedRange {
+            first_row: 7,
+            first_column: 11,
+            last_row: 10,
+            last_column: 12
+        }
+    );
+    assert_eq!(merged.to_string(), "K7:L10");
+    assert_eq!(range("L10:K7"), merged);
+    assert!(MergedRange::parse("K7").is_err());
+}
+
+#[test]
+fn merge_keeps_top_left_value() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 2, 2, "Hello").unwrap();
+    model.set_user_input(0, 2, 3, "World").unwrap();
+    model.set_user_input(0, 3, 2, "42").unwrap();
+    model.set_user_input(0, 5, 5, "=B3").unwrap();
+
+    model.merge_cells(&area(2, 2, 2, 2)).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B2:C3")]);
+    assert_eq!(model.get_cell_content(0, 2, 2), Ok("Hello".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 3), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 3, 2), Ok("".to_string()));
+    assert_eq!(model.get_formatted_cell_value(0, 5, 5), Ok("0".to_string()));
+
+    assert_eq!(
+        model.get_merged_range(0, 3, 3).unwrap(),
+        Some(range("B2:C3"))
+    );
+    assert_eq!(model.get_merged_range(0, 4, 3).unwrap(), None);
+
+    model.undo().unwrap();
+    assert!(model.get_merged_cells(0).unwrap().is_empty());
+    assert_eq!(model.get_cell_content(0, 2, 3), Ok("World".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 5, 5),
+        Ok("42".to_string())
+    );
+
+    model.redo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B2:C3")]);
+    assert_eq!(model.get_cell_content(0, 2, 3), Ok("".to_string()));
+
+    let send_queue = model.flush_send_queue();
+    let mut model2 = new_empty_user_model();
+    model2.apply_external_diffs(&send_queue).unwrap();
+    assert_eq!(model2.get_merged_cells(0).unwrap(), vec![range("B2:C3")]);
+    assert_eq!(model2.get_cell_content(0, 2, 2), Ok("Hello".to_string()));
+}
+
+#[test]
+fn merge_across_and_center() {
+    let mut model = new_empty_user_model();
+    model.merge_cells_across(&area(1, 1, 3, 2)).unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![range("A1:B1"), range("A2:B2"), range("A3:B3")]
+    );
+    model.undo().unwrap();
+    assert!(model.get_merged_cells(0).unwrap().is_empty());
+
+    model.merge_and_center(&area(5, 1, 1, 3)).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("A5:C5")]);
+    let style = model.get_cell_style(0, 5, 1).unwrap();
+    assert_eq!(
+        style.alignment.unwrap().horizontal,
+        HorizontalAlignment::Center
+    );
+    model.undo().unwrap();
+    assert!(model.get_merged_cells(0).unwrap().is_empty());
+    assert_eq!(model.get_cell_style(0, 5, 1).unwrap().alignment, None);
+}
+
+#[test]
+fn overlapping_merges() {
+    let mut model = new_empty_user_model();
+    model.merge_cells(&area(2, 2, 2, 2)).unwrap();
+    assert_eq!(
+        model.merge_cells(&area(3, 3, 2, 2)),
+        Err("Cannot merge a range that partially overlaps merged cells".to_string())
+    );
+    assert_eq!(
+        model.merge_cells(&area(1, 1, 1, 1)),
+        Err("Cannot merge a single cell".to_string())
+    );
+    // A bigger block replaces the blocks inside it
+    model.merge_cells(&area(1, 1, 5, 5)).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("A1:E5")]);
+    model.undo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B2:C3")]);
+}
+
+#[test]
+fn unmerge() {
+    let mut model = new_empty_user_model();
+    model.merge_cells(&area(2, 2, 2, 2)).unwrap();
+    model.merge_cells(&area(10, 1, 1, 4)).unwrap();
+    model.unmerge_cells(&area(3, 3, 1, 1)).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("A10:D10")]);
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![range("B2:C3"), range("A10:D10")]
+    );
+}
+
+#[test]
+fn insert_and_delete_rows() {
+    let mut model = new_empty_user_model();
+    model.merge_cells(&area(3, 1, 3, 2)).unwrap();
+    model.merge_cells(&area(10, 1, 2, 2)).unwrap();
+
+    // Inserting in the middle of a block expands it
+    model.insert_rows(0, 4, 2).unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![range("A3:B7"), range("A12:B13")]
+    );
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![range("A3:B5"), range("A10:B11")]
+    );
+
+    // Deleting part of a block shrinks it, a block that becomes a single cell is removed
+    model.delete_rows(0, 4, 8).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("A3:B3")]);
+    model.delete_rows(0, 1, 1).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("A2:B2")]);
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![range("A3:B5"), range("A10:B11")]
+    );
+    model.redo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("A3:B3")]);
+}
+
+#[test]
+fn insert_and_delete_columns() {
+    let mut model = new_empty_user_model();
+    model.merge_cells(&area(1, 2, 2, 2)).unwrap();
+    model.insert_columns(0, 1, 1).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("C1:D2")]);
+    model.delete_columns(0, 4, 1).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("C1:C2")]);
+    model.delete_columns(0, 3, 1).unwrap();
+    assert!(model.get_merged_cells(0).unwrap().is_empty());
+    model.undo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("C1:C2")]);
+    model.undo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("C1:D2")]);
+}
+
+#[test]
+fn move_rows_and_columns() {
+    let mut model = new_empty_user_model();
+    model.merge_cells(&area(3, 2, 3, 2)).unwrap();
+
+    // Moving a row from above the block to below shifts the block up
+    model.move_row_action(0, 1, 6).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B2:C4")]);
+    model.undo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B3:C5")]);
+
+    // We cannot move a row into or out of a block
+    assert_eq!(
+        model.move_row_action(0, 1, 3),
+        Err("Cannot move part of a merged cell".to_string())
+    );
+    assert_eq!(
+        model.move_row_action(0, 4, 5),
+        Err("Cannot move part of a merged cell".to_string())
+    );
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B3:C5")]);
+
+    model.move_column_action(0, 5, -4).unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("C3:D5")]);
+    model.undo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap(), vec![range("B3:C5")]);
+}
+
+#[test]
+fn navigation_skips_merged_cells() {
+    let mut model = new_empty_user_model();
+    model.merge_cells(&area(2, 2, 2, 3)).unwrap();
+
+    model.set_selected_cell(2, 1).unwrap();
+    model.on_arrow_right().unwrap();
+    let view = model.get_selected_view();
+    assert_eq!((view.row, view.column), (2, 2));
+    assert_eq!(view.range, [2, 2, 3, 4]);
+
+    model.on_arrow_right().unwrap();
+    let view = model.get_selected_view();
+    assert_eq!((view.row, view.column), (2, 5));
+    assert_eq!(view.range, [2, 5, 2, 5]);
+
+    model.on_arrow_left().unwrap();
+    assert_eq!(model.get_selected_view().range, [2, 2, 3, 4]);
+    model.on_arrow_down().unwrap();
+    assert_eq!(model.get_selected_view().range, [4, 2, 4, 2]);
+    model.on_arrow_up().unwrap();
+    assert_eq!(model.get_selected_view().range, [2, 2, 3, 4]);
+    model.on_arrow_up().unwrap();
+    assert_eq!(model.get_selected_view().range, [1, 2, 1, 2]);
+
+    // Clicking inside the block selects the whole block
+    model.set_selected_cell(3, 4).unwrap();
+    let view = model.get_selected_view();
+    assert_eq!((view.row, view.column), (2, 2));
+    assert_eq!(view.range, [2, 2, 3, 4]);
+}
+
+#[test]
+fn merge_does_not_clear_locked_cells() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Title").unwrap();
+    model.set_user_input(0, 1, 2, "Locked").unwrap();
+    model
+        .update_range_style(&area(3, 1, 1, 2), "protection.locked", "false")
+        .unwrap();
+    model.set_user_input(0, 3, 2, "Unlocked").unwrap();
+    let protection = SheetProtection {
+        format_cells: true,
+        ..Default::default()
+    };
+    model.protect_sheet(0, &protection, None).unwrap();
+
+    assert_eq!(
+        model.merge_cells(&area(1, 1, 1, 2)),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert!(model.get_merged_cells(0).unwrap().is_empty());
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("Locked".to_string()));
+
+    // Empty locked cells and unlocked cells can be merged
+    model.merge_cells(&area(3, 1, 1, 2)).unwrap();
+    model.merge_cells(&area(5, 1, 2, 2)).unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![range("A3:B3"), range("A5:B6")]
+    );
+    assert_eq!(model.get_cell_content(0, 3, 2), Ok("".to_string()));
+}
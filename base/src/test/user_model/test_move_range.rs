@@ -0,0 +1,356 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{Comment, MergedRange};
+
+fn area(row: i32, column: i32, width: i32, height: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn move_values_styles_and_formulas() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(0, 2, 1, "=A1*2").unwrap();
+    model.set_user_input(0, 2, 2, "=A2+$A$1+C5").unwrap();
+    model.set_user_input(0, 5, 3, "10").unwrap();
+    model.set_user_input(0, 10, 1, "=SUM(A1:A2)").unwrap();
+    model.new_sheet().unwrap();
+    model.set_user_input(1, 1, 1, "=Sheet1!A2").unwrap();
+    model
+        .update_range_style(&area(1, 1, 1, 1), "font.b", "true")
+        .unwrap();
+
+    // A1:B2 to D4:E5
+    model.set_selected_sheet(0).unwrap();
+    model.move_range(&area(1, 1, 2, 2), 4, 4).unwrap();
+
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 2), Ok("".to_string()));
+    assert!(!model.get_cell_style(0, 1, 1).unwrap().font.b);
+    assert_eq!(model.get_cell_content(0, 4, 4), Ok("1".to_string()));
+    assert!(model.get_cell_style(0, 4, 4).unwrap().font.b);
+    // References inside the block move with it, the rest stay
+    assert_eq!(model.get_cell_content(0, 5, 4), Ok("=D4*2".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 5, 5),
+        Ok("=D5+$D$4+C5".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 5, 5),
+        Ok("13".to_string())
+    );
+    // Formulas pointing to the moved cells follow them, in every sheet
+    assert_eq!(
+        model.get_cell_content(0, 10, 1),
+        Ok("=SUM(D4:D5)".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(1, 1, 1),
+        Ok("=Sheet1!D5".to_string())
+    );
+    assert_eq!(model.get_formatted_cell_value(1, 1, 1), Ok("2".to_string()));
+    assert_eq!(model.get_selected_view().range, [4, 4, 5, 5]);
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("=A1*2".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 4), Ok("".to_string()));
+    assert!(model.get_cell_style(0, 1, 1).unwrap().font.b);
+    assert_eq!(
+        model.get_cell_content(0, 10, 1),
+        Ok("=SUM(A1:A2)".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(1, 1, 1),
+        Ok("=Sheet1!A2".to_string())
+    );
+    assert_eq!(model.get_selected_view().range, [1, 1, 1, 1]);
+
+    model.redo().unwrap();
+    assert_eq!(
+        model.get_cell_content(0, 5, 5),
+        Ok("=D5+$D$4+C5".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(1, 1, 1),
+        Ok("=Sheet1!D5".to_string())
+    );
+}
+
+#[test]
+fn overwritten_cells() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(0, 1, 2, "2").unwrap();
+    model.set_user_input(0, 1, 3, "=B1*10").unwrap();
+    model.set_user_input(0, 3, 1, "=A1+B1").unwrap();
+
+    // A1 lands on B1
+    model.move_range(&area(1, 1, 1, 1), 1, 2).unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("1".to_string()));
+    assert_eq!(model.get_cell_content(0, 1, 3), Ok("=#REF!*10".to_string()));
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("=B1+#REF!".to_string()));
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("2".to_string()));
+    assert_eq!(model.get_cell_content(0, 1, 3), Ok("=B1*10".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 3),
+        Ok("20".to_string())
+    );
+}
+
+#[test]
+fn overlapping_move() {
+    let mut model = new_empty_user_model();
+    for row in 1..=3 {
+        model.set_user_input(0, row, 1, &format!("{row}")).unwrap();
+    }
+    model.set_user_input(0, 5, 2, "=SUM(A1:A3)").unwrap();
+    model.move_range(&area(1, 1, 1, 3), 2, 1).unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("1".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("3".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 5, 2),
+        Ok("=SUM(A2:A4)".to_string())
+    );
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("1".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("".to_string()));
+}
+
+#[test]
+fn merged_cells_and_comments() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Title").unwrap();
+    model.merge_cells(&area(1, 1, 2, 1)).unwrap();
+    model.merge_cells(&area(6, 3, 2, 2)).unwrap();
+    model.model.workbook.worksheets[0].comments.push(Comment {
+        text: "Note".to_string(),
+        author_name: "Me".to_string(),
+        author_id: None,
+        cell_ref: "B2".to_string(),
+    });
+
+    // A block can't be split
+    assert_eq!(
+        model.move_range(&area(1, 1, 1, 2), 5, 5),
+        Err("Cannot move part of a merged cell".to_string())
+    );
+
+    // A1:B2 lands on C6:D7, over the second block
+    model.move_range(&area(1, 1, 2, 2), 6, 3).unwrap();
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![MergedRange {
+            first_row: 6,
+            first_column: 3,
+            last_row: 6,
+            last_column: 4,
+        }]
+    );
+    assert_eq!(model.get_cell_content(0, 6, 3), Ok("Title".to_string()));
+    assert_eq!(
+        model.model.workbook.worksheets[0].comments[0].cell_ref,
+        "D7"
+    );
+
+    model.undo().unwrap();
+    assert_eq!(model.get_merged_cells(0).unwrap().len(), 2);
+    assert_eq!(
+        model.model.workbook.worksheets[0].comments[0].cell_ref,
+        "B2"
+    );
+}
+
+#[test]
+fn move_to_part_of_a_merged_cell() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.merge_cells(&area(3, 3, 2, 2)).unwrap();
+
+    // A1:B2 would land on D4:E5, over part of C3:D4
+    assert_eq!(
+        model.move_range(&area(1, 1, 2, 2), 4, 4),
+        Err("Cannot move cells to part of a merged cell".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("1".to_string()));
+    assert_eq!(
+        model.get_merged_cells(0).unwrap(),
+        vec![MergedRange {
+            first_row: 3,
+            first_column: 3,
+            last_row: 4,
+            last_column: 4,
+        }]
+    );
+}
+
+#[test]
+fn formulas_in_other_sheets() {
+    let mut model = new_empty_user_model();
+    model.new_sheet().unwrap();
+    model.set_user_input(0, 1, 1, "5").unwrap();
+    model.set_user_input(0, 3, 3, "7").unwrap();
+    model.set_user_input(1, 1, 1, "=Sheet1!A1*2").unwrap();
+    model.set_user_input(1, 2, 1, "=Sheet1!C3+1").unwrap();
+    model.set_user_input(1, 3, 1, "=Sheet1!H8").unwrap();
+
+    // A1 moves to C3, the old C3 is overwritten
+    model.move_range(&area(1, 1, 1, 1), 3, 3).unwrap();
+    assert_eq!(
+        model.get_cell_content(1, 1, 1),
+        Ok("=Sheet1!C3*2".to_string())
+    );
+    assert_eq!(model.get_cell_content(1, 2, 1), Ok("=#REF!+1".to_string()));
+    assert_eq!(
+        model.get_cell_content(1, 3, 1),
+        Ok("=Sheet1!H8".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(1, 1, 1),
+        Ok("10".to_string())
+    );
+
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_cell_content(1, 1, 1),
+        Ok("=Sheet1!A1*2".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(1, 2, 1),
+        Ok("=Sheet1!C3+1".to_string())
+    );
+    assert_eq!(model.get_formatted_cell_value(1, 2, 1), Ok("8".to_string()));
+}
+
+#[test]
+fn invalid_moves() {
+    let mut model = new_empty_user_model();
+    assert_eq!(
+        model.move_range(&area(1, 1, 2, 2), 1_048_576, 1),
+        Err("Invalid target area".to_string())
+    );
+    assert_eq!(
+        model.move_range(&area(0, 1, 2, 2), 3, 1),
+        Err("Invalid source area".to_string())
+    );
+}
+
+#[test]
+fn insert_cells() {
+    let mut model = new_empty_user_model();
+    for row in 1..=3 {
+        model.set_user_input(0, row, 2, &format!("{row}")).unwrap();
+    }
+    model.set_user_input(0, 1, 1, "=SUM(B1:B3)").unwrap();
+    model.set_user_input(0, 2, 3, "keep").unwrap();
+
+    model.insert_cells_shift_down(&area(2, 2, 1, 2)).unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("1".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 2), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 2), Ok("2".to_string()));
+    assert_eq!(model.get_cell_content(0, 5, 2), Ok("3".to_string()));
+    // Only the column of the area moves
+    assert_eq!(model.get_cell_content(0, 2, 3), Ok("keep".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 1, 1),
+        Ok("=SUM(B1:B5)".to_string())
+    );
+    assert_eq!(model.get_formatted_cell_value(0, 1, 1), Ok("6".to_string()));
+
+    model.insert_cells_shift_right(&area(1, 1, 1, 1)).unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 1, 2),
+        Ok("=SUM(B1:B5)".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 1, 3), Ok("1".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 2), Ok("2".to_string()));
+
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 2, 2), Ok("2".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 1, 1),
+        Ok("=SUM(B1:B3)".to_string())
+    );
+}
+
+#[test]
+fn insert_cells_at_the_end_of_the_sheet() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1_048_576, 1, "last").unwrap();
+    assert_eq!(
+        model.insert_cells_shift_down(&area(5, 1, 1, 1)),
+        Err("Cannot move cells outside of the sheet".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(0, 1_048_576, 1),
+        Ok("last".to_string())
+    );
+}
+
+#[test]
+fn delete_cells() {
+    let mut model = new_empty_user_model();
+    for column in 1..=4 {
+        model
+            .set_user_input(0, 1, column, &format!("{column}"))
+            .unwrap();
+    }
+    model.set_user_input(0, 3, 1, "=SUM(A1:D1)").unwrap();
+    model.set_user_input(0, 4, 1, "=B1+C1").unwrap();
+
+    model.delete_cells_shift_left(&area(1, 2, 1, 1)).unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("3".to_string()));
+    assert_eq!(model.get_cell_content(0, 1, 3), Ok("4".to_string()));
+    assert_eq!(model.get_cell_content(0, 1, 4), Ok("".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 3, 1),
+        Ok("=SUM(A1:C1)".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("=#REF!+B1".to_string()));
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("2".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("=B1+C1".to_string()));
+    assert_eq!(model.get_formatted_cell_value(0, 4, 1), Ok("5".to_string()));
+
+    // The formulas below move up too
+    model.delete_cells_shift_up(&area(2, 1, 1, 1)).unwrap();
+    assert_eq!(
+        model.get_cell_content(0, 2, 1),
+        Ok("=SUM(A1:D1)".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("=B1+C1".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("".to_string()));
+}
+
+#[test]
+fn ranges_across_the_shifted_cells() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(0, 1, 2, "2").unwrap();
+    model.set_user_input(0, 1, 3, "3").unwrap();
+    model.set_user_input(0, 5, 1, "=SUM(A1:C1)").unwrap();
+    model.set_user_input(0, 6, 1, "=B1").unwrap();
+
+    // Only part of the range moves down so it is left as is
+    model.insert_cells_shift_down(&area(1, 2, 1, 1)).unwrap();
+    assert_eq!(
+        model.get_cell_content(0, 5, 1),
+        Ok("=SUM(A1:C1)".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 6, 1), Ok("=B2".to_string()));
+    assert_eq!(model.get_formatted_cell_value(0, 5, 1), Ok("4".to_string()));
+}
@@ -0,0 +1,227 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::get_builtin_named_styles;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{Alignment, Color, HorizontalAlignment, PatternType, SheetProtection, Style};
+use crate::ProtectionError;
+
+fn area(row: i32, column: i32, width: i32, height: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn builtin_styles() {
+    let builtin = get_builtin_named_styles();
+    let heading = builtin.iter().find(|s| s.name == "Heading 1").unwrap();
+    assert_eq!(heading.builtin_id, Some(16));
+    assert!(heading.style.font.b);
+
+    let mut model = new_empty_user_model();
+    assert!(model.get_named_style("Heading 1").is_err());
+    model
+        .apply_named_style(&area(1, 1, 2, 2), "Heading 1")
+        .unwrap();
+    assert_eq!(
+        model.get_named_style("Heading 1").unwrap().builtin_id,
+        Some(16)
+    );
+
+    let style = model.get_cell_style(0, 2, 2).unwrap();
+    assert_eq!(style.named_style, Some("Heading 1".to_string()));
+    assert!(style.font.b);
+    assert_eq!(style.font.sz, 15);
+
+    model.undo().unwrap();
+    assert!(model.get_named_style("Heading 1").is_err());
+    assert_eq!(model.get_cell_style(0, 2, 2).unwrap(), Style::default());
+
+    model.redo().unwrap();
+    assert_eq!(
+        model.get_cell_style(0, 2, 2).unwrap().named_style,
+        Some("Heading 1".to_string())
+    );
+
+    assert!(model
+        .apply_named_style(&area(1, 1, 1, 1), "Unknown")
+        .is_err());
+}
+
+#[test]
+fn update_propagates_to_cells() {
+    let mut model = new_empty_user_model();
+    let mut style = Style::default();
+    style.fill.pattern_type = PatternType::Solid;
+    style.fill.fg_color = Some(Color::Rgb("#FFFF00".to_string()));
+    style.font.b = true;
+    model.create_named_style("Highlight", &style).unwrap();
+    assert_eq!(
+        model.create_named_style("Highlight", &style),
+        Err("A style with that name already exists".to_string())
+    );
+    model
+        .apply_named_style(&area(1, 1, 2, 1), "Highlight")
+        .unwrap();
+
+    // Direct formatting keeps the link to the named style
+    model
+        .update_range_style(&area(1, 1, 1, 1), "font.i", "true")
+        .unwrap();
+    assert_eq!(
+        model.get_cell_style(0, 1, 1).unwrap().named_style,
+        Some("Highlight".to_string())
+    );
+
+    // A1 has its own font, B1 does not
+    let mut new_style = style.clone();
+    new_style.fill.fg_color = Some(Color::Rgb("#00FF00".to_string()));
+    new_style.font.color = Some(Color::Rgb("#FF0000".to_string()));
+    model.update_named_style("Highlight", &new_style).unwrap();
+
+    let a1 = model.get_cell_style(0, 1, 1).unwrap();
+    let b1 = model.get_cell_style(0, 1, 2).unwrap();
+    assert_eq!(a1.fill.fg_color, Some(Color::Rgb("#00FF00".to_string())));
+    assert_eq!(a1.font.color, Some(Color::Rgb("#000000".to_string())));
+    assert!(a1.font.i);
+    assert_eq!(b1.fill.fg_color, Some(Color::Rgb("#00FF00".to_string())));
+    assert_eq!(b1.font.color, Some(Color::Rgb("#FF0000".to_string())));
+
+    model.undo().unwrap();
+    let b1 = model.get_cell_style(0, 1, 2).unwrap();
+    assert_eq!(b1.fill.fg_color, Some(Color::Rgb("#FFFF00".to_string())));
+    assert_eq!(b1.font.color, Some(Color::Rgb("#000000".to_string())));
+
+    model.redo().unwrap();
+    let b1 = model.get_cell_style(0, 1, 2).unwrap();
+    assert_eq!(b1.font.color, Some(Color::Rgb("#FF0000".to_string())));
+}
+
+#[test]
+fn delete_named_style() {
+    let mut model = new_empty_user_model();
+    let mut style = Style::default();
+    style.font.b = true;
+    model.create_named_style("Bold", &style).unwrap();
+    model.apply_named_style(&area(1, 1, 1, 1), "Bold").unwrap();
+
+    model.delete_named_style("Bold").unwrap();
+    assert!(model.get_named_style("Bold").is_err());
+    // The cell keeps its formatting
+    let a1 = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(a1.named_style, None);
+    assert!(a1.font.b);
+
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_cell_style(0, 1, 1).unwrap().named_style,
+        Some("Bold".to_string())
+    );
+
+    assert_eq!(
+        model.delete_named_style("normal"),
+        Err("The Normal style can not be deleted".to_string())
+    );
+}
+
+#[test]
+fn direct_formatting_is_tracked() {
+    let mut model = new_empty_user_model();
+    let centered = Some(Alignment {
+        horizontal: HorizontalAlignment::Center,
+        ..Default::default()
+    });
+    let mut style = Style::default();
+    style.font.b = true;
+    model.create_named_style("Title", &style).unwrap();
+    model.apply_named_style(&area(1, 1, 2, 1), "Title").unwrap();
+    model
+        .update_range_style(&area(1, 1, 1, 1), "alignment.horizontal", "center")
+        .unwrap();
+
+    // The style gets the alignment A1 has, A1 is still formatted directly
+    let mut new_style = style.clone();
+    new_style.alignment = centered.clone();
+    model.update_named_style("Title", &new_style).unwrap();
+    assert_eq!(model.get_cell_style(0, 1, 2).unwrap().alignment, centered);
+
+    new_style.alignment = None;
+    model.update_named_style("Title", &new_style).unwrap();
+    assert_eq!(model.get_cell_style(0, 1, 1).unwrap().alignment, centered);
+    assert_eq!(model.get_cell_style(0, 1, 2).unwrap().alignment, None);
+}
+
+#[test]
+fn undo_create_removes_the_style_format() {
+    let mut model = new_empty_user_model();
+    let count = model.get_model().workbook.styles.cell_style_xfs.len();
+    let mut style = Style::default();
+    style.font.b = true;
+    model.create_named_style("Bold", &style).unwrap();
+    model
+        .create_named_style("Other", &Style::default())
+        .unwrap();
+    assert_eq!(
+        model.get_model().workbook.styles.cell_style_xfs.len(),
+        count + 2
+    );
+
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_model().workbook.styles.cell_style_xfs.len(),
+        count
+    );
+
+    model.redo().unwrap();
+    model.redo().unwrap();
+    // Deleting a style that is not the last one keeps the others in place
+    model.delete_named_style("Bold").unwrap();
+    assert_eq!(
+        model.get_model().workbook.styles.cell_style_xfs.len(),
+        count + 1
+    );
+    assert_eq!(
+        model.get_named_style("Other").unwrap().style.named_style,
+        Some("Other".to_string())
+    );
+    assert!(!model.get_named_style("Other").unwrap().style.font.b);
+}
+
+#[test]
+fn protected_sheets() {
+    let mut model = new_empty_user_model();
+    let mut style = Style::default();
+    style.font.b = true;
+    model.create_named_style("Bold", &style).unwrap();
+    model.create_named_style("Unused", &style).unwrap();
+    model.apply_named_style(&area(1, 1, 1, 1), "Bold").unwrap();
+    model
+        .protect_sheet(0, &SheetProtection::default(), None)
+        .unwrap();
+
+    assert_eq!(
+        model.update_named_style("Bold", &Style::default()),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.delete_named_style("Bold"),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    // No cell of the protected sheet uses it
+    model.delete_named_style("Unused").unwrap();
+
+    model.unprotect_sheet(0, None).unwrap();
+    let protection = SheetProtection {
+        format_cells: true,
+        ..Default::default()
+    };
+    model.protect_sheet(0, &protection, None).unwrap();
+    model.update_named_style("Bold", &Style::default()).unwrap();
+    assert!(!model.get_cell_style(0, 1, 1).unwrap().font.b);
+}
@@ -1,7 +1,7 @@
 #![allow(clippy::unwrap_used)]
 
 use crate::test::util::new_empty_model;
-use crate::types::Fill;
+use crate::types::{Color, Fill, PatternType};
 use crate::UserModel;
 
 #[test]
@@ -10,9 +10,10 @@ fn simple_pasting() {
     let mut model = UserModel::from_model(model);
     let mut style = model.get_cell_style(0, 1, 1).unwrap();
     style.fill = Fill {
-        pattern_type: "solid".to_string(),
-        fg_color: Some("#FF5577".to_string()),
-        bg_color: Some("#33FF44".to_string()),
+        pattern_type: PatternType::Solid,
+        fg_color: Some(Color::Rgb("#FF5577".to_string())),
+        bg_color: Some(Color::Rgb("#33FF44".to_string())),
+        ..Default::default()
     };
     let styles = vec![vec![style.clone()]];
 
@@ -0,0 +1,166 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::test::user_model::util::new_empty_user_model;
+use crate::UserModel;
+
+fn hidden_rows(model: &UserModel, rows: i32) -> Vec<i32> {
+    (1..=rows)
+        .filter(|row| model.is_row_hidden(0, *row).unwrap())
+        .collect()
+}
+
+fn outline_levels(model: &UserModel, rows: i32) -> Vec<u8> {
+    (1..=rows)
+        .map(|row| model.get_row_outline_level(0, row).unwrap())
+        .collect()
+}
+
+#[test]
+fn group_and_ungroup_rows() {
+    let mut model = new_empty_user_model();
+    model.group_rows(0, 2, 5).unwrap();
+    model.group_rows(0, 3, 4).unwrap();
+    assert_eq!(outline_levels(&model, 6), [0, 1, 2, 2, 1, 0]);
+
+    model.ungroup_rows(0, 1, 3).unwrap();
+    assert_eq!(outline_levels(&model, 6), [0, 0, 1, 2, 1, 0]);
+
+    model.undo().unwrap();
+    assert_eq!(outline_levels(&model, 6), [0, 1, 2, 2, 1, 0]);
+    model.undo().unwrap();
+    assert_eq!(outline_levels(&model, 6), [0, 1, 1, 1, 1, 0]);
+    model.redo().unwrap();
+    assert_eq!(outline_levels(&model, 6), [0, 1, 2, 2, 1, 0]);
+}
+
+#[test]
+fn group_too_deep() {
+    let mut model = new_empty_user_model();
+    for _ in 0..7 {
+        model.group_rows(0, 2, 3).unwrap();
+    }
+    assert_eq!(model.get_row_outline_level(0, 2), Ok(7));
+    assert_eq!(
+        model.group_rows(0, 1, 3),
+        Err("Groups cannot be nested more than 7 levels".to_string())
+    );
+    assert_eq!(model.get_row_outline_level(0, 1), Ok(0));
+}
+
+#[test]
+fn collapse_and_expand_rows() {
+    let mut model = new_empty_user_model();
+    // Rows 2-5 are the details of the summary row 6, rows 3-4 are the details of row 5
+    model.group_rows(0, 2, 5).unwrap();
+    model.group_rows(0, 3, 4).unwrap();
+
+    // Collapsing from a row in the group collapses the innermost group
+    model.collapse_row_group(0, 3).unwrap();
+    assert_eq!(hidden_rows(&model, 7), [3, 4]);
+    assert!(model.is_row_collapsed(0, 5).unwrap());
+
+    // Collapsing from the summary row
+    model.collapse_row_group(0, 6).unwrap();
+    assert_eq!(hidden_rows(&model, 7), [2, 3, 4, 5]);
+    assert!(model.is_row_collapsed(0, 6).unwrap());
+
+    // The collapsed subgroup stays collapsed
+    model.expand_row_group(0, 6).unwrap();
+    assert_eq!(hidden_rows(&model, 7), [3, 4]);
+    assert!(!model.is_row_collapsed(0, 6).unwrap());
+
+    model.expand_row_group(0, 5).unwrap();
+    assert_eq!(hidden_rows(&model, 7), Vec::<i32>::new());
+
+    model.undo().unwrap();
+    assert_eq!(hidden_rows(&model, 7), [3, 4]);
+    model.undo().unwrap();
+    assert_eq!(hidden_rows(&model, 7), [2, 3, 4, 5]);
+    assert!(model.is_row_collapsed(0, 6).unwrap());
+
+    let send_queue = model.flush_send_queue();
+    let mut model2 = new_empty_user_model();
+    model2.apply_external_diffs(&send_queue).unwrap();
+    assert_eq!(hidden_rows(&model2, 7), [2, 3, 4, 5]);
+    assert_eq!(outline_levels(&model2, 6), [0, 1, 2, 2, 1, 0]);
+}
+
+#[test]
+fn summary_above() {
+    let mut model = new_empty_user_model();
+    model.set_outline_summary_below(0, false).unwrap();
+    model.group_rows(0, 3, 4).unwrap();
+    model.collapse_row_group(0, 2).unwrap();
+    assert_eq!(hidden_rows(&model, 5), [3, 4]);
+    assert!(model.is_row_collapsed(0, 2).unwrap());
+
+    model.undo().unwrap();
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert!(model.get_outline_summary_below(0).unwrap());
+}
+
+#[test]
+fn not_in_a_group() {
+    let mut model = new_empty_user_model();
+    model.group_columns(0, 2, 3).unwrap();
+    assert_eq!(
+        model.collapse_row_group(0, 2),
+        Err("Row 2 is not in a group".to_string())
+    );
+    assert_eq!(
+        model.collapse_column_group(0, 6),
+        Err("Column F is not in a group".to_string())
+    );
+}
+
+#[test]
+fn collapse_and_expand_columns() {
+    let mut model = new_empty_user_model();
+    model.set_columns_width(0, 2, 4, 200.0).unwrap();
+    model.group_columns(0, 3, 4).unwrap();
+    model.collapse_column_group(0, 5).unwrap();
+    assert!(!model.is_column_hidden(0, 2).unwrap());
+    assert!(model.is_column_hidden(0, 3).unwrap());
+    assert!(model.is_column_hidden(0, 4).unwrap());
+    assert!(model.is_column_collapsed(0, 5).unwrap());
+    // The width is kept
+    assert_eq!(model.get_column_width(0, 4).unwrap(), 200.0);
+
+    model.expand_column_group(0, 3).unwrap();
+    assert!(!model.is_column_hidden(0, 3).unwrap());
+    assert!(!model.is_column_collapsed(0, 5).unwrap());
+
+    model.set_columns_hidden(0, 7, 8, true).unwrap();
+    assert!(model.is_column_hidden(0, 8).unwrap());
+    model.undo().unwrap();
+    assert!(!model.is_column_hidden(0, 8).unwrap());
+}
+
+#[test]
+fn subtotal_skips_collapsed_rows() {
+    let mut model = new_empty_user_model();
+    for row in 1..=4 {
+        model.set_user_input(0, row, 1, &row.to_string()).unwrap();
+    }
+    model.set_user_input(0, 1, 2, "5").unwrap();
+    model
+        .set_user_input(0, 6, 1, "=SUBTOTAL(109,A1:A4)")
+        .unwrap();
+    model.set_user_input(0, 7, 1, "=SUBTOTAL(9,A1:A4)").unwrap();
+    model
+        .set_user_input(0, 8, 1, "=SUBTOTAL(109,A1:B1)")
+        .unwrap();
+    model.group_rows(0, 2, 3).unwrap();
+    model.collapse_row_group(0, 4).unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 6, 1).unwrap(), "5");
+    assert_eq!(model.get_formatted_cell_value(0, 7, 1).unwrap(), "10");
+
+    // Hidden columns are not ignored
+    model.set_columns_hidden(0, 2, 2, true).unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 8, 1).unwrap(), "6");
+
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_formatted_cell_value(0, 6, 1).unwrap(), "10");
+}
@@ -0,0 +1,154 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{PageOrientation, PageSetup, PrintTitles};
+
+#[test]
+fn set_page_setup() {
+    let mut model = new_empty_user_model();
+    assert_eq!(model.get_page_setup(0).unwrap(), PageSetup::default());
+
+    let mut page_setup = model.get_page_setup(0).unwrap();
+    page_setup.orientation = PageOrientation::Landscape;
+    page_setup.paper_size = 9;
+    page_setup.fit_to_page = true;
+    page_setup.fit_to_height = 0;
+    page_setup.header_footer.odd_footer = "&RPage &P of &N".to_string();
+    model.set_page_setup(0, page_setup.clone()).unwrap();
+    assert_eq!(model.get_page_setup(0).unwrap(), page_setup);
+
+    model.undo().unwrap();
+    assert_eq!(model.get_page_setup(0).unwrap(), PageSetup::default());
+    model.redo().unwrap();
+    assert_eq!(model.get_page_setup(0).unwrap(), page_setup);
+
+    let send_queue = model.flush_send_queue();
+    let mut model2 = new_empty_user_model();
+    model2.apply_external_diffs(&send_queue).unwrap();
+    assert_eq!(model2.get_page_setup(0).unwrap(), page_setup);
+}
+
+#[test]
+fn invalid_page_setup() {
+    let mut model = new_empty_user_model();
+    let mut page_setup = PageSetup {
+        scale: 500,
+        ..Default::default()
+    };
+    assert_eq!(
+        model.set_page_setup(0, page_setup.clone()),
+        Err("Invalid scale: 500".to_string())
+    );
+    page_setup.scale = 100;
+    page_setup.margins.left = -1.0;
+    assert_eq!(
+        model.set_page_setup(0, page_setup),
+        Err("Invalid margin: -1".to_string())
+    );
+    assert_eq!(
+        model.insert_row_page_break(0, 0),
+        Err("Invalid page break row: 0".to_string())
+    );
+}
+
+#[test]
+fn page_breaks() {
+    let mut model = new_empty_user_model();
+    model.insert_row_page_break(0, 40).unwrap();
+    model.insert_row_page_break(0, 20).unwrap();
+    model.insert_row_page_break(0, 40).unwrap();
+    model.insert_column_page_break(0, 8).unwrap();
+    assert_eq!(model.get_page_setup(0).unwrap().row_breaks, [20, 40]);
+    assert_eq!(model.get_page_setup(0).unwrap().column_breaks, [8]);
+
+    model.delete_row_page_break(0, 20).unwrap();
+    assert_eq!(model.get_page_setup(0).unwrap().row_breaks, [40]);
+    model.undo().unwrap();
+    assert_eq!(model.get_page_setup(0).unwrap().row_breaks, [20, 40]);
+}
+
+#[test]
+fn print_area() {
+    let mut model = new_empty_user_model();
+    model.rename_sheet(0, "Board pack").unwrap();
+    assert_eq!(model.get_print_area(0).unwrap(), None);
+    model
+        .set_print_area(&Area {
+            sheet: 0,
+            row: 2,
+            column: 1,
+            width: 6,
+            height: 39,
+        })
+        .unwrap();
+    assert_eq!(
+        model.get_print_area(0).unwrap(),
+        Some("'Board pack'!$A$2:$F$40".to_string())
+    );
+    // The print area is a defined name local to the sheet
+    assert_eq!(
+        model.get_defined_name_list(),
+        [(
+            "_xlnm.Print_Area".to_string(),
+            Some(0),
+            "'Board pack'!$A$2:$F$40".to_string()
+        )]
+    );
+
+    model.delete_print_area(0).unwrap();
+    assert_eq!(model.get_print_area(0).unwrap(), None);
+    model.undo().unwrap();
+    assert_eq!(
+        model.get_print_area(0).unwrap(),
+        Some("'Board pack'!$A$2:$F$40".to_string())
+    );
+}
+
+#[test]
+fn print_titles() {
+    let mut model = new_empty_user_model();
+    let print_titles = PrintTitles {
+        rows: Some((1, 2)),
+        columns: Some((1, 1)),
+    };
+    model.set_print_titles(0, &print_titles).unwrap();
+    assert_eq!(model.get_print_titles(0).unwrap(), print_titles);
+    assert_eq!(
+        model.get_defined_name_list()[0].2,
+        "Sheet1!$A:$A,Sheet1!$1:$2"
+    );
+
+    let only_rows = PrintTitles {
+        rows: Some((3, 3)),
+        columns: None,
+    };
+    model.set_print_titles(0, &only_rows).unwrap();
+    assert_eq!(model.get_print_titles(0).unwrap(), only_rows);
+
+    model.set_print_titles(0, &PrintTitles::default()).unwrap();
+    assert!(model.get_defined_name_list().is_empty());
+
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert_eq!(model.get_print_titles(0).unwrap(), print_titles);
+
+    // Renaming the sheet keeps the print titles
+    model.rename_sheet(0, "Board pack").unwrap();
+    assert_eq!(model.get_print_titles(0).unwrap(), print_titles);
+    assert_eq!(
+        model.get_defined_name_list()[0].2,
+        "'Board pack'!$A:$A,'Board pack'!$1:$2"
+    );
+
+    assert_eq!(
+        model.set_print_titles(
+            0,
+            &PrintTitles {
+                rows: Some((3, 2)),
+                columns: None,
+            }
+        ),
+        Err("Invalid print title rows".to_string())
+    );
+}
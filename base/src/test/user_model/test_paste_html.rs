@@ -0,0 +1,302 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{BorderStyle, Color, HorizontalAlignment, MergedRange, VerticalAlignment};
+use crate::UserModel;
+
+fn area(row: i32, column: i32, width: i32, height: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width,
+        height,
+    }
+}
+
+fn copy_html(model: &mut UserModel, range: [i32; 4]) -> String {
+    model.set_selected_cell(range[0], range[1]).unwrap();
+    model
+        .set_selected_range(range[0], range[1], range[2], range[3])
+        .unwrap();
+    model.copy_to_html().unwrap()
+}
+
+fn paste_html(model: &mut UserModel, row: i32, column: i32, html: &str) {
+    model.set_selected_cell(row, column).unwrap();
+    model.paste_html_string(html).unwrap();
+}
+
+#[test]
+fn copy_as_html() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Fish & <Chips>").unwrap();
+    model.set_user_input(0, 1, 3, "3.5").unwrap();
+    model.set_user_input(0, 2, 1, "=C1*2").unwrap();
+    model
+        .update_range_style(&area(1, 1, 1, 1), "font.b", "true")
+        .unwrap();
+    model
+        .update_range_style(&area(1, 3, 1, 1), "fill.fg_color", "#FF0000")
+        .unwrap();
+    model
+        .update_range_style(&area(1, 3, 1, 1), "num_fmt", "0.00")
+        .unwrap();
+    model.merge_cells(&area(1, 1, 2, 1)).unwrap();
+
+    let html = copy_html(&mut model, [1, 1, 2, 3]);
+    assert!(html.starts_with("<table"));
+    assert!(html.contains("<col style=\"width:"));
+    assert!(html.contains(" colspan=\"2\""));
+    assert!(html.contains(">Fish &amp; &lt;Chips&gt;</td>"));
+    assert!(html.contains("font-weight:bold"));
+    assert!(html.contains("background-color:#FF0000"));
+    assert!(html.contains("mso-number-format:'0.00'"));
+    // Formatted values
+    assert!(html.contains(">3.50</td>"));
+    assert!(html.contains(">7</td>"));
+    // The merged cell covers B1, the first row has two cells
+    let first_row = html.split("</tr>").next().unwrap();
+    assert_eq!(first_row.matches("<td").count(), 2);
+}
+
+#[test]
+fn html_round_trip() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Name").unwrap();
+    model.set_user_input(0, 1, 2, "0.25").unwrap();
+    model.set_user_input(0, 2, 1, "'=not a formula").unwrap();
+    model
+        .update_range_style(&area(1, 1, 1, 1), "font.i", "true")
+        .unwrap();
+    model
+        .update_range_style(&area(1, 1, 1, 1), "alignment.horizontal", "center")
+        .unwrap();
+    model
+        .update_range_style(&area(1, 2, 1, 1), "num_fmt", "0%")
+        .unwrap();
+    let html = copy_html(&mut model, [1, 1, 2, 2]);
+
+    let mut other = new_empty_user_model();
+    paste_html(&mut other, 3, 3, &html);
+    assert_eq!(other.get_cell_content(0, 3, 3), Ok("Name".to_string()));
+    let style = other.get_cell_style(0, 3, 3).unwrap();
+    assert!(style.font.i);
+    assert_eq!(
+        style.alignment.unwrap().horizontal,
+        HorizontalAlignment::Center
+    );
+    assert_eq!(
+        other.get_formatted_cell_value(0, 3, 4),
+        Ok("25%".to_string())
+    );
+    assert_eq!(
+        other.get_cell_content(0, 4, 3),
+        Ok("=not a formula".to_string())
+    );
+    assert_eq!(other.get_selected_view().range, [3, 3, 4, 4]);
+
+    other.undo().unwrap();
+    assert_eq!(other.get_cell_content(0, 3, 3), Ok("".to_string()));
+    assert!(!other.get_cell_style(0, 3, 3).unwrap().font.i);
+}
+
+#[test]
+fn paste_excel_html() {
+    let html = r#"<html xmlns:x="urn:schemas-microsoft-com:office:excel">
+<head>
+<style>
+<!--table
+	{mso-displayed-decimal-separator:"\.";}
+td
+	{padding-top:1px;
+	font-size:11.0pt;
+	font-family:Calibri, sans-serif;
+	mso-number-format:General;
+	white-space:nowrap;}
+.xl65
+	{font-weight:700;
+	color:red;
+	border-bottom:.5pt solid windowtext;}
+.xl66
+	{mso-number-format:"0\.00";
+	background:#FFFF00;
+	mso-pattern:black none;}
+.xl67
+	{mso-number-format:"\@";}
+-->
+</style>
+</head>
+<body>
+<table border=0 cellpadding=0 cellspacing=0 width=128>
+<!--StartFragment-->
+ <tr height=20>
+  <td height=20 class=xl65 width=64>Total&nbsp;cost</td>
+  <td class=xl66 align=right x:num="1234.5">1234.50</td>
+ </tr>
+ <tr height=20>
+  <td height=20 class=xl67>00123</td>
+  <td>&nbsp;</td>
+ </tr>
+<!--EndFragment-->
+</table>
+</body>
+</html>"#;
+    let mut model = new_empty_user_model();
+    paste_html(&mut model, 1, 1, html);
+
+    assert_eq!(
+        model.get_cell_content(0, 1, 1),
+        Ok("Total cost".to_string())
+    );
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert!(style.font.b);
+    assert_eq!(style.font.sz, 11);
+    assert_eq!(style.font.name, "Calibri");
+    assert_eq!(style.font.color, Some(Color::Rgb("#FF0000".to_string())));
+    let border = style.border.bottom.unwrap();
+    assert_eq!(border.style, BorderStyle::Thin);
+    assert_eq!(border.color, Some(Color::Rgb("#000000".to_string())));
+
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("1234.5".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 2),
+        Ok("1234.50".to_string())
+    );
+    let style = model.get_cell_style(0, 1, 2).unwrap();
+    assert_eq!(style.fill.fg_color, Some(Color::Rgb("#FFFF00".to_string())));
+    assert_eq!(
+        style.alignment.unwrap().horizontal,
+        HorizontalAlignment::Right
+    );
+
+    // Text format keeps the leading zeros
+    assert_eq!(model.get_cell_content(0, 2, 1), Ok("00123".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 2), Ok("".to_string()));
+}
+
+#[test]
+fn paste_google_sheets_html() {
+    let html = r#"<meta charset='utf-8'><google-sheets-html-origin><style type="text/css"><!--td {border: 1px solid #cccccc;}br {mso-data-placement:same-cell;}--></style><table xmlns="http://www.w3.org/1999/xhtml" cellspacing="0" cellpadding="0" dir="ltr" border="1" style="table-layout:fixed;font-size:10pt;font-family:Arial;width:0px;border-collapse:collapse;border:none"><colgroup><col width="100"/><col width="100"/></colgroup><tbody><tr style="height:21px;"><td style="overflow:hidden;padding:2px 3px 2px 3px;vertical-align:middle;background-color:rgb(201, 218, 248);font-weight:bold;" rowspan="2" colspan="2" data-sheets-value="{&quot;1&quot;:2,&quot;2&quot;:&quot;Header&quot;}">Header</td><td style="font-size:16px;text-decoration:underline line-through;" data-sheets-value="{&quot;1&quot;:3,&quot;3&quot;:0.1}">10%</td></tr><tr style="height:21px;"><td style="white-space:normal;">Line one<br>Line   two</td></tr></tbody></table>"#;
+    let mut model = new_empty_user_model();
+    paste_html(&mut model, 1, 1, html);
+
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("Header".to_string()));
+    // The cells covered by the header get its style
+    for (row, column) in [(1, 1), (1, 2), (2, 1), (2, 2)] {
+        let style = model.get_cell_style(0, row, column).unwrap();
+        assert_eq!(style.fill.fg_color, Some(Color::Rgb("#C9DAF8".to_string())));
+        assert!(style.font.b);
+        assert_eq!(style.alignment.unwrap().vertical, VerticalAlignment::Center);
+    }
+    assert_eq!(model.get_cell_content(0, 1, 2), Ok("".to_string()));
+
+    assert_eq!(model.get_cell_content(0, 1, 3), Ok("0.1".to_string()));
+    let style = model.get_cell_style(0, 1, 3).unwrap();
+    assert_eq!(style.font.sz, 12);
+    assert!(style.font.is_underlined());
+    assert!(style.font.strike);
+    assert_eq!(
+        style.border.top.unwrap().color,
+        Some(Color::Rgb("#CCCCCC".to_string()))
+    );
+
+    // The second row starts after the header
+    assert_eq!(
+        model.get_cell_content(0, 2, 3),
+        Ok("Line one\nLine two".to_string())
+    );
+    assert!(
+        model
+            .get_cell_style(0, 2, 3)
+            .unwrap()
+            .alignment
+            .unwrap()
+            .wrap_text
+    );
+    assert_eq!(model.get_selected_view().range, [1, 1, 2, 3]);
+    assert_eq!(
+        model.get_merged_cells(0),
+        Ok(vec![MergedRange::parse("A1:B2").unwrap()])
+    );
+}
+
+#[test]
+fn merged_cells_round_trip() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "Title").unwrap();
+    model.set_user_input(0, 2, 1, "a").unwrap();
+    model.set_user_input(0, 2, 2, "b").unwrap();
+    model.merge_cells(&area(1, 1, 2, 1)).unwrap();
+    let html = copy_html(&mut model, [1, 1, 2, 2]);
+
+    let mut other = new_empty_user_model();
+    other.set_user_input(0, 3, 4, "covered").unwrap();
+    paste_html(&mut other, 3, 3, &html);
+    assert_eq!(
+        other.get_merged_cells(0),
+        Ok(vec![MergedRange::parse("C3:D3").unwrap()])
+    );
+    assert_eq!(other.get_cell_content(0, 3, 3), Ok("Title".to_string()));
+    assert_eq!(other.get_cell_content(0, 3, 4), Ok("".to_string()));
+    assert_eq!(other.get_cell_content(0, 4, 4), Ok("b".to_string()));
+
+    // The paste and the merge are undone together
+    other.undo().unwrap();
+    assert_eq!(other.get_merged_cells(0), Ok(vec![]));
+    assert_eq!(other.get_cell_content(0, 3, 3), Ok("".to_string()));
+    assert_eq!(other.get_cell_content(0, 3, 4), Ok("covered".to_string()));
+    other.redo().unwrap();
+    assert_eq!(
+        other.get_merged_cells(0),
+        Ok(vec![MergedRange::parse("C3:D3").unwrap()])
+    );
+
+    // A span partially overlapping merged cells leaves the sheet untouched
+    let mut other = new_empty_user_model();
+    other.merge_cells(&area(1, 2, 1, 2)).unwrap();
+    other.set_selected_cell(1, 1).unwrap();
+    assert!(other.paste_html_string(&html).is_err());
+    assert_eq!(other.get_cell_content(0, 1, 1), Ok("".to_string()));
+    assert_eq!(other.get_cell_content(0, 2, 2), Ok("".to_string()));
+    assert_eq!(
+        other.get_merged_cells(0),
+        Ok(vec![MergedRange::parse("B1:B2").unwrap()])
+    );
+}
+
+#[test]
+fn paste_web_page_html() {
+    let html = "<p>Some text</p><table><thead><tr><th>Fruit</th><th>Price</th></tr></thead>\
+        <tbody><tr><td><b>Apple</b></td><td>1.5</td></tr>\
+        <tr><td>=SUM(1)</td><td>n/a</td></tr></tbody></table><table><tr><td>Ignored</td></tr></table>";
+    let mut model = new_empty_user_model();
+    paste_html(&mut model, 1, 1, html);
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("Fruit".to_string()));
+    assert!(model.get_cell_style(0, 1, 2).unwrap().font.b);
+    assert!(model.get_cell_style(0, 2, 1).unwrap().font.b);
+    assert!(!model.get_cell_style(0, 2, 2).unwrap().font.b);
+    assert_eq!(model.get_cell_content(0, 2, 2), Ok("1.5".to_string()));
+    // Formulas in html are pasted as text
+    assert_eq!(model.get_cell_content(0, 3, 1), Ok("=SUM(1)".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 3, 1),
+        Ok("=SUM(1)".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 3, 2), Ok("n/a".to_string()));
+    assert_eq!(model.get_cell_content(0, 4, 1), Ok("".to_string()));
+
+    assert_eq!(
+        model.paste_html_string("<p>No table here</p>"),
+        Err("No table found".to_string())
+    );
+}
+
+#[test]
+fn paste_html_with_locale() {
+    let mut model = UserModel::new_empty("model", "de", "UTC", "de").unwrap();
+    let html = r#"<table><tr><td x:num="2.5">2.50</td></tr></table>"#;
+    paste_html(&mut model, 1, 1, html);
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("2,5".to_string()));
+}
@@ -0,0 +1,377 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::constants::{LAST_COLUMN, LAST_ROW};
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::CellType;
+use crate::{ClipboardPayload, PasteMode, PasteOperation, PasteOptions, UserModel};
+
+// A1:B3 with numbers, a formula, a text, a bold cell and an empty cell
+fn source_model<'a>() -> UserModel<'a> {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "10").unwrap();
+    model.set_user_input(0, 1, 2, "=A1*2").unwrap();
+    model.set_user_input(0, 2, 1, "'00123").unwrap();
+    model.set_user_input(0, 3, 2, "2.5").unwrap();
+    model.set_selected_cell(1, 1).unwrap();
+    model.set_selected_range(1, 1, 1, 1).unwrap();
+    model.set_selected_cell(1, 2).unwrap();
+    model
+        .update_range_style(
+            &crate::expressions::types::Area {
+                sheet: 0,
+                row: 1,
+                column: 2,
+                width: 1,
+                height: 1,
+            },
+            "font.b",
+            "true",
+        )
+        .unwrap();
+    model.set_columns_width(0, 2, 2, 150.0).unwrap();
+    model
+}
+
+fn copy(model: &mut UserModel, range: [i32; 4]) -> ClipboardPayload {
+    model.set_selected_cell(range[0], range[1]).unwrap();
+    model
+        .set_selected_range(range[0], range[1], range[2], range[3])
+        .unwrap();
+    model.copy_to_payload().unwrap()
+}
+
+fn paste(
+    model: &mut UserModel,
+    row: i32,
+    column: i32,
+    payload: &ClipboardPayload,
+    options: &PasteOptions,
+) {
+    model.set_selected_cell(row, column).unwrap();
+    model.paste_payload(payload, options).unwrap();
+}
+
+#[test]
+fn paste_all() {
+    let mut model = source_model();
+    let payload = copy(&mut model, [1, 1, 3, 2]);
+    assert_eq!(payload.cells.len(), 3);
+    assert_eq!(payload.column_widths.len(), 2);
+    assert_eq!(payload.cells[0][1].formula, Some("A1*2".to_string()));
+
+    paste(&mut model, 5, 3, &payload, &PasteOptions::default());
+    assert_eq!(model.get_cell_content(0, 5, 3), Ok("10".to_string()));
+    assert_eq!(model.get_cell_content(0, 5, 4), Ok("=C5*2".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 5, 4),
+        Ok("20".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, 6, 3), Ok("00123".to_string()));
+    assert!(model.get_cell_style(0, 5, 4).unwrap().font.b);
+    // The pasted area is selected
+    assert_eq!(model.get_selected_view().range, [5, 3, 7, 4]);
+
+    // All the changes are undone at once
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 5, 3), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 5, 4), Ok("".to_string()));
+    assert!(!model.get_cell_style(0, 5, 4).unwrap().font.b);
+    model.redo().unwrap();
+    assert_eq!(model.get_cell_content(0, 5, 4), Ok("=C5*2".to_string()));
+}
+
+#[test]
+fn paste_values_formulas_and_formats() {
+    let mut model = source_model();
+    let payload = copy(&mut model, [1, 1, 2, 2]);
+
+    let values = PasteOptions {
+        mode: PasteMode::Values,
+        ..Default::default()
+    };
+    paste(&mut model, 10, 1, &payload, &values);
+    assert_eq!(model.get_cell_content(0, 10, 2), Ok("20".to_string()));
+    assert_eq!(model.get_cell_content(0, 11, 1), Ok("00123".to_string()));
+    assert!(!model.get_cell_style(0, 10, 2).unwrap().font.b);
+
+    let formulas = PasteOptions {
+        mode: PasteMode::Formulas,
+        ..Default::default()
+    };
+    paste(&mut model, 20, 1, &payload, &formulas);
+    assert_eq!(model.get_cell_content(0, 20, 2), Ok("=A20*2".to_string()));
+    assert!(!model.get_cell_style(0, 20, 2).unwrap().font.b);
+
+    let formats = PasteOptions {
+        mode: PasteMode::Formats,
+        ..Default::default()
+    };
+    model.set_user_input(0, 30, 2, "keep").unwrap();
+    paste(&mut model, 30, 1, &payload, &formats);
+    assert_eq!(model.get_cell_content(0, 30, 2), Ok("keep".to_string()));
+    assert!(model.get_cell_style(0, 30, 2).unwrap().font.b);
+}
+
+#[test]
+fn paste_column_widths() {
+    let mut model = source_model();
+    let payload = copy(&mut model, [1, 1, 1, 2]);
+    let options = PasteOptions {
+        mode: PasteMode::ColumnWidths,
+        ..Default::default()
+    };
+    paste(&mut model, 1, 5, &payload, &options);
+    assert_eq!(model.get_column_width(0, 6), Ok(150.0));
+    assert_eq!(model.get_cell_content(0, 1, 6), Ok("".to_string()));
+    model.undo().unwrap();
+    assert_ne!(model.get_column_width(0, 6), Ok(150.0));
+}
+
+#[test]
+fn transpose_and_skip_blanks() {
+    let mut model = source_model();
+    let payload = copy(&mut model, [1, 1, 3, 2]);
+    model.set_user_input(0, 1, 7, "kept").unwrap();
+    let options = PasteOptions {
+        transpose: true,
+        skip_blanks: true,
+        ..Default::default()
+    };
+    paste(&mut model, 1, 5, &payload, &options);
+    // A1:B3 becomes E1:G2
+    assert_eq!(model.get_cell_content(0, 1, 5), Ok("10".to_string()));
+    assert_eq!(model.get_cell_content(0, 1, 6), Ok("00123".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 5), Ok("=E1*2".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 6), Ok("".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 7), Ok("2.5".to_string()));
+    // A3 is empty, G1 was left untouched
+    assert_eq!(model.get_cell_content(0, 1, 7), Ok("kept".to_string()));
+    assert_eq!(model.get_selected_view().range, [1, 5, 2, 7]);
+
+    // Without skipping blanks the empty cell clears the target
+    let options = PasteOptions {
+        skip_blanks: false,
+        ..Default::default()
+    };
+    model.set_user_input(0, 12, 1, "gone").unwrap();
+    paste(&mut model, 10, 1, &payload, &options);
+    assert_eq!(model.get_cell_content(0, 12, 1), Ok("".to_string()));
+}
+
+#[test]
+fn arithmetic_operations() {
+    let mut model = source_model();
+    let payload = copy(&mut model, [1, 1, 1, 2]);
+    model.set_user_input(0, 5, 1, "5").unwrap();
+    model.set_user_input(0, 5, 2, "=1+1").unwrap();
+
+    let options = PasteOptions {
+        mode: PasteMode::Values,
+        operation: PasteOperation::Add,
+        ..Default::default()
+    };
+    paste(&mut model, 5, 1, &payload, &options);
+    assert_eq!(model.get_cell_content(0, 5, 1), Ok("15".to_string()));
+    assert_eq!(model.get_cell_content(0, 5, 2), Ok("=1+1+20".to_string()));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 5, 2),
+        Ok("22".to_string())
+    );
+
+    let options = PasteOptions {
+        operation: PasteOperation::Multiply,
+        ..options
+    };
+    paste(&mut model, 6, 1, &payload, &options);
+    // Empty cells count as zero
+    assert_eq!(model.get_cell_content(0, 6, 1), Ok("0".to_string()));
+
+    model.set_user_input(0, 7, 1, "3").unwrap();
+    model.set_user_input(0, 7, 2, "text").unwrap();
+    let options = PasteOptions {
+        mode: PasteMode::Formulas,
+        operation: PasteOperation::Subtract,
+        ..Default::default()
+    };
+    paste(&mut model, 7, 1, &payload, &options);
+    assert_eq!(model.get_cell_content(0, 7, 1), Ok("-7".to_string()));
+    // Text is not combined
+    assert_eq!(model.get_cell_content(0, 7, 2), Ok("text".to_string()));
+
+    model.set_user_input(0, 100, 1, "0").unwrap();
+    let zero = copy(&mut model, [100, 1, 100, 1]);
+    let options = PasteOptions {
+        mode: PasteMode::Values,
+        operation: PasteOperation::Divide,
+        ..Default::default()
+    };
+    paste(&mut model, 7, 1, &zero, &options);
+    assert_eq!(
+        model.get_formatted_cell_value(0, 7, 1),
+        Ok("#DIV/0!".to_string())
+    );
+}
+
+#[test]
+fn paste_link() {
+    let mut model = source_model();
+    model.new_sheet().unwrap();
+    model.set_selected_sheet(0).unwrap();
+    let payload = copy(&mut model, [1, 1, 1, 2]);
+    model.set_selected_sheet(1).unwrap();
+    let options = PasteOptions {
+        link: true,
+        ..Default::default()
+    };
+    paste(&mut model, 3, 3, &payload, &options);
+    assert_eq!(
+        model.get_cell_content(1, 3, 3),
+        Ok("=Sheet1!A1".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(1, 3, 4),
+        Ok("=Sheet1!B1".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(1, 3, 4),
+        Ok("20".to_string())
+    );
+}
+
+#[test]
+fn paste_into_another_workbook() {
+    let mut source = source_model();
+    source.new_sheet().unwrap();
+    source.new_sheet().unwrap();
+    source.set_user_input(1, 1, 1, "7").unwrap();
+    source.set_selected_sheet(0).unwrap();
+    source.set_user_input(0, 4, 1, "=Sheet2!A1+1").unwrap();
+    source
+        .set_user_input(0, 4, 2, "=SUM(Sheet3!A1:A3)")
+        .unwrap();
+    source.set_user_input(0, 5, 1, "=B5").unwrap();
+    source.set_user_input(0, 5, 2, "TRUE").unwrap();
+    let payload = copy(&mut source, [1, 1, 5, 2]);
+
+    // The payload can be sent elsewhere
+    let json = serde_json::to_string(&payload).unwrap();
+    let payload: ClipboardPayload = serde_json::from_str(&json).unwrap();
+
+    let mut target = UserModel::new_empty("target", "de", "UTC", "de").unwrap();
+    target.rename_sheet(0, "Sheet2").unwrap();
+    paste(&mut target, 1, 1, &payload, &PasteOptions::default());
+
+    // Localized formulas and numbers
+    assert_eq!(target.get_cell_content(0, 3, 2), Ok("2,5".to_string()));
+    assert_eq!(target.get_cell_content(0, 1, 2), Ok("=A1*2".to_string()));
+    assert_eq!(target.get_cell_content(0, 5, 2), Ok("WAHR".to_string()));
+    // Styles are added to the target workbook
+    assert!(target.get_cell_style(0, 1, 2).unwrap().font.b);
+    // Sheet2 exists in the target, Sheet3 does not
+    assert_eq!(
+        target.get_cell_content(0, 4, 1),
+        Ok("=Sheet2!A1+1".to_string())
+    );
+    assert_eq!(
+        target.get_cell_content(0, 4, 2),
+        Ok("=SUMME(#BEZUG!)".to_string())
+    );
+    assert_eq!(target.get_cell_content(0, 5, 1), Ok("=B5".to_string()));
+}
+
+#[test]
+fn pasted_text_is_only_quoted_when_needed() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "hello").unwrap();
+    model.set_user_input(0, 2, 1, "=\"5\"").unwrap();
+    model.set_user_input(0, 3, 1, "'TRUE").unwrap();
+    let payload = copy(&mut model, [1, 1, 3, 1]);
+    let values = PasteOptions {
+        mode: PasteMode::Values,
+        ..Default::default()
+    };
+    paste(&mut model, 1, 3, &payload, &values);
+
+    assert_eq!(model.get_cell_content(0, 1, 3), Ok("hello".to_string()));
+    assert!(!model.get_cell_style(0, 1, 3).unwrap().quote_prefix);
+    // The text result of a formula that looks like a number is kept as text
+    assert_eq!(model.get_cell_content(0, 2, 3), Ok("5".to_string()));
+    assert!(model.get_cell_style(0, 2, 3).unwrap().quote_prefix);
+    assert_eq!(model.get_cell_type(0, 2, 3), Ok(CellType::Text));
+    assert_eq!(model.get_cell_type(0, 3, 3), Ok(CellType::Text));
+}
+
+#[test]
+fn transpose_mixed_references() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "1").unwrap();
+    model.set_user_input(0, 2, 1, "2").unwrap();
+    model.set_user_input(0, 1, 2, "=$A1*10").unwrap();
+    model.set_user_input(0, 2, 2, "=$A2*10+$A$1").unwrap();
+    let payload = copy(&mut model, [1, 1, 2, 2]);
+    let options = PasteOptions {
+        transpose: true,
+        ..Default::default()
+    };
+    paste(&mut model, 1, 5, &payload, &options);
+
+    // The header column A1:A2 becomes the header row E1:F1
+    assert_eq!(model.get_cell_content(0, 1, 6), Ok("2".to_string()));
+    assert_eq!(model.get_cell_content(0, 2, 5), Ok("=E$1*10".to_string()));
+    assert_eq!(
+        model.get_cell_content(0, 2, 6),
+        Ok("=F$1*10+$A$1".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 2, 6),
+        Ok("21".to_string())
+    );
+}
+
+#[test]
+fn paste_at_the_edge_of_the_sheet() {
+    let mut model = source_model();
+    let payload = copy(&mut model, [1, 1, 3, 2]);
+    model.set_user_input(0, 5, 5, "last edit").unwrap();
+
+    // A1:B3 does not fit at A1048575, nothing is written
+    model.set_selected_cell(LAST_ROW - 1, 1).unwrap();
+    assert_eq!(
+        model.paste_payload(&payload, &PasteOptions::default()),
+        Err("Cannot paste outside of the sheet".to_string())
+    );
+    assert_eq!(
+        model.get_cell_content(0, LAST_ROW - 1, 1),
+        Ok("".to_string())
+    );
+    assert_eq!(model.get_cell_content(0, LAST_ROW, 1), Ok("".to_string()));
+
+    // The column widths do not fit either
+    model.set_selected_cell(1, LAST_COLUMN).unwrap();
+    let options = PasteOptions {
+        mode: PasteMode::ColumnWidths,
+        ..Default::default()
+    };
+    assert!(model.paste_payload(&payload, &options).is_err());
+    assert_eq!(
+        model.get_column_width(0, LAST_COLUMN),
+        model.get_column_width(0, 1)
+    );
+
+    // Undo goes back to the last change that was made
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 5, 5), Ok("".to_string()));
+
+    // The last rows of the sheet can be pasted over
+    paste(
+        &mut model,
+        LAST_ROW - 2,
+        LAST_COLUMN - 1,
+        &payload,
+        &PasteOptions::default(),
+    );
+    assert_eq!(
+        model.get_cell_content(0, LAST_ROW, LAST_COLUMN),
+        Ok("2.5".to_string())
+    );
+}
@@ -0,0 +1,193 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::{ProtectionPassword, SheetProtection};
+use crate::ProtectionError;
+
+fn area(row: i32, column: i32, width: i32, height: i32) -> Area {
+    Area {
+        sheet: 0,
+        row,
+        column,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn locked_cells() {
+    let mut model = new_empty_user_model();
+    model
+        .update_range_style(&area(1, 1, 2, 2), "protection.locked", "false")
+        .unwrap();
+    model
+        .protect_sheet(0, &SheetProtection::default(), None)
+        .unwrap();
+    assert!(!model.is_cell_locked(0, 2, 2).unwrap());
+    assert!(model.is_cell_locked(0, 3, 1).unwrap());
+
+    model.set_user_input(0, 2, 2, "42").unwrap();
+    assert_eq!(
+        model.set_user_input(0, 3, 1, "42"),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.range_clear_contents(&area(1, 1, 3, 2)),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.update_range_style(&area(1, 1, 1, 1), "font.b", "true"),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.insert_rows(0, 5, 1),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.set_columns_width(0, 1, 1, 200.0),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(model.get_formatted_cell_value(0, 2, 2).unwrap(), "42");
+
+    // Undoing the protection allows the edits again
+    model.undo().unwrap();
+    model.undo().unwrap();
+    model.set_user_input(0, 3, 1, "42").unwrap();
+}
+
+#[test]
+fn allowed_operations() {
+    let mut model = new_empty_user_model();
+    let protection = SheetProtection {
+        insert_rows: true,
+        format_columns: true,
+        ..Default::default()
+    };
+    model.protect_sheet(0, &protection, None).unwrap();
+    model.insert_rows(0, 5, 1).unwrap();
+    model.set_columns_width(0, 1, 1, 200.0).unwrap();
+    model.group_columns(0, 2, 3).unwrap();
+    assert_eq!(
+        model.delete_rows(0, 5, 1),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.group_rows(0, 2, 3),
+        Err(ProtectionError::ProtectedSheet.to_string())
+    );
+    assert_eq!(
+        model.get_sheet_protection(0).unwrap(),
+        Some(protection.clone())
+    );
+    assert_eq!(
+        model.protect_sheet(0, &protection, None),
+        Err("The sheet is already protected".to_string())
+    );
+
+    let send_queue = model.flush_send_queue();
+    let mut model2 = new_empty_user_model();
+    model2.apply_external_diffs(&send_queue).unwrap();
+    assert_eq!(model2.get_sheet_protection(0).unwrap(), Some(protection));
+}
+
+#[test]
+fn sheet_password() {
+    let mut model = new_empty_user_model();
+    model
+        .protect_sheet(0, &SheetProtection::default(), Some("secret"))
+        .unwrap();
+    assert_eq!(
+        model.unprotect_sheet(0, Some("Secret")),
+        Err("The password you supplied is not correct".to_string())
+    );
+    assert_eq!(
+        model.unprotect_sheet(0, None),
+        Err("The password you supplied is not correct".to_string())
+    );
+    model.unprotect_sheet(0, Some("secret")).unwrap();
+    model.set_user_input(0, 1, 1, "42").unwrap();
+    assert_eq!(
+        model.unprotect_sheet(0, None),
+        Err("The sheet is not protected".to_string())
+    );
+}
+
+#[test]
+fn excel_password_hashes() {
+    // Computed with a salt of the bytes 0 to 15
+    let password = ProtectionPassword::Hashed {
+        algorithm_name: "SHA-512".to_string(),
+        hash_value: "M5SOVnbQG4SHyBnRVAYzAx8mPtxyyzMuWxcMv7tkyFO3MBXX9OJjklwPglNHdoHVkKPm4MPfUblqHmAsXfF5HA==".to_string(),
+        salt_value: "AAECAwQFBgcICQoLDA0ODw==".to_string(),
+        spin_count: 100_000,
+    };
+    assert_eq!(password.verify("secret"), Ok(true));
+    assert_eq!(password.verify("Secret"), Ok(false));
+    let salt: Vec<u8> = (0..16).collect();
+    assert_eq!(ProtectionPassword::with_salt("secret", &salt), password);
+
+    let legacy = ProtectionPassword::Legacy("83AF".to_string());
+    assert_eq!(legacy.verify("password"), Ok(true));
+    assert_eq!(legacy.verify("secret"), Ok(false));
+}
+
+#[test]
+fn new_passwords_have_random_salts() {
+    let first = ProtectionPassword::new("secret").unwrap();
+    let second = ProtectionPassword::new("secret").unwrap();
+    assert_ne!(first, second);
+    assert_eq!(first.verify("secret"), Ok(true));
+    assert_eq!(second.verify("secret"), Ok(true));
+}
+
+#[test]
+fn protection_errors_are_recognized() {
+    let mut model = new_empty_user_model();
+    model
+        .protect_sheet(0, &SheetProtection::default(), None)
+        .unwrap();
+    let error = model.set_user_input(0, 1, 1, "1").unwrap_err();
+    assert_eq!(
+        ProtectionError::from_message(&error),
+        Some(ProtectionError::ProtectedSheet)
+    );
+    assert_eq!(ProtectionError::from_message("Invalid sheet index"), None);
+}
+
+#[test]
+fn hidden_formulas() {
+    let mut model = new_empty_user_model();
+    model.set_user_input(0, 1, 1, "=1+1").unwrap();
+    model.set_user_input(0, 2, 1, "7").unwrap();
+    model
+        .update_range_style(&area(1, 1, 1, 2), "protection.hidden", "true")
+        .unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1).unwrap(), "=1+1");
+    model
+        .protect_sheet(0, &SheetProtection::default(), None)
+        .unwrap();
+    assert_eq!(model.get_cell_content(0, 1, 1).unwrap(), "");
+    assert_eq!(model.get_cell_content(0, 2, 1).unwrap(), "7");
+    assert_eq!(model.get_formatted_cell_value(0, 1, 1).unwrap(), "2");
+}
+
+#[test]
+fn workbook_structure() {
+    let mut model = new_empty_user_model();
+    model.protect_workbook(true, false, Some("secret")).unwrap();
+    assert!(model.is_workbook_structure_locked());
+    let error = Err(ProtectionError::ProtectedWorkbook.to_string());
+    assert_eq!(model.new_sheet(), error);
+    assert_eq!(model.rename_sheet(0, "Data"), error);
+    assert_eq!(model.hide_sheet(0), error);
+    assert_eq!(model.delete_sheet(0), error);
+    // Cells can still be edited
+    model.set_user_input(0, 1, 1, "42").unwrap();
+
+    model.unprotect_workbook(Some("secret")).unwrap();
+    model.rename_sheet(0, "Data").unwrap();
+    model.undo().unwrap();
+    model.undo().unwrap();
+    assert!(model.is_workbook_structure_locked());
+}
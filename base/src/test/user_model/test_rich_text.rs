@@ -0,0 +1,105 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::test::util::new_empty_model;
+use crate::types::{FontVerticalAlign, RunFont, TextRun};
+use crate::UserModel;
+
+fn heading_runs() -> Vec<TextRun> {
+    vec![
+        TextRun {
+            text: "Total ".to_string(),
+            font: None,
+        },
+        TextRun {
+            text: "sales".to_string(),
+            font: Some(RunFont {
+                b: Some(true),
+                color: Some("#FF0000".to_string()),
+                ..Default::default()
+            }),
+        },
+        TextRun {
+            text: "2".to_string(),
+            font: Some(RunFont {
+                vert_align: Some(FontVerticalAlign::Superscript),
+                ..Default::default()
+            }),
+        },
+    ]
+}
+
+#[test]
+fn set_and_get_rich_text() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_cell_rich_text(0, 1, 1, &heading_runs()).unwrap();
+    model.set_user_input(0, 1, 2, "=LEN(A1)").unwrap();
+    model.set_user_input(0, 1, 3, "=UPPER(A1)").unwrap();
+
+    assert_eq!(model.get_cell_rich_text(0, 1, 1), Ok(Some(heading_runs())));
+    assert_eq!(
+        model.get_cell_content(0, 1, 1),
+        Ok("Total sales2".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 2),
+        Ok("12".to_string())
+    );
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 3),
+        Ok("TOTAL SALES2".to_string())
+    );
+
+    // Typing the same text gives a plain string
+    model.set_user_input(0, 2, 1, "Total sales2").unwrap();
+    assert_eq!(model.get_cell_rich_text(0, 2, 1), Ok(None));
+    assert_eq!(model.get_cell_rich_text(0, 1, 1), Ok(Some(heading_runs())));
+
+    assert!(model.set_cell_rich_text(0, 3, 1, &[]).is_err());
+}
+
+#[test]
+fn undo_redo_rich_text() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_user_input(0, 1, 1, "Hello").unwrap();
+    model.set_cell_rich_text(0, 1, 1, &heading_runs()).unwrap();
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_rich_text(0, 1, 1), Ok(None));
+    assert_eq!(model.get_cell_content(0, 1, 1), Ok("Hello".to_string()));
+
+    model.redo().unwrap();
+    assert_eq!(model.get_cell_rich_text(0, 1, 1), Ok(Some(heading_runs())));
+
+    let bytes = model.to_bytes();
+    let model = UserModel::from_bytes(&bytes, "en").unwrap();
+    assert_eq!(model.get_cell_rich_text(0, 1, 1), Ok(Some(heading_runs())));
+}
+
+#[test]
+fn rich_text_reuses_shared_strings() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    model.set_user_input(0, 1, 1, "Total sales2").unwrap();
+    let count = model.get_model().workbook.shared_strings.len();
+
+    // Runs without formatting are plain text
+    let plain_runs = vec![
+        TextRun {
+            text: "Total ".to_string(),
+            font: None,
+        },
+        TextRun {
+            text: "sales2".to_string(),
+            font: None,
+        },
+    ];
+    model.set_cell_rich_text(0, 2, 1, &plain_runs).unwrap();
+    assert_eq!(model.get_cell_rich_text(0, 2, 1), Ok(None));
+    assert_eq!(model.get_model().workbook.shared_strings.len(), count);
+
+    model.set_cell_rich_text(0, 3, 1, &heading_runs()).unwrap();
+    model.set_cell_rich_text(0, 4, 1, &heading_runs()).unwrap();
+    assert_eq!(model.get_model().workbook.shared_strings.len(), count + 1);
+}
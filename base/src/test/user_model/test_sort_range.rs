@@ -0,0 +1,175 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::expressions::types::Area;
+use crate::test::user_model::util::new_empty_user_model;
+use crate::types::SortKey;
+use crate::UserModel;
+
+fn set_column(model: &mut UserModel, column: i32, values: &[&str]) {
+    for (index, value) in values.iter().enumerate() {
+        model
+            .set_user_input(0, index as i32 + 1, column, value)
+            .unwrap();
+    }
+}
+
+fn get_column(model: &UserModel, column: i32, rows: i32) -> Vec<String> {
+    (1..=rows)
+        .map(|row| model.get_formatted_cell_value(0, row, column).unwrap())
+        .collect()
+}
+
+fn area(column: i32, width: i32, height: i32) -> Area {
+    Area {
+        sheet: 0,
+        row: 1,
+        column,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn sort_by_multiple_columns() {
+    let mut model = new_empty_user_model();
+    set_column(&mut model, 1, &["b", "a", "b", "a", "c"]);
+    set_column(&mut model, 2, &["1", "2", "3", "4", "5"]);
+    let keys = [
+        SortKey {
+            column: 1,
+            ascending: true,
+        },
+        SortKey {
+            column: 2,
+            ascending: false,
+        },
+    ];
+    model.sort_range(&area(1, 2, 5), &keys).unwrap();
+    assert_eq!(get_column(&model, 1, 5), ["a", "a", "b", "b", "c"]);
+    assert_eq!(get_column(&model, 2, 5), ["4", "2", "3", "1", "5"]);
+
+    // A single undo restores the range
+    model.undo().unwrap();
+    assert_eq!(get_column(&model, 1, 5), ["b", "a", "b", "a", "c"]);
+    assert_eq!(get_column(&model, 2, 5), ["1", "2", "3", "4", "5"]);
+
+    model.redo().unwrap();
+    assert_eq!(get_column(&model, 2, 5), ["4", "2", "3", "1", "5"]);
+
+    let send_queue = model.flush_send_queue();
+    let mut model2 = new_empty_user_model();
+    model2.apply_external_diffs(&send_queue).unwrap();
+    assert_eq!(get_column(&model2, 1, 5), ["a", "a", "b", "b", "c"]);
+    assert_eq!(get_column(&model2, 2, 5), ["4", "2", "3", "1", "5"]);
+}
+
+#[test]
+fn sort_mixed_types() {
+    let mut model = new_empty_user_model();
+    set_column(
+        &mut model,
+        1,
+        &["TRUE", "", "b", "#N/A", "2", "A", "1", "FALSE"],
+    );
+    let keys = [SortKey {
+        column: 1,
+        ascending: true,
+    }];
+    model.sort_range(&area(1, 1, 8), &keys).unwrap();
+    assert_eq!(
+        get_column(&model, 1, 8),
+        ["1", "2", "A", "b", "FALSE", "TRUE", "#N/A", ""]
+    );
+
+    // Blank cells are always at the end
+    let keys = [SortKey {
+        column: 1,
+        ascending: false,
+    }];
+    model.sort_range(&area(1, 1, 8), &keys).unwrap();
+    assert_eq!(
+        get_column(&model, 1, 8),
+        ["#N/A", "TRUE", "FALSE", "b", "A", "2", "1", ""]
+    );
+}
+
+#[test]
+fn sort_is_stable_and_case_insensitive() {
+    let mut model = new_empty_user_model();
+    set_column(&mut model, 1, &["b", "B", "a", "A"]);
+    set_column(&mut model, 2, &["1", "2", "3", "4"]);
+    let keys = [SortKey {
+        column: 1,
+        ascending: true,
+    }];
+    model.sort_range(&area(1, 2, 4), &keys).unwrap();
+    assert_eq!(get_column(&model, 1, 4), ["a", "A", "b", "B"]);
+    assert_eq!(get_column(&model, 2, 4), ["3", "4", "1", "2"]);
+}
+
+#[test]
+fn sort_locale_collation() {
+    let mut model = new_empty_user_model();
+    set_column(&mut model, 1, &["o", "nz", "ñ", "Étage", "etc", "n"]);
+    let keys = [SortKey {
+        column: 1,
+        ascending: true,
+    }];
+    model.sort_range(&area(1, 1, 6), &keys).unwrap();
+    assert_eq!(
+        get_column(&model, 1, 6),
+        ["Étage", "etc", "n", "ñ", "nz", "o"]
+    );
+
+    // In Spanish 'ñ' is a letter after 'n'
+    model.set_locale("es").unwrap();
+    model.sort_range(&area(1, 1, 6), &keys).unwrap();
+    assert_eq!(
+        get_column(&model, 1, 6),
+        ["Étage", "etc", "n", "nz", "ñ", "o"]
+    );
+}
+
+#[test]
+fn sort_moves_styles_and_formulas() {
+    let mut model = new_empty_user_model();
+    set_column(&mut model, 1, &["3", "1", "2"]);
+    set_column(&mut model, 2, &["=A1*10", "=A2*10", "=$A$2*10"]);
+    model
+        .update_range_style(&area(1, 1, 1), "font.b", "true")
+        .unwrap();
+    let keys = [SortKey {
+        column: 1,
+        ascending: true,
+    }];
+    model.sort_range(&area(1, 2, 3), &keys).unwrap();
+    assert_eq!(get_column(&model, 1, 3), ["1", "2", "3"]);
+    // Relative references are displaced, absolute references are kept
+    assert_eq!(model.get_cell_content(0, 1, 2).unwrap(), "=A1*10");
+    assert_eq!(model.get_cell_content(0, 2, 2).unwrap(), "=$A$2*10");
+    assert_eq!(model.get_cell_content(0, 3, 2).unwrap(), "=A3*10");
+    assert_eq!(get_column(&model, 2, 3), ["10", "20", "30"]);
+    assert!(model.get_cell_style(0, 3, 1).unwrap().font.b);
+    assert!(!model.get_cell_style(0, 1, 1).unwrap().font.b);
+
+    model.undo().unwrap();
+    assert_eq!(model.get_cell_content(0, 3, 2).unwrap(), "=$A$2*10");
+    assert!(model.get_cell_style(0, 1, 1).unwrap().font.b);
+}
+
+#[test]
+fn sort_errors() {
+    let mut model = new_empty_user_model();
+    let keys = [SortKey {
+        column: 3,
+        ascending: true,
+    }];
+    assert_eq!(
+        model.sort_range(&area(1, 2, 3), &keys),
+        Err("Sort column 3 is outside the range".to_string())
+    );
+    assert_eq!(
+        model.sort_range(&area(1, 2, 3), &[]),
+        Err("At least one sort key is needed".to_string())
+    );
+}
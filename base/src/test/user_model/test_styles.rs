@@ -3,7 +3,10 @@
 use crate::{
     expressions::types::Area,
     test::user_model::util::new_empty_user_model,
-    types::{Alignment, HorizontalAlignment, VerticalAlignment},
+    types::{
+        Alignment, Color, FontUnderline, FontVerticalAlign, HorizontalAlignment, PatternType,
+        ReadingOrder, VerticalAlignment,
+    },
 };
 
 #[test]
@@ -20,9 +23,9 @@ fn basic_fonts() {
     let style = model.get_cell_style(0, 1, 1).unwrap();
     assert!(!style.font.i);
     assert!(!style.font.b);
-    assert!(!style.font.u);
+    assert!(!style.font.is_underlined());
     assert!(!style.font.strike);
-    assert_eq!(style.font.color, Some("#000000".to_owned()));
+    assert_eq!(style.font.color, Some(Color::Rgb("#000000".to_string())));
 
     // bold
     model.update_range_style(&range, "font.b", "true").unwrap();
@@ -37,7 +40,7 @@ fn basic_fonts() {
     // underline
     model.update_range_style(&range, "font.u", "true").unwrap();
     let style = model.get_cell_style(0, 1, 1).unwrap();
-    assert!(style.font.u);
+    assert!(style.font.is_underlined());
 
     // strike
     model
@@ -51,7 +54,7 @@ fn basic_fonts() {
         .update_range_style(&range, "font.color", "#F1F1F1")
         .unwrap();
     let style = model.get_cell_style(0, 1, 1).unwrap();
-    assert_eq!(style.font.color, Some("#F1F1F1".to_owned()));
+    assert_eq!(style.font.color, Some(Color::Rgb("#F1F1F1".to_string())));
 
     while model.can_undo() {
         model.undo().unwrap();
@@ -60,9 +63,9 @@ fn basic_fonts() {
     let style = model.get_cell_style(0, 1, 1).unwrap();
     assert!(!style.font.i);
     assert!(!style.font.b);
-    assert!(!style.font.u);
+    assert!(!style.font.is_underlined());
     assert!(!style.font.strike);
-    assert_eq!(style.font.color, Some("#000000".to_owned()));
+    assert_eq!(style.font.color, Some(Color::Rgb("#000000".to_string())));
 
     while model.can_redo() {
         model.redo().unwrap();
@@ -71,9 +74,9 @@ fn basic_fonts() {
     let style = model.get_cell_style(0, 1, 1).unwrap();
     assert!(style.font.i);
     assert!(style.font.b);
-    assert!(style.font.u);
+    assert!(style.font.is_underlined());
     assert!(style.font.strike);
-    assert_eq!(style.font.color, Some("#F1F1F1".to_owned()));
+    assert_eq!(style.font.color, Some(Color::Rgb("#F1F1F1".to_string())));
 
     let send_queue = model.flush_send_queue();
 
@@ -83,9 +86,9 @@ fn basic_fonts() {
     let style = model2.get_cell_style(0, 1, 1).unwrap();
     assert!(style.font.i);
     assert!(style.font.b);
-    assert!(style.font.u);
+    assert!(style.font.is_underlined());
     assert!(style.font.strike);
-    assert_eq!(style.font.color, Some("#F1F1F1".to_owned()));
+    assert_eq!(style.font.color, Some(Color::Rgb("#F1F1F1".to_string())));
 }
 
 #[test]
@@ -145,7 +148,7 @@ fn basic_fill() {
     let style = model.get_cell_style(0, 1, 1).unwrap();
     assert_eq!(style.fill.bg_color, None);
     assert_eq!(style.fill.fg_color, None);
-    assert_eq!(&style.fill.pattern_type, "none");
+    assert_eq!(style.fill.pattern_type, PatternType::None);
 
     // bg_color
     model
@@ -155,9 +158,9 @@ fn basic_fill() {
         .update_range_style(&range, "fill.fg_color", "#F3F4F5")
         .unwrap();
     let style = model.get_cell_style(0, 1, 1).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#F2F2F2".to_owned()));
-    assert_eq!(style.fill.fg_color, Some("#F3F4F5".to_owned()));
-    assert_eq!(&style.fill.pattern_type, "solid");
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#F2F2F2".to_string())));
+    assert_eq!(style.fill.fg_color, Some(Color::Rgb("#F3F4F5".to_string())));
+    assert_eq!(style.fill.pattern_type, PatternType::Solid);
 
     let send_queue = model.flush_send_queue();
 
@@ -165,8 +168,8 @@ fn basic_fill() {
     model2.apply_external_diffs(&send_queue).unwrap();
 
     let style = model2.get_cell_style(0, 1, 1).unwrap();
-    assert_eq!(style.fill.bg_color, Some("#F2F2F2".to_owned()));
-    assert_eq!(style.fill.fg_color, Some("#F3F4F5".to_owned()));
+    assert_eq!(style.fill.bg_color, Some(Color::Rgb("#F2F2F2".to_string())));
+    assert_eq!(style.fill.fg_color, Some(Color::Rgb("#F3F4F5".to_string())));
 }
 
 #[test]
@@ -252,7 +255,8 @@ fn basic_alignment() {
         Some(Alignment {
             horizontal: HorizontalAlignment::Center,
             vertical: VerticalAlignment::Bottom,
-            wrap_text: false
+            wrap_text: false,
+            ..Default::default()
         })
     );
 
@@ -265,7 +269,8 @@ fn basic_alignment() {
         Some(Alignment {
             horizontal: HorizontalAlignment::CenterContinuous,
             vertical: VerticalAlignment::Bottom,
-            wrap_text: false
+            wrap_text: false,
+            ..Default::default()
         })
     );
 
@@ -286,7 +291,8 @@ fn basic_alignment() {
         Some(Alignment {
             horizontal: HorizontalAlignment::General,
             vertical: VerticalAlignment::Distributed,
-            wrap_text: false
+            wrap_text: false,
+            ..Default::default()
         })
     );
 
@@ -299,7 +305,8 @@ fn basic_alignment() {
         Some(Alignment {
             horizontal: HorizontalAlignment::General,
             vertical: VerticalAlignment::Justify,
-            wrap_text: false
+            wrap_text: false,
+            ..Default::default()
         })
     );
 
@@ -315,7 +322,8 @@ fn basic_alignment() {
         Some(Alignment {
             horizontal: HorizontalAlignment::General,
             vertical: VerticalAlignment::Justify,
-            wrap_text: false
+            wrap_text: false,
+            ..Default::default()
         })
     );
 }
@@ -391,7 +399,8 @@ fn basic_wrap_text() {
         Some(Alignment {
             horizontal: HorizontalAlignment::General,
             vertical: VerticalAlignment::Bottom,
-            wrap_text: true
+            wrap_text: true,
+            ..Default::default()
         })
     );
     model.undo().unwrap();
@@ -406,7 +415,8 @@ fn basic_wrap_text() {
         Some(Alignment {
             horizontal: HorizontalAlignment::General,
             vertical: VerticalAlignment::Bottom,
-            wrap_text: true
+            wrap_text: true,
+            ..Default::default()
         })
     );
 
@@ -480,3 +490,115 @@ fn cell_clear_formatting() {
     assert!(!style.font.b);
     assert_eq!(style.alignment, None);
 }
+
+#[test]
+fn extended_font_attributes() {
+    let mut model = new_empty_user_model();
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+
+    model
+        .update_range_style(&range, "font.u", "double")
+        .unwrap();
+    model
+        .update_range_style(&range, "font.vert_align", "superscript")
+        .unwrap();
+    model
+        .update_range_style(&range, "font.outline", "true")
+        .unwrap();
+    model
+        .update_range_style(&range, "font.shadow", "true")
+        .unwrap();
+    model
+        .update_range_style(&range, "font.condense", "true")
+        .unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert!(style.font.is_underlined());
+    assert_eq!(style.font.underline, FontUnderline::Double);
+    assert_eq!(style.font.vert_align, FontVerticalAlign::Superscript);
+    assert!(style.font.outline);
+    assert!(style.font.shadow);
+    assert!(style.font.condense);
+
+    model.update_range_style(&range, "font.u", "false").unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert!(!style.font.is_underlined());
+    assert_eq!(style.font.underline, FontUnderline::None);
+
+    assert_eq!(
+        model.update_range_style(&range, "font.u", "triple"),
+        Err("Invalid value for underline: 'triple'.".to_string())
+    );
+    assert_eq!(
+        model.update_range_style(&range, "font.vert_align", "top"),
+        Err("Invalid value for vertical align: 'top'.".to_string())
+    );
+
+    model.undo().unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.font.underline, FontUnderline::Double);
+}
+
+#[test]
+fn extended_alignment_attributes() {
+    let mut model = new_empty_user_model();
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+
+    model
+        .update_range_style(&range, "alignment.text_rotation", "255")
+        .unwrap();
+    model
+        .update_range_style(&range, "alignment.indent", "2")
+        .unwrap();
+    model
+        .update_range_style(&range, "alignment.shrink_to_fit", "true")
+        .unwrap();
+    model
+        .update_range_style(&range, "alignment.justify_last_line", "true")
+        .unwrap();
+    model
+        .update_range_style(&range, "alignment.reading_order", "righttoleft")
+        .unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(
+        style.alignment,
+        Some(Alignment {
+            text_rotation: 255,
+            indent: 2,
+            shrink_to_fit: true,
+            justify_last_line: true,
+            reading_order: ReadingOrder::RightToLeft,
+            ..Default::default()
+        })
+    );
+
+    assert_eq!(
+        model.update_range_style(&range, "alignment.text_rotation", "200"),
+        Err("Invalid value for text rotation: '200'.".to_string())
+    );
+    assert_eq!(
+        model.update_range_style(&range, "alignment.indent", "-1"),
+        Err("Invalid value for indent: '-1'.".to_string())
+    );
+    assert_eq!(
+        model.update_range_style(&range, "alignment.reading_order", "up"),
+        Err("Invalid value for reading order: 'up'.".to_string())
+    );
+
+    while model.can_undo() {
+        model.undo().unwrap();
+    }
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.alignment, None);
+}
@@ -0,0 +1,166 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::diffs;
+use crate::expressions::types::Area;
+use crate::test::util::new_empty_model;
+use crate::types::{Color, PatternType, Theme};
+use crate::{Model, UserModel};
+
+fn set_theme_colors(model: &mut Model) {
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    // Accent 1
+    style.font.color = Some(Color::theme(4, 0.0));
+    // Accent 2, lighter 60%
+    style.fill.pattern_type = PatternType::Solid;
+    style.fill.fg_color = Some(Color::theme(5, 0.6));
+    model.set_cell_style(0, 1, 1, &style).unwrap();
+}
+
+#[test]
+fn set_theme_recolors_cells() {
+    let mut model = new_empty_model();
+    set_theme_colors(&mut model);
+    let mut model = UserModel::from_model(model);
+    assert_eq!(model.get_theme(), &Theme::default());
+
+    let mut theme = Theme {
+        name: "Custom".to_string(),
+        ..Default::default()
+    };
+    theme.colors[4] = "#112233".to_string();
+    theme.colors[5] = "#FF0000".to_string();
+    model.set_theme(theme.clone()).unwrap();
+    assert_eq!(model.get_theme(), &theme);
+
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::Rgb("#112233".to_string())));
+    assert_eq!(style.fill.fg_color, Some(Color::Rgb("#FF9999".to_string())));
+
+    model.undo().unwrap();
+    assert_eq!(model.get_theme(), &Theme::default());
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::Rgb("#4472C4".to_string())));
+
+    model.redo().unwrap();
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::Rgb("#112233".to_string())));
+
+    let bytes = model.to_bytes();
+    let model = UserModel::from_bytes(&bytes, "en").unwrap();
+    assert_eq!(model.get_theme(), &theme);
+}
+
+#[test]
+fn explicit_color_unlinks_theme() {
+    let mut model = new_empty_model();
+    set_theme_colors(&mut model);
+    let mut model = UserModel::from_model(model);
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    model
+        .update_range_style(&range, "font.color", "#00FF00")
+        .unwrap();
+
+    let mut theme = Theme::default();
+    theme.colors[4] = "#112233".to_string();
+    model.set_theme(theme).unwrap();
+
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::Rgb("#00FF00".to_string())));
+    // The fill is still linked to the theme
+    let style = model.get_model().get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(style.fill.fg_color, Some(Color::theme(5, 0.6)));
+}
+
+#[test]
+fn editing_a_style_keeps_theme_colors() {
+    let mut model = new_empty_model();
+    set_theme_colors(&mut model);
+    let mut model = UserModel::from_model(model);
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    model.update_range_style(&range, "font.b", "true").unwrap();
+
+    let mut theme = Theme::default();
+    theme.colors[4] = "#112233".to_string();
+    model.set_theme(theme).unwrap();
+
+    let style = model.get_cell_style(0, 1, 1).unwrap();
+    assert!(style.font.b);
+    assert_eq!(style.font.color, Some(Color::Rgb("#112233".to_string())));
+    let style = model.get_model().get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::theme(4, 0.0)));
+}
+
+#[test]
+fn set_theme_diff_round_trip() {
+    let mut model = UserModel::from_model(new_empty_model());
+    let theme = Theme {
+        name: "Custom".to_string(),
+        xml: Some("<a:theme name=\"Custom\"/>".to_string()),
+        ..Default::default()
+    };
+    model.set_theme(theme.clone()).unwrap();
+    let changes = diffs::from_bytes(&model.flush_send_queue())
+        .unwrap()
+        .changes;
+    let decoded = diffs::from_json(&diffs::to_json(&changes)).unwrap();
+    assert_eq!(decoded.changes, changes);
+
+    let mut other = UserModel::from_model(new_empty_model());
+    other
+        .apply_external_diffs(&diffs::to_bytes(&decoded.changes))
+        .unwrap();
+    assert_eq!(other.get_theme(), &theme);
+}
+
+#[test]
+fn tints_are_clamped() {
+    assert_eq!(
+        Color::theme(4, f64::NAN),
+        Color::Theme {
+            theme: 4,
+            tint: 0.0
+        }
+    );
+    assert_eq!(
+        Color::theme(4, -2.5),
+        Color::Theme {
+            theme: 4,
+            tint: -1.0
+        }
+    );
+    let color: Color = serde_json::from_str(r#"{"theme": 4, "tint": 7}"#).unwrap();
+    assert_eq!(color, Color::theme(4, 1.0));
+    let color: Color = serde_json::from_str(r##""#FF0000""##).unwrap();
+    assert_eq!(color, Color::Rgb("#FF0000".to_string()));
+}
+
+#[test]
+fn invalid_theme() {
+    let mut model = UserModel::from_model(new_empty_model());
+    let mut theme = Theme::default();
+    theme.colors.pop();
+    assert_eq!(
+        model.set_theme(theme),
+        Err("A theme must have 12 colors".to_string())
+    );
+
+    let mut theme = Theme::default();
+    theme.colors[0] = "white".to_string();
+    assert_eq!(
+        model.set_theme(theme),
+        Err("Invalid color: 'white'.".to_string())
+    );
+    assert!(!model.can_undo());
+}
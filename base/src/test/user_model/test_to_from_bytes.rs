@@ -50,7 +50,7 @@ fn language() {
 }
 
 #[test]
-fn formulatext_english() {
+fn formulatext_localized() {
     let mut model = UserModel::from_model(new_empty_model());
     model.set_user_input(0, 1, 1, "=SUM(1, 2, 3)").unwrap();
     model.set_user_input(0, 1, 2, "=FORMULATEXT(A1)").unwrap();
@@ -59,6 +59,6 @@ fn formulatext_english() {
 
     assert_eq!(
         model.get_formatted_cell_value(0, 1, 2),
-        Ok("=SUM(1,2,3)".to_string())
+        Ok("=SUMME(1,2,3)".to_string())
     );
 }
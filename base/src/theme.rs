@@ -1,18 +1,25 @@
-#![allow(clippy::unwrap_used)]
+//! Workbook themes and the colours that depend on them.
+//!
+//! Styles keep colours as they are defined in the workbook. Colours linked to the theme or to the
+//! legacy palette are resolved to RGB values when they are read, so changing the theme recolours
+//! every style linked to it.
 
 use core::cmp::max;
 use core::cmp::min;
 
-// https://gist.github.com/emanuel-sanabria-developer/5793377
-// https://github.com/ClosedXML/ClosedXML/wiki/Excel-Indexed-Colors
+use crate::{
+    model::Model,
+    types::{BorderItem, Color, Style, Theme},
+    utils::is_valid_hex_color,
+};
 
 // Warning: Excel uses a weird normalization for HSL colors (0, 255)
 // We use a more standard one but our HSL numbers will not coincide with Excel's
 
 pub(crate) fn hex_to_rgb(h: &str) -> [i32; 3] {
-    let r = i32::from_str_radix(&h[1..3], 16).unwrap();
-    let g = i32::from_str_radix(&h[3..5], 16).unwrap();
-    let b = i32::from_str_radix(&h[5..7], 16).unwrap();
+    let r = i32::from_str_radix(&h[1..3], 16).unwrap_or(0);
+    let g = i32::from_str_radix(&h[3..5], 16).unwrap_or(0);
+    let b = i32::from_str_radix(&h[5..7], 16).unwrap_or(0);
     [r, g, b]
 }
 
@@ -20,7 +27,7 @@ pub(crate) fn rgb_to_hex(rgb: [i32; 3]) -> String {
     format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
 }
 
-pub(crate) fn rgb_to_hsl(rgb: [i32; 3]) -> [i32; 3] {
+fn rgb_to_hsl(rgb: [i32; 3]) -> [i32; 3] {
     let r = rgb[0];
     let g = rgb[1];
     let b = rgb[2];
@@ -78,7 +85,7 @@ fn hue_to_rgb(p: f64, q: f64, t: f64) -> f64 {
     p
 }
 
-pub(crate) fn hsl_to_rgb(hsl: [i32; 3]) -> [i32; 3] {
+fn hsl_to_rgb(hsl: [i32; 3]) -> [i32; 3] {
     let hue = (hsl[0] as f64) / 360.0;
     let saturation = (hsl[1] as f64) / 100.0;
     let luminosity = (hsl[2] as f64) / 100.0;
@@ -127,48 +134,154 @@ fn hex_with_tint_to_rgb(hex: &str, tint: f64) -> String {
     rgb_to_hex(hsl_to_rgb(hsl))
 }
 
-pub fn get_themed_color(theme: i32, tint: f64) -> String {
-    let color_theme = [
-        "#FFFFFF", "#000000", // "window",
-        "#E7E6E6", "#44546A", "#4472C4", "#ED7D31", "#A5A5A5", "#FFC000", "#5B9BD5", "#70AD47",
-        "#0563C1", "#954F72",
-    ];
-    hex_with_tint_to_rgb(color_theme[theme as usize], tint)
+impl Default for Theme {
+    /// The Office theme
+    fn default() -> Self {
+        Theme {
+            name: "Office Theme".to_string(),
+            colors: [
+                "#FFFFFF", "#000000", "#E7E6E6", "#44546A", "#4472C4", "#ED7D31", "#A5A5A5",
+                "#FFC000", "#5B9BD5", "#70AD47", "#0563C1", "#954F72",
+            ]
+            .iter()
+            .map(|color| color.to_string())
+            .collect(),
+            major_font: "Calibri Light".to_string(),
+            minor_font: "Calibri".to_string(),
+            xml: None,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the RGB value of the theme colour `index` with a tint
+    pub fn get_color(&self, index: i32, tint: f64) -> String {
+        let color = self
+            .colors
+            .get(index as usize)
+            .map(|color| color.as_str())
+            .unwrap_or("#000000");
+        hex_with_tint_to_rgb(color, tint)
+    }
+}
+
+// https://gist.github.com/emanuel-sanabria-developer/5793377
+// https://github.com/ClosedXML/ClosedXML/wiki/Excel-Indexed-Colors
+const INDEXED_COLORS: [&str; 64] = [
+    "#000000", "#FFFFFF", "#FF0000", "#00FF00", "#0000FF", "#FFFF00", "#FF00FF", "#00FFFF",
+    "#000000", "#FFFFFF", "#FF0000", "#00FF00", "#0000FF", "#FFFF00", "#FF00FF", "#00FFFF",
+    "#800000", "#008000", "#000080", "#808000", "#800080", "#008080", "#C0C0C0", "#808080",
+    "#9999FF", "#993366", "#FFFFCC", "#CCFFFF", "#660066", "#FF8080", "#0066CC", "#CCCCFF",
+    "#000080", "#FF00FF", "#FFFF00", "#00FFFF", "#800080", "#800000", "#008080", "#0000FF",
+    "#00CCFF", "#CCFFFF", "#CCFFCC", "#FFFF99", "#99CCFF", "#FF99CC", "#CC99FF", "#FFCC99",
+    "#3366FF", "#33CCCC", "#99CC00", "#FFCC00", "#FF9900", "#FF6600", "#666699", "#969696",
+    "#003366", "#339966", "#003300", "#333300", "#993300", "#993366", "#333399",
+    "#333333",
+    // 64, Transparent)
+];
+
+/// Returns a tint between -1.0 and 1.0. NaN is no tint at all.
+pub(crate) fn clamp_tint(tint: f64) -> f64 {
+    if tint.is_nan() {
+        0.0
+    } else {
+        tint.clamp(-1.0, 1.0)
+    }
+}
+
+impl Color {
+    /// Returns the theme colour `index` with a tint. The tint is clamped between -1.0 and 1.0.
+    pub fn theme(index: i32, tint: f64) -> Color {
+        Color::Theme {
+            theme: index,
+            tint: clamp_tint(tint),
+        }
+    }
+
+    /// Returns the RGB value of the colour in a workbook with the given theme
+    pub fn to_rgb(&self, theme: &Theme) -> String {
+        match self {
+            Color::Rgb(rgb) => rgb.clone(),
+            Color::Indexed { indexed } => usize::try_from(*indexed)
+                .ok()
+                .and_then(|index| INDEXED_COLORS.get(index))
+                .unwrap_or(&INDEXED_COLORS[0])
+                .to_string(),
+            Color::Theme { theme: index, tint } => theme.get_color(*index, *tint),
+        }
+    }
+}
+
+fn resolve_color(color: &mut Option<Color>, theme: &Theme) {
+    if let Some(value) = color {
+        *value = Color::Rgb(value.to_rgb(theme));
+    }
+}
+
+fn resolve_border_item(item: &mut Option<BorderItem>, theme: &Theme) {
+    if let Some(item) = item {
+        resolve_color(&mut item.color, theme);
+    }
+}
+
+impl Style {
+    /// Replaces every colour of the style by its RGB value in a workbook with the given theme
+    pub fn resolve_colors(&mut self, theme: &Theme) {
+        resolve_color(&mut self.font.color, theme);
+        resolve_color(&mut self.fill.fg_color, theme);
+        resolve_color(&mut self.fill.bg_color, theme);
+        if let Some(gradient) = &mut self.fill.gradient {
+            for stop in gradient.stops.iter_mut() {
+                resolve_color(&mut stop.color, theme);
+            }
+        }
+        let border = &mut self.border;
+        resolve_border_item(&mut border.left, theme);
+        resolve_border_item(&mut border.right, theme);
+        resolve_border_item(&mut border.top, theme);
+        resolve_border_item(&mut border.bottom, theme);
+        resolve_border_item(&mut border.diagonal, theme);
+    }
 }
 
-pub fn get_indexed_color(index: i32) -> String {
-    let color_list = [
-        "#000000", "#FFFFFF", "#FF0000", "#00FF00", "#0000FF", "#FFFF00", "#FF00FF", "#00FFFF",
-        "#000000", "#FFFFFF", "#FF0000", "#00FF00", "#0000FF", "#FFFF00", "#FF00FF", "#00FFFF",
-        "#800000", "#008000", "#000080", "#808000", "#800080", "#008080", "#C0C0C0", "#808080",
-        "#9999FF", "#993366", "#FFFFCC", "#CCFFFF", "#660066", "#FF8080", "#0066CC", "#CCCCFF",
-        "#000080", "#FF00FF", "#FFFF00", "#00FFFF", "#800080", "#800000", "#008080", "#0000FF",
-        "#00CCFF", "#CCFFFF", "#CCFFCC", "#FFFF99", "#99CCFF", "#FF99CC", "#CC99FF", "#FFCC99",
-        "#3366FF", "#33CCCC", "#99CC00", "#FFCC00", "#FF9900", "#FF6600", "#666699", "#969696",
-        "#003366", "#339966", "#003300", "#333300", "#993300", "#993366", "#333399",
-        "#333333",
-        // 64, Transparent)
-    ];
-    if index > 63 {
-        return color_list[0].to_string();
+impl Model<'_> {
+    /// Returns the theme of the workbook
+    pub fn get_theme(&self) -> &Theme {
+        &self.workbook.theme
+    }
+
+    /// Changes the theme of the workbook, recolouring every style linked to it.
+    /// If the new theme has no theme part we keep the one of the current theme.
+    pub fn set_theme(&mut self, mut theme: Theme) -> Result<(), String> {
+        if theme.colors.len() != 12 {
+            return Err("A theme must have 12 colors".to_string());
+        }
+        if let Some(color) = theme.colors.iter().find(|c| !is_valid_hex_color(c)) {
+            return Err(format!("Invalid color: '{color}'."));
+        }
+        if theme.xml.is_none() {
+            theme.xml = self.workbook.theme.xml.take();
+        }
+        self.workbook.theme = theme;
+        Ok(())
     }
-    color_list[index as usize].to_string()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::import::colors::*;
+    use super::*;
 
     #[test]
     fn test_known_colors() {
-        let color1 = get_themed_color(0, -0.05);
+        let theme = Theme::default();
+        let color1 = theme.get_color(0, -0.05);
         assert_eq!(color1, "#F2F2F2");
 
-        let color2 = get_themed_color(5, -0.25);
+        let color2 = theme.get_color(5, -0.25);
         // Excel returns "#C65911" (rounding error)
         assert_eq!(color2, "#C55911");
 
-        let color3 = get_themed_color(4, 0.6);
+        let color3 = theme.get_color(4, 0.6);
         // Excel returns "#b4c6e7" (rounding error)
         assert_eq!(color3, "#B5C8E8");
     }
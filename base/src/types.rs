@@ -1,8 +1,8 @@
 use bitcode::{Decode, Encode};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{collections::HashMap, fmt::Display};
 
-use crate::expressions::token::Error;
+use crate::{expressions::token::Error, theme::clamp_tint};
 
 fn default_as_false() -> bool {
     false
@@ -12,6 +12,10 @@ fn is_false(b: &bool) -> bool {
     !*b
 }
 
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
 #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub struct Metadata {
     pub application: String,
@@ -26,6 +30,21 @@ pub struct Metadata {
 pub struct WorkbookSettings {
     pub tz: String,
     pub locale: String,
+    pub date_system: DateSystem,
+    /// User defined lists that autofill extends, like `North, East, South, West`
+    pub custom_lists: Vec<Vec<String>>,
+}
+
+/// Dates are stored as serial numbers, the number of days since an epoch.
+/// * In the 1900 date system 1 is 1900-01-01 (and 1900 is wrongly considered a leap year)
+/// * In the 1904 date system, used by old versions of Excel for Mac, 0 is 1904-01-01
+///
+/// See 18.2.28 workbookPr (Workbook Properties), date1904 attribute.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DateSystem {
+    #[default]
+    Excel1900,
+    Excel1904,
 }
 
 /// A Workbook View tracks of the selected sheet for each view
@@ -43,6 +62,9 @@ pub struct WorkbookView {
 #[derive(Encode, Decode, Debug, PartialEq, Clone)]
 pub struct Workbook {
     pub shared_strings: Vec<String>,
+    /// The formatted runs of the shared strings that are rich text, by shared string index.
+    /// The plain text of those strings is still in `shared_strings`.
+    pub rich_text: HashMap<u32, Vec<TextRun>>,
     pub defined_names: Vec<DefinedName>,
     pub worksheets: Vec<Worksheet>,
     pub styles: Styles,
@@ -51,6 +73,11 @@ pub struct Workbook {
     pub metadata: Metadata,
     pub tables: HashMap<String, Table>,
     pub views: HashMap<u32, WorkbookView>,
+    /// The workbook protection, if the workbook is protected
+    pub protection: Option<WorkbookProtection>,
+    /// Parts of the xlsx file we do not understand (charts, images, pivot tables, VBA,...)
+    pub opaque_parts: OpaqueParts,
+    pub theme: Theme,
 }
 
 /// A defined name. The `sheet_id` is the sheet index in case the name is local
@@ -64,7 +91,8 @@ pub struct DefinedName {
 /// * state:
 ///   18.18.68 ST_SheetState (Sheet Visibility Types)
 ///   hidden, veryHidden, visible
-#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
 pub enum SheetState {
     Visible,
     Hidden,
@@ -84,7 +112,7 @@ impl Display for SheetState {
 /// Represents the state of the worksheet as seen by the user. This includes
 /// details such as the currently selected cell, the visible range, and the
 /// position of the viewport.
-#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
 pub struct WorksheetView {
     /// The row index of the currently selected cell.
     pub row: i32,
@@ -99,7 +127,7 @@ pub struct WorksheetView {
 }
 
 /// Internal representation of a worksheet Excel object
-#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
 pub struct Worksheet {
     pub dimension: String,
     pub cols: Vec<Col>,
@@ -110,13 +138,26 @@ pub struct Worksheet {
     pub sheet_id: u32,
     pub state: SheetState,
     pub color: Option<String>,
-    pub merge_cells: Vec<String>,
+    /// Blocks of merged cells, they never overlap
+    pub merge_cells: Vec<MergedRange>,
     pub comments: Vec<Comment>,
     pub frozen_rows: i32,
     pub frozen_columns: i32,
     pub views: HashMap<u32, WorksheetView>,
     /// Whether or not to show the grid lines in the worksheet
     pub show_grid_lines: bool,
+    /// The sheet level AutoFilter, if any
+    pub auto_filter: Option<AutoFilter>,
+    /// Whether the summary rows of the outline groups are below the detail rows
+    pub summary_below: bool,
+    /// Whether the summary columns of the outline groups are to the right of the detail columns
+    pub summary_right: bool,
+    /// Print settings of the worksheet
+    pub page_setup: PageSetup,
+    /// The sheet protection, if the sheet is protected
+    pub protection: Option<SheetProtection>,
+    /// Relationships and elements of the sheet we do not understand (drawings, pictures,...)
+    pub opaque_parts: OpaqueSheetParts,
 }
 
 /// Internal representation of Excel's sheet_data
@@ -124,7 +165,7 @@ pub struct Worksheet {
 pub type SheetData = HashMap<i32, HashMap<i32, Cell>>;
 
 // ECMA-376-1:2016 section 18.3.1.73
-#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
 pub struct Row {
     /// Row index
     pub r: i32,
@@ -133,10 +174,14 @@ pub struct Row {
     pub custom_height: bool,
     pub s: i32,
     pub hidden: bool,
+    /// Outline (grouping) level of the row, 0 if the row is not grouped
+    pub outline_level: u8,
+    /// Whether the outline group the row is a summary of is collapsed
+    pub collapsed: bool,
 }
 
 // ECMA-376-1:2016 section 18.3.1.13
-#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
 pub struct Col {
     // Column definitions are defined on ranges, unlike rows which store unique, per-row entries.
     /// First column affected by this record. Settings apply to column in \[min, max\] range.
@@ -146,6 +191,22 @@ pub struct Col {
     pub width: f64,
     pub custom_width: bool,
     pub style: Option<i32>,
+    pub hidden: bool,
+    /// Outline (grouping) level of the columns, 0 if they are not grouped
+    pub outline_level: u8,
+    /// Whether the outline group the columns are a summary of is collapsed
+    pub collapsed: bool,
+}
+
+/// A block of merged cells (both corners included). The value and style of the block
+/// are those of the top left cell.
+// ECMA-376-1:2016 section 18.3.1.55
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MergedRange {
+    pub first_row: i32,
+    pub first_column: i32,
+    pub last_row: i32,
+    pub last_column: i32,
 }
 
 /// Cell type enum matching Excel TYPE() function values.
@@ -159,7 +220,7 @@ pub enum CellType {
     CompoundData = 128,
 }
 
-#[derive(Encode, Decode, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, Clone, PartialEq)]
 pub enum Cell {
     EmptyCell {
         s: i32,
@@ -226,7 +287,7 @@ impl Default for Cell {
     }
 }
 
-#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub struct Comment {
     pub text: String,
     pub author_name: String,
@@ -235,7 +296,7 @@ pub struct Comment {
 }
 
 // ECMA-376-1:2016 section 18.5.1.2
-#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
 pub struct Table {
     pub name: String,
     pub display_name: String,
@@ -249,6 +310,377 @@ pub struct Table {
     pub columns: Vec<TableColumn>,
     pub style_info: TableStyleInfo,
     pub has_filters: bool,
+    pub auto_filter: Option<AutoFilter>,
+}
+
+/// Print settings of a worksheet.
+/// The print area and print titles are the defined names `_xlnm.Print_Area` and `_xlnm.Print_Titles`.
+// ECMA-376-1:2016 sections 18.3.1.62 (pageMargins), 18.3.1.63 (pageSetup) and 18.3.1.70 (printOptions)
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PageSetup {
+    pub orientation: PageOrientation,
+    /// Excel paper size code, i.e. 1 is Letter and 9 is A4
+    pub paper_size: u32,
+    /// Print scaling in percent (10 to 400). Ignored if `fit_to_page` is true
+    pub scale: u32,
+    /// Whether the sheet is scaled to fit `fit_to_width` by `fit_to_height` pages
+    pub fit_to_page: bool,
+    /// Number of pages wide to fit the sheet in, 0 means automatic
+    pub fit_to_width: u32,
+    /// Number of pages tall to fit the sheet in, 0 means automatic
+    pub fit_to_height: u32,
+    pub first_page_number: Option<u32>,
+    pub margins: PageMargins,
+    pub header_footer: HeaderFooter,
+    pub print_grid_lines: bool,
+    /// Whether the row and column headings are printed
+    pub print_headings: bool,
+    pub horizontal_centered: bool,
+    pub vertical_centered: bool,
+    /// Manual page breaks. A page break at row `r` starts a new page after row `r`
+    pub row_breaks: Vec<i32>,
+    /// Manual page breaks. A page break at column `c` starts a new page after column `c`
+    pub column_breaks: Vec<i32>,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSetup {
+            orientation: PageOrientation::Portrait,
+            paper_size: 1,
+            scale: 100,
+            fit_to_page: false,
+            fit_to_width: 1,
+            fit_to_height: 1,
+            first_page_number: None,
+            margins: Default::default(),
+            header_footer: Default::default(),
+            print_grid_lines: false,
+            print_headings: false,
+            horizontal_centered: false,
+            vertical_centered: false,
+            row_breaks: vec![],
+            column_breaks: vec![],
+        }
+    }
+}
+
+/// Rows and columns repeated on every printed page, as (first, last) pairs
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct PrintTitles {
+    pub rows: Option<(i32, i32)>,
+    pub columns: Option<(i32, i32)>,
+}
+
+/// A password hash of a protected sheet or workbook
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub enum ProtectionPassword {
+    /// The 16 bit hash of old Excel versions, as an hexadecimal string
+    Legacy(String),
+    /// A salted hash, iterated `spin_count` times. The hash and the salt are base64 encoded.
+    Hashed {
+        algorithm_name: String,
+        hash_value: String,
+        salt_value: String,
+        spin_count: u32,
+    },
+}
+
+/// The protection of a sheet and the operations that are still allowed while it is protected.
+/// Cells can only be edited if their style is not locked.
+// ECMA-376-1:2016 section 18.3.1.85
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct SheetProtection {
+    /// The password hash is not sent to the clients
+    #[serde(skip)]
+    pub password: Option<ProtectionPassword>,
+    pub select_locked_cells: bool,
+    pub select_unlocked_cells: bool,
+    pub format_cells: bool,
+    pub format_columns: bool,
+    pub format_rows: bool,
+    pub insert_columns: bool,
+    pub insert_rows: bool,
+    pub insert_hyperlinks: bool,
+    pub delete_columns: bool,
+    pub delete_rows: bool,
+    pub sort: bool,
+    pub auto_filter: bool,
+    pub pivot_tables: bool,
+    pub objects: bool,
+    pub scenarios: bool,
+}
+
+impl Default for SheetProtection {
+    fn default() -> Self {
+        SheetProtection {
+            password: None,
+            select_locked_cells: true,
+            select_unlocked_cells: true,
+            format_cells: false,
+            format_columns: false,
+            format_rows: false,
+            insert_columns: false,
+            insert_rows: false,
+            insert_hyperlinks: false,
+            delete_columns: false,
+            delete_rows: false,
+            sort: false,
+            auto_filter: false,
+            pivot_tables: false,
+            objects: false,
+            scenarios: false,
+        }
+    }
+}
+
+/// The protection of the workbook structure (sheets can't be added, deleted, renamed or hidden)
+/// and of the workbook windows.
+// ECMA-376-1:2016 section 18.2.29
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
+pub struct WorkbookProtection {
+    pub password: Option<ProtectionPassword>,
+    pub lock_structure: bool,
+    pub lock_windows: bool,
+}
+
+/// A relationship of the xlsx package that points to a part we do not understand.
+/// `target_mode` is "External" for links outside of the package.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct OpaqueRelationship {
+    pub id: String,
+    pub rel_type: String,
+    pub target: String,
+    pub target_mode: Option<String>,
+}
+
+/// A file of the xlsx package that is kept as is. `path` is the path within the zip file.
+#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct OpaquePart {
+    pub path: String,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// An XML element we do not understand, kept verbatim, i.e. `<drawing r:id="rId1"/>`
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct OpaqueElement {
+    pub name: String,
+    pub xml: String,
+}
+
+/// The parts of an xlsx file that are not part of the model, kept so that saving the workbook
+/// does not lose them.
+#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
+pub struct OpaqueParts {
+    /// The content type of the workbook part (different for macro enabled workbooks)
+    pub workbook_content_type: Option<String>,
+    /// All the files we keep (including their own relationships files)
+    pub parts: Vec<OpaquePart>,
+    /// Relationships in `_rels/.rels`
+    pub package_relationships: Vec<OpaqueRelationship>,
+    /// Relationships in `xl/_rels/workbook.xml.rels`
+    pub workbook_relationships: Vec<OpaqueRelationship>,
+    /// Elements of `xl/workbook.xml` pointing to the workbook relationships
+    pub workbook_elements: Vec<OpaqueElement>,
+}
+
+/// The relationships of a worksheet pointing to parts we do not understand and the elements
+/// of the worksheet that use them.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
+pub struct OpaqueSheetParts {
+    pub relationships: Vec<OpaqueRelationship>,
+    pub elements: Vec<OpaqueElement>,
+}
+
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum PageOrientation {
+    Portrait,
+    Landscape,
+}
+
+impl Display for PageOrientation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PageOrientation::Portrait => write!(formatter, "portrait"),
+            PageOrientation::Landscape => write!(formatter, "landscape"),
+        }
+    }
+}
+
+/// Page margins in inches
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PageMargins {
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+    pub header: f64,
+    pub footer: f64,
+}
+
+impl Default for PageMargins {
+    fn default() -> Self {
+        // Excel "Normal" margins
+        PageMargins {
+            left: 0.7,
+            right: 0.7,
+            top: 0.75,
+            bottom: 0.75,
+            header: 0.3,
+            footer: 0.3,
+        }
+    }
+}
+
+/// Headers and footers are strings with Excel formatting codes, i.e. `&CPage &P of &N`
+// ECMA-376-1:2016 section 18.3.1.46
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct HeaderFooter {
+    pub odd_header: String,
+    pub odd_footer: String,
+    /// Used only if `different_odd_even` is true
+    pub even_header: String,
+    pub even_footer: String,
+    /// Used only if `different_first` is true
+    pub first_header: String,
+    pub first_footer: String,
+    pub different_odd_even: bool,
+    pub different_first: bool,
+}
+
+/// An AutoFilter on a worksheet or on a table.
+/// Filters are non dynamic, they are applied by hiding the rows that do not match.
+// ECMA-376-1:2016 section 18.3.1.2
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AutoFilter {
+    /// The range of the filter including the header row, i.e. "A1:D20"
+    pub reference: String,
+    pub filter_columns: Vec<FilterColumn>,
+}
+
+// ECMA-376-1:2016 section 18.3.2.7
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FilterColumn {
+    /// Index of the column relative to the first column of the filter range (0 based)
+    pub col_id: u32,
+    pub filter: ColumnFilter,
+}
+
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum ColumnFilter {
+    /// Shows the rows whose formatted value is in the list (case insensitive).
+    /// If `blank` is true empty cells are shown as well.
+    Values { values: Vec<String>, blank: bool },
+    /// One or two criteria joined with AND (`and` is true) or OR
+    Custom {
+        and: bool,
+        filters: Vec<CustomFilter>,
+    },
+    /// Shows the top (or bottom) `value` items or percent
+    Top10 {
+        top: bool,
+        percent: bool,
+        value: f64,
+    },
+    /// Filters relative to today's date or to the average of the column
+    Dynamic { filter_type: DynamicFilterType },
+    /// Shows the rows with the given fill color (`cell_color` is true) or font color
+    Color { cell_color: bool, color: String },
+}
+
+// ECMA-376-1:2016 section 18.3.2.2
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CustomFilter {
+    pub operator: FilterOperator,
+    /// The value to compare with. Text values can contain the wildcards `*` and `?`
+    pub value: String,
+}
+
+// ECMA-376-1:2016 section 18.18.31 ST_FilterOperator
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum FilterOperator {
+    Equal,
+    LessThan,
+    LessThanOrEqual,
+    NotEqual,
+    GreaterThanOrEqual,
+    GreaterThan,
+}
+
+impl Display for FilterOperator {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FilterOperator::Equal => write!(formatter, "equal"),
+            FilterOperator::LessThan => write!(formatter, "lessThan"),
+            FilterOperator::LessThanOrEqual => write!(formatter, "lessThanOrEqual"),
+            FilterOperator::NotEqual => write!(formatter, "notEqual"),
+            FilterOperator::GreaterThanOrEqual => write!(formatter, "greaterThanOrEqual"),
+            FilterOperator::GreaterThan => write!(formatter, "greaterThan"),
+        }
+    }
+}
+
+// ECMA-376-1:2016 section 18.18.26 ST_DynamicFilterType
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum DynamicFilterType {
+    AboveAverage,
+    BelowAverage,
+    Tomorrow,
+    Today,
+    Yesterday,
+    NextWeek,
+    ThisWeek,
+    LastWeek,
+    NextMonth,
+    ThisMonth,
+    LastMonth,
+    NextQuarter,
+    ThisQuarter,
+    LastQuarter,
+    NextYear,
+    ThisYear,
+    LastYear,
+    YearToDate,
+    /// Dates in the given quarter (1 to 4) of any year
+    Quarter(u32),
+    /// Dates in the given month (1 to 12) of any year
+    Month(u32),
+}
+
+impl Display for DynamicFilterType {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DynamicFilterType::AboveAverage => write!(formatter, "aboveAverage"),
+            DynamicFilterType::BelowAverage => write!(formatter, "belowAverage"),
+            DynamicFilterType::Tomorrow => write!(formatter, "tomorrow"),
+            DynamicFilterType::Today => write!(formatter, "today"),
+            DynamicFilterType::Yesterday => write!(formatter, "yesterday"),
+            DynamicFilterType::NextWeek => write!(formatter, "nextWeek"),
+            DynamicFilterType::ThisWeek => write!(formatter, "thisWeek"),
+            DynamicFilterType::LastWeek => write!(formatter, "lastWeek"),
+            DynamicFilterType::NextMonth => write!(formatter, "nextMonth"),
+            DynamicFilterType::ThisMonth => write!(formatter, "thisMonth"),
+            DynamicFilterType::LastMonth => write!(formatter, "lastMonth"),
+            DynamicFilterType::NextQuarter => write!(formatter, "nextQuarter"),
+            DynamicFilterType::ThisQuarter => write!(formatter, "thisQuarter"),
+            DynamicFilterType::LastQuarter => write!(formatter, "lastQuarter"),
+            DynamicFilterType::NextYear => write!(formatter, "nextYear"),
+            DynamicFilterType::ThisYear => write!(formatter, "thisYear"),
+            DynamicFilterType::LastYear => write!(formatter, "lastYear"),
+            DynamicFilterType::YearToDate => write!(formatter, "yearToDate"),
+            DynamicFilterType::Quarter(q) => write!(formatter, "Q{q}"),
+            DynamicFilterType::Month(m) => write!(formatter, "M{m}"),
+        }
+    }
+}
+
+/// A sort criteria used when sorting a range
+#[derive(Encode, Decode, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SortKey {
+    /// The column to sort by (absolute column index)
+    pub column: i32,
+    pub ascending: bool,
 }
 
 // totals_row_label vs totals_row_function might be mutually exclusive. Use an enum?
@@ -306,9 +738,8 @@ impl Default for Styles {
             fills: vec![
                 Default::default(),
                 Fill {
-                    pattern_type: "gray125".to_string(),
-                    fg_color: None,
-                    bg_color: None,
+                    pattern_type: PatternType::Gray125,
+                    ..Default::default()
                 },
             ],
             borders: vec![Default::default()],
@@ -328,6 +759,11 @@ pub struct Style {
     pub font: Font,
     pub border: Border,
     pub quote_prefix: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protection: Option<CellProtection>,
+    /// The named cell style the style is linked to. `None` is the Normal style.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub named_style: Option<String>,
 }
 
 impl Default for Style {
@@ -339,6 +775,28 @@ impl Default for Style {
             font: Font::default(),
             border: Border::default(),
             quote_prefix: false,
+            protection: None,
+            named_style: None,
+        }
+    }
+}
+
+/// Protection flags of a cell. They only take effect when the sheet is protected.
+/// A style without protection flags is locked and not hidden.
+// ECMA-376-1:2016 section 18.8.33
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct CellProtection {
+    /// The cell can't be edited
+    pub locked: bool,
+    /// The formula of the cell is not shown
+    pub hidden: bool,
+}
+
+impl Default for CellProtection {
+    fn default() -> Self {
+        CellProtection {
+            locked: true,
+            hidden: false,
         }
     }
 }
@@ -386,9 +844,8 @@ pub struct Font {
     #[serde(default = "default_as_false")]
     #[serde(skip_serializing_if = "is_false")]
     pub strike: bool,
-    #[serde(default = "default_as_false")]
-    #[serde(skip_serializing_if = "is_false")]
-    pub u: bool, // seems that Excel supports a bit more - double underline / account underline etc.
+    #[serde(default, skip_serializing_if = "FontUnderline::is_default")]
+    pub underline: FontUnderline,
     #[serde(default = "default_as_false")]
     #[serde(skip_serializing_if = "is_false")]
     pub b: bool,
@@ -396,7 +853,7 @@ pub struct Font {
     #[serde(skip_serializing_if = "is_false")]
     pub i: bool,
     pub sz: i32,
-    pub color: Option<String>,
+    pub color: Option<Color>,
     pub name: String,
     // This is the font family fallback
     // 1 -> serif
@@ -405,40 +862,317 @@ pub struct Font {
     // ...
     pub family: i32,
     pub scheme: FontScheme,
+    #[serde(default, skip_serializing_if = "FontVerticalAlign::is_default")]
+    pub vert_align: FontVerticalAlign,
+    #[serde(default = "default_as_false")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub outline: bool,
+    #[serde(default = "default_as_false")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub shadow: bool,
+    #[serde(default = "default_as_false")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub condense: bool,
 }
 
 impl Default for Font {
     fn default() -> Self {
         Font {
             strike: false,
-            u: false,
+            underline: FontUnderline::None,
             b: false,
             i: false,
             sz: 13,
-            color: Some("#000000".to_string()),
+            color: Some(Color::Rgb("#000000".to_string())),
             name: "Calibri".to_string(),
             family: 2,
             scheme: FontScheme::Minor,
+            vert_align: FontVerticalAlign::Baseline,
+            outline: false,
+            shadow: false,
+            condense: false,
+        }
+    }
+}
+
+impl Font {
+    /// Returns true if the font has any kind of underline
+    pub fn is_underlined(&self) -> bool {
+        self.underline != FontUnderline::None
+    }
+}
+
+/// The kind of underline of a font (18.4.13 u)
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FontUnderline {
+    #[default]
+    None,
+    Single,
+    Double,
+    SingleAccounting,
+    DoubleAccounting,
+}
+
+impl FontUnderline {
+    fn is_default(&self) -> bool {
+        self == &FontUnderline::default()
+    }
+}
+
+impl Display for FontUnderline {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FontUnderline::None => write!(formatter, "none"),
+            FontUnderline::Single => write!(formatter, "single"),
+            FontUnderline::Double => write!(formatter, "double"),
+            FontUnderline::SingleAccounting => write!(formatter, "singleAccounting"),
+            FontUnderline::DoubleAccounting => write!(formatter, "doubleAccounting"),
         }
     }
 }
 
-// TODO: Maybe use an enum for the pattern_type values here?
+/// The vertical position of the text in a font (18.4.14 vertAlign)
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FontVerticalAlign {
+    #[default]
+    Baseline,
+    Superscript,
+    Subscript,
+}
+
+impl FontVerticalAlign {
+    fn is_default(&self) -> bool {
+        self == &FontVerticalAlign::default()
+    }
+}
+
+impl Display for FontVerticalAlign {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FontVerticalAlign::Baseline => write!(formatter, "baseline"),
+            FontVerticalAlign::Superscript => write!(formatter, "superscript"),
+            FontVerticalAlign::Subscript => write!(formatter, "subscript"),
+        }
+    }
+}
+
+/// The font properties of a rich text run (18.4.7 rPr).
+/// Properties that are `None` are inherited from the font of the cell.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
+pub struct RunFont {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub i: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underline: Option<FontUnderline>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strike: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sz: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vert_align: Option<FontVerticalAlign>,
+}
+
+/// A run of text with its own formatting inside a rich text string (18.4.4 r)
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct TextRun {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<RunFont>,
+}
+
+/// A colour as defined in the workbook (18.8.3 color).
+/// Colours linked to the theme or to the legacy palette are resolved to RGB when they are read,
+/// see [Color::to_rgb].
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Color {
+    /// An RGB value like "#FF0000"
+    Rgb(String),
+    /// An index in the legacy palette
+    Indexed { indexed: i32 },
+    /// A slot of the theme colour scheme. The tint goes from -1.0 (darkest) to 1.0 (lightest)
+    Theme {
+        theme: i32,
+        #[serde(default, deserialize_with = "deserialize_tint")]
+        tint: f64,
+    },
+}
+
+// Tints are clamped when they are parsed (see [Color::theme]), so they are never NaN
+impl Eq for Color {}
+
+fn deserialize_tint<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(clamp_tint(f64::deserialize(deserializer)?))
+}
+
+/// The theme of the workbook (20.1.6.9 theme): the colour scheme and the major and minor fonts
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct Theme {
+    pub name: String,
+    /// The 12 colours of the scheme in the order of the `theme` attribute of colours:
+    /// lt1, dk1, lt2, dk2, accent1 to accent6, hlink and folHlink
+    pub colors: Vec<String>,
+    pub major_font: String,
+    pub minor_font: String,
+    /// The theme part of the original file. When saving we only replace its colours and fonts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xml: Option<String>,
+}
+
+/// The pattern of a fill (18.18.55 ST_PatternType)
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PatternType {
+    #[default]
+    None,
+    Solid,
+    MediumGray,
+    DarkGray,
+    LightGray,
+    DarkHorizontal,
+    DarkVertical,
+    DarkDown,
+    DarkUp,
+    DarkGrid,
+    DarkTrellis,
+    LightHorizontal,
+    LightVertical,
+    LightDown,
+    LightUp,
+    LightGrid,
+    LightTrellis,
+    Gray125,
+    Gray0625,
+}
+
+impl Display for PatternType {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatternType::None => write!(formatter, "none"),
+            PatternType::Solid => write!(formatter, "solid"),
+            PatternType::MediumGray => write!(formatter, "mediumGray"),
+            PatternType::DarkGray => write!(formatter, "darkGray"),
+            PatternType::LightGray => write!(formatter, "lightGray"),
+            PatternType::DarkHorizontal => write!(formatter, "darkHorizontal"),
+            PatternType::DarkVertical => write!(formatter, "darkVertical"),
+            PatternType::DarkDown => write!(formatter, "darkDown"),
+            PatternType::DarkUp => write!(formatter, "darkUp"),
+            PatternType::DarkGrid => write!(formatter, "darkGrid"),
+            PatternType::DarkTrellis => write!(formatter, "darkTrellis"),
+            PatternType::LightHorizontal => write!(formatter, "lightHorizontal"),
+            PatternType::LightVertical => write!(formatter, "lightVertical"),
+            PatternType::LightDown => write!(formatter, "lightDown"),
+            PatternType::LightUp => write!(formatter, "lightUp"),
+            PatternType::LightGrid => write!(formatter, "lightGrid"),
+            PatternType::LightTrellis => write!(formatter, "lightTrellis"),
+            PatternType::Gray125 => write!(formatter, "gray125"),
+            PatternType::Gray0625 => write!(formatter, "gray0625"),
+        }
+    }
+}
+
+impl std::str::FromStr for PatternType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(PatternType::None),
+            "solid" => Ok(PatternType::Solid),
+            "mediumGray" => Ok(PatternType::MediumGray),
+            "darkGray" => Ok(PatternType::DarkGray),
+            "lightGray" => Ok(PatternType::LightGray),
+            "darkHorizontal" => Ok(PatternType::DarkHorizontal),
+            "darkVertical" => Ok(PatternType::DarkVertical),
+            "darkDown" => Ok(PatternType::DarkDown),
+            "darkUp" => Ok(PatternType::DarkUp),
+            "darkGrid" => Ok(PatternType::DarkGrid),
+            "darkTrellis" => Ok(PatternType::DarkTrellis),
+            "lightHorizontal" => Ok(PatternType::LightHorizontal),
+            "lightVertical" => Ok(PatternType::LightVertical),
+            "lightDown" => Ok(PatternType::LightDown),
+            "lightUp" => Ok(PatternType::LightUp),
+            "lightGrid" => Ok(PatternType::LightGrid),
+            "lightTrellis" => Ok(PatternType::LightTrellis),
+            "gray125" => Ok(PatternType::Gray125),
+            "gray0625" => Ok(PatternType::Gray0625),
+            _ => Err(format!("Invalid pattern type: '{value}'.")),
+        }
+    }
+}
+
+/// 18.18.34 ST_GradientType
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GradientType {
+    /// The colours change along a line at `degree` degrees
+    #[default]
+    Linear,
+    /// The colours change from the rectangle given by `left`, `right`, `top` and `bottom`
+    /// to the edges of the cell
+    Path,
+}
+
+/// A colour of a gradient at a position between 0.0 and 1.0 (18.8.38 stop)
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
+pub struct GradientStop {
+    pub position: f64,
+    pub color: Option<Color>,
+}
+
+impl Eq for GradientStop {}
+
+/// 18.8.24 gradientFill
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone, Default)]
+pub struct GradientFill {
+    #[serde(default)]
+    pub gradient_type: GradientType,
+    /// Angle of a linear gradient, clockwise from left to right
+    #[serde(default)]
+    pub degree: f64,
+    /// The inner rectangle of a path gradient, as fractions of the cell size
+    #[serde(default)]
+    pub left: f64,
+    #[serde(default)]
+    pub right: f64,
+    #[serde(default)]
+    pub top: f64,
+    #[serde(default)]
+    pub bottom: f64,
+    pub stops: Vec<GradientStop>,
+}
+
+impl Eq for GradientFill {}
+
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub struct Fill {
-    pub pattern_type: String,
+    pub pattern_type: PatternType,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub fg_color: Option<String>,
+    pub fg_color: Option<Color>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub bg_color: Option<String>,
+    pub bg_color: Option<Color>,
+    /// If present the fill is a gradient and the pattern is ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gradient: Option<GradientFill>,
 }
 
 impl Default for Fill {
     fn default() -> Self {
         Fill {
-            pattern_type: "none".to_string(),
+            pattern_type: PatternType::None,
             fg_color: Default::default(),
             bg_color: Default::default(),
+            gradient: None,
         }
     }
 }
@@ -524,6 +1258,47 @@ pub struct Alignment {
     #[serde(default = "default_as_false")]
     #[serde(skip_serializing_if = "is_false")]
     pub wrap_text: bool,
+    /// Degrees from 0 to 180: 1 to 90 rotate the text upwards, 91 to 180 downwards (91 is -1 degree).
+    /// The value 255 is vertical text, with the letters stacked from top to bottom.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub text_rotation: i32,
+    /// Indentation level of the text, each one is three spaces wide
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub indent: i32,
+    #[serde(default = "default_as_false")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub shrink_to_fit: bool,
+    #[serde(default = "default_as_false")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub justify_last_line: bool,
+    #[serde(default, skip_serializing_if = "ReadingOrder::is_default")]
+    pub reading_order: ReadingOrder,
+}
+
+/// The reading order of the text of a cell (0 context, 1 left to right, 2 right to left)
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingOrder {
+    #[default]
+    Context,
+    LeftToRight,
+    RightToLeft,
+}
+
+impl ReadingOrder {
+    fn is_default(&self) -> bool {
+        self == &ReadingOrder::default()
+    }
+}
+
+impl Display for ReadingOrder {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReadingOrder::Context => write!(formatter, "context"),
+            ReadingOrder::LeftToRight => write!(formatter, "lefttoright"),
+            ReadingOrder::RightToLeft => write!(formatter, "righttoleft"),
+        }
+    }
 }
 
 #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
@@ -538,6 +1313,8 @@ pub struct CellStyleXfs {
     pub apply_protection: bool,
     pub apply_font: bool,
     pub apply_fill: bool,
+    pub alignment: Option<Alignment>,
+    pub protection: Option<CellProtection>,
 }
 
 impl Default for CellStyleXfs {
@@ -553,6 +1330,8 @@ impl Default for CellStyleXfs {
             apply_protection: true,
             apply_font: true,
             apply_fill: true,
+            alignment: None,
+            protection: None,
         }
     }
 }
@@ -572,8 +1351,11 @@ pub struct CellXfs {
     pub apply_fill: bool,
     pub quote_prefix: bool,
     pub alignment: Option<Alignment>,
+    pub protection: Option<CellProtection>,
 }
 
+/// A named cell style (18.8.7 cellStyle). `xf_id` is the index in `cell_style_xfs`.
+/// Custom styles have a negative `builtin_id`.
 #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub struct CellStyles {
     pub name: String,
@@ -591,6 +1373,13 @@ impl Default for CellStyles {
     }
 }
 
+impl CellStyles {
+    /// The `builtin_id` of a built-in style, `None` for custom styles
+    pub fn get_builtin_id(&self) -> Option<i32> {
+        (self.builtin_id >= 0).then_some(self.builtin_id)
+    }
+}
+
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, PartialOrd, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum BorderStyle {
@@ -624,7 +1413,7 @@ impl Display for BorderStyle {
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub struct BorderItem {
     pub style: BorderStyle,
-    pub color: Option<String>,
+    pub color: Option<Color>,
 }
 
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
@@ -647,6 +1436,15 @@ pub struct Border {
     pub diagonal: Option<BorderItem>,
 }
 
+/// A named cell style as shown in the UI ("Normal", "Heading 1", "Currency", custom ones,...)
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct NamedStyle {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builtin_id: Option<i32>,
+    pub style: Style,
+}
+
 /// Information need to show a sheet tab in the UI
 /// The color is serialized only if it is not Color::None
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
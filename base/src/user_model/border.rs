@@ -349,6 +349,7 @@ impl<'a> UserModel<'a> {
         border_area: &BorderArea,
     ) -> Result<(), String> {
         let sheet = range.sheet;
+        self.check_sheet_allows(sheet, |p| p.format_cells)?;
         let first_row = range.row;
         let first_column = range.column;
         let last_row = first_row + range.height - 1;
@@ -388,7 +389,11 @@ impl<'a> UserModel<'a> {
             let row = first_row - 1;
             for column in first_column..=last_column {
                 let old_value = self.model.get_style_for_cell(sheet, row, column)?;
-                if is_max_border(Some(&border_area.item), old_value.border.bottom.as_ref()) {
+                if is_max_border(
+                    Some(&border_area.item),
+                    old_value.border.bottom.as_ref(),
+                    self.model.get_theme(),
+                ) {
                     let mut style = old_value.clone();
                     if border_area.r#type == BorderType::None {
                         style.border.bottom = None;
@@ -420,7 +425,11 @@ impl<'a> UserModel<'a> {
             for row in first_row..=last_row {
                 let old_value = self.model.get_style_for_cell(sheet, row, column)?;
                 // If the border in the adjacent cell is "heavier" we change it
-                if is_max_border(Some(&border_area.item), old_value.border.left.as_ref()) {
+                if is_max_border(
+                    Some(&border_area.item),
+                    old_value.border.left.as_ref(),
+                    self.model.get_theme(),
+                ) {
                     let mut style = old_value.clone();
                     if border_area.r#type == BorderType::None {
                         style.border.left = None;
@@ -451,7 +460,11 @@ impl<'a> UserModel<'a> {
             let row = last_row + 1;
             for column in first_column..=last_column {
                 let old_value = self.model.get_style_for_cell(sheet, row, column)?;
-                if is_max_border(Some(&border_area.item), old_value.border.top.as_ref()) {
+                if is_max_border(
+                    Some(&border_area.item),
+                    old_value.border.top.as_ref(),
+                    self.model.get_theme(),
+                ) {
                     let mut style = old_value.clone();
                     if border_area.r#type == BorderType::None {
                         style.border.top = None;
@@ -482,7 +495,11 @@ impl<'a> UserModel<'a> {
             let column = first_column - 1;
             for row in first_row..=last_row {
                 let old_value = self.model.get_style_for_cell(sheet, row, column)?;
-                if is_max_border(Some(&border_area.item), old_value.border.right.as_ref()) {
+                if is_max_border(
+                    Some(&border_area.item),
+                    old_value.border.right.as_ref(),
+                    self.model.get_theme(),
+                ) {
                     let mut style = old_value.clone();
                     if border_area.r#type == BorderType::None {
                         style.border.right = None;
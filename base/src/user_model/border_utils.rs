@@ -1,4 +1,4 @@
-use crate::types::BorderItem;
+use crate::types::{BorderItem, Theme};
 
 fn parse_color(s: &str) -> Option<(u8, u8, u8)> {
     let s = s.trim_start_matches('#');
@@ -48,7 +48,7 @@ fn is_max_color(a: &str, b: &str) -> bool {
 }
 
 /// Is border b "heavier" than a?
-pub(crate) fn is_max_border(a: Option<&BorderItem>, b: Option<&BorderItem>) -> bool {
+pub(crate) fn is_max_border(a: Option<&BorderItem>, b: Option<&BorderItem>, theme: &Theme) -> bool {
     match (a, b) {
         (_, None) => false,
         (None, Some(_)) => true,
@@ -61,7 +61,9 @@ pub(crate) fn is_max_border(a: Option<&BorderItem>, b: Option<&BorderItem>) -> b
             match (&item_a.color, &item_b.color) {
                 (_, None) => false,
                 (None, Some(_)) => true,
-                (Some(color_a), Some(color_b)) => is_max_color(color_a, color_b),
+                (Some(color_a), Some(color_b)) => {
+                    is_max_color(&color_a.to_rgb(theme), &color_b.to_rgb(theme))
+                }
             }
         }
     }
@@ -70,19 +72,20 @@ pub(crate) fn is_max_border(a: Option<&BorderItem>, b: Option<&BorderItem>) -> b
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::BorderStyle;
+    use crate::types::{BorderStyle, Color};
 
     #[test]
     fn compare_borders() {
         let b = BorderItem {
             style: BorderStyle::Thin,
-            color: Some("#FFF".to_string()),
+            color: Some(Color::Rgb("#FFF".to_string())),
         };
+        let theme = Theme::default();
         // Some border *always* beats no border
-        assert!(is_max_border(None, Some(&b)));
+        assert!(is_max_border(None, Some(&b), &theme));
 
         // No border is beaten by some border
-        assert!(!is_max_border(Some(&b), None));
+        assert!(!is_max_border(Some(&b), None, &theme));
     }
 
     #[test]
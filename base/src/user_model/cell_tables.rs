@@ -0,0 +1,275 @@
+// Cells refer to the shared strings of the workbook, the formulas of their sheet and the styles of
+// the workbook by index. Those indices are different in every client of a collaborative session
+// (they depend on the order things were added), so the cells of the diffs that go to other
+// clients refer to tables that go along with them instead.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::Model,
+    types::{Cell, Style, TextRun, Worksheet},
+};
+
+use super::history::{Diff, QueueDiffs};
+
+/// A shared string, with its runs if it is rich text
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct SharedText {
+    text: String,
+    runs: Option<Vec<TextRun>>,
+}
+
+/// The strings, formulas (R1C1) and styles the cells of a list of diffs refer to
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+pub(super) struct CellTables {
+    strings: Vec<SharedText>,
+    formulas: Vec<String>,
+    styles: Vec<Style>,
+}
+
+// An index in a cell of a diff
+enum CellIndex<'a> {
+    String(&'a mut i32),
+    // The formula of a cell in a sheet
+    Formula(u32, &'a mut i32),
+    Style(&'a mut i32),
+}
+
+type Visitor<'a> = dyn FnMut(CellIndex) -> Result<(), String> + 'a;
+
+impl CellTables {
+    /// Moves the cells of the diffs from the indices of the model to the indices of the tables
+    pub(super) fn export(&mut self, model: &Model, list: &mut [QueueDiffs]) -> Result<(), String> {
+        let workbook = &model.workbook;
+        for diff in list
+            .iter_mut()
+            .flat_map(|queue_diffs| queue_diffs.list.iter_mut())
+        {
+            visit_diff(diff, &mut |index| {
+                match index {
+                    CellIndex::String(si) => {
+                        let text = workbook
+                            .shared_strings
+                            .get(*si as usize)
+                            .ok_or_else(|| format!("Invalid shared string {si}"))?;
+                        let runs = workbook.rich_text.get(&(*si as u32)).cloned();
+                        *si = self.add_string(SharedText {
+                            text: text.clone(),
+                            runs,
+                        });
+                    }
+                    CellIndex::Formula(sheet, f) => {
+                        let formula = workbook
+                            .worksheet(sheet)?
+                            .shared_formulas
+                            .get(*f as usize)
+                            .ok_or_else(|| format!("Invalid formula {f}"))?;
+                        *f = self.add_formula(formula);
+                    }
+                    CellIndex::Style(s) => *s = self.add_style(workbook.styles.get_style(*s)?),
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Moves the cells of the diff from the indices of the tables to the indices of the model,
+    /// adding what the model does not have
+    pub(super) fn import(&self, model: &mut Model, diff: &mut Diff) -> Result<(), String> {
+        visit_diff(diff, &mut |index| {
+            match index {
+                CellIndex::String(si) => {
+                    let string = get_entry(&self.strings, *si)?;
+                    *si = model
+                        .get_shared_string_index_or_create(&string.text, string.runs.as_deref());
+                }
+                CellIndex::Formula(sheet, f) => {
+                    let formula = get_entry(&self.formulas, *f)?;
+                    *f = model.get_shared_formula_index_or_create(sheet, formula)?;
+                }
+                CellIndex::Style(s) => {
+                    let style = get_entry(&self.styles, *s)?;
+                    *s = model.workbook.styles.get_style_index_or_create(style);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Moves the cells of the diffs from the indices of `self` to the indices of `tables`
+    pub(super) fn copy_to(
+        &self,
+        list: &mut [QueueDiffs],
+        tables: &mut CellTables,
+    ) -> Result<(), String> {
+        for diff in list
+            .iter_mut()
+            .flat_map(|queue_diffs| queue_diffs.list.iter_mut())
+        {
+            visit_diff(diff, &mut |index| {
+                match index {
+                    CellIndex::String(si) => {
+                        *si = tables.add_string(get_entry(&self.strings, *si)?.clone())
+                    }
+                    CellIndex::Formula(_, f) => {
+                        *f = tables.add_formula(get_entry(&self.formulas, *f)?)
+                    }
+                    CellIndex::Style(s) => {
+                        *s = tables.add_style(get_entry(&self.styles, *s)?.clone())
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    fn add_string(&mut self, string: SharedText) -> i32 {
+        match self.strings.iter().position(|other| other == &string) {
+            Some(index) => index as i32,
+            None => {
+                self.strings.push(string);
+                self.strings.len() as i32 - 1
+            }
+        }
+    }
+
+    fn add_formula(&mut self, formula: &str) -> i32 {
+        match self.formulas.iter().position(|other| other == formula) {
+            Some(index) => index as i32,
+            None => {
+                self.formulas.push(formula.to_string());
+                self.formulas.len() as i32 - 1
+            }
+        }
+    }
+
+    fn add_style(&mut self, style: Style) -> i32 {
+        match self.styles.iter().position(|other| other == &style) {
+            Some(index) => index as i32,
+            None => {
+                self.styles.push(style);
+                self.styles.len() as i32 - 1
+            }
+        }
+    }
+}
+
+fn get_entry<T>(table: &[T], index: i32) -> Result<&T, String> {
+    table
+        .get(index as usize)
+        .ok_or_else(|| format!("Invalid cell table index {index}"))
+}
+
+// Visits the indices in the cells of a diff. `sheet` is the sheet the formulas of the cell belong
+// to, `None` if they are in the worksheet that holds the cell.
+fn visit_cell(sheet: Option<u32>, cell: &mut Cell, visit: &mut Visitor) -> Result<(), String> {
+    match cell {
+        Cell::EmptyCell { s }
+        | Cell::BooleanCell { s, .. }
+        | Cell::NumberCell { s, .. }
+        | Cell::ErrorCell { s, .. } => visit(CellIndex::Style(s)),
+        Cell::SharedString { si, s } => {
+            visit(CellIndex::String(si))?;
+            visit(CellIndex::Style(s))
+        }
+        Cell::CellFormula { f, s }
+        | Cell::CellFormulaBoolean { f, s, .. }
+        | Cell::CellFormulaNumber { f, s, .. }
+        | Cell::CellFormulaString { f, s, .. }
+        | Cell::CellFormulaError { f, s, .. } => {
+            if let Some(sheet) = sheet {
+                visit(CellIndex::Formula(sheet, f))?;
+            }
+            visit(CellIndex::Style(s))
+        }
+    }
+}
+
+// The formulas of a deleted sheet are kept in the sheet itself
+fn visit_worksheet(worksheet: &mut Worksheet, visit: &mut Visitor) -> Result<(), String> {
+    for cell in worksheet
+        .sheet_data
+        .values_mut()
+        .flat_map(|row| row.values_mut())
+    {
+        visit_cell(None, cell, visit)?;
+    }
+    for row in worksheet.rows.iter_mut() {
+        visit(CellIndex::Style(&mut row.s))?;
+    }
+    for col in worksheet.cols.iter_mut() {
+        if let Some(style) = col.style.as_mut() {
+            visit(CellIndex::Style(style))?;
+        }
+    }
+    Ok(())
+}
+
+fn visit_diff(diff: &mut Diff, visit: &mut Visitor) -> Result<(), String> {
+    match diff {
+        Diff::SetCellValue {
+            sheet, old_value, ..
+        }
+        | Diff::SetCellRichText {
+            sheet, old_value, ..
+        }
+        | Diff::CellClearContents {
+            sheet, old_value, ..
+        }
+        | Diff::CellClearAll {
+            sheet, old_value, ..
+        } => {
+            if let Some(cell) = old_value.as_mut() {
+                visit_cell(Some(*sheet), cell, visit)?;
+            }
+        }
+        Diff::DeleteRows {
+            sheet, old_data, ..
+        } => {
+            for row_data in old_data.iter_mut() {
+                if let Some(row) = row_data.row.as_mut() {
+                    visit(CellIndex::Style(&mut row.s))?;
+                }
+                for cell in row_data.data.values_mut() {
+                    visit_cell(Some(*sheet), cell, visit)?;
+                }
+            }
+        }
+        Diff::DeleteColumns {
+            sheet, old_data, ..
+        } => {
+            for column_data in old_data.iter_mut() {
+                if let Some(style) = column_data.column.as_mut().and_then(|c| c.style.as_mut()) {
+                    visit(CellIndex::Style(style))?;
+                }
+                for cell in column_data.data.values_mut() {
+                    visit_cell(Some(*sheet), cell, visit)?;
+                }
+            }
+        }
+        Diff::DeleteSheet { old_data, .. } => visit_worksheet(old_data, visit)?,
+        Diff::SortRange {
+            sheet, old_data, ..
+        } => {
+            for cell in old_data.iter_mut().flatten().flatten() {
+                visit_cell(Some(*sheet), cell, visit)?;
+            }
+        }
+        Diff::MoveCells { old_data, .. } | Diff::SetDateSystem { old_data, .. } => {
+            for cell_data in old_data.iter_mut() {
+                if let Some(cell) = cell_data.cell.as_mut() {
+                    visit_cell(Some(cell_data.sheet), cell, visit)?;
+                }
+            }
+        }
+        Diff::DeleteNamedStyle { linked_styles, .. } => {
+            for style in linked_styles.iter_mut() {
+                visit(CellIndex::Style(style))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,639 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cell::CellValue,
+    constants::LAST_COLUMN,
+    expressions::{
+        parser::{stringify::to_localized_string, Node},
+        token::{get_error_by_english_name, Error, OpProduct, OpSum},
+        types::{Area, CellReferenceRC},
+        utils::{is_valid_column_number, is_valid_row, number_to_column, quote_name},
+    },
+    language::get_default_language,
+    locale::get_default_locale,
+    number_format::to_excel_precision_str,
+    types::{Cell, CellType, Style},
+};
+
+use super::{
+    history::{Diff, DiffList},
+    UserModel,
+};
+
+const OUTSIDE_OF_THE_SHEET_ERROR: &str = "Cannot paste outside of the sheet";
+
+/// What is pasted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasteMode {
+    /// Contents and formats
+    #[default]
+    All,
+    /// The values of the cells, formulas are replaced by their results
+    Values,
+    /// The contents of the cells, keeping the formats of the target
+    Formulas,
+    /// Only the formats
+    Formats,
+    /// Only the widths of the columns
+    ColumnWidths,
+}
+
+/// How the pasted values are combined with the values already in the target
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasteOperation {
+    /// The pasted values replace the existing ones
+    #[default]
+    None,
+    /// target + pasted
+    Add,
+    /// target - pasted
+    Subtract,
+    /// target * pasted
+    Multiply,
+    /// target / pasted
+    Divide,
+}
+
+/// Options of a paste special
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct PasteOptions {
+    /// What is pasted
+    pub mode: PasteMode,
+    /// How the pasted values are combined with the existing ones
+    pub operation: PasteOperation,
+    /// Empty cells in the clipboard leave the target cells untouched
+    pub skip_blanks: bool,
+    /// Rows are pasted as columns and columns as rows
+    pub transpose: bool,
+    /// Each target cell gets a reference to the copied cell instead of its content
+    pub link: bool,
+}
+
+/// The value of a copied cell
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CopiedValue {
+    /// A number
+    Number(f64),
+    /// A boolean
+    Boolean(bool),
+    /// A text
+    Text(String),
+    /// An error, by its English name (i.e. `#DIV/0!`)
+    Error(String),
+}
+
+/// A copied cell
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CopiedCell {
+    /// The formula of the cell in English, without the leading `=`.
+    /// Relative references are relative to the copied cell
+    pub formula: Option<String>,
+    /// The value of the cell, for formulas the result. `None` if the cell is empty
+    pub value: Option<CopiedValue>,
+    /// The style of the cell
+    pub style: Style,
+}
+
+/// A copied range of cells that can be pasted in any model.
+///
+/// It does not depend on the styles, the language or the locale of the workbook it comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClipboardPayload {
+    /// The name of the sheet the cells were copied from
+    pub sheet_name: String,
+    /// The first row of the copied range
+    pub row: i32,
+    /// The first column of the copied range
+    pub column: i32,
+    /// The copied cells, row by row
+    pub cells: Vec<Vec<CopiedCell>>,
+    /// The widths of the copied columns
+    pub column_widths: Vec<f64>,
+}
+
+// The pasted content of a cell before it is combined with the target
+enum PastedContent {
+    Formula(Node),
+    Value(CopiedValue),
+    // A text from a cell with quote prefix
+    QuotedText(String),
+    Empty,
+}
+
+impl UserModel<'_> {
+    /// Returns a copy of the selected area that can be pasted in this or any other model
+    ///
+    /// See also:
+    /// * [UserModel::paste_payload]
+    pub fn copy_to_payload(&self) -> Result<ClipboardPayload, String> {
+        let view = self.get_selected_view();
+        let sheet = view.sheet;
+        let [row_start, column_start, row_end, column_end] = view.range;
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        let dimension = worksheet.dimension();
+        let row_end = row_end.min(dimension.max_row);
+        let column_end = column_end.min(dimension.max_column);
+        let sheet_name = worksheet.get_name();
+        let language = get_default_language();
+
+        let mut cells = Vec::new();
+        for row in row_start..=row_end {
+            let mut cells_row = Vec::new();
+            for column in column_start..=column_end {
+                let cell = self.model.workbook.worksheet(sheet)?.cell(row, column);
+                let formula = match cell.and_then(Cell::get_formula) {
+                    Some(index) => {
+                        let node = &self.model.parsed_formulas[sheet as usize][index as usize];
+                        let context = CellReferenceRC {
+                            sheet: sheet_name.clone(),
+                            row,
+                            column,
+                        };
+                        Some(to_localized_string(
+                            node,
+                            &context,
+                            get_default_locale(),
+                            language,
+                        ))
+                    }
+                    None => None,
+                };
+                let value = match cell {
+                    None | Some(Cell::EmptyCell { .. }) => None,
+                    Some(cell) => {
+                        let shared_strings = &self.model.workbook.shared_strings;
+                        match (cell.get_type(), cell.value(shared_strings, language)) {
+                            (_, CellValue::None) => None,
+                            (_, CellValue::Number(v)) => Some(CopiedValue::Number(v)),
+                            (_, CellValue::Boolean(v)) => Some(CopiedValue::Boolean(v)),
+                            (CellType::ErrorValue, CellValue::String(v)) => {
+                                Some(CopiedValue::Error(v))
+                            }
+                            (_, CellValue::String(v)) => Some(CopiedValue::Text(v)),
+                        }
+                    }
+                };
+                let style = self.model.get_style_for_cell(sheet, row, column)?;
+                cells_row.push(CopiedCell {
+                    formula,
+                    value,
+                    style,
+                });
+            }
+            cells.push(cells_row);
+        }
+        let mut column_widths = Vec::new();
+        for column in column_start..=column_end {
+            column_widths.push(self.model.get_column_width(sheet, column)?);
+        }
+        Ok(ClipboardPayload {
+            sheet_name,
+            row: row_start,
+            column: column_start,
+            cells,
+            column_widths,
+        })
+    }
+
+    /// Pastes the payload at the selected cell with the given options. It can come from a
+    /// different model: styles are added to this workbook and references to sheets are resolved
+    /// by name. References to sheets that do not exist in this workbook become `#REF!`.
+    /// All the changes are undone at once.
+    pub fn paste_payload(
+        &mut self,
+        payload: &ClipboardPayload,
+        options: &PasteOptions,
+    ) -> Result<(), String> {
+        let view = self.get_selected_view();
+        let sheet = view.sheet;
+        let [target_row, target_column, _, _] = view.range;
+        let height = payload.cells.len() as i32;
+        let width = payload.column_widths.len() as i32;
+        if height == 0 || width == 0 {
+            return Ok(());
+        }
+        let mut diff_list = Vec::new();
+
+        if options.mode == PasteMode::ColumnWidths && !options.link {
+            if !is_valid_column_number(target_column + width - 1) {
+                return Err(OUTSIDE_OF_THE_SHEET_ERROR.to_string());
+            }
+            self.check_sheet_allows(sheet, |p| p.format_columns)?;
+            if let Err(message) =
+                self.paste_column_widths(payload, sheet, target_column, &mut diff_list)
+            {
+                // Leave the sheet as it was before the paste
+                self.apply_undo_diff_list(&diff_list)?;
+                return Err(message);
+            }
+            self.push_diff_list(diff_list);
+            return Ok(());
+        }
+
+        let (target_height, target_width) = if options.transpose {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        if !is_valid_row(target_row + target_height - 1)
+            || !is_valid_column_number(target_column + target_width - 1)
+        {
+            return Err(OUTSIDE_OF_THE_SHEET_ERROR.to_string());
+        }
+        self.check_area_editable(&Area {
+            sheet,
+            row: target_row,
+            column: target_column,
+            width: target_width,
+            height: target_height,
+        })?;
+
+        if let Err(message) = self.paste_payload_cells(
+            payload,
+            options,
+            sheet,
+            (target_row, target_column),
+            &mut diff_list,
+        ) {
+            self.apply_undo_diff_list(&diff_list)?;
+            return Err(message);
+        }
+        self.push_diff_list(diff_list);
+        self.set_selected_range(
+            target_row,
+            target_column,
+            target_row + target_height - 1,
+            target_column + target_width - 1,
+        )?;
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // Sets the widths of the columns from `target_column` adding the changes to the diff list
+    fn paste_column_widths(
+        &mut self,
+        payload: &ClipboardPayload,
+        sheet: u32,
+        target_column: i32,
+        diff_list: &mut DiffList,
+    ) -> Result<(), String> {
+        for (delta, &new_value) in payload.column_widths.iter().enumerate() {
+            let column = target_column + delta as i32;
+            let old_value = self.model.get_column_width(sheet, column)?;
+            self.model.set_column_width(sheet, column, new_value)?;
+            diff_list.push(Diff::SetColumnWidth {
+                sheet,
+                column,
+                new_value,
+                old_value,
+            });
+        }
+        Ok(())
+    }
+
+    // Writes the cells of the payload from (`target_row`, `target_column`) adding the changes to
+    // the diff list
+    fn paste_payload_cells(
+        &mut self,
+        payload: &ClipboardPayload,
+        options: &PasteOptions,
+        sheet: u32,
+        (target_row, target_column): (i32, i32),
+        diff_list: &mut DiffList,
+    ) -> Result<(), String> {
+        for (delta_row, cells_row) in payload.cells.iter().enumerate() {
+            for (delta_column, copied) in cells_row.iter().enumerate() {
+                if options.skip_blanks && copied.value.is_none() && copied.formula.is_none() {
+                    continue;
+                }
+                let source_row = payload.row + delta_row as i32;
+                let source_column = payload.column + delta_column as i32;
+                let (row, column) = if options.transpose {
+                    (
+                        target_row + delta_column as i32,
+                        target_column + delta_row as i32,
+                    )
+                } else {
+                    (
+                        target_row + delta_row as i32,
+                        target_column + delta_column as i32,
+                    )
+                };
+
+                let paste_style =
+                    options.mode == PasteMode::All || options.mode == PasteMode::Formats;
+                if options.link || options.mode != PasteMode::Formats {
+                    let content = if options.link {
+                        let reference = format!(
+                            "{}!{}{}",
+                            quote_name(&payload.sheet_name),
+                            number_to_column(source_column).unwrap_or_default(),
+                            source_row
+                        );
+                        PastedContent::Formula(
+                            self.parse_pasted_formula(&reference, sheet, row, column, false)?,
+                        )
+                    } else {
+                        match (&copied.formula, &copied.value, options.mode) {
+                            (Some(formula), _, PasteMode::All | PasteMode::Formulas) => {
+                                PastedContent::Formula(self.parse_pasted_formula(
+                                    formula,
+                                    sheet,
+                                    source_row,
+                                    source_column,
+                                    options.transpose,
+                                )?)
+                            }
+                            (_, Some(CopiedValue::Text(text)), _) if copied.style.quote_prefix => {
+                                PastedContent::QuotedText(text.clone())
+                            }
+                            (_, Some(value), _) => PastedContent::Value(value.clone()),
+                            (_, None, _) => PastedContent::Empty,
+                        }
+                    };
+                    self.paste_content(sheet, row, column, content, options.operation, diff_list)?;
+                }
+                if paste_style && !options.link {
+                    let old_value = self.model.get_cell_style_or_none(sheet, row, column)?;
+                    self.model
+                        .set_cell_style(sheet, row, column, &copied.style)?;
+                    diff_list.push(Diff::SetCellStyle {
+                        sheet,
+                        row,
+                        column,
+                        old_value: Box::new(old_value),
+                        new_value: Box::new(copied.style.clone()),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Parses the English formula written in (row, column) as if it was in `sheet`.
+    // References to sheets that do not exist in this workbook become #REF! and relative
+    // references are transposed if needed
+    fn parse_pasted_formula(
+        &mut self,
+        formula: &str,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        transpose: bool,
+    ) -> Result<Node, String> {
+        let context = CellReferenceRC {
+            sheet: self.model.workbook.worksheet(sheet)?.get_name(),
+            row,
+            column,
+        };
+        let (locale, language) = (self.model.locale, self.model.language);
+        self.model.parser.set_locale(get_default_locale());
+        self.model.parser.set_language(get_default_language());
+        let mut node = self.model.parser.parse(formula, &context);
+        self.model.parser.set_locale(locale);
+        self.model.parser.set_language(language);
+        adjust_pasted_node(&mut node, transpose);
+        Ok(node)
+    }
+
+    // Sets the pasted content in the cell, combined with its current value by the operation
+    fn paste_content(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        content: PastedContent,
+        operation: PasteOperation,
+        diff_list: &mut Vec<Diff>,
+    ) -> Result<(), String> {
+        let content = if operation == PasteOperation::None {
+            content
+        } else {
+            match self.combine_with_target(sheet, row, column, content, operation)? {
+                Some(content) => content,
+                // Text, booleans and errors are not combined, the cell is left untouched
+                None => return Ok(()),
+            }
+        };
+        let old_value = self
+            .model
+            .workbook
+            .worksheet(sheet)?
+            .cell(row, column)
+            .cloned();
+        let context = CellReferenceRC {
+            sheet: self.model.workbook.worksheet(sheet)?.get_name(),
+            row,
+            column,
+        };
+        let (locale, language) = (self.model.locale, self.model.language);
+        let new_value = match content {
+            PastedContent::Empty => {
+                if old_value.is_some() {
+                    self.model.cell_clear_contents(sheet, row, column)?;
+                    diff_list.push(Diff::CellClearContents {
+                        sheet,
+                        row,
+                        column,
+                        old_value: Box::new(old_value),
+                    });
+                }
+                return Ok(());
+            }
+            PastedContent::Formula(node) => {
+                format!(
+                    "={}",
+                    to_localized_string(&node, &context, locale, language)
+                )
+            }
+            PastedContent::Value(CopiedValue::Number(v)) => {
+                let value = to_excel_precision_str(v);
+                if locale.numbers.symbols.decimal != "." {
+                    value.replace('.', &locale.numbers.symbols.decimal)
+                } else {
+                    value
+                }
+            }
+            PastedContent::Value(CopiedValue::Boolean(v)) => {
+                if v {
+                    language.booleans.r#true.to_string()
+                } else {
+                    language.booleans.r#false.to_string()
+                }
+            }
+            PastedContent::QuotedText(text) => format!("'{text}"),
+            // The quote makes sure the text is not read as a number or a formula
+            PastedContent::Value(CopiedValue::Text(text)) => {
+                if self.model.input_needs_quoting(&text) {
+                    format!("'{text}")
+                } else {
+                    text
+                }
+            }
+            PastedContent::Value(CopiedValue::Error(name)) => {
+                match get_error_by_english_name(&name) {
+                    Some(error) => error.to_localized_error_string(language),
+                    None => format!("'{name}"),
+                }
+            }
+        };
+        self.model
+            .set_user_input(sheet, row, column, new_value.clone())?;
+        diff_list.push(Diff::SetCellValue {
+            sheet,
+            row,
+            column,
+            new_value,
+            old_value: Box::new(old_value),
+        });
+        Ok(())
+    }
+
+    // Returns `target operation pasted`, or None if either side is not a number or a formula.
+    // Empty cells count as zero.
+    fn combine_with_target(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        content: PastedContent,
+        operation: PasteOperation,
+    ) -> Result<Option<PastedContent>, String> {
+        let right = match content {
+            PastedContent::Formula(node) => node,
+            PastedContent::Value(CopiedValue::Number(v)) => Node::NumberKind(v),
+            PastedContent::Empty => Node::NumberKind(0.0),
+            PastedContent::Value(_) | PastedContent::QuotedText(_) => return Ok(None),
+        };
+        let cell = self.model.workbook.worksheet(sheet)?.cell(row, column);
+        let left = match cell {
+            None | Some(Cell::EmptyCell { .. }) => Node::NumberKind(0.0),
+            Some(Cell::NumberCell { v, .. }) => Node::NumberKind(*v),
+            Some(cell) => match cell.get_formula() {
+                Some(index) => self.model.parsed_formulas[sheet as usize][index as usize].clone(),
+                None => return Ok(None),
+            },
+        };
+        if let (Node::NumberKind(x), Node::NumberKind(y)) = (&left, &right) {
+            let value = match operation {
+                PasteOperation::None | PasteOperation::Add => x + y,
+                PasteOperation::Subtract => x - y,
+                PasteOperation::Multiply => x * y,
+                PasteOperation::Divide => {
+                    if *y == 0.0 {
+                        return Ok(Some(PastedContent::Value(CopiedValue::Error(
+                            Error::DIV.to_string(),
+                        ))));
+                    }
+                    x / y
+                }
+            };
+            return Ok(Some(PastedContent::Value(CopiedValue::Number(value))));
+        }
+        let (left, right) = (Box::new(left), Box::new(right));
+        let node = match operation {
+            PasteOperation::None | PasteOperation::Add => Node::OpSumKind {
+                kind: OpSum::Add,
+                left,
+                right,
+            },
+            PasteOperation::Subtract => Node::OpSumKind {
+                kind: OpSum::Minus,
+                left,
+                right,
+            },
+            PasteOperation::Multiply => Node::OpProductKind {
+                kind: OpProduct::Times,
+                left,
+                right,
+            },
+            PasteOperation::Divide => Node::OpProductKind {
+                kind: OpProduct::Divide,
+                left,
+                right,
+            },
+        };
+        Ok(Some(PastedContent::Formula(node)))
+    }
+}
+
+// Replaces the references to sheets that do not exist with #REF!
+// If `transpose` is set the row and column offsets of relative references are swapped
+// The row and the column of a transposed reference swap places, each with its anchor, so `$A2`
+// becomes `B$1`. Absolute references are left alone. Returns false if an absolute row is too big
+// to be a column.
+fn transpose_reference(
+    row: &mut i32,
+    column: &mut i32,
+    absolute_row: &mut bool,
+    absolute_column: &mut bool,
+) -> bool {
+    if *absolute_row && *absolute_column {
+        return true;
+    }
+    if *absolute_row && *row > LAST_COLUMN {
+        return false;
+    }
+    std::mem::swap(row, column);
+    std::mem::swap(absolute_row, absolute_column);
+    true
+}
+
+fn adjust_pasted_node(node: &mut Node, transpose: bool) {
+    match node {
+        Node::WrongReferenceKind {
+            sheet_name: Some(_),
+            ..
+        }
+        | Node::WrongRangeKind {
+            sheet_name: Some(_),
+            ..
+        } => *node = Node::ErrorKind(Error::REF),
+        Node::ReferenceKind {
+            row,
+            column,
+            absolute_row,
+            absolute_column,
+            ..
+        } if transpose => {
+            let fits = transpose_reference(row, column, absolute_row, absolute_column);
+            if !fits {
+                *node = Node::ErrorKind(Error::REF);
+            }
+        }
+        Node::RangeKind {
+            row1,
+            column1,
+            absolute_row1,
+            absolute_column1,
+            row2,
+            column2,
+            absolute_row2,
+            absolute_column2,
+            ..
+        } if transpose => {
+            let fits = transpose_reference(row1, column1, absolute_row1, absolute_column1);
+            if !(transpose_reference(row2, column2, absolute_row2, absolute_column2) && fits) {
+                *node = Node::ErrorKind(Error::REF);
+            }
+        }
+        Node::OpRangeKind { left, right }
+        | Node::OpConcatenateKind { left, right }
+        | Node::OpSumKind { left, right, .. }
+        | Node::OpProductKind { left, right, .. }
+        | Node::OpPowerKind { left, right }
+        | Node::CompareKind { left, right, .. } => {
+            adjust_pasted_node(left, transpose);
+            adjust_pasted_node(right, transpose);
+        }
+        Node::FunctionKind { args, .. } | Node::InvalidFunctionKind { args, .. } => {
+            for arg in args {
+                adjust_pasted_node(arg, transpose);
+            }
+        }
+        Node::UnaryKind { right, .. } => adjust_pasted_node(right, transpose),
+        Node::ImplicitIntersection { child, .. } => adjust_pasted_node(child, transpose),
+        _ => {}
+    }
+}
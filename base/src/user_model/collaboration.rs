@@ -0,0 +1,550 @@
+// Collaborative editing.
+//
+// Several users edit the same workbook. Every change goes through a server that just gives it a
+// revision number and broadcasts it to all clients, the server does not need to know anything
+// about the workbook.
+// Each client applies the local changes that have not been committed yet on top of the workbook
+// with all the committed changes (the "confirmed" state). To apply a committed change the local
+// changes are undone, the committed change is applied and the local changes are applied again.
+//
+// A change is created against some revision of the workbook. When it is committed other changes
+// might have been committed in between, so before applying it we transform it against them (see
+// `transform.rs`). All clients apply the same committed changes in the same order with the same
+// transformations, so they all end up with the same workbook.
+//
+// A client has at most one operation waiting for the server at any time. Local changes made in
+// the meantime are buffered and sent once the previous operation has been committed.
+
+use std::collections::HashMap;
+
+use crate::{
+    model::Model,
+    types::{WorkbookView, WorksheetView},
+};
+
+use super::{
+    cell_tables::CellTables,
+    diffs,
+    history::{Diff, DiffType, HistoryEntry, QueueDiffs},
+    revert::get_inverse,
+    transform::transform_queue_diffs,
+    UserModel,
+};
+
+// Operations are sent as the client id (4 bytes) and the base revision (8 bytes), little endian,
+// followed by the cell tables (see `cell_tables.rs`) as JSON, prefixed by their length (4 bytes),
+// and the list of diffs in the wire format of `diffs.rs`.
+// A committed operation is the revision and the oldest base revision the server still accepts
+// (8 bytes each) followed by the operation.
+const OPERATION_HEADER_LEN: usize = 12;
+const COMMITTED_HEADER_LEN: usize = 16;
+
+// The views of the workbook and the views of each sheet by sheet id
+type SavedViews = (
+    HashMap<u32, WorkbookView>,
+    HashMap<u32, HashMap<u32, WorksheetView>>,
+);
+
+/// A list of changes made by a client on top of a revision of the workbook.
+/// The cells in the diffs refer to the cell tables of the session.
+#[derive(Clone)]
+struct Operation {
+    client_id: u32,
+    base_revision: u64,
+    list: Vec<QueueDiffs>,
+}
+
+impl Operation {
+    fn to_bytes(&self, tables: &CellTables) -> Result<Vec<u8>, String> {
+        // Only the cells of the operation go with it
+        let mut list = self.list.clone();
+        let mut operation_tables = CellTables::default();
+        tables.copy_to(&mut list, &mut operation_tables)?;
+        let tables_json = serde_json::to_vec(&operation_tables).map_err(|e| e.to_string())?;
+        let mut bytes = self.client_id.to_le_bytes().to_vec();
+        bytes.extend(self.base_revision.to_le_bytes());
+        bytes.extend((tables_json.len() as u32).to_le_bytes());
+        bytes.extend(tables_json);
+        bytes.extend(diffs::to_bytes(&list));
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8], tables: &mut CellTables) -> Result<Operation, String> {
+        let raw = read_operation(bytes)?;
+        let operation_tables: CellTables =
+            serde_json::from_slice(raw.tables).map_err(|e| format!("Invalid cell tables: {e}"))?;
+        let decoded = diffs::from_bytes(raw.diffs)?;
+        if !decoded.unknown.is_empty() {
+            let ops: Vec<&str> = decoded.unknown.iter().map(|u| u.op.as_str()).collect();
+            return Err(format!("Unknown diffs: {}", ops.join(", ")));
+        }
+        let mut list = decoded.changes;
+        operation_tables.copy_to(&mut list, tables)?;
+        Ok(Operation {
+            client_id: raw.client_id,
+            base_revision: raw.base_revision,
+            list,
+        })
+    }
+}
+
+// The parts of an operation, the cell tables and the diffs are not decoded
+struct RawOperation<'a> {
+    client_id: u32,
+    base_revision: u64,
+    tables: &'a [u8],
+    diffs: &'a [u8],
+}
+
+fn read_operation(bytes: &[u8]) -> Result<RawOperation<'_>, String> {
+    let tables_start = OPERATION_HEADER_LEN + 4;
+    let (client_id, base_revision, tables_len) = match (
+        bytes.get(0..4),
+        bytes.get(4..OPERATION_HEADER_LEN),
+        bytes.get(OPERATION_HEADER_LEN..tables_start),
+    ) {
+        (Some(client_id), Some(base_revision), Some(tables_len)) => (
+            u32::from_le_bytes(client_id.try_into().map_err(|_| "Invalid client id")?),
+            u64::from_le_bytes(base_revision.try_into().map_err(|_| "Invalid revision")?),
+            u32::from_le_bytes(tables_len.try_into().map_err(|_| "Invalid cell tables")?),
+        ),
+        _ => return Err("Operation too short".to_string()),
+    };
+    let diffs_start = tables_start + tables_len as usize;
+    match (
+        bytes.get(tables_start..diffs_start),
+        bytes.get(diffs_start..),
+    ) {
+        (Some(tables), Some(diffs)) => Ok(RawOperation {
+            client_id,
+            base_revision,
+            tables,
+            diffs,
+        }),
+        _ => Err("Operation too short".to_string()),
+    }
+}
+
+// Returns the revision, the oldest base revision and the operation of a committed operation
+fn read_committed_operation(bytes: &[u8]) -> Result<(u64, u64, &[u8]), String> {
+    match (bytes.get(0..8), bytes.get(8..COMMITTED_HEADER_LEN)) {
+        (Some(revision), Some(min_base_revision)) => Ok((
+            u64::from_le_bytes(revision.try_into().map_err(|_| "Invalid revision")?),
+            u64::from_le_bytes(
+                min_base_revision
+                    .try_into()
+                    .map_err(|_| "Invalid revision")?,
+            ),
+            &bytes[COMMITTED_HEADER_LEN..],
+        )),
+        _ => Err("Committed operation too short".to_string()),
+    }
+}
+
+/// Orders the operations sent by the clients of a collaborative session.
+///
+/// Every operation gets the next revision number. The result must be sent to all the clients,
+/// including the one that sent the operation.
+///
+/// Clients keep the committed operations that operations sent later might need to be transformed
+/// against. The server tells them the oldest revision an operation can be based on, so that
+/// clients must be added with [CollaborationServer::add_client] when they join.
+///
+/// See also:
+/// * [UserModel::take_pending_operation]
+/// * [UserModel::apply_committed_operation]
+#[derive(Default)]
+pub struct CollaborationServer {
+    revision: u64,
+    // The revision each client has last based an operation on, or joined at
+    clients: HashMap<u32, u64>,
+}
+
+impl CollaborationServer {
+    /// Creates a server for a workbook at revision `revision`
+    pub fn new(revision: u64) -> CollaborationServer {
+        CollaborationServer {
+            revision,
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Adds a client that starts from the workbook at the current revision
+    pub fn add_client(&mut self, client_id: u32) {
+        self.clients.insert(client_id, self.revision);
+    }
+
+    /// Removes a client that left the session
+    pub fn remove_client(&mut self, client_id: u32) {
+        self.clients.remove(&client_id);
+    }
+
+    // The oldest revision a client can still base an operation on
+    fn get_min_base_revision(&self) -> u64 {
+        self.clients
+            .values()
+            .copied()
+            .min()
+            .unwrap_or(self.revision)
+    }
+
+    /// Returns the revision of the last committed operation
+    pub fn get_revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Commits an operation sent by a client and returns the committed operation
+    pub fn commit(&mut self, operation: &[u8]) -> Result<Vec<u8>, String> {
+        // The diffs are passed on as they are, the server might not know all of them
+        let RawOperation {
+            client_id,
+            base_revision,
+            diffs: diff_bytes,
+            ..
+        } = read_operation(operation)
+            .map_err(|message| format!("Error parsing operation: {message}"))?;
+        diffs::from_bytes(diff_bytes)
+            .map_err(|message| format!("Error parsing operation: {message}"))?;
+        if base_revision > self.revision {
+            return Err(format!(
+                "Operation based on unknown revision {base_revision}"
+            ));
+        }
+        // Clients might not have the operations needed to transform it anymore
+        if base_revision < self.get_min_base_revision() {
+            return Err(format!(
+                "Operation based on revision {base_revision} that is no longer available"
+            ));
+        }
+        // The operations of a client are based on increasing revisions
+        self.clients.insert(client_id, base_revision);
+        self.revision += 1;
+        let mut committed = self.revision.to_le_bytes().to_vec();
+        committed.extend(self.get_min_base_revision().to_le_bytes());
+        committed.extend(operation);
+        Ok(committed)
+    }
+}
+
+/// The state of a client in a collaborative session
+pub(super) struct Collaboration {
+    client_id: u32,
+    revision: u64,
+    // The committed operations, as they were applied to the confirmed state.
+    // Only the ones after the oldest base revision the server accepts are kept.
+    log: Vec<(u64, Vec<QueueDiffs>)>,
+    // The operation sent to the server and not yet committed
+    in_flight: Option<Operation>,
+    // Local changes not yet sent to the server
+    buffer: Vec<QueueDiffs>,
+    // The strings, formulas and styles the cells in the diffs of the log, the operation in flight
+    // and the buffer refer to
+    tables: CellTables,
+    // The changes that take the model back to the confirmed state, in the order the local
+    // changes were made. They are applied last one first.
+    revert: Vec<QueueDiffs>,
+}
+
+impl<'a> UserModel<'a> {
+    /// Starts a collaborative session. The model must be the workbook at revision `revision`.
+    /// `client_id` must be different for each client of the session.
+    ///
+    /// Local changes are not sent with [UserModel::flush_send_queue] anymore, but with
+    /// [UserModel::take_pending_operation].
+    pub fn start_collaboration(&mut self, client_id: u32, revision: u64) {
+        self.send_queue = vec![];
+        self.collaboration = Some(Collaboration {
+            client_id,
+            revision,
+            log: vec![],
+            in_flight: None,
+            buffer: vec![],
+            tables: CellTables::default(),
+            revert: vec![],
+        });
+    }
+
+    /// Returns the revision of the last committed operation applied to the model, if in a
+    /// collaborative session
+    pub fn get_revision(&self) -> Option<u64> {
+        self.collaboration.as_ref().map(|c| c.revision)
+    }
+
+    /// Returns true if there are local changes that have not been committed yet
+    pub fn has_pending_changes(&self) -> bool {
+        match &self.collaboration {
+            Some(c) => c.in_flight.is_some() || !c.buffer.is_empty(),
+            None => false,
+        }
+    }
+
+    /// Returns the operation with the local changes that needs to be sent to the server, if any.
+    ///
+    /// Returns `None` if there are no changes or if the previous operation has not been committed
+    /// yet. In that case the changes are kept and sent later.
+    pub fn take_pending_operation(&mut self) -> Option<Vec<u8>> {
+        let collaboration = self.collaboration.as_mut()?;
+        if collaboration.in_flight.is_some() || collaboration.buffer.is_empty() {
+            return None;
+        }
+        let operation = Operation {
+            client_id: collaboration.client_id,
+            base_revision: collaboration.revision,
+            list: std::mem::take(&mut collaboration.buffer),
+        };
+        match operation.to_bytes(&collaboration.tables) {
+            Ok(bytes) => {
+                collaboration.in_flight = Some(operation);
+                Some(bytes)
+            }
+            Err(_) => {
+                collaboration.buffer = operation.list;
+                None
+            }
+        }
+    }
+
+    /// Applies an operation committed by the server.
+    ///
+    /// Operations must be applied in the order of their revision. Local changes that are not
+    /// committed yet are transformed and kept on top of it.
+    pub fn apply_committed_operation(&mut self, committed: &[u8]) -> Result<(), String> {
+        let (revision, min_base_revision, operation) = read_committed_operation(committed)
+            .map_err(|message| format!("Error parsing committed operation: {message}"))?;
+        let mut collaboration = match self.collaboration.take() {
+            Some(collaboration) => collaboration,
+            None => return Err("Not in a collaborative session".to_string()),
+        };
+        let operation = if revision != collaboration.revision + 1 {
+            Err(format!(
+                "Expected revision {}, got {}",
+                collaboration.revision + 1,
+                revision
+            ))
+        } else {
+            Operation::from_bytes(operation, &mut collaboration.tables)
+                .map_err(|message| format!("Error parsing committed operation: {message}"))
+        };
+        let operation = match operation {
+            Ok(operation) => operation,
+            Err(message) => {
+                self.collaboration = Some(collaboration);
+                return Err(message);
+            }
+        };
+
+        // The views of the user are kept, sheets are matched by id
+        let views = self.get_views();
+        let pause_evaluation = self.pause_evaluation;
+        self.pause_evaluation = true;
+
+        // We go back to the confirmed state undoing the local changes, last ones first
+        for queue_diffs in std::mem::take(&mut collaboration.revert).into_iter().rev() {
+            self.apply_queue_diffs(vec![queue_diffs], None);
+        }
+
+        let concurrent: Vec<QueueDiffs> = collaboration
+            .log
+            .iter()
+            .filter(|(revision, _)| *revision > operation.base_revision)
+            .flat_map(|(_, list)| list.clone())
+            .collect();
+        let list = transform_queue_diffs(&mut self.model, operation.list, &concurrent, true);
+
+        // Local changes go after the committed operation
+        let mut history_changes = list.clone();
+        if operation.client_id == collaboration.client_id {
+            collaboration.in_flight = None;
+            history_changes = vec![];
+        } else if let Some(in_flight) = collaboration.in_flight.take() {
+            let in_flight_list =
+                transform_queue_diffs(&mut self.model, in_flight.list.clone(), &list, true);
+            history_changes =
+                transform_queue_diffs(&mut self.model, list.clone(), &in_flight.list, false);
+            collaboration.in_flight = Some(Operation {
+                list: in_flight_list,
+                ..in_flight
+            });
+        }
+        collaboration.buffer = transform_queue_diffs(
+            &mut self.model,
+            std::mem::take(&mut collaboration.buffer),
+            &history_changes,
+            true,
+        );
+        self.transform_history(&history_changes);
+
+        // Diffs that do not apply anymore are ignored, every client ignores the same ones
+        let tables = &collaboration.tables;
+        let (list, _) = self.apply_queue_diffs(list, Some(tables));
+        collaboration.revision = revision;
+        collaboration.log.push((revision, list));
+        // Operations committed later are based on newer revisions
+        collaboration
+            .log
+            .retain(|(revision, _)| *revision > min_base_revision);
+        if let Some(in_flight) = collaboration.in_flight.as_mut() {
+            let (list, revert) =
+                self.apply_queue_diffs(std::mem::take(&mut in_flight.list), Some(tables));
+            in_flight.list = list;
+            collaboration.revert.extend(revert);
+        }
+        let (buffer, revert) =
+            self.apply_queue_diffs(std::mem::take(&mut collaboration.buffer), Some(tables));
+        collaboration.buffer = buffer;
+        collaboration.revert.extend(revert);
+        self.pause_evaluation = pause_evaluation;
+
+        self.restore_views(views);
+        self.collaboration = Some(collaboration);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    /// Keeps a local change to send it to the server. `revert` are the changes that revert it,
+    /// if it is not just its inverse.
+    pub(super) fn push_local_change(
+        &mut self,
+        queue_diffs: QueueDiffs,
+        revert: Option<Vec<QueueDiffs>>,
+    ) {
+        let collaboration = match self.collaboration.as_mut() {
+            Some(collaboration) => collaboration,
+            None => return,
+        };
+        // Changes made by the user are made on the current state of the model
+        let revert = revert.unwrap_or_else(|| vec![get_inverse(&queue_diffs)]);
+        collaboration.revert.extend(revert);
+        let mut list = vec![queue_diffs];
+        // A change we can't send is reverted with the next committed operation
+        if collaboration.tables.export(&self.model, &mut list).is_ok() {
+            collaboration.buffer.extend(list);
+        }
+    }
+
+    /// Applies a local undo or redo in a collaborative session and returns the changes that
+    /// revert it.
+    ///
+    /// The diffs of the history might have been made before remote changes to the same cells, so
+    /// their inverse might not go back to the current state.
+    pub(super) fn apply_local_queue_diffs(&mut self, queue_diffs: &QueueDiffs) -> Vec<QueueDiffs> {
+        let pause_evaluation = self.pause_evaluation;
+        self.pause_evaluation = true;
+        let (_, revert) = self.apply_queue_diffs(vec![queue_diffs.clone()], None);
+        self.pause_evaluation = pause_evaluation;
+        self.evaluate_if_not_paused();
+        revert
+    }
+
+    // Applies the diffs one by one. Returns the ones that could be applied and the changes that
+    // revert them exactly, in the order they were applied.
+    // The cells of the diffs refer to `tables` if given, to the model otherwise.
+    fn apply_queue_diffs(
+        &mut self,
+        list: Vec<QueueDiffs>,
+        tables: Option<&CellTables>,
+    ) -> (Vec<QueueDiffs>, Vec<QueueDiffs>) {
+        let mut applied_list = Vec::new();
+        let mut revert = Vec::new();
+        for queue_diffs in list {
+            let diffs: Vec<Diff> = match queue_diffs.r#type {
+                DiffType::Redo => queue_diffs.list,
+                DiffType::Undo => queue_diffs.list.into_iter().rev().collect(),
+            };
+            let mut applied = Vec::new();
+            for diff in diffs {
+                let mut model_diff = diff.clone();
+                if let Some(tables) = tables {
+                    if tables.import(&mut self.model, &mut model_diff).is_err() {
+                        continue;
+                    }
+                }
+                // If we can't tell how to revert it the diff can't be applied either
+                let diff_revert = match self.get_revert(&queue_diffs.r#type, &model_diff) {
+                    Ok(diff_revert) => diff_revert,
+                    Err(_) => continue,
+                };
+                let result = match queue_diffs.r#type {
+                    DiffType::Redo => self.apply_diff_list(&vec![model_diff]),
+                    DiffType::Undo => self.apply_undo_diff_list(&vec![model_diff]),
+                };
+                if result.is_ok() {
+                    applied.push(diff);
+                    revert.extend(self.get_revert_steps(diff_revert).unwrap_or_default());
+                }
+            }
+            if matches!(queue_diffs.r#type, DiffType::Undo) {
+                applied.reverse();
+            }
+            if !applied.is_empty() {
+                applied_list.push(QueueDiffs {
+                    r#type: queue_diffs.r#type,
+                    list: applied,
+                });
+            }
+        }
+        (applied_list, revert)
+    }
+
+    // Undo and redo must still work after the remote changes
+    fn transform_history(&mut self, changes: &[QueueDiffs]) {
+        if changes.is_empty() {
+            return;
+        }
+        let transform = |model: &mut Model, stack: &mut Vec<HistoryEntry>, diff_type: DiffType| {
+            *stack = std::mem::take(stack)
+                .into_iter()
+                .filter_map(|entry| {
+                    let list = vec![QueueDiffs {
+                        r#type: diff_type.clone(),
+                        list: entry.diff_list,
+                    }];
+                    transform_queue_diffs(model, list, changes, true)
+                        .pop()
+                        .map(|queue_diffs| HistoryEntry {
+                            diff_list: queue_diffs.list,
+                            ..entry
+                        })
+                })
+                .collect();
+        };
+        transform(
+            &mut self.model,
+            &mut self.history.undo_stack,
+            DiffType::Undo,
+        );
+        transform(
+            &mut self.model,
+            &mut self.history.redo_stack,
+            DiffType::Redo,
+        );
+    }
+
+    // The views of the workbook and of each sheet by id
+    fn get_views(&self) -> SavedViews {
+        let workbook = &self.model.workbook;
+        let worksheets = workbook
+            .worksheets
+            .iter()
+            .map(|worksheet| (worksheet.sheet_id, worksheet.views.clone()))
+            .collect();
+        (workbook.views.clone(), worksheets)
+    }
+
+    // Sheets are matched by id, sheets might have been added or deleted
+    fn restore_views(&mut self, (mut views, mut worksheet_views): SavedViews) {
+        let worksheets = &mut self.model.workbook.worksheets;
+        for worksheet in worksheets.iter_mut() {
+            if let Some(old) = worksheet_views.remove(&worksheet.sheet_id) {
+                worksheet.views = old;
+            }
+        }
+        let sheet_count = worksheets.len() as u32;
+        for view in views.values_mut() {
+            if view.sheet >= sheet_count {
+                view.sheet = sheet_count.saturating_sub(1);
+            }
+        }
+        self.model.workbook.views = views;
+    }
+}
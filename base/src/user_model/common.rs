@@ -2,28 +2,43 @@
 
 use std::{collections::HashMap, fmt::Debug, io::Cursor};
 
-use csv::{ReaderBuilder, WriterBuilder};
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    auto_filter::validate_column_filter,
     constants::{self, LAST_COLUMN, LAST_ROW},
     expressions::{
+        parser::parse_range,
         types::{Area, CellReferenceIndex},
-        utils::{is_valid_column_number, is_valid_row},
+        utils::{is_valid_column_number, is_valid_row, number_to_column},
     },
-    model::{FmtSettings, Model},
+    formatter::lexer::{is_likely_date_number_format, is_time_number_format},
+    model::{get_milliseconds_since_epoch, FmtSettings, Model},
+    page_setup::{PRINT_AREA, PRINT_TITLES},
     types::{
-        Alignment, BorderItem, Cell, CellType, Col, HorizontalAlignment, SheetProperties,
-        SheetState, Style, VerticalAlignment,
+        Alignment, AutoFilter, BorderItem, Cell, CellProtection, CellType, Col, Color,
+        ColumnFilter, DateSystem, FilterColumn, FontUnderline, FontVerticalAlign,
+        HorizontalAlignment, NamedStyle, PatternType, ReadingOrder, SheetProperties, SheetState,
+        SortKey, Style, TextRun, Theme, VerticalAlignment,
     },
     utils::is_valid_hex_color,
 };
 
 use crate::user_model::history::{
-    ColumnData, Diff, DiffList, DiffType, History, QueueDiffs, RowData,
+    CellData, ColumnData, Diff, DiffList, DiffType, History, HistoryEntry, QueueDiffs, RowData,
 };
 
-use super::{border_utils::is_max_border, sequence_detector::detect_progression};
+use super::{
+    border_utils::is_max_border,
+    collaboration::Collaboration,
+    diffs,
+    sequence_detector::{
+        detect_date_progression, detect_progression, detect_time_progression, detect_trend,
+        AutoFillMode, DateUnit, Progression,
+    },
+    ui::SelectedView,
+};
 
 /// Data for the clipboard
 pub type ClipboardData = HashMap<i32, HashMap<i32, ClipboardCell>>;
@@ -32,8 +47,8 @@ pub type ClipboardTuple = (i32, i32, i32, i32);
 
 #[derive(Serialize, Deserialize)]
 pub struct ClipboardCell {
-    text: String,
-    style: Style,
+    pub(crate) text: String,
+    pub(crate) style: Style,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -73,14 +88,14 @@ fn boolean(value: &str) -> Result<bool, String> {
     }
 }
 
-fn color(value: &str) -> Result<Option<String>, String> {
+fn color(value: &str) -> Result<Option<Color>, String> {
     if value.is_empty() {
         return Ok(None);
     }
     if !is_valid_hex_color(value) {
         return Err(format!("Invalid color: '{value}'."));
     }
-    Ok(Some(value.to_owned()))
+    Ok(Some(Color::Rgb(value.to_owned())))
 }
 
 fn horizontal(value: &str) -> Result<HorizontalAlignment, String> {
@@ -110,6 +125,72 @@ fn vertical(value: &str) -> Result<VerticalAlignment, String> {
     }
 }
 
+// Either a boolean or the kind of underline
+fn underline(value: &str) -> Result<FontUnderline, String> {
+    match value {
+        "false" | "none" => Ok(FontUnderline::None),
+        "true" | "single" => Ok(FontUnderline::Single),
+        "double" => Ok(FontUnderline::Double),
+        "singleaccounting" => Ok(FontUnderline::SingleAccounting),
+        "doubleaccounting" => Ok(FontUnderline::DoubleAccounting),
+        _ => Err(format!("Invalid value for underline: '{value}'.")),
+    }
+}
+
+fn vert_align(value: &str) -> Result<FontVerticalAlign, String> {
+    match value {
+        "baseline" => Ok(FontVerticalAlign::Baseline),
+        "superscript" => Ok(FontVerticalAlign::Superscript),
+        "subscript" => Ok(FontVerticalAlign::Subscript),
+        _ => Err(format!("Invalid value for vertical align: '{value}'.")),
+    }
+}
+
+fn reading_order(value: &str) -> Result<ReadingOrder, String> {
+    match value {
+        "context" => Ok(ReadingOrder::Context),
+        "lefttoright" => Ok(ReadingOrder::LeftToRight),
+        "righttoleft" => Ok(ReadingOrder::RightToLeft),
+        _ => Err(format!("Invalid value for reading order: '{value}'.")),
+    }
+}
+
+// Degrees from 0 to 180 or 255 for vertical text
+fn text_rotation(value: &str) -> Result<i32, String> {
+    match value.parse::<i32>() {
+        Ok(rotation) if (0..=180).contains(&rotation) || rotation == 255 => Ok(rotation),
+        _ => Err(format!("Invalid value for text rotation: '{value}'.")),
+    }
+}
+
+fn indent(value: &str) -> Result<i32, String> {
+    match value.parse::<i32>() {
+        Ok(indent) if (0..=250).contains(&indent) => Ok(indent),
+        _ => Err(format!("Invalid value for indent: '{value}'.")),
+    }
+}
+
+// Sets (or removes if `filter` is None) the criteria of a column of the AutoFilter
+fn set_filter_column(
+    mut auto_filter: AutoFilter,
+    col_id: u32,
+    filter: Option<ColumnFilter>,
+) -> Result<AutoFilter, String> {
+    let (first_column, _, last_column, _) = parse_range(&auto_filter.reference)?;
+    if col_id as i32 > last_column - first_column {
+        return Err(format!("Invalid filter column: {col_id}"));
+    }
+    auto_filter.filter_columns.retain(|f| f.col_id != col_id);
+    if let Some(filter) = filter {
+        validate_column_filter(&filter)?;
+        auto_filter
+            .filter_columns
+            .push(FilterColumn { col_id, filter });
+        auto_filter.filter_columns.sort_by_key(|f| f.col_id);
+    }
+    Ok(auto_filter)
+}
+
 fn update_style(old_value: &Style, style_path: &str, value: &str) -> Result<Style, String> {
     let mut style = old_value.clone();
     match style_path {
@@ -120,11 +201,23 @@ fn update_style(old_value: &Style, style_path: &str, value: &str) -> Result<Styl
             style.font.i = boolean(value)?;
         }
         "font.u" => {
-            style.font.u = boolean(value)?;
+            style.font.underline = underline(value)?;
         }
         "font.strike" => {
             style.font.strike = boolean(value)?;
         }
+        "font.vert_align" => {
+            style.font.vert_align = vert_align(value)?;
+        }
+        "font.outline" => {
+            style.font.outline = boolean(value)?;
+        }
+        "font.shadow" => {
+            style.font.shadow = boolean(value)?;
+        }
+        "font.condense" => {
+            style.font.condense = boolean(value)?;
+        }
         "font.color" => {
             style.font.color = color(value)?;
         }
@@ -141,11 +234,17 @@ fn update_style(old_value: &Style, style_path: &str, value: &str) -> Result<Styl
         }
         "fill.bg_color" => {
             style.fill.bg_color = color(value)?;
-            style.fill.pattern_type = "solid".to_string();
+            style.fill.pattern_type = PatternType::Solid;
+            style.fill.gradient = None;
         }
         "fill.fg_color" => {
             style.fill.fg_color = color(value)?;
-            style.fill.pattern_type = "solid".to_string();
+            style.fill.pattern_type = PatternType::Solid;
+            style.fill.gradient = None;
+        }
+        "fill.pattern_type" => {
+            style.fill.pattern_type = value.parse()?;
+            style.fill.gradient = None;
         }
         "num_fmt" => {
             value.clone_into(&mut style.num_fmt);
@@ -186,6 +285,41 @@ fn update_style(old_value: &Style, style_path: &str, value: &str) -> Result<Styl
                 style.alignment = Some(alignment)
             }
         },
+        "alignment.text_rotation" => {
+            let alignment = style.alignment.get_or_insert_with(Default::default);
+            alignment.text_rotation = text_rotation(value)?;
+        }
+        "alignment.indent" => {
+            let alignment = style.alignment.get_or_insert_with(Default::default);
+            alignment.indent = indent(value)?;
+        }
+        "alignment.shrink_to_fit" => {
+            let alignment = style.alignment.get_or_insert_with(Default::default);
+            alignment.shrink_to_fit = boolean(value)?;
+        }
+        "alignment.justify_last_line" => {
+            let alignment = style.alignment.get_or_insert_with(Default::default);
+            alignment.justify_last_line = boolean(value)?;
+        }
+        "alignment.reading_order" => {
+            let alignment = style.alignment.get_or_insert_with(Default::default);
+            alignment.reading_order = reading_order(value)?;
+        }
+        "protection.locked" => {
+            let protection = style.protection.get_or_insert_with(Default::default);
+            protection.locked = boolean(value)?;
+            // The default protection is the same as no protection flags
+            if *protection == CellProtection::default() {
+                style.protection = None;
+            }
+        }
+        "protection.hidden" => {
+            let protection = style.protection.get_or_insert_with(Default::default);
+            protection.hidden = boolean(value)?;
+            if *protection == CellProtection::default() {
+                style.protection = None;
+            }
+        }
         _ => {
             return Err(format!("Invalid style path: '{style_path}'."));
         }
@@ -221,9 +355,10 @@ fn update_style(old_value: &Style, style_path: &str, value: &str) -> Result<Styl
 /// ```
 pub struct UserModel<'a> {
     pub(crate) model: Model<'a>,
-    history: History,
-    send_queue: Vec<QueueDiffs>,
-    pause_evaluation: bool,
+    pub(super) history: History,
+    pub(super) send_queue: Vec<QueueDiffs>,
+    pub(super) pause_evaluation: bool,
+    pub(super) collaboration: Option<Collaboration>,
 }
 
 impl<'a> Debug for UserModel<'a> {
@@ -240,6 +375,7 @@ impl<'a> UserModel<'a> {
             history: History::default(),
             send_queue: vec![],
             pause_evaluation: false,
+            collaboration: None,
         }
     }
 
@@ -259,6 +395,7 @@ impl<'a> UserModel<'a> {
             history: History::default(),
             send_queue: vec![],
             pause_evaluation: false,
+            collaboration: None,
         })
     }
 
@@ -273,6 +410,7 @@ impl<'a> UserModel<'a> {
             history: History::default(),
             send_queue: vec![],
             pause_evaluation: false,
+            collaboration: None,
         })
     }
 
@@ -304,12 +442,19 @@ impl<'a> UserModel<'a> {
     /// See also:
     /// * [UserModel::redo]
     pub fn undo(&mut self) -> Result<(), String> {
-        if let Some(diff_list) = self.history.undo() {
-            self.apply_undo_diff_list(&diff_list)?;
-            self.send_queue.push(QueueDiffs {
+        if let Some(entry) = self.history.undo() {
+            let queue_diffs = QueueDiffs {
                 r#type: DiffType::Undo,
-                list: diff_list.clone(),
-            });
+                list: entry.diff_list.clone(),
+            };
+            let revert = if self.collaboration.is_some() {
+                Some(self.apply_local_queue_diffs(&queue_diffs))
+            } else {
+                self.apply_undo_diff_list(&entry.diff_list)?;
+                None
+            };
+            self.push_send_queue(queue_diffs, revert);
+            self.restore_view(&entry.view);
         };
         Ok(())
     }
@@ -319,12 +464,19 @@ impl<'a> UserModel<'a> {
     /// See also:
     /// * [UserModel::redo]
     pub fn redo(&mut self) -> Result<(), String> {
-        if let Some(diff_list) = self.history.redo() {
-            self.apply_diff_list(&diff_list)?;
-            self.send_queue.push(QueueDiffs {
+        if let Some(entry) = self.history.redo() {
+            let queue_diffs = QueueDiffs {
                 r#type: DiffType::Redo,
-                list: diff_list.clone(),
-            });
+                list: entry.diff_list.clone(),
+            };
+            let revert = if self.collaboration.is_some() {
+                Some(self.apply_local_queue_diffs(&queue_diffs))
+            } else {
+                self.apply_diff_list(&entry.diff_list)?;
+                None
+            };
+            self.push_send_queue(queue_diffs, revert);
+            self.restore_view(&entry.redo_view);
         };
         Ok(())
     }
@@ -339,6 +491,18 @@ impl<'a> UserModel<'a> {
         !self.history.redo_stack.is_empty()
     }
 
+    /// Sets the maximum number of changes that can be undone, the oldest are dropped.
+    /// `None` means there is no limit (the default).
+    pub fn set_max_history_depth(&mut self, max_depth: Option<usize>) {
+        self.history.set_max_depth(max_depth);
+    }
+
+    /// Consecutive cell inputs made within `milliseconds` of each other are undone as a single
+    /// change. 0 (the default) disables the grouping.
+    pub fn set_typing_group_interval(&mut self, milliseconds: i64) {
+        self.history.typing_interval = milliseconds;
+    }
+
     /// Pauses automatic evaluation.
     ///
     /// See also:
@@ -369,34 +533,36 @@ impl<'a> UserModel<'a> {
     /// Returns the list of pending diffs and removes them from the queue
     ///
     /// This is used together with [apply_external_diffs](UserModel::apply_external_diffs) to keep two remote models
-    /// in sync.
+    /// in sync. The diffs are in the binary form of the [diff wire format](crate::diffs).
     ///
     /// See also:
     /// * [UserModel::apply_external_diffs]
     pub fn flush_send_queue(&mut self) -> Vec<u8> {
-        // This can never fail :O:
-        let q = bitcode::encode(&self.send_queue);
+        let q = diffs::to_bytes(&self.send_queue);
         self.send_queue = vec![];
         q
     }
 
     /// This are external diffs that need to be applied to the model
     ///
-    /// This is used together with [flush_send_queue](UserModel::flush_send_queue) to keep two remote models in sync
+    /// This is used together with [flush_send_queue](UserModel::flush_send_queue) to keep two remote models in sync.
+    /// Nothing is applied if some of the diffs are unknown to this version.
     ///
     /// See also:
     /// * [UserModel::flush_send_queue]
     pub fn apply_external_diffs(&mut self, diff_list_str: &[u8]) -> Result<(), String> {
-        if let Ok(queue_diffs_list) = bitcode::decode::<Vec<QueueDiffs>>(diff_list_str) {
-            for queue_diff in queue_diffs_list {
-                if matches!(queue_diff.r#type, DiffType::Redo) {
-                    self.apply_diff_list(&queue_diff.list)?;
-                } else {
-                    self.apply_undo_diff_list(&queue_diff.list)?;
-                }
+        let decoded = diffs::from_bytes(diff_list_str)
+            .map_err(|message| format!("Error parsing diff list: {message}"))?;
+        if !decoded.unknown.is_empty() {
+            let ops: Vec<&str> = decoded.unknown.iter().map(|u| u.op.as_str()).collect();
+            return Err(format!("Unknown diffs: {}", ops.join(", ")));
+        }
+        for queue_diff in decoded.changes {
+            if matches!(queue_diff.r#type, DiffType::Redo) {
+                self.apply_diff_list(&queue_diff.list)?;
+            } else {
+                self.apply_undo_diff_list(&queue_diff.list)?;
             }
-        } else {
-            return Err("Error parsing diff list".to_string());
         }
         Ok(())
     }
@@ -418,6 +584,7 @@ impl<'a> UserModel<'a> {
         if !is_valid_row(row) {
             return Err("Invalid row".to_string());
         }
+        self.check_cell_editable(sheet, row, column)?;
         let old_value = self
             .model
             .workbook
@@ -454,19 +621,116 @@ impl<'a> UserModel<'a> {
             self.model.set_row_height(sheet, row, cell_height)?;
         }
 
-        self.push_diff_list(diff_list);
+        self.push_typing_diff_list(diff_list);
+        Ok(())
+    }
+
+    /// Set the input in a cell with formulas in R1C1 notation, like `=SUM(R[-2]C[0]:R[-1]C[0])`
+    ///
+    /// See also:
+    /// * [Model::set_user_input_r1c1]
+    pub fn set_user_input_r1c1(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        value: &str,
+    ) -> Result<(), String> {
+        let value = self.model.r1c1_input_to_a1(sheet, row, column, value)?;
+        self.set_user_input(sheet, row, column, &value)
+    }
+
+    /// Sets a rich text string in a cell
+    ///
+    /// See also:
+    /// * [Model::set_cell_rich_text]
+    pub fn set_cell_rich_text(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        runs: &[TextRun],
+    ) -> Result<(), String> {
+        if !is_valid_column_number(column) {
+            return Err("Invalid column".to_string());
+        }
+        if !is_valid_row(row) {
+            return Err("Invalid row".to_string());
+        }
+        self.check_cell_editable(sheet, row, column)?;
+        let old_value = self
+            .model
+            .workbook
+            .worksheet(sheet)?
+            .cell(row, column)
+            .cloned();
+        self.model.set_cell_rich_text(sheet, row, column, runs)?;
+
+        self.evaluate_if_not_paused();
+
+        self.push_diff_list(vec![Diff::SetCellRichText {
+            sheet,
+            row,
+            column,
+            new_value: runs.to_vec(),
+            old_value: Box::new(old_value),
+        }]);
         Ok(())
     }
 
+    /// Returns the runs of a cell with rich text, or `None` if the cell is not rich text
+    ///
+    /// See also:
+    /// * [Model::get_cell_rich_text]
+    #[inline]
+    pub fn get_cell_rich_text(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<Option<Vec<TextRun>>, String> {
+        self.model.get_cell_rich_text(sheet, row, column)
+    }
+
     /// Returns the content of a cell
     ///
     /// See also:
     /// * [Model::get_cell_content]
-    #[inline]
     pub fn get_cell_content(&self, sheet: u32, row: i32, column: i32) -> Result<String, String> {
+        // The formulas of hidden cells are not shown on protected sheets
+        if self.model.is_cell_formula_hidden(sheet, row, column)?
+            && self
+                .model
+                .workbook
+                .worksheet(sheet)?
+                .cell(row, column)
+                .is_some_and(|cell| cell.has_formula())
+        {
+            return Ok("".to_string());
+        }
         self.model.get_localized_cell_content(sheet, row, column)
     }
 
+    /// Returns the content of a cell with formulas in R1C1 notation
+    ///
+    /// See also:
+    /// * [Model::get_cell_formula_r1c1]
+    pub fn get_cell_content_r1c1(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<String, String> {
+        let content = self.get_cell_content(sheet, row, column)?;
+        if content.is_empty() {
+            return Ok(content);
+        }
+        match self.model.get_cell_formula_r1c1(sheet, row, column)? {
+            Some(formula) => Ok(formula),
+            None => Ok(content),
+        }
+    }
+
     /// Returns the formatted value of a cell
     ///
     /// See also:
@@ -494,9 +758,11 @@ impl<'a> UserModel<'a> {
     /// See also:
     /// * [Model::new_sheet]
     pub fn new_sheet(&mut self) -> Result<(), String> {
+        self.check_workbook_structure()?;
+        let view = self.get_selected_view();
         let (name, index) = self.model.new_sheet();
         self.set_selected_sheet(index)?;
-        self.push_diff_list(vec![Diff::NewSheet { index, name }]);
+        self.push_diff_list_with_view(vec![Diff::NewSheet { index, name }], view);
         Ok(())
     }
 
@@ -505,6 +771,7 @@ impl<'a> UserModel<'a> {
     /// See also:
     /// * [Model::delete_sheet]
     pub fn delete_sheet(&mut self, sheet: u32) -> Result<(), String> {
+        self.check_workbook_structure()?;
         let worksheet = self.model.workbook.worksheet(sheet)?;
 
         self.push_diff_list(vec![Diff::DeleteSheet {
@@ -524,11 +791,46 @@ impl<'a> UserModel<'a> {
         Ok(())
     }
 
+    /// Inserts a copy of the sheet right after it and selects it
+    ///
+    /// See also:
+    /// * [Model::duplicate_sheet]
+    pub fn duplicate_sheet(&mut self, sheet: u32, new_name: &str) -> Result<(), String> {
+        self.check_workbook_structure()?;
+        let view = self.get_selected_view();
+        let index = self.model.duplicate_sheet(sheet, new_name)?;
+        self.set_selected_sheet(index)?;
+        self.push_diff_list_with_view(
+            vec![Diff::DuplicateSheet {
+                sheet,
+                new_name: new_name.to_string(),
+            }],
+            view,
+        );
+        Ok(())
+    }
+
+    /// Moves the sheet at index `from` to index `to`
+    ///
+    /// See also:
+    /// * [Model::move_sheet]
+    pub fn move_sheet(&mut self, from: u32, to: u32) -> Result<(), String> {
+        self.check_workbook_structure()?;
+        if from == to {
+            return Ok(());
+        }
+        let view = self.get_selected_view();
+        self.model.move_sheet(from, to)?;
+        self.push_diff_list_with_view(vec![Diff::MoveSheet { from, to }], view);
+        Ok(())
+    }
+
     /// Renames a sheet by index
     ///
     /// See also:
     /// * [Model::rename_sheet_by_index]
     pub fn rename_sheet(&mut self, sheet: u32, new_name: &str) -> Result<(), String> {
+        self.check_workbook_structure()?;
         let old_value = self.model.workbook.worksheet(sheet)?.name.clone();
         if old_value == new_name {
             return Ok(());
@@ -548,6 +850,7 @@ impl<'a> UserModel<'a> {
     /// * [Model::set_sheet_state]
     /// * [UserModel::unhide_sheet]
     pub fn hide_sheet(&mut self, sheet: u32) -> Result<(), String> {
+        self.check_workbook_structure()?;
         let sheet_count = self.model.workbook.worksheets.len() as u32;
         for index in 1..sheet_count {
             let sheet_index = (sheet + index) % sheet_count;
@@ -574,6 +877,7 @@ impl<'a> UserModel<'a> {
     /// * [Model::set_sheet_state]
     /// * [UserModel::hide_sheet]
     pub fn unhide_sheet(&mut self, sheet: u32) -> Result<(), String> {
+        self.check_workbook_structure()?;
         let old_value = self.model.workbook.worksheet(sheet)?.state.clone();
         self.push_diff_list(vec![Diff::SetSheetState {
             index: sheet,
@@ -592,6 +896,7 @@ impl<'a> UserModel<'a> {
     /// * [Model::set_sheet_color]
     /// * [UserModel::get_worksheets_properties]
     pub fn set_sheet_color(&mut self, sheet: u32, color: &str) -> Result<(), String> {
+        self.check_workbook_structure()?;
         let old_value = match &self.model.workbook.worksheet(sheet)?.color {
             Some(c) => c.clone(),
             None => "".to_string(),
@@ -610,6 +915,8 @@ impl<'a> UserModel<'a> {
     /// See also:
     /// * [Model::cell_clear_all]
     pub fn range_clear_all(&mut self, range: &Area) -> Result<(), String> {
+        self.check_area_editable(range)?;
+        self.check_sheet_allows(range.sheet, |p| p.format_cells)?;
         let sheet = range.sheet;
         // TODO: full rows/columns
         let mut diff_list = Vec::new();
@@ -642,6 +949,7 @@ impl<'a> UserModel<'a> {
     /// See also:
     /// * [Model::cell_clear_contents]
     pub fn range_clear_contents(&mut self, range: &Area) -> Result<(), String> {
+        self.check_area_editable(range)?;
         let sheet = range.sheet;
         let mut diff_list = Vec::new();
         // TODO: full rows/columns
@@ -815,6 +1123,7 @@ impl<'a> UserModel<'a> {
     /// * [UserModel::range_clear_all]
     /// * [UserModel::range_clear_contents]
     pub fn range_clear_formatting(&mut self, range: &Area) -> Result<(), String> {
+        self.check_sheet_allows(range.sheet, |p| p.format_cells)?;
         let sheet = range.sheet;
         let mut diff_list = Vec::new();
         if range.row == 1 && range.height == LAST_ROW {
@@ -880,6 +1189,7 @@ impl<'a> UserModel<'a> {
     ///
     /// See also [`Model::insert_rows`].
     pub fn insert_rows(&mut self, sheet: u32, row: i32, row_count: i32) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.insert_rows)?;
         self.model.insert_rows(sheet, row, row_count)?;
 
         let diff_list = vec![Diff::InsertRows {
@@ -910,6 +1220,7 @@ impl<'a> UserModel<'a> {
         column: i32,
         column_count: i32,
     ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.insert_columns)?;
         self.model.insert_columns(sheet, column, column_count)?;
 
         let diff_list = vec![Diff::InsertColumns {
@@ -930,26 +1241,9 @@ impl<'a> UserModel<'a> {
     ///
     /// See also [`Model::delete_rows`].
     pub fn delete_rows(&mut self, sheet: u32, row: i32, row_count: i32) -> Result<(), String> {
-        let worksheet = self.model.workbook.worksheet(sheet)?;
-        let mut old_data = Vec::new();
-        // Collect data for all rows to be deleted
-        for r in row..row + row_count {
-            let mut row_data = None;
-            for rd in &worksheet.rows {
-                if rd.r == r {
-                    row_data = Some(rd.clone());
-                    break;
-                }
-            }
-            let data = match worksheet.sheet_data.get(&r) {
-                Some(s) => s.clone(),
-                None => HashMap::new(),
-            };
-            old_data.push(RowData {
-                row: row_data,
-                data,
-            });
-        }
+        self.check_sheet_allows(sheet, |p| p.delete_rows)?;
+        let old_data = self.get_rows_data(sheet, row, row_count)?;
+        let old_merged_cells = self.model.workbook.worksheet(sheet)?.merge_cells.clone();
 
         self.model.delete_rows(sheet, row, row_count)?;
 
@@ -958,6 +1252,7 @@ impl<'a> UserModel<'a> {
             row,
             count: row_count,
             old_data,
+            old_merged_cells,
         }];
         self.push_diff_list(diff_list);
         self.evaluate_if_not_paused();
@@ -977,9 +1272,62 @@ impl<'a> UserModel<'a> {
         column: i32,
         column_count: i32,
     ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.delete_columns)?;
+        let old_data = self.get_columns_data(sheet, column, column_count)?;
+        let old_merged_cells = self.model.workbook.worksheet(sheet)?.merge_cells.clone();
+
+        self.model.delete_columns(sheet, column, column_count)?;
+
+        let diff_list = vec![Diff::DeleteColumns {
+            sheet,
+            column,
+            count: column_count,
+            old_data,
+            old_merged_cells,
+        }];
+        self.push_diff_list(diff_list);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // The data of `row_count` rows starting at `row`, needed to undo deleting them
+    pub(super) fn get_rows_data(
+        &self,
+        sheet: u32,
+        row: i32,
+        row_count: i32,
+    ) -> Result<Vec<RowData>, String> {
         let worksheet = self.model.workbook.worksheet(sheet)?;
-        let mut old_data = Vec::new();
-        // Collect data for all columns to be deleted
+        let mut rows_data = Vec::new();
+        for r in row..row + row_count {
+            let mut row_data = None;
+            for rd in &worksheet.rows {
+                if rd.r == r {
+                    row_data = Some(rd.clone());
+                    break;
+                }
+            }
+            let data = match worksheet.sheet_data.get(&r) {
+                Some(s) => s.clone(),
+                None => HashMap::new(),
+            };
+            rows_data.push(RowData {
+                row: row_data,
+                data,
+            });
+        }
+        Ok(rows_data)
+    }
+
+    // The data of `column_count` columns starting at `column`, needed to undo deleting them
+    pub(super) fn get_columns_data(
+        &self,
+        sheet: u32,
+        column: i32,
+        column_count: i32,
+    ) -> Result<Vec<ColumnData>, String> {
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        let mut columns_data = Vec::new();
         for c in column..column + column_count {
             let mut column_data = None;
             for col in &worksheet.cols {
@@ -987,9 +1335,7 @@ impl<'a> UserModel<'a> {
                     column_data = Some(Col {
                         min: c,
                         max: c,
-                        width: col.width,
-                        custom_width: col.custom_width,
-                        style: col.style,
+                        ..col.clone()
                     });
                     break;
                 }
@@ -1002,23 +1348,12 @@ impl<'a> UserModel<'a> {
                 }
             }
 
-            old_data.push(ColumnData {
+            columns_data.push(ColumnData {
                 column: column_data,
                 data,
             });
         }
-
-        self.model.delete_columns(sheet, column, column_count)?;
-
-        let diff_list = vec![Diff::DeleteColumns {
-            sheet,
-            column,
-            count: column_count,
-            old_data,
-        }];
-        self.push_diff_list(diff_list);
-        self.evaluate_if_not_paused();
-        Ok(())
+        Ok(columns_data)
     }
 
     /// Moves a column horizontally and adjusts formulas
@@ -1028,22 +1363,24 @@ impl<'a> UserModel<'a> {
         column: i32,
         delta: i32,
     ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |_| false)?;
+        self.model.move_column_action(sheet, column, delta)?;
         let diff_list = vec![Diff::MoveColumn {
             sheet,
             column,
             delta,
         }];
         self.push_diff_list(diff_list);
-        self.model.move_column_action(sheet, column, delta)?;
         self.evaluate_if_not_paused();
         Ok(())
     }
 
     /// Moves a row vertically and adjusts formulas
     pub fn move_row_action(&mut self, sheet: u32, row: i32, delta: i32) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |_| false)?;
+        self.model.move_row_action(sheet, row, delta)?;
         let diff_list = vec![Diff::MoveRow { sheet, row, delta }];
         self.push_diff_list(diff_list);
-        self.model.move_row_action(sheet, row, delta)?;
         self.evaluate_if_not_paused();
         Ok(())
     }
@@ -1059,6 +1396,7 @@ impl<'a> UserModel<'a> {
         column_end: i32,
         width: f64,
     ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.format_columns)?;
         let mut diff_list = Vec::new();
         for column in column_start..=column_end {
             let old_value = self.model.get_column_width(sheet, column)?;
@@ -1085,6 +1423,7 @@ impl<'a> UserModel<'a> {
         row_end: i32,
         height: f64,
     ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.format_rows)?;
         let mut diff_list = Vec::new();
         for row in row_start..=row_end {
             let old_value = self.model.get_row_height(sheet, row)?;
@@ -1186,6 +1525,7 @@ impl<'a> UserModel<'a> {
         } else {
             return Ok(());
         };
+        self.check_sheet_allows(sheet, |p| p.format_cells)?;
 
         // If the pasted area is smaller than the selected area we increase it
         let [row_start, column_start, row_end, column_end] = range;
@@ -1221,21 +1561,23 @@ impl<'a> UserModel<'a> {
     }
 
     // Updates the style of a cell, adding the new style to the diff list
-    fn update_single_cell_style(
+    fn update_single_cell_style<F>(
         &mut self,
         sheet: u32,
         row: i32,
         column: i32,
-        style_path: &str,
-        value: &str,
+        update: &F,
         diff_list: &mut Vec<Diff>,
-    ) -> Result<(), String> {
+    ) -> Result<(), String>
+    where
+        F: Fn(&Style) -> Result<Style, String>,
+    {
         // This is the value in the cell itself
         let old_value = self.model.get_cell_style_or_none(sheet, row, column)?;
 
         // This takes into account row or column styles. If none of those are present, it will return the default style
-        let old_style = self.get_cell_style(sheet, row, column)?;
-        let new_style = update_style(&old_style, style_path, value)?;
+        let old_style = self.get_cell_style_with_borders(sheet, row, column)?;
+        let new_style = update(&old_style)?;
         self.model.set_cell_style(sheet, row, column, &new_style)?;
         diff_list.push(Diff::SetCellStyle {
             sheet,
@@ -1256,48 +1598,159 @@ impl<'a> UserModel<'a> {
         style_path: &str,
         value: &str,
     ) -> Result<(), String> {
-        let sheet = range.sheet;
+        self.check_sheet_allows(range.sheet, |p| p.format_cells)?;
         let mut diff_list = Vec::new();
-        if range.row == 1 && range.height == LAST_ROW {
-            // Full columns
-            let styled_rows = &self.model.workbook.worksheet(sheet)?.rows.clone();
-            // We need all the rows in the column to update the style
-            // NB: This is too much, this is all the rows that have values
-            let data_rows: Vec<i32> = self
-                .model
-                .workbook
-                .worksheet(sheet)?
-                .sheet_data
-                .keys()
-                .copied()
-                .collect();
-            for column in range.column..range.column + range.width {
-                // we set the style of the full column
-                let old_style = self.model.get_column_style(sheet, column)?;
-                let style = match old_style.as_ref() {
-                    Some(s) => s,
-                    None => &Style::default(),
-                };
-                let style = update_style(style, style_path, value)?;
-                self.model.set_column_style(sheet, column, &style)?;
-                diff_list.push(Diff::SetColumnStyle {
-                    sheet,
-                    column,
-                    old_value: Box::new(old_style),
-                    new_value: Box::new(style),
-                });
+        self.update_range_style_with(
+            range,
+            &|style| update_style(style, style_path, value),
+            &mut diff_list,
+        )?;
+        self.push_diff_list(diff_list);
+        Ok(())
+    }
 
-                // We need to update the styles in all cells that have a row style
-                for row_s in styled_rows.iter() {
-                    let row = row_s.r;
-                    self.update_single_cell_style(
-                        sheet,
-                        row,
-                        column,
-                        style_path,
-                        value,
-                        &mut diff_list,
-                    )?;
+    /// Applies the named style `style_name` to a range, replacing any direct formatting.
+    /// Built-in styles are added to the workbook the first time they are used.
+    ///
+    /// See also:
+    /// * [UserModel::get_named_styles]
+    /// * [get_builtin_named_styles]
+    pub fn apply_named_style(&mut self, range: &Area, style_name: &str) -> Result<(), String> {
+        self.check_sheet_allows(range.sheet, |p| p.format_cells)?;
+        let mut diff_list = Vec::new();
+        if !self
+            .model
+            .get_named_styles()
+            .iter()
+            .any(|named_style| named_style.name == style_name)
+        {
+            self.model.add_builtin_named_style(style_name)?;
+            let named_style = self.get_named_style(style_name)?;
+            diff_list.push(Diff::CreateNamedStyle {
+                name: style_name.to_string(),
+                builtin_id: named_style.builtin_id,
+                style: Box::new(named_style.style),
+            });
+        }
+        let style = self.get_named_style(style_name)?.style;
+        self.update_range_style_with(range, &|_| Ok(style.clone()), &mut diff_list)?;
+        self.push_diff_list(diff_list);
+        Ok(())
+    }
+
+    /// Returns the named styles of the workbook
+    ///
+    /// See also:
+    /// * [Model::get_named_styles]
+    #[inline]
+    pub fn get_named_styles(&self) -> Vec<NamedStyle> {
+        self.model.get_named_styles()
+    }
+
+    /// Returns the named style `style_name` of the workbook
+    pub fn get_named_style(&self, style_name: &str) -> Result<NamedStyle, String> {
+        self.model
+            .get_named_styles()
+            .into_iter()
+            .find(|named_style| named_style.name == style_name)
+            .ok_or_else(|| format!("Style '{style_name}' not found"))
+    }
+
+    /// Creates a new custom named style
+    ///
+    /// See also:
+    /// * [Model::create_named_style]
+    pub fn create_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
+        self.model.create_named_style(style_name, style)?;
+        let named_style = self.get_named_style(style_name)?;
+        self.push_diff_list(vec![Diff::CreateNamedStyle {
+            name: style_name.to_string(),
+            builtin_id: None,
+            style: Box::new(named_style.style),
+        }]);
+        Ok(())
+    }
+
+    /// Changes a named style. Cells using the style are updated unless they override the
+    /// changed properties.
+    ///
+    /// See also:
+    /// * [Model::update_named_style]
+    pub fn update_named_style(&mut self, style_name: &str, style: &Style) -> Result<(), String> {
+        self.check_named_style_editable(style_name)?;
+        let old_value = self.get_named_style(style_name)?.style;
+        self.model.update_named_style(style_name, style)?;
+        let new_value = self.get_named_style(style_name)?.style;
+        self.push_diff_list(vec![Diff::UpdateNamedStyle {
+            name: style_name.to_string(),
+            old_value: Box::new(old_value),
+            new_value: Box::new(new_value),
+        }]);
+        Ok(())
+    }
+
+    /// Deletes a named style. Cells using it keep their formatting and are linked to the
+    /// Normal style.
+    ///
+    /// See also:
+    /// * [Model::delete_named_style]
+    pub fn delete_named_style(&mut self, style_name: &str) -> Result<(), String> {
+        self.check_named_style_editable(style_name)?;
+        let named_style = self.get_named_style(style_name)?;
+        let linked_styles = self.model.delete_named_style(style_name)?;
+        self.push_diff_list(vec![Diff::DeleteNamedStyle {
+            name: style_name.to_string(),
+            builtin_id: named_style.builtin_id,
+            old_value: Box::new(named_style.style),
+            linked_styles,
+        }]);
+        Ok(())
+    }
+
+    // Updates the style of every cell, row and column in the range with `update`
+    pub(super) fn update_range_style_with<F>(
+        &mut self,
+        range: &Area,
+        update: &F,
+        diff_list: &mut Vec<Diff>,
+    ) -> Result<(), String>
+    where
+        F: Fn(&Style) -> Result<Style, String>,
+    {
+        let sheet = range.sheet;
+        if range.row == 1 && range.height == LAST_ROW {
+            // Full columns
+            let styled_rows = &self.model.workbook.worksheet(sheet)?.rows.clone();
+            // We need all the rows in the column to update the style
+            // NB: This is too much, this is all the rows that have values
+            let data_rows: Vec<i32> = self
+                .model
+                .workbook
+                .worksheet(sheet)?
+                .sheet_data
+                .keys()
+                .copied()
+                .collect();
+            for column in range.column..range.column + range.width {
+                // we set the style of the full column
+                let old_style = self.model.get_column_style(sheet, column)?;
+                let style = match old_style.as_ref() {
+                    Some(s) => s,
+                    None => &Style::default(),
+                };
+                let style = update(style)?;
+                self.model.set_column_style(sheet, column, &style)?;
+                diff_list.push(Diff::SetColumnStyle {
+                    sheet,
+                    column,
+                    old_value: Box::new(old_style),
+                    new_value: Box::new(style),
+                });
+
+                // We need to update the styles in all cells that have a row style
+                for row_s in styled_rows.iter() {
+                    let row = row_s.r;
+                    self.update_single_cell_style(sheet, row, column, update, diff_list)?;
                 }
 
                 // Update style in all cells that have different styles
@@ -1308,14 +1761,7 @@ impl<'a> UserModel<'a> {
                     {
                         if data_row.get(&column).is_some() {
                             // If the cell has non empty content it will always have some style
-                            self.update_single_cell_style(
-                                sheet,
-                                row,
-                                column,
-                                style_path,
-                                value,
-                                &mut diff_list,
-                            )?;
+                            self.update_single_cell_style(sheet, row, column, update, diff_list)?;
                         }
                     }
                 }
@@ -1334,27 +1780,13 @@ impl<'a> UserModel<'a> {
                     .map(|row_data| row_data.keys().copied().collect())
                     .unwrap_or_default();
                 for column in columns {
-                    self.update_single_cell_style(
-                        sheet,
-                        row,
-                        column,
-                        style_path,
-                        value,
-                        &mut diff_list,
-                    )?;
+                    self.update_single_cell_style(sheet, row, column, update, diff_list)?;
                 }
 
                 // We need to go through all the cells that have a column style and merge the styles
                 for col in styled_columns.iter() {
                     for column in col.min..col.max + 1 {
-                        self.update_single_cell_style(
-                            sheet,
-                            row,
-                            column,
-                            style_path,
-                            value,
-                            &mut diff_list,
-                        )?;
+                        self.update_single_cell_style(sheet, row, column, update, diff_list)?;
                     }
                 }
 
@@ -1364,7 +1796,7 @@ impl<'a> UserModel<'a> {
                     Some(s) => s,
                     None => &Style::default(),
                 };
-                let style = update_style(style, style_path, value)?;
+                let style = update(style)?;
                 self.model.set_row_style(sheet, row, &style)?;
                 diff_list.push(Diff::SetRowStyle {
                     sheet,
@@ -1376,18 +1808,10 @@ impl<'a> UserModel<'a> {
         } else {
             for row in range.row..range.row + range.height {
                 for column in range.column..range.column + range.width {
-                    self.update_single_cell_style(
-                        sheet,
-                        row,
-                        column,
-                        style_path,
-                        value,
-                        &mut diff_list,
-                    )?;
+                    self.update_single_cell_style(sheet, row, column, update, diff_list)?;
                 }
             }
         }
-        self.push_diff_list(diff_list);
         Ok(())
     }
 
@@ -1397,9 +1821,25 @@ impl<'a> UserModel<'a> {
     /// In the object structure the borders of the cells might be difference,
     /// We always pick the "heaviest" border.
     ///
+    /// Colours are returned as RGB values in the theme of the workbook.
+    ///
     /// See also:
     /// * [Model::get_style_for_cell]
     pub fn get_cell_style(&self, sheet: u32, row: i32, column: i32) -> Result<Style, String> {
+        let mut style = self.get_cell_style_with_borders(sheet, row, column)?;
+        style.resolve_colors(self.model.get_theme());
+        Ok(style)
+    }
+
+    // The style of the cell with the borders it shares with its neighbours. Colours are kept as
+    // they are defined in the workbook.
+    fn get_cell_style_with_borders(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<Style, String> {
+        let theme = self.model.get_theme();
         let mut style = self.model.get_style_for_cell(sheet, row, column)?;
 
         // We need to check if the adjacent cells have a "heavier" border
@@ -1439,19 +1879,19 @@ impl<'a> UserModel<'a> {
             None
         };
 
-        if is_max_border(style.border.top.as_ref(), border_top.as_ref()) {
+        if is_max_border(style.border.top.as_ref(), border_top.as_ref(), theme) {
             style.border.top = border_top;
         }
 
-        if is_max_border(style.border.right.as_ref(), border_right.as_ref()) {
+        if is_max_border(style.border.right.as_ref(), border_right.as_ref(), theme) {
             style.border.right = border_right;
         }
 
-        if is_max_border(style.border.bottom.as_ref(), border_bottom.as_ref()) {
+        if is_max_border(style.border.bottom.as_ref(), border_bottom.as_ref(), theme) {
             style.border.bottom = border_bottom;
         }
 
-        if is_max_border(style.border.left.as_ref(), border_left.as_ref()) {
+        if is_max_border(style.border.left.as_ref(), border_left.as_ref(), theme) {
             style.border.left = border_left;
         }
 
@@ -1461,6 +1901,19 @@ impl<'a> UserModel<'a> {
     /// Fills the cells from `source_area` until `to_row`.
     /// This simulates the user clicking on the cell outline handle and dragging it downwards (or upwards)
     pub fn auto_fill_rows(&mut self, source_area: &Area, to_row: i32) -> Result<(), String> {
+        self.auto_fill_rows_with_mode(source_area, to_row, AutoFillMode::Auto)
+    }
+
+    /// Fills the cells from `source_area` until `to_row` extending them as given by `mode`
+    ///
+    /// See also:
+    /// * [UserModel::auto_fill_rows]
+    pub fn auto_fill_rows_with_mode(
+        &mut self,
+        source_area: &Area,
+        to_row: i32,
+        mode: AutoFillMode,
+    ) -> Result<(), String> {
         let mut diff_list = Vec::new();
         let sheet = source_area.sheet;
         let row1 = source_area.row;
@@ -1490,6 +1943,16 @@ impl<'a> UserModel<'a> {
             return Err(format!("Invalid row: '{to_row}'"));
         }
 
+        let first_row = row1.min(to_row);
+        let last_row = (row1 + height1 - 1).max(to_row);
+        self.check_area_editable(&Area {
+            sheet,
+            row: first_row,
+            column: column1,
+            width: width1,
+            height: last_row - first_row + 1,
+        })?;
+
         // anchor_row is the first row that repeats in each case.
         let anchor_row;
         let sign;
@@ -1512,11 +1975,8 @@ impl<'a> UserModel<'a> {
 
         for column in column1..column1 + width1 {
             let mut index = 0;
-            let locale = &self.model.locale;
-            let values = (row1..height1 + row1)
-                .map(|row| self.get_cell_content(sheet, row, column))
-                .collect::<Result<Vec<_>, _>>()?;
-            let possible_progression = detect_progression(&values, locale);
+            let cells: Vec<(i32, i32)> = (row1..height1 + row1).map(|row| (row, column)).collect();
+            let possible_progression = self.detect_fill_series(sheet, &cells, sign == -1, mode)?;
             for (range_idx, row_ref) in row_range.iter().enumerate() {
                 // Save value and style first
                 let row = *row_ref;
@@ -1574,6 +2034,19 @@ impl<'a> UserModel<'a> {
     /// Fills the cells from `source_area` until `to_column`.
     /// This simulates the user clicking on the cell outline handle and dragging it to the right (or to the left)
     pub fn auto_fill_columns(&mut self, source_area: &Area, to_column: i32) -> Result<(), String> {
+        self.auto_fill_columns_with_mode(source_area, to_column, AutoFillMode::Auto)
+    }
+
+    /// Fills the cells from `source_area` until `to_column` extending them as given by `mode`
+    ///
+    /// See also:
+    /// * [UserModel::auto_fill_columns]
+    pub fn auto_fill_columns_with_mode(
+        &mut self,
+        source_area: &Area,
+        to_column: i32,
+        mode: AutoFillMode,
+    ) -> Result<(), String> {
         let mut diff_list = Vec::new();
         let sheet = source_area.sheet;
         let first_row = source_area.row;
@@ -1603,6 +2076,16 @@ impl<'a> UserModel<'a> {
             return Err(format!("Invalid row: '{to_column}'"));
         }
 
+        let area_first_column = first_column.min(to_column);
+        let area_last_column = last_column.max(to_column);
+        self.check_area_editable(&Area {
+            sheet,
+            row: first_row,
+            column: area_first_column,
+            width: area_last_column - area_first_column + 1,
+            height: last_row - first_row + 1,
+        })?;
+
         // anchor_column is the first column that repeats in each case.
         let anchor_column;
         let sign;
@@ -1625,7 +2108,11 @@ impl<'a> UserModel<'a> {
 
         for row in first_row..=last_row {
             let mut index = 0;
-            for column_ref in &column_range {
+            let cells: Vec<(i32, i32)> = (first_column..=last_column)
+                .map(|column| (row, column))
+                .collect();
+            let possible_progression = self.detect_fill_series(sheet, &cells, sign == -1, mode)?;
+            for (range_idx, column_ref) in column_range.iter().enumerate() {
                 let column = *column_ref;
                 // Save value and style first
                 let old_value = self
@@ -1638,9 +2125,12 @@ impl<'a> UserModel<'a> {
 
                 // compute the new value and set it
                 let source_column = anchor_column + index;
-                let target_value = self
-                    .model
-                    .extend_to(sheet, row, source_column, row, column)?;
+                let target_value = match possible_progression {
+                    Some(ref detected_progression) => detected_progression.next(range_idx),
+                    None => self
+                        .model
+                        .extend_to(sheet, row, source_column, row, column)?,
+                };
                 self.model
                     .set_user_input(sheet, row, column, target_value.to_string())?;
 
@@ -1674,6 +2164,87 @@ impl<'a> UserModel<'a> {
         Ok(())
     }
 
+    // The series that extends the cells or `None` if they are copied.
+    // If `backwards` the cells are extended up or left, from the first one.
+    fn detect_fill_series(
+        &self,
+        sheet: u32,
+        cells: &[(i32, i32)],
+        backwards: bool,
+        mode: AutoFillMode,
+    ) -> Result<Option<Progression>, String> {
+        let mut cells = cells.to_vec();
+        if backwards {
+            cells.reverse();
+        }
+        let locale = self.model.locale;
+        let decimal_sep = locale.numbers.symbols.decimal.chars().next().unwrap_or('.');
+        let date_system = self.model.get_date_system();
+        // The values of the cells if they are all numbers (not formulas)
+        let mut numbers = Some(Vec::new());
+        let mut all_dates = true;
+        let mut all_times = true;
+        for &(row, column) in &cells {
+            match self.model.workbook.worksheet(sheet)?.cell(row, column) {
+                Some(Cell::NumberCell { v, .. }) => {
+                    if let Some(numbers) = numbers.as_mut() {
+                        numbers.push(*v);
+                    }
+                    let num_fmt = &self.model.get_style_for_cell(sheet, row, column)?.num_fmt;
+                    all_dates = all_dates && is_likely_date_number_format(num_fmt);
+                    all_times = all_times && is_time_number_format(num_fmt);
+                }
+                _ => numbers = None,
+            }
+        }
+        let date_unit = match mode {
+            AutoFillMode::Days => Some(DateUnit::Day),
+            AutoFillMode::Weekdays => Some(DateUnit::Weekday),
+            AutoFillMode::Months => Some(DateUnit::Month),
+            AutoFillMode::Years => Some(DateUnit::Year),
+            _ => None,
+        };
+        let progression = match mode {
+            AutoFillMode::Copy => None,
+            AutoFillMode::Days
+            | AutoFillMode::Weekdays
+            | AutoFillMode::Months
+            | AutoFillMode::Years => numbers.and_then(|numbers| {
+                detect_date_progression(&numbers, date_unit, date_system, decimal_sep)
+            }),
+            AutoFillMode::LinearTrend | AutoFillMode::GrowthTrend => numbers.and_then(|numbers| {
+                detect_trend(&numbers, mode == AutoFillMode::GrowthTrend, decimal_sep)
+            }),
+            AutoFillMode::Auto => {
+                let date_progression = match numbers {
+                    Some(numbers) if all_times => detect_time_progression(&numbers, decimal_sep),
+                    Some(numbers) if all_dates => {
+                        detect_date_progression(&numbers, None, date_system, decimal_sep)
+                    }
+                    _ => None,
+                };
+                match date_progression {
+                    Some(progression) => Some(progression),
+                    None => {
+                        let values = cells
+                            .iter()
+                            .map(|&(row, column)| self.get_cell_content(sheet, row, column))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        detect_progression(&values, locale, self.model.get_custom_lists())
+                    }
+                }
+            }
+        };
+        // A single value has no direction of its own
+        match progression {
+            Some(mut progression) if backwards && cells.len() == 1 => {
+                progression.reverse();
+                Ok(Some(progression))
+            }
+            progression => Ok(progression),
+        }
+    }
+
     /// Returns information about the sheets
     ///
     /// See also:
@@ -1701,6 +2272,142 @@ impl<'a> UserModel<'a> {
         Ok(self.model.workbook.worksheet(sheet)?.show_grid_lines)
     }
 
+    /// Returns the AutoFilter of the sheet if any
+    pub fn get_auto_filter(&self, sheet: u32) -> Result<Option<AutoFilter>, String> {
+        Ok(self.model.workbook.worksheet(sheet)?.auto_filter.clone())
+    }
+
+    /// Adds an AutoFilter to the sheet on the `area`. The first row of the area is the header.
+    /// Replaces any existing AutoFilter in the sheet, showing the rows it had filtered.
+    pub fn set_auto_filter(&mut self, area: &Area) -> Result<(), String> {
+        self.check_sheet_allows(area.sheet, |_| false)?;
+        if area.height < 1 || area.width < 1 {
+            return Err("Invalid filter range".to_string());
+        }
+        let first_column = number_to_column(area.column).ok_or("Invalid column")?;
+        let last_column = number_to_column(area.column + area.width - 1).ok_or("Invalid column")?;
+        let last_row = area.row + area.height - 1;
+        if !is_valid_row(area.row) || !is_valid_row(last_row) {
+            return Err("Invalid row".to_string());
+        }
+        let auto_filter = AutoFilter {
+            reference: format!("{first_column}{}:{last_column}{last_row}", area.row),
+            filter_columns: vec![],
+        };
+        self.update_auto_filter(area.sheet, Some(auto_filter))
+    }
+
+    /// Removes the AutoFilter of the sheet showing all the filtered rows
+    pub fn delete_auto_filter(&mut self, sheet: u32) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |_| false)?;
+        if self.model.workbook.worksheet(sheet)?.auto_filter.is_none() {
+            return Ok(());
+        }
+        self.update_auto_filter(sheet, None)
+    }
+
+    /// Sets the criteria of the column `col_id` (relative to the first column of the filter)
+    /// of the sheet AutoFilter and applies the filter.
+    pub fn set_auto_filter_column(
+        &mut self,
+        sheet: u32,
+        col_id: u32,
+        filter: ColumnFilter,
+    ) -> Result<(), String> {
+        let auto_filter = match &self.model.workbook.worksheet(sheet)?.auto_filter {
+            Some(auto_filter) => auto_filter.clone(),
+            None => return Err("The sheet has no AutoFilter".to_string()),
+        };
+        let auto_filter = set_filter_column(auto_filter, col_id, Some(filter))?;
+        self.update_auto_filter(sheet, Some(auto_filter))
+    }
+
+    /// Removes the criteria of the column `col_id` of the sheet AutoFilter
+    pub fn clear_auto_filter_column(&mut self, sheet: u32, col_id: u32) -> Result<(), String> {
+        let auto_filter = match &self.model.workbook.worksheet(sheet)?.auto_filter {
+            Some(auto_filter) => auto_filter.clone(),
+            None => return Err("The sheet has no AutoFilter".to_string()),
+        };
+        let auto_filter = set_filter_column(auto_filter, col_id, None)?;
+        self.update_auto_filter(sheet, Some(auto_filter))
+    }
+
+    /// Applies again the criteria of the sheet AutoFilter.
+    /// Filters are not dynamic, so this is needed after the values in the range change.
+    pub fn reapply_auto_filter(&mut self, sheet: u32) -> Result<(), String> {
+        let auto_filter = self.model.workbook.worksheet(sheet)?.auto_filter.clone();
+        if auto_filter.is_none() {
+            return Err("The sheet has no AutoFilter".to_string());
+        }
+        self.update_auto_filter(sheet, auto_filter)
+    }
+
+    /// Returns the AutoFilter of the table if any
+    pub fn get_table_auto_filter(&self, table_name: &str) -> Result<Option<AutoFilter>, String> {
+        match self.model.workbook.tables.get(table_name) {
+            Some(table) => Ok(table.auto_filter.clone()),
+            None => Err(format!("Table not found: '{table_name}'")),
+        }
+    }
+
+    /// Sets the criteria of the column `col_id` of the table and applies the filter
+    pub fn set_table_filter_column(
+        &mut self,
+        table_name: &str,
+        col_id: u32,
+        filter: ColumnFilter,
+    ) -> Result<(), String> {
+        let auto_filter = self.get_table_auto_filter_or_default(table_name)?;
+        let auto_filter = set_filter_column(auto_filter, col_id, Some(filter))?;
+        self.update_table_auto_filter(table_name, Some(auto_filter))
+    }
+
+    /// Removes the criteria of the column `col_id` of the table
+    pub fn clear_table_filter_column(
+        &mut self,
+        table_name: &str,
+        col_id: u32,
+    ) -> Result<(), String> {
+        let auto_filter = self.get_table_auto_filter_or_default(table_name)?;
+        let auto_filter = set_filter_column(auto_filter, col_id, None)?;
+        self.update_table_auto_filter(table_name, Some(auto_filter))
+    }
+
+    /// Sorts the rows of the `area` by the `keys`, moving the styles with the values.
+    ///
+    /// See also:
+    /// * [Model::sort_range]
+    pub fn sort_range(&mut self, area: &Area, keys: &[SortKey]) -> Result<(), String> {
+        self.check_sheet_allows(area.sheet, |p| p.sort)?;
+        self.check_area_editable(area)?;
+        let old_data = self.get_area_cells(area)?;
+        self.model.sort_range(area, keys)?;
+        self.push_diff_list(vec![Diff::SortRange {
+            sheet: area.sheet,
+            row: area.row,
+            column: area.column,
+            width: area.width,
+            height: area.height,
+            keys: keys.to_vec(),
+            old_data,
+        }]);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // The cells of the area row by row
+    pub(super) fn get_area_cells(&self, area: &Area) -> Result<Vec<Vec<Option<Cell>>>, String> {
+        let worksheet = self.model.workbook.worksheet(area.sheet)?;
+        let mut cells = Vec::new();
+        for row in area.row..area.row + area.height {
+            let row_cells = (area.column..area.column + area.width)
+                .map(|column| worksheet.cell(row, column).cloned())
+                .collect();
+            cells.push(row_cells);
+        }
+        Ok(cells)
+    }
+
     /// Returns the largest column in the row less than a column whose cell has a non empty value.
     /// If there are none it returns `None`.
     /// This is useful when rendering a part of a worksheet to know which cells spill over
@@ -1799,14 +2506,26 @@ impl<'a> UserModel<'a> {
         )
         .map_err(|e| format!("Error converting from utf8: '{e}'"))?;
 
+        let range = (row_start, column_start, row_end, column_end);
         Ok(Clipboard {
             csv: csv.trim().to_string(),
             data,
             sheet,
-            range: (row_start, column_start, row_end, column_end),
+            range,
         })
     }
 
+    /// Returns the selected area as an html table, to be used as the `text/html` clipboard item
+    pub fn copy_to_html(&self) -> Result<String, String> {
+        let selected_area = self.get_selected_view();
+        let sheet = selected_area.sheet;
+        let [row_start, column_start, row_end, column_end] = selected_area.range;
+        let dimension = self.model.workbook.worksheet(sheet)?.dimension();
+        let row_end = row_end.min(dimension.max_row);
+        let column_end = column_end.min(dimension.max_column);
+        self.range_to_html(sheet, (row_start, column_start, row_end, column_end))
+    }
+
     /// Paste text that we copied
     pub fn paste_from_clipboard(
         &mut self,
@@ -1816,6 +2535,31 @@ impl<'a> UserModel<'a> {
         is_cut: bool,
     ) -> Result<(), String> {
         let mut diff_list = Vec::new();
+        let (max_row, max_column) = self.paste_clipboard_data(
+            source_sheet,
+            source_range,
+            clipboard,
+            is_cut,
+            &mut diff_list,
+        )?;
+        self.push_diff_list(diff_list);
+        // select the pasted area
+        let [selected_row, selected_column, _, _] = self.get_selected_view().range;
+        self.set_selected_range(selected_row, selected_column, max_row, max_column)?;
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // Pastes the clipboard at the selected cell adding the changes to the diff list.
+    // Returns the last row and column of the pasted area.
+    pub(crate) fn paste_clipboard_data(
+        &mut self,
+        source_sheet: u32,
+        source_range: ClipboardTuple,
+        clipboard: &ClipboardData,
+        is_cut: bool,
+        diff_list: &mut DiffList,
+    ) -> Result<(i32, i32), String> {
         let view = self.get_selected_view();
         let (source_first_row, source_first_column, source_last_row, source_last_column) =
             source_range;
@@ -1830,6 +2574,17 @@ impl<'a> UserModel<'a> {
             width: source_last_column - source_first_column + 1,
             height: source_last_row - source_first_row + 1,
         };
+        self.check_area_editable(&Area {
+            row: selected_row,
+            column: selected_column,
+            ..*area
+        })?;
+        if is_cut {
+            self.check_area_editable(&Area {
+                sheet: source_sheet,
+                ..*area
+            })?;
+        }
         for (source_row, data_row) in clipboard {
             let delta_row = source_row - source_first_row;
             let target_row = selected_row + delta_row;
@@ -1915,11 +2670,7 @@ impl<'a> UserModel<'a> {
                 }
             }
         }
-        self.push_diff_list(diff_list);
-        // select the pasted area
-        self.set_selected_range(selected_row, selected_column, max_row, max_column)?;
-        self.evaluate_if_not_paused();
-        Ok(())
+        Ok((max_row, max_column))
     }
 
     /// Paste a csv-string into the model
@@ -1934,36 +2685,37 @@ impl<'a> UserModel<'a> {
             .delimiter(b'\t')
             .has_headers(false)
             .from_reader(csv_reader);
-        for record in reader.records() {
-            match record {
-                Ok(r) => {
-                    column = area.column;
-                    for value in &r {
-                        let old_value = self
-                            .model
-                            .workbook
-                            .worksheet(sheet)?
-                            .cell(row, column)
-                            .cloned();
-                        // let old_style = self.model.get_style_for_cell(sheet, row, column)?;
-                        self.model
-                            .set_user_input(sheet, row, column, value.to_string())?;
+        // Records that can't be read are skipped
+        let records: Vec<StringRecord> = reader.records().filter_map(Result::ok).collect();
+        self.check_area_editable(&Area {
+            sheet,
+            row: area.row,
+            column: area.column,
+            width: records.iter().map(|r| r.len()).max().unwrap_or(0) as i32,
+            height: records.len() as i32,
+        })?;
+        for r in records {
+            column = area.column;
+            for value in &r {
+                let old_value = self
+                    .model
+                    .workbook
+                    .worksheet(sheet)?
+                    .cell(row, column)
+                    .cloned();
+                // let old_style = self.model.get_style_for_cell(sheet, row, column)?;
+                self.model
+                    .set_user_input(sheet, row, column, value.to_string())?;
 
-                        diff_list.push(Diff::SetCellValue {
-                            sheet,
-                            row,
-                            column,
-                            new_value: value.to_string(),
-                            old_value: Box::new(old_value),
-                        });
-                        column += 1;
-                    }
-                }
-                Err(_) => {
-                    // skip
-                    continue;
-                }
-            };
+                diff_list.push(Diff::SetCellValue {
+                    sheet,
+                    row,
+                    column,
+                    new_value: value.to_string(),
+                    old_value: Box::new(old_value),
+                });
+                column += 1;
+            }
             row += 1;
         }
         self.push_diff_list(diff_list);
@@ -2065,7 +2817,10 @@ impl<'a> UserModel<'a> {
             new_value: locale.to_string(),
         }];
         self.push_diff_list(diff_list);
-        self.model.set_locale(locale)
+        self.model.set_locale(locale)?;
+        // FORMULATEXT depends on the locale
+        self.evaluate_if_not_paused();
+        Ok(())
     }
 
     /// Gets the timezone of the model
@@ -2073,43 +2828,298 @@ impl<'a> UserModel<'a> {
         self.model.get_timezone()
     }
 
-    /// Gets the locale of the model
-    pub fn get_locale(&self) -> String {
-        self.model.get_locale()
-    }
-
-    /// Get the language for the model
-    pub fn get_language(&self) -> String {
-        self.model.get_language()
-    }
-
-    /// Sets the language for the model
-    pub fn set_language(&mut self, language: &str) -> Result<(), String> {
-        self.model.set_language(language)
+    /// Sets the date system of the workbook, shifting the dates stored in cells
+    /// so that they keep showing the same date.
+    ///
+    /// See also:
+    /// * [Model::set_date_system]
+    pub fn set_date_system(&mut self, date_system: DateSystem) -> Result<(), String> {
+        let old_value = self.get_date_system();
+        if old_value == date_system {
+            return Ok(());
+        }
+        let old_data = self.get_date_cells_data()?;
+        self.model.set_date_system(date_system)?;
+        self.push_diff_list(vec![Diff::SetDateSystem {
+            old_value,
+            new_value: date_system,
+            old_data,
+        }]);
+        Ok(())
     }
 
-    /// Gets the formatting settings for the model
-    pub fn get_fmt_settings(&self) -> FmtSettings {
-        self.model.get_fmt_settings()
+    /// Gets the date system of the workbook
+    pub fn get_date_system(&self) -> DateSystem {
+        self.model.get_date_system()
     }
 
-    // **** Private methods ****** //
+    // The cells that change if the date system is set
+    pub(crate) fn get_date_cells_data(&self) -> Result<Vec<CellData>, String> {
+        let mut old_data = Vec::new();
+        for CellReferenceIndex { sheet, row, column } in self.model.get_date_cells()? {
+            old_data.push(CellData {
+                sheet,
+                row,
+                column,
+                cell: self
+                    .model
+                    .workbook
+                    .worksheet(sheet)?
+                    .cell(row, column)
+                    .cloned(),
+            });
+        }
+        Ok(old_data)
+    }
+
+    /// Sets the user defined lists that autofill extends
+    ///
+    /// See also:
+    /// * [Model::set_custom_lists]
+    pub fn set_custom_lists(&mut self, lists: Vec<Vec<String>>) -> Result<(), String> {
+        let old_value = self.model.get_custom_lists().clone();
+        self.model.set_custom_lists(lists.clone())?;
+        self.push_diff_list(vec![Diff::SetCustomLists {
+            old_value,
+            new_value: lists,
+        }]);
+        Ok(())
+    }
+
+    /// Gets the user defined lists that autofill extends
+    pub fn get_custom_lists(&self) -> Vec<Vec<String>> {
+        self.model.get_custom_lists().clone()
+    }
+
+    /// Sets the theme of the workbook. Colors linked to the theme are updated.
+    ///
+    /// See also:
+    /// * [Model::set_theme]
+    pub fn set_theme(&mut self, theme: Theme) -> Result<(), String> {
+        let old_value = Box::new(self.model.get_theme().clone());
+        self.model.set_theme(theme.clone())?;
+        self.push_diff_list(vec![Diff::SetTheme {
+            old_value,
+            new_value: Box::new(theme),
+        }]);
+        Ok(())
+    }
+
+    /// Gets the theme of the workbook
+    pub fn get_theme(&self) -> &Theme {
+        self.model.get_theme()
+    }
+
+    /// Gets the locale of the model
+    pub fn get_locale(&self) -> String {
+        self.model.get_locale()
+    }
+
+    /// Get the language for the model
+    pub fn get_language(&self) -> String {
+        self.model.get_language()
+    }
+
+    /// Sets the language for the model
+    pub fn set_language(&mut self, language: &str) -> Result<(), String> {
+        self.model.set_language(language)?;
+        // FORMULATEXT depends on the language
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    /// Gets the formatting settings for the model
+    pub fn get_fmt_settings(&self) -> FmtSettings {
+        self.model.get_fmt_settings()
+    }
+
+    // **** Private methods ****** //
+
+    // Queues the diffs to be sent to other models. In a collaborative session `revert` are the
+    // changes that revert them, if they are not just their inverse.
+    fn push_send_queue(&mut self, queue_diffs: QueueDiffs, revert: Option<Vec<QueueDiffs>>) {
+        if self.collaboration.is_some() {
+            self.push_local_change(queue_diffs, revert);
+        } else {
+            self.send_queue.push(queue_diffs);
+        }
+    }
 
+    // Saves the change with the current view, the action must not have changed the selection yet
     pub(crate) fn push_diff_list(&mut self, diff_list: DiffList) {
-        self.send_queue.push(QueueDiffs {
-            r#type: DiffType::Redo,
-            list: diff_list.clone(),
+        let view = self.get_selected_view();
+        self.push_diff_list_with_view(diff_list, view);
+    }
+
+    // Saves the change with the view from before the action, for actions that change the selection
+    pub(crate) fn push_diff_list_with_view(&mut self, diff_list: DiffList, view: SelectedView) {
+        self.push_send_queue(
+            QueueDiffs {
+                r#type: DiffType::Redo,
+                list: diff_list.clone(),
+            },
+            None,
+        );
+        self.history.push(HistoryEntry {
+            diff_list,
+            view,
+            redo_view: self.get_selected_view(),
+            typing_time: None,
         });
-        self.history.push(diff_list);
     }
 
-    fn evaluate_if_not_paused(&mut self) {
+    // Like push_diff_list, but the change might be grouped with the previous cell inputs
+    fn push_typing_diff_list(&mut self, diff_list: DiffList) {
+        let view = self.get_selected_view();
+        self.push_send_queue(
+            QueueDiffs {
+                r#type: DiffType::Redo,
+                list: diff_list.clone(),
+            },
+            None,
+        );
+        let entry = HistoryEntry {
+            diff_list,
+            redo_view: view.clone(),
+            view,
+            typing_time: None,
+        };
+        if self.history.typing_interval > 0 {
+            self.history.push_typing(HistoryEntry {
+                typing_time: Some(get_milliseconds_since_epoch()),
+                ..entry
+            });
+        } else {
+            self.history.push(entry);
+        }
+    }
+
+    pub(crate) fn evaluate_if_not_paused(&mut self) {
         if !self.pause_evaluation {
             self.model.evaluate();
         }
     }
 
-    fn apply_undo_diff_list(&mut self, diff_list: &DiffList) -> Result<(), String> {
+    // Hides the rows that do not pass the `new_value` filter and shows the rest.
+    // Rows in the range of the `old_value` filter are shown.
+    fn update_filtered_rows(
+        &mut self,
+        sheet: u32,
+        old_value: Option<&AutoFilter>,
+        new_value: Option<&AutoFilter>,
+        diff_list: &mut DiffList,
+    ) -> Result<(), String> {
+        let mut rows = Vec::new();
+        for auto_filter in [old_value, new_value].into_iter().flatten() {
+            let (_, first_row, _, last_row) = parse_range(&auto_filter.reference)?;
+            rows.extend(first_row + 1..=last_row);
+        }
+        rows.sort_unstable();
+        rows.dedup();
+        let hidden_rows = match new_value {
+            Some(auto_filter) => self.model.get_auto_filter_hidden_rows(sheet, auto_filter)?,
+            None => vec![],
+        };
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        for row in rows {
+            let old_value = worksheet.is_row_hidden(row)?;
+            let new_value = hidden_rows.binary_search(&row).is_ok();
+            if old_value != new_value {
+                worksheet.set_row_hidden(row, new_value)?;
+                diff_list.push(Diff::SetRowHidden {
+                    sheet,
+                    row,
+                    new_value,
+                    old_value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn update_auto_filter(
+        &mut self,
+        sheet: u32,
+        new_value: Option<AutoFilter>,
+    ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.auto_filter)?;
+        let old_value = self.model.workbook.worksheet(sheet)?.auto_filter.clone();
+        let mut diff_list = Vec::new();
+        self.update_filtered_rows(
+            sheet,
+            old_value.as_ref(),
+            new_value.as_ref(),
+            &mut diff_list,
+        )?;
+        self.model.workbook.worksheet_mut(sheet)?.auto_filter = new_value.clone();
+        diff_list.push(Diff::SetAutoFilter {
+            sheet,
+            new_value: Box::new(new_value),
+            old_value: Box::new(old_value),
+        });
+        self.push_diff_list(diff_list);
+        // SUBTOTAL and AGGREGATE depend on the hidden rows
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // The table AutoFilter covers the table without the totals row
+    fn get_table_auto_filter_or_default(&self, table_name: &str) -> Result<AutoFilter, String> {
+        let table = match self.model.workbook.tables.get(table_name) {
+            Some(table) => table,
+            None => return Err(format!("Table not found: '{table_name}'")),
+        };
+        if let Some(auto_filter) = &table.auto_filter {
+            return Ok(auto_filter.clone());
+        }
+        if table.header_row_count == 0 {
+            return Err("Tables without a header row can not be filtered".to_string());
+        }
+        let (first_column, first_row, last_column, last_row) = parse_range(&table.reference)?;
+        let last_row = last_row - table.totals_row_count as i32;
+        let first_column = number_to_column(first_column).ok_or("Invalid column")?;
+        let last_column = number_to_column(last_column).ok_or("Invalid column")?;
+        Ok(AutoFilter {
+            reference: format!("{first_column}{first_row}:{last_column}{last_row}"),
+            filter_columns: vec![],
+        })
+    }
+
+    fn update_table_auto_filter(
+        &mut self,
+        table_name: &str,
+        new_value: Option<AutoFilter>,
+    ) -> Result<(), String> {
+        let (sheet_name, old_value) = match self.model.workbook.tables.get(table_name) {
+            Some(table) => (table.sheet_name.clone(), table.auto_filter.clone()),
+            None => return Err(format!("Table not found: '{table_name}'")),
+        };
+        let sheet = self
+            .model
+            .get_sheet_index_by_name(&sheet_name)
+            .ok_or_else(|| format!("Sheet not found: '{sheet_name}'"))?;
+        self.check_sheet_allows(sheet, |p| p.auto_filter)?;
+        let mut diff_list = Vec::new();
+        self.update_filtered_rows(
+            sheet,
+            old_value.as_ref(),
+            new_value.as_ref(),
+            &mut diff_list,
+        )?;
+        self.model
+            .set_table_auto_filter(table_name, new_value.clone())?;
+        diff_list.push(Diff::SetTableAutoFilter {
+            table: table_name.to_string(),
+            new_value: Box::new(new_value),
+            old_value: Box::new(old_value),
+        });
+        self.push_diff_list(diff_list);
+        // SUBTOTAL and AGGREGATE depend on the hidden rows
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    pub(super) fn apply_undo_diff_list(&mut self, diff_list: &DiffList) -> Result<(), String> {
         let mut needs_evaluation = false;
         for diff in diff_list.iter().rev() {
             match diff {
@@ -2119,6 +3129,13 @@ impl<'a> UserModel<'a> {
                     column,
                     new_value: _,
                     old_value,
+                }
+                | Diff::SetCellRichText {
+                    sheet,
+                    row,
+                    column,
+                    new_value: _,
+                    old_value,
                 } => {
                     needs_evaluation = true;
                     match *old_value.clone() {
@@ -2200,6 +3217,7 @@ impl<'a> UserModel<'a> {
                     row,
                     count: _,
                     old_data,
+                    old_merged_cells,
                 } => {
                     needs_evaluation = true;
                     self.model
@@ -2212,6 +3230,7 @@ impl<'a> UserModel<'a> {
                         }
                         worksheet.sheet_data.insert(r, row_data.data.clone());
                     }
+                    worksheet.merge_cells = old_merged_cells.clone();
                 }
                 Diff::InsertColumns {
                     sheet,
@@ -2226,6 +3245,7 @@ impl<'a> UserModel<'a> {
                     column,
                     count: _,
                     old_data,
+                    old_merged_cells,
                 } => {
                     needs_evaluation = true;
                     self.model
@@ -2240,8 +3260,12 @@ impl<'a> UserModel<'a> {
                             let width = col.width * constants::COLUMN_WIDTH_FACTOR;
                             let style = col.style;
                             worksheet.set_column_width_and_style(c, width, style)?;
+                            worksheet.set_column_hidden(c, col.hidden)?;
+                            worksheet.set_column_outline_level(c, col.outline_level)?;
+                            worksheet.set_column_collapsed(c, col.collapsed)?;
                         }
                     }
+                    worksheet.merge_cells = old_merged_cells.clone();
                 }
                 Diff::SetFrozenRowsCount {
                     sheet,
@@ -2266,6 +3290,24 @@ impl<'a> UserModel<'a> {
                 } => {
                     self.model.rename_sheet_by_index(*index, old_value)?;
                 }
+                Diff::DuplicateSheet { sheet, new_name: _ } => {
+                    // Removes the copy together with its tables and local defined names
+                    let worksheet = self.model.workbook.worksheet(*sheet + 1)?;
+                    let (name, sheet_id) = (worksheet.get_name(), worksheet.sheet_id);
+                    self.model
+                        .workbook
+                        .tables
+                        .retain(|_, table| table.sheet_name != name);
+                    self.model
+                        .workbook
+                        .defined_names
+                        .retain(|defined_name| defined_name.sheet_id != Some(sheet_id));
+                    self.model.delete_sheet(*sheet + 1)?;
+                    self.set_selected_sheet(*sheet)?;
+                }
+                Diff::MoveSheet { from, to } => {
+                    self.model.move_sheet(*to, *from)?;
+                }
                 Diff::SetSheetColor {
                     index,
                     old_value,
@@ -2348,6 +3390,12 @@ impl<'a> UserModel<'a> {
                     worksheet.state = old_data.state.clone();
                     worksheet.color = old_data.color.clone();
                     worksheet.merge_cells = old_data.merge_cells.clone();
+                    worksheet.auto_filter = old_data.auto_filter.clone();
+                    worksheet.summary_below = old_data.summary_below;
+                    worksheet.summary_right = old_data.summary_right;
+                    worksheet.page_setup = old_data.page_setup.clone();
+                    worksheet.protection = old_data.protection.clone();
+                    worksheet.opaque_parts = old_data.opaque_parts.clone();
                     worksheet.shared_formulas = old_data.shared_formulas.clone();
                     self.model.reset_parsed_structures();
 
@@ -2417,6 +3465,7 @@ impl<'a> UserModel<'a> {
                     old_value,
                     new_value: _,
                 } => {
+                    needs_evaluation = true;
                     self.model.set_locale(old_value)?;
                 }
                 Diff::SetTimezone {
@@ -2425,6 +3474,251 @@ impl<'a> UserModel<'a> {
                 } => {
                     self.model.set_timezone(old_value)?;
                 }
+                Diff::SetDateSystem {
+                    old_value,
+                    new_value: _,
+                    old_data,
+                } => {
+                    // Dates get back their exact serial numbers
+                    for cell_data in old_data {
+                        if let Some(cell) = &cell_data.cell {
+                            self.model
+                                .workbook
+                                .worksheet_mut(cell_data.sheet)?
+                                .update_cell(cell_data.row, cell_data.column, cell.clone())?;
+                        }
+                    }
+                    self.model.workbook.settings.date_system = *old_value;
+                    needs_evaluation = true;
+                }
+                Diff::SetCustomLists {
+                    old_value,
+                    new_value: _,
+                } => {
+                    self.model.set_custom_lists(old_value.clone())?;
+                }
+                Diff::SetTheme {
+                    old_value,
+                    new_value: _,
+                } => {
+                    self.model.set_theme(*old_value.clone())?;
+                }
+                Diff::CreateNamedStyle { name, .. } => {
+                    self.model.delete_named_style(name)?;
+                }
+                Diff::UpdateNamedStyle {
+                    name,
+                    old_value,
+                    new_value: _,
+                } => {
+                    self.model.update_named_style(name, old_value)?;
+                }
+                Diff::DeleteNamedStyle {
+                    name,
+                    builtin_id,
+                    old_value,
+                    linked_styles,
+                } => {
+                    let styles = &mut self.model.workbook.styles;
+                    styles.add_named_style(name, old_value, *builtin_id)?;
+                    styles.link_named_style(name, linked_styles)?;
+                }
+                Diff::SetRowHidden {
+                    sheet,
+                    row,
+                    new_value: _,
+                    old_value,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_row_hidden(*row, *old_value)?;
+                }
+                Diff::SetColumnHidden {
+                    sheet,
+                    column,
+                    new_value: _,
+                    old_value,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_column_hidden(*column, *old_value)?;
+                }
+                Diff::SetRowOutlineLevel {
+                    sheet,
+                    row,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_row_outline_level(*row, *old_value)?;
+                }
+                Diff::SetColumnOutlineLevel {
+                    sheet,
+                    column,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_column_outline_level(*column, *old_value)?;
+                }
+                Diff::SetRowCollapsed {
+                    sheet,
+                    row,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_row_collapsed(*row, *old_value)?;
+                }
+                Diff::SetColumnCollapsed {
+                    sheet,
+                    column,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_column_collapsed(*column, *old_value)?;
+                }
+                Diff::SetPageSetup {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.page_setup = *old_value.clone();
+                }
+                Diff::SetPrintArea {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .set_local_defined_name(*sheet, PRINT_AREA, old_value.clone())?;
+                }
+                Diff::SetPrintTitles {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .set_local_defined_name(*sheet, PRINT_TITLES, old_value.clone())?;
+                }
+                Diff::SetSheetProtection {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.protection = *old_value.clone();
+                }
+                Diff::SetWorkbookProtection {
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.protection = old_value.clone();
+                }
+                Diff::SetOutlineSummaryBelow {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.summary_below = *old_value;
+                }
+                Diff::SetOutlineSummaryRight {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.summary_right = *old_value;
+                }
+                Diff::SetAutoFilter {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.auto_filter = *old_value.clone();
+                }
+                Diff::SetTableAutoFilter {
+                    table,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model
+                        .set_table_auto_filter(table, *old_value.clone())?;
+                }
+                Diff::SetMergedCells {
+                    sheet,
+                    new_value: _,
+                    old_value,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.merge_cells = old_value.clone();
+                }
+                Diff::SortRange {
+                    sheet,
+                    row,
+                    column,
+                    width: _,
+                    height: _,
+                    keys: _,
+                    old_data,
+                } => {
+                    needs_evaluation = true;
+                    for (row_offset, cells) in old_data.iter().enumerate() {
+                        let target_row = *row + row_offset as i32;
+                        for (column_offset, cell) in cells.iter().enumerate() {
+                            let target_column = *column + column_offset as i32;
+                            match cell {
+                                Some(cell) => self
+                                    .model
+                                    .workbook
+                                    .worksheet_mut(*sheet)?
+                                    .update_cell(target_row, target_column, cell.clone())?,
+                                None => {
+                                    self.model
+                                        .cell_clear_all(*sheet, target_row, target_column)?;
+                                }
+                            }
+                        }
+                    }
+                }
+                Diff::MoveCells {
+                    sheet,
+                    old_data,
+                    old_merged_cells,
+                    old_comments,
+                    ..
+                } => {
+                    needs_evaluation = true;
+                    for cell_data in old_data {
+                        match &cell_data.cell {
+                            Some(cell) => self
+                                .model
+                                .workbook
+                                .worksheet_mut(cell_data.sheet)?
+                                .update_cell(cell_data.row, cell_data.column, cell.clone())?,
+                            None => self.model.cell_clear_all(
+                                cell_data.sheet,
+                                cell_data.row,
+                                cell_data.column,
+                            )?,
+                        }
+                    }
+                    let worksheet = self.model.workbook.worksheet_mut(*sheet)?;
+                    worksheet.merge_cells = old_merged_cells.clone();
+                    worksheet.comments = old_comments.clone();
+                }
             }
         }
         if needs_evaluation {
@@ -2434,7 +3728,7 @@ impl<'a> UserModel<'a> {
     }
 
     /// Applies diff list
-    fn apply_diff_list(&mut self, diff_list: &DiffList) -> Result<(), String> {
+    pub(super) fn apply_diff_list(&mut self, diff_list: &DiffList) -> Result<(), String> {
         let mut needs_evaluation = false;
         for diff in diff_list {
             match diff {
@@ -2449,6 +3743,17 @@ impl<'a> UserModel<'a> {
                     self.model
                         .set_user_input(*sheet, *row, *column, new_value.to_string())?;
                 }
+                Diff::SetCellRichText {
+                    sheet,
+                    row,
+                    column,
+                    new_value,
+                    old_value: _,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .set_cell_rich_text(*sheet, *row, *column, new_value)?;
+                }
                 Diff::SetColumnWidth {
                     sheet,
                     column,
@@ -2502,6 +3807,7 @@ impl<'a> UserModel<'a> {
                     row,
                     count,
                     old_data: _,
+                    old_merged_cells: _,
                 } => {
                     self.model.delete_rows(*sheet, *row, *count)?;
                     needs_evaluation = true;
@@ -2519,6 +3825,7 @@ impl<'a> UserModel<'a> {
                     column,
                     count,
                     old_data: _,
+                    old_merged_cells: _,
                 } => {
                     self.model.delete_columns(*sheet, *column, *count)?;
                     needs_evaluation = true;
@@ -2550,6 +3857,13 @@ impl<'a> UserModel<'a> {
                 } => {
                     self.model.rename_sheet_by_index(*index, new_value)?;
                 }
+                Diff::DuplicateSheet { sheet, new_name } => {
+                    let index = self.model.duplicate_sheet(*sheet, new_name)?;
+                    self.set_selected_sheet(index)?;
+                }
+                Diff::MoveSheet { from, to } => {
+                    self.model.move_sheet(*from, *to)?;
+                }
                 Diff::SetSheetColor {
                     index,
                     old_value: _,
@@ -2648,6 +3962,7 @@ impl<'a> UserModel<'a> {
                     old_value: _,
                     new_value,
                 } => {
+                    needs_evaluation = true;
                     self.model.set_locale(new_value)?;
                 }
                 Diff::SetTimezone {
@@ -2656,6 +3971,226 @@ impl<'a> UserModel<'a> {
                 } => {
                     self.model.set_timezone(new_value)?;
                 }
+                Diff::SetDateSystem {
+                    old_value: _,
+                    new_value,
+                    old_data: _,
+                } => {
+                    self.model.set_date_system(*new_value)?;
+                }
+                Diff::SetCustomLists {
+                    old_value: _,
+                    new_value,
+                } => {
+                    self.model.set_custom_lists(new_value.clone())?;
+                }
+                Diff::SetTheme {
+                    old_value: _,
+                    new_value,
+                } => {
+                    self.model.set_theme(*new_value.clone())?;
+                }
+                Diff::CreateNamedStyle {
+                    name,
+                    builtin_id,
+                    style,
+                } => {
+                    self.model
+                        .workbook
+                        .styles
+                        .add_named_style(name, style, *builtin_id)?;
+                }
+                Diff::UpdateNamedStyle {
+                    name,
+                    old_value: _,
+                    new_value,
+                } => {
+                    self.model.update_named_style(name, new_value)?;
+                }
+                Diff::DeleteNamedStyle { name, .. } => {
+                    self.model.delete_named_style(name)?;
+                }
+                Diff::SetRowHidden {
+                    sheet,
+                    row,
+                    new_value,
+                    old_value: _,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_row_hidden(*row, *new_value)?;
+                }
+                Diff::SetColumnHidden {
+                    sheet,
+                    column,
+                    new_value,
+                    old_value: _,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_column_hidden(*column, *new_value)?;
+                }
+                Diff::SetRowOutlineLevel {
+                    sheet,
+                    row,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_row_outline_level(*row, *new_value)?;
+                }
+                Diff::SetColumnOutlineLevel {
+                    sheet,
+                    column,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_column_outline_level(*column, *new_value)?;
+                }
+                Diff::SetRowCollapsed {
+                    sheet,
+                    row,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_row_collapsed(*row, *new_value)?;
+                }
+                Diff::SetColumnCollapsed {
+                    sheet,
+                    column,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model
+                        .workbook
+                        .worksheet_mut(*sheet)?
+                        .set_column_collapsed(*column, *new_value)?;
+                }
+                Diff::SetPageSetup {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.page_setup = *new_value.clone();
+                }
+                Diff::SetPrintArea {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .set_local_defined_name(*sheet, PRINT_AREA, new_value.clone())?;
+                }
+                Diff::SetPrintTitles {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    needs_evaluation = true;
+                    self.model
+                        .set_local_defined_name(*sheet, PRINT_TITLES, new_value.clone())?;
+                }
+                Diff::SetSheetProtection {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.protection = *new_value.clone();
+                }
+                Diff::SetWorkbookProtection {
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.protection = new_value.clone();
+                }
+                Diff::SetOutlineSummaryBelow {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.summary_below = *new_value;
+                }
+                Diff::SetOutlineSummaryRight {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.summary_right = *new_value;
+                }
+                Diff::SetAutoFilter {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.auto_filter = *new_value.clone();
+                }
+                Diff::SetTableAutoFilter {
+                    table,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model
+                        .set_table_auto_filter(table, *new_value.clone())?;
+                }
+                Diff::SetMergedCells {
+                    sheet,
+                    new_value,
+                    old_value: _,
+                } => {
+                    self.model.workbook.worksheet_mut(*sheet)?.merge_cells = new_value.clone();
+                }
+                Diff::SortRange {
+                    sheet,
+                    row,
+                    column,
+                    width,
+                    height,
+                    keys,
+                    old_data: _,
+                } => {
+                    needs_evaluation = true;
+                    let area = Area {
+                        sheet: *sheet,
+                        row: *row,
+                        column: *column,
+                        width: *width,
+                        height: *height,
+                    };
+                    self.model.sort_range(&area, keys)?;
+                }
+                Diff::MoveCells {
+                    sheet,
+                    row,
+                    column,
+                    width,
+                    height,
+                    row_delta,
+                    column_delta,
+                    ..
+                } => {
+                    needs_evaluation = true;
+                    let area = Area {
+                        sheet: *sheet,
+                        row: *row,
+                        column: *column,
+                        width: *width,
+                        height: *height,
+                    };
+                    self.model.move_cells(&area, *row_delta, *column_delta)?;
+                }
             }
         }
 
@@ -0,0 +1,467 @@
+//! # Diff wire format
+//!
+//! The changes made in a [UserModel](crate::UserModel) are lists of [Diff]s that can be sent to
+//! other models (see [UserModel::flush_send_queue](crate::UserModel::flush_send_queue)).
+//! This module defines a stable, versioned serialization of those lists that other programs can
+//! read. There are two equivalent forms, JSON and a compact binary form.
+//!
+//! Each diff is identified by its name (`op`), never by its position in the [Diff] enum, so adding
+//! new kinds of diffs does not change existing ones. A decoder that finds a diff it does not know
+//! about (written by a newer version) reports it in [DecodedDiffs::unknown] and decodes the rest.
+//! Fields that a decoder does not know about are ignored.
+//!
+//! ## JSON
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "changes": [
+//!     {
+//!       "type": "redo",
+//!       "diffs": [
+//!         {
+//!           "op": "set_cell_value",
+//!           "sheet": 0,
+//!           "row": 1,
+//!           "column": 1,
+//!           "new_value": "=A2*2",
+//!           "old_value": null
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! * `version`: the version of the format, currently [FORMAT_VERSION].
+//! * `changes`: the lists of diffs in the order they were made. `type` is `redo` if the list is
+//!   applied in order and `undo` if it is undone (the diffs are reverted in reverse order).
+//! * `op`: the name of the [Diff] variant in snake case. The rest of the fields are the fields of
+//!   the variant.
+//!
+//! ## Binary
+//!
+//! The binary form holds the same document as the JSON form with the `version` moved to a header:
+//!
+//! * The 4 bytes `ICDF`.
+//! * The version, a varint.
+//! * The key table: a varint with the number of keys followed by the keys as strings. Object keys
+//!   are written as indexes in this table. Objects whose keys are all integers (like the cells of
+//!   a row by column) are written as pairs of key and value instead and their keys are not in the
+//!   table.
+//! * The list of changes, a value.
+//!
+//! Integers are LEB128 varints. A string is a varint with the length in bytes followed by the UTF-8
+//! bytes. A value is a one byte tag followed by its data:
+//!
+//! | Tag | Value   | Data                                                   |
+//! |-----|---------|--------------------------------------------------------|
+//! | 0   | null    |                                                        |
+//! | 1   | false   |                                                        |
+//! | 2   | true    |                                                        |
+//! | 3   | integer | a zigzag encoded varint                                |
+//! | 4   | integer | a varint, for positive numbers that do not fit in i64  |
+//! | 5   | float   | 8 bytes, little endian IEEE 754                        |
+//! | 6   | string  | a string                                               |
+//! | 7   | array   | a varint with the number of items, then the items      |
+//! | 8   | object  | a varint with the number of fields, then for each field the key index and the value |
+//! | 9   | object with integer keys | a varint with the number of fields, then for each field the key as a zigzag encoded varint and the value |
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Number, Value};
+
+pub use super::history::{CellData, ColumnData, Diff, DiffType, QueueDiffs, RowData};
+
+/// The current version of the wire format
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 4] = b"ICDF";
+
+// Nesting limit when reading binary values, the documents we write are not nearly as deep
+const MAX_DEPTH: usize = 128;
+
+/// The result of decoding a list of changes
+#[derive(Debug, PartialEq)]
+pub struct DecodedDiffs {
+    /// The version of the format the changes were written with
+    pub version: u32,
+    /// The lists of diffs. Diffs that could not be decoded are not included.
+    pub changes: Vec<QueueDiffs>,
+    /// The diffs that could not be decoded
+    pub unknown: Vec<UnknownDiff>,
+}
+
+/// A diff that could not be decoded, most likely written by a newer version
+#[derive(Debug, PartialEq)]
+pub struct UnknownDiff {
+    /// Index of the change in the list of changes
+    pub change: usize,
+    /// Index of the diff in the change
+    pub index: usize,
+    /// Name of the diff, empty if it does not have one
+    pub op: String,
+    /// Why the diff could not be decoded
+    pub reason: String,
+}
+
+fn diff_to_value(diff: &Diff) -> Value {
+    // Variants are serialized as {"variant_name": {fields}}
+    let (op, fields) = match serde_json::to_value(diff) {
+        Ok(Value::Object(map)) => match map.into_iter().next() {
+            Some((op, Value::Object(fields))) => (op, fields),
+            _ => return Value::Null,
+        },
+        _ => return Value::Null,
+    };
+    let mut object = Map::new();
+    object.insert("op".to_string(), Value::String(op));
+    object.extend(fields);
+    Value::Object(object)
+}
+
+fn value_to_diff(value: &Value) -> Result<Diff, (String, String)> {
+    let mut fields = match value {
+        Value::Object(fields) => fields.clone(),
+        _ => return Err((String::new(), "A diff must be an object".to_string())),
+    };
+    let op = match fields.remove("op") {
+        Some(Value::String(op)) => op,
+        _ => return Err((String::new(), "Missing 'op'".to_string())),
+    };
+    let mut tagged = Map::new();
+    tagged.insert(op.clone(), Value::Object(fields));
+    serde_json::from_value(Value::Object(tagged)).map_err(|e| (op, e.to_string()))
+}
+
+fn changes_to_value(changes: &[QueueDiffs]) -> Value {
+    Value::Array(
+        changes
+            .iter()
+            .map(|queue_diffs| {
+                let mut object = Map::new();
+                let diff_type = match queue_diffs.r#type {
+                    DiffType::Undo => "undo",
+                    DiffType::Redo => "redo",
+                };
+                object.insert("type".to_string(), Value::String(diff_type.to_string()));
+                object.insert(
+                    "diffs".to_string(),
+                    Value::Array(queue_diffs.list.iter().map(diff_to_value).collect()),
+                );
+                Value::Object(object)
+            })
+            .collect(),
+    )
+}
+
+fn value_to_changes(version: u32, value: &Value) -> Result<DecodedDiffs, String> {
+    let items = match value {
+        Value::Array(items) => items,
+        _ => return Err("'changes' must be an array".to_string()),
+    };
+    let mut changes = Vec::new();
+    let mut unknown = Vec::new();
+    for (change, item) in items.iter().enumerate() {
+        let r#type = match item.get("type").and_then(|t| t.as_str()) {
+            Some("undo") => DiffType::Undo,
+            Some("redo") => DiffType::Redo,
+            _ => return Err(format!("Invalid type in change {change}")),
+        };
+        let diffs = match item.get("diffs") {
+            Some(Value::Array(diffs)) => diffs,
+            _ => return Err(format!("Invalid diffs in change {change}")),
+        };
+        let mut list = Vec::new();
+        for (index, diff) in diffs.iter().enumerate() {
+            match value_to_diff(diff) {
+                Ok(diff) => list.push(diff),
+                Err((op, reason)) => unknown.push(UnknownDiff {
+                    change,
+                    index,
+                    op,
+                    reason,
+                }),
+            }
+        }
+        changes.push(QueueDiffs { r#type, list });
+    }
+    Ok(DecodedDiffs {
+        version,
+        changes,
+        unknown,
+    })
+}
+
+/// Serializes a list of changes as JSON
+pub fn to_json(changes: &[QueueDiffs]) -> String {
+    let mut document = Map::new();
+    document.insert("version".to_string(), Value::from(FORMAT_VERSION));
+    document.insert("changes".to_string(), changes_to_value(changes));
+    Value::Object(document).to_string()
+}
+
+/// Reads a list of changes from JSON
+pub fn from_json(json: &str) -> Result<DecodedDiffs, String> {
+    let document: Value = serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {e}"))?;
+    let version = match document.get("version").and_then(|v| v.as_u64()) {
+        Some(version) if version > 0 && version <= u32::MAX as u64 => version as u32,
+        _ => return Err("Missing or invalid version".to_string()),
+    };
+    match document.get("changes") {
+        Some(changes) => value_to_changes(version, changes),
+        None => Err("Missing 'changes'".to_string()),
+    }
+}
+
+/// Serializes a list of changes in the compact binary form
+pub fn to_bytes(changes: &[QueueDiffs]) -> Vec<u8> {
+    let value = changes_to_value(changes);
+    let mut keys = Vec::new();
+    let mut key_indexes = HashMap::new();
+    collect_keys(&value, &mut keys, &mut key_indexes);
+    let mut body = Vec::new();
+    write_value(&value, &key_indexes, &mut body);
+
+    let mut bytes = MAGIC.to_vec();
+    write_varint(FORMAT_VERSION as u64, &mut bytes);
+    write_varint(keys.len() as u64, &mut bytes);
+    for key in &keys {
+        write_string(key, &mut bytes);
+    }
+    bytes.extend(body);
+    bytes
+}
+
+/// Reads a list of changes in the compact binary form
+pub fn from_bytes(bytes: &[u8]) -> Result<DecodedDiffs, String> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.read_bytes(MAGIC.len())? != MAGIC {
+        return Err("Not a list of diffs".to_string());
+    }
+    let version = match reader.read_varint()? {
+        version if version > 0 && version <= u32::MAX as u64 => version as u32,
+        _ => return Err("Invalid version".to_string()),
+    };
+    let key_count = reader.read_varint()?;
+    let mut keys = Vec::new();
+    for _ in 0..key_count {
+        keys.push(reader.read_string()?);
+    }
+    let value = reader.read_value(&keys, 0)?;
+    if reader.position != bytes.len() {
+        return Err("Unexpected data after the list of diffs".to_string());
+    }
+    value_to_changes(version, &value)
+}
+
+// The keys of the object if they are all integers, written as such
+fn integer_keys(fields: &Map<String, Value>) -> Option<Vec<i64>> {
+    if fields.is_empty() {
+        return None;
+    }
+    fields
+        .keys()
+        .map(|key| match key.parse::<i64>() {
+            Ok(n) if n.to_string() == *key => Some(n),
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_keys(value: &Value, keys: &mut Vec<String>, key_indexes: &mut HashMap<String, usize>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_keys(item, keys, key_indexes);
+            }
+        }
+        Value::Object(fields) => {
+            let is_integer_keyed = integer_keys(fields).is_some();
+            for (key, field) in fields {
+                if !is_integer_keyed && !key_indexes.contains_key(key) {
+                    key_indexes.insert(key.clone(), keys.len());
+                    keys.push(key.clone());
+                }
+                collect_keys(field, keys, key_indexes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn write_varint(mut value: u64, bytes: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_zigzag(value: i64, bytes: &mut Vec<u8>) {
+    write_varint(((value << 1) ^ (value >> 63)) as u64, bytes);
+}
+
+fn write_string(value: &str, bytes: &mut Vec<u8>) {
+    write_varint(value.len() as u64, bytes);
+    bytes.extend(value.as_bytes());
+}
+
+fn write_value(value: &Value, key_indexes: &HashMap<String, usize>, bytes: &mut Vec<u8>) {
+    match value {
+        Value::Null => bytes.push(0),
+        Value::Bool(false) => bytes.push(1),
+        Value::Bool(true) => bytes.push(2),
+        Value::Number(number) => {
+            if let Some(n) = number.as_i64() {
+                bytes.push(3);
+                write_zigzag(n, bytes);
+            } else if let Some(n) = number.as_u64() {
+                bytes.push(4);
+                write_varint(n, bytes);
+            } else {
+                bytes.push(5);
+                bytes.extend(number.as_f64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Value::String(s) => {
+            bytes.push(6);
+            write_string(s, bytes);
+        }
+        Value::Array(items) => {
+            bytes.push(7);
+            write_varint(items.len() as u64, bytes);
+            for item in items {
+                write_value(item, key_indexes, bytes);
+            }
+        }
+        Value::Object(fields) => match integer_keys(fields) {
+            Some(integer_keys) => {
+                bytes.push(9);
+                write_varint(fields.len() as u64, bytes);
+                for (key, field) in integer_keys.into_iter().zip(fields.values()) {
+                    write_zigzag(key, bytes);
+                    write_value(field, key_indexes, bytes);
+                }
+            }
+            None => {
+                bytes.push(8);
+                write_varint(fields.len() as u64, bytes);
+                for (key, field) in fields {
+                    let index = key_indexes.get(key).copied().unwrap_or_default();
+                    write_varint(index as u64, bytes);
+                    write_value(field, key_indexes, bytes);
+                }
+            }
+        },
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn read_bytes(&mut self, count: usize) -> Result<&[u8], String> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "Unexpected end of data".to_string())?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, String> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid varint".to_string())
+    }
+
+    fn read_zigzag(&mut self) -> Result<i64, String> {
+        let n = self.read_varint()?;
+        Ok(((n >> 1) as i64) ^ -((n & 1) as i64))
+    }
+
+    fn read_length(&mut self) -> Result<usize, String> {
+        let length = self.read_varint()?;
+        // Every item takes at least one byte
+        if length > (self.bytes.len() - self.position) as u64 {
+            return Err("Unexpected end of data".to_string());
+        }
+        Ok(length as usize)
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        let length = self.read_length()?;
+        String::from_utf8(self.read_bytes(length)?.to_vec())
+            .map_err(|_| "Invalid UTF-8 string".to_string())
+    }
+
+    fn read_value(&mut self, keys: &[String], depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("Too many nested values".to_string());
+        }
+        Ok(match self.read_byte()? {
+            0 => Value::Null,
+            1 => Value::Bool(false),
+            2 => Value::Bool(true),
+            3 => Value::from(self.read_zigzag()?),
+            4 => Value::from(self.read_varint()?),
+            5 => {
+                let mut data = [0u8; 8];
+                data.copy_from_slice(self.read_bytes(8)?);
+                Number::from_f64(f64::from_le_bytes(data))
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null)
+            }
+            6 => Value::String(self.read_string()?),
+            7 => {
+                let length = self.read_length()?;
+                let mut items = Vec::with_capacity(length);
+                for _ in 0..length {
+                    items.push(self.read_value(keys, depth + 1)?);
+                }
+                Value::Array(items)
+            }
+            8 => {
+                let length = self.read_length()?;
+                let mut fields = Map::new();
+                for _ in 0..length {
+                    let index = self.read_varint()?;
+                    let key = keys
+                        .get(index as usize)
+                        .ok_or_else(|| format!("Invalid key index {index}"))?;
+                    fields.insert(key.clone(), self.read_value(keys, depth + 1)?);
+                }
+                Value::Object(fields)
+            }
+            9 => {
+                let length = self.read_length()?;
+                let mut fields = Map::new();
+                for _ in 0..length {
+                    let key = self.read_zigzag()?;
+                    fields.insert(key.to_string(), self.read_value(keys, depth + 1)?);
+                }
+                Value::Object(fields)
+            }
+            tag => return Err(format!("Invalid value tag {tag}")),
+        })
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(feature = "use_regex_lite")]
+use regex_lite as regex;
+
+use crate::{
+    expressions::{parser::Node, types::CellReferenceRC},
+    find::{FindLookIn, FindMatch, FindOptions},
+    types::Cell,
+};
+
+use super::{history::Diff, UserModel};
+
+impl<'a> UserModel<'a> {
+    /// Returns the cells where `text` is found
+    ///
+    /// See also:
+    /// * [Model::find](crate::Model::find)
+    pub fn find<'b>(
+        &'b self,
+        text: &str,
+        options: &FindOptions,
+    ) -> Result<impl Iterator<Item = FindMatch> + 'b, String> {
+        self.model.find(text, options)
+    }
+
+    /// Replaces `text` with `replacement` in the content of all the matching cells.
+    /// Returns the number of cells changed. All the changes are undone at once.
+    ///
+    /// When looking in values the cells are found by their value and cells with formulas are
+    /// skipped. Numbers are replaced in the value as it is shown (like `1,000` or a date) and the
+    /// result is entered as if typed, other cells are replaced in their content.
+    /// Comments can't be replaced.
+    /// If `options.regex` is set the replacement can use the groups of the regular expression
+    /// (`$1`, `${name}`).
+    /// Cells that are locked and formulas that would not be valid after the replacement are
+    /// left untouched.
+    pub fn replace_all(
+        &mut self,
+        text: &str,
+        replacement: &str,
+        options: &FindOptions,
+    ) -> Result<usize, String> {
+        if options.look_in == FindLookIn::Comments {
+            return Err("Replace does not work in comments".to_string());
+        }
+        let regex = options.get_regex(text)?;
+        let matches: Vec<FindMatch> = self.model.find(text, options)?.collect();
+        let mut diff_list = Vec::new();
+        for FindMatch {
+            sheet,
+            row,
+            column,
+            text,
+        } in matches
+        {
+            if self.check_cell_editable(sheet, row, column).is_err() {
+                continue;
+            }
+            let old_value = self
+                .model
+                .workbook
+                .worksheet(sheet)?
+                .cell(row, column)
+                .cloned();
+            let text = if options.look_in == FindLookIn::Values {
+                match &old_value {
+                    // The values of formulas are computed
+                    Some(cell) if cell.has_formula() => continue,
+                    // The same text that was found
+                    Some(Cell::NumberCell { .. }) => text,
+                    _ => self.model.get_localized_cell_content(sheet, row, column)?,
+                }
+            } else {
+                text
+            };
+            let new_value = if options.regex {
+                regex.replace_all(&text, replacement)
+            } else {
+                regex.replace_all(&text, regex::NoExpand(replacement))
+            }
+            .to_string();
+            if new_value == text || !self.is_valid_input(sheet, row, column, &new_value)? {
+                continue;
+            }
+            self.model
+                .set_user_input(sheet, row, column, new_value.clone())?;
+            diff_list.push(Diff::SetCellValue {
+                sheet,
+                row,
+                column,
+                new_value,
+                old_value: Box::new(old_value),
+            });
+        }
+        let count = diff_list.len();
+        if count > 0 {
+            self.push_diff_list(diff_list);
+            self.evaluate_if_not_paused();
+        }
+        Ok(count)
+    }
+
+    // Returns false if the input is a formula that cannot be parsed
+    fn is_valid_input(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        value: &str,
+    ) -> Result<bool, String> {
+        let formula = match value.strip_prefix('=') {
+            Some(formula) => formula,
+            None => return Ok(true),
+        };
+        let context = CellReferenceRC {
+            sheet: self.model.workbook.worksheet(sheet)?.get_name(),
+            row,
+            column,
+        };
+        let node = self.model.parser.parse(formula, &context);
+        Ok(!matches!(node, Node::ParseErrorKind { .. }))
+    }
+}
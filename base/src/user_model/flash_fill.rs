@@ -0,0 +1,424 @@
+use crate::expressions::types::Area;
+
+use super::{history::Diff, UserModel};
+
+// Maximum number of steps while looking for a program, keeps the search bounded for long texts
+const SEARCH_LIMIT: usize = 50_000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Case {
+    AsIs,
+    Upper,
+    Lower,
+    Proper,
+}
+
+const CASES: [Case; 4] = [Case::AsIs, Case::Upper, Case::Lower, Case::Proper];
+
+const FIELD_SEPARATORS: [char; 6] = [',', ';', '@', '/', '|', ':'];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TokenKind {
+    // Text between spaces or separators like '@' or ','
+    Field,
+    // Letters and digits
+    Word,
+    Letters,
+    Digits,
+}
+
+impl TokenKind {
+    fn is_part(&self, c: char) -> bool {
+        match self {
+            TokenKind::Field => !c.is_whitespace() && !FIELD_SEPARATORS.contains(&c),
+            TokenKind::Word => c.is_alphanumeric(),
+            TokenKind::Letters => c.is_alphabetic(),
+            TokenKind::Digits => c.is_ascii_digit(),
+        }
+    }
+}
+
+// A piece of the result of a flash fill program
+#[derive(Clone, PartialEq, Debug)]
+enum Segment {
+    Constant(String),
+    // The whole input cell
+    Input {
+        input: usize,
+        case: Case,
+    },
+    // A token of an input cell, counted from the end if `index` is negative.
+    // If `prefix` is set only the first characters of the token are used (initials).
+    Token {
+        input: usize,
+        kind: TokenKind,
+        index: i32,
+        case: Case,
+        prefix: Option<usize>,
+    },
+}
+
+fn tokens(text: &str, kind: TokenKind) -> Vec<&str> {
+    text.split(|c: char| !kind.is_part(c))
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+fn apply_case(text: &str, case: Case) -> String {
+    match case {
+        Case::AsIs => text.to_string(),
+        Case::Upper => text.to_uppercase(),
+        Case::Lower => text.to_lowercase(),
+        Case::Proper => {
+            let mut result = String::new();
+            let mut previous_is_letter = false;
+            for c in text.chars() {
+                if previous_is_letter {
+                    result.extend(c.to_lowercase());
+                } else {
+                    result.extend(c.to_uppercase());
+                }
+                previous_is_letter = c.is_alphabetic();
+            }
+            result
+        }
+    }
+}
+
+impl Segment {
+    fn evaluate(&self, inputs: &[String]) -> Option<String> {
+        match self {
+            Segment::Constant(text) => Some(text.clone()),
+            Segment::Input { input, case } => Some(apply_case(inputs.get(*input)?, *case)),
+            Segment::Token {
+                input,
+                kind,
+                index,
+                case,
+                prefix,
+            } => {
+                let tokens = tokens(inputs.get(*input)?, *kind);
+                let position = if *index >= 0 {
+                    *index as usize
+                } else {
+                    tokens.len().checked_sub(index.unsigned_abs() as usize)?
+                };
+                let token = tokens.get(position)?;
+                let token = match prefix {
+                    Some(length) => token.chars().take(*length).collect(),
+                    None => token.to_string(),
+                };
+                Some(apply_case(&token, *case))
+            }
+        }
+    }
+}
+
+// All the segments that can be built from the inputs, in order of preference
+fn all_segments(inputs: &[String]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    for (input, text) in inputs.iter().enumerate() {
+        for case in CASES {
+            segments.push(Segment::Input { input, case });
+        }
+        for kind in [
+            TokenKind::Field,
+            TokenKind::Word,
+            TokenKind::Letters,
+            TokenKind::Digits,
+        ] {
+            let count = tokens(text, kind).len() as i32;
+            let indexes = (0..count).chain((1..=count).map(|i| -i));
+            for index in indexes {
+                for prefix in [None, Some(1)] {
+                    for case in CASES {
+                        segments.push(Segment::Token {
+                            input,
+                            kind,
+                            index,
+                            case,
+                            prefix,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    segments
+}
+
+// An example of the transformation: the inputs and the expected result
+struct Example {
+    inputs: Vec<String>,
+    output: String,
+}
+
+// Depth first search of the simplest program (fewest constant characters, then fewest segments)
+// that produces the output of every example
+struct Search<'a> {
+    examples: &'a [Example],
+    // The segments that can be used with the values they produce for the first example
+    segments: Vec<(Segment, String)>,
+    best: Option<((usize, usize), Vec<Segment>)>,
+    steps: usize,
+}
+
+impl Search<'_> {
+    fn score(program: &[Segment]) -> (usize, usize) {
+        let mut constant_chars = 0;
+        let mut segments = 0;
+        let mut previous_is_constant = false;
+        for segment in program {
+            if let Segment::Constant(text) = segment {
+                constant_chars += text.chars().count();
+                if previous_is_constant {
+                    continue;
+                }
+                previous_is_constant = true;
+            } else {
+                previous_is_constant = false;
+            }
+            segments += 1;
+        }
+        (constant_chars, segments)
+    }
+
+    // `positions` are the byte offsets already produced in the output of each example
+    fn search(&mut self, program: &mut Vec<Segment>, positions: &[usize]) {
+        self.steps += 1;
+        if self.steps > SEARCH_LIMIT {
+            return;
+        }
+        let score = Self::score(program);
+        if let Some((best_score, _)) = &self.best {
+            if score >= *best_score {
+                return;
+            }
+        }
+        let first = &self.examples[0];
+        if positions[0] == first.output.len() {
+            let done = self
+                .examples
+                .iter()
+                .zip(positions)
+                .all(|(example, position)| *position == example.output.len());
+            // A program of constants only would write the same text in every cell
+            let uses_inputs = program
+                .iter()
+                .any(|segment| !matches!(segment, Segment::Constant(_)));
+            if done && uses_inputs {
+                self.best = Some((score, program.clone()));
+            }
+            return;
+        }
+        let rest = &first.output[positions[0]..];
+
+        let mut candidates = Vec::new();
+        // Segments producing the same texts in all examples are equivalent, only the first is tried
+        let mut seen: Vec<Vec<String>> = Vec::new();
+        for (segment, value) in &self.segments {
+            if value.is_empty() || !rest.starts_with(value.as_str()) {
+                continue;
+            }
+            let values = match self.advance(segment, positions) {
+                Some(values) => values,
+                None => continue,
+            };
+            if seen.contains(&values) {
+                continue;
+            }
+            seen.push(values.clone());
+            candidates.push((segment.clone(), values));
+        }
+        // Longer matches first
+        candidates.sort_by_key(|(_, values)| std::cmp::Reverse(values[0].len()));
+        if let Some(c) = rest.chars().next() {
+            let constant = Segment::Constant(c.to_string());
+            if let Some(values) = self.advance(&constant, positions) {
+                candidates.push((constant, values));
+            }
+        }
+        for (segment, values) in candidates {
+            let new_positions: Vec<usize> = positions
+                .iter()
+                .zip(&values)
+                .map(|(position, value)| position + value.len())
+                .collect();
+            program.push(segment);
+            self.search(program, &new_positions);
+            program.pop();
+        }
+    }
+
+    // The values of the segment in each example if they continue the outputs
+    fn advance(&self, segment: &Segment, positions: &[usize]) -> Option<Vec<String>> {
+        let mut values = Vec::new();
+        for (example, position) in self.examples.iter().zip(positions) {
+            let value = segment.evaluate(&example.inputs)?;
+            if !example.output[*position..].starts_with(value.as_str()) {
+                return None;
+            }
+            values.push(value);
+        }
+        Some(values)
+    }
+}
+
+// Finds the simplest program consistent with all the examples
+fn infer_program(examples: &[Example]) -> Option<Vec<Segment>> {
+    let first = examples.first()?;
+    let segments = all_segments(&first.inputs)
+        .into_iter()
+        .filter_map(|segment| {
+            let value = segment.evaluate(&first.inputs)?;
+            Some((segment, value))
+        })
+        .collect();
+    let mut search = Search {
+        examples,
+        segments,
+        best: None,
+        steps: 0,
+    };
+    search.search(&mut Vec::new(), &vec![0; examples.len()]);
+    search.best.map(|(_, program)| program)
+}
+
+fn run_program(program: &[Segment], inputs: &[String]) -> Option<String> {
+    let mut result = String::new();
+    for segment in program {
+        result.push_str(&segment.evaluate(inputs)?);
+    }
+    Some(result)
+}
+
+impl UserModel<'_> {
+    /// Fills the empty cells of a column following the pattern of the cells that are not empty.
+    ///
+    /// The values of the columns next to it (on both sides, up to the first empty column) are
+    /// the inputs and the cells already typed in the column are the examples. The result is built
+    /// from pieces of the inputs (whole cells, words, numbers or initials, possibly changing the case)
+    /// and constant texts. For instance with "John Smith" in column A and "Smith, J." typed in B1
+    /// the rest of column B is filled with the last names and initials of column A.
+    ///
+    /// If the area is a single cell the rows around it with inputs are filled.
+    pub fn flash_fill(&mut self, area: &Area) -> Result<(), String> {
+        if area.width != 1 {
+            return Err("Flash fill works on a single column".to_string());
+        }
+        let sheet = area.sheet;
+        let column = area.column;
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        let max_row = worksheet.dimension().max_row;
+        let max_column = worksheet.dimension().max_column;
+        let last_area_row = area.row + area.height - 1;
+        let is_empty = |row: i32, column: i32| -> Result<bool, String> {
+            Ok(self
+                .get_formatted_cell_value(sheet, row, column)?
+                .is_empty())
+        };
+        let column_has_data = |column: i32| -> Result<bool, String> {
+            for row in area.row..=last_area_row {
+                if !is_empty(row, column)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+
+        // The input columns
+        let mut input_columns = Vec::new();
+        let mut left = column - 1;
+        while left >= 1 && column_has_data(left)? {
+            input_columns.insert(0, left);
+            left -= 1;
+        }
+        let mut right = column + 1;
+        while right <= max_column && column_has_data(right)? {
+            input_columns.push(right);
+            right += 1;
+        }
+        if input_columns.is_empty() {
+            return Err("There is no data next to the column".to_string());
+        }
+
+        let has_inputs = |row: i32| -> Result<bool, String> {
+            for input_column in &input_columns {
+                if !is_empty(row, *input_column)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+        let (mut first_row, mut last_row) = (area.row, last_area_row);
+        if area.height == 1 {
+            while first_row > 1 && has_inputs(first_row - 1)? {
+                first_row -= 1;
+            }
+            while last_row < max_row && has_inputs(last_row + 1)? {
+                last_row += 1;
+            }
+        }
+
+        let mut examples = Vec::new();
+        let mut rows_to_fill = Vec::new();
+        for row in first_row..=last_row {
+            let inputs = input_columns
+                .iter()
+                .map(|input_column| self.get_formatted_cell_value(sheet, row, *input_column))
+                .collect::<Result<Vec<String>, String>>()?;
+            let output = self.get_formatted_cell_value(sheet, row, column)?;
+            if !output.is_empty() {
+                examples.push(Example { inputs, output });
+            } else if inputs.iter().any(|input| !input.is_empty()) {
+                rows_to_fill.push((row, inputs));
+            }
+        }
+        if examples.is_empty() {
+            return Err("Type an example of the result first".to_string());
+        }
+        if rows_to_fill.is_empty() {
+            return Err("Nothing to fill".to_string());
+        }
+        let program = match infer_program(&examples) {
+            Some(program) => program,
+            None => return Err("Could not find a pattern in the examples".to_string()),
+        };
+        self.check_area_editable(&Area {
+            sheet,
+            row: first_row,
+            column,
+            width: 1,
+            height: last_row - first_row + 1,
+        })?;
+
+        let mut diff_list = Vec::new();
+        for (row, inputs) in rows_to_fill {
+            let text = match run_program(&program, &inputs) {
+                Some(text) if !text.is_empty() => text,
+                // The pattern does not apply to this row
+                _ => continue,
+            };
+            let old_value = self
+                .model
+                .workbook
+                .worksheet(sheet)?
+                .cell(row, column)
+                .cloned();
+            // The results are always texts
+            let new_value = format!("'{text}");
+            self.model
+                .set_user_input(sheet, row, column, new_value.clone())?;
+            diff_list.push(Diff::SetCellValue {
+                sheet,
+                row,
+                column,
+                new_value,
+                old_value: Box::new(old_value),
+            });
+        }
+        self.push_diff_list(diff_list);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+}
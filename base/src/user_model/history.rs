@@ -1,24 +1,58 @@
 use std::collections::HashMap;
 
 use bitcode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
 
-use crate::types::{Cell, Col, Row, SheetState, Style, Worksheet};
+use crate::types::{
+    AutoFilter, Cell, Col, Comment, DateSystem, MergedRange, PageSetup, Row, SheetProtection,
+    SheetState, SortKey, Style, TextRun, Theme, WorkbookProtection, Worksheet,
+};
 
-#[derive(Clone, Encode, Decode)]
-pub(crate) struct RowData {
-    pub(crate) row: Option<Row>,
-    pub(crate) data: HashMap<i32, Cell>,
+use super::ui::SelectedView;
+
+/// The data of a deleted row
+#[derive(Clone, Encode, Decode, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RowData {
+    /// The row settings, if any
+    pub row: Option<Row>,
+    /// The cells of the row by column
+    pub data: HashMap<i32, Cell>,
+}
+
+/// The data of a deleted column
+#[derive(Clone, Encode, Decode, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ColumnData {
+    /// The column settings, if any
+    pub column: Option<Col>,
+    /// The cells of the column by row
+    pub data: HashMap<i32, Cell>,
 }
 
-#[derive(Clone, Encode, Decode)]
-pub(crate) struct ColumnData {
-    pub(crate) column: Option<Col>,
-    pub(crate) data: HashMap<i32, Cell>,
+/// A cell before a change
+#[derive(Clone, Encode, Decode, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CellData {
+    /// The index of the sheet
+    pub sheet: u32,
+    /// The row of the cell
+    pub row: i32,
+    /// The column of the cell
+    pub column: i32,
+    /// The cell, `None` if it was empty
+    pub cell: Option<Cell>,
 }
 
-#[derive(Clone, Encode, Decode)]
-pub(crate) enum Diff {
+/// A single change of the workbook.
+///
+/// Every diff has all the information needed to apply it and to undo it, `old_value` fields
+/// hold the state before the change.
+/// See the [diffs](crate::diffs) module for the wire format.
+#[derive(Clone, Encode, Decode, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum Diff {
     // Cell diffs
+    /// The user input of a cell was set
     SetCellValue {
         sheet: u32,
         row: i32,
@@ -26,12 +60,14 @@ pub(crate) enum Diff {
         new_value: String,
         old_value: Box<Option<Cell>>,
     },
+    /// The content of a cell was cleared, keeping the style
     CellClearContents {
         sheet: u32,
         row: i32,
         column: i32,
         old_value: Box<Option<Cell>>,
     },
+    /// The content and style of a cell were cleared
     CellClearAll {
         sheet: u32,
         row: i32,
@@ -39,12 +75,14 @@ pub(crate) enum Diff {
         old_value: Box<Option<Cell>>,
         old_style: Box<Style>,
     },
+    /// The style of a cell was cleared
     CellClearFormatting {
         sheet: u32,
         row: i32,
         column: i32,
         old_style: Box<Option<Style>>,
     },
+    /// The style of a cell was set
     SetCellStyle {
         sheet: u32,
         row: i32,
@@ -53,110 +91,122 @@ pub(crate) enum Diff {
         new_value: Box<Style>,
     },
     // Column and Row diffs
+    /// The width of a column was set
     SetColumnWidth {
         sheet: u32,
         column: i32,
         new_value: f64,
         old_value: f64,
     },
+    /// The height of a row was set
     SetRowHeight {
         sheet: u32,
         row: i32,
         new_value: f64,
         old_value: f64,
     },
+    /// The style of a full column was set
     SetColumnStyle {
         sheet: u32,
         column: i32,
         old_value: Box<Option<Style>>,
         new_value: Box<Style>,
     },
+    /// The style of a full row was set
     SetRowStyle {
         sheet: u32,
         row: i32,
         old_value: Box<Option<Style>>,
         new_value: Box<Style>,
     },
+    /// The style of a full column was removed
     DeleteColumnStyle {
         sheet: u32,
         column: i32,
         old_value: Box<Option<Style>>,
     },
+    /// The style of a full row was removed
     DeleteRowStyle {
         sheet: u32,
         row: i32,
         old_value: Box<Option<Style>>,
     },
-    InsertRows {
-        sheet: u32,
-        row: i32,
-        count: i32,
-    },
+    /// `count` rows were inserted before `row`
+    InsertRows { sheet: u32, row: i32, count: i32 },
+    /// `count` rows were deleted starting at `row`
     DeleteRows {
         sheet: u32,
         row: i32,
         count: i32,
         old_data: Vec<RowData>,
+        old_merged_cells: Vec<MergedRange>,
     },
-    InsertColumns {
-        sheet: u32,
-        column: i32,
-        count: i32,
-    },
+    /// `count` columns were inserted before `column`
+    InsertColumns { sheet: u32, column: i32, count: i32 },
+    /// `count` columns were deleted starting at `column`
     DeleteColumns {
         sheet: u32,
         column: i32,
         count: i32,
         old_data: Vec<ColumnData>,
+        old_merged_cells: Vec<MergedRange>,
     },
+    /// A sheet was deleted
     DeleteSheet {
         sheet: u32,
         old_data: Box<Worksheet>,
     },
+    /// The number of frozen rows was set
     SetFrozenRowsCount {
         sheet: u32,
         new_value: i32,
         old_value: i32,
     },
+    /// The number of frozen columns was set
     SetFrozenColumnsCount {
         sheet: u32,
         new_value: i32,
         old_value: i32,
     },
-    NewSheet {
-        index: u32,
-        name: String,
-    },
+    /// A sheet was added at position `index`
+    NewSheet { index: u32, name: String },
+    /// A sheet was renamed
     RenameSheet {
         index: u32,
         old_value: String,
         new_value: String,
     },
+    /// The tab color of a sheet was set
     SetSheetColor {
         index: u32,
         old_value: String,
         new_value: String,
     },
+    /// A sheet was hidden or shown
     SetSheetState {
         index: u32,
         old_value: SheetState,
         new_value: SheetState,
     },
+    /// The grid lines of a sheet were shown or hidden
     SetShowGridLines {
         sheet: u32,
         old_value: bool,
         new_value: bool,
     },
+    /// A defined name was created
     CreateDefinedName {
         name: String,
         scope: Option<u32>,
         value: String,
     },
+    /// A defined name was deleted
     DeleteDefinedName {
         name: String,
         scope: Option<u32>,
         old_value: String,
     },
+    /// A defined name was modified
     UpdateDefinedName {
         name: String,
         scope: Option<u32>,
@@ -165,70 +215,300 @@ pub(crate) enum Diff {
         new_scope: Option<u32>,
         new_formula: String,
     },
-    MoveColumn {
-        sheet: u32,
-        column: i32,
-        delta: i32,
-    },
-    MoveRow {
-        sheet: u32,
-        row: i32,
-        delta: i32,
-    },
+    /// A column was moved `delta` positions
+    MoveColumn { sheet: u32, column: i32, delta: i32 },
+    /// A row was moved `delta` positions
+    MoveRow { sheet: u32, row: i32, delta: i32 },
+    /// The locale of the workbook was set
     SetLocale {
         old_value: String,
         new_value: String,
     },
+    /// The timezone of the workbook was set
     SetTimezone {
         old_value: String,
         new_value: String,
     },
-    // FIXME: we are missing SetViewDiffs
+    /// The date system of the workbook was set. `old_data` are the cells with dates that were
+    /// shifted to keep showing the same date.
+    SetDateSystem {
+        old_value: DateSystem,
+        new_value: DateSystem,
+        old_data: Vec<CellData>,
+    },
+    /// The custom lists used by autofill were set
+    SetCustomLists {
+        old_value: Vec<Vec<String>>,
+        new_value: Vec<Vec<String>>,
+    },
+    /// The theme of the workbook was set
+    SetTheme {
+        old_value: Box<Theme>,
+        new_value: Box<Theme>,
+    },
+    /// A row was hidden or shown
+    SetRowHidden {
+        sheet: u32,
+        row: i32,
+        new_value: bool,
+        old_value: bool,
+    },
+    /// A column was hidden or shown
+    SetColumnHidden {
+        sheet: u32,
+        column: i32,
+        new_value: bool,
+        old_value: bool,
+    },
+    /// The outline (grouping) level of a row was set
+    SetRowOutlineLevel {
+        sheet: u32,
+        row: i32,
+        new_value: u8,
+        old_value: u8,
+    },
+    /// The outline (grouping) level of a column was set
+    SetColumnOutlineLevel {
+        sheet: u32,
+        column: i32,
+        new_value: u8,
+        old_value: u8,
+    },
+    /// An outline group of rows was collapsed or expanded
+    SetRowCollapsed {
+        sheet: u32,
+        row: i32,
+        new_value: bool,
+        old_value: bool,
+    },
+    /// An outline group of columns was collapsed or expanded
+    SetColumnCollapsed {
+        sheet: u32,
+        column: i32,
+        new_value: bool,
+        old_value: bool,
+    },
+    /// The position of the summary rows of the outline groups was set
+    SetOutlineSummaryBelow {
+        sheet: u32,
+        new_value: bool,
+        old_value: bool,
+    },
+    /// The position of the summary columns of the outline groups was set
+    SetOutlineSummaryRight {
+        sheet: u32,
+        new_value: bool,
+        old_value: bool,
+    },
+    /// The print settings of a sheet were set
+    SetPageSetup {
+        sheet: u32,
+        new_value: Box<PageSetup>,
+        old_value: Box<PageSetup>,
+    },
+    /// The print area of a sheet was set
+    SetPrintArea {
+        sheet: u32,
+        new_value: Option<String>,
+        old_value: Option<String>,
+    },
+    /// The rows and columns printed on every page were set
+    SetPrintTitles {
+        sheet: u32,
+        new_value: Option<String>,
+        old_value: Option<String>,
+    },
+    /// A sheet was protected or unprotected
+    SetSheetProtection {
+        sheet: u32,
+        new_value: Box<Option<SheetProtection>>,
+        old_value: Box<Option<SheetProtection>>,
+    },
+    /// The workbook was protected or unprotected
+    SetWorkbookProtection {
+        new_value: Option<WorkbookProtection>,
+        old_value: Option<WorkbookProtection>,
+    },
+    /// The AutoFilter of a sheet was set
+    SetAutoFilter {
+        sheet: u32,
+        new_value: Box<Option<AutoFilter>>,
+        old_value: Box<Option<AutoFilter>>,
+    },
+    /// The AutoFilter of a table was set
+    SetTableAutoFilter {
+        table: String,
+        new_value: Box<Option<AutoFilter>>,
+        old_value: Box<Option<AutoFilter>>,
+    },
+    /// The blocks of merged cells of a sheet were set
+    SetMergedCells {
+        sheet: u32,
+        new_value: Vec<MergedRange>,
+        old_value: Vec<MergedRange>,
+    },
+    /// A range was sorted
+    SortRange {
+        sheet: u32,
+        row: i32,
+        column: i32,
+        width: i32,
+        height: i32,
+        keys: Vec<SortKey>,
+        // The cells of the range before sorting, row by row
+        old_data: Vec<Vec<Option<Cell>>>,
+    },
+    /// A block of cells was moved `row_delta` rows and `column_delta` columns, updating the
+    /// references to it. `old_data` are all the cells of the workbook that changed.
+    MoveCells {
+        sheet: u32,
+        row: i32,
+        column: i32,
+        width: i32,
+        height: i32,
+        row_delta: i32,
+        column_delta: i32,
+        old_data: Vec<CellData>,
+        old_merged_cells: Vec<MergedRange>,
+        old_comments: Vec<Comment>,
+    },
+    /// A cell was set to a rich text
+    SetCellRichText {
+        sheet: u32,
+        row: i32,
+        column: i32,
+        new_value: Vec<TextRun>,
+        old_value: Box<Option<Cell>>,
+    },
+    /// A named cell style was created
+    CreateNamedStyle {
+        name: String,
+        builtin_id: Option<i32>,
+        style: Box<Style>,
+    },
+    /// A named cell style was modified
+    UpdateNamedStyle {
+        name: String,
+        old_value: Box<Style>,
+        new_value: Box<Style>,
+    },
+    /// A named cell style was deleted, `linked_styles` were the styles linked to it
+    DeleteNamedStyle {
+        name: String,
+        builtin_id: Option<i32>,
+        old_value: Box<Style>,
+        // The styles that were linked to the named style
+        linked_styles: Vec<i32>,
+    },
+    /// The sheet at `sheet` was copied right after it with the name `new_name`
+    DuplicateSheet { sheet: u32, new_name: String },
+    /// The sheet at `from` was moved to `to`
+    MoveSheet { from: u32, to: u32 },
 }
 
 pub(crate) type DiffList = Vec<Diff>;
 
+/// A user action in the undo/redo stacks
+#[derive(Clone)]
+pub(crate) struct HistoryEntry {
+    pub(crate) diff_list: DiffList,
+    // The selection and scroll position before the action was made, restored on undo.
+    // Views are local to the user, they are never sent to other models.
+    pub(crate) view: SelectedView,
+    // The selection and scroll position after the action was made, restored on redo
+    pub(crate) redo_view: SelectedView,
+    // Time of the last keystroke if the entry is the user typing in cells
+    pub(crate) typing_time: Option<i64>,
+}
+
 #[derive(Default)]
 pub(crate) struct History {
-    pub(crate) undo_stack: Vec<DiffList>,
-    pub(crate) redo_stack: Vec<DiffList>,
+    pub(crate) undo_stack: Vec<HistoryEntry>,
+    pub(crate) redo_stack: Vec<HistoryEntry>,
+    // Maximum number of entries in the undo stack, unlimited if None
+    pub(crate) max_depth: Option<usize>,
+    // Consecutive typing within this number of milliseconds is a single entry, 0 to disable
+    pub(crate) typing_interval: i64,
 }
 
 impl History {
-    pub fn push(&mut self, diff_list: DiffList) {
-        self.undo_stack.push(diff_list);
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.undo_stack.push(entry);
         self.redo_stack = vec![];
+        self.truncate();
+    }
+
+    /// Adds the typing to the last entry if it was typing too and recent enough
+    pub fn push_typing(&mut self, entry: HistoryEntry) {
+        if let (Some(time), true) = (entry.typing_time, self.redo_stack.is_empty()) {
+            if let Some(last) = self.undo_stack.last_mut() {
+                if let Some(last_time) = last.typing_time {
+                    if time - last_time <= self.typing_interval
+                        && last.view.sheet == entry.view.sheet
+                    {
+                        last.diff_list.extend(entry.diff_list);
+                        last.redo_view = entry.redo_view;
+                        last.typing_time = Some(time);
+                        return;
+                    }
+                }
+            }
+        }
+        self.push(entry);
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+        self.truncate();
+    }
+
+    // Drops the oldest entries over the limit
+    fn truncate(&mut self) {
+        if let Some(max_depth) = self.max_depth {
+            if self.undo_stack.len() > max_depth {
+                let excess = self.undo_stack.len() - max_depth;
+                self.undo_stack.drain(0..excess);
+            }
+        }
     }
 
-    pub fn undo(&mut self) -> Option<Vec<Diff>> {
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
         match self.undo_stack.pop() {
-            Some(diff_list) => {
-                self.redo_stack.push(diff_list.clone());
-                Some(diff_list)
+            Some(mut entry) => {
+                // Typing after an undo never goes into a redone entry
+                entry.typing_time = None;
+                self.redo_stack.push(entry.clone());
+                Some(entry)
             }
             None => None,
         }
     }
 
-    pub fn redo(&mut self) -> Option<Vec<Diff>> {
+    pub fn redo(&mut self) -> Option<HistoryEntry> {
         match self.redo_stack.pop() {
-            Some(diff_list) => {
-                self.undo_stack.push(diff_list.clone());
-                Some(diff_list)
+            Some(entry) => {
+                self.undo_stack.push(entry.clone());
+                Some(entry)
             }
             None => None,
         }
     }
 }
 
-#[derive(Clone, Encode, Decode)]
+/// Whether a list of diffs is applied forwards (redo) or backwards (undo)
+#[derive(Clone, Encode, Decode, Debug, PartialEq)]
 pub enum DiffType {
+    /// The diffs are undone, in reverse order
     Undo,
+    /// The diffs are applied in order
     Redo,
 }
 
-#[derive(Clone, Encode, Decode)]
+/// A list of diffs that is applied as a whole, i.e. a user action or its undo
+#[derive(Clone, Encode, Decode, Debug, PartialEq)]
 pub struct QueueDiffs {
+    /// Whether the list is applied or undone
     pub r#type: DiffType,
-    pub list: DiffList,
+    /// The diffs of the list
+    pub list: Vec<Diff>,
 }
@@ -0,0 +1,1003 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    number_format::to_excel_precision_str,
+    types::{
+        Alignment, BorderItem, BorderStyle, Color, FontUnderline, HorizontalAlignment, MergedRange,
+        PatternType, Style, Theme, VerticalAlignment,
+    },
+};
+
+use super::{
+    common::{ClipboardCell, ClipboardData},
+    UserModel,
+};
+
+impl UserModel<'_> {
+    // Returns the range as an html table. Styles are inlined and merged cells use colspan/rowspan.
+    pub(crate) fn range_to_html(
+        &self,
+        sheet: u32,
+        range: (i32, i32, i32, i32),
+    ) -> Result<String, String> {
+        let (row_start, column_start, row_end, column_end) = range;
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        // Merged cells are clipped to the range, the first cell spans over the rest
+        let mut spans = HashMap::new();
+        let mut covered = HashSet::new();
+        for merged in &worksheet.merge_cells {
+            let first_row = merged.first_row.max(row_start);
+            let first_column = merged.first_column.max(column_start);
+            let last_row = merged.last_row.min(row_end);
+            let last_column = merged.last_column.min(column_end);
+            if first_row > last_row || first_column > last_column {
+                continue;
+            }
+            spans.insert(
+                (first_row, first_column),
+                (last_row - first_row + 1, last_column - first_column + 1),
+            );
+            for row in first_row..=last_row {
+                for column in first_column..=last_column {
+                    if (row, column) != (first_row, first_column) {
+                        covered.insert((row, column));
+                    }
+                }
+            }
+        }
+
+        let mut html = "<table style=\"border-collapse:collapse\"><colgroup>".to_string();
+        for column in column_start..=column_end {
+            let width = self.get_column_width(sheet, column)?;
+            html.push_str(&format!("<col style=\"width:{width}px\">"));
+        }
+        html.push_str("</colgroup><tbody>");
+        for row in row_start..=row_end {
+            let height = self.get_row_height(sheet, row)?;
+            html.push_str(&format!("<tr style=\"height:{height}px\">"));
+            for column in column_start..=column_end {
+                if covered.contains(&(row, column)) {
+                    continue;
+                }
+                html.push_str("<td");
+                if let Some((rowspan, colspan)) = spans.get(&(row, column)) {
+                    if *rowspan > 1 {
+                        html.push_str(&format!(" rowspan=\"{rowspan}\""));
+                    }
+                    if *colspan > 1 {
+                        html.push_str(&format!(" colspan=\"{colspan}\""));
+                    }
+                }
+                let style = self.model.get_style_for_cell(sheet, row, column)?;
+                let text = self.get_formatted_cell_value(sheet, row, column)?;
+                html.push_str(&format!(
+                    " style=\"{}\">{}</td>",
+                    escape_html(&style_to_css(&style, self.model.get_theme())),
+                    escape_html(&text).replace('\n', "<br>")
+                ));
+            }
+            html.push_str("</tr>");
+        }
+        html.push_str("</tbody></table>");
+        Ok(html)
+    }
+
+    /// Pastes the first table of an html fragment at the selected cell.
+    ///
+    /// Styles are read from the inline `style` attributes and from the `<style>` blocks, so tables
+    /// copied from other spreadsheets (Excel, Google Sheets) or web pages keep their formatting.
+    /// Cells spanning several rows or columns are pasted as merged cells.
+    ///
+    /// See also:
+    /// * [UserModel::paste_from_clipboard]
+    /// * [UserModel::merge_cells]
+    pub fn paste_html_string(&mut self, html: &str) -> Result<(), String> {
+        let (data, spans, height, width) = self.parse_html_table(html)?;
+        let view = self.get_selected_view();
+        let sheet = view.sheet;
+        let [selected_row, selected_column, _, _] = view.range;
+        let mut diff_list = Vec::new();
+        let (max_row, max_column) =
+            self.paste_clipboard_data(sheet, (1, 1, height, width), &data, false, &mut diff_list)?;
+        let ranges: Vec<MergedRange> = spans
+            .iter()
+            .map(|span| MergedRange {
+                first_row: span.first_row + selected_row - 1,
+                first_column: span.first_column + selected_column - 1,
+                last_row: span.last_row + selected_row - 1,
+                last_column: span.last_column + selected_column - 1,
+            })
+            .collect();
+        if !ranges.is_empty() {
+            if let Err(message) = self.merge_ranges_in_list(sheet, &ranges, &mut diff_list) {
+                // Leave the sheet as it was before the paste
+                self.apply_undo_diff_list(&diff_list)?;
+                return Err(message);
+            }
+        }
+        self.push_diff_list(diff_list);
+        self.set_selected_range(selected_row, selected_column, max_row, max_column)?;
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // Returns the cells of the first table in the html, starting at row 1 and column 1,
+    // the blocks of cells spanning several rows or columns and the number of rows and columns
+    // of the table
+    fn parse_html_table(
+        &self,
+        html: &str,
+    ) -> Result<(ClipboardData, Vec<MergedRange>, i32, i32), String> {
+        let tokens = tokenize(html);
+        let mut rules = CssRules::default();
+        for token in &tokens {
+            if let Token::StyleSheet(css) = token {
+                rules.add_style_sheet(css);
+            }
+        }
+
+        let mut rows: Vec<Vec<HtmlCell>> = Vec::new();
+        let mut cell: Option<HtmlCell> = None;
+        // Nesting level of tables, the contents of nested tables are read as text
+        let mut depth = 0;
+        for token in tokens {
+            match token {
+                Token::Tag(tag) if tag.name == "table" => {
+                    if tag.closing {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        depth += 1;
+                    }
+                }
+                _ if depth == 0 => {}
+                Token::Tag(tag) if depth == 1 && tag.name == "tr" => {
+                    if let Some(c) = cell.take() {
+                        push_cell(&mut rows, c);
+                    }
+                    if !tag.closing {
+                        rows.push(Vec::new());
+                    }
+                }
+                Token::Tag(tag) if depth == 1 && (tag.name == "td" || tag.name == "th") => {
+                    if let Some(c) = cell.take() {
+                        push_cell(&mut rows, c);
+                    }
+                    if !tag.closing {
+                        cell = Some(HtmlCell::new(&tag, &rules));
+                    }
+                }
+                Token::Tag(tag) => {
+                    if let Some(c) = cell.as_mut() {
+                        c.apply_tag(&tag, &rules);
+                    }
+                }
+                Token::Text(text) => {
+                    if let Some(c) = cell.as_mut() {
+                        c.push_text(&text);
+                    }
+                }
+                Token::StyleSheet(_) => {}
+            }
+        }
+        if let Some(c) = cell.take() {
+            push_cell(&mut rows, c);
+        }
+        if rows.iter().all(|r| r.is_empty()) {
+            return Err("No table found".to_string());
+        }
+
+        // Places the cells in the grid taking into account the cells spanning several rows
+        let decimal = &self.model.locale.numbers.symbols.decimal;
+        let mut data: ClipboardData = HashMap::new();
+        let mut spans = Vec::new();
+        let mut width = 0;
+        let height = rows.len() as i32;
+        for (index, html_row) in rows.into_iter().enumerate() {
+            let row = index as i32 + 1;
+            let mut column = 1;
+            for html_cell in html_row {
+                while data.get(&row).is_some_and(|r| r.contains_key(&column)) {
+                    column += 1;
+                }
+                let text = html_cell.value(decimal);
+                if html_cell.rowspan > 1 || html_cell.colspan > 1 {
+                    spans.push(MergedRange {
+                        first_row: row,
+                        first_column: column,
+                        last_row: row + html_cell.rowspan - 1,
+                        last_column: column + html_cell.colspan - 1,
+                    });
+                }
+                for r in row..row + html_cell.rowspan {
+                    for c in column..column + html_cell.colspan {
+                        let text = if (r, c) == (row, column) {
+                            text.clone()
+                        } else {
+                            String::new()
+                        };
+                        data.entry(r).or_default().insert(
+                            c,
+                            ClipboardCell {
+                                text,
+                                style: html_cell.style.clone(),
+                            },
+                        );
+                    }
+                }
+                column += html_cell.colspan;
+                width = width.max(column - 1);
+            }
+        }
+        let height = data.keys().copied().max().unwrap_or(0).max(height);
+        Ok((data, spans, height, width))
+    }
+}
+
+fn push_cell(rows: &mut Vec<Vec<HtmlCell>>, cell: HtmlCell) {
+    match rows.last_mut() {
+        Some(row) => row.push(cell),
+        // A cell outside of a row starts one
+        None => rows.push(vec![cell]),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => {
+                    let code = if let Some(hex) = entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                    {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(decimal) = entity.strip_prefix('#') {
+                        decimal.parse::<u32>().ok()
+                    } else {
+                        None
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+struct Tag {
+    // lower case
+    name: String,
+    closing: bool,
+    // The names are lower case, the values are decoded
+    attributes: HashMap<String, String>,
+}
+
+enum Token {
+    Tag(Tag),
+    Text(String),
+    // The contents of a <style> element
+    StyleSheet(String),
+}
+
+// Splits the html in tags and texts. This is not a full html parser, just enough to read tables.
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = match comment.find("-->") {
+                Some(end) => &comment[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            // doctype, processing instructions and conditional comments
+            rest = match rest.find('>') {
+                Some(end) => &rest[end + 1..],
+                None => "",
+            };
+            continue;
+        }
+        if rest.starts_with('<') {
+            if let Some((tag, length)) = parse_tag(rest) {
+                rest = &rest[length..];
+                if !tag.closing && (tag.name == "style" || tag.name == "script") {
+                    let end_tag = format!("</{}", tag.name);
+                    let end = rest
+                        .to_ascii_lowercase()
+                        .find(&end_tag)
+                        .unwrap_or(rest.len());
+                    if tag.name == "style" {
+                        tokens.push(Token::StyleSheet(rest[..end].to_string()));
+                    }
+                    rest = &rest[end..];
+                    rest = match rest.find('>') {
+                        Some(end) => &rest[end + 1..],
+                        None => "",
+                    };
+                } else {
+                    tokens.push(Token::Tag(tag));
+                }
+                continue;
+            }
+        }
+        // A '<' that does not start a tag is text
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '<')
+            .map_or(rest.len(), |(i, _)| i);
+        tokens.push(Token::Text(decode_entities(&rest[..end])));
+        rest = &rest[end..];
+    }
+    tokens
+}
+
+// Parses the tag at the beginning of `text`, returns the tag and its length in bytes
+fn parse_tag(text: &str) -> Option<(Tag, usize)> {
+    let mut chars = text.char_indices().skip(1).peekable();
+    let closing = matches!(chars.peek(), Some((_, '/')));
+    if closing {
+        chars.next();
+    }
+    let mut name = String::new();
+    while let Some((_, c)) = chars.peek() {
+        if c.is_ascii_alphanumeric() || *c == ':' || *c == '-' {
+            name.push(c.to_ascii_lowercase());
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    if name.is_empty() {
+        return None;
+    }
+    let mut attributes = HashMap::new();
+    loop {
+        while chars
+            .next_if(|(_, c)| c.is_whitespace() || *c == '/')
+            .is_some()
+        {}
+        let (index, c) = chars.next()?;
+        if c == '>' {
+            return Some((
+                Tag {
+                    name,
+                    closing,
+                    attributes,
+                },
+                index + 1,
+            ));
+        }
+        let mut attribute = c.to_ascii_lowercase().to_string();
+        while let Some((_, c)) =
+            chars.next_if(|(_, c)| !c.is_whitespace() && !matches!(c, '=' | '>' | '/'))
+        {
+            attribute.push(c.to_ascii_lowercase());
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if(|(_, c)| *c == '=').is_some() {
+            while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+            match chars.next_if(|(_, c)| *c == '"' || *c == '\'') {
+                Some((_, quote)) => loop {
+                    let (_, c) = chars.next()?;
+                    if c == quote {
+                        break;
+                    }
+                    value.push(c);
+                },
+                None => {
+                    while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && *c != '>')
+                    {
+                        value.push(c);
+                    }
+                }
+            }
+        }
+        attributes.insert(attribute, decode_entities(&value));
+    }
+}
+
+// Splits a list of css declarations (`name: value; name: value`)
+fn parse_declarations(css: &str) -> Vec<(String, String)> {
+    let mut declarations = Vec::new();
+    let mut declaration = String::new();
+    let mut quote = None;
+    let mut chars = css.chars();
+    let mut push = |declaration: &str| {
+        if let Some((name, value)) = declaration.split_once(':') {
+            declarations.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                declaration.push(c);
+                if let Some(next) = chars.next() {
+                    declaration.push(next);
+                }
+            }
+            '"' | '\'' if quote.is_none() => {
+                quote = Some(c);
+                declaration.push(c);
+            }
+            c if Some(c) == quote => {
+                quote = None;
+                declaration.push(c);
+            }
+            ';' if quote.is_none() => {
+                push(&declaration);
+                declaration.clear();
+            }
+            _ => declaration.push(c),
+        }
+    }
+    push(&declaration);
+    declarations
+}
+
+// The rules of the <style> blocks that apply to cells
+#[derive(Default)]
+struct CssRules {
+    // Rules for all the cells (`td {...}`)
+    cells: Vec<(String, String)>,
+    // Rules by class name (`.xl65 {...}`, `td.xl65 {...}`)
+    classes: HashMap<String, Vec<(String, String)>>,
+}
+
+impl CssRules {
+    fn add_style_sheet(&mut self, css: &str) {
+        let mut css = css.replace("<!--", "").replace("-->", "");
+        // Removes the comments
+        while let Some(start) = css.find("/*") {
+            match css[start..].find("*/") {
+                Some(end) => css.replace_range(start..start + end + 2, ""),
+                None => css.truncate(start),
+            }
+        }
+        for rule in css.split('}') {
+            let (selectors, body) = match rule.split_once('{') {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let declarations = parse_declarations(body);
+            for selector in selectors.split(',') {
+                let selector = selector.trim().to_ascii_lowercase();
+                if selector == "td" || selector == "th" {
+                    self.cells.extend(declarations.iter().cloned());
+                } else if let Some((element, class)) = selector.rsplit_once('.') {
+                    if matches!(element, "" | "td" | "th" | "font" | "span") {
+                        self.classes
+                            .entry(class.to_string())
+                            .or_default()
+                            .extend(declarations.iter().cloned());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// A cell read from an html table
+struct HtmlCell {
+    text: String,
+    // The raw number if the spreadsheet that wrote the html gave it
+    number: Option<f64>,
+    style: Style,
+    rowspan: i32,
+    colspan: i32,
+}
+
+impl HtmlCell {
+    fn new(tag: &Tag, rules: &CssRules) -> HtmlCell {
+        let mut style = Style::default();
+        for (name, value) in &rules.cells {
+            apply_declaration(&mut style, name, value);
+        }
+        let span = |name: &str| {
+            tag.attributes
+                .get(name)
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .map_or(1, |v| v.clamp(1, 1000))
+        };
+        // Excel writes `x:num`, Google Sheets the value as json in `data-sheets-value`
+        let number = tag
+            .attributes
+            .get("x:num")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .or_else(|| {
+                let value = tag.attributes.get("data-sheets-value")?;
+                let json: serde_json::Value = serde_json::from_str(value).ok()?;
+                json.get("3")?.as_f64()
+            });
+        let mut cell = HtmlCell {
+            text: String::new(),
+            number,
+            style,
+            rowspan: span("rowspan"),
+            colspan: span("colspan"),
+        };
+        if tag.name == "th" {
+            cell.style.font.b = true;
+        }
+        cell.apply_tag(tag, rules);
+        cell
+    }
+
+    // Applies the formatting of the tag to the whole cell
+    fn apply_tag(&mut self, tag: &Tag, rules: &CssRules) {
+        if tag.closing {
+            if matches!(tag.name.as_str(), "p" | "div") {
+                self.new_line();
+            }
+            return;
+        }
+        let style = &mut self.style;
+        match tag.name.as_str() {
+            "br" => self.new_line(),
+            "b" | "strong" => style.font.b = true,
+            "i" | "em" => style.font.i = true,
+            "u" => style.font.underline = FontUnderline::Single,
+            "s" | "strike" | "del" => style.font.strike = true,
+            _ => {}
+        }
+        let style = &mut self.style;
+        if let Some(color) = tag.attributes.get("color").and_then(|c| parse_color(c)) {
+            style.font.color = Some(Color::Rgb(color));
+        }
+        if let Some(color) = tag.attributes.get("bgcolor").and_then(|c| parse_color(c)) {
+            style.fill.pattern_type = PatternType::Solid;
+            style.fill.fg_color = Some(Color::Rgb(color));
+        }
+        if let Some(align) = tag.attributes.get("align") {
+            apply_declaration(style, "text-align", align);
+        }
+        if let Some(valign) = tag.attributes.get("valign") {
+            apply_declaration(style, "vertical-align", valign);
+        }
+        if let Some(classes) = tag.attributes.get("class") {
+            for class in classes.split_whitespace() {
+                if let Some(declarations) = rules.classes.get(&class.to_ascii_lowercase()) {
+                    for (name, value) in declarations {
+                        apply_declaration(style, name, value);
+                    }
+                }
+            }
+        }
+        if let Some(css) = tag.attributes.get("style") {
+            for (name, value) in parse_declarations(css) {
+                apply_declaration(style, &name, &value);
+            }
+        }
+    }
+
+    // White space is collapsed as browsers do
+    fn push_text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_ascii_whitespace() {
+                if !self.text.is_empty() && !self.text.ends_with([' ', '\n']) {
+                    self.text.push(' ');
+                }
+            } else {
+                self.text.push(c);
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        let length = self.text.trim_end_matches(' ').len();
+        self.text.truncate(length);
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+    }
+
+    // The user input for the cell
+    fn value(&self, decimal: &str) -> String {
+        let text = self.text.replace('\u{a0}', " ");
+        let text = text
+            .split('\n')
+            .map(str::trim)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let text = text.trim();
+        if let Some(number) = self.number {
+            return to_excel_precision_str(number).replace('.', decimal);
+        }
+        // Texts that look like formulas and cells formatted as text are entered as text
+        if !text.is_empty() && (text.starts_with('=') || self.style.num_fmt == "@") {
+            return format!("'{text}");
+        }
+        text.to_string()
+    }
+}
+
+fn apply_declaration(style: &mut Style, name: &str, value: &str) {
+    let value = value.trim_end_matches("!important").trim();
+    let lower = value.to_ascii_lowercase();
+    match name {
+        "font-weight" => {
+            style.font.b = matches!(lower.as_str(), "bold" | "bolder")
+                || lower.parse::<i32>().is_ok_and(|weight| weight >= 600);
+        }
+        "font-style" => style.font.i = lower == "italic" || lower == "oblique",
+        "text-decoration" | "text-decoration-line" => {
+            style.font.underline = if lower.contains("underline") {
+                FontUnderline::Single
+            } else {
+                FontUnderline::None
+            };
+            style.font.strike = lower.contains("line-through");
+        }
+        "color" => {
+            if let Some(color) = parse_color(&lower) {
+                style.font.color = Some(Color::Rgb(color));
+            }
+        }
+        "background" | "background-color" => {
+            // The background shorthand can have images, positions,...
+            if let Some(color) = normalize_spaces(&lower)
+                .split_whitespace()
+                .find_map(parse_color)
+            {
+                style.fill.pattern_type = PatternType::Solid;
+                style.fill.fg_color = Some(Color::Rgb(color));
+            }
+        }
+        "font-size" => {
+            if let Some(sz) = parse_length(&lower).map(|points| points.round() as i32) {
+                if sz > 0 {
+                    style.font.sz = sz;
+                }
+            }
+        }
+        "font-family" => {
+            let family = value
+                .split(',')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .trim_matches(['"', '\'']);
+            if !family.is_empty() {
+                style.font.name = family.to_string();
+            }
+        }
+        "text-align" => {
+            let horizontal = match lower.as_str() {
+                "left" | "start" => HorizontalAlignment::Left,
+                "right" | "end" => HorizontalAlignment::Right,
+                "center" => HorizontalAlignment::Center,
+                "justify" => HorizontalAlignment::Justify,
+                "general" => HorizontalAlignment::General,
+                _ => return,
+            };
+            alignment(style).horizontal = horizontal;
+        }
+        "vertical-align" => {
+            let vertical = match lower.as_str() {
+                "top" => VerticalAlignment::Top,
+                "middle" => VerticalAlignment::Center,
+                "bottom" => VerticalAlignment::Bottom,
+                _ => return,
+            };
+            alignment(style).vertical = vertical;
+        }
+        "white-space" => {
+            alignment(style).wrap_text = matches!(
+                lower.as_str(),
+                "normal" | "pre-wrap" | "pre-line" | "break-spaces"
+            );
+        }
+        "border" => {
+            let border = parse_border(&lower);
+            style.border.top = border.clone();
+            style.border.right = border.clone();
+            style.border.bottom = border.clone();
+            style.border.left = border;
+        }
+        "border-top" => style.border.top = parse_border(&lower),
+        "border-right" => style.border.right = parse_border(&lower),
+        "border-bottom" => style.border.bottom = parse_border(&lower),
+        "border-left" => style.border.left = parse_border(&lower),
+        "mso-number-format" => {
+            let format = value.trim_matches(['"', '\'']);
+            let num_fmt = match format {
+                "General" => "general".to_string(),
+                "Percent" => "0.00%".to_string(),
+                "Fixed" => "0.00".to_string(),
+                "Standard" => "#,##0.00".to_string(),
+                "Scientific" => "0.00E+00".to_string(),
+                // Other named formats ("Short Date", "Long Time",...) depend on the locale
+                _ if format.starts_with(|c: char| c.is_ascii_uppercase())
+                    && format.chars().all(|c| c.is_ascii_alphabetic() || c == ' ') =>
+                {
+                    return
+                }
+                _ => unescape_css(format),
+            };
+            style.num_fmt = num_fmt;
+        }
+        _ => {}
+    }
+}
+
+fn alignment(style: &mut Style) -> &mut Alignment {
+    style.alignment.get_or_insert_with(Alignment::default)
+}
+
+fn unescape_css(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                result.push(next);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Removes the spaces inside the parenthesis, `rgb(1, 2, 3)` => `rgb(1,2,3)`
+fn normalize_spaces(value: &str) -> String {
+    let mut result = String::new();
+    let mut depth = 0;
+    for c in value.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth > 0 => continue,
+            _ => {}
+        }
+        result.push(c);
+    }
+    result
+}
+
+// Returns the length in points
+fn parse_length(value: &str) -> Option<f64> {
+    let (number, factor) = if let Some(number) = value.strip_suffix("pt") {
+        (number, 1.0)
+    } else if let Some(number) = value.strip_suffix("px") {
+        (number, 0.75)
+    } else {
+        (value, 0.75)
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|length| length * factor)
+}
+
+// Reads colors like `#F00`, `#FF0000`, `rgb(255, 0, 0)` or `red` as `#FF0000`
+fn parse_color(value: &str) -> Option<String> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return match hex.len() {
+            3 => Some(format!(
+                "#{}",
+                hex.chars()
+                    .flat_map(|c| [c, c])
+                    .collect::<String>()
+                    .to_uppercase()
+            )),
+            6 => Some(format!("#{}", hex.to_uppercase())),
+            _ => None,
+        };
+    }
+    if let Some(arguments) = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+    {
+        let arguments: Vec<&str> = arguments
+            .trim_end_matches(')')
+            .split(',')
+            .map(str::trim)
+            .collect();
+        if arguments.len() < 3 {
+            return None;
+        }
+        // Fully transparent
+        if arguments.get(3).and_then(|a| a.parse::<f64>().ok()) == Some(0.0) {
+            return None;
+        }
+        let mut color = "#".to_string();
+        for argument in &arguments[..3] {
+            let component = argument.parse::<f64>().ok()?.round().clamp(0.0, 255.0) as u8;
+            color.push_str(&format!("{component:02X}"));
+        }
+        return Some(color);
+    }
+    let color = match value.as_str() {
+        "black" | "windowtext" | "auto" => "#000000",
+        "white" | "window" => "#FFFFFF",
+        "red" => "#FF0000",
+        "lime" => "#00FF00",
+        "green" => "#008000",
+        "blue" => "#0000FF",
+        "yellow" => "#FFFF00",
+        "orange" => "#FFA500",
+        "gray" | "grey" => "#808080",
+        "silver" => "#C0C0C0",
+        "maroon" => "#800000",
+        "navy" => "#000080",
+        "purple" => "#800080",
+        "teal" => "#008080",
+        "olive" => "#808000",
+        "aqua" | "cyan" => "#00FFFF",
+        "fuchsia" | "magenta" => "#FF00FF",
+        _ => return None,
+    };
+    Some(color.to_string())
+}
+
+// Reads a border like `1px solid #000000` or `.5pt dashed windowtext`
+fn parse_border(value: &str) -> Option<BorderItem> {
+    let mut line = None;
+    // CSS default is medium
+    let mut width = 2.25;
+    let mut color = None;
+    for token in normalize_spaces(value).split_whitespace() {
+        match token {
+            "none" | "hidden" => return None,
+            "solid" | "double" | "dotted" | "dashed" | "groove" | "ridge" | "inset" | "outset" => {
+                line = Some(token.to_string())
+            }
+            "thin" => width = 0.75,
+            "medium" => width = 2.25,
+            "thick" => width = 3.75,
+            _ => {
+                if let Some(length) = parse_length(token) {
+                    width = length;
+                } else if let Some(c) = parse_color(token) {
+                    color = Some(c);
+                }
+            }
+        }
+    }
+    let style = match line?.as_str() {
+        "double" => BorderStyle::Double,
+        "dotted" => BorderStyle::Dotted,
+        "dashed" => BorderStyle::MediumDashed,
+        _ if width < 1.5 => BorderStyle::Thin,
+        _ if width < 3.0 => BorderStyle::Medium,
+        _ => BorderStyle::Thick,
+    };
+    if width <= 0.0 {
+        return None;
+    }
+    Some(BorderItem {
+        style,
+        color: Some(Color::Rgb(color.unwrap_or_else(|| "#000000".to_string()))),
+    })
+}
+
+fn style_to_css(style: &Style, theme: &Theme) -> String {
+    let font = &style.font;
+    let mut css = vec![
+        format!("font-family:'{}'", font.name.replace(['\'', '"'], "")),
+        format!("font-size:{}pt", font.sz),
+    ];
+    if let Some(color) = &font.color {
+        css.push(format!("color:{}", color.to_rgb(theme)));
+    }
+    if font.b {
+        css.push("font-weight:bold".to_string());
+    }
+    if font.i {
+        css.push("font-style:italic".to_string());
+    }
+    match (font.is_underlined(), font.strike) {
+        (true, true) => css.push("text-decoration:underline line-through".to_string()),
+        (true, false) => css.push("text-decoration:underline".to_string()),
+        (false, true) => css.push("text-decoration:line-through".to_string()),
+        (false, false) => {}
+    }
+    if style.fill.pattern_type != PatternType::None {
+        if let Some(color) = style
+            .fill
+            .fg_color
+            .as_ref()
+            .or(style.fill.bg_color.as_ref())
+        {
+            css.push(format!("background-color:{}", color.to_rgb(theme)));
+        }
+    }
+    let alignment = style.alignment.clone().unwrap_or_default();
+    let horizontal = match alignment.horizontal {
+        HorizontalAlignment::Left => Some("left"),
+        HorizontalAlignment::Right => Some("right"),
+        HorizontalAlignment::Center | HorizontalAlignment::CenterContinuous => Some("center"),
+        HorizontalAlignment::Justify | HorizontalAlignment::Distributed => Some("justify"),
+        HorizontalAlignment::General | HorizontalAlignment::Fill => None,
+    };
+    if let Some(horizontal) = horizontal {
+        css.push(format!("text-align:{horizontal}"));
+    }
+    let vertical = match alignment.vertical {
+        VerticalAlignment::Top => "top",
+        VerticalAlignment::Bottom => "bottom",
+        VerticalAlignment::Center | VerticalAlignment::Distributed | VerticalAlignment::Justify => {
+            "middle"
+        }
+    };
+    css.push(format!("vertical-align:{vertical}"));
+    if alignment.wrap_text {
+        css.push("white-space:normal".to_string());
+    } else {
+        css.push("white-space:nowrap".to_string());
+    }
+    let border = &style.border;
+    for (side, item) in [
+        ("top", &border.top),
+        ("right", &border.right),
+        ("bottom", &border.bottom),
+        ("left", &border.left),
+    ] {
+        if let Some(item) = item {
+            let (width, line) = match item.style {
+                BorderStyle::Thin => ("1px", "solid"),
+                BorderStyle::Medium => ("2px", "solid"),
+                BorderStyle::Thick => ("3px", "solid"),
+                BorderStyle::Double => ("3px", "double"),
+                BorderStyle::Dotted => ("1px", "dotted"),
+                BorderStyle::MediumDashed
+                | BorderStyle::MediumDashDot
+                | BorderStyle::MediumDashDotDot
+                | BorderStyle::SlantDashDot => ("2px", "dashed"),
+            };
+            let color = match &item.color {
+                Some(color) => color.to_rgb(theme),
+                None => "#000000".to_string(),
+            };
+            css.push(format!("border-{side}:{width} {line} {color}"));
+        }
+    }
+    // Excel reads the number format from this property
+    if !style.num_fmt.eq_ignore_ascii_case("general") {
+        let num_fmt = style.num_fmt.replace('\\', "\\\\").replace('\'', "\\'");
+        css.push(format!("mso-number-format:'{num_fmt}'"));
+    }
+    css.join(";")
+}
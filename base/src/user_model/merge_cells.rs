@@ -0,0 +1,173 @@
+use crate::{
+    expressions::types::Area,
+    types::{Cell, HorizontalAlignment, MergedRange},
+};
+
+use super::{
+    history::{Diff, DiffList},
+    UserModel,
+};
+
+fn get_merged_range(area: &Area) -> MergedRange {
+    MergedRange {
+        first_row: area.row,
+        first_column: area.column,
+        last_row: area.row + area.height - 1,
+        last_column: area.column + area.width - 1,
+    }
+}
+
+impl<'a> UserModel<'a> {
+    /// Returns the blocks of merged cells of the sheet
+    ///
+    /// See also:
+    /// * [Model::get_merged_cells]
+    #[inline]
+    pub fn get_merged_cells(&self, sheet: u32) -> Result<Vec<MergedRange>, String> {
+        self.model.get_merged_cells(sheet)
+    }
+
+    /// Returns the block of merged cells that contains the cell, if any
+    ///
+    /// See also:
+    /// * [Model::get_merged_range]
+    #[inline]
+    pub fn get_merged_range(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<Option<MergedRange>, String> {
+        self.model.get_merged_range(sheet, row, column)
+    }
+
+    /// Merges the cells of the area into a single block.
+    /// Only the content of the top left cell is kept, the rest of the cells are cleared.
+    /// On a protected sheet it fails if any of the cells to clear is locked.
+    pub fn merge_cells(&mut self, area: &Area) -> Result<(), String> {
+        self.merge_ranges(area, &[get_merged_range(area)], false)
+    }
+
+    /// Merges the cells of each row of the area into a block
+    pub fn merge_cells_across(&mut self, area: &Area) -> Result<(), String> {
+        let range = get_merged_range(area);
+        let ranges: Vec<MergedRange> = (range.first_row..=range.last_row)
+            .map(|row| MergedRange {
+                first_row: row,
+                last_row: row,
+                ..range
+            })
+            .collect();
+        self.merge_ranges(area, &ranges, false)
+    }
+
+    /// Merges the cells of the area and centers the text horizontally
+    pub fn merge_and_center(&mut self, area: &Area) -> Result<(), String> {
+        self.merge_ranges(area, &[get_merged_range(area)], true)
+    }
+
+    /// Unmerges all the blocks of merged cells that have at least one cell in the area
+    pub fn unmerge_cells(&mut self, area: &Area) -> Result<(), String> {
+        let sheet = area.sheet;
+        self.check_sheet_allows(sheet, |p| p.format_cells)?;
+        let old_value = self.model.get_merged_cells(sheet)?;
+        self.model.unmerge_range(sheet, &get_merged_range(area))?;
+        let new_value = self.model.get_merged_cells(sheet)?;
+        if new_value != old_value {
+            self.push_diff_list(vec![Diff::SetMergedCells {
+                sheet,
+                new_value,
+                old_value,
+            }]);
+        }
+        Ok(())
+    }
+
+    fn merge_ranges(
+        &mut self,
+        area: &Area,
+        ranges: &[MergedRange],
+        center: bool,
+    ) -> Result<(), String> {
+        let sheet = area.sheet;
+        let mut diff_list = Vec::new();
+        let needs_evaluation = self.merge_ranges_in_list(sheet, ranges, &mut diff_list)?;
+        if center {
+            self.update_range_style_with(
+                area,
+                &|style| {
+                    let mut style = style.clone();
+                    let mut alignment = style.alignment.unwrap_or_default();
+                    alignment.horizontal = HorizontalAlignment::Center;
+                    style.alignment = Some(alignment);
+                    Ok(style)
+                },
+                &mut diff_list,
+            )?;
+        }
+        self.push_diff_list(diff_list);
+        if needs_evaluation {
+            self.evaluate_if_not_paused();
+        }
+        Ok(())
+    }
+
+    // Merges the ranges adding the changes to the diff list.
+    // Returns true if some cell was cleared and the model needs to be evaluated.
+    pub(crate) fn merge_ranges_in_list(
+        &mut self,
+        sheet: u32,
+        ranges: &[MergedRange],
+        diff_list: &mut DiffList,
+    ) -> Result<bool, String> {
+        self.check_sheet_allows(sheet, |p| p.format_cells)?;
+
+        // Only the top left cell of each block keeps its content
+        let mut cleared_cells = Vec::new();
+        for (row, row_data) in &self.model.workbook.worksheet(sheet)?.sheet_data {
+            for (column, cell) in row_data {
+                if matches!(cell, Cell::EmptyCell { .. }) {
+                    continue;
+                }
+                if ranges.iter().any(|range| {
+                    range.contains(*row, *column)
+                        && (*row, *column) != (range.first_row, range.first_column)
+                }) {
+                    cleared_cells.push((*row, *column, cell.clone()));
+                }
+            }
+        }
+        // The content of locked cells can not be cleared
+        for (row, column, _) in &cleared_cells {
+            self.check_cell_editable(sheet, *row, *column)?;
+        }
+
+        let old_value = self.model.get_merged_cells(sheet)?;
+        for range in ranges {
+            if let Err(message) = self.model.merge_range(sheet, *range) {
+                self.model.workbook.worksheet_mut(sheet)?.merge_cells = old_value;
+                return Err(message);
+            }
+        }
+        let new_value = self.model.get_merged_cells(sheet)?;
+        diff_list.push(Diff::SetMergedCells {
+            sheet,
+            new_value,
+            old_value,
+        });
+
+        // We do not need this, but it keeps the diff list deterministic
+        cleared_cells.sort_by_key(|(row, column, _)| (*row, *column));
+        let needs_evaluation = !cleared_cells.is_empty();
+        for (row, column, old_value) in cleared_cells {
+            self.model.cell_clear_contents(sheet, row, column)?;
+            diff_list.push(Diff::CellClearContents {
+                sheet,
+                row,
+                column,
+                old_value: Box::new(Some(old_value)),
+            });
+        }
+        Ok(needs_evaluation)
+    }
+}
@@ -2,12 +2,31 @@
 
 mod border;
 mod border_utils;
+mod cell_tables;
+mod clipboard;
+mod collaboration;
 mod common;
+pub mod diffs;
+mod find;
+mod flash_fill;
 pub(crate) mod history;
+mod html;
+mod merge_cells;
+mod move_range;
+mod outline;
+mod page_setup;
+mod protection;
+mod revert;
 mod sequence_detector;
+mod transform;
 mod ui;
 
+pub use clipboard::{
+    ClipboardPayload, CopiedCell, CopiedValue, PasteMode, PasteOperation, PasteOptions,
+};
+pub use collaboration::CollaborationServer;
 pub use common::UserModel;
+pub use sequence_detector::AutoFillMode;
 
 #[cfg(test)]
 pub use ui::SelectedView;
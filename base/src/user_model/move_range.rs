@@ -0,0 +1,276 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    actions::{get_shifted_block, CellShift},
+    constants::{LAST_COLUMN, LAST_ROW},
+    expressions::{
+        parser::stringify::node_references_areas,
+        types::Area,
+        utils::{is_valid_column_number, is_valid_row},
+    },
+    types::{Cell, Comment, MergedRange},
+};
+
+use super::{
+    history::{CellData, Diff},
+    UserModel,
+};
+
+// The cells of a block that is going to be moved that can change
+pub(super) struct MoveCellsSnapshot {
+    area: Area,
+    row_delta: i32,
+    column_delta: i32,
+    old_cells: BTreeMap<(u32, i32, i32), Cell>,
+    old_merged_cells: Vec<MergedRange>,
+    old_comments: Vec<Comment>,
+}
+
+// Whether the cell is in the block or where it lands
+fn is_in_region(
+    area: &Area,
+    row_delta: i32,
+    column_delta: i32,
+    sheet: u32,
+    row: i32,
+    column: i32,
+) -> bool {
+    let in_block = |row_delta: i32, column_delta: i32| {
+        row >= area.row + row_delta
+            && row < area.row + row_delta + area.height
+            && column >= area.column + column_delta
+            && column < area.column + column_delta + area.width
+    };
+    sheet == area.sheet && (in_block(0, 0) || in_block(row_delta, column_delta))
+}
+
+fn is_valid_area(area: &Area) -> bool {
+    area.width > 0
+        && area.height > 0
+        && is_valid_row(area.row)
+        && is_valid_row(area.row + area.height - 1)
+        && is_valid_column_number(area.column)
+        && is_valid_column_number(area.column + area.width - 1)
+}
+
+impl<'a> UserModel<'a> {
+    /// Moves the cells of `source` so that its top left cell lands on (`target_row`, `target_column`)
+    /// of the same sheet, like dragging the selection or cut and paste.
+    ///
+    /// Values, styles, comments and merged cells go with the cells and the cells they land on are
+    /// overwritten. Formulas anywhere in the workbook that point to the moved cells follow them,
+    /// references to the overwritten cells become `#REF!` and any other reference is left untouched.
+    /// The moved cells are selected.
+    ///
+    /// See also:
+    /// * [Model::move_cells]
+    pub fn move_range(
+        &mut self,
+        source: &Area,
+        target_row: i32,
+        target_column: i32,
+    ) -> Result<(), String> {
+        let target = Area {
+            row: target_row,
+            column: target_column,
+            ..*source
+        };
+        if !is_valid_area(source) {
+            return Err("Invalid source area".to_string());
+        }
+        if !is_valid_area(&target) {
+            return Err("Invalid target area".to_string());
+        }
+        self.check_area_editable(source)?;
+        self.check_area_editable(&target)?;
+        self.move_cells(
+            source,
+            target_row - source.row,
+            target_column - source.column,
+        )?;
+        if self.get_selected_sheet() == source.sheet {
+            self.set_selected_cell(target_row, target_column)?;
+            self.set_selected_range(
+                target_row,
+                target_column,
+                target_row + source.height - 1,
+                target_column + source.width - 1,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Inserts blank cells in `area` shifting the cells below it down
+    ///
+    /// See also:
+    /// * [Model::insert_cells_shift_down]
+    pub fn insert_cells_shift_down(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Down)
+    }
+
+    /// Inserts blank cells in `area` shifting the cells on its right to the right
+    ///
+    /// See also:
+    /// * [Model::insert_cells_shift_right]
+    pub fn insert_cells_shift_right(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Right)
+    }
+
+    /// Deletes the cells of `area` shifting the cells below it up.
+    /// References to the deleted cells become `#REF!`.
+    ///
+    /// See also:
+    /// * [Model::delete_cells_shift_up]
+    pub fn delete_cells_shift_up(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Up)
+    }
+
+    /// Deletes the cells of `area` shifting the cells on its right to the left.
+    /// References to the deleted cells become `#REF!`.
+    ///
+    /// See also:
+    /// * [Model::delete_cells_shift_left]
+    pub fn delete_cells_shift_left(&mut self, area: &Area) -> Result<(), String> {
+        self.shift_cells(area, CellShift::Left)
+    }
+
+    fn shift_cells(&mut self, area: &Area, shift: CellShift) -> Result<(), String> {
+        if !is_valid_area(area) {
+            return Err("Invalid area".to_string());
+        }
+        // All the cells from the area to the end of the sheet change
+        let changed_area = match shift {
+            CellShift::Down | CellShift::Up => Area {
+                height: LAST_ROW - area.row + 1,
+                ..*area
+            },
+            CellShift::Right | CellShift::Left => Area {
+                width: LAST_COLUMN - area.column + 1,
+                ..*area
+            },
+        };
+        self.check_area_editable(&changed_area)?;
+        let (block, row_delta, column_delta) = get_shifted_block(area, shift);
+        self.move_cells(&block, row_delta, column_delta)
+    }
+
+    // Moves the block of cells keeping a copy of every cell that changes to undo the move
+    fn move_cells(&mut self, area: &Area, row_delta: i32, column_delta: i32) -> Result<(), String> {
+        let snapshot = self.get_move_cells_snapshot(area, row_delta, column_delta)?;
+        self.model.move_cells(area, row_delta, column_delta)?;
+        let diff = self.get_move_cells_diff(snapshot)?;
+        self.push_diff_list(vec![diff]);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    // The cells that can change moving the block: the ones in the block or where it lands and
+    // the formulas that point to any of them
+    pub(super) fn get_move_cells_snapshot(
+        &self,
+        area: &Area,
+        row_delta: i32,
+        column_delta: i32,
+    ) -> Result<MoveCellsSnapshot, String> {
+        let worksheet = self.model.workbook.worksheet(area.sheet)?;
+        let old_merged_cells = worksheet.merge_cells.clone();
+        let old_comments = worksheet.comments.clone();
+        let source = Area {
+            sheet: area.sheet,
+            row: area.row,
+            column: area.column,
+            width: area.width,
+            height: area.height,
+        };
+        let target = Area {
+            row: area.row + row_delta,
+            column: area.column + column_delta,
+            ..*area
+        };
+        let region = [source, target];
+        let mut old_cells = BTreeMap::new();
+        for (sheet_index, worksheet) in self.model.workbook.worksheets.iter().enumerate() {
+            let sheet_index = sheet_index as u32;
+            let parsed_formulas = &self.model.parsed_formulas[sheet_index as usize];
+            for (row, row_data) in &worksheet.sheet_data {
+                for (column, cell) in row_data {
+                    let changes =
+                        is_in_region(area, row_delta, column_delta, sheet_index, *row, *column)
+                            || cell.get_formula().is_some_and(|index| {
+                                parsed_formulas.get(index as usize).is_some_and(|node| {
+                                    node_references_areas(node, *row, *column, &region)
+                                })
+                            });
+                    if changes {
+                        old_cells.insert((sheet_index, *row, *column), cell.clone());
+                    }
+                }
+            }
+        }
+        Ok(MoveCellsSnapshot {
+            area: Area {
+                sheet: area.sheet,
+                row: area.row,
+                column: area.column,
+                width: area.width,
+                height: area.height,
+            },
+            row_delta,
+            column_delta,
+            old_cells,
+            old_merged_cells,
+            old_comments,
+        })
+    }
+
+    // The diff of the move once it is done, with the cells that changed
+    pub(super) fn get_move_cells_diff(&self, snapshot: MoveCellsSnapshot) -> Result<Diff, String> {
+        let MoveCellsSnapshot {
+            area,
+            row_delta,
+            column_delta,
+            mut old_cells,
+            old_merged_cells,
+            old_comments,
+        } = snapshot;
+        let sheet = area.sheet;
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        let mut positions: BTreeSet<(u32, i32, i32)> = old_cells.keys().copied().collect();
+        for (row, row_data) in &worksheet.sheet_data {
+            for column in row_data.keys() {
+                if is_in_region(&area, row_delta, column_delta, sheet, *row, *column) {
+                    positions.insert((sheet, *row, *column));
+                }
+            }
+        }
+        let mut old_data = Vec::new();
+        for (sheet_index, row, column) in positions {
+            let old_cell = old_cells.remove(&(sheet_index, row, column));
+            let new_cell = self
+                .model
+                .workbook
+                .worksheet(sheet_index)?
+                .cell(row, column);
+            if old_cell.as_ref() != new_cell {
+                old_data.push(CellData {
+                    sheet: sheet_index,
+                    row,
+                    column,
+                    cell: old_cell,
+                });
+            }
+        }
+        Ok(Diff::MoveCells {
+            sheet,
+            row: area.row,
+            column: area.column,
+            width: area.width,
+            height: area.height,
+            row_delta,
+            column_delta,
+            old_data,
+            old_merged_cells,
+            old_comments,
+        })
+    }
+}
@@ -0,0 +1,508 @@
+use crate::{
+    constants::{LAST_COLUMN, LAST_ROW, MAX_OUTLINE_LEVEL},
+    expressions::utils::number_to_column,
+    types::SheetProtection,
+};
+
+use super::{history::Diff, UserModel};
+
+// Rows and columns are grouped in the same way, this is the direction of the group
+#[derive(Clone, Copy)]
+enum Axis {
+    Row,
+    Column,
+}
+
+impl Axis {
+    fn last(self) -> i32 {
+        match self {
+            Axis::Row => LAST_ROW,
+            Axis::Column => LAST_COLUMN,
+        }
+    }
+
+    fn name(self, index: i32) -> String {
+        match self {
+            Axis::Row => format!("Row {index}"),
+            Axis::Column => format!("Column {}", number_to_column(index).unwrap_or_default()),
+        }
+    }
+
+    // Grouping, hiding and collapsing needs the sheet protection to allow formatting
+    fn format_allowed(self, protection: &SheetProtection) -> bool {
+        match self {
+            Axis::Row => protection.format_rows,
+            Axis::Column => protection.format_columns,
+        }
+    }
+}
+
+impl<'a> UserModel<'a> {
+    fn outline_level(&self, sheet: u32, axis: Axis, index: i32) -> Result<u8, String> {
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        match axis {
+            Axis::Row => worksheet.get_row_outline_level(index),
+            Axis::Column => worksheet.get_column_outline_level(index),
+        }
+    }
+
+    fn is_collapsed(&self, sheet: u32, axis: Axis, index: i32) -> Result<bool, String> {
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        match axis {
+            Axis::Row => worksheet.is_row_collapsed(index),
+            Axis::Column => worksheet.is_column_collapsed(index),
+        }
+    }
+
+    // Whether the summary row (column) is below (to the right of) the group
+    fn summary_after(&self, sheet: u32, axis: Axis) -> Result<bool, String> {
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        Ok(match axis {
+            Axis::Row => worksheet.summary_below,
+            Axis::Column => worksheet.summary_right,
+        })
+    }
+
+    fn update_hidden(
+        &mut self,
+        sheet: u32,
+        axis: Axis,
+        index: i32,
+        new_value: bool,
+        diff_list: &mut Vec<Diff>,
+    ) -> Result<(), String> {
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        match axis {
+            Axis::Row => {
+                let old_value = worksheet.is_row_hidden(index)?;
+                if old_value != new_value {
+                    worksheet.set_row_hidden(index, new_value)?;
+                    diff_list.push(Diff::SetRowHidden {
+                        sheet,
+                        row: index,
+                        new_value,
+                        old_value,
+                    });
+                }
+            }
+            Axis::Column => {
+                let old_value = worksheet.is_column_hidden(index)?;
+                if old_value != new_value {
+                    worksheet.set_column_hidden(index, new_value)?;
+                    diff_list.push(Diff::SetColumnHidden {
+                        sheet,
+                        column: index,
+                        new_value,
+                        old_value,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update_outline_level(
+        &mut self,
+        sheet: u32,
+        axis: Axis,
+        index: i32,
+        new_value: u8,
+        diff_list: &mut Vec<Diff>,
+    ) -> Result<(), String> {
+        let old_value = self.outline_level(sheet, axis, index)?;
+        if old_value == new_value {
+            return Ok(());
+        }
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        match axis {
+            Axis::Row => {
+                worksheet.set_row_outline_level(index, new_value)?;
+                diff_list.push(Diff::SetRowOutlineLevel {
+                    sheet,
+                    row: index,
+                    new_value,
+                    old_value,
+                });
+            }
+            Axis::Column => {
+                worksheet.set_column_outline_level(index, new_value)?;
+                diff_list.push(Diff::SetColumnOutlineLevel {
+                    sheet,
+                    column: index,
+                    new_value,
+                    old_value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn update_collapsed(
+        &mut self,
+        sheet: u32,
+        axis: Axis,
+        index: i32,
+        new_value: bool,
+        diff_list: &mut Vec<Diff>,
+    ) -> Result<(), String> {
+        let old_value = self.is_collapsed(sheet, axis, index)?;
+        if old_value == new_value {
+            return Ok(());
+        }
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        match axis {
+            Axis::Row => {
+                worksheet.set_row_collapsed(index, new_value)?;
+                diff_list.push(Diff::SetRowCollapsed {
+                    sheet,
+                    row: index,
+                    new_value,
+                    old_value,
+                });
+            }
+            Axis::Column => {
+                worksheet.set_column_collapsed(index, new_value)?;
+                diff_list.push(Diff::SetColumnCollapsed {
+                    sheet,
+                    column: index,
+                    new_value,
+                    old_value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the largest block of consecutive rows (columns) around `index` with outline level at least `level`
+    fn outline_block(
+        &self,
+        sheet: u32,
+        axis: Axis,
+        index: i32,
+        level: u8,
+    ) -> Result<(i32, i32), String> {
+        let mut start = index;
+        while start > 1 && self.outline_level(sheet, axis, start - 1)? >= level {
+            start -= 1;
+        }
+        let mut end = index;
+        while end < axis.last() && self.outline_level(sheet, axis, end + 1)? >= level {
+            end += 1;
+        }
+        Ok((start, end))
+    }
+
+    // Returns the summary row (column) of a group, if it exists
+    fn outline_summary(
+        &self,
+        sheet: u32,
+        axis: Axis,
+        start: i32,
+        end: i32,
+    ) -> Result<Option<i32>, String> {
+        let summary = if self.summary_after(sheet, axis)? {
+            end + 1
+        } else {
+            start - 1
+        };
+        if summary < 1 || summary > axis.last() {
+            return Ok(None);
+        }
+        Ok(Some(summary))
+    }
+
+    // Returns the group (start, end, level) of `index`.
+    // If `index` is the summary of a group that group is returned, otherwise the innermost group `index` belongs to.
+    fn outline_group(&self, sheet: u32, axis: Axis, index: i32) -> Result<(i32, i32, u8), String> {
+        if index < 1 || index > axis.last() {
+            return Err(format!("{} is not valid.", axis.name(index)));
+        }
+        let level = self.outline_level(sheet, axis, index)?;
+        let adjacent = if self.summary_after(sheet, axis)? {
+            index - 1
+        } else {
+            index + 1
+        };
+        if adjacent >= 1
+            && adjacent <= axis.last()
+            && self.outline_level(sheet, axis, adjacent)? > level
+        {
+            let (start, end) = self.outline_block(sheet, axis, adjacent, level + 1)?;
+            return Ok((start, end, level + 1));
+        }
+        if level == 0 {
+            return Err(format!("{} is not in a group", axis.name(index)));
+        }
+        let (start, end) = self.outline_block(sheet, axis, index, level)?;
+        Ok((start, end, level))
+    }
+
+    // Shows the rows (columns) in the group but keeps the collapsed subgroups hidden
+    fn show_outline_block(
+        &mut self,
+        sheet: u32,
+        axis: Axis,
+        start: i32,
+        end: i32,
+        level: u8,
+        diff_list: &mut Vec<Diff>,
+    ) -> Result<(), String> {
+        let mut index = start;
+        while index <= end {
+            if self.outline_level(sheet, axis, index)? <= level {
+                self.update_hidden(sheet, axis, index, false, diff_list)?;
+                index += 1;
+                continue;
+            }
+            let mut sub_end = index;
+            while sub_end < end && self.outline_level(sheet, axis, sub_end + 1)? > level {
+                sub_end += 1;
+            }
+            let is_collapsed = match self.outline_summary(sheet, axis, index, sub_end)? {
+                Some(summary) => self.is_collapsed(sheet, axis, summary)?,
+                None => false,
+            };
+            if !is_collapsed {
+                self.show_outline_block(sheet, axis, index, sub_end, level + 1, diff_list)?;
+            }
+            index = sub_end + 1;
+        }
+        Ok(())
+    }
+
+    fn group(&mut self, sheet: u32, axis: Axis, start: i32, end: i32) -> Result<(), String> {
+        if start < 1 || end > axis.last() || start > end {
+            return Err("Invalid range to group".to_string());
+        }
+        self.check_sheet_allows(sheet, |p| axis.format_allowed(p))?;
+        for index in start..=end {
+            if self.outline_level(sheet, axis, index)? >= MAX_OUTLINE_LEVEL {
+                return Err(format!(
+                    "Groups cannot be nested more than {MAX_OUTLINE_LEVEL} levels"
+                ));
+            }
+        }
+        let mut diff_list = Vec::new();
+        for index in start..=end {
+            let level = self.outline_level(sheet, axis, index)?;
+            self.update_outline_level(sheet, axis, index, level + 1, &mut diff_list)?;
+        }
+        self.push_diff_list(diff_list);
+        Ok(())
+    }
+
+    fn ungroup(&mut self, sheet: u32, axis: Axis, start: i32, end: i32) -> Result<(), String> {
+        if start < 1 || end > axis.last() || start > end {
+            return Err("Invalid range to ungroup".to_string());
+        }
+        self.check_sheet_allows(sheet, |p| axis.format_allowed(p))?;
+        let mut diff_list = Vec::new();
+        for index in start..=end {
+            let level = self.outline_level(sheet, axis, index)?;
+            if level > 0 {
+                self.update_outline_level(sheet, axis, index, level - 1, &mut diff_list)?;
+            }
+        }
+        if !diff_list.is_empty() {
+            self.push_diff_list(diff_list);
+        }
+        Ok(())
+    }
+
+    fn collapse(&mut self, sheet: u32, axis: Axis, index: i32) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| axis.format_allowed(p))?;
+        let (start, end, _) = self.outline_group(sheet, axis, index)?;
+        let mut diff_list = Vec::new();
+        for i in start..=end {
+            self.update_hidden(sheet, axis, i, true, &mut diff_list)?;
+        }
+        if let Some(summary) = self.outline_summary(sheet, axis, start, end)? {
+            self.update_collapsed(sheet, axis, summary, true, &mut diff_list)?;
+        }
+        self.push_diff_list(diff_list);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    fn expand(&mut self, sheet: u32, axis: Axis, index: i32) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| axis.format_allowed(p))?;
+        let (start, end, level) = self.outline_group(sheet, axis, index)?;
+        let mut diff_list = Vec::new();
+        self.show_outline_block(sheet, axis, start, end, level, &mut diff_list)?;
+        if let Some(summary) = self.outline_summary(sheet, axis, start, end)? {
+            self.update_collapsed(sheet, axis, summary, false, &mut diff_list)?;
+        }
+        self.push_diff_list(diff_list);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    fn set_hidden(
+        &mut self,
+        sheet: u32,
+        axis: Axis,
+        start: i32,
+        end: i32,
+        hidden: bool,
+    ) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| axis.format_allowed(p))?;
+        let mut diff_list = Vec::new();
+        for index in start..=end {
+            self.update_hidden(sheet, axis, index, hidden, &mut diff_list)?;
+        }
+        self.push_diff_list(diff_list);
+        self.evaluate_if_not_paused();
+        Ok(())
+    }
+
+    /// Hides (`true`) or shows (`false`) a range of rows in a single diff list
+    pub fn set_rows_hidden(
+        &mut self,
+        sheet: u32,
+        row_start: i32,
+        row_end: i32,
+        hidden: bool,
+    ) -> Result<(), String> {
+        self.set_hidden(sheet, Axis::Row, row_start, row_end, hidden)
+    }
+
+    /// Hides (`true`) or shows (`false`) a range of columns in a single diff list
+    pub fn set_columns_hidden(
+        &mut self,
+        sheet: u32,
+        column_start: i32,
+        column_end: i32,
+        hidden: bool,
+    ) -> Result<(), String> {
+        self.set_hidden(sheet, Axis::Column, column_start, column_end, hidden)
+    }
+
+    /// Returns true if the row is hidden
+    pub fn is_row_hidden(&self, sheet: u32, row: i32) -> Result<bool, String> {
+        self.model.workbook.worksheet(sheet)?.is_row_hidden(row)
+    }
+
+    /// Returns true if the column is hidden
+    pub fn is_column_hidden(&self, sheet: u32, column: i32) -> Result<bool, String> {
+        self.model
+            .workbook
+            .worksheet(sheet)?
+            .is_column_hidden(column)
+    }
+
+    /// Returns the outline level of the row, 0 if the row is not grouped
+    pub fn get_row_outline_level(&self, sheet: u32, row: i32) -> Result<u8, String> {
+        self.outline_level(sheet, Axis::Row, row)
+    }
+
+    /// Returns the outline level of the column, 0 if the column is not grouped
+    pub fn get_column_outline_level(&self, sheet: u32, column: i32) -> Result<u8, String> {
+        self.outline_level(sheet, Axis::Column, column)
+    }
+
+    /// Returns true if the row is the summary of a collapsed group
+    pub fn is_row_collapsed(&self, sheet: u32, row: i32) -> Result<bool, String> {
+        self.is_collapsed(sheet, Axis::Row, row)
+    }
+
+    /// Returns true if the column is the summary of a collapsed group
+    pub fn is_column_collapsed(&self, sheet: u32, column: i32) -> Result<bool, String> {
+        self.is_collapsed(sheet, Axis::Column, column)
+    }
+
+    /// Groups the rows from `row_start` to `row_end`, increasing their outline level by one.
+    /// Groups can be nested up to seven levels.
+    pub fn group_rows(&mut self, sheet: u32, row_start: i32, row_end: i32) -> Result<(), String> {
+        self.group(sheet, Axis::Row, row_start, row_end)
+    }
+
+    /// Decreases by one the outline level of the grouped rows from `row_start` to `row_end`.
+    /// The visibility of the rows does not change.
+    pub fn ungroup_rows(&mut self, sheet: u32, row_start: i32, row_end: i32) -> Result<(), String> {
+        self.ungroup(sheet, Axis::Row, row_start, row_end)
+    }
+
+    /// Groups the columns from `column_start` to `column_end`, increasing their outline level by one.
+    /// Groups can be nested up to seven levels.
+    pub fn group_columns(
+        &mut self,
+        sheet: u32,
+        column_start: i32,
+        column_end: i32,
+    ) -> Result<(), String> {
+        self.group(sheet, Axis::Column, column_start, column_end)
+    }
+
+    /// Decreases by one the outline level of the grouped columns from `column_start` to `column_end`.
+    /// The visibility of the columns does not change.
+    pub fn ungroup_columns(
+        &mut self,
+        sheet: u32,
+        column_start: i32,
+        column_end: i32,
+    ) -> Result<(), String> {
+        self.ungroup(sheet, Axis::Column, column_start, column_end)
+    }
+
+    /// Collapses a group of rows, hiding them and marking their summary row as collapsed.
+    /// `row` can be the summary row of the group or any row in the group, in that case the innermost group is collapsed.
+    pub fn collapse_row_group(&mut self, sheet: u32, row: i32) -> Result<(), String> {
+        self.collapse(sheet, Axis::Row, row)
+    }
+
+    /// Expands a group of rows. Rows in collapsed subgroups stay hidden.
+    /// `row` can be the summary row of the group or any row in the group.
+    pub fn expand_row_group(&mut self, sheet: u32, row: i32) -> Result<(), String> {
+        self.expand(sheet, Axis::Row, row)
+    }
+
+    /// Collapses a group of columns, hiding them and marking their summary column as collapsed.
+    /// `column` can be the summary column of the group or any column in the group.
+    pub fn collapse_column_group(&mut self, sheet: u32, column: i32) -> Result<(), String> {
+        self.collapse(sheet, Axis::Column, column)
+    }
+
+    /// Expands a group of columns. Columns in collapsed subgroups stay hidden.
+    /// `column` can be the summary column of the group or any column in the group.
+    pub fn expand_column_group(&mut self, sheet: u32, column: i32) -> Result<(), String> {
+        self.expand(sheet, Axis::Column, column)
+    }
+
+    /// Returns true if the summary rows of the groups are below the detail rows
+    pub fn get_outline_summary_below(&self, sheet: u32) -> Result<bool, String> {
+        self.summary_after(sheet, Axis::Row)
+    }
+
+    /// Sets whether the summary rows of the groups are below (`true`) or above (`false`) the detail rows
+    pub fn set_outline_summary_below(&mut self, sheet: u32, value: bool) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.format_rows)?;
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        let old_value = worksheet.summary_below;
+        worksheet.summary_below = value;
+        self.push_diff_list(vec![Diff::SetOutlineSummaryBelow {
+            sheet,
+            new_value: value,
+            old_value,
+        }]);
+        Ok(())
+    }
+
+    /// Returns true if the summary columns of the groups are to the right of the detail columns
+    pub fn get_outline_summary_right(&self, sheet: u32) -> Result<bool, String> {
+        self.summary_after(sheet, Axis::Column)
+    }
+
+    /// Sets whether the summary columns of the groups are to the right (`true`) or left (`false`) of the detail columns
+    pub fn set_outline_summary_right(&mut self, sheet: u32, value: bool) -> Result<(), String> {
+        self.check_sheet_allows(sheet, |p| p.format_columns)?;
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        let old_value = worksheet.summary_right;
+        worksheet.summary_right = value;
+        self.push_diff_list(vec![Diff::SetOutlineSummaryRight {
+            sheet,
+            new_value: value,
+            old_value,
+        }]);
+        Ok(())
+    }
+}
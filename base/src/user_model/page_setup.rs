@@ -0,0 +1,124 @@
+use crate::{
+    expressions::types::Area,
+    page_setup::{validate_page_setup, PRINT_AREA, PRINT_TITLES},
+    types::{PageSetup, PrintTitles},
+};
+
+use super::{history::Diff, UserModel};
+
+impl<'a> UserModel<'a> {
+    /// Returns the print settings of the sheet
+    pub fn get_page_setup(&self, sheet: u32) -> Result<PageSetup, String> {
+        Ok(self.model.workbook.worksheet(sheet)?.page_setup.clone())
+    }
+
+    /// Sets the print settings of the sheet
+    pub fn set_page_setup(&mut self, sheet: u32, page_setup: PageSetup) -> Result<(), String> {
+        validate_page_setup(&page_setup)?;
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        let old_value = std::mem::replace(&mut worksheet.page_setup, page_setup.clone());
+        self.push_diff_list(vec![Diff::SetPageSetup {
+            sheet,
+            new_value: Box::new(page_setup),
+            old_value: Box::new(old_value),
+        }]);
+        Ok(())
+    }
+
+    fn update_page_breaks(
+        &mut self,
+        sheet: u32,
+        update: impl FnOnce(&mut PageSetup),
+    ) -> Result<(), String> {
+        let mut page_setup = self.get_page_setup(sheet)?;
+        update(&mut page_setup);
+        page_setup.row_breaks.sort_unstable();
+        page_setup.row_breaks.dedup();
+        page_setup.column_breaks.sort_unstable();
+        page_setup.column_breaks.dedup();
+        self.set_page_setup(sheet, page_setup)
+    }
+
+    /// Adds a manual page break after `row`
+    pub fn insert_row_page_break(&mut self, sheet: u32, row: i32) -> Result<(), String> {
+        self.update_page_breaks(sheet, |page_setup| page_setup.row_breaks.push(row))
+    }
+
+    /// Removes the manual page break after `row`
+    pub fn delete_row_page_break(&mut self, sheet: u32, row: i32) -> Result<(), String> {
+        self.update_page_breaks(sheet, |page_setup| {
+            page_setup.row_breaks.retain(|r| *r != row)
+        })
+    }
+
+    /// Adds a manual page break after `column`
+    pub fn insert_column_page_break(&mut self, sheet: u32, column: i32) -> Result<(), String> {
+        self.update_page_breaks(sheet, |page_setup| page_setup.column_breaks.push(column))
+    }
+
+    /// Removes the manual page break after `column`
+    pub fn delete_column_page_break(&mut self, sheet: u32, column: i32) -> Result<(), String> {
+        self.update_page_breaks(sheet, |page_setup| {
+            page_setup.column_breaks.retain(|c| *c != column)
+        })
+    }
+
+    /// Returns the print area of the sheet, i.e. `Sheet1!$A$1:$F$40`
+    ///
+    /// See also:
+    /// * [Model::get_print_area]
+    #[inline]
+    pub fn get_print_area(&self, sheet: u32) -> Result<Option<String>, String> {
+        self.model.get_print_area(sheet)
+    }
+
+    /// Sets the print area of the sheet to `area`
+    pub fn set_print_area(&mut self, area: &Area) -> Result<(), String> {
+        let formula = self.model.get_print_area_formula(area)?;
+        self.update_print_area(area.sheet, Some(formula))
+    }
+
+    /// Removes the print area of the sheet, the whole sheet will be printed
+    pub fn delete_print_area(&mut self, sheet: u32) -> Result<(), String> {
+        self.update_print_area(sheet, None)
+    }
+
+    fn update_print_area(&mut self, sheet: u32, new_value: Option<String>) -> Result<(), String> {
+        let old_value = self.model.get_local_defined_name(sheet, PRINT_AREA)?;
+        self.model
+            .set_local_defined_name(sheet, PRINT_AREA, new_value.clone())?;
+        self.push_diff_list(vec![Diff::SetPrintArea {
+            sheet,
+            new_value,
+            old_value,
+        }]);
+        Ok(())
+    }
+
+    /// Returns the rows and columns printed on every page
+    ///
+    /// See also:
+    /// * [Model::get_print_titles]
+    #[inline]
+    pub fn get_print_titles(&self, sheet: u32) -> Result<PrintTitles, String> {
+        self.model.get_print_titles(sheet)
+    }
+
+    /// Sets the rows and columns printed on every page
+    pub fn set_print_titles(
+        &mut self,
+        sheet: u32,
+        print_titles: &PrintTitles,
+    ) -> Result<(), String> {
+        let new_value = self.model.get_print_titles_formula(sheet, print_titles)?;
+        let old_value = self.model.get_local_defined_name(sheet, PRINT_TITLES)?;
+        self.model
+            .set_local_defined_name(sheet, PRINT_TITLES, new_value.clone())?;
+        self.push_diff_list(vec![Diff::SetPrintTitles {
+            sheet,
+            new_value,
+            old_value,
+        }]);
+        Ok(())
+    }
+}
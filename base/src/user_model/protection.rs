@@ -0,0 +1,199 @@
+use crate::{
+    expressions::types::Area,
+    protection::{check_password, ProtectionError},
+    types::{ProtectionPassword, SheetProtection, WorkbookProtection},
+};
+
+use super::{history::Diff, UserModel};
+
+fn new_password(password: Option<&str>) -> Result<Option<ProtectionPassword>, String> {
+    password
+        .filter(|password| !password.is_empty())
+        .map(ProtectionPassword::new)
+        .transpose()
+}
+
+impl<'a> UserModel<'a> {
+    /// Protects the sheet with an optional password.
+    /// `protection` sets the operations that are still allowed on the protected sheet.
+    /// Only the cells that are not locked can be edited.
+    pub fn protect_sheet(
+        &mut self,
+        sheet: u32,
+        protection: &SheetProtection,
+        password: Option<&str>,
+    ) -> Result<(), String> {
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        if worksheet.protection.is_some() {
+            return Err("The sheet is already protected".to_string());
+        }
+        let protection = SheetProtection {
+            password: new_password(password)?,
+            ..protection.clone()
+        };
+        worksheet.protection = Some(protection.clone());
+        self.push_diff_list(vec![Diff::SetSheetProtection {
+            sheet,
+            new_value: Box::new(Some(protection)),
+            old_value: Box::new(None),
+        }]);
+        Ok(())
+    }
+
+    /// Removes the protection of the sheet. Fails if the password is not correct.
+    pub fn unprotect_sheet(&mut self, sheet: u32, password: Option<&str>) -> Result<(), String> {
+        let worksheet = self.model.workbook.worksheet_mut(sheet)?;
+        let protection = match &worksheet.protection {
+            Some(protection) => protection,
+            None => return Err("The sheet is not protected".to_string()),
+        };
+        check_password(&protection.password, password)?;
+        let old_value = worksheet.protection.take();
+        self.push_diff_list(vec![Diff::SetSheetProtection {
+            sheet,
+            new_value: Box::new(None),
+            old_value: Box::new(old_value),
+        }]);
+        Ok(())
+    }
+
+    /// Returns the protection of the sheet, if the sheet is protected
+    pub fn get_sheet_protection(&self, sheet: u32) -> Result<Option<SheetProtection>, String> {
+        Ok(self.model.get_sheet_protection(sheet)?.cloned())
+    }
+
+    /// Protects the workbook structure and/or windows with an optional password.
+    /// Sheets can't be added, deleted, duplicated, moved, renamed, hidden or colored if the
+    /// structure is protected.
+    pub fn protect_workbook(
+        &mut self,
+        lock_structure: bool,
+        lock_windows: bool,
+        password: Option<&str>,
+    ) -> Result<(), String> {
+        if self.model.workbook.protection.is_some() {
+            return Err("The workbook is already protected".to_string());
+        }
+        let protection = WorkbookProtection {
+            password: new_password(password)?,
+            lock_structure,
+            lock_windows,
+        };
+        self.model.workbook.protection = Some(protection.clone());
+        self.push_diff_list(vec![Diff::SetWorkbookProtection {
+            new_value: Some(protection),
+            old_value: None,
+        }]);
+        Ok(())
+    }
+
+    /// Removes the protection of the workbook. Fails if the password is not correct.
+    pub fn unprotect_workbook(&mut self, password: Option<&str>) -> Result<(), String> {
+        let protection = match &self.model.workbook.protection {
+            Some(protection) => protection,
+            None => return Err("The workbook is not protected".to_string()),
+        };
+        check_password(&protection.password, password)?;
+        let old_value = self.model.workbook.protection.take();
+        self.push_diff_list(vec![Diff::SetWorkbookProtection {
+            new_value: None,
+            old_value,
+        }]);
+        Ok(())
+    }
+
+    /// Returns true if the workbook structure is protected
+    ///
+    /// See also:
+    /// * [Model::is_workbook_structure_locked]
+    #[inline]
+    pub fn is_workbook_structure_locked(&self) -> bool {
+        self.model.is_workbook_structure_locked()
+    }
+
+    /// Returns true if the sheet is protected and the cell is locked
+    pub fn is_cell_locked(&self, sheet: u32, row: i32, column: i32) -> Result<bool, String> {
+        self.model.is_area_locked(&Area {
+            sheet,
+            row,
+            column,
+            width: 1,
+            height: 1,
+        })
+    }
+
+    // Fails if the sheet is protected and the cell is locked
+    pub(crate) fn check_cell_editable(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<(), String> {
+        if self.is_cell_locked(sheet, row, column)? {
+            return Err(ProtectionError::ProtectedSheet.into());
+        }
+        Ok(())
+    }
+
+    // Fails if the sheet is protected and some cell in the area is locked
+    pub(crate) fn check_area_editable(&self, area: &Area) -> Result<(), String> {
+        if self.model.is_area_locked(area)? {
+            return Err(ProtectionError::ProtectedSheet.into());
+        }
+        Ok(())
+    }
+
+    // Fails if the sheet is protected and the protection does not allow the operation
+    pub(crate) fn check_sheet_allows(
+        &self,
+        sheet: u32,
+        allowed: impl Fn(&SheetProtection) -> bool,
+    ) -> Result<(), String> {
+        match self.model.get_sheet_protection(sheet)? {
+            Some(protection) if !allowed(protection) => Err(ProtectionError::ProtectedSheet.into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Fails if a cell, row or column of a protected sheet that does not allow formatting cells
+    // uses the named style
+    pub(crate) fn check_named_style_editable(&self, style_name: &str) -> Result<(), String> {
+        let styles = &self.model.workbook.styles;
+        let Some(xf_id) = styles
+            .cell_styles
+            .iter()
+            .find(|cell_style| cell_style.name == style_name)
+            .map(|cell_style| cell_style.xf_id)
+        else {
+            return Ok(());
+        };
+        let uses_style = |s: i32| styles.cell_xfs.get(s as usize).map(|xf| xf.xf_id) == Some(xf_id);
+        for (sheet, worksheet) in self.model.workbook.worksheets.iter().enumerate() {
+            if worksheet.protection.is_none() {
+                continue;
+            }
+            let in_use = worksheet
+                .sheet_data
+                .values()
+                .flat_map(|row| row.values())
+                .any(|cell| uses_style(cell.get_style()))
+                || worksheet.rows.iter().any(|row| uses_style(row.s))
+                || worksheet
+                    .cols
+                    .iter()
+                    .any(|col| col.style.is_some_and(uses_style));
+            if in_use {
+                self.check_sheet_allows(sheet as u32, |p| p.format_cells)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Fails if the workbook structure is protected
+    pub(crate) fn check_workbook_structure(&self) -> Result<(), String> {
+        if self.model.is_workbook_structure_locked() {
+            return Err(ProtectionError::ProtectedWorkbook.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,652 @@
+// Reverting changes exactly.
+//
+// In a collaborative session the local changes are undone to apply the changes of other users
+// and applied again afterwards (see `collaboration.rs`). The inverse of a diff is not always
+// enough to go back: its old values might be older than the changes of other users made to the
+// same cells or settings, and a structural change (like deleting rows) breaks formulas (#REF!)
+// that its inverse does not fix.
+// Here we find, right before a diff is applied, the changes that take the workbook back to the
+// state it has now.
+
+use crate::{
+    expressions::types::Area,
+    page_setup::{PRINT_AREA, PRINT_TITLES},
+};
+
+use super::{
+    history::{CellData, Diff, DiffType, QueueDiffs},
+    move_range::MoveCellsSnapshot,
+    UserModel,
+};
+
+/// How to revert a diff, found before applying it
+pub(super) enum Revert {
+    /// The changes that revert the diff, they are applied last one first
+    Steps(Vec<QueueDiffs>),
+    /// The cells that change moving a block are only known once they are moved
+    MoveCells(Box<MoveCellsSnapshot>),
+}
+
+/// The diffs that undo a list of diffs that has just been applied, i.e. the inverse
+pub(super) fn get_inverse(queue_diffs: &QueueDiffs) -> QueueDiffs {
+    QueueDiffs {
+        r#type: match queue_diffs.r#type {
+            DiffType::Redo => DiffType::Undo,
+            DiffType::Undo => DiffType::Redo,
+        },
+        list: queue_diffs.list.clone(),
+    }
+}
+
+// A diff applied as a redo sets the value as it is now
+fn set_current_value(diff: Diff) -> Vec<QueueDiffs> {
+    vec![QueueDiffs {
+        r#type: DiffType::Redo,
+        list: vec![diff],
+    }]
+}
+
+// A diff applied as an undo sets the old values
+fn restore(diff: Diff) -> QueueDiffs {
+    QueueDiffs {
+        r#type: DiffType::Undo,
+        list: vec![diff],
+    }
+}
+
+impl UserModel<'_> {
+    /// How to revert `diff` if it is applied now
+    pub(super) fn get_revert(&self, diff_type: &DiffType, diff: &Diff) -> Result<Revert, String> {
+        let is_redo = matches!(diff_type, DiffType::Redo);
+        let inverse = get_inverse(&QueueDiffs {
+            r#type: diff_type.clone(),
+            list: vec![diff.clone()],
+        });
+        let steps = match diff {
+            Diff::SetCellValue {
+                sheet, row, column, ..
+            }
+            | Diff::SetCellRichText {
+                sheet, row, column, ..
+            }
+            | Diff::CellClearContents {
+                sheet, row, column, ..
+            }
+            | Diff::CellClearAll {
+                sheet, row, column, ..
+            }
+            | Diff::CellClearFormatting {
+                sheet, row, column, ..
+            }
+            | Diff::SetCellStyle {
+                sheet, row, column, ..
+            } => vec![self.get_cell_restore(*sheet, *row, *column)?],
+            Diff::DeleteSheet { sheet, .. } if is_redo => self.get_sheet_restore(*sheet)?,
+            Diff::NewSheet { index, .. } if !is_redo => self.get_sheet_restore(*index)?,
+            Diff::DuplicateSheet { sheet, .. } if !is_redo => self.get_sheet_restore(*sheet + 1)?,
+            Diff::DeleteRows {
+                sheet, row, count, ..
+            } if is_redo => self.get_rows_restore(*sheet, *row, *count)?,
+            Diff::InsertRows { sheet, row, count } if !is_redo => {
+                self.get_rows_restore(*sheet, *row, *count)?
+            }
+            Diff::DeleteColumns {
+                sheet,
+                column,
+                count,
+                ..
+            } if is_redo => self.get_columns_restore(*sheet, *column, *count)?,
+            Diff::InsertColumns {
+                sheet,
+                column,
+                count,
+            } if !is_redo => self.get_columns_restore(*sheet, *column, *count)?,
+            Diff::InsertRows { .. }
+            | Diff::DeleteRows { .. }
+            | Diff::InsertColumns { .. }
+            | Diff::DeleteColumns { .. }
+            | Diff::NewSheet { .. }
+            | Diff::DeleteSheet { .. }
+            | Diff::DuplicateSheet { .. }
+            | Diff::MoveRow { .. }
+            | Diff::MoveColumn { .. } => vec![self.get_formulas_restore(), inverse],
+            Diff::MoveCells {
+                sheet,
+                row,
+                column,
+                width,
+                height,
+                row_delta,
+                column_delta,
+                ..
+            } if is_redo => {
+                let area = Area {
+                    sheet: *sheet,
+                    row: *row,
+                    column: *column,
+                    width: *width,
+                    height: *height,
+                };
+                let snapshot = self.get_move_cells_snapshot(&area, *row_delta, *column_delta)?;
+                return Ok(Revert::MoveCells(Box::new(snapshot)));
+            }
+            Diff::MoveCells {
+                sheet,
+                row,
+                column,
+                width,
+                height,
+                row_delta,
+                column_delta,
+                old_data,
+                ..
+            } => {
+                // Undoing the move restores the cells of `old_data`
+                let worksheet = self.model.workbook.worksheet(*sheet)?;
+                let mut data = Vec::new();
+                for cell_data in old_data {
+                    let cell = self
+                        .model
+                        .workbook
+                        .worksheet(cell_data.sheet)?
+                        .cell(cell_data.row, cell_data.column)
+                        .cloned();
+                    data.push(CellData { cell, ..*cell_data });
+                }
+                vec![restore(Diff::MoveCells {
+                    sheet: *sheet,
+                    row: *row,
+                    column: *column,
+                    width: *width,
+                    height: *height,
+                    row_delta: *row_delta,
+                    column_delta: *column_delta,
+                    old_data: data,
+                    old_merged_cells: worksheet.merge_cells.clone(),
+                    old_comments: worksheet.comments.clone(),
+                })]
+            }
+            Diff::SortRange {
+                sheet,
+                row,
+                column,
+                width,
+                height,
+                keys,
+                ..
+            } => {
+                let area = Area {
+                    sheet: *sheet,
+                    row: *row,
+                    column: *column,
+                    width: *width,
+                    height: *height,
+                };
+                vec![restore(Diff::SortRange {
+                    sheet: *sheet,
+                    row: *row,
+                    column: *column,
+                    width: *width,
+                    height: *height,
+                    keys: keys.clone(),
+                    old_data: self.get_area_cells(&area)?,
+                })]
+            }
+            Diff::CreateDefinedName { name, scope, .. } if !is_redo => {
+                self.get_defined_name_restore(name, *scope)?
+            }
+            Diff::DeleteDefinedName { name, scope, .. } if is_redo => {
+                self.get_defined_name_restore(name, *scope)?
+            }
+            Diff::UpdateDefinedName {
+                name,
+                scope,
+                new_name,
+                new_scope,
+                ..
+            } => {
+                // Renames the defined name back, with the formula it has now
+                let ((name, scope), (new_name, new_scope)) = if is_redo {
+                    ((name, scope), (new_name, new_scope))
+                } else {
+                    ((new_name, new_scope), (name, scope))
+                };
+                let formula = self.model.get_defined_name_formula(name, *scope)?;
+                set_current_value(Diff::UpdateDefinedName {
+                    name: new_name.clone(),
+                    scope: *new_scope,
+                    old_formula: String::new(),
+                    new_name: name.clone(),
+                    new_scope: *scope,
+                    new_formula: formula,
+                })
+            }
+            Diff::SetDateSystem {
+                old_value,
+                new_value,
+                old_data,
+            } => {
+                // Restores the date system and the dates it shifts as they are now
+                let (old_data, new_value) = if is_redo {
+                    (self.get_date_cells_data()?, *new_value)
+                } else {
+                    let mut data = Vec::new();
+                    for cell_data in old_data {
+                        let cell = self
+                            .model
+                            .workbook
+                            .worksheet(cell_data.sheet)?
+                            .cell(cell_data.row, cell_data.column)
+                            .cloned();
+                        data.push(CellData { cell, ..*cell_data });
+                    }
+                    (data, *old_value)
+                };
+                vec![restore(Diff::SetDateSystem {
+                    old_value: self.get_date_system(),
+                    new_value,
+                    old_data,
+                })]
+            }
+            _ => match self.get_current_value(diff)? {
+                Some(diff) => set_current_value(diff),
+                None => vec![inverse],
+            },
+        };
+        Ok(Revert::Steps(steps))
+    }
+
+    /// The changes that revert a diff that has just been applied
+    pub(super) fn get_revert_steps(&self, revert: Revert) -> Result<Vec<QueueDiffs>, String> {
+        match revert {
+            Revert::Steps(steps) => Ok(steps),
+            Revert::MoveCells(snapshot) => Ok(vec![restore(self.get_move_cells_diff(*snapshot)?)]),
+        }
+    }
+
+    // Restores the cell as it is now
+    fn get_cell_restore(&self, sheet: u32, row: i32, column: i32) -> Result<QueueDiffs, String> {
+        let cell = self
+            .model
+            .workbook
+            .worksheet(sheet)?
+            .cell(row, column)
+            .cloned();
+        Ok(restore(Diff::SetCellValue {
+            sheet,
+            row,
+            column,
+            new_value: String::new(),
+            old_value: Box::new(cell),
+        }))
+    }
+
+    // Restores the sheet as it is now if it is deleted, and the formulas that referenced it
+    fn get_sheet_restore(&self, sheet: u32) -> Result<Vec<QueueDiffs>, String> {
+        let worksheet = self.model.workbook.worksheet(sheet)?;
+        Ok(vec![
+            self.get_formulas_restore(),
+            restore(Diff::DeleteSheet {
+                sheet,
+                old_data: Box::new(worksheet.clone()),
+            }),
+        ])
+    }
+
+    // Restores the rows as they are now if they are deleted, and the formulas that referenced them
+    fn get_rows_restore(
+        &self,
+        sheet: u32,
+        row: i32,
+        count: i32,
+    ) -> Result<Vec<QueueDiffs>, String> {
+        Ok(vec![
+            self.get_formulas_restore(),
+            restore(Diff::DeleteRows {
+                sheet,
+                row,
+                count,
+                old_data: self.get_rows_data(sheet, row, count)?,
+                old_merged_cells: self.model.workbook.worksheet(sheet)?.merge_cells.clone(),
+            }),
+        ])
+    }
+
+    // Restores the columns as they are now if they are deleted, and the formulas that referenced
+    // them
+    fn get_columns_restore(
+        &self,
+        sheet: u32,
+        column: i32,
+        count: i32,
+    ) -> Result<Vec<QueueDiffs>, String> {
+        Ok(vec![
+            self.get_formulas_restore(),
+            restore(Diff::DeleteColumns {
+                sheet,
+                column,
+                count,
+                old_data: self.get_columns_data(sheet, column, count)?,
+                old_merged_cells: self.model.workbook.worksheet(sheet)?.merge_cells.clone(),
+            }),
+        ])
+    }
+
+    // Restores every cell with a formula as it is now
+    fn get_formulas_restore(&self) -> QueueDiffs {
+        let mut list = Vec::new();
+        for (sheet, worksheet) in self.model.workbook.worksheets.iter().enumerate() {
+            for (row, row_data) in &worksheet.sheet_data {
+                for (column, cell) in row_data {
+                    if cell.has_formula() {
+                        list.push(Diff::SetCellValue {
+                            sheet: sheet as u32,
+                            row: *row,
+                            column: *column,
+                            new_value: String::new(),
+                            old_value: Box::new(Some(cell.clone())),
+                        });
+                    }
+                }
+            }
+        }
+        QueueDiffs {
+            r#type: DiffType::Undo,
+            list,
+        }
+    }
+
+    // Creates the defined name as it is now if it is deleted
+    fn get_defined_name_restore(
+        &self,
+        name: &str,
+        scope: Option<u32>,
+    ) -> Result<Vec<QueueDiffs>, String> {
+        Ok(set_current_value(Diff::CreateDefinedName {
+            name: name.to_string(),
+            scope,
+            value: self.model.get_defined_name_formula(name, scope)?,
+        }))
+    }
+
+    // The diff that sets what `diff` changes to its current value, if the inverse of `diff`
+    // might not do it
+    fn get_current_value(&self, diff: &Diff) -> Result<Option<Diff>, String> {
+        let model = &self.model;
+        let current = match diff.clone() {
+            Diff::SetColumnWidth {
+                sheet,
+                column,
+                old_value,
+                ..
+            } => Diff::SetColumnWidth {
+                sheet,
+                column,
+                new_value: model.get_column_width(sheet, column)?,
+                old_value,
+            },
+            Diff::SetRowHeight {
+                sheet,
+                row,
+                old_value,
+                ..
+            } => Diff::SetRowHeight {
+                sheet,
+                row,
+                new_value: model.get_row_height(sheet, row)?,
+                old_value,
+            },
+            Diff::SetColumnStyle { sheet, column, .. }
+            | Diff::DeleteColumnStyle { sheet, column, .. } => {
+                match model.get_column_style(sheet, column)? {
+                    Some(style) => Diff::SetColumnStyle {
+                        sheet,
+                        column,
+                        old_value: Box::new(None),
+                        new_value: Box::new(style),
+                    },
+                    None => Diff::DeleteColumnStyle {
+                        sheet,
+                        column,
+                        old_value: Box::new(None),
+                    },
+                }
+            }
+            Diff::SetRowStyle { sheet, row, .. } | Diff::DeleteRowStyle { sheet, row, .. } => {
+                match model.get_row_style(sheet, row)? {
+                    Some(style) => Diff::SetRowStyle {
+                        sheet,
+                        row,
+                        old_value: Box::new(None),
+                        new_value: Box::new(style),
+                    },
+                    None => Diff::DeleteRowStyle {
+                        sheet,
+                        row,
+                        old_value: Box::new(None),
+                    },
+                }
+            }
+            Diff::SetFrozenRowsCount {
+                sheet, old_value, ..
+            } => Diff::SetFrozenRowsCount {
+                sheet,
+                new_value: model.get_frozen_rows_count(sheet)?,
+                old_value,
+            },
+            Diff::SetFrozenColumnsCount {
+                sheet, old_value, ..
+            } => Diff::SetFrozenColumnsCount {
+                sheet,
+                new_value: model.get_frozen_columns_count(sheet)?,
+                old_value,
+            },
+            Diff::RenameSheet {
+                index, old_value, ..
+            } => Diff::RenameSheet {
+                index,
+                old_value,
+                new_value: model.workbook.worksheet(index)?.get_name(),
+            },
+            Diff::SetSheetColor {
+                index, old_value, ..
+            } => Diff::SetSheetColor {
+                index,
+                old_value,
+                new_value: model
+                    .workbook
+                    .worksheet(index)?
+                    .color
+                    .clone()
+                    .unwrap_or_default(),
+            },
+            Diff::SetSheetState {
+                index, old_value, ..
+            } => Diff::SetSheetState {
+                index,
+                old_value,
+                new_value: model.workbook.worksheet(index)?.state.clone(),
+            },
+            Diff::SetShowGridLines {
+                sheet, old_value, ..
+            } => Diff::SetShowGridLines {
+                sheet,
+                old_value,
+                new_value: model.workbook.worksheet(sheet)?.show_grid_lines,
+            },
+            Diff::SetLocale { old_value, .. } => Diff::SetLocale {
+                old_value,
+                new_value: model.get_locale(),
+            },
+            Diff::SetTimezone { old_value, .. } => Diff::SetTimezone {
+                old_value,
+                new_value: model.get_timezone(),
+            },
+            Diff::SetCustomLists { old_value, .. } => Diff::SetCustomLists {
+                old_value,
+                new_value: model.get_custom_lists().clone(),
+            },
+            Diff::SetTheme { old_value, .. } => Diff::SetTheme {
+                old_value,
+                new_value: Box::new(model.get_theme().clone()),
+            },
+            Diff::UpdateNamedStyle {
+                name, old_value, ..
+            } => {
+                let style = model.workbook.styles.get_named_style(&name)?;
+                Diff::UpdateNamedStyle {
+                    name,
+                    old_value,
+                    new_value: Box::new(style),
+                }
+            }
+            Diff::SetRowHidden {
+                sheet,
+                row,
+                old_value,
+                ..
+            } => Diff::SetRowHidden {
+                sheet,
+                row,
+                new_value: model.workbook.worksheet(sheet)?.is_row_hidden(row)?,
+                old_value,
+            },
+            Diff::SetColumnHidden {
+                sheet,
+                column,
+                old_value,
+                ..
+            } => Diff::SetColumnHidden {
+                sheet,
+                column,
+                new_value: model.workbook.worksheet(sheet)?.is_column_hidden(column)?,
+                old_value,
+            },
+            Diff::SetRowOutlineLevel {
+                sheet,
+                row,
+                old_value,
+                ..
+            } => Diff::SetRowOutlineLevel {
+                sheet,
+                row,
+                new_value: model
+                    .workbook
+                    .worksheet(sheet)?
+                    .get_row_outline_level(row)?,
+                old_value,
+            },
+            Diff::SetColumnOutlineLevel {
+                sheet,
+                column,
+                old_value,
+                ..
+            } => Diff::SetColumnOutlineLevel {
+                sheet,
+                column,
+                new_value: model
+                    .workbook
+                    .worksheet(sheet)?
+                    .get_column_outline_level(column)?,
+                old_value,
+            },
+            Diff::SetRowCollapsed {
+                sheet,
+                row,
+                old_value,
+                ..
+            } => Diff::SetRowCollapsed {
+                sheet,
+                row,
+                new_value: model.workbook.worksheet(sheet)?.is_row_collapsed(row)?,
+                old_value,
+            },
+            Diff::SetColumnCollapsed {
+                sheet,
+                column,
+                old_value,
+                ..
+            } => Diff::SetColumnCollapsed {
+                sheet,
+                column,
+                new_value: model
+                    .workbook
+                    .worksheet(sheet)?
+                    .is_column_collapsed(column)?,
+                old_value,
+            },
+            Diff::SetOutlineSummaryBelow {
+                sheet, old_value, ..
+            } => Diff::SetOutlineSummaryBelow {
+                sheet,
+                new_value: model.workbook.worksheet(sheet)?.summary_below,
+                old_value,
+            },
+            Diff::SetOutlineSummaryRight {
+                sheet, old_value, ..
+            } => Diff::SetOutlineSummaryRight {
+                sheet,
+                new_value: model.workbook.worksheet(sheet)?.summary_right,
+                old_value,
+            },
+            Diff::SetPageSetup {
+                sheet, old_value, ..
+            } => Diff::SetPageSetup {
+                sheet,
+                new_value: Box::new(model.workbook.worksheet(sheet)?.page_setup.clone()),
+                old_value,
+            },
+            Diff::SetPrintArea {
+                sheet, old_value, ..
+            } => Diff::SetPrintArea {
+                sheet,
+                new_value: model.get_local_defined_name(sheet, PRINT_AREA)?,
+                old_value,
+            },
+            Diff::SetPrintTitles {
+                sheet, old_value, ..
+            } => Diff::SetPrintTitles {
+                sheet,
+                new_value: model.get_local_defined_name(sheet, PRINT_TITLES)?,
+                old_value,
+            },
+            Diff::SetSheetProtection {
+                sheet, old_value, ..
+            } => Diff::SetSheetProtection {
+                sheet,
+                new_value: Box::new(model.workbook.worksheet(sheet)?.protection.clone()),
+                old_value,
+            },
+            Diff::SetWorkbookProtection { old_value, .. } => Diff::SetWorkbookProtection {
+                new_value: model.workbook.protection.clone(),
+                old_value,
+            },
+            Diff::SetAutoFilter {
+                sheet, old_value, ..
+            } => Diff::SetAutoFilter {
+                sheet,
+                new_value: Box::new(model.workbook.worksheet(sheet)?.auto_filter.clone()),
+                old_value,
+            },
+            Diff::SetTableAutoFilter {
+                table, old_value, ..
+            } => {
+                let auto_filter = self.get_table_auto_filter(&table)?;
+                Diff::SetTableAutoFilter {
+                    table,
+                    new_value: Box::new(auto_filter),
+                    old_value,
+                }
+            }
+            Diff::SetMergedCells {
+                sheet, old_value, ..
+            } => Diff::SetMergedCells {
+                sheet,
+                new_value: model.workbook.worksheet(sheet)?.merge_cells.clone(),
+                old_value,
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(current))
+    }
+}
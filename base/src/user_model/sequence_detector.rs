@@ -1,4 +1,36 @@
-use crate::locale::Locale;
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    formatter::dates::{convert_to_serial_number, from_excel_date},
+    locale::Locale,
+    types::DateSystem,
+};
+
+/// How autofill extends the selected cells
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutoFillMode {
+    /// Numbers, dates, times and lists are extended if they form a series, otherwise the cells are copied
+    #[default]
+    Auto,
+    /// The cells are copied
+    Copy,
+    /// Dates are extended by days
+    Days,
+    /// Dates are extended by days, skipping Saturdays and Sundays
+    Weekdays,
+    /// Dates are extended by months
+    Months,
+    /// Dates are extended by years
+    Years,
+    /// Numbers are extended with the best fit line
+    LinearTrend,
+    /// Numbers are extended with the best fit exponential curve
+    GrowthTrend,
+}
+
+/// Lists that autofill extends on top of the day and month names of the locale
+const BUILTIN_LISTS: [&[&str]; 1] = [&["Q1", "Q2", "Q3", "Q4"]];
 
 pub(crate) struct NumericProgression {
     last: f64,
@@ -25,18 +57,135 @@ impl SuffixedProgression {
     }
 }
 
-pub(crate) struct DateProgression {
+// Cycles through a list of names (days, months, custom lists)
+pub(crate) struct ListProgression {
     numeric_progression: NumericProgression,
-    dates: Vec<String>,
+    items: Vec<String>,
 }
 
-impl DateProgression {
+impl ListProgression {
     fn next(&self, i: usize) -> String {
         let num_next_index = self.numeric_progression.next(i);
-        let months_len = self.dates.len() as f64;
-        let next_index = (num_next_index % months_len + months_len) % months_len;
-        self.dates[next_index as usize].clone()
+        let items_len = self.items.len() as f64;
+        let next_index = (num_next_index % items_len + items_len) % items_len;
+        self.items[next_index as usize].clone()
+    }
+}
+
+/// The unit of a date series
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum DateUnit {
+    Day,
+    Weekday,
+    Month,
+    Year,
+}
+
+// Dates as serial numbers, `step` units from one to the next. The time of the day is kept.
+pub(crate) struct DateUnitProgression {
+    last: NaiveDate,
+    time: f64,
+    step: i64,
+    unit: DateUnit,
+    date_system: DateSystem,
+    decimal_sep: char,
+}
+
+impl DateUnitProgression {
+    fn next(&self, i: usize) -> String {
+        let count = self.step * (i as i64 + 1);
+        let date = match self.unit {
+            DateUnit::Day => add_days(self.last, count),
+            DateUnit::Weekday => add_weekdays(self.last, count),
+            DateUnit::Month => add_months(self.last, count),
+            DateUnit::Year => add_months(self.last, count * 12),
+        };
+        match date {
+            Some(date) => {
+                let serial_number = convert_to_serial_number(date, self.date_system) as f64;
+                format_number(serial_number + self.time, self.decimal_sep)
+            }
+            None => "#NUM!".to_string(),
+        }
+    }
+}
+
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    if days >= 0 {
+        date.checked_add_days(Days::new(days as u64))
+    } else {
+        date.checked_sub_days(Days::new(days.unsigned_abs()))
+    }
+}
+
+fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let months_abs = u32::try_from(months.unsigned_abs()).ok()?;
+    if months >= 0 {
+        date.checked_add_months(Months::new(months_abs))
+    } else {
+        date.checked_sub_months(Months::new(months_abs))
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn add_weekdays(date: NaiveDate, weekdays: i64) -> Option<NaiveDate> {
+    let direction = weekdays.signum();
+    let mut date = date;
+    let mut remaining = weekdays.abs();
+    while remaining > 0 {
+        date = add_days(date, direction)?;
+        if !is_weekend(date) {
+            remaining -= 1;
+        }
     }
+    Some(date)
+}
+
+// Number of weekdays from `start` to `end`, negative if `end` is before `start`
+fn weekdays_between(start: NaiveDate, end: NaiveDate) -> i64 {
+    let (first, last, sign) = if start <= end {
+        (start, end, 1)
+    } else {
+        (end, start, -1)
+    };
+    let count = first
+        .iter_days()
+        .skip(1)
+        .take_while(|date| *date <= last)
+        .filter(|date| !is_weekend(*date))
+        .count() as i64;
+    sign * count
+}
+
+fn month_index(date: NaiveDate) -> i64 {
+    date.year() as i64 * 12 + date.month0() as i64
+}
+
+// Values computed with the best fit line (or exponential curve) through the source values
+pub(crate) struct TrendProgression {
+    intercept: f64,
+    slope: f64,
+    count: usize,
+    growth: bool,
+    decimal_sep: char,
+}
+
+impl TrendProgression {
+    fn next(&self, i: usize) -> String {
+        let x = (self.count + i) as f64;
+        let value = self.intercept + self.slope * x;
+        let value = if self.growth { value.exp() } else { value };
+        format_number(value, self.decimal_sep)
+    }
+}
+
+fn format_number(value: f64, decimal_sep: char) -> String {
+    round_sig(value)
+        .to_string()
+        .replace('.', &decimal_sep.to_string())
 }
 
 fn round_sig(value: f64) -> f64 {
@@ -59,19 +208,35 @@ fn round_sig(value: f64) -> f64 {
 pub(crate) enum Progression {
     Numeric(NumericProgression),
     SuffixedNumber(SuffixedProgression),
-    Date(DateProgression),
+    List(ListProgression),
+    Date(DateUnitProgression),
+    Trend(TrendProgression),
 }
 impl Progression {
     fn format_number(progression: &NumericProgression, i: usize) -> String {
-        round_sig(progression.next(i))
-            .to_string()
-            .replace('.', &progression.decimal_sep.to_string())
+        format_number(progression.next(i), progression.decimal_sep)
     }
     pub(crate) fn next(&self, i: usize) -> String {
         match self {
             Progression::Numeric(num_prog) => Self::format_number(num_prog, i),
             Progression::SuffixedNumber(suffnum_prog) => suffnum_prog.next(i),
+            Progression::List(list_prog) => list_prog.next(i),
             Progression::Date(date_prog) => date_prog.next(i),
+            Progression::Trend(trend_prog) => trend_prog.next(i),
+        }
+    }
+    // Goes through the series in the opposite direction, used when a single value is extended up or left
+    pub(crate) fn reverse(&mut self) {
+        match self {
+            Progression::Numeric(num_prog) => num_prog.step = -num_prog.step,
+            Progression::SuffixedNumber(suffnum_prog) => {
+                suffnum_prog.numeric_progression.step = -suffnum_prog.numeric_progression.step
+            }
+            Progression::List(list_prog) => {
+                list_prog.numeric_progression.step = -list_prog.numeric_progression.step
+            }
+            Progression::Date(date_prog) => date_prog.step = -date_prog.step,
+            Progression::Trend(_) => {}
         }
     }
 }
@@ -280,63 +445,95 @@ impl SequenceDetector for SuffixedNumberDetector<'_> {
     }
 }
 
-struct DateProgressionDetector<'a> {
-    locale: &'a Locale,
+// Finds the position of each value in `items` and checks they are evenly spaced.
+// A single value is followed by the next item.
+fn find_list_progression(
+    values: &[String],
+    items: &[String],
+    locale: &Locale,
+) -> Option<Progression> {
+    let indexes = values
+        .iter()
+        .map(|value| {
+            items
+                .iter()
+                .position(|item| item.eq_ignore_ascii_case(value))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let numeric_progression = if let [index] = indexes[..] {
+        NumericProgression {
+            last: index as f64,
+            step: 1.0,
+            decimal_sep: '.',
+        }
+    } else {
+        let indexes: Vec<String> = indexes.iter().map(|index| index.to_string()).collect();
+        match (NumericProgressionDetector { locale }).detect(&indexes) {
+            Some(Progression::Numeric(numeric_progression)) => numeric_progression,
+            _ => return None,
+        }
+    };
+    Some(Progression::List(ListProgression {
+        numeric_progression,
+        items: items.to_vec(),
+    }))
 }
 
-impl<'a> DateProgressionDetector<'a> {
-    fn find_progression(&self, values: &[String], dates: &[String]) -> Option<Progression> {
-        let indexes = values
-            .iter()
-            .map(|value| {
-                dates
-                    .iter()
-                    .position(|date| date.eq_ignore_ascii_case(value))
-                    .map(|idx| idx.to_string())
-            })
-            .collect::<Option<Vec<_>>>();
-
-        if let Some(indices) = indexes {
-            if let Some(Progression::Numeric(numeric_progression)) = (NumericProgressionDetector {
-                locale: self.locale,
-            })
-            .detect(&indices)
-            {
-                let date_progression = DateProgression {
-                    numeric_progression,
-                    dates: dates.to_vec(),
-                };
-                return Some(Progression::Date(date_progression));
-            }
-        }
-        None
-    }
+struct DateProgressionDetector<'a> {
+    locale: &'a Locale,
 }
 
 impl<'a> SequenceDetector for DateProgressionDetector<'a> {
     fn detect(&self, values: &[String]) -> Option<Progression> {
-        if values.len() < 2 {
-            return None;
-        }
-
         let dates = &self.locale.dates;
 
-        [
+        let mut lists = vec![
             &dates.day_names,
             &dates.day_names_short,
             &dates.months,
             &dates.months_short,
-            &dates.months_letter,
-        ]
-        .iter()
-        .find_map(|&names_vec| self.find_progression(values, names_vec))
+        ];
+        // A single letter is too ambiguous to start a series
+        if values.len() > 1 {
+            lists.push(&dates.months_letter);
+        }
+        lists
+            .iter()
+            .find_map(|&names_vec| find_list_progression(values, names_vec, self.locale))
+    }
+}
+
+struct CustomListDetector<'a> {
+    locale: &'a Locale,
+    lists: &'a [Vec<String>],
+}
+
+impl SequenceDetector for CustomListDetector<'_> {
+    fn detect(&self, values: &[String]) -> Option<Progression> {
+        BUILTIN_LISTS
+            .iter()
+            .map(|list| list.iter().map(|item| item.to_string()).collect::<Vec<_>>())
+            .chain(self.lists.iter().cloned())
+            .find_map(|list| find_list_progression(values, &list, self.locale))
     }
 }
 
-pub(crate) fn detect_progression(values: &[String], locale: &Locale) -> Option<Progression> {
+pub(crate) fn detect_progression(
+    values: &[String],
+    locale: &Locale,
+    custom_lists: &[Vec<String>],
+) -> Option<Progression> {
     if let Some(progression) = (NumericProgressionDetector { locale }).detect(values) {
         return Some(progression);
     }
+    if let Some(progression) = (CustomListDetector {
+        locale,
+        lists: custom_lists,
+    })
+    .detect(values)
+    {
+        return Some(progression);
+    }
     if let Some(progression) = (SuffixedNumberDetector { locale }).detect(values) {
         return Some(progression);
     }
@@ -346,6 +543,140 @@ pub(crate) fn detect_progression(values: &[String], locale: &Locale) -> Option<P
     None
 }
 
+/// Detects a series of dates given as serial numbers.
+/// If `unit` is `None` the unit is guessed from the dates: the same day of the month in
+/// evenly spaced months (or years) or evenly spaced days. A single date is followed by the next day.
+pub(crate) fn detect_date_progression(
+    serial_numbers: &[f64],
+    unit: Option<DateUnit>,
+    date_system: DateSystem,
+    decimal_sep: char,
+) -> Option<Progression> {
+    let dates = serial_numbers
+        .iter()
+        .map(|value| from_excel_date(value.floor() as i64, date_system).ok())
+        .collect::<Option<Vec<NaiveDate>>>()?;
+    let (first, last) = (*dates.first()?, *dates.last()?);
+    let time = serial_numbers.last()?.fract();
+    let second = dates.get(1).copied();
+    let step_in = |unit: DateUnit| -> i64 {
+        let step = match second {
+            Some(second) => match unit {
+                DateUnit::Day => (second - first).num_days(),
+                DateUnit::Weekday => weekdays_between(first, second),
+                DateUnit::Month => month_index(second) - month_index(first),
+                DateUnit::Year => (second.year() - first.year()) as i64,
+            },
+            None => 1,
+        };
+        if step == 0 {
+            1
+        } else {
+            step
+        }
+    };
+    let (unit, step) = match unit {
+        Some(unit) => (unit, step_in(unit)),
+        None if dates.len() == 1 => (DateUnit::Day, 1),
+        None => {
+            let months: Vec<i64> = dates.iter().map(|date| month_index(*date)).collect();
+            let month_step = months[1] - months[0];
+            let days: Vec<i64> = dates
+                .iter()
+                .map(|date| date.num_days_from_ce() as i64)
+                .collect();
+            let day_step = days[1] - days[0];
+            if month_step != 0
+                && dates.iter().all(|date| date.day() == first.day())
+                && months.windows(2).all(|w| w[1] - w[0] == month_step)
+            {
+                if month_step % 12 == 0 {
+                    (DateUnit::Year, month_step / 12)
+                } else {
+                    (DateUnit::Month, month_step)
+                }
+            } else if day_step != 0 && days.windows(2).all(|w| w[1] - w[0] == day_step) {
+                (DateUnit::Day, day_step)
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(Progression::Date(DateUnitProgression {
+        last,
+        time,
+        step,
+        unit,
+        date_system,
+        decimal_sep,
+    }))
+}
+
+/// Detects a series of times of the day given as serial numbers (fractions of a day).
+/// The times must be evenly spaced. A single time is followed by the same time an hour later.
+pub(crate) fn detect_time_progression(
+    serial_numbers: &[f64],
+    decimal_sep: char,
+) -> Option<Progression> {
+    let last = *serial_numbers.last()?;
+    let step = match serial_numbers {
+        [_] => 1.0 / 24.0,
+        [first, second, ..] => {
+            let step = second - first;
+            // Serial numbers are compared up to a millisecond
+            let tolerance = 1.0 / 86_400_000.0;
+            if step.abs() < tolerance
+                || serial_numbers
+                    .windows(2)
+                    .any(|w| (w[1] - w[0] - step).abs() > tolerance)
+            {
+                return None;
+            }
+            step
+        }
+        [] => return None,
+    };
+    Some(Progression::Numeric(NumericProgression {
+        last,
+        step,
+        decimal_sep,
+    }))
+}
+
+/// The best fit line through the values, or the best fit exponential curve if `growth` is true.
+/// Needs at least two values and, for growth trends, all of them positive.
+pub(crate) fn detect_trend(values: &[f64], growth: bool, decimal_sep: char) -> Option<Progression> {
+    if values.len() < 2 {
+        return None;
+    }
+    if growth && values.iter().any(|value| *value <= 0.0) {
+        return None;
+    }
+    let ys: Vec<f64> = if growth {
+        values.iter().map(|value| value.ln()).collect()
+    } else {
+        values.to_vec()
+    };
+    let count = ys.len() as f64;
+    let mean_x = (count - 1.0) / 2.0;
+    let mean_y = ys.iter().sum::<f64>() / count;
+    let mut sxx = 0.0;
+    let mut sxy = 0.0;
+    for (x, y) in ys.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        sxx += dx * dx;
+        sxy += dx * (y - mean_y);
+    }
+    let slope = sxy / sxx;
+    Some(Progression::Trend(TrendProgression {
+        intercept: mean_y - slope * mean_x,
+        slope,
+        count: ys.len(),
+        growth,
+        decimal_sep,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -628,22 +959,22 @@ mod tests {
         let locale = get_locale("en").unwrap();
 
         let values = vec!["1".to_string(), "3".to_string()];
-        let p = detect_progression(&values, locale).unwrap();
+        let p = detect_progression(&values, locale, &[]).unwrap();
         assert_eq!(p.next(0), "5");
 
         let values = vec!["X10".to_string(), "X20".to_string()];
-        let p = detect_progression(&values, locale).unwrap();
+        let p = detect_progression(&values, locale, &[]).unwrap();
         assert_eq!(p.next(0), "X30");
 
         let values = vec!["Mar".to_string(), "Apr".to_string()];
-        let p = detect_progression(&values, locale).unwrap();
+        let p = detect_progression(&values, locale, &[]).unwrap();
         assert_eq!(p.next(0), "May");
 
         let values = vec!["1".to_string(), "A".to_string(), "foo".to_string()];
-        assert!(detect_progression(&values, locale).is_none());
+        assert!(detect_progression(&values, locale, &[]).is_none());
 
         let values = vec!["1".to_string(), "2".to_string()];
-        let p = detect_progression(&values, locale).unwrap();
+        let p = detect_progression(&values, locale, &[]).unwrap();
         assert!(matches!(p, Progression::Numeric(_)));
         assert_eq!(p.next(0), "3");
     }
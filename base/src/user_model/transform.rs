@@ -0,0 +1,981 @@
+// Operational transformation of diffs.
+//
+// When two users edit the workbook at the same time, a diff created by one of them might refer to
+// rows or columns that the other one has inserted, deleted or moved in the meantime.
+// Here we rewrite a diff so that it can be applied after such a structural change.
+//
+// Sheets are referred to by index, so sheet insertions, deletions and moves remap the sheets of
+// the other diffs. Formulas refer to sheets by name, a sheet rename rewrites the formulas typed
+// concurrently.
+
+use crate::{
+    expressions::{
+        parser::{
+            stringify::{rename_sheet_in_node, to_localized_string_displaced, DisplaceData},
+            Node,
+        },
+        types::CellReferenceRC,
+    },
+    model::Model,
+};
+
+use super::history::{Diff, DiffType, QueueDiffs};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Axis {
+    Row,
+    Column,
+    // The sheets of the workbook, `sheet` is not used
+    Sheet,
+}
+
+/// A change in the rows or columns of a sheet that displaces cells
+#[derive(Clone, Copy)]
+enum StructuralChange {
+    Insert {
+        axis: Axis,
+        sheet: u32,
+        index: i32,
+        count: i32,
+    },
+    Delete {
+        axis: Axis,
+        sheet: u32,
+        index: i32,
+        count: i32,
+    },
+    Move {
+        axis: Axis,
+        sheet: u32,
+        index: i32,
+        delta: i32,
+    },
+}
+
+impl StructuralChange {
+    /// Returns the structural change of a diff, if any. Undoing an insertion is a deletion.
+    fn from_diff(diff_type: &DiffType, diff: &Diff) -> Option<StructuralChange> {
+        let undo = matches!(diff_type, DiffType::Undo);
+        let (axis, sheet, index, count, insert) = match diff {
+            Diff::InsertRows { sheet, row, count } => (Axis::Row, *sheet, *row, *count, !undo),
+            Diff::DeleteRows {
+                sheet, row, count, ..
+            } => (Axis::Row, *sheet, *row, *count, undo),
+            Diff::InsertColumns {
+                sheet,
+                column,
+                count,
+            } => (Axis::Column, *sheet, *column, *count, !undo),
+            Diff::DeleteColumns {
+                sheet,
+                column,
+                count,
+                ..
+            } => (Axis::Column, *sheet, *column, *count, undo),
+            Diff::MoveRow { sheet, row, delta } => {
+                return Some(StructuralChange::new_move(
+                    Axis::Row,
+                    *sheet,
+                    *row,
+                    *delta,
+                    undo,
+                ))
+            }
+            Diff::MoveColumn {
+                sheet,
+                column,
+                delta,
+            } => {
+                return Some(StructuralChange::new_move(
+                    Axis::Column,
+                    *sheet,
+                    *column,
+                    *delta,
+                    undo,
+                ))
+            }
+            _ => return None,
+        };
+        Some(if insert {
+            StructuralChange::Insert {
+                axis,
+                sheet,
+                index,
+                count,
+            }
+        } else {
+            StructuralChange::Delete {
+                axis,
+                sheet,
+                index,
+                count,
+            }
+        })
+    }
+
+    /// Returns the change in the list of sheets of a diff, if any
+    fn from_sheet_diff(diff_type: &DiffType, diff: &Diff) -> Option<StructuralChange> {
+        let undo = matches!(diff_type, DiffType::Undo);
+        let (index, insert) = match diff {
+            Diff::NewSheet { index, .. } => (*index, !undo),
+            Diff::DeleteSheet { sheet, .. } => (*sheet, undo),
+            // The copy goes right after the sheet
+            Diff::DuplicateSheet { sheet, .. } => (*sheet + 1, !undo),
+            Diff::MoveSheet { from, to } => {
+                return Some(StructuralChange::new_move(
+                    Axis::Sheet,
+                    0,
+                    *from as i32,
+                    *to as i32 - *from as i32,
+                    undo,
+                ))
+            }
+            _ => return None,
+        };
+        let (axis, sheet, index, count) = (Axis::Sheet, 0, index as i32, 1);
+        Some(if insert {
+            StructuralChange::Insert {
+                axis,
+                sheet,
+                index,
+                count,
+            }
+        } else {
+            StructuralChange::Delete {
+                axis,
+                sheet,
+                index,
+                count,
+            }
+        })
+    }
+
+    fn axis(&self) -> Axis {
+        match *self {
+            StructuralChange::Insert { axis, .. }
+            | StructuralChange::Delete { axis, .. }
+            | StructuralChange::Move { axis, .. } => axis,
+        }
+    }
+
+    /// New index of the sheet `sheet`, `None` if it was deleted
+    fn map_sheet(&self, sheet: u32) -> Option<u32> {
+        self.map_line(Axis::Sheet, 0, sheet as i32)
+            .map(|sheet| sheet as u32)
+    }
+
+    fn new_move(axis: Axis, sheet: u32, index: i32, delta: i32, undo: bool) -> StructuralChange {
+        if undo {
+            StructuralChange::Move {
+                axis,
+                sheet,
+                index: index + delta,
+                delta: -delta,
+            }
+        } else {
+            StructuralChange::Move {
+                axis,
+                sheet,
+                index,
+                delta,
+            }
+        }
+    }
+
+    fn affects(&self, line_axis: Axis, line_sheet: u32) -> bool {
+        let (axis, sheet) = match *self {
+            StructuralChange::Insert { axis, sheet, .. }
+            | StructuralChange::Delete { axis, sheet, .. }
+            | StructuralChange::Move { axis, sheet, .. } => (axis, sheet),
+        };
+        axis == line_axis && sheet == line_sheet
+    }
+
+    /// New position of the row or column `line`, `None` if it was deleted
+    fn map_line(&self, axis: Axis, sheet: u32, line: i32) -> Option<i32> {
+        if !self.affects(axis, sheet) {
+            return Some(line);
+        }
+        match *self {
+            StructuralChange::Insert { index, count, .. } => {
+                Some(if line >= index { line + count } else { line })
+            }
+            StructuralChange::Delete { index, count, .. } => {
+                if line < index {
+                    Some(line)
+                } else if line >= index + count {
+                    Some(line - count)
+                } else {
+                    None
+                }
+            }
+            StructuralChange::Move { index, delta, .. } => {
+                let target = index + delta;
+                Some(if line == index {
+                    target
+                } else if delta > 0 && line > index && line <= target {
+                    line - 1
+                } else if delta < 0 && line >= target && line < index {
+                    line + 1
+                } else {
+                    line
+                })
+            }
+        }
+    }
+
+    /// New position of an insertion point before `point`.
+    /// If `later` is true the insertion goes after another insertion at the same point.
+    fn map_insertion_point(&self, axis: Axis, sheet: u32, point: i32, later: bool) -> i32 {
+        if !self.affects(axis, sheet) {
+            return point;
+        }
+        match *self {
+            StructuralChange::Insert { index, count, .. } => {
+                if point > index || (point == index && later) {
+                    point + count
+                } else {
+                    point
+                }
+            }
+            StructuralChange::Delete { index, count, .. } => {
+                if point <= index {
+                    point
+                } else if point >= index + count {
+                    point - count
+                } else {
+                    index
+                }
+            }
+            StructuralChange::Move { .. } => self.map_line(axis, sheet, point).unwrap_or(point),
+        }
+    }
+
+    /// Splits the lines [first, first + count) in runs of contiguous lines after the change.
+    /// Returns a list of (new first line, count, offset of the run in the original lines)
+    fn map_runs(&self, axis: Axis, sheet: u32, first: i32, count: i32) -> Vec<(i32, i32, usize)> {
+        let mut runs: Vec<(i32, i32, usize)> = Vec::new();
+        for offset in 0..count {
+            if let Some(line) = self.map_line(axis, sheet, first + offset) {
+                match runs.last_mut() {
+                    Some((run_first, run_count, _)) if *run_first + *run_count == line => {
+                        *run_count += 1;
+                    }
+                    _ => runs.push((line, 1, offset as usize)),
+                }
+            }
+        }
+        runs
+    }
+
+    fn get_displace_data(&self) -> DisplaceData {
+        match *self {
+            // Formulas refer to sheets by name
+            StructuralChange::Insert {
+                axis: Axis::Sheet, ..
+            }
+            | StructuralChange::Delete {
+                axis: Axis::Sheet, ..
+            }
+            | StructuralChange::Move {
+                axis: Axis::Sheet, ..
+            } => DisplaceData::None,
+            StructuralChange::Insert {
+                axis: Axis::Row,
+                sheet,
+                index,
+                count,
+            } => DisplaceData::Row {
+                sheet,
+                row: index,
+                delta: count,
+            },
+            StructuralChange::Insert {
+                axis: Axis::Column,
+                sheet,
+                index,
+                count,
+            } => DisplaceData::Column {
+                sheet,
+                column: index,
+                delta: count,
+            },
+            StructuralChange::Delete {
+                axis: Axis::Row,
+                sheet,
+                index,
+                count,
+            } => DisplaceData::Row {
+                sheet,
+                row: index,
+                delta: -count,
+            },
+            StructuralChange::Delete {
+                axis: Axis::Column,
+                sheet,
+                index,
+                count,
+            } => DisplaceData::Column {
+                sheet,
+                column: index,
+                delta: -count,
+            },
+            StructuralChange::Move {
+                axis: Axis::Row,
+                sheet,
+                index,
+                delta,
+            } => DisplaceData::RowMove {
+                sheet,
+                row: index,
+                delta,
+            },
+            StructuralChange::Move {
+                axis: Axis::Column,
+                sheet,
+                index,
+                delta,
+            } => DisplaceData::ColumnMove {
+                sheet,
+                column: index,
+                delta,
+            },
+        }
+    }
+}
+
+/// A change made by another user that the diffs need to be adapted to
+enum Change {
+    Structural(StructuralChange),
+    RenameSheet { old_name: String, new_name: String },
+}
+
+impl Change {
+    fn from_diff(diff_type: &DiffType, diff: &Diff) -> Option<Change> {
+        if let Diff::RenameSheet {
+            old_value,
+            new_value,
+            ..
+        } = diff
+        {
+            let (old_name, new_name) = match diff_type {
+                DiffType::Redo => (old_value, new_value),
+                DiffType::Undo => (new_value, old_value),
+            };
+            return Some(Change::RenameSheet {
+                old_name: old_name.clone(),
+                new_name: new_name.clone(),
+            });
+        }
+        StructuralChange::from_diff(diff_type, diff)
+            .or_else(|| StructuralChange::from_sheet_diff(diff_type, diff))
+            .map(Change::Structural)
+    }
+}
+
+/// Rewrites the references of a formula typed in (sheet, row, column) after the change
+fn displace_user_input(
+    model: &mut Model,
+    sheet: u32,
+    row: i32,
+    column: i32,
+    value: &str,
+    change: &StructuralChange,
+) -> String {
+    let formula = match value.strip_prefix('=') {
+        Some(formula) => formula,
+        None => return value.to_string(),
+    };
+    let sheet_name = match model.workbook.worksheet(sheet) {
+        Ok(worksheet) => worksheet.get_name(),
+        Err(_) => return value.to_string(),
+    };
+    let context = CellReferenceRC {
+        sheet: sheet_name,
+        row,
+        column,
+    };
+    let node = model.parser.parse(formula, &context);
+    if let Node::ParseErrorKind { .. } = node {
+        return value.to_string();
+    }
+    let displaced = to_localized_string_displaced(
+        &node,
+        &context,
+        &change.get_displace_data(),
+        model.locale,
+        model.language,
+    );
+    format!("={displaced}")
+}
+
+/// Returns the diffs equivalent to `diff` once `change` has been applied.
+/// The result is empty if the diff does not make sense anymore (i.e. the cell was deleted).
+fn transform_diff(
+    model: &mut Model,
+    diff_type: &DiffType,
+    diff: &Diff,
+    change: &StructuralChange,
+    later: bool,
+) -> Vec<Diff> {
+    let undo = matches!(diff_type, DiffType::Undo);
+    let mut diff = diff.clone();
+    match &mut diff {
+        Diff::SetCellValue {
+            sheet,
+            row,
+            column,
+            new_value,
+            ..
+        } => {
+            if !undo {
+                *new_value = displace_user_input(model, *sheet, *row, *column, new_value, change);
+            }
+            match (
+                change.map_line(Axis::Row, *sheet, *row),
+                change.map_line(Axis::Column, *sheet, *column),
+            ) {
+                (Some(new_row), Some(new_column)) => {
+                    *row = new_row;
+                    *column = new_column;
+                }
+                _ => return vec![],
+            }
+        }
+        Diff::SetCellRichText {
+            sheet, row, column, ..
+        }
+        | Diff::CellClearContents {
+            sheet, row, column, ..
+        }
+        | Diff::CellClearAll {
+            sheet, row, column, ..
+        }
+        | Diff::CellClearFormatting {
+            sheet, row, column, ..
+        }
+        | Diff::SetCellStyle {
+            sheet, row, column, ..
+        } => match (
+            change.map_line(Axis::Row, *sheet, *row),
+            change.map_line(Axis::Column, *sheet, *column),
+        ) {
+            (Some(new_row), Some(new_column)) => {
+                *row = new_row;
+                *column = new_column;
+            }
+            _ => return vec![],
+        },
+        Diff::SetRowHeight { sheet, row, .. }
+        | Diff::SetRowStyle { sheet, row, .. }
+        | Diff::DeleteRowStyle { sheet, row, .. }
+        | Diff::SetRowHidden { sheet, row, .. }
+        | Diff::SetRowOutlineLevel { sheet, row, .. }
+        | Diff::SetRowCollapsed { sheet, row, .. } => {
+            match change.map_line(Axis::Row, *sheet, *row) {
+                Some(new_row) => *row = new_row,
+                None => return vec![],
+            }
+        }
+        Diff::SetColumnWidth { sheet, column, .. }
+        | Diff::SetColumnStyle { sheet, column, .. }
+        | Diff::DeleteColumnStyle { sheet, column, .. }
+        | Diff::SetColumnHidden { sheet, column, .. }
+        | Diff::SetColumnOutlineLevel { sheet, column, .. }
+        | Diff::SetColumnCollapsed { sheet, column, .. } => {
+            match change.map_line(Axis::Column, *sheet, *column) {
+                Some(new_column) => *column = new_column,
+                None => return vec![],
+            }
+        }
+        Diff::InsertRows { sheet, row, count } if undo => {
+            // Undoing an insertion deletes the rows, they must still be contiguous
+            match change.map_runs(Axis::Row, *sheet, *row, *count)[..] {
+                [(new_row, new_count, _)] => {
+                    *row = new_row;
+                    *count = new_count;
+                }
+                _ => return vec![],
+            }
+        }
+        Diff::InsertRows { sheet, row, .. } | Diff::DeleteRows { sheet, row, .. } if undo => {
+            // Undoing a deletion inserts rows
+            *row = change.map_insertion_point(Axis::Row, *sheet, *row, later);
+        }
+        Diff::InsertRows { sheet, row, .. } => {
+            *row = change.map_insertion_point(Axis::Row, *sheet, *row, later);
+        }
+        Diff::DeleteRows {
+            sheet,
+            row,
+            count,
+            old_data,
+            old_merged_cells,
+        } => {
+            // Rows inserted in the middle of the deleted ones are kept, bottom runs go first
+            let runs = change.map_runs(Axis::Row, *sheet, *row, *count);
+            return runs
+                .iter()
+                .rev()
+                .map(|(new_row, new_count, offset)| Diff::DeleteRows {
+                    sheet: *sheet,
+                    row: *new_row,
+                    count: *new_count,
+                    old_data: old_data
+                        .iter()
+                        .skip(*offset)
+                        .take(*new_count as usize)
+                        .cloned()
+                        .collect(),
+                    old_merged_cells: old_merged_cells.clone(),
+                })
+                .collect();
+        }
+        Diff::InsertColumns {
+            sheet,
+            column,
+            count,
+        } if undo => match change.map_runs(Axis::Column, *sheet, *column, *count)[..] {
+            [(new_column, new_count, _)] => {
+                *column = new_column;
+                *count = new_count;
+            }
+            _ => return vec![],
+        },
+        Diff::InsertColumns { sheet, column, .. } | Diff::DeleteColumns { sheet, column, .. }
+            if undo =>
+        {
+            *column = change.map_insertion_point(Axis::Column, *sheet, *column, later);
+        }
+        Diff::InsertColumns { sheet, column, .. } => {
+            *column = change.map_insertion_point(Axis::Column, *sheet, *column, later);
+        }
+        Diff::DeleteColumns {
+            sheet,
+            column,
+            count,
+            old_data,
+            old_merged_cells,
+        } => {
+            let runs = change.map_runs(Axis::Column, *sheet, *column, *count);
+            return runs
+                .iter()
+                .rev()
+                .map(|(new_column, new_count, offset)| Diff::DeleteColumns {
+                    sheet: *sheet,
+                    column: *new_column,
+                    count: *new_count,
+                    old_data: old_data
+                        .iter()
+                        .skip(*offset)
+                        .take(*new_count as usize)
+                        .cloned()
+                        .collect(),
+                    old_merged_cells: old_merged_cells.clone(),
+                })
+                .collect();
+        }
+        Diff::MoveRow { sheet, row, delta } => {
+            // The row goes next to the row that was at the target position
+            let target = *row + *delta;
+            let new_row = match change.map_line(Axis::Row, *sheet, *row) {
+                Some(new_row) => new_row,
+                None => return vec![],
+            };
+            let new_target = change
+                .map_line(Axis::Row, *sheet, target)
+                .unwrap_or_else(|| change.map_insertion_point(Axis::Row, *sheet, target, later));
+            if new_target == new_row {
+                return vec![];
+            }
+            *row = new_row;
+            *delta = new_target - new_row;
+        }
+        Diff::MoveColumn {
+            sheet,
+            column,
+            delta,
+        } => {
+            let target = *column + *delta;
+            let new_column = match change.map_line(Axis::Column, *sheet, *column) {
+                Some(new_column) => new_column,
+                None => return vec![],
+            };
+            let new_target = change
+                .map_line(Axis::Column, *sheet, target)
+                .unwrap_or_else(|| change.map_insertion_point(Axis::Column, *sheet, target, later));
+            if new_target == new_column {
+                return vec![];
+            }
+            *column = new_column;
+            *delta = new_target - new_column;
+        }
+        Diff::SortRange {
+            sheet,
+            row,
+            column,
+            width,
+            height,
+            ..
+        } => {
+            // The range is sorted only if it has not been split or resized
+            let rows = change.map_runs(Axis::Row, *sheet, *row, *height);
+            let columns = change.map_runs(Axis::Column, *sheet, *column, *width);
+            match (&rows[..], &columns[..]) {
+                ([(new_row, new_height, _)], [(new_column, new_width, _)])
+                    if *new_height == *height && *new_width == *width =>
+                {
+                    *row = *new_row;
+                    *column = *new_column;
+                }
+                _ => return vec![],
+            }
+        }
+        Diff::MoveCells {
+            sheet,
+            row,
+            column,
+            row_delta,
+            column_delta,
+            ..
+        } => {
+            // The block follows its first cell, it is not moved if that cell was deleted.
+            // The destination follows the cell it lands on, or the place where it was if deleted.
+            let (new_row, new_column) = match (
+                change.map_line(Axis::Row, *sheet, *row),
+                change.map_line(Axis::Column, *sheet, *column),
+            ) {
+                (Some(new_row), Some(new_column)) => (new_row, new_column),
+                _ => return vec![],
+            };
+            let target_row = *row + *row_delta;
+            let target_column = *column + *column_delta;
+            let new_target_row = change
+                .map_line(Axis::Row, *sheet, target_row)
+                .unwrap_or_else(|| {
+                    change.map_insertion_point(Axis::Row, *sheet, target_row, later)
+                });
+            let new_target_column = change
+                .map_line(Axis::Column, *sheet, target_column)
+                .unwrap_or_else(|| {
+                    change.map_insertion_point(Axis::Column, *sheet, target_column, later)
+                });
+            if (new_target_row, new_target_column) == (new_row, new_column) {
+                return vec![];
+            }
+            *row = new_row;
+            *column = new_column;
+            *row_delta = new_target_row - new_row;
+            *column_delta = new_target_column - new_column;
+        }
+        Diff::SetDateSystem { old_data, .. } => {
+            // The dates follow their cells, the ones in deleted cells are dropped
+            *old_data = std::mem::take(old_data)
+                .into_iter()
+                .filter_map(|mut cell_data| {
+                    cell_data.row = change.map_line(Axis::Row, cell_data.sheet, cell_data.row)?;
+                    cell_data.column =
+                        change.map_line(Axis::Column, cell_data.sheet, cell_data.column)?;
+                    Some(cell_data)
+                })
+                .collect();
+        }
+        _ => {}
+    }
+    vec![diff]
+}
+
+/// Rewrites the references to the sheet `old_name` in a formula typed before it was renamed
+fn rename_sheet_in_user_input(
+    model: &mut Model,
+    sheet: u32,
+    row: i32,
+    column: i32,
+    value: &str,
+    old_name: &str,
+    new_name: &str,
+) -> String {
+    let formula = match value.strip_prefix('=') {
+        Some(formula) => formula,
+        None => return value.to_string(),
+    };
+    let sheet_name = match model.workbook.worksheet(sheet) {
+        Ok(worksheet) => worksheet.get_name(),
+        Err(_) => return value.to_string(),
+    };
+    // The model might be before or after the rename, we parse with the old name
+    let mut worksheet_names = model.workbook.get_worksheet_names();
+    let sheet_index = match worksheet_names
+        .iter()
+        .position(|name| name == old_name)
+        .or_else(|| worksheet_names.iter().position(|name| name == new_name))
+    {
+        Some(index) => index,
+        None => return value.to_string(),
+    };
+    worksheet_names[sheet_index] = old_name.to_string();
+    let context = CellReferenceRC {
+        sheet: if sheet_name == new_name {
+            old_name.to_string()
+        } else {
+            sheet_name
+        },
+        row,
+        column,
+    };
+    let defined_names = model.workbook.get_defined_names_with_scope();
+    model
+        .parser
+        .set_worksheets_and_names(worksheet_names, defined_names.clone());
+    let mut node = model.parser.parse(formula, &context);
+    model
+        .parser
+        .set_worksheets_and_names(model.workbook.get_worksheet_names(), defined_names);
+    if let Node::ParseErrorKind { .. } = node {
+        return value.to_string();
+    }
+    rename_sheet_in_node(&mut node, sheet_index as u32, new_name);
+    let formula = to_localized_string_displaced(
+        &node,
+        &context,
+        &DisplaceData::None,
+        model.locale,
+        model.language,
+    );
+    format!("={formula}")
+}
+
+/// Returns the diff equivalent to `diff` once the sheet `old_name` is renamed to `new_name`
+fn rename_sheet_in_diff(
+    model: &mut Model,
+    diff_type: &DiffType,
+    diff: &Diff,
+    old_name: &str,
+    new_name: &str,
+) -> Diff {
+    let mut diff = diff.clone();
+    if let (
+        DiffType::Redo,
+        Diff::SetCellValue {
+            sheet,
+            row,
+            column,
+            new_value,
+            ..
+        },
+    ) = (diff_type, &mut diff)
+    {
+        *new_value =
+            rename_sheet_in_user_input(model, *sheet, *row, *column, new_value, old_name, new_name);
+    }
+    diff
+}
+
+// The index of the sheet of the diff, if it is about a single sheet
+fn get_diff_sheet(diff: &mut Diff) -> Option<&mut u32> {
+    match diff {
+        Diff::SetCellValue { sheet, .. }
+        | Diff::SetCellRichText { sheet, .. }
+        | Diff::CellClearContents { sheet, .. }
+        | Diff::CellClearAll { sheet, .. }
+        | Diff::CellClearFormatting { sheet, .. }
+        | Diff::SetCellStyle { sheet, .. }
+        | Diff::SetColumnWidth { sheet, .. }
+        | Diff::SetRowHeight { sheet, .. }
+        | Diff::SetColumnStyle { sheet, .. }
+        | Diff::SetRowStyle { sheet, .. }
+        | Diff::DeleteColumnStyle { sheet, .. }
+        | Diff::DeleteRowStyle { sheet, .. }
+        | Diff::InsertRows { sheet, .. }
+        | Diff::DeleteRows { sheet, .. }
+        | Diff::InsertColumns { sheet, .. }
+        | Diff::DeleteColumns { sheet, .. }
+        | Diff::SetFrozenRowsCount { sheet, .. }
+        | Diff::SetFrozenColumnsCount { sheet, .. }
+        | Diff::SetShowGridLines { sheet, .. }
+        | Diff::MoveColumn { sheet, .. }
+        | Diff::MoveRow { sheet, .. }
+        | Diff::SetRowHidden { sheet, .. }
+        | Diff::SetColumnHidden { sheet, .. }
+        | Diff::SetRowOutlineLevel { sheet, .. }
+        | Diff::SetColumnOutlineLevel { sheet, .. }
+        | Diff::SetRowCollapsed { sheet, .. }
+        | Diff::SetColumnCollapsed { sheet, .. }
+        | Diff::SetOutlineSummaryBelow { sheet, .. }
+        | Diff::SetOutlineSummaryRight { sheet, .. }
+        | Diff::SetPageSetup { sheet, .. }
+        | Diff::SetPrintArea { sheet, .. }
+        | Diff::SetPrintTitles { sheet, .. }
+        | Diff::SetSheetProtection { sheet, .. }
+        | Diff::SetAutoFilter { sheet, .. }
+        | Diff::SetMergedCells { sheet, .. }
+        | Diff::SortRange { sheet, .. }
+        | Diff::RenameSheet { index: sheet, .. }
+        | Diff::SetSheetColor { index: sheet, .. }
+        | Diff::SetSheetState { index: sheet, .. } => Some(sheet),
+        _ => None,
+    }
+}
+
+/// Returns the diffs equivalent to `diff` once the sheets have been inserted, deleted or moved.
+/// The result is empty if the sheet of the diff was deleted.
+fn transform_sheet_diff(
+    diff_type: &DiffType,
+    diff: &Diff,
+    change: &StructuralChange,
+    later: bool,
+) -> Vec<Diff> {
+    let undo = matches!(diff_type, DiffType::Undo);
+    let insertion_point = |index: u32| -> u32 {
+        change.map_insertion_point(Axis::Sheet, 0, index as i32, later) as u32
+    };
+    let mut diff = diff.clone();
+    if let Some(sheet) = get_diff_sheet(&mut diff) {
+        match change.map_sheet(*sheet) {
+            Some(new_sheet) => *sheet = new_sheet,
+            None => return vec![],
+        }
+        return vec![diff];
+    }
+    // Undoing a new sheet deletes it, undoing a deletion inserts it back
+    let deletes_sheet = matches!(diff, Diff::NewSheet { .. }) == undo;
+    match &mut diff {
+        Diff::NewSheet { index, .. } | Diff::DeleteSheet { sheet: index, .. } => {
+            if deletes_sheet {
+                match change.map_sheet(*index) {
+                    Some(new_index) => *index = new_index,
+                    None => return vec![],
+                }
+            } else {
+                *index = insertion_point(*index);
+            }
+        }
+        Diff::DuplicateSheet { sheet, .. } if undo => {
+            // Undoing deletes the copy, right after the sheet
+            match change.map_sheet(*sheet + 1) {
+                Some(copy) if copy > 0 => *sheet = copy - 1,
+                _ => return vec![],
+            }
+        }
+        Diff::DuplicateSheet { sheet, .. } => match change.map_sheet(*sheet) {
+            Some(new_sheet) => *sheet = new_sheet,
+            None => return vec![],
+        },
+        Diff::MoveSheet { from, to } => {
+            // When undone the sheet goes back from `to` to `from`
+            let (index, target) = if undo { (*to, *from) } else { (*from, *to) };
+            let new_index = match change.map_sheet(index) {
+                Some(new_index) => new_index,
+                None => return vec![],
+            };
+            let new_target = change
+                .map_sheet(target)
+                .unwrap_or_else(|| insertion_point(target));
+            if new_index == new_target {
+                return vec![];
+            }
+            (*from, *to) = if undo {
+                (new_target, new_index)
+            } else {
+                (new_index, new_target)
+            };
+        }
+        Diff::CreateDefinedName { scope, .. } | Diff::DeleteDefinedName { scope, .. } => {
+            if let Some(sheet) = scope {
+                match change.map_sheet(*sheet) {
+                    Some(new_sheet) => *sheet = new_sheet,
+                    None => return vec![],
+                }
+            }
+        }
+        Diff::UpdateDefinedName {
+            scope, new_scope, ..
+        } => {
+            for sheet in [scope, new_scope].into_iter().flatten() {
+                match change.map_sheet(*sheet) {
+                    Some(new_sheet) => *sheet = new_sheet,
+                    None => return vec![],
+                }
+            }
+        }
+        Diff::MoveCells {
+            sheet, old_data, ..
+        } => {
+            match change.map_sheet(*sheet) {
+                Some(new_sheet) => *sheet = new_sheet,
+                None => return vec![],
+            }
+            // Cells of other sheets with formulas that referenced the block
+            *old_data = std::mem::take(old_data)
+                .into_iter()
+                .filter_map(|mut cell_data| {
+                    cell_data.sheet = change.map_sheet(cell_data.sheet)?;
+                    Some(cell_data)
+                })
+                .collect();
+        }
+        Diff::SetDateSystem { old_data, .. } => {
+            *old_data = std::mem::take(old_data)
+                .into_iter()
+                .filter_map(|mut cell_data| {
+                    cell_data.sheet = change.map_sheet(cell_data.sheet)?;
+                    Some(cell_data)
+                })
+                .collect();
+        }
+        _ => {}
+    }
+    vec![diff]
+}
+
+/// Transforms the list of diffs `list` so that it can be applied after the diffs in `applied`.
+/// `later` is true if `list` goes after `applied` when both insert rows (or columns) at the
+/// same place.
+pub(crate) fn transform_queue_diffs(
+    model: &mut Model,
+    list: Vec<QueueDiffs>,
+    applied: &[QueueDiffs],
+    later: bool,
+) -> Vec<QueueDiffs> {
+    let mut list = list;
+    for queue_diffs in applied {
+        let diffs: Vec<&Diff> = match queue_diffs.r#type {
+            DiffType::Redo => queue_diffs.list.iter().collect(),
+            // Undo diff lists are applied in reverse order
+            DiffType::Undo => queue_diffs.list.iter().rev().collect(),
+        };
+        for diff in diffs {
+            if let Some(change) = Change::from_diff(&queue_diffs.r#type, diff) {
+                list = list
+                    .into_iter()
+                    .map(|item| QueueDiffs {
+                        list: item
+                            .list
+                            .iter()
+                            .flat_map(|d| match &change {
+                                Change::RenameSheet { old_name, new_name } => {
+                                    vec![rename_sheet_in_diff(
+                                        model,
+                                        &item.r#type,
+                                        d,
+                                        old_name,
+                                        new_name,
+                                    )]
+                                }
+                                Change::Structural(change) if change.axis() == Axis::Sheet => {
+                                    transform_sheet_diff(&item.r#type, d, change, later)
+                                }
+                                Change::Structural(change) => {
+                                    transform_diff(model, &item.r#type, d, change, later)
+                                }
+                            })
+                            .collect(),
+                        r#type: item.r#type,
+                    })
+                    .filter(|item| !item.list.is_empty())
+                    .collect();
+            }
+        }
+    }
+    list
+}
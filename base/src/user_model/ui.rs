@@ -5,12 +5,13 @@ use serde::{Deserialize, Serialize};
 use crate::{
     constants::{LAST_COLUMN, LAST_ROW},
     expressions::utils::{is_valid_column_number, is_valid_row},
+    types::MergedRange,
     worksheet::NavigationDirection,
 };
 
 use super::common::UserModel;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[cfg_attr(test, derive(PartialEq, Debug))]
 pub struct SelectedView {
     pub sheet: u32,
@@ -22,6 +23,19 @@ pub struct SelectedView {
 }
 
 impl<'a> UserModel<'a> {
+    /// Returns the block of merged cells that contains the cell or a block with just the cell
+    fn get_cell_block(&self, sheet: u32, row: i32, column: i32) -> MergedRange {
+        match self.model.get_merged_range(sheet, row, column) {
+            Ok(Some(range)) => range,
+            _ => MergedRange {
+                first_row: row,
+                first_column: column,
+                last_row: row,
+                last_column: column,
+            },
+        }
+    }
+
     /// Returns the selected sheet index
     pub fn get_selected_sheet(&self) -> u32 {
         if let Some(view) = self.model.workbook.views.get(&self.model.view_id) {
@@ -77,6 +91,26 @@ impl<'a> UserModel<'a> {
         }
     }
 
+    // Restores a view saved in the history. The sheet might not exist anymore.
+    pub(super) fn restore_view(&mut self, selected_view: &SelectedView) {
+        let view_id = self.model.view_id;
+        let sheet = selected_view.sheet;
+        let worksheet = match self.model.workbook.worksheet_mut(sheet) {
+            Ok(worksheet) => worksheet,
+            Err(_) => return,
+        };
+        if let Some(view) = worksheet.views.get_mut(&view_id) {
+            view.row = selected_view.row;
+            view.column = selected_view.column;
+            view.range = selected_view.range;
+            view.top_row = selected_view.top_row;
+            view.left_column = selected_view.left_column;
+        }
+        if let Some(view) = self.model.workbook.views.get_mut(&view_id) {
+            view.sheet = sheet;
+        }
+    }
+
     /// Sets the the selected sheet
     pub fn set_selected_sheet(&mut self, sheet: u32) -> Result<(), String> {
         if self.model.workbook.worksheet(sheet).is_err() {
@@ -104,11 +138,18 @@ impl<'a> UserModel<'a> {
         if self.model.workbook.worksheet(sheet).is_err() {
             return Err(format!("Invalid worksheet index {sheet}"));
         }
+        // Selecting a merged cell selects the whole block
+        let block = self.get_cell_block(sheet, row, column);
         if let Ok(worksheet) = self.model.workbook.worksheet_mut(sheet) {
             if let Some(view) = worksheet.views.get_mut(&0) {
-                view.row = row;
-                view.column = column;
-                view.range = [row, column, row, column];
+                view.row = block.first_row;
+                view.column = block.first_column;
+                view.range = [
+                    block.first_row,
+                    block.first_column,
+                    block.last_row,
+                    block.last_column,
+                ];
             }
         }
         Ok(())
@@ -393,21 +434,32 @@ impl<'a> UserModel<'a> {
             Some(s) => s,
             None => return Err("View not found".to_string()),
         };
-        let new_column = view.column + 1;
+        // Merged cells are skipped over as a whole
+        let new_column = self
+            .get_cell_block(sheet, view.row, view.column)
+            .last_column
+            + 1;
         if !is_valid_column_number(new_column) {
             return Ok(());
         }
+        let block = self.get_cell_block(sheet, view.row, new_column);
         // if the column is not fully visible we 'scroll' right until it is
         let mut width = 0.0;
         let mut column = view.left_column;
-        while column <= new_column {
+        while column <= block.last_column {
             width += self.model.get_column_width(sheet, column)?;
             column += 1;
         }
         if let Ok(worksheet) = self.model.workbook.worksheet_mut(sheet) {
             if let Some(view) = worksheet.views.get_mut(&self.model.view_id) {
-                view.column = new_column;
-                view.range = [view.row, new_column, view.row, new_column];
+                view.row = block.first_row;
+                view.column = block.first_column;
+                view.range = [
+                    block.first_row,
+                    block.first_column,
+                    block.last_row,
+                    block.last_column,
+                ];
                 if width > window_width as f64 {
                     view.left_column += 1;
                 }
@@ -431,17 +483,28 @@ impl<'a> UserModel<'a> {
             Some(s) => s,
             None => return Err("View not found".to_string()),
         };
-        let new_column = view.column - 1;
+        // Merged cells are skipped over as a whole
+        let new_column = self
+            .get_cell_block(sheet, view.row, view.column)
+            .first_column
+            - 1;
         if !is_valid_column_number(new_column) {
             return Ok(());
         }
+        let block = self.get_cell_block(sheet, view.row, new_column);
         // if the column is not fully visible we 'scroll' right until it is
         if let Ok(worksheet) = self.model.workbook.worksheet_mut(sheet) {
             if let Some(view) = worksheet.views.get_mut(&self.model.view_id) {
-                view.column = new_column;
-                view.range = [view.row, new_column, view.row, new_column];
-                if new_column < view.left_column {
-                    view.left_column = new_column;
+                view.row = block.first_row;
+                view.column = block.first_column;
+                view.range = [
+                    block.first_row,
+                    block.first_column,
+                    block.last_row,
+                    block.last_column,
+                ];
+                if block.first_column < view.left_column {
+                    view.left_column = block.first_column;
                 }
             }
         }
@@ -463,17 +526,25 @@ impl<'a> UserModel<'a> {
             Some(s) => s,
             None => return Err("View not found".to_string()),
         };
-        let new_row = view.row - 1;
+        // Merged cells are skipped over as a whole
+        let new_row = self.get_cell_block(sheet, view.row, view.column).first_row - 1;
         if !is_valid_row(new_row) {
             return Ok(());
         }
+        let block = self.get_cell_block(sheet, new_row, view.column);
         // if the column is not fully visible we 'scroll' right until it is
         if let Ok(worksheet) = self.model.workbook.worksheet_mut(sheet) {
             if let Some(view) = worksheet.views.get_mut(&self.model.view_id) {
-                view.row = new_row;
-                view.range = [new_row, view.column, new_row, view.column];
-                if new_row < view.top_row {
-                    view.top_row = new_row;
+                view.row = block.first_row;
+                view.column = block.first_column;
+                view.range = [
+                    block.first_row,
+                    block.first_column,
+                    block.last_row,
+                    block.last_column,
+                ];
+                if block.first_row < view.top_row {
+                    view.top_row = block.first_row;
                 }
             }
         }
@@ -496,21 +567,29 @@ impl<'a> UserModel<'a> {
             Some(s) => s,
             None => return Err("View not found".to_string()),
         };
-        let new_row = view.row + 1;
+        // Merged cells are skipped over as a whole
+        let new_row = self.get_cell_block(sheet, view.row, view.column).last_row + 1;
         if !is_valid_row(new_row) {
             return Ok(());
         }
+        let block = self.get_cell_block(sheet, new_row, view.column);
         // if the row is not fully visible we 'scroll' down until it is
         let mut height = 0.0;
         let mut row = view.top_row;
-        while row <= new_row + 1 && row <= LAST_ROW {
+        while row <= block.last_row + 1 && row <= LAST_ROW {
             height += self.model.get_row_height(sheet, row)?;
             row += 1;
         }
         if let Ok(worksheet) = self.model.workbook.worksheet_mut(sheet) {
             if let Some(view) = worksheet.views.get_mut(&self.model.view_id) {
-                view.row = new_row;
-                view.range = [new_row, view.column, new_row, view.column];
+                view.row = block.first_row;
+                view.column = block.first_column;
+                view.range = [
+                    block.first_row,
+                    block.first_column,
+                    block.last_row,
+                    block.last_column,
+                ];
                 if height > window_height as f64 {
                     view.top_row += 1;
                 }
@@ -747,6 +826,10 @@ impl<'a> UserModel<'a> {
             return Ok(()); // No change in selection
         }
 
+        // If we land on a merged cell we select the whole block
+        let block = self.get_cell_block(sheet, new_row, new_column);
+        let (new_row, new_column) = (block.first_row, block.first_column);
+
         let mut top_row = view.top_row;
         let mut left_column = view.left_column;
 
@@ -795,7 +878,12 @@ impl<'a> UserModel<'a> {
             if let Some(view) = worksheet.views.get_mut(&self.model.view_id) {
                 view.row = new_row;
                 view.column = new_column;
-                view.range = [new_row, new_column, new_row, new_column];
+                view.range = [
+                    block.first_row,
+                    block.first_column,
+                    block.last_row,
+                    block.last_column,
+                ];
 
                 view.top_row = top_row;
                 view.left_column = left_column;
@@ -111,6 +111,9 @@ impl Worksheet {
             width: constants::DEFAULT_COLUMN_WIDTH,
             custom_width: false,
             style: Some(style_index),
+            hidden: false,
+            outline_level: 0,
+            collapsed: false,
         }];
         Ok(())
     }
@@ -139,6 +142,8 @@ impl Worksheet {
             custom_height: false,
             s: style_index,
             hidden: false,
+            outline_level: 0,
+            collapsed: false,
         });
         Ok(())
     }
@@ -185,34 +190,29 @@ impl Worksheet {
         if split {
             let min = cols[index].min;
             let max = cols[index].max;
-            let custom_width = cols[index].custom_width;
-            let width = cols[index].width;
             let pre = Col {
                 min,
                 max: column - 1,
-                width,
-                custom_width,
-                style: cols[index].style,
+                ..cols[index].clone()
             };
             let col = Col {
                 min: column,
                 max: column,
-                width,
-                custom_width,
                 style: None,
+                ..cols[index].clone()
             };
             let post = Col {
                 min: column + 1,
                 max,
-                width,
-                custom_width,
-                style: cols[index].style,
+                ..cols[index].clone()
             };
+            // We keep the column if it has any property other than the style
+            let keep_column = col.custom_width || col.hidden || col.outline_level > 0;
             cols.remove(index);
             if column != max {
                 cols.insert(index, post);
             }
-            if custom_width {
+            if keep_column {
                 cols.insert(index, col);
             }
             if column != min {
@@ -362,10 +362,121 @@ impl Worksheet {
             custom_height: true,
             s: 0,
             hidden: false,
+            outline_level: 0,
+            collapsed: false,
         });
         Ok(())
     }
 
+    /// Hides or shows a row.
+    ///
+    /// Fails if row index is outside allowed range.
+    pub fn set_row_hidden(&mut self, row: i32, hidden: bool) -> Result<(), String> {
+        if !is_valid_row(row) {
+            return Err(format!("Row number '{row}' is not valid."));
+        }
+        for r in self.rows.iter_mut() {
+            if r.r == row {
+                r.hidden = hidden;
+                return Ok(());
+            }
+        }
+        if hidden {
+            self.rows.push(Row {
+                height: constants::DEFAULT_ROW_HEIGHT / constants::ROW_HEIGHT_FACTOR,
+                r: row,
+                custom_format: false,
+                custom_height: false,
+                s: 0,
+                hidden: true,
+                outline_level: 0,
+                collapsed: false,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns true if the row is hidden
+    pub fn is_row_hidden(&self, row: i32) -> Result<bool, String> {
+        if !is_valid_row(row) {
+            return Err(format!("Row number '{row}' is not valid."));
+        }
+        Ok(self.rows.iter().any(|r| r.r == row && r.hidden))
+    }
+
+    // Returns the row properties, adding them if the row has none
+    fn row_mut(&mut self, row: i32) -> &mut Row {
+        let index = match self.rows.iter().position(|r| r.r == row) {
+            Some(index) => index,
+            None => {
+                self.rows.push(Row {
+                    height: constants::DEFAULT_ROW_HEIGHT / constants::ROW_HEIGHT_FACTOR,
+                    r: row,
+                    custom_format: false,
+                    custom_height: false,
+                    s: 0,
+                    hidden: false,
+                    outline_level: 0,
+                    collapsed: false,
+                });
+                self.rows.len() - 1
+            }
+        };
+        &mut self.rows[index]
+    }
+
+    /// Sets the outline level of a row. Level 0 means the row is not grouped.
+    ///
+    /// Fails if row index is outside allowed range or the level is larger than 7.
+    pub fn set_row_outline_level(&mut self, row: i32, outline_level: u8) -> Result<(), String> {
+        if !is_valid_row(row) {
+            return Err(format!("Row number '{row}' is not valid."));
+        }
+        if outline_level > constants::MAX_OUTLINE_LEVEL {
+            return Err(format!("Invalid outline level: {outline_level}"));
+        }
+        if outline_level == 0 && !self.rows.iter().any(|r| r.r == row) {
+            return Ok(());
+        }
+        self.row_mut(row).outline_level = outline_level;
+        Ok(())
+    }
+
+    /// Returns the outline level of a row
+    pub fn get_row_outline_level(&self, row: i32) -> Result<u8, String> {
+        if !is_valid_row(row) {
+            return Err(format!("Row number '{row}' is not valid."));
+        }
+        Ok(self
+            .rows
+            .iter()
+            .find(|r| r.r == row)
+            .map_or(0, |r| r.outline_level))
+    }
+
+    /// Marks the outline group summarized by the row as collapsed or expanded.
+    /// Note that this does not hide or show any row.
+    ///
+    /// Fails if row index is outside allowed range.
+    pub fn set_row_collapsed(&mut self, row: i32, collapsed: bool) -> Result<(), String> {
+        if !is_valid_row(row) {
+            return Err(format!("Row number '{row}' is not valid."));
+        }
+        if !collapsed && !self.rows.iter().any(|r| r.r == row) {
+            return Ok(());
+        }
+        self.row_mut(row).collapsed = collapsed;
+        Ok(())
+    }
+
+    /// Returns true if the outline group summarized by the row is collapsed
+    pub fn is_row_collapsed(&self, row: i32) -> Result<bool, String> {
+        if !is_valid_row(row) {
+            return Err(format!("Row number '{row}' is not valid."));
+        }
+        Ok(self.rows.iter().any(|r| r.r == row && r.collapsed))
+    }
+
     /// Changes the width of a column.
     ///   * If the column does not a have a width we simply add it
     ///   * If it has, it might be part of a range and we need to split the range.
@@ -395,6 +506,9 @@ impl Worksheet {
             width: width / constants::COLUMN_WIDTH_FACTOR,
             custom_width: width != constants::DEFAULT_COLUMN_WIDTH,
             style,
+            hidden: false,
+            outline_level: 0,
+            collapsed: false,
         };
         let mut index = 0;
         let mut split = false;
@@ -423,18 +537,17 @@ impl Worksheet {
             let pre = Col {
                 min,
                 max: column - 1,
-                width: cols[index].width,
-                custom_width: cols[index].custom_width,
-                style: cols[index].style,
+                ..cols[index].clone()
             };
             let post = Col {
                 min: column + 1,
                 max,
-                width: cols[index].width,
-                custom_width: cols[index].custom_width,
-                style: cols[index].style,
+                ..cols[index].clone()
             };
             col.style = cols[index].style;
+            col.hidden = cols[index].hidden;
+            col.outline_level = cols[index].outline_level;
+            col.collapsed = cols[index].collapsed;
             cols.remove(index);
             if column != max {
                 cols.insert(index, post);
@@ -486,6 +599,152 @@ impl Worksheet {
         Ok(None)
     }
 
+    // Returns the column properties, splitting the range the column belongs to if needed
+    fn column_mut(&mut self, column: i32) -> &mut Col {
+        let cols = &mut self.cols;
+        let mut index = 0;
+        let mut found = false;
+        for c in cols.iter() {
+            if c.min <= column && column <= c.max {
+                found = true;
+                break;
+            }
+            if column < c.min {
+                break;
+            }
+            index += 1;
+        }
+        if !found {
+            cols.insert(
+                index,
+                Col {
+                    min: column,
+                    max: column,
+                    width: constants::DEFAULT_COLUMN_WIDTH / constants::COLUMN_WIDTH_FACTOR,
+                    custom_width: false,
+                    style: None,
+                    hidden: false,
+                    outline_level: 0,
+                    collapsed: false,
+                },
+            );
+        } else if cols[index].min != column || cols[index].max != column {
+            let min = cols[index].min;
+            let max = cols[index].max;
+            let col = cols.remove(index);
+            if column != max {
+                cols.insert(
+                    index,
+                    Col {
+                        min: column + 1,
+                        max,
+                        ..col.clone()
+                    },
+                );
+            }
+            cols.insert(
+                index,
+                Col {
+                    min: column,
+                    max: column,
+                    ..col.clone()
+                },
+            );
+            if column != min {
+                cols.insert(
+                    index,
+                    Col {
+                        min,
+                        max: column - 1,
+                        ..col
+                    },
+                );
+                index += 1;
+            }
+        }
+        &mut cols[index]
+    }
+
+    fn get_column(&self, column: i32) -> Option<&Col> {
+        self.cols
+            .iter()
+            .find(|c| c.min <= column && column <= c.max)
+    }
+
+    /// Hides or shows a column.
+    ///
+    /// Fails if column index is outside allowed range.
+    pub fn set_column_hidden(&mut self, column: i32, hidden: bool) -> Result<(), String> {
+        if !is_valid_column_number(column) {
+            return Err(format!("Column number '{column}' is not valid."));
+        }
+        if !hidden && self.get_column(column).is_none() {
+            return Ok(());
+        }
+        self.column_mut(column).hidden = hidden;
+        Ok(())
+    }
+
+    /// Returns true if the column is hidden
+    pub fn is_column_hidden(&self, column: i32) -> Result<bool, String> {
+        if !is_valid_column_number(column) {
+            return Err(format!("Column number '{column}' is not valid."));
+        }
+        Ok(self.get_column(column).is_some_and(|c| c.hidden))
+    }
+
+    /// Sets the outline level of a column. Level 0 means the column is not grouped.
+    ///
+    /// Fails if column index is outside allowed range or the level is larger than 7.
+    pub fn set_column_outline_level(
+        &mut self,
+        column: i32,
+        outline_level: u8,
+    ) -> Result<(), String> {
+        if !is_valid_column_number(column) {
+            return Err(format!("Column number '{column}' is not valid."));
+        }
+        if outline_level > constants::MAX_OUTLINE_LEVEL {
+            return Err(format!("Invalid outline level: {outline_level}"));
+        }
+        if outline_level == 0 && self.get_column(column).is_none() {
+            return Ok(());
+        }
+        self.column_mut(column).outline_level = outline_level;
+        Ok(())
+    }
+
+    /// Returns the outline level of a column
+    pub fn get_column_outline_level(&self, column: i32) -> Result<u8, String> {
+        if !is_valid_column_number(column) {
+            return Err(format!("Column number '{column}' is not valid."));
+        }
+        Ok(self.get_column(column).map_or(0, |c| c.outline_level))
+    }
+
+    /// Marks the outline group summarized by the column as collapsed or expanded.
+    /// Note that this does not hide or show any column.
+    ///
+    /// Fails if column index is outside allowed range.
+    pub fn set_column_collapsed(&mut self, column: i32, collapsed: bool) -> Result<(), String> {
+        if !is_valid_column_number(column) {
+            return Err(format!("Column number '{column}' is not valid."));
+        }
+        if !collapsed && self.get_column(column).is_none() {
+            return Ok(());
+        }
+        self.column_mut(column).collapsed = collapsed;
+        Ok(())
+    }
+
+    /// Returns true if the outline group summarized by the column is collapsed
+    pub fn is_column_collapsed(&self, column: i32) -> Result<bool, String> {
+        if !is_valid_column_number(column) {
+            return Err(format!("Column number '{column}' is not valid."));
+        }
+        Ok(self.get_column(column).is_some_and(|c| c.collapsed))
+    }
+
     // Returns non empty cells in a column
     pub fn column_cell_references(&self, column: i32) -> Result<Vec<CellReferenceIndex>, String> {
         let mut column_cell_references: Vec<CellReferenceIndex> = Vec::new();
@@ -45,6 +45,8 @@ export declare class UserModel {
   static fromBytes(bytes: Uint8Array, languageId: string): UserModel
   canUndo(): boolean
   canRedo(): boolean
+  setMaxHistoryDepth(maxDepth?: number | undefined | null): void
+  setTypingGroupInterval(milliseconds: number): void
   pauseEvaluation(): void
   resumeEvaluation(): void
   flushSendQueue(): Array<number>
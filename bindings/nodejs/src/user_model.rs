@@ -7,7 +7,8 @@ use napi::{self, bindgen_prelude::*, Result, Unknown};
 use ironcalc::base::{
   expressions::types::Area,
   types::{CellType, Style},
-  BorderArea, ClipboardData, UserModel as BaseModel,
+  AutoFillMode, BorderArea, ClipboardData, ClipboardPayload, FindMatch, FindOptions, PasteOptions,
+  UserModel as BaseModel,
 };
 
 #[derive(Serialize)]
@@ -67,6 +68,18 @@ impl UserModel {
     self.model.can_redo()
   }
 
+  #[napi(js_name = "setMaxHistoryDepth")]
+  pub fn set_max_history_depth(&mut self, max_depth: Option<u32>) {
+    self
+      .model
+      .set_max_history_depth(max_depth.map(|depth| depth as usize))
+  }
+
+  #[napi(js_name = "setTypingGroupInterval")]
+  pub fn set_typing_group_interval(&mut self, milliseconds: i64) {
+    self.model.set_typing_group_interval(milliseconds)
+  }
+
   #[napi(js_name = "pauseEvaluation")]
   pub fn pause_evaluation(&mut self) {
     self.model.pause_evaluation()
@@ -124,6 +137,19 @@ impl UserModel {
     self.model.rename_sheet(sheet, &name).map_err(to_js_error)
   }
 
+  #[napi(js_name = "duplicateSheet")]
+  pub fn duplicate_sheet(&mut self, sheet: u32, new_name: String) -> Result<()> {
+    self
+      .model
+      .duplicate_sheet(sheet, &new_name)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "moveSheet")]
+  pub fn move_sheet(&mut self, from: u32, to: u32) -> Result<()> {
+    self.model.move_sheet(from, to).map_err(to_js_error)
+  }
+
   #[napi(js_name = "setSheetColor")]
   pub fn set_sheet_color(&mut self, sheet: u32, color: String) -> Result<()> {
     self
@@ -273,6 +299,28 @@ impl UserModel {
       .map_err(to_js_error)
   }
 
+  #[napi(js_name = "setUserInputR1C1")]
+  pub fn set_user_input_r1c1(
+    &mut self,
+    sheet: u32,
+    row: i32,
+    column: i32,
+    input: String,
+  ) -> Result<()> {
+    self
+      .model
+      .set_user_input_r1c1(sheet, row, column, &input)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "getCellContentR1C1")]
+  pub fn get_cell_content_r1c1(&self, sheet: u32, row: i32, column: i32) -> Result<String> {
+    self
+      .model
+      .get_cell_content_r1c1(sheet, row, column)
+      .map_err(to_js_error)
+  }
+
   #[napi(js_name = "getFormattedCellValue")]
   pub fn get_formatted_cell_value(&self, sheet: u32, row: i32, column: i32) -> Result<String> {
     self
@@ -345,6 +393,39 @@ impl UserModel {
       .map_err(|e| to_js_error(e.to_string()))
   }
 
+  #[napi(js_name = "find")]
+  pub fn find(&'_ self, env: Env, text: String, options: Unknown) -> Result<Unknown<'_>> {
+    let options: FindOptions = env
+      .from_js_value(options)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    let matches: Vec<FindMatch> = self
+      .model
+      .find(&text, &options)
+      .map_err(to_js_error)?
+      .collect();
+    env
+      .to_js_value(&matches)
+      .map_err(|e| to_js_error(e.to_string()))
+  }
+
+  #[napi(js_name = "replaceAll")]
+  pub fn replace_all(
+    &mut self,
+    env: Env,
+    text: String,
+    replacement: String,
+    options: Unknown,
+  ) -> Result<u32> {
+    let options: FindOptions = env
+      .from_js_value(options)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    let count = self
+      .model
+      .replace_all(&text, &replacement, &options)
+      .map_err(to_js_error)?;
+    Ok(count as u32)
+  }
+
   #[napi(js_name = "onPasteStyles")]
   pub fn on_paste_styles(&mut self, env: Env, styles: Unknown) -> Result<()> {
     let styles: &Vec<Vec<Style>> = &env
@@ -475,6 +556,122 @@ impl UserModel {
       .map_err(to_js_error)
   }
 
+  #[napi(js_name = "autoFillRowsWithMode")]
+  pub fn auto_fill_rows_with_mode(
+    &mut self,
+    env: Env,
+    source_area: Unknown,
+    to_row: i32,
+    mode: Unknown,
+  ) -> Result<()> {
+    let area: Area = env
+      .from_js_value(source_area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    let mode: AutoFillMode = env
+      .from_js_value(mode)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .auto_fill_rows_with_mode(&area, to_row, mode)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "autoFillColumnsWithMode")]
+  pub fn auto_fill_columns_with_mode(
+    &mut self,
+    env: Env,
+    source_area: Unknown,
+    to_column: i32,
+    mode: Unknown,
+  ) -> Result<()> {
+    let area: Area = env
+      .from_js_value(source_area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    let mode: AutoFillMode = env
+      .from_js_value(mode)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .auto_fill_columns_with_mode(&area, to_column, mode)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "flashFill")]
+  pub fn flash_fill(&mut self, env: Env, area: Unknown) -> Result<()> {
+    let area: Area = env
+      .from_js_value(area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self.model.flash_fill(&area).map_err(to_js_error)
+  }
+
+  #[napi(js_name = "moveRange")]
+  pub fn move_range(
+    &mut self,
+    env: Env,
+    source: Unknown,
+    target_row: i32,
+    target_column: i32,
+  ) -> Result<()> {
+    let source: Area = env
+      .from_js_value(source)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .move_range(&source, target_row, target_column)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "insertCellsShiftDown")]
+  pub fn insert_cells_shift_down(&mut self, env: Env, area: Unknown) -> Result<()> {
+    let area: Area = env
+      .from_js_value(area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .insert_cells_shift_down(&area)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "insertCellsShiftRight")]
+  pub fn insert_cells_shift_right(&mut self, env: Env, area: Unknown) -> Result<()> {
+    let area: Area = env
+      .from_js_value(area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .insert_cells_shift_right(&area)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "deleteCellsShiftUp")]
+  pub fn delete_cells_shift_up(&mut self, env: Env, area: Unknown) -> Result<()> {
+    let area: Area = env
+      .from_js_value(area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self.model.delete_cells_shift_up(&area).map_err(to_js_error)
+  }
+
+  #[napi(js_name = "deleteCellsShiftLeft")]
+  pub fn delete_cells_shift_left(&mut self, env: Env, area: Unknown) -> Result<()> {
+    let area: Area = env
+      .from_js_value(area)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .delete_cells_shift_left(&area)
+      .map_err(to_js_error)
+  }
+
+  #[napi(js_name = "setCustomLists")]
+  pub fn set_custom_lists(&mut self, lists: Vec<Vec<String>>) -> Result<()> {
+    self.model.set_custom_lists(lists).map_err(to_js_error)
+  }
+
+  #[napi(js_name = "getCustomLists")]
+  pub fn get_custom_lists(&self) -> Vec<Vec<String>> {
+    self.model.get_custom_lists()
+  }
+
   #[napi(js_name = "onArrowRight")]
   pub fn on_arrow_right(&mut self) -> Result<()> {
     self.model.on_arrow_right().map_err(to_js_error)
@@ -588,6 +785,11 @@ impl UserModel {
       .map_err(|e| to_js_error(e.to_string()))
   }
 
+  #[napi(js_name = "copyToHtml")]
+  pub fn copy_to_html(&self) -> Result<String> {
+    self.model.copy_to_html().map_err(to_js_error)
+  }
+
   #[napi(js_name = "pasteFromClipboard")]
   pub fn paste_from_clipboard(
     &mut self,
@@ -609,6 +811,28 @@ impl UserModel {
       .map_err(|e| to_js_error(e.to_string()))
   }
 
+  #[napi(js_name = "copyToPayload")]
+  pub fn copy_to_payload(&'_ self, env: Env) -> Result<Unknown<'_>> {
+    let payload = self.model.copy_to_payload().map_err(to_js_error)?;
+    env
+      .to_js_value(&payload)
+      .map_err(|e| to_js_error(e.to_string()))
+  }
+
+  #[napi(js_name = "pastePayload")]
+  pub fn paste_payload(&mut self, env: Env, payload: Unknown, options: Unknown) -> Result<()> {
+    let payload: ClipboardPayload = env
+      .from_js_value(payload)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    let options: PasteOptions = env
+      .from_js_value(options)
+      .map_err(|e| to_js_error(e.to_string()))?;
+    self
+      .model
+      .paste_payload(&payload, &options)
+      .map_err(to_js_error)
+  }
+
   #[napi(js_name = "pasteCsvText")]
   pub fn paste_csv_string(&mut self, env: Env, area: Unknown, csv: String) -> Result<()> {
     let range: Area = env
@@ -620,6 +844,11 @@ impl UserModel {
       .map_err(|e| to_js_error(e.to_string()))
   }
 
+  #[napi(js_name = "pasteHtmlString")]
+  pub fn paste_html_string(&mut self, html: String) -> Result<()> {
+    self.model.paste_html_string(&html).map_err(to_js_error)
+  }
+
   #[napi(js_name = "getDefinedNameList")]
   pub fn get_defined_name_list(&'_ self, env: Env) -> Result<Unknown<'_>> {
     let data: Vec<DefinedName> = self
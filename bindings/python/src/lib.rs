@@ -1,9 +1,9 @@
 use pyo3::exceptions::PyException;
 use pyo3::{create_exception, prelude::*, wrap_pyfunction};
 
-use types::{PyCellType, PySheetProperty, PyStyle};
+use types::{PyCellType, PyFindMatch, PyFindOptions, PySheetProperty, PyStyle};
 use xlsx::base::types::{Style, Workbook};
-use xlsx::base::{Model, UserModel};
+use xlsx::base::{FindOptions, Model, UserModel};
 
 use xlsx::export::{save_to_icalc, save_to_xlsx};
 use xlsx::import;
@@ -11,6 +11,16 @@ use xlsx::import;
 mod types;
 
 create_exception!(_ironcalc, WorkbookError, PyException);
+create_exception!(_ironcalc, ProtectionError, WorkbookError);
+
+// Actions not allowed by a protected sheet or workbook raise a `ProtectionError`
+fn to_workbook_error(error: String) -> PyErr {
+    if xlsx::base::ProtectionError::from_message(&error).is_some() {
+        ProtectionError::new_err(error)
+    } else {
+        WorkbookError::new_err(error)
+    }
+}
 
 fn leak_str(s: &str) -> &'static str {
     Box::leak(s.to_owned().into_boxed_str())
@@ -39,13 +49,42 @@ impl PyUserModel {
     pub fn apply_external_diffs(&mut self, external_diffs: &[u8]) -> PyResult<()> {
         self.model
             .apply_external_diffs(external_diffs)
-            .map_err(|e| WorkbookError::new_err(e.to_string()))
+            .map_err(to_workbook_error)
     }
 
     pub fn flush_send_queue(&mut self) -> Vec<u8> {
         self.model.flush_send_queue()
     }
 
+    /// Undoes the last change, if any
+    pub fn undo(&mut self) -> PyResult<()> {
+        self.model.undo().map_err(to_workbook_error)
+    }
+
+    /// Redoes the last undone change, if any
+    pub fn redo(&mut self) -> PyResult<()> {
+        self.model.redo().map_err(to_workbook_error)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.model.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.model.can_redo()
+    }
+
+    /// Sets the maximum number of changes that can be undone, `None` for no limit
+    #[pyo3(signature = (max_depth = None))]
+    pub fn set_max_history_depth(&mut self, max_depth: Option<usize>) {
+        self.model.set_max_history_depth(max_depth)
+    }
+
+    /// Cell inputs made within `milliseconds` of each other are undone at once, 0 to disable
+    pub fn set_typing_group_interval(&mut self, milliseconds: i64) {
+        self.model.set_typing_group_interval(milliseconds)
+    }
+
     pub fn set_user_input(
         &mut self,
         sheet: u32,
@@ -55,7 +94,7 @@ impl PyUserModel {
     ) -> PyResult<()> {
         self.model
             .set_user_input(sheet, row, column, value)
-            .map_err(|e| WorkbookError::new_err(e.to_string()))
+            .map_err(to_workbook_error)
     }
 
     pub fn get_formatted_cell_value(&self, sheet: u32, row: i32, column: i32) -> PyResult<String> {
@@ -82,12 +121,46 @@ impl PyUserModel {
         ))
     }
 
+    /// Returns the cells where `text` is found
+    #[pyo3(signature = (text, options = None))]
+    pub fn find(&self, text: &str, options: Option<&PyFindOptions>) -> PyResult<Vec<PyFindMatch>> {
+        let options = get_find_options(options)?;
+        Ok(self
+            .model
+            .find(text, &options)
+            .map_err(WorkbookError::new_err)?
+            .map(PyFindMatch::from)
+            .collect())
+    }
+
+    /// Replaces `text` with `replacement` in all the matching cells and returns the number of
+    /// cells changed. When looking in values cells with formulas are not changed.
+    #[pyo3(signature = (text, replacement, options = None))]
+    pub fn replace_all(
+        &mut self,
+        text: &str,
+        replacement: &str,
+        options: Option<&PyFindOptions>,
+    ) -> PyResult<usize> {
+        let options = get_find_options(options)?;
+        self.model
+            .replace_all(text, replacement, &options)
+            .map_err(to_workbook_error)
+    }
+
     pub fn to_bytes(&self) -> PyResult<Vec<u8>> {
         let bytes = self.model.to_bytes();
         Ok(bytes)
     }
 }
 
+fn get_find_options(options: Option<&PyFindOptions>) -> PyResult<FindOptions> {
+    match options {
+        Some(options) => options.try_into().map_err(WorkbookError::new_err),
+        None => Ok(FindOptions::default()),
+    }
+}
+
 /// This is a model implementing the 'raw' API
 #[pyclass]
 pub struct PyModel {
@@ -178,10 +251,11 @@ impl PyModel {
 
     // Get styles
     pub fn get_cell_style(&self, sheet: u32, row: i32, column: i32) -> PyResult<PyStyle> {
-        let style = self
+        let mut style = self
             .model
             .get_style_for_cell(sheet, row, column)
             .map_err(|e| WorkbookError::new_err(e.to_string()))?;
+        style.resolve_colors(self.model.get_theme());
         Ok(style.into())
     }
 
@@ -305,6 +379,19 @@ impl PyModel {
             .map_err(|e| WorkbookError::new_err(e.to_string()))
     }
 
+    /// Inserts a copy of the sheet right after it and returns the index of the copy
+    pub fn duplicate_sheet(&mut self, sheet: u32, new_name: &str) -> PyResult<u32> {
+        self.model
+            .duplicate_sheet(sheet, new_name)
+            .map_err(|e| WorkbookError::new_err(e.to_string()))
+    }
+
+    pub fn move_sheet(&mut self, from: u32, to: u32) -> PyResult<()> {
+        self.model
+            .move_sheet(from, to)
+            .map_err(|e| WorkbookError::new_err(e.to_string()))
+    }
+
     /// Gets the dimensions of a worksheet, returning the bounds of all non-empty cells.
     /// Returns a tuple of (min_row, max_row, min_column, max_column).
     /// For an empty sheet, returns (1, 1, 1, 1).
@@ -461,6 +548,9 @@ fn ironcalc(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(create_user_model_from_bytes, m)?)?;
     m.add_function(wrap_pyfunction!(create_user_model_from_xlsx, m)?)?;
     m.add_function(wrap_pyfunction!(create_user_model_from_icalc, m)?)?;
+    m.add_class::<PyFindOptions>()?;
+    m.add("WorkbookError", m.py().get_type::<WorkbookError>())?;
+    m.add("ProtectionError", m.py().get_type::<ProtectionError>())?;
 
     Ok(())
 }
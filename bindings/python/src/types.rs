@@ -1,8 +1,11 @@
 use pyo3::prelude::*;
+use xlsx::base::expressions::types::Area;
 use xlsx::base::types::{
-    Alignment, Border, BorderItem, BorderStyle, CellType, Fill, Font, FontScheme,
-    HorizontalAlignment, Style, VerticalAlignment,
+    Alignment, Border, BorderItem, BorderStyle, CellType, Color, Fill, Font, FontScheme,
+    FontUnderline, FontVerticalAlign, HorizontalAlignment, ReadingOrder, Style, Theme,
+    VerticalAlignment,
 };
+use xlsx::base::{FindLookIn, FindMatch, FindOptions, FindOrder, FindScope};
 
 #[derive(Clone)]
 #[pyclass]
@@ -58,6 +61,24 @@ pub struct PyAlignment {
     pub vertical: PyVerticalAlignment,
     #[pyo3(get)]
     pub wrap_text: bool,
+    #[pyo3(get)]
+    pub text_rotation: i32,
+    #[pyo3(get)]
+    pub indent: i32,
+    #[pyo3(get)]
+    pub shrink_to_fit: bool,
+    #[pyo3(get)]
+    pub justify_last_line: bool,
+    #[pyo3(get)]
+    pub reading_order: PyReadingOrder,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(PartialEq, Clone)]
+pub enum PyReadingOrder {
+    Context,
+    LeftToRight,
+    RightToLeft,
 }
 
 #[pyclass]
@@ -127,6 +148,24 @@ pub enum PyFontScheme {
     None,
 }
 
+#[pyclass(eq, eq_int)]
+#[derive(PartialEq, Clone)]
+pub enum PyFontUnderline {
+    Single,
+    Double,
+    SingleAccounting,
+    DoubleAccounting,
+    None,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(PartialEq, Clone)]
+pub enum PyFontVerticalAlign {
+    Baseline,
+    Superscript,
+    Subscript,
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct PyFont {
@@ -135,6 +174,8 @@ pub struct PyFont {
     #[pyo3(get)]
     pub u: bool,
     #[pyo3(get)]
+    pub underline: PyFontUnderline,
+    #[pyo3(get)]
     pub b: bool,
     #[pyo3(get)]
     pub i: bool,
@@ -148,6 +189,14 @@ pub struct PyFont {
     pub family: i32,
     #[pyo3(get)]
     pub scheme: PyFontScheme,
+    #[pyo3(get)]
+    pub vert_align: PyFontVerticalAlign,
+    #[pyo3(get)]
+    pub outline: bool,
+    #[pyo3(get)]
+    pub shadow: bool,
+    #[pyo3(get)]
+    pub condense: bool,
 }
 
 #[pyclass]
@@ -172,6 +221,75 @@ pub enum PyCellType {
     CompoundData = 128,
 }
 
+/// Options of a search. Unless `regex` is set the text is an Excel wildcard.
+/// `look_in` is one of "values", "formulas" or "comments". The search is in the whole workbook
+/// unless `sheet` is given, and in the area (row, column, width, height) of the sheet if `area`
+/// is given too.
+#[pyclass(name = "FindOptions")]
+#[derive(Clone)]
+pub struct PyFindOptions {
+    #[pyo3(get, set)]
+    pub match_case: bool,
+    #[pyo3(get, set)]
+    pub whole_cell: bool,
+    #[pyo3(get, set)]
+    pub regex: bool,
+    #[pyo3(get, set)]
+    pub look_in: String,
+    #[pyo3(get, set)]
+    pub by_columns: bool,
+    #[pyo3(get, set)]
+    pub sheet: Option<u32>,
+    #[pyo3(get, set)]
+    pub area: Option<(i32, i32, i32, i32)>,
+}
+
+#[pymethods]
+impl PyFindOptions {
+    #[new]
+    #[pyo3(signature = (
+        match_case = false,
+        whole_cell = false,
+        regex = false,
+        look_in = "values".to_string(),
+        by_columns = false,
+        sheet = None,
+        area = None
+    ))]
+    pub fn new(
+        match_case: bool,
+        whole_cell: bool,
+        regex: bool,
+        look_in: String,
+        by_columns: bool,
+        sheet: Option<u32>,
+        area: Option<(i32, i32, i32, i32)>,
+    ) -> Self {
+        PyFindOptions {
+            match_case,
+            whole_cell,
+            regex,
+            look_in,
+            by_columns,
+            sheet,
+            area,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyFindMatch {
+    #[pyo3(get)]
+    pub sheet: u32,
+    #[pyo3(get)]
+    pub row: i32,
+    #[pyo3(get)]
+    pub column: i32,
+    #[pyo3(get)]
+    pub text: String,
+}
+
 // Conversions from references to Py* types to non-Py types
 
 // Enums
@@ -213,6 +331,38 @@ impl From<PyFontScheme> for FontScheme {
     }
 }
 
+impl From<PyFontUnderline> for FontUnderline {
+    fn from(py_underline: PyFontUnderline) -> Self {
+        match py_underline {
+            PyFontUnderline::Single => FontUnderline::Single,
+            PyFontUnderline::Double => FontUnderline::Double,
+            PyFontUnderline::SingleAccounting => FontUnderline::SingleAccounting,
+            PyFontUnderline::DoubleAccounting => FontUnderline::DoubleAccounting,
+            PyFontUnderline::None => FontUnderline::None,
+        }
+    }
+}
+
+impl From<PyFontVerticalAlign> for FontVerticalAlign {
+    fn from(py_align: PyFontVerticalAlign) -> Self {
+        match py_align {
+            PyFontVerticalAlign::Baseline => FontVerticalAlign::Baseline,
+            PyFontVerticalAlign::Superscript => FontVerticalAlign::Superscript,
+            PyFontVerticalAlign::Subscript => FontVerticalAlign::Subscript,
+        }
+    }
+}
+
+impl From<PyReadingOrder> for ReadingOrder {
+    fn from(py_order: PyReadingOrder) -> Self {
+        match py_order {
+            PyReadingOrder::Context => ReadingOrder::Context,
+            PyReadingOrder::LeftToRight => ReadingOrder::LeftToRight,
+            PyReadingOrder::RightToLeft => ReadingOrder::RightToLeft,
+        }
+    }
+}
+
 impl From<&PyBorderStyle> for BorderStyle {
     fn from(py_style: &PyBorderStyle) -> Self {
         match *py_style {
@@ -231,12 +381,19 @@ impl From<&PyBorderStyle> for BorderStyle {
 
 // Structs
 
+// Python styles use RGB values. Styles are resolved against the theme of the workbook before
+// they get here, see `PyModel::get_cell_style`.
+fn to_rgb(color: Option<Color>) -> Option<String> {
+    color.map(|color| color.to_rgb(&Theme::default()))
+}
+
 impl From<&PyFill> for Fill {
     fn from(py_fill: &PyFill) -> Self {
         Fill {
-            pattern_type: py_fill.pattern_type.clone(),
-            fg_color: py_fill.fg_color.clone(),
-            bg_color: py_fill.bg_color.clone(),
+            pattern_type: py_fill.pattern_type.parse().unwrap_or_default(),
+            fg_color: py_fill.fg_color.clone().map(Color::Rgb),
+            bg_color: py_fill.bg_color.clone().map(Color::Rgb),
+            ..Default::default()
         }
     }
 }
@@ -245,14 +402,23 @@ impl From<&PyFont> for Font {
     fn from(py_font: &PyFont) -> Self {
         Font {
             strike: py_font.strike,
-            u: py_font.u,
+            // `u` without a kind of underline is a single underline
+            underline: match (py_font.u, &py_font.underline) {
+                (false, _) => FontUnderline::None,
+                (true, PyFontUnderline::None) => FontUnderline::Single,
+                (true, underline) => underline.clone().into(),
+            },
             b: py_font.b,
             i: py_font.i,
             sz: py_font.sz,
-            color: py_font.color.clone(),
+            color: py_font.color.clone().map(Color::Rgb),
             name: py_font.name.clone(),
             family: py_font.family,
             scheme: py_font.scheme.clone().into(),
+            vert_align: py_font.vert_align.clone().into(),
+            outline: py_font.outline,
+            shadow: py_font.shadow,
+            condense: py_font.condense,
         }
     }
 }
@@ -261,7 +427,7 @@ impl From<&PyBorderItem> for BorderItem {
     fn from(py_item: &PyBorderItem) -> Self {
         BorderItem {
             style: (&py_item.style).into(),
-            color: py_item.color.clone(),
+            color: py_item.color.clone().map(Color::Rgb),
         }
     }
 }
@@ -286,6 +452,11 @@ impl From<&PyAlignment> for Alignment {
             horizontal: py_align.horizontal.clone().into(),
             vertical: py_align.vertical.clone().into(),
             wrap_text: py_align.wrap_text,
+            text_rotation: py_align.text_rotation,
+            indent: py_align.indent,
+            shrink_to_fit: py_align.shrink_to_fit,
+            justify_last_line: py_align.justify_last_line,
+            reading_order: py_align.reading_order.clone().into(),
         }
     }
 }
@@ -299,6 +470,8 @@ impl From<&PyStyle> for Style {
             font: (&py_style.font).into(),
             border: (&py_style.border).into(),
             quote_prefix: py_style.quote_prefix,
+            protection: None,
+            named_style: None,
         }
     }
 }
@@ -307,9 +480,9 @@ impl From<&PyStyle> for Style {
 impl From<Fill> for PyFill {
     fn from(fill: Fill) -> Self {
         PyFill {
-            pattern_type: fill.pattern_type,
-            fg_color: fill.fg_color,
-            bg_color: fill.bg_color,
+            pattern_type: fill.pattern_type.to_string(),
+            fg_color: to_rgb(fill.fg_color),
+            bg_color: to_rgb(fill.bg_color),
         }
     }
 }
@@ -354,6 +527,38 @@ impl From<FontScheme> for PyFontScheme {
     }
 }
 
+impl From<FontUnderline> for PyFontUnderline {
+    fn from(underline: FontUnderline) -> Self {
+        match underline {
+            FontUnderline::Single => PyFontUnderline::Single,
+            FontUnderline::Double => PyFontUnderline::Double,
+            FontUnderline::SingleAccounting => PyFontUnderline::SingleAccounting,
+            FontUnderline::DoubleAccounting => PyFontUnderline::DoubleAccounting,
+            FontUnderline::None => PyFontUnderline::None,
+        }
+    }
+}
+
+impl From<FontVerticalAlign> for PyFontVerticalAlign {
+    fn from(align: FontVerticalAlign) -> Self {
+        match align {
+            FontVerticalAlign::Baseline => PyFontVerticalAlign::Baseline,
+            FontVerticalAlign::Superscript => PyFontVerticalAlign::Superscript,
+            FontVerticalAlign::Subscript => PyFontVerticalAlign::Subscript,
+        }
+    }
+}
+
+impl From<ReadingOrder> for PyReadingOrder {
+    fn from(order: ReadingOrder) -> Self {
+        match order {
+            ReadingOrder::Context => PyReadingOrder::Context,
+            ReadingOrder::LeftToRight => PyReadingOrder::LeftToRight,
+            ReadingOrder::RightToLeft => PyReadingOrder::RightToLeft,
+        }
+    }
+}
+
 // From non-Py to Py
 impl From<BorderStyle> for PyBorderStyle {
     fn from(style: BorderStyle) -> Self {
@@ -376,14 +581,19 @@ impl From<Font> for PyFont {
     fn from(font: Font) -> Self {
         PyFont {
             strike: font.strike,
-            u: font.u,
+            u: font.is_underlined(),
+            underline: font.underline.into(),
             b: font.b,
             i: font.i,
             sz: font.sz,
-            color: font.color,
+            color: to_rgb(font.color),
             name: font.name,
             family: font.family,
             scheme: font.scheme.into(),
+            vert_align: font.vert_align.into(),
+            outline: font.outline,
+            shadow: font.shadow,
+            condense: font.condense,
         }
     }
 }
@@ -393,7 +603,7 @@ impl From<BorderItem> for PyBorderItem {
     fn from(item: BorderItem) -> Self {
         PyBorderItem {
             style: item.style.into(),
-            color: item.color,
+            color: to_rgb(item.color),
         }
     }
 }
@@ -420,6 +630,11 @@ impl From<Alignment> for PyAlignment {
             horizontal: align.horizontal.into(),
             vertical: align.vertical.into(),
             wrap_text: align.wrap_text,
+            text_rotation: align.text_rotation,
+            indent: align.indent,
+            shrink_to_fit: align.shrink_to_fit,
+            justify_last_line: align.justify_last_line,
+            reading_order: align.reading_order.into(),
         }
     }
 }
@@ -465,3 +680,51 @@ impl From<CellType> for PyCellType {
         }
     }
 }
+
+impl TryFrom<&PyFindOptions> for FindOptions {
+    type Error = String;
+
+    fn try_from(options: &PyFindOptions) -> Result<Self, Self::Error> {
+        let look_in = match options.look_in.as_str() {
+            "values" => FindLookIn::Values,
+            "formulas" => FindLookIn::Formulas,
+            "comments" => FindLookIn::Comments,
+            other => return Err(format!("Invalid look_in: '{other}'")),
+        };
+        let scope = match (options.sheet, options.area) {
+            (None, None) => FindScope::Workbook,
+            (Some(sheet), None) => FindScope::Sheet(sheet),
+            (Some(sheet), Some((row, column, width, height))) => FindScope::Selection(Area {
+                sheet,
+                row,
+                column,
+                width,
+                height,
+            }),
+            (None, Some(_)) => return Err("An area needs a sheet".to_string()),
+        };
+        Ok(FindOptions {
+            match_case: options.match_case,
+            whole_cell: options.whole_cell,
+            regex: options.regex,
+            look_in,
+            order: if options.by_columns {
+                FindOrder::ByColumns
+            } else {
+                FindOrder::ByRows
+            },
+            scope,
+        })
+    }
+}
+
+impl From<FindMatch> for PyFindMatch {
+    fn from(find_match: FindMatch) -> Self {
+        PyFindMatch {
+            sheet: find_match.sheet,
+            row: find_match.row,
+            column: find_match.column,
+            text: find_match.text,
+        }
+    }
+}
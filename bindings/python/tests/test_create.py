@@ -55,3 +55,24 @@ def test_sheet_dimensions_user_model():
     # Check dimensions
     min_row, max_row, min_col, max_col = model.get_sheet_dimensions(0)
     assert (min_row, max_row, min_col, max_col) == (2, 2, 3, 3)
+
+
+def test_user_history():
+    model = ic.create_user_model("model", "en", "UTC", "en")
+    model.set_max_history_depth(2)
+    for value in ["1", "2", "3"]:
+        model.set_user_input(0, 1, 1, value)
+
+    model.undo()
+    model.undo()
+    assert model.get_formatted_cell_value(0, 1, 1) == "1"
+    assert not model.can_undo()
+    model.redo()
+    assert model.get_formatted_cell_value(0, 1, 1) == "2"
+
+    # Inputs far apart in time are not grouped
+    model.set_typing_group_interval(0)
+    model.set_user_input(0, 2, 1, "a")
+    model.set_user_input(0, 2, 2, "b")
+    model.undo()
+    assert model.get_formatted_cell_value(0, 2, 1) == "a"
@@ -0,0 +1,45 @@
+import pytest
+
+import ironcalc as ic
+
+
+def test_find():
+    model = ic.create_user_model("model", "en", "UTC", "en")
+    model.set_user_input(0, 1, 1, "Apple")
+    model.set_user_input(0, 2, 1, "=UPPER(A1)")
+    model.set_user_input(0, 1, 2, "pear")
+
+    matches = model.find("apple")
+    assert [(m.sheet, m.row, m.column, m.text) for m in matches] == [
+        (0, 1, 1, "Apple"),
+        (0, 2, 1, "APPLE"),
+    ]
+
+    options = ic.FindOptions(match_case=True, look_in="formulas")
+    matches = model.find("A1", options)
+    assert [(m.row, m.column, m.text) for m in matches] == [(2, 1, "=UPPER(A1)")]
+
+    options = ic.FindOptions(sheet=0, area=(1, 2, 1, 1))
+    assert [m.text for m in model.find("p*", options)] == ["pear"]
+
+    with pytest.raises(Exception):
+        model.find("apple", ic.FindOptions(look_in="everywhere"))
+
+
+def test_replace_all():
+    model = ic.create_user_model("model", "en", "UTC", "en")
+    model.set_user_input(0, 1, 1, "red apple")
+    model.set_user_input(0, 2, 1, "=A1")
+    model.set_user_input(0, 3, 1, '=SUBSTITUTE(A1, "red", "green")')
+
+    # Looking in values only constants are changed
+    assert model.replace_all("apple", "pear") == 1
+    assert model.get_formatted_cell_value(0, 2, 1) == "red pear"
+
+    options = ic.FindOptions(look_in="formulas")
+    assert model.replace_all("green", "blue", options) == 1
+    assert model.get_formatted_cell_value(0, 3, 1) == "blue pear"
+
+    options = ic.FindOptions(regex=True, look_in="formulas")
+    assert model.replace_all(r"(\w+) pear", "$1 plum", options) == 1
+    assert model.get_formatted_cell_value(0, 1, 1) == "red plum"
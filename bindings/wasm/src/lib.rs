@@ -10,9 +10,11 @@ use ironcalc_base::{
         types::Area,
         utils::{number_to_column, quote_name as quote_name_ic},
     },
-    types::{CellType, Style},
+    get_builtin_named_styles as builtin_named_styles,
+    types::{CellType, DateSystem, Style, TextRun, Theme},
     worksheet::NavigationDirection,
-    BorderArea, ClipboardData, UserModel as BaseModel,
+    AutoFillMode, BorderArea, ClipboardData, ClipboardPayload, FindMatch, FindOptions,
+    PasteOptions, UserModel as BaseModel,
 };
 
 fn to_js_error(error: String) -> JsError {
@@ -27,6 +29,15 @@ pub fn get_tokens(formula: &str) -> Result<JsValue, JsError> {
     serde_wasm_bindgen::to_value(&tokens).map_err(JsError::from)
 }
 
+/// Returns the Excel built-in cell styles
+#[wasm_bindgen(
+    js_name = "getBuiltinNamedStyles",
+    unchecked_return_type = "NamedStyle[]"
+)]
+pub fn get_builtin_named_styles() -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(&builtin_named_styles()).map_err(JsError::from)
+}
+
 #[wasm_bindgen(js_name = "columnNameFromNumber")]
 pub fn column_name_from_number(column: i32) -> Result<String, JsError> {
     match number_to_column(column) {
@@ -52,6 +63,19 @@ pub fn get_supported_locales() -> Vec<String> {
     ironcalc_base::get_supported_locales()
 }
 
+/// Gets all supported languages
+#[wasm_bindgen(js_name = "getSupportedLanguages")]
+pub fn get_supported_languages() -> Vec<String> {
+    ironcalc_base::get_supported_languages()
+}
+
+/// Returns true if the error message is from an action not allowed by a protected sheet or
+/// workbook
+#[wasm_bindgen(js_name = "isProtectionError")]
+pub fn is_protection_error(message: &str) -> bool {
+    ironcalc_base::ProtectionError::from_message(message).is_some()
+}
+
 #[derive(Serialize)]
 struct DefinedName {
     name: String,
@@ -137,6 +161,16 @@ impl Model {
         self.model.can_redo()
     }
 
+    #[wasm_bindgen(js_name = "setMaxHistoryDepth")]
+    pub fn set_max_history_depth(&mut self, max_depth: Option<usize>) {
+        self.model.set_max_history_depth(max_depth)
+    }
+
+    #[wasm_bindgen(js_name = "setTypingGroupInterval")]
+    pub fn set_typing_group_interval(&mut self, milliseconds: i64) {
+        self.model.set_typing_group_interval(milliseconds)
+    }
+
     #[wasm_bindgen(js_name = "pauseEvaluation")]
     pub fn pause_evaluation(&mut self) {
         self.model.pause_evaluation()
@@ -193,6 +227,18 @@ impl Model {
         self.model.rename_sheet(sheet, name).map_err(to_js_error)
     }
 
+    #[wasm_bindgen(js_name = "duplicateSheet")]
+    pub fn duplicate_sheet(&mut self, sheet: u32, new_name: &str) -> Result<(), JsError> {
+        self.model
+            .duplicate_sheet(sheet, new_name)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "moveSheet")]
+    pub fn move_sheet(&mut self, from: u32, to: u32) -> Result<(), JsError> {
+        self.model.move_sheet(from, to).map_err(to_js_error)
+    }
+
     #[wasm_bindgen(js_name = "setSheetColor")]
     pub fn set_sheet_color(&mut self, sheet: u32, color: &str) -> Result<(), JsError> {
         self.model
@@ -367,6 +413,93 @@ impl Model {
             .map_err(to_js_error)
     }
 
+    #[wasm_bindgen(js_name = "setUserInputR1C1")]
+    pub fn set_user_input_r1c1(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        input: &str,
+    ) -> Result<(), JsError> {
+        self.model
+            .set_user_input_r1c1(sheet, row, column, input)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "getCellContentR1C1")]
+    pub fn get_cell_content_r1c1(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<String, JsError> {
+        self.model
+            .get_cell_content_r1c1(sheet, row, column)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "setCellRichText")]
+    pub fn set_cell_rich_text(
+        &mut self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+        #[wasm_bindgen(unchecked_param_type = "TextRun[]")] runs: JsValue,
+    ) -> Result<(), JsError> {
+        let runs: Vec<TextRun> =
+            serde_wasm_bindgen::from_value(runs).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .set_cell_rich_text(sheet, row, column, &runs)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(
+        js_name = "getCellRichText",
+        unchecked_return_type = "TextRun[] | undefined"
+    )]
+    pub fn get_cell_rich_text(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<JsValue, JsError> {
+        let runs = self
+            .model
+            .get_cell_rich_text(sheet, row, column)
+            .map_err(to_js_error)?;
+        serde_wasm_bindgen::to_value(&runs).map_err(|e| to_js_error(e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "find", unchecked_return_type = "FindMatch[]")]
+    pub fn find(
+        &self,
+        text: &str,
+        #[wasm_bindgen(unchecked_param_type = "FindOptions")] options: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let options: FindOptions =
+            serde_wasm_bindgen::from_value(options).map_err(|e| to_js_error(e.to_string()))?;
+        let matches: Vec<FindMatch> = self
+            .model
+            .find(text, &options)
+            .map_err(to_js_error)?
+            .collect();
+        serde_wasm_bindgen::to_value(&matches).map_err(|e| to_js_error(e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "replaceAll")]
+    pub fn replace_all(
+        &mut self,
+        text: &str,
+        replacement: &str,
+        #[wasm_bindgen(unchecked_param_type = "FindOptions")] options: JsValue,
+    ) -> Result<usize, JsError> {
+        let options: FindOptions =
+            serde_wasm_bindgen::from_value(options).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .replace_all(text, replacement, &options)
+            .map_err(to_js_error)
+    }
+
     #[wasm_bindgen(js_name = "getFormattedCellValue")]
     pub fn get_formatted_cell_value(
         &self,
@@ -450,6 +583,22 @@ impl Model {
             .map_err(to_js_error)
     }
 
+    /// Returns a single colour approximating the fill of the cell (patterns and gradients
+    /// are blended), or undefined if the cell has no fill
+    #[wasm_bindgen(js_name = "getCellFillColor")]
+    pub fn get_cell_fill_color(
+        &self,
+        sheet: u32,
+        row: i32,
+        column: i32,
+    ) -> Result<Option<String>, JsError> {
+        let style = self
+            .model
+            .get_cell_style(sheet, row, column)
+            .map_err(to_js_error)?;
+        Ok(style.fill.get_color(self.model.get_theme()))
+    }
+
     #[wasm_bindgen(js_name = "getCellStyle", unchecked_return_type = "CellStyle")]
     pub fn get_cell_style(
         &mut self,
@@ -465,6 +614,59 @@ impl Model {
         serde_wasm_bindgen::to_value(&style).map_err(|e| to_js_error(e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = "getNamedStyles", unchecked_return_type = "NamedStyle[]")]
+    pub fn get_named_styles(&self) -> Result<JsValue, JsError> {
+        let mut named_styles = self.model.get_named_styles();
+        for named_style in named_styles.iter_mut() {
+            named_style.style.resolve_colors(self.model.get_theme());
+        }
+        serde_wasm_bindgen::to_value(&named_styles).map_err(|e| to_js_error(e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "createNamedStyle")]
+    pub fn create_named_style(
+        &mut self,
+        name: &str,
+        #[wasm_bindgen(unchecked_param_type = "CellStyle")] style: JsValue,
+    ) -> Result<(), JsError> {
+        let style: Style =
+            serde_wasm_bindgen::from_value(style).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .create_named_style(name, &style)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "updateNamedStyle")]
+    pub fn update_named_style(
+        &mut self,
+        name: &str,
+        #[wasm_bindgen(unchecked_param_type = "CellStyle")] style: JsValue,
+    ) -> Result<(), JsError> {
+        let style: Style =
+            serde_wasm_bindgen::from_value(style).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .update_named_style(name, &style)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "deleteNamedStyle")]
+    pub fn delete_named_style(&mut self, name: &str) -> Result<(), JsError> {
+        self.model.delete_named_style(name).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "applyNamedStyle")]
+    pub fn apply_named_style(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] range: JsValue,
+        name: &str,
+    ) -> Result<(), JsError> {
+        let range: Area =
+            serde_wasm_bindgen::from_value(range).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .apply_named_style(&range, name)
+            .map_err(to_js_error)
+    }
+
     #[wasm_bindgen(js_name = "onPasteStyles")]
     pub fn on_paste_styles(
         &mut self,
@@ -601,6 +803,108 @@ impl Model {
             .map_err(to_js_error)
     }
 
+    #[wasm_bindgen(js_name = "autoFillRowsWithMode")]
+    pub fn auto_fill_rows_with_mode(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] source_area: JsValue,
+        to_row: i32,
+        #[wasm_bindgen(unchecked_param_type = "AutoFillMode")] mode: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(source_area).map_err(|e| to_js_error(e.to_string()))?;
+        let mode: AutoFillMode =
+            serde_wasm_bindgen::from_value(mode).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .auto_fill_rows_with_mode(&area, to_row, mode)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "autoFillColumnsWithMode")]
+    pub fn auto_fill_columns_with_mode(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] source_area: JsValue,
+        to_column: i32,
+        #[wasm_bindgen(unchecked_param_type = "AutoFillMode")] mode: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(source_area).map_err(|e| to_js_error(e.to_string()))?;
+        let mode: AutoFillMode =
+            serde_wasm_bindgen::from_value(mode).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .auto_fill_columns_with_mode(&area, to_column, mode)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "flashFill")]
+    pub fn flash_fill(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] area: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(area).map_err(|e| to_js_error(e.to_string()))?;
+        self.model.flash_fill(&area).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "moveRange")]
+    pub fn move_range(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] source: JsValue,
+        target_row: i32,
+        target_column: i32,
+    ) -> Result<(), JsError> {
+        let source: Area =
+            serde_wasm_bindgen::from_value(source).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .move_range(&source, target_row, target_column)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "insertCellsShiftDown")]
+    pub fn insert_cells_shift_down(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] area: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(area).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .insert_cells_shift_down(&area)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "insertCellsShiftRight")]
+    pub fn insert_cells_shift_right(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] area: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(area).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .insert_cells_shift_right(&area)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "deleteCellsShiftUp")]
+    pub fn delete_cells_shift_up(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] area: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(area).map_err(|e| to_js_error(e.to_string()))?;
+        self.model.delete_cells_shift_up(&area).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "deleteCellsShiftLeft")]
+    pub fn delete_cells_shift_left(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Area")] area: JsValue,
+    ) -> Result<(), JsError> {
+        let area: Area =
+            serde_wasm_bindgen::from_value(area).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .delete_cells_shift_left(&area)
+            .map_err(to_js_error)
+    }
+
     #[wasm_bindgen(js_name = "onArrowRight")]
     pub fn on_arrow_right(&mut self) -> Result<(), JsError> {
         self.model.on_arrow_right().map_err(to_js_error)
@@ -724,6 +1028,11 @@ impl Model {
         serde_wasm_bindgen::to_value(&data).map_err(|e| to_js_error(e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = "copyToHtml")]
+    pub fn copy_to_html(&self) -> Result<String, JsError> {
+        self.model.copy_to_html().map_err(to_js_error)
+    }
+
     #[wasm_bindgen(js_name = "pasteFromClipboard")]
     pub fn paste_from_clipboard(
         &mut self,
@@ -742,6 +1051,27 @@ impl Model {
             .map_err(|e| to_js_error(e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = "copyToPayload", unchecked_return_type = "ClipboardPayload")]
+    pub fn copy_to_payload(&self) -> Result<JsValue, JsError> {
+        let payload = self.model.copy_to_payload().map_err(to_js_error)?;
+        serde_wasm_bindgen::to_value(&payload).map_err(|e| to_js_error(e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "pastePayload")]
+    pub fn paste_payload(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "ClipboardPayload")] payload: JsValue,
+        #[wasm_bindgen(unchecked_param_type = "PasteOptions")] options: JsValue,
+    ) -> Result<(), JsError> {
+        let payload: ClipboardPayload =
+            serde_wasm_bindgen::from_value(payload).map_err(|e| to_js_error(e.to_string()))?;
+        let options: PasteOptions =
+            serde_wasm_bindgen::from_value(options).map_err(|e| to_js_error(e.to_string()))?;
+        self.model
+            .paste_payload(&payload, &options)
+            .map_err(to_js_error)
+    }
+
     #[wasm_bindgen(js_name = "pasteCsvText")]
     pub fn paste_csv_string(
         &mut self,
@@ -755,6 +1085,11 @@ impl Model {
             .map_err(|e| to_js_error(e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = "pasteHtmlString")]
+    pub fn paste_html_string(&mut self, html: &str) -> Result<(), JsError> {
+        self.model.paste_html_string(html).map_err(to_js_error)
+    }
+
     #[wasm_bindgen(
         js_name = "getDefinedNameList",
         unchecked_return_type = "DefinedName[]"
@@ -857,6 +1192,58 @@ impl Model {
             .map_err(|e| to_js_error(e.to_string()))
     }
 
+    /// Switches between the 1900 and the 1904 date systems, shifting the stored dates
+    #[wasm_bindgen(js_name = "setDate1904")]
+    pub fn set_date_1904(&mut self, date1904: bool) -> Result<(), JsError> {
+        let date_system = if date1904 {
+            DateSystem::Excel1904
+        } else {
+            DateSystem::Excel1900
+        };
+        self.model
+            .set_date_system(date_system)
+            .map_err(|e| to_js_error(e.to_string()))
+    }
+
+    /// Returns true if the workbook uses the 1904 date system
+    #[wasm_bindgen(js_name = "getDate1904")]
+    pub fn get_date_1904(&self) -> bool {
+        self.model.get_date_system() == DateSystem::Excel1904
+    }
+
+    /// Sets the user defined lists that autofill extends
+    #[wasm_bindgen(js_name = "setCustomLists")]
+    pub fn set_custom_lists(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "string[][]")] lists: JsValue,
+    ) -> Result<(), JsError> {
+        let lists: Vec<Vec<String>> =
+            serde_wasm_bindgen::from_value(lists).map_err(|e| to_js_error(e.to_string()))?;
+        self.model.set_custom_lists(lists).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "getCustomLists", unchecked_return_type = "string[][]")]
+    pub fn get_custom_lists(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.model.get_custom_lists())
+            .map_err(|e| to_js_error(e.to_string()))
+    }
+
+    /// Sets the theme of the workbook, updating the colours linked to it
+    #[wasm_bindgen(js_name = "setTheme")]
+    pub fn set_theme(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Theme")] theme: JsValue,
+    ) -> Result<(), JsError> {
+        let theme: Theme =
+            serde_wasm_bindgen::from_value(theme).map_err(|e| to_js_error(e.to_string()))?;
+        self.model.set_theme(theme).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = "getTheme", unchecked_return_type = "Theme")]
+    pub fn get_theme(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(self.model.get_theme()).map_err(|e| to_js_error(e.to_string()))
+    }
+
     /// Gets the timezone of the model
     #[wasm_bindgen(js_name = "getTimezone")]
     pub fn get_timezone(&self) -> String {
@@ -116,14 +116,58 @@ export interface WorksheetProperties {
   state: string;
 }
 
+export type PatternType =
+  | "none"
+  | "solid"
+  | "mediumGray"
+  | "darkGray"
+  | "lightGray"
+  | "darkHorizontal"
+  | "darkVertical"
+  | "darkDown"
+  | "darkUp"
+  | "darkGrid"
+  | "darkTrellis"
+  | "lightHorizontal"
+  | "lightVertical"
+  | "lightDown"
+  | "lightUp"
+  | "lightGrid"
+  | "lightTrellis"
+  | "gray125"
+  | "gray0625";
+
+interface GradientStop {
+  position: number;
+  color?: string;
+}
+
+export interface GradientFill {
+  gradient_type: "linear" | "path";
+  degree: number;
+  left: number;
+  right: number;
+  top: number;
+  bottom: number;
+  stops: GradientStop[];
+}
+
 interface CellStyleFill {
-  pattern_type: string;
+  pattern_type: PatternType;
   fg_color?: string;
   bg_color?: string;
+  gradient?: GradientFill;
 }
 
+export type FontUnderline =
+  | "none"
+  | "single"
+  | "double"
+  | "singleaccounting"
+  | "doubleaccounting";
+
 interface CellStyleFont {
-  u: boolean;
+  underline?: FontUnderline;
   b: boolean;
   i: boolean;
   strike: boolean;
@@ -132,6 +176,48 @@ interface CellStyleFont {
   name: string;
   family: number;
   scheme: string;
+  vert_align?: "baseline" | "superscript" | "subscript";
+  outline?: boolean;
+  shadow?: boolean;
+  condense?: boolean;
+}
+
+interface RunFont {
+  b?: boolean;
+  i?: boolean;
+  underline?: FontUnderline;
+  strike?: boolean;
+  sz?: number;
+  color?: string;
+  name?: string;
+  vert_align?: "baseline" | "superscript" | "subscript";
+}
+
+export interface TextRun {
+  text: string;
+  font?: RunFont;
+}
+
+export type FindLookIn = "Values" | "Formulas" | "Comments";
+
+export type FindOrder = "ByRows" | "ByColumns";
+
+export type FindScope = { Selection: Area } | { Sheet: number } | "Workbook";
+
+export interface FindOptions {
+  match_case?: boolean;
+  whole_cell?: boolean;
+  regex?: boolean;
+  look_in?: FindLookIn;
+  order?: FindOrder;
+  scope?: FindScope;
+}
+
+export interface FindMatch {
+  sheet: number;
+  row: number;
+  column: number;
+  text: string;
 }
 
 export interface BorderOptions {
@@ -188,6 +274,12 @@ interface Alignment {
   horizontal: HorizontalAlignment;
   vertical: VerticalAlignment;
   wrap_text: boolean;
+  // 0 to 180 degrees, 255 for vertical text
+  text_rotation?: number;
+  indent?: number;
+  shrink_to_fit?: boolean;
+  justify_last_line?: boolean;
+  reading_order?: "context" | "lefttoright" | "righttoleft";
 }
 
 export interface CellStyle {
@@ -198,6 +290,23 @@ export interface CellStyle {
   border: CellStyleBorder;
   num_fmt: string;
   alignment?: Alignment;
+  named_style?: string;
+}
+
+export interface Theme {
+  name: string;
+  // lt1, dk1, lt2, dk2, accent1 to accent6, hlink and folHlink
+  colors: string[];
+  major_font: string;
+  minor_font: string;
+  // The theme part of the original file, kept if missing when setting a theme
+  xml?: string;
+}
+
+export interface NamedStyle {
+  name: string;
+  builtin_id?: number;
+  style: CellStyle;
 }
 
 export interface SelectedView {
@@ -229,6 +338,53 @@ export interface Clipboard {
   range: [number, number, number, number];
 }
 
+export type AutoFillMode =
+  | "Auto"
+  | "Copy"
+  | "Days"
+  | "Weekdays"
+  | "Months"
+  | "Years"
+  | "LinearTrend"
+  | "GrowthTrend";
+
+export type PasteMode =
+  | "All"
+  | "Values"
+  | "Formulas"
+  | "Formats"
+  | "ColumnWidths";
+
+export type PasteOperation = "None" | "Add" | "Subtract" | "Multiply" | "Divide";
+
+export interface PasteOptions {
+  mode?: PasteMode;
+  operation?: PasteOperation;
+  skip_blanks?: boolean;
+  transpose?: boolean;
+  link?: boolean;
+}
+
+export type CopiedValue =
+  | { Number: number }
+  | { Boolean: boolean }
+  | { Text: string }
+  | { Error: string };
+
+export interface CopiedCell {
+  formula: string | null;
+  value: CopiedValue | null;
+  style: CellStyle;
+}
+
+export interface ClipboardPayload {
+  sheet_name: string;
+  row: number;
+  column: number;
+  cells: CopiedCell[][];
+  column_widths: number[];
+}
+
 export interface DefinedName {
   name: string;
   scope?: number;
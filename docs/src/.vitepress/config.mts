@@ -2028,6 +2028,10 @@ export default defineConfig({
             text: "JavaScript",
             link: "/programming/javascript-bindings",
           },
+          {
+            text: "Diff format",
+            link: "/programming/diff-format",
+          },
         ],
       },
       {
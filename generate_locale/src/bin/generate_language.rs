@@ -0,0 +1,40 @@
+//! Encodes the function names, errors and booleans of every language into `language.bin`.
+//! The structure of the data is the one IronCalc decodes, so the json must have all the fields.
+
+use std::collections::HashMap;
+use std::fs;
+use std::{io::Write, path::PathBuf};
+
+use clap::Parser;
+use ironcalc_base::language::Language;
+
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None)]
+pub struct Opt {
+    /// Json file with all the languages, i.e. `base/src/language/language.json`
+    #[clap(long, value_parser)]
+    input: PathBuf,
+
+    /// output bin file
+    #[clap(long, value_parser)]
+    output: PathBuf,
+}
+
+fn main() -> Result<(), String> {
+    let opt = Opt::from_args();
+    let input_str = opt.input.display().to_string();
+    let contents =
+        fs::read_to_string(&opt.input).or(Err(format!("Failed reading file: {}", input_str)))?;
+    let languages: HashMap<String, Language> = serde_json::from_str(&contents)
+        .map_err(|error| format!("Failed parsing languages file {}: {}", input_str, error))?;
+    for (code, language) in &languages {
+        if code != &language.code {
+            return Err(format!("Language {} has code {}", code, language.code));
+        }
+    }
+
+    let bytes = bitcode::encode(&languages);
+    let mut f = fs::File::create(opt.output).or(Err("Failed to create file"))?;
+    f.write_all(&bytes).or(Err("Failed writing"))?;
+    Ok(())
+}
@@ -1,9 +1,9 @@
-use std::{collections::HashMap, sync::OnceLock};
-
-/// Static map from CLDR date patterns to Excel custom date formats.
+/// Converts a CLDR date pattern into an Excel custom date format.
 ///
 /// CLDR tokens:
 /// - EEEE → dddd (weekday name)
+/// - E / EE / EEE → ddd (short weekday name)
+/// - MMMMM → mmmmm (month letter)
 /// - MMMM → mmmm (full month)
 /// - MMM  → mmm  (short month)
 /// - d / dd → d / dd
@@ -11,52 +11,86 @@ use std::{collections::HashMap, sync::OnceLock};
 /// - y → yyyy (4-digit year)
 /// - yy → yy (2-digit year)
 ///
-/// Literal text like `'de'` must be quoted in Excel using double quotes.
-static CLDR_TO_EXCEL_DATE_FORMATS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
-
-/// Look up the Excel date format corresponding to a CLDR pattern.
-pub fn cldr_to_excel_date_format(cldr_pattern: &str) -> String {
-    println!("Converting CLDR pattern: {}", cldr_pattern);
-    CLDR_TO_EXCEL_DATE_FORMATS
-        .get_or_init(|| {
-            let mut m = HashMap::new();
-
-            // German-style with dots
-            m.insert("EEEE, d. MMMM y", "dddd, d. mmmm yyyy");
-            m.insert("d. MMMM y", "d. mmmm yyyy");
-            m.insert("dd.MM.y", "dd.mm.yyyy");
-            m.insert("dd.MM.yy", "dd.mm.yy");
-
-            // "EEEE, d MMMM y" / slashed short date
-            m.insert("EEEE, d MMMM y", "dddd, d mmmm yyyy");
-            m.insert("d MMMM y", "d mmmm yyyy");
-            m.insert("d MMM y", "d mmm yyyy");
-            m.insert("dd/MM/y", "dd/mm/yyyy");
-
-            // US-style
-            m.insert("EEEE, MMMM d, y", "dddd, mmmm d, yyyy");
-            m.insert("MMMM d, y", "mmmm d, yyyy");
-            m.insert("MMM d, y", "mmm d, yyyy");
-            m.insert("M/d/yy", "m/d/yy");
-
-            // Spanish-style with literal "de"
-            m.insert(
-                "EEEE, d 'de' MMMM 'de' y",
-                "dddd, d \"de\" mmmm \"de\" yyyy",
-            );
-            m.insert("d 'de' MMMM 'de' y", "d \"de\" mmmm \"de\" yyyy");
-            // medium here reuses "d MMM y" → already in map above
-            m.insert("d/M/yy", "d/m/yy");
-
-            // Variant without comma before month
-            m.insert("EEEE d MMMM y", "dddd d mmmm yyyy");
-
-            // Italian-style with slashes
-            m.insert("dd/MM/yy", "dd/mm/yy");
+/// Literal text like `'de'` or `年` must be quoted in Excel using double quotes.
+/// Spaces and punctuation are kept as they are.
+pub fn cldr_to_excel_date_format(cldr_pattern: &str) -> Result<String, String> {
+    let chars: Vec<char> = cldr_pattern.chars().collect();
+    let len = chars.len();
+    let mut result = String::new();
+    // Literal text waiting to be quoted
+    let mut literal = String::new();
+    let mut index = 0;
+    while index < len {
+        let c = chars[index];
+        if c == '\'' {
+            // Quoted text. Two single quotes are a literal single quote
+            index += 1;
+            if index < len && chars[index] == '\'' {
+                literal.push('\'');
+                index += 1;
+                continue;
+            }
+            while index < len {
+                if chars[index] == '\'' {
+                    if index + 1 < len && chars[index + 1] == '\'' {
+                        literal.push('\'');
+                        index += 2;
+                        continue;
+                    }
+                    break;
+                }
+                literal.push(chars[index]);
+                index += 1;
+            }
+            index += 1;
+            continue;
+        }
+        if !c.is_alphabetic() {
+            push_literal(&mut result, &mut literal);
+            result.push(c);
+            index += 1;
+            continue;
+        }
+        if !c.is_ascii_alphabetic() {
+            literal.push(c);
+            index += 1;
+            continue;
+        }
+        let mut count = 1;
+        while index + count < len && chars[index + count] == c {
+            count += 1;
+        }
+        let token = match (c, count) {
+            ('E', 4) | ('c', 4) => "dddd",
+            ('E', 1..=3) | ('c', 3) => "ddd",
+            ('d', 1) => "d",
+            ('d', 2) => "dd",
+            ('M', 1) | ('L', 1) => "m",
+            ('M', 2) | ('L', 2) => "mm",
+            ('M', 3) | ('L', 3) => "mmm",
+            ('M', 4) | ('L', 4) => "mmmm",
+            ('M', 5) | ('L', 5) => "mmmmm",
+            ('y', 2) => "yy",
+            ('y', _) => "yyyy",
+            _ => {
+                return Err(format!(
+                    "Unexpected field '{}' in date pattern '{cldr_pattern}'",
+                    c.to_string().repeat(count)
+                ))
+            }
+        };
+        push_literal(&mut result, &mut literal);
+        result.push_str(token);
+        index += count;
+    }
+    push_literal(&mut result, &mut literal);
+    Ok(result)
+}
 
-            m
-        })
-        .get(cldr_pattern)
-        .unwrap()
-        .to_string()
+// Quotes the pending literal text, if any
+fn push_literal(result: &mut String, literal: &mut String) {
+    if !literal.is_empty() {
+        result.push_str(&format!("\"{literal}\""));
+        literal.clear();
+    }
 }
@@ -26,6 +26,13 @@ pub struct NumbersProperties {
     pub decimal_formats: DecimalFormats,
     #[serde(rename = "currencyFormats-numberSystem-latn")]
     pub currency_formats: CurrencyFormats,
+    // Formulas always use the latin symbols, but some locales display numbers with other digits
+    #[serde(
+        rename = "nativeDigits",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub native_digits: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Encode)]
@@ -90,12 +97,12 @@ pub struct DateFormats {
 }
 
 impl DateFormats {
-    pub fn to_excel_formats(&self) -> DateFormats {
-        DateFormats {
-            full: cldr_utils::cldr_to_excel_date_format(&self.full),
-            long: cldr_utils::cldr_to_excel_date_format(&self.long),
-            medium: cldr_utils::cldr_to_excel_date_format(&self.medium),
-            short: cldr_utils::cldr_to_excel_date_format(&self.short),
-        }
+    pub fn to_excel_formats(&self) -> Result<DateFormats, String> {
+        Ok(DateFormats {
+            full: cldr_utils::cldr_to_excel_date_format(&self.full)?,
+            long: cldr_utils::cldr_to_excel_date_format(&self.long)?,
+            medium: cldr_utils::cldr_to_excel_date_format(&self.medium)?,
+            short: cldr_utils::cldr_to_excel_date_format(&self.short)?,
+        })
     }
 }
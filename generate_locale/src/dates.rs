@@ -42,16 +42,16 @@ struct CaGregorian {
     main: HashMap<String, CaGId>,
 }
 
-pub fn get_dates_formatting(cldr_dir: &str, locale_id: &str) -> Result<Dates, &'static str> {
+pub fn get_dates_formatting(cldr_dir: &str, locale_id: &str) -> Result<Dates, String> {
     let calendar_file = format!(
         "{}cldr-json/cldr-dates-{}/main/{}/ca-gregorian.json",
         cldr_dir, LOCAL_TYPE, locale_id
     );
 
-    let contents =
-        fs::read_to_string(calendar_file).or(Err("Failed reading 'ca-gregorian' file"))?;
-    let ca_gregorian: CaGregorian =
-        serde_json::from_str(&contents).or(Err("Failed parsing 'ca-gregorian' file"))?;
+    let contents = fs::read_to_string(calendar_file)
+        .or(Err("Failed reading 'ca-gregorian' file".to_string()))?;
+    let ca_gregorian: CaGregorian = serde_json::from_str(&contents)
+        .or(Err("Failed parsing 'ca-gregorian' file".to_string()))?;
     let gregorian = &ca_gregorian.main[locale_id].dates.calendars.gregorian;
     // See: http://cldr.unicode.org/translation/date-time-1/date-time-patterns
     // for the difference between stand-alone and format. We will use only the format mode
@@ -86,7 +86,7 @@ pub fn get_dates_formatting(cldr_dir: &str, locale_id: &str) -> Result<Dates, &'
         months,
         months_short,
         months_letter,
-        date_formats: gregorian.date_formats.to_excel_formats(),
+        date_formats: gregorian.date_formats.to_excel_formats()?,
         time_formats: gregorian.time_formats.clone(),
         date_time_formats: gregorian.date_time_formats.clone(),
     })
@@ -15,7 +15,7 @@ mod util;
 
 use currency::get_locale_currency;
 use dates::get_dates_formatting;
-use util::get_all_locales_id;
+use util::{get_all_locales_id, get_territory_locale_id};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -26,17 +26,51 @@ pub struct Opt {
 
     /// Folder with the cldr data
     #[clap(long, value_parser)]
-    cldr_dir: String,
+    cldr_dir: Option<String>,
 
-    /// output json file with all locale info
+    /// Json file with the locales to encode instead of reading the cldr data.
+    /// Use it after correcting a generated file by hand
+    #[clap(long, value_parser)]
+    input: Option<PathBuf>,
+
+    /// output json file with all locale info, `locales.bin` is written next to it
     #[clap(long, value_parser)]
     output: PathBuf,
 }
 
 fn main() -> Result<(), String> {
     let opt = Opt::from_args();
-    let cldr_dir = opt.cldr_dir;
-    let locales_list: Vec<String> = if let Some(locales_path) = opt.locales {
+    let locales = match (opt.input, opt.cldr_dir) {
+        (Some(input_path), _) => {
+            let input_path_str = input_path.display().to_string();
+            let contents = fs::read_to_string(input_path)
+                .or(Err(format!("Failed reading file: {}", input_path_str)))?;
+            serde_json::from_str(&contents)
+                .or(Err(format!("Failed parsing locales: {}", input_path_str)))?
+        }
+        (None, Some(cldr_dir)) => get_cldr_locales(&cldr_dir, opt.locales)?,
+        (None, None) => return Err("Either --cldr-dir or --input is required".to_string()),
+    };
+
+    let s = serde_json::to_string(&locales).or(Err("Failed to stringify data"))?;
+    let mut f = fs::File::create(&opt.output).or(Err("Failed to create file"))?;
+    f.write_all(s.as_bytes()).or(Err("Failed writing"))?;
+
+    // save to locales.bin using bitcode
+    let bytes = bitcode::encode(&locales);
+    let mut f_bin = fs::File::create(opt.output.with_file_name("locales.bin"))
+        .or(Err("Failed to create locales.bin"))?;
+    f_bin
+        .write_all(&bytes)
+        .or(Err("Failed writing locales.bin"))?;
+    Ok(())
+}
+
+fn get_cldr_locales(
+    cldr_dir: &str,
+    locales_path: Option<PathBuf>,
+) -> Result<HashMap<String, Locale>, String> {
+    let locales_list: Vec<String> = if let Some(locales_path) = locales_path {
         let locales_path_str = locales_path.display().to_string();
         let contents = fs::read_to_string(locales_path)
             .or(Err(format!("Failed reading file: {}", locales_path_str)))?;
@@ -45,23 +79,17 @@ fn main() -> Result<(), String> {
             locales_path_str
         )))?
     } else {
-        get_all_locales_id(&cldr_dir)
+        get_all_locales_id(cldr_dir)
     };
 
     let mut locales = HashMap::new();
 
     for locale_id in &locales_list {
-        let full_locale_id = match locale_id.as_str() {
-            "en" => "en-US",
-            "es" => "es-ES",
-            "fr" => "fr-FR",
-            "de" => "de-DE",
-            "it" => "it-IT",
-            _ => locale_id.as_str(),
-        };
-        let dates = get_dates_formatting(&cldr_dir, locale_id)?;
-        let numbers = get_numbers_formatting(&cldr_dir, locale_id)?;
-        let currency = get_locale_currency(&cldr_dir, full_locale_id, locale_id)?;
+        // The currency depends on the territory, i.e. "pt" is "pt-BR"
+        let full_locale_id = get_territory_locale_id(cldr_dir, locale_id)?;
+        let dates = get_dates_formatting(cldr_dir, locale_id)?;
+        let numbers = get_numbers_formatting(cldr_dir, locale_id)?;
+        let currency = get_locale_currency(cldr_dir, &full_locale_id, locale_id)?;
         let currency = Currency {
             iso: currency.iso.clone(),
             symbol: currency.symbol.clone(),
@@ -75,16 +103,5 @@ fn main() -> Result<(), String> {
             },
         );
     }
-
-    let s = serde_json::to_string(&locales).or(Err("Failed to stringify data"))?;
-    let mut f = fs::File::create(opt.output).or(Err("Failed to create file"))?;
-    f.write_all(s.as_bytes()).or(Err("Failed writing"))?;
-
-    // save to locales.bin using bitcode
-    let bytes = bitcode::encode(&locales);
-    let mut f_bin = fs::File::create("locales.bin").or(Err("Failed to create locales.bin"))?;
-    f_bin
-        .write_all(&bytes)
-        .or(Err("Failed writing locales.bin"))?;
-    Ok(())
+    Ok(locales)
 }
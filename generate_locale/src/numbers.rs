@@ -17,6 +17,84 @@ struct NumbersJSON {
     main: HashMap<String, NumbersJSONId>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct DefaultNumberingSystem {
+    #[serde(rename = "defaultNumberingSystem")]
+    default_numbering_system: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DefaultNumberingSystemId {
+    numbers: DefaultNumberingSystem,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DefaultNumberingSystemJSON {
+    main: HashMap<String, DefaultNumberingSystemId>,
+}
+
+// This comes from cldr-core/supplemental/numberingSystems.json
+// {
+//   "supplemental": {
+//     "numberingSystems": {
+//       "arab": { "_digits": "٠١٢٣٤٥٦٧٨٩", "_type": "numeric" },
+//       "latn": { "_digits": "0123456789", "_type": "numeric" }
+//     }
+//   }
+// }
+#[derive(Serialize, Deserialize)]
+struct NumberingSystem {
+    #[serde(rename = "_digits")]
+    digits: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NumberingSystemsSupplemental {
+    numbering_systems: HashMap<String, NumberingSystem>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NumberingSystemsJSON {
+    supplemental: NumberingSystemsSupplemental,
+}
+
+// Returns the digits of the default numbering system of the locale if they are not the latin ones
+fn get_native_digits(
+    cldr_dir: &str,
+    locale_id: &str,
+    contents: &str,
+) -> Result<Option<String>, String> {
+    let numbers_json: DefaultNumberingSystemJSON =
+        serde_json::from_str(contents).or(Err("Failed parsing 'numbers.json' file"))?;
+    let numbering_system = &numbers_json.main[locale_id]
+        .numbers
+        .default_numbering_system;
+    if numbering_system == "latn" {
+        return Ok(None);
+    }
+    let systems_file = format!(
+        "{}cldr-json/cldr-core/supplemental/numberingSystems.json",
+        cldr_dir
+    );
+    let contents =
+        fs::read_to_string(&systems_file).or(Err(format!("Failed reading '{}'", systems_file)))?;
+    let systems: NumberingSystemsJSON =
+        serde_json::from_str(&contents).or(Err("Failed parsing 'numberingSystems.json' file"))?;
+    match systems
+        .supplemental
+        .numbering_systems
+        .get(numbering_system)
+        .and_then(|system| system.digits.clone())
+    {
+        Some(digits) => Ok(Some(digits)),
+        None => Err(format!(
+            "Numbering system {} of locale {} is not numeric",
+            numbering_system, locale_id
+        )),
+    }
+}
+
 pub fn get_numbers_formatting(
     cldr_dir: &str,
     locale_id: &str,
@@ -51,5 +129,7 @@ pub fn get_numbers_formatting(
         );
         return Err(message);
     };
-    Ok(numbers_json.main[locale_id].numbers.clone())
+    let mut numbers = numbers_json.main[locale_id].numbers.clone();
+    numbers.native_digits = get_native_digits(cldr_dir, locale_id, &contents)?;
+    Ok(numbers)
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 
 use crate::constants::LOCAL_TYPE;
@@ -24,3 +25,44 @@ pub fn get_all_locales_id(cldr_dir: &str) -> Vec<String> {
         locales.available_locales.full
     }
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LikelySubtagsSupplemental {
+    likely_subtags: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LikelySubtags {
+    supplemental: LikelySubtagsSupplemental,
+}
+
+/// Returns the locale id with its most likely territory, i.e. "pt" => "pt-BR" or "zh" => "zh-CN".
+/// Locales that already have a territory are returned as they are.
+pub fn get_territory_locale_id(cldr_dir: &str, locale_id: &str) -> Result<String, String> {
+    if locale_id.contains('-') {
+        return Ok(locale_id.to_string());
+    }
+    let subtags_file = format!(
+        "{}cldr-json/cldr-core/supplemental/likelySubtags.json",
+        cldr_dir
+    );
+    let contents =
+        fs::read_to_string(&subtags_file).or(Err(format!("Failed reading '{}'", subtags_file)))?;
+    let subtags: LikelySubtags =
+        serde_json::from_str(&contents).or(Err("Failed parsing 'likelySubtags.json' file"))?;
+    // Something like "pt-Latn-BR"
+    let likely = subtags
+        .supplemental
+        .likely_subtags
+        .get(locale_id)
+        .ok_or(format!(
+            "No likely subtags found for locale ID {}",
+            locale_id
+        ))?;
+    let territory = likely
+        .split('-')
+        .next_back()
+        .ok_or(format!("No territory found for locale ID {}", locale_id))?;
+    Ok(format!("{}-{}", locale_id, territory))
+}
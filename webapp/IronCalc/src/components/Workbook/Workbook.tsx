@@ -262,8 +262,8 @@ const Workbook = (props: { model: Model; workbookState: WorkbookState }) => {
     },
     onUnderline: () => {
       const { sheet, row, column } = model.getSelectedView();
-      const value = model.getCellStyle(sheet, row, column).font.u;
-      onToggleUnderline(!value);
+      const { underline } = model.getCellStyle(sheet, row, column).font;
+      onToggleUnderline((underline ?? "none") === "none");
     },
     onNavigationToEdge: (direction: NavigationKey): void => {
       model.onNavigateToEdgeInDirection(direction);
@@ -410,9 +410,9 @@ const Workbook = (props: { model: Model; workbookState: WorkbookState }) => {
         }
         const mimeTypes = [
           "application/json",
+          "text/html",
           "text/plain",
           "text/csv",
-          "text/html",
         ];
         let mimeType = null;
         let value = null;
@@ -450,6 +450,19 @@ const Workbook = (props: { model: Model; workbookState: WorkbookState }) => {
             source.type === "cut",
           );
           setRedrawId((id) => id + 1);
+        } else if (mimeType === "text/html") {
+          try {
+            model.pasteHtmlString(value);
+          } catch {
+            // Not a table, we paste it as text
+            const { sheet, range } = model.getSelectedView();
+            const text = event.clipboardData.getData("text/plain");
+            model.pasteCsvText(
+              { sheet, row: range[0], column: range[1], width: 1, height: 1 },
+              text,
+            );
+          }
+          setRedrawId((id) => id + 1);
         } else if (mimeType === "text/plain") {
           const {
             sheet,
@@ -506,6 +519,7 @@ const Workbook = (props: { model: Model; workbookState: WorkbookState }) => {
           clipboardId,
         });
         event.clipboardData.setData("text/plain", data.csv.trim());
+        event.clipboardData.setData("text/html", model.copyToHtml());
         event.clipboardData.setData("application/json", clipboardJsonStr);
         event.preventDefault();
         event.stopPropagation();
@@ -544,6 +558,7 @@ const Workbook = (props: { model: Model; workbookState: WorkbookState }) => {
           clipboardId,
         });
         event.clipboardData.setData("text/plain", data.csv);
+        event.clipboardData.setData("text/html", model.copyToHtml());
         event.clipboardData.setData("application/json", clipboardJsonStr);
         workbookState.setCutRange({
           sheet: model.getSelectedSheet(),
@@ -663,7 +678,7 @@ const Workbook = (props: { model: Model; workbookState: WorkbookState }) => {
         fontColor={style.font.color}
         fontSize={style.font.sz}
         bold={style.font.b}
-        underline={style.font.u}
+        underline={(style.font.underline ?? "none") !== "none"}
         italic={style.font.i}
         strike={style.font.strike}
         horizontalAlign={
@@ -599,7 +599,7 @@ export default class WorksheetCanvas {
       fontSize,
       textColor,
       font,
-      underlined: style.font?.u || false,
+      underlined: (style.font?.underline ?? "none") !== "none",
       strike: style.font?.strike || false,
       lines: [] as [string, number, number, number][],
     };
@@ -1,4 +1,7 @@
-use ironcalc::{base::Model, export::save_to_xlsx};
+use ironcalc::{
+    base::{types::Color, Model},
+    export::save_to_xlsx,
+};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut model = Model::new_empty("hello_styles", "en", "UTC", "en")?;
@@ -6,9 +9,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // We are going to change styles in cell A1
     let (sheet, row, column) = (0, 1, 1);
     let mut style = model.get_style_for_cell(sheet, row, column)?;
-    style.fill.fg_color = Some("#FF9011".to_string());
+    style.fill.fg_color = Some(Color::Rgb("#FF9011".to_string()));
     style.font.b = true;
-    style.font.color = Some("#E91E63".to_string());
+    style.font.color = Some(Color::Rgb("#E91E63".to_string()));
     model.set_cell_style(sheet, row, column, &style)?;
 
     // saves to disk
@@ -0,0 +1,112 @@
+use ironcalc_base::types::{AutoFilter, ColumnFilter, Workbook};
+
+use super::escape::escape_xml;
+
+/// A differential format (dxf) with the color of a color filter: (cell_color, color)
+pub(crate) type FilterDxf = (bool, String);
+
+/// Returns the differential formats used by the color filters in the workbook.
+/// IronCalc does not have dxfs, these are the only ones we export.
+pub(crate) fn get_filter_dxfs(workbook: &Workbook) -> Vec<FilterDxf> {
+    let mut dxfs = Vec::new();
+    for worksheet in &workbook.worksheets {
+        if let Some(auto_filter) = &worksheet.auto_filter {
+            for filter_column in &auto_filter.filter_columns {
+                if let ColumnFilter::Color { cell_color, color } = &filter_column.filter {
+                    let dxf = (*cell_color, color.clone());
+                    if !dxfs.contains(&dxf) {
+                        dxfs.push(dxf);
+                    }
+                }
+            }
+        }
+    }
+    dxfs
+}
+
+pub(crate) fn get_dxfs_xml(dxfs: &[FilterDxf]) -> String {
+    let dxfs_str: Vec<String> = dxfs
+        .iter()
+        .map(|(cell_color, color)| {
+            let rgb = color.trim_start_matches('#');
+            if *cell_color {
+                format!(
+                    "<dxf><fill><patternFill><bgColor rgb=\"FF{rgb}\"/></patternFill></fill></dxf>"
+                )
+            } else {
+                format!("<dxf><font><color rgb=\"FF{rgb}\"/></font></dxf>")
+            }
+        })
+        .collect();
+    if dxfs_str.is_empty() {
+        return "<dxfs count=\"0\"/>".to_string();
+    }
+    format!(
+        "<dxfs count=\"{}\">{}</dxfs>",
+        dxfs_str.len(),
+        dxfs_str.join("")
+    )
+}
+
+pub(crate) fn get_auto_filter_xml(auto_filter: &AutoFilter, dxfs: &[FilterDxf]) -> String {
+    let mut filter_columns_str = Vec::new();
+    for filter_column in &auto_filter.filter_columns {
+        let filter = match &filter_column.filter {
+            ColumnFilter::Values { values, blank } => {
+                let blank = if *blank { " blank=\"1\"" } else { "" };
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|v| format!("<filter val=\"{}\"/>", escape_xml(v)))
+                    .collect();
+                format!("<filters{blank}>{}</filters>", values.join(""))
+            }
+            ColumnFilter::Custom { and, filters } => {
+                let and = if *and { " and=\"1\"" } else { "" };
+                let filters: Vec<String> = filters
+                    .iter()
+                    .map(|f| {
+                        format!(
+                            "<customFilter operator=\"{}\" val=\"{}\"/>",
+                            f.operator,
+                            escape_xml(&f.value)
+                        )
+                    })
+                    .collect();
+                format!("<customFilters{and}>{}</customFilters>", filters.join(""))
+            }
+            ColumnFilter::Top10 {
+                top,
+                percent,
+                value,
+            } => {
+                format!(
+                    "<top10 top=\"{}\" percent=\"{}\" val=\"{value}\"/>",
+                    i32::from(*top),
+                    i32::from(*percent)
+                )
+            }
+            ColumnFilter::Dynamic { filter_type } => {
+                format!("<dynamicFilter type=\"{filter_type}\"/>")
+            }
+            ColumnFilter::Color { cell_color, color } => {
+                let dxf_id = dxfs
+                    .iter()
+                    .position(|(c, s)| c == cell_color && s == color)
+                    .unwrap_or(0);
+                format!(
+                    "<colorFilter dxfId=\"{dxf_id}\" cellColor=\"{}\"/>",
+                    i32::from(*cell_color)
+                )
+            }
+        };
+        filter_columns_str.push(format!(
+            "<filterColumn colId=\"{}\">{filter}</filterColumn>",
+            filter_column.col_id
+        ));
+    }
+    format!(
+        "<autoFilter ref=\"{}\">{}</autoFilter>",
+        auto_filter.reference,
+        filter_columns_str.join("")
+    )
+}
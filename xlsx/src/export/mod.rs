@@ -1,10 +1,15 @@
 #![allow(clippy::unwrap_used)]
 
 mod _rels;
+mod auto_filter;
 mod doc_props;
 mod escape;
+mod opaque_parts;
+mod page_setup;
+mod protection;
 mod shared_strings;
 mod styles;
+mod theme;
 mod workbook;
 mod workbook_xml_rels;
 mod worksheets;
@@ -33,8 +38,17 @@ fn get_content_types_xml(workbook: &Workbook) -> String {
         r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#.to_string(),
         r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#.to_string(),
         r#"<Default Extension="xml" ContentType="application/xml"/>"#.to_string(),
-        r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#.to_string(),
     ];
+    // i.e. macro enabled workbooks have a different content type
+    let workbook_content_type = match &workbook.opaque_parts.workbook_content_type {
+        Some(content_type) => escape::escape_xml(content_type).to_string(),
+        None => {
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml".to_string()
+        }
+    };
+    content.push(format!(
+        r#"<Override PartName="/xl/workbook.xml" ContentType="{workbook_content_type}"/>"#
+    ));
     for worksheet in 0..workbook.worksheets.len() {
         let sheet = format!(
             r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
@@ -42,16 +56,17 @@ fn get_content_types_xml(workbook: &Workbook) -> String {
         );
         content.push(sheet);
     }
-    // we skip the theme and calcChain
-    // r#"<Override PartName="/xl/theme/theme1.xml" ContentType="application/vnd.openxmlformats-officedocument.theme+xml"/>"#,
+    // we skip the calcChain
     // r#"<Override PartName="/xl/calcChain.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.calcChain+xml"/>"#,
     content.extend([
         r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#.to_string(),
         r#"<Override PartName="/xl/sharedStrings.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"/>"#.to_string(),
+        r#"<Override PartName="/xl/theme/theme1.xml" ContentType="application/vnd.openxmlformats-officedocument.theme+xml"/>"#.to_string(),
         r#"<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>"#.to_string(),
         r#"<Override PartName="/docProps/app.xml" ContentType="application/vnd.openxmlformats-officedocument.extended-properties+xml"/>"#.to_string(),
-        r#"</Types>"#.to_string(),
     ]);
+    content.extend(opaque_parts::get_content_types_overrides(workbook));
+    content.push(r#"</Types>"#.to_string());
     format!("{XML_DECLARATION}\n{}", content.join(""))
 }
 
@@ -98,7 +113,11 @@ pub fn save_xlsx_to_writer<W: Write + Seek>(model: &Model, writer: W) -> Result<
     zip.start_file("xl/sharedStrings.xml", options)?;
     zip.write_all(shared_strings::get_shared_strings_xml(workbook).as_bytes())?;
     zip.start_file("xl/styles.xml", options)?;
-    zip.write_all(styles::get_styles_xml(workbook).as_bytes())?;
+    let dxfs = auto_filter::get_filter_dxfs(workbook);
+    zip.write_all(styles::get_styles_xml(workbook, &dxfs).as_bytes())?;
+    zip.add_directory("xl/theme", options)?;
+    zip.start_file("xl/theme/theme1.xml", options)?;
+    zip.write_all(theme::get_theme_xml(&workbook.theme).as_bytes())?;
     zip.start_file("xl/workbook.xml", options)?;
     zip.write_all(workbook::get_workbook_xml(workbook, selected_sheet).as_bytes())?;
 
@@ -127,9 +146,20 @@ pub fn save_xlsx_to_writer<W: Write + Seek>(model: &Model, writer: W) -> Result<
                 &model.parsed_formulas[sheet_index],
                 sheet_dimension_str,
                 is_sheet_selected,
+                &dxfs,
             )
             .as_bytes(),
         )?;
+        if let Some(relationships) = opaque_parts::get_sheet_relationships_xml(worksheet) {
+            zip.start_file(format!("xl/worksheets/_rels/sheet{id}.xml.rels"), options)?;
+            zip.write_all(relationships.as_bytes())?;
+        }
+    }
+
+    // Parts we do not understand are written back as they were
+    for part in &workbook.opaque_parts.parts {
+        zip.start_file(part.path.as_str(), options)?;
+        zip.write_all(&part.data)?;
     }
 
     let writer = zip.finish()?;
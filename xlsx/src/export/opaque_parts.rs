@@ -0,0 +1,167 @@
+//! Writes back the parts of the xlsx package we kept as is when importing the file.
+//! The relationships of the package and of the workbook are renumbered after ours.
+
+use std::collections::HashMap;
+
+use ironcalc_base::types::{OpaqueRelationship, Workbook, Worksheet};
+
+use super::escape::escape_xml;
+use super::xml_constants::XML_DECLARATION;
+
+// The package relationships we write are rId1, rId2 and rId3
+const PACKAGE_RELATIONSHIPS_COUNT: usize = 3;
+
+fn get_relationship_xml(relationship: &OpaqueRelationship, id: &str) -> String {
+    let target_mode = match &relationship.target_mode {
+        Some(target_mode) => format!(" TargetMode=\"{}\"", escape_xml(target_mode)),
+        None => "".to_string(),
+    };
+    format!(
+        "<Relationship Id=\"{id}\" Type=\"{}\" Target=\"{}\"{target_mode}/>",
+        escape_xml(&relationship.rel_type),
+        escape_xml(&relationship.target)
+    )
+}
+
+/// Returns the `<Override>` elements of `[Content_Types].xml` for the kept parts
+pub(crate) fn get_content_types_overrides(workbook: &Workbook) -> Vec<String> {
+    workbook
+        .opaque_parts
+        .parts
+        .iter()
+        // Relationships files are covered by the `rels` default
+        .filter(|part| !part.path.ends_with(".rels"))
+        .filter_map(|part| {
+            part.content_type.as_ref().map(|content_type| {
+                format!(
+                    "<Override PartName=\"/{}\" ContentType=\"{}\"/>",
+                    escape_xml(&part.path),
+                    escape_xml(content_type)
+                )
+            })
+        })
+        .collect()
+}
+
+/// Returns the kept relationships of `_rels/.rels`
+pub(crate) fn get_package_relationships_xml(workbook: &Workbook) -> String {
+    workbook
+        .opaque_parts
+        .package_relationships
+        .iter()
+        .enumerate()
+        .map(|(index, relationship)| {
+            let id = format!("rId{}", PACKAGE_RELATIONSHIPS_COUNT + index + 1);
+            get_relationship_xml(relationship, &id)
+        })
+        .collect()
+}
+
+/// Maps the original ids of the kept workbook relationships to the ones we write.
+/// We write one relationship per sheet plus the styles and the shared strings.
+pub(crate) fn get_workbook_relationship_ids(workbook: &Workbook) -> HashMap<String, String> {
+    let first_id = workbook.worksheets.len() + 4;
+    workbook
+        .opaque_parts
+        .workbook_relationships
+        .iter()
+        .enumerate()
+        .map(|(index, relationship)| (relationship.id.clone(), format!("rId{}", first_id + index)))
+        .collect()
+}
+
+/// Returns the kept relationships of `xl/_rels/workbook.xml.rels`
+pub(crate) fn get_workbook_relationships_xml(workbook: &Workbook) -> String {
+    let ids = get_workbook_relationship_ids(workbook);
+    workbook
+        .opaque_parts
+        .workbook_relationships
+        .iter()
+        .map(|relationship| get_relationship_xml(relationship, &ids[&relationship.id]))
+        .collect()
+}
+
+// Replaces the values of the `name` attributes in a single pass
+fn replace_attribute_values(xml: &str, name: &str, replace: impl Fn(&str) -> String) -> String {
+    let attribute = format!(" {name}=\"");
+    let mut result = String::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&attribute) {
+        let (before, after) = rest.split_at(start + attribute.len());
+        result.push_str(before);
+        let end = after.find('"').unwrap_or(after.len());
+        result.push_str(&replace(&after[..end]));
+        rest = &after[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn rename_relationship_ids(xml: &str, ids: &HashMap<String, String>) -> String {
+    replace_attribute_values(xml, "r:id", |id| {
+        ids.get(id).map_or(id, |new_id| new_id.as_str()).to_string()
+    })
+}
+
+/// Returns the kept elements of the workbook with the given name, i.e. `pivotCaches`
+pub(crate) fn get_workbook_elements_xml(workbook: &Workbook, name: &str) -> String {
+    let ids = get_workbook_relationship_ids(workbook);
+    workbook
+        .opaque_parts
+        .workbook_elements
+        .iter()
+        .filter(|element| element.name == name)
+        .map(|element| rename_relationship_ids(&element.xml, &ids))
+        .collect()
+}
+
+/// Returns the kept chart sheets of the `sheets` list. They go after the worksheets, with
+/// `sheetId`s after the ones of the worksheets, that might have been added since the import.
+pub(crate) fn get_chart_sheets_xml(workbook: &Workbook) -> String {
+    let ids = get_workbook_relationship_ids(workbook);
+    let max_sheet_id = workbook
+        .worksheets
+        .iter()
+        .map(|worksheet| worksheet.sheet_id)
+        .max()
+        .unwrap_or(0);
+    workbook
+        .opaque_parts
+        .workbook_elements
+        .iter()
+        .filter(|element| element.name == "sheet")
+        .enumerate()
+        .map(|(index, element)| {
+            let xml = rename_relationship_ids(&element.xml, &ids);
+            replace_attribute_values(&xml, "sheetId", |_| {
+                (max_sheet_id as usize + index + 1).to_string()
+            })
+        })
+        .collect()
+}
+
+/// Returns the relationships file of the worksheet, if it has any kept relationship.
+/// The ids of the relationships are the original ones.
+pub(crate) fn get_sheet_relationships_xml(worksheet: &Worksheet) -> Option<String> {
+    let relationships = &worksheet.opaque_parts.relationships;
+    if relationships.is_empty() {
+        return None;
+    }
+    let relationships: String = relationships
+        .iter()
+        .map(|relationship| get_relationship_xml(relationship, &relationship.id))
+        .collect();
+    Some(format!(
+        "{XML_DECLARATION}\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{relationships}</Relationships>"
+    ))
+}
+
+/// Returns the kept elements of the worksheet (drawings, pictures,...)
+pub(crate) fn get_sheet_elements_xml(worksheet: &Worksheet) -> String {
+    worksheet
+        .opaque_parts
+        .elements
+        .iter()
+        .map(|element| element.xml.as_str())
+        .collect()
+}
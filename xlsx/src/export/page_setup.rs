@@ -0,0 +1,102 @@
+use ironcalc_base::types::PageSetup;
+
+use super::escape::escape_xml;
+
+fn get_breaks_xml(tag_name: &str, breaks: &[i32], max: i32) -> String {
+    if breaks.is_empty() {
+        return "".to_string();
+    }
+    let count = breaks.len();
+    let breaks_str: Vec<String> = breaks
+        .iter()
+        .map(|id| format!("<brk id=\"{id}\" max=\"{max}\" man=\"1\"/>"))
+        .collect();
+    format!(
+        "<{tag_name} count=\"{count}\" manualBreakCount=\"{count}\">{}</{tag_name}>",
+        breaks_str.join("")
+    )
+}
+
+/// Returns the print settings of the worksheet: printOptions, pageMargins, pageSetup, headerFooter,
+/// rowBreaks and colBreaks, in that order.
+pub(crate) fn get_page_setup_xml(page_setup: &PageSetup) -> String {
+    let mut xml = String::new();
+
+    let mut print_options = String::new();
+    if page_setup.horizontal_centered {
+        print_options.push_str(" horizontalCentered=\"1\"");
+    }
+    if page_setup.vertical_centered {
+        print_options.push_str(" verticalCentered=\"1\"");
+    }
+    if page_setup.print_headings {
+        print_options.push_str(" headings=\"1\"");
+    }
+    if page_setup.print_grid_lines {
+        print_options.push_str(" gridLines=\"1\"");
+    }
+    if !print_options.is_empty() {
+        xml.push_str(&format!("<printOptions{print_options}/>"));
+    }
+
+    let margins = &page_setup.margins;
+    xml.push_str(&format!(
+        "<pageMargins left=\"{}\" right=\"{}\" top=\"{}\" bottom=\"{}\" header=\"{}\" footer=\"{}\"/>",
+        margins.left, margins.right, margins.top, margins.bottom, margins.header, margins.footer
+    ));
+
+    let first_page_number = match page_setup.first_page_number {
+        Some(number) => format!(" firstPageNumber=\"{number}\" useFirstPageNumber=\"1\""),
+        None => "".to_string(),
+    };
+    xml.push_str(&format!(
+        "<pageSetup paperSize=\"{}\" scale=\"{}\"{first_page_number} fitToWidth=\"{}\" fitToHeight=\"{}\" orientation=\"{}\"/>",
+        page_setup.paper_size,
+        page_setup.scale,
+        page_setup.fit_to_width,
+        page_setup.fit_to_height,
+        page_setup.orientation,
+    ));
+
+    let header_footer = &page_setup.header_footer;
+    let mut header_footer_str = String::new();
+    for (tag_name, text) in [
+        ("oddHeader", &header_footer.odd_header),
+        ("oddFooter", &header_footer.odd_footer),
+        ("evenHeader", &header_footer.even_header),
+        ("evenFooter", &header_footer.even_footer),
+        ("firstHeader", &header_footer.first_header),
+        ("firstFooter", &header_footer.first_footer),
+    ] {
+        if !text.is_empty() {
+            header_footer_str.push_str(&format!("<{tag_name}>{}</{tag_name}>", escape_xml(text)));
+        }
+    }
+    if !header_footer_str.is_empty()
+        || header_footer.different_odd_even
+        || header_footer.different_first
+    {
+        let different_odd_even = if header_footer.different_odd_even {
+            " differentOddEven=\"1\""
+        } else {
+            ""
+        };
+        let different_first = if header_footer.different_first {
+            " differentFirst=\"1\""
+        } else {
+            ""
+        };
+        xml.push_str(&format!(
+            "<headerFooter{different_odd_even}{different_first}>{header_footer_str}</headerFooter>"
+        ));
+    }
+
+    // `max` is the last column (row) index 0-based
+    xml.push_str(&get_breaks_xml("rowBreaks", &page_setup.row_breaks, 16383));
+    xml.push_str(&get_breaks_xml(
+        "colBreaks",
+        &page_setup.column_breaks,
+        1048575,
+    ));
+    xml
+}
@@ -0,0 +1,90 @@
+use ironcalc_base::types::{ProtectionPassword, SheetProtection, WorkbookProtection};
+
+// `prefix` is "" for sheets and "workbook" for workbooks, i.e. `workbookHashValue`
+fn get_password_attributes(password: &Option<ProtectionPassword>, prefix: &str) -> String {
+    let name = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            let mut chars = name.chars();
+            let first = chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .unwrap_or_default();
+            format!("{prefix}{first}{}", chars.as_str())
+        }
+    };
+    match password {
+        Some(ProtectionPassword::Hashed {
+            algorithm_name,
+            hash_value,
+            salt_value,
+            spin_count,
+        }) => format!(
+            " {}=\"{algorithm_name}\" {}=\"{hash_value}\" {}=\"{salt_value}\" {}=\"{spin_count}\"",
+            name("algorithmName"),
+            name("hashValue"),
+            name("saltValue"),
+            name("spinCount")
+        ),
+        Some(ProtectionPassword::Legacy(hash)) => format!(" {}=\"{hash}\"", name("password")),
+        None => "".to_string(),
+    }
+}
+
+/// Returns the `<sheetProtection>` element of the worksheet.
+/// Note that the attributes are true if the operation is *not* allowed.
+pub(crate) fn get_sheet_protection_xml(protection: &Option<SheetProtection>) -> String {
+    let protection = match protection {
+        Some(protection) => protection,
+        None => return "".to_string(),
+    };
+    let mut attributes = get_password_attributes(&protection.password, "");
+    attributes.push_str(" sheet=\"1\"");
+    // These are allowed by default
+    for (name, allowed) in [
+        ("objects", protection.objects),
+        ("scenarios", protection.scenarios),
+        ("selectLockedCells", protection.select_locked_cells),
+        ("selectUnlockedCells", protection.select_unlocked_cells),
+    ] {
+        if !allowed {
+            attributes.push_str(&format!(" {name}=\"1\""));
+        }
+    }
+    // These are not allowed by default
+    for (name, allowed) in [
+        ("formatCells", protection.format_cells),
+        ("formatColumns", protection.format_columns),
+        ("formatRows", protection.format_rows),
+        ("insertColumns", protection.insert_columns),
+        ("insertRows", protection.insert_rows),
+        ("insertHyperlinks", protection.insert_hyperlinks),
+        ("deleteColumns", protection.delete_columns),
+        ("deleteRows", protection.delete_rows),
+        ("sort", protection.sort),
+        ("autoFilter", protection.auto_filter),
+        ("pivotTables", protection.pivot_tables),
+    ] {
+        if allowed {
+            attributes.push_str(&format!(" {name}=\"0\""));
+        }
+    }
+    format!("<sheetProtection{attributes}/>")
+}
+
+/// Returns the `<workbookProtection>` element of the workbook
+pub(crate) fn get_workbook_protection_xml(protection: &Option<WorkbookProtection>) -> String {
+    let protection = match protection {
+        Some(protection) => protection,
+        None => return "".to_string(),
+    };
+    let mut attributes = get_password_attributes(&protection.password, "workbook");
+    if protection.lock_structure {
+        attributes.push_str(" lockStructure=\"1\"");
+    }
+    if protection.lock_windows {
+        attributes.push_str(" lockWindows=\"1\"");
+    }
+    format!("<workbookProtection{attributes}/>")
+}
@@ -1,4 +1,4 @@
-use ironcalc_base::types::Workbook;
+use ironcalc_base::types::{FontUnderline, RunFont, TextRun, Workbook};
 
 use super::{escape::escape_xml, xml_constants::XML_DECLARATION};
 
@@ -6,11 +6,66 @@ pub(crate) fn get_shared_strings_xml(model: &Workbook) -> String {
     let mut shared_strings: Vec<String> = vec![];
     let count = &model.shared_strings.len();
     let unique_count = &model.shared_strings.len();
-    for shared_string in &model.shared_strings {
-        shared_strings.push(format!("<si><t>{}</t></si>", escape_xml(shared_string)));
+    for (index, shared_string) in model.shared_strings.iter().enumerate() {
+        match model.rich_text.get(&(index as u32)) {
+            Some(runs) => shared_strings.push(format!("<si>{}</si>", get_runs_xml(runs))),
+            None => shared_strings.push(format!("<si><t>{}</t></si>", escape_xml(shared_string))),
+        }
     }
     format!("{}\n\
       <sst xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" count=\"{count}\" uniqueCount=\"{unique_count}\">\
         {}\
       </sst>", XML_DECLARATION, shared_strings.join(""))
 }
+
+fn get_runs_xml(runs: &[TextRun]) -> String {
+    let mut runs_xml = String::new();
+    for run in runs {
+        let properties = match &run.font {
+            Some(font) => get_run_properties_xml(font),
+            None => "".to_string(),
+        };
+        runs_xml.push_str(&format!(
+            "<r>{properties}<t xml:space=\"preserve\">{}</t></r>",
+            escape_xml(&run.text)
+        ));
+    }
+    runs_xml
+}
+
+// The order of the elements is the one Excel uses
+fn get_run_properties_xml(font: &RunFont) -> String {
+    let mut properties = String::new();
+    let flags = [("b", font.b), ("i", font.i), ("strike", font.strike)];
+    for (name, value) in flags {
+        match value {
+            Some(true) => properties.push_str(&format!("<{name}/>")),
+            Some(false) => properties.push_str(&format!("<{name} val=\"0\"/>")),
+            None => {}
+        }
+    }
+    match font.underline {
+        Some(FontUnderline::Single) => properties.push_str("<u/>"),
+        Some(kind) => properties.push_str(&format!("<u val=\"{kind}\"/>")),
+        None => {}
+    }
+    if let Some(vert_align) = font.vert_align {
+        properties.push_str(&format!("<vertAlign val=\"{vert_align}\"/>"));
+    }
+    if let Some(sz) = font.sz {
+        properties.push_str(&format!("<sz val=\"{sz}\"/>"));
+    }
+    if let Some(color) = &font.color {
+        properties.push_str(&format!(
+            "<color rgb=\"FF{}\"/>",
+            color.trim_start_matches('#')
+        ));
+    }
+    if let Some(name) = &font.name {
+        properties.push_str(&format!("<rFont val=\"{}\"/>", escape_xml(name)));
+    }
+    if properties.is_empty() {
+        return properties;
+    }
+    format!("<rPr>{properties}</rPr>")
+}
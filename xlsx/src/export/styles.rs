@@ -1,23 +1,36 @@
 use ironcalc_base::types::{
-    Alignment, BorderItem, HorizontalAlignment, Styles, VerticalAlignment, Workbook,
+    Alignment, BorderItem, CellProtection, Color, FontUnderline, FontVerticalAlign, GradientFill,
+    GradientType, HorizontalAlignment, ReadingOrder, Styles, VerticalAlignment, Workbook,
 };
 
-use super::{escape::escape_xml, xml_constants::XML_DECLARATION};
+use super::{
+    auto_filter::{get_dxfs_xml, FilterDxf},
+    escape::escape_xml,
+    xml_constants::XML_DECLARATION,
+};
 
 fn get_fonts_xml(styles: &Styles) -> String {
     let fonts = &styles.fonts;
     let mut fonts_str: Vec<String> = vec![];
     for font in fonts {
         let size = format!("<sz val=\"{}\"/>", font.sz);
-        let color = if let Some(some_color) = &font.color {
-            format!("<color rgb=\"FF{}\"/>", some_color.trim_start_matches('#'))
-        } else {
-            "".to_string()
-        };
+        let color = get_color_xml(&font.color, "color");
         let name = format!("<name val=\"{}\"/>", escape_xml(&font.name));
         let bold = if font.b { "<b/>" } else { "" };
         let italic = if font.i { "<i/>" } else { "" };
-        let underline = if font.u { "<u/>" } else { "" };
+        let underline = match font.underline {
+            FontUnderline::None => "".to_string(),
+            FontUnderline::Single => "<u/>".to_string(),
+            kind => format!("<u val=\"{kind}\"/>"),
+        };
+        let vert_align = if font.vert_align != FontVerticalAlign::Baseline {
+            format!("<vertAlign val=\"{}\"/>", font.vert_align)
+        } else {
+            "".to_string()
+        };
+        let outline = if font.outline { "<outline/>" } else { "" };
+        let shadow = if font.shadow { "<shadow/>" } else { "" };
+        let condense = if font.condense { "<condense/>" } else { "" };
         let strike = if font.strike { "<strike/>" } else { "" };
         let family = format!("<family val=\"{}\"/>", font.family);
         let scheme = format!("<scheme val=\"{}\"/>", font.scheme);
@@ -30,6 +43,10 @@ fn get_fonts_xml(styles: &Styles) -> String {
                 {italic}\
                 {underline}\
                 {strike}\
+                {outline}\
+                {shadow}\
+                {condense}\
+                {vert_align}\
                 {family}\
                 {scheme}\
              </font>"
@@ -42,19 +59,60 @@ fn get_fonts_xml(styles: &Styles) -> String {
     )
 }
 
-fn get_color_xml(color: &Option<String>, name: &str) -> String {
-    // We blindly append FF at the beginning of these RGB color to make it ARGB
-    if let Some(some_color) = color {
-        format!("<{name} rgb=\"FF{}\"/>", some_color.trim_start_matches('#'))
-    } else {
-        "".to_string()
+fn get_color_xml(color: &Option<Color>, name: &str) -> String {
+    match color {
+        Some(Color::Theme { theme, tint }) if *tint != 0.0 => {
+            format!("<{name} theme=\"{theme}\" tint=\"{tint}\"/>")
+        }
+        Some(Color::Theme { theme, .. }) => format!("<{name} theme=\"{theme}\"/>"),
+        Some(Color::Indexed { indexed }) => format!("<{name} indexed=\"{indexed}\"/>"),
+        // We blindly append FF at the beginning of these RGB color to make it ARGB
+        Some(Color::Rgb(rgb)) => format!("<{name} rgb=\"FF{}\"/>", rgb.trim_start_matches('#')),
+        None => "".to_string(),
     }
 }
 
+fn get_gradient_fill_xml(gradient: &GradientFill) -> String {
+    let mut attributes = String::new();
+    match gradient.gradient_type {
+        GradientType::Linear => {
+            if gradient.degree != 0.0 {
+                attributes.push_str(&format!(" degree=\"{}\"", gradient.degree));
+            }
+        }
+        GradientType::Path => {
+            attributes.push_str(" type=\"path\"");
+            for (name, value) in [
+                ("left", gradient.left),
+                ("right", gradient.right),
+                ("top", gradient.top),
+                ("bottom", gradient.bottom),
+            ] {
+                if value != 0.0 {
+                    attributes.push_str(&format!(" {name}=\"{value}\""));
+                }
+            }
+        }
+    }
+    let stops: String = gradient
+        .stops
+        .iter()
+        .map(|stop| {
+            let color = get_color_xml(&stop.color, "color");
+            format!("<stop position=\"{}\">{color}</stop>", stop.position)
+        })
+        .collect();
+    format!("<gradientFill{attributes}>{stops}</gradientFill>")
+}
+
 fn get_fills_xml(styles: &Styles) -> String {
     let fills = &styles.fills;
     let mut fills_str: Vec<String> = vec![];
     for fill in fills {
+        if let Some(gradient) = &fill.gradient {
+            fills_str.push(format!("<fill>{}</fill>", get_gradient_fill_xml(gradient)));
+            continue;
+        }
         let pattern_type = &fill.pattern_type;
         let fg_color = get_color_xml(&fill.fg_color, "fgColor");
         let bg_color = get_color_xml(&fill.bg_color, "bgColor");
@@ -138,7 +196,49 @@ fn get_alignment(alignment: &Alignment) -> String {
     } else {
         "".to_string()
     };
-    format!("<alignment{wrap_text}{horizontal}{vertical}/>")
+    let text_rotation = if alignment.text_rotation != 0 {
+        format!(" textRotation=\"{}\"", alignment.text_rotation)
+    } else {
+        "".to_string()
+    };
+    let indent = if alignment.indent != 0 {
+        format!(" indent=\"{}\"", alignment.indent)
+    } else {
+        "".to_string()
+    };
+    let shrink_to_fit = if alignment.shrink_to_fit {
+        " shrinkToFit=\"1\""
+    } else {
+        ""
+    };
+    let justify_last_line = if alignment.justify_last_line {
+        " justifyLastLine=\"1\""
+    } else {
+        ""
+    };
+    let reading_order = match alignment.reading_order {
+        ReadingOrder::Context => "",
+        ReadingOrder::LeftToRight => " readingOrder=\"1\"",
+        ReadingOrder::RightToLeft => " readingOrder=\"2\"",
+    };
+    format!(
+        "<alignment{wrap_text}{horizontal}{vertical}{text_rotation}{indent}{shrink_to_fit}\
+         {justify_last_line}{reading_order}/>"
+    )
+}
+
+fn get_protection(protection: &CellProtection) -> String {
+    let locked = if protection.locked {
+        ""
+    } else {
+        " locked=\"0\""
+    };
+    let hidden = if protection.hidden {
+        " hidden=\"1\""
+    } else {
+        ""
+    };
+    format!("<protection{locked}{hidden}/>")
 }
 
 fn get_cell_style_xfs_xml(styles: &Styles) -> String {
@@ -164,16 +264,30 @@ fn get_cell_style_xfs_xml(styles: &Styles) -> String {
         } else {
             ""
         };
-        cell_style_str.push(format!(
-            "<xf \
-              borderId=\"{border_id}\" \
+        let properties = format!(
+            "borderId=\"{border_id}\" \
               fillId=\"{fill_id}\" \
               fontId=\"{font_id}\" \
               numFmtId=\"{num_fmt_id}\"\
               {apply_alignment_str}\
               {apply_font_str}\
-              {apply_fill_str}/>"
-        ));
+              {apply_fill_str}"
+        );
+        let alignment = cell_style_xf
+            .alignment
+            .as_ref()
+            .map(get_alignment)
+            .unwrap_or_default();
+        let protection = cell_style_xf
+            .protection
+            .as_ref()
+            .map(get_protection)
+            .unwrap_or_default();
+        if alignment.is_empty() && protection.is_empty() {
+            cell_style_str.push(format!("<xf {properties}/>"));
+        } else {
+            cell_style_str.push(format!("<xf {properties}>{alignment}{protection}</xf>"));
+        }
     }
     let style_count = cell_style_xfs.len();
     format!(
@@ -211,6 +325,11 @@ fn get_cell_xfs_xml(styles: &Styles) -> String {
         } else {
             ""
         };
+        let apply_protection_str = if cell_xf.protection.is_some() {
+            r#" applyProtection="1""#
+        } else {
+            ""
+        };
         let properties = format!(
             "xfId=\"{xf_id}\" \
                 borderId=\"{border_id}\" \
@@ -220,13 +339,23 @@ fn get_cell_xfs_xml(styles: &Styles) -> String {
                 {quote_prefix_str}\
                 {apply_alignment_str}\
                 {apply_font_str}\
-                {apply_fill_str}"
+                {apply_fill_str}\
+                {apply_protection_str}"
         );
-        if let Some(alignment) = &cell_xf.alignment {
-            let alignment = get_alignment(alignment);
-            cell_xfs_str.push(format!("<xf {properties}>{alignment}</xf>"));
-        } else {
+        let alignment = cell_xf
+            .alignment
+            .as_ref()
+            .map(get_alignment)
+            .unwrap_or_default();
+        let protection = cell_xf
+            .protection
+            .as_ref()
+            .map(get_protection)
+            .unwrap_or_default();
+        if alignment.is_empty() && protection.is_empty() {
             cell_xfs_str.push(format!("<xf {properties}/>"));
+        } else {
+            cell_xfs_str.push(format!("<xf {properties}>{alignment}{protection}</xf>"));
         }
     }
     let style_count = cell_xfs.len();
@@ -244,9 +373,13 @@ fn get_cell_styles_xml(styles: &Styles) -> String {
         let xf_id = cell_style.xf_id;
         let name = &cell_style.name;
         let name = escape_xml(name);
-        let builtin_id = cell_style.builtin_id;
+        // Custom styles do not have a builtinId
+        let builtin_id = cell_style
+            .get_builtin_id()
+            .map(|id| format!(" builtinId=\"{id}\""))
+            .unwrap_or_default();
         cell_styles_str.push(format!(
-            "<cellStyle xfId=\"{xf_id}\" name=\"{name}\" builtinId=\"{builtin_id}\"/>"
+            "<cellStyle xfId=\"{xf_id}\" name=\"{name}\"{builtin_id}/>"
         ));
     }
     let style_count = cell_styles.len();
@@ -256,7 +389,7 @@ fn get_cell_styles_xml(styles: &Styles) -> String {
     )
 }
 
-pub(crate) fn get_styles_xml(model: &Workbook) -> String {
+pub(crate) fn get_styles_xml(model: &Workbook, dxfs: &[FilterDxf]) -> String {
     let styles = &model.styles;
     let fonts = get_fonts_xml(styles);
     let fills = get_fills_xml(styles);
@@ -265,6 +398,7 @@ pub(crate) fn get_styles_xml(model: &Workbook) -> String {
     let cell_style_xfs = get_cell_style_xfs_xml(styles);
     let cell_xfs = get_cell_xfs_xml(styles);
     let cell_styles = get_cell_styles_xml(styles);
+    let dxfs = get_dxfs_xml(dxfs);
 
     format!(
         "{XML_DECLARATION}
@@ -276,7 +410,7 @@ pub(crate) fn get_styles_xml(model: &Workbook) -> String {
 {cell_style_xfs}\
 {cell_xfs}\
 {cell_styles}\
-<dxfs count=\"0\"/>\
+{dxfs}\
 </styleSheet>"
     )
 }
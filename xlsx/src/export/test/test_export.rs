@@ -1,6 +1,15 @@
 use std::fs;
 
-use ironcalc_base::Model;
+use ironcalc_base::cell::CellValue;
+use ironcalc_base::expressions::types::Area;
+use ironcalc_base::types::{
+    Alignment, AutoFilter, CellProtection, Color, ColumnFilter, CustomFilter, DateSystem,
+    DefinedName, DynamicFilterType, Fill, FilterColumn, FilterOperator, FontUnderline,
+    FontVerticalAlign, GradientFill, GradientStop, GradientType, HorizontalAlignment,
+    PageOrientation, PatternType, ProtectionPassword, ReadingOrder, RunFont, SheetProtection,
+    Style, TextRun, Theme, WorkbookProtection,
+};
+use ironcalc_base::{Model, UserModel};
 
 use crate::error::XlsxError;
 use crate::export::save_to_icalc;
@@ -126,6 +135,69 @@ fn frozen_rows_and_columns() {
     fs::remove_file(temp_file_name).unwrap();
 }
 
+#[test]
+fn auto_filter() {
+    let mut model = new_empty_model();
+    model.set_user_input(0, 1, 1, "Name".to_string()).unwrap();
+    model.set_user_input(0, 2, 1, "Apples".to_string()).unwrap();
+    let auto_filter = AutoFilter {
+        reference: "A1:E20".to_string(),
+        filter_columns: vec![
+            FilterColumn {
+                col_id: 0,
+                filter: ColumnFilter::Values {
+                    values: vec!["Apples".to_string(), "Pears & Plums".to_string()],
+                    blank: true,
+                },
+            },
+            FilterColumn {
+                col_id: 1,
+                filter: ColumnFilter::Custom {
+                    and: true,
+                    filters: vec![
+                        CustomFilter {
+                            operator: FilterOperator::GreaterThan,
+                            value: "20".to_string(),
+                        },
+                        CustomFilter {
+                            operator: FilterOperator::NotEqual,
+                            value: "3*".to_string(),
+                        },
+                    ],
+                },
+            },
+            FilterColumn {
+                col_id: 2,
+                filter: ColumnFilter::Top10 {
+                    top: false,
+                    percent: true,
+                    value: 15.0,
+                },
+            },
+            FilterColumn {
+                col_id: 3,
+                filter: ColumnFilter::Dynamic {
+                    filter_type: DynamicFilterType::Quarter(2),
+                },
+            },
+            FilterColumn {
+                col_id: 4,
+                filter: ColumnFilter::Color {
+                    cell_color: false,
+                    color: "#FF0000".to_string(),
+                },
+            },
+        ],
+    };
+    model.workbook.worksheets[0].auto_filter = Some(auto_filter.clone());
+    model.evaluate();
+    let temp_file_name = "temp_file_test_auto_filter.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    assert_eq!(model.workbook.worksheets[0].auto_filter, Some(auto_filter));
+    fs::remove_file(temp_file_name).unwrap();
+}
+
 #[test]
 fn test_formulas() {
     let mut model = new_empty_model();
@@ -218,3 +290,431 @@ fn test_existing_file() {
 
     fs::remove_file(file_name).unwrap();
 }
+
+#[test]
+fn outline_and_hidden_columns() {
+    let mut model = new_empty_model();
+    model.set_user_input(0, 2, 1, "Detail".to_string()).unwrap();
+    let worksheet = model.workbook.worksheet_mut(0).unwrap();
+    worksheet.set_column_hidden(2, true).unwrap();
+    worksheet.set_column_outline_level(3, 1).unwrap();
+    worksheet.set_column_outline_level(4, 1).unwrap();
+    worksheet.set_column_collapsed(5, true).unwrap();
+    // Rows 2 and 3 are grouped and collapsed, row 3 has no cells
+    worksheet.set_row_outline_level(2, 2).unwrap();
+    worksheet.set_row_outline_level(3, 1).unwrap();
+    worksheet.set_row_hidden(2, true).unwrap();
+    worksheet.set_row_hidden(3, true).unwrap();
+    worksheet.set_row_collapsed(1, true).unwrap();
+    worksheet.summary_below = false;
+    model.evaluate();
+    let temp_file_name = "temp_file_test_outline.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    let worksheet = model.workbook.worksheet(0).unwrap();
+    assert!(worksheet.is_column_hidden(2).unwrap());
+    assert!(!worksheet.is_column_hidden(3).unwrap());
+    assert_eq!(worksheet.get_column_outline_level(3).unwrap(), 1);
+    assert_eq!(worksheet.get_column_outline_level(4).unwrap(), 1);
+    assert_eq!(worksheet.get_column_outline_level(5).unwrap(), 0);
+    assert!(worksheet.is_column_collapsed(5).unwrap());
+    assert_eq!(worksheet.get_row_outline_level(2).unwrap(), 2);
+    assert_eq!(worksheet.get_row_outline_level(3).unwrap(), 1);
+    assert!(worksheet.is_row_hidden(2).unwrap());
+    assert!(worksheet.is_row_hidden(3).unwrap());
+    assert!(worksheet.is_row_collapsed(1).unwrap());
+    assert!(!worksheet.summary_below);
+    assert!(worksheet.summary_right);
+}
+
+#[test]
+fn page_setup() {
+    let mut model = new_empty_model();
+    model.set_user_input(0, 1, 1, "Title".to_string()).unwrap();
+    let sheet_id = model.workbook.worksheets[0].sheet_id;
+    let page_setup = &mut model.workbook.worksheets[0].page_setup;
+    page_setup.orientation = PageOrientation::Landscape;
+    page_setup.paper_size = 9;
+    page_setup.fit_to_page = true;
+    page_setup.fit_to_height = 0;
+    page_setup.first_page_number = Some(3);
+    page_setup.margins.left = 0.5;
+    page_setup.header_footer.odd_header = "&CBoard pack".to_string();
+    page_setup.print_grid_lines = true;
+    page_setup.horizontal_centered = true;
+    page_setup.row_breaks = vec![20, 40];
+    page_setup.column_breaks = vec![6];
+    model.workbook.defined_names.push(DefinedName {
+        name: "_xlnm.Print_Area".to_string(),
+        formula: "Sheet1!$A$1:$F$40".to_string(),
+        sheet_id: Some(sheet_id),
+    });
+    model.workbook.defined_names.push(DefinedName {
+        name: "_xlnm.Print_Titles".to_string(),
+        formula: "Sheet1!$A:$A,Sheet1!$1:$2".to_string(),
+        sheet_id: Some(sheet_id),
+    });
+    model.evaluate();
+    let expected = model.workbook.worksheets[0].page_setup.clone();
+
+    let temp_file_name = "temp_file_test_page_setup.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    assert_eq!(model.workbook.worksheets[0].page_setup, expected);
+    assert_eq!(
+        model.get_print_area(0).unwrap(),
+        Some("Sheet1!$A$1:$F$40".to_string())
+    );
+    let print_titles = model.get_print_titles(0).unwrap();
+    assert_eq!(print_titles.columns, Some((1, 1)));
+    assert_eq!(print_titles.rows, Some((1, 2)));
+}
+
+#[test]
+fn protection() {
+    let mut model = new_empty_model();
+    model.set_user_input(0, 1, 1, "=1+1".to_string()).unwrap();
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    style.protection = Some(CellProtection {
+        locked: false,
+        hidden: true,
+    });
+    model.set_cell_style(0, 1, 1, &style).unwrap();
+    let protection = SheetProtection {
+        password: Some(ProtectionPassword::Hashed {
+            algorithm_name: "SHA-512".to_string(),
+            hash_value: "M5SOVnbQG4SHyBnRVAYzAx8mPtxyyzMuWxcMv7tkyFO3MBXX9OJjklwPglNHdoHVkKPm4MPfUblqHmAsXfF5HA==".to_string(),
+            salt_value: "AAECAwQFBgcICQoLDA0ODw==".to_string(),
+            spin_count: 100_000,
+        }),
+        format_columns: true,
+        auto_filter: true,
+        select_locked_cells: false,
+        ..Default::default()
+    };
+    model.workbook.worksheets[0].protection = Some(protection.clone());
+    let workbook_protection = WorkbookProtection {
+        password: Some(ProtectionPassword::Legacy("83AF".to_string())),
+        lock_structure: true,
+        lock_windows: false,
+    };
+    model.workbook.protection = Some(workbook_protection.clone());
+    model.evaluate();
+
+    let temp_file_name = "temp_file_test_protection.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    assert_eq!(model.workbook.worksheets[0].protection, Some(protection));
+    assert_eq!(model.workbook.protection, Some(workbook_protection));
+    let style = model.get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(
+        style.protection,
+        Some(CellProtection {
+            locked: false,
+            hidden: true,
+        })
+    );
+    assert!(!model
+        .is_area_locked(&Area {
+            sheet: 0,
+            row: 1,
+            column: 1,
+            width: 1,
+            height: 1,
+        })
+        .unwrap());
+}
+
+#[test]
+fn date_system_1904() {
+    let mut model = new_empty_model();
+    model
+        .set_user_input(0, 1, 1, "2024-03-05".to_string())
+        .unwrap();
+    model.set_date_system(DateSystem::Excel1904).unwrap();
+    model.evaluate();
+    // 45356 is 2024-03-05 in the 1900 date system
+    assert_eq!(
+        model.get_cell_value_by_index(0, 1, 1),
+        Ok(CellValue::Number(45356.0 - 1462.0))
+    );
+
+    let temp_file_name = "temp_file_test_date_system.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    assert_eq!(model.get_date_system(), DateSystem::Excel1904);
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1).unwrap(),
+        "2024-03-05"
+    );
+}
+
+#[test]
+fn extended_font_and_alignment() {
+    let mut model = new_empty_model();
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    style.font.underline = FontUnderline::DoubleAccounting;
+    style.font.vert_align = FontVerticalAlign::Subscript;
+    style.font.outline = true;
+    style.font.shadow = true;
+    style.font.condense = true;
+    style.alignment = Some(Alignment {
+        text_rotation: 135,
+        indent: 3,
+        shrink_to_fit: true,
+        justify_last_line: true,
+        reading_order: ReadingOrder::LeftToRight,
+        ..Default::default()
+    });
+    model.set_cell_style(0, 1, 1, &style).unwrap();
+
+    let temp_file_name = "temp_file_test_extended_font_and_alignment.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    let imported = model.get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(imported.font, style.font);
+    assert_eq!(imported.alignment, style.alignment);
+}
+
+#[test]
+fn pattern_and_gradient_fills() {
+    let mut model = new_empty_model();
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    style.fill = Fill {
+        pattern_type: PatternType::LightGrid,
+        fg_color: Some(Color::Rgb("#FF0000".to_string())),
+        bg_color: Some(Color::Rgb("#FFFF00".to_string())),
+        ..Default::default()
+    };
+    model.set_cell_style(0, 1, 1, &style).unwrap();
+    let mut style = model.get_style_for_cell(0, 1, 2).unwrap();
+    style.fill = Fill {
+        gradient: Some(GradientFill {
+            gradient_type: GradientType::Linear,
+            degree: 90.0,
+            stops: vec![
+                GradientStop {
+                    position: 0.0,
+                    color: Some(Color::Rgb("#FFFFFF".to_string())),
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: Some(Color::theme(4, 0.0)),
+                },
+            ],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    model.set_cell_style(0, 1, 2, &style).unwrap();
+    let mut style = model.get_style_for_cell(0, 1, 3).unwrap();
+    style.fill = Fill {
+        gradient: Some(GradientFill {
+            gradient_type: GradientType::Path,
+            left: 0.2,
+            right: 0.8,
+            top: 0.2,
+            bottom: 0.8,
+            stops: vec![
+                GradientStop {
+                    position: 0.0,
+                    color: Some(Color::Rgb("#00FF00".to_string())),
+                },
+                GradientStop {
+                    position: 0.5,
+                    color: Some(Color::Rgb("#FFFFFF".to_string())),
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: Some(Color::Rgb("#0000FF".to_string())),
+                },
+            ],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    model.set_cell_style(0, 1, 3, &style).unwrap();
+
+    let temp_file_name = "temp_file_test_pattern_and_gradient_fills.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let imported = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    for column in 1..=3 {
+        assert_eq!(
+            imported.get_style_for_cell(0, 1, column).unwrap().fill,
+            model.get_style_for_cell(0, 1, column).unwrap().fill
+        );
+    }
+}
+
+#[test]
+fn theme_colors() {
+    let mut model = new_empty_model();
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    style.font.color = Some(Color::theme(4, 0.0));
+    style.fill.pattern_type = PatternType::Solid;
+    style.fill.fg_color = Some(Color::theme(5, 0.6));
+    model.set_cell_style(0, 1, 1, &style).unwrap();
+    let mut theme = Theme::default();
+    theme.colors[4] = "#112233".to_string();
+    theme.minor_font = "Arial".to_string();
+    model.set_theme(theme.clone()).unwrap();
+
+    let temp_file_name = "temp_file_test_theme_colors.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    assert_eq!(model.get_theme().colors, theme.colors);
+    assert_eq!(model.get_theme().minor_font, "Arial");
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::theme(4, 0.0)));
+    assert_eq!(style.fill.fg_color, Some(Color::theme(5, 0.6)));
+    style.resolve_colors(model.get_theme());
+    assert_eq!(style.font.color, Some(Color::Rgb("#112233".to_string())));
+}
+
+#[test]
+fn invalid_tints_are_clamped() {
+    let mut model = new_empty_model();
+    let mut style = model.get_style_for_cell(0, 1, 1).unwrap();
+    style.font.color = Some(Color::Theme {
+        theme: 4,
+        tint: f64::NAN,
+    });
+    style.fill.pattern_type = PatternType::Solid;
+    style.fill.fg_color = Some(Color::Theme {
+        theme: 5,
+        tint: 3.0,
+    });
+    model.set_cell_style(0, 1, 1, &style).unwrap();
+
+    let temp_file_name = "temp_file_test_invalid_tints_are_clamped.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    let style = model.get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(style.font.color, Some(Color::theme(4, 0.0)));
+    assert_eq!(style.fill.fg_color, Some(Color::theme(5, 1.0)));
+}
+
+#[test]
+fn theme_of_imported_file() {
+    let mut model = load_from_xlsx("tests/example.xlsx", "en", "UTC", "en").unwrap();
+    assert_eq!(model.get_theme().name, "Office 2013 - 2022 Theme");
+    assert_eq!(model.get_theme().colors, Theme::default().colors);
+    let mut theme = model.get_theme().clone();
+    theme.colors[1] = "#202020".to_string();
+    model.set_theme(theme.clone()).unwrap();
+
+    let temp_file_name = "temp_file_test_theme_of_imported_file.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    assert_eq!(model.get_theme().name, "Office 2013 - 2022 Theme");
+    assert_eq!(model.get_theme().colors, theme.colors);
+    // The rest of the original theme part is kept
+    let xml = model.get_theme().xml.as_ref().unwrap();
+    assert!(xml.contains("thm15:themeFamily"));
+    assert!(xml.contains("<a:dk1><a:srgbClr val=\"202020\"/></a:dk1>"));
+}
+
+#[test]
+fn rich_text() {
+    let mut model = new_empty_model();
+    let runs = vec![
+        TextRun {
+            text: "Bold".to_string(),
+            font: Some(RunFont {
+                b: Some(true),
+                underline: Some(FontUnderline::DoubleAccounting),
+                sz: Some(14),
+                color: Some("#FF0000".to_string()),
+                name: Some("Arial".to_string()),
+                ..Default::default()
+            }),
+        },
+        TextRun {
+            text: " & plain ".to_string(),
+            font: None,
+        },
+        TextRun {
+            text: "x".to_string(),
+            font: Some(RunFont {
+                underline: Some(FontUnderline::None),
+                vert_align: Some(FontVerticalAlign::Subscript),
+                ..Default::default()
+            }),
+        },
+    ];
+    model.set_cell_rich_text(0, 1, 1, &runs).unwrap();
+    model
+        .set_user_input(0, 2, 1, "Bold & plain x".to_string())
+        .unwrap();
+
+    let temp_file_name = "temp_file_test_rich_text.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    assert_eq!(model.get_cell_rich_text(0, 1, 1), Ok(Some(runs)));
+    assert_eq!(model.get_cell_rich_text(0, 2, 1), Ok(None));
+    assert_eq!(
+        model.get_formatted_cell_value(0, 1, 1).unwrap(),
+        "Bold & plain x"
+    );
+}
+
+#[test]
+fn named_styles_round_trip() {
+    let model = new_empty_model();
+    let mut model = UserModel::from_model(model);
+    let mut style = Style::default();
+    style.font.i = true;
+    style.alignment = Some(Alignment {
+        horizontal: HorizontalAlignment::Center,
+        ..Default::default()
+    });
+    model.create_named_style("Fancy", &style).unwrap();
+    let range = Area {
+        sheet: 0,
+        row: 1,
+        column: 1,
+        width: 1,
+        height: 1,
+    };
+    model.apply_named_style(&range, "Fancy").unwrap();
+    let range = Area { row: 2, ..range };
+    model.apply_named_style(&range, "Total").unwrap();
+
+    let temp_file_name = "temp_file_test_named_styles_round_trip.xlsx";
+    save_to_xlsx(model.get_model(), temp_file_name).unwrap();
+    let model = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+
+    let named_styles = model.get_named_styles();
+    let fancy = named_styles.iter().find(|s| s.name == "Fancy").unwrap();
+    assert_eq!(fancy.builtin_id, None);
+    assert_eq!(fancy.style.alignment, style.alignment);
+    let total = named_styles.iter().find(|s| s.name == "Total").unwrap();
+    assert_eq!(total.builtin_id, Some(25));
+
+    let a1 = model.get_style_for_cell(0, 1, 1).unwrap();
+    assert_eq!(a1.named_style, Some("Fancy".to_string()));
+    assert!(a1.font.i);
+    let a2 = model.get_style_for_cell(0, 2, 1).unwrap();
+    assert_eq!(a2.named_style, Some("Total".to_string()));
+}
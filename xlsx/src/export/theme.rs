@@ -0,0 +1,149 @@
+use ironcalc_base::types::Theme;
+use roxmltree::Node;
+
+use super::{escape::escape_xml, xml_constants::XML_DECLARATION};
+
+// The colours of the scheme in the order used by the `theme` attribute of colours
+const COLOR_NAMES: [&str; 12] = [
+    "lt1", "dk1", "lt2", "dk2", "accent1", "accent2", "accent3", "accent4", "accent5", "accent6",
+    "hlink", "folHlink",
+];
+
+// The order of the colours in the clrScheme element
+const SCHEME_ORDER: [usize; 12] = [1, 0, 3, 2, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const FORMAT_SCHEME: &str = "<a:fmtScheme name=\"Office\">\
+<a:fillStyleLst>\
+<a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:tint val=\"50000\"/></a:schemeClr></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:shade val=\"80000\"/></a:schemeClr></a:solidFill>\
+</a:fillStyleLst>\
+<a:lnStyleLst>\
+<a:ln w=\"6350\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill></a:ln>\
+<a:ln w=\"12700\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill></a:ln>\
+<a:ln w=\"19050\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill></a:ln>\
+</a:lnStyleLst>\
+<a:effectStyleLst>\
+<a:effectStyle><a:effectLst/></a:effectStyle>\
+<a:effectStyle><a:effectLst/></a:effectStyle>\
+<a:effectStyle><a:effectLst/></a:effectStyle>\
+</a:effectStyleLst>\
+<a:bgFillStyleLst>\
+<a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:tint val=\"95000\"/></a:schemeClr></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:shade val=\"90000\"/></a:schemeClr></a:solidFill>\
+</a:bgFillStyleLst>\
+</a:fmtScheme>";
+
+fn get_rgb(color: &str) -> &str {
+    color.trim_start_matches('#')
+}
+
+// A theme part with only the colours and the fonts of the theme
+fn get_new_theme_xml(theme: &Theme) -> String {
+    let colors: String = SCHEME_ORDER
+        .iter()
+        .map(|&index| {
+            let name = COLOR_NAMES[index];
+            let rgb = get_rgb(&theme.colors[index]);
+            format!("<a:{name}><a:srgbClr val=\"{rgb}\"/></a:{name}>")
+        })
+        .collect();
+    let name = escape_xml(&theme.name);
+    let major_font = escape_xml(&theme.major_font);
+    let minor_font = escape_xml(&theme.minor_font);
+    format!(
+        "{XML_DECLARATION}\n\
+        <a:theme xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" name=\"{name}\">\
+        <a:themeElements>\
+        <a:clrScheme name=\"{name}\">{colors}</a:clrScheme>\
+        <a:fontScheme name=\"{name}\">\
+        <a:majorFont><a:latin typeface=\"{major_font}\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/></a:majorFont>\
+        <a:minorFont><a:latin typeface=\"{minor_font}\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/></a:minorFont>\
+        </a:fontScheme>\
+        {FORMAT_SCHEME}\
+        </a:themeElements>\
+        <a:objectDefaults/>\
+        <a:extraClrSchemeLst/>\
+        </a:theme>"
+    )
+}
+
+// Returns the `a:` in `<a:dk1>`
+fn get_prefix<'a>(xml: &'a str, node: &Node) -> &'a str {
+    let text = &xml[node.range()];
+    match text.find(':') {
+        Some(index) if index < text.find(['>', ' ', '/']).unwrap_or(0) => &text[1..=index],
+        _ => "",
+    }
+}
+
+fn get_scheme_color(node: &Node) -> Option<String> {
+    node.children()
+        .find_map(|child| match child.tag_name().name() {
+            "srgbClr" => child.attribute("val"),
+            "sysClr" => child.attribute("lastClr"),
+            _ => None,
+        })
+        .map(|value| value.to_uppercase())
+}
+
+// Replaces the colours and fonts that changed in the original theme part
+fn update_theme_xml(theme: &Theme, xml: &str) -> Option<String> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let mut replacements = Vec::new();
+    for node in doc.root_element().descendants() {
+        match node.tag_name().name() {
+            "clrScheme" => {
+                for child in node.children().filter(|n| n.is_element()) {
+                    let name = child.tag_name().name();
+                    let Some(index) = COLOR_NAMES.iter().position(|&n| n == name) else {
+                        continue;
+                    };
+                    let rgb = get_rgb(&theme.colors[index]);
+                    if get_scheme_color(&child).as_deref() != Some(rgb) {
+                        let prefix = get_prefix(xml, &child);
+                        replacements.push((
+                            child.range(),
+                            format!(
+                                "<{prefix}{name}><{prefix}srgbClr val=\"{rgb}\"/></{prefix}{name}>"
+                            ),
+                        ));
+                    }
+                }
+            }
+            "majorFont" | "minorFont" => {
+                let typeface = if node.tag_name().name() == "majorFont" {
+                    &theme.major_font
+                } else {
+                    &theme.minor_font
+                };
+                let Some(latin) = node.children().find(|n| n.has_tag_name("latin")) else {
+                    continue;
+                };
+                if latin.attribute("typeface") != Some(typeface.as_str()) {
+                    let prefix = get_prefix(xml, &latin);
+                    replacements.push((
+                        latin.range(),
+                        format!("<{prefix}latin typeface=\"{}\"/>", escape_xml(typeface)),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut result = xml.to_string();
+    for (range, text) in replacements.into_iter().rev() {
+        result.replace_range(range, &text);
+    }
+    Some(result)
+}
+
+/// Returns the theme part (`xl/theme/theme1.xml`). If the workbook was imported we keep the
+/// original part and only update its colour scheme and fonts.
+pub(crate) fn get_theme_xml(theme: &Theme) -> String {
+    match &theme.xml {
+        Some(xml) => update_theme_xml(theme, xml).unwrap_or_else(|| get_new_theme_xml(theme)),
+        None => get_new_theme_xml(theme),
+    }
+}
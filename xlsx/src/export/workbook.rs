@@ -30,9 +30,11 @@
 
 use std::collections::HashMap;
 
-use ironcalc_base::types::{SheetState, Workbook};
+use ironcalc_base::types::{DateSystem, SheetState, Workbook};
 
 use super::escape::escape_xml;
+use super::opaque_parts::{get_chart_sheets_xml, get_workbook_elements_xml};
+use super::protection::get_workbook_protection_xml;
 use super::xml_constants::XML_DECLARATION;
 
 pub(crate) fn get_workbook_xml(workbook: &Workbook, selected_sheet: u32) -> String {
@@ -77,19 +79,31 @@ pub(crate) fn get_workbook_xml(workbook: &Workbook, selected_sheet: u32) -> Stri
         ))
     }
 
-    let sheets = sheets_str.join("");
+    let sheets = sheets_str.join("") + &get_chart_sheets_xml(workbook);
     let defined_names = defined_names_str.join("");
+    // <workbookPr date1904="1"/>
+    let workbook_pr = match workbook.settings.date_system {
+        DateSystem::Excel1900 => "",
+        DateSystem::Excel1904 => "<workbookPr date1904=\"1\"/>",
+    };
+    let workbook_protection = get_workbook_protection_xml(&workbook.protection);
+    let external_references = get_workbook_elements_xml(workbook, "externalReferences");
+    let pivot_caches = get_workbook_elements_xml(workbook, "pivotCaches");
     format!("{XML_DECLARATION}\n\
     <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+    {workbook_pr}\
+    {workbook_protection}\
     <bookViews>
     <workbookView activeTab=\"{selected_sheet}\"/>\
     </bookViews>
       <sheets>\
         {sheets}\
       </sheets>\
+      {external_references}\
       <definedNames>\
         {defined_names}\
       </definedNames>\
       <calcPr/>\
+      {pivot_caches}\
     </workbook>")
 }
@@ -1,5 +1,6 @@
 use ironcalc_base::types::Workbook;
 
+use super::opaque_parts::get_workbook_relationships_xml;
 use super::xml_constants::{XML_DECLARATION, XML_WORKSHEET};
 
 pub(crate) fn get_workbook_xml_rels(workbook: &Workbook) -> String {
@@ -18,6 +19,11 @@ pub(crate) fn get_workbook_xml_rels(workbook: &Workbook) -> String {
     relationships_str.push(
         format!("<Relationship Id=\"rId{id}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/sharedStrings\" Target=\"sharedStrings.xml\"/>")
     );
+    id += 1;
+    relationships_str.push(
+        format!("<Relationship Id=\"rId{id}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme\" Target=\"theme/theme1.xml\"/>")
+    );
+    relationships_str.push(get_workbook_relationships_xml(workbook));
     format!(
         "{XML_DECLARATION}\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{}</Relationships>",
         relationships_str.join("")
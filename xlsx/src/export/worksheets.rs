@@ -29,7 +29,14 @@ use ironcalc_base::{
     types::{Cell, Worksheet},
 };
 
-use super::{escape::escape_xml, xml_constants::XML_DECLARATION};
+use super::{
+    auto_filter::{get_auto_filter_xml, FilterDxf},
+    escape::escape_xml,
+    opaque_parts::get_sheet_elements_xml,
+    page_setup::get_page_setup_xml,
+    protection::get_sheet_protection_xml,
+    xml_constants::XML_DECLARATION,
+};
 
 fn get_cell_style_attribute(s: i32) -> String {
     if s == 0 {
@@ -54,11 +61,26 @@ fn get_formula_attribute(
     escape_xml(formula).to_string()
 }
 
+fn get_outline_attributes(hidden: bool, outline_level: u8, collapsed: bool) -> String {
+    let mut attributes = String::new();
+    if hidden {
+        attributes.push_str(r#" hidden="1""#);
+    }
+    if outline_level > 0 {
+        attributes.push_str(&format!(r#" outlineLevel="{outline_level}""#));
+    }
+    if collapsed {
+        attributes.push_str(r#" collapsed="1""#);
+    }
+    attributes
+}
+
 pub(crate) fn get_worksheet_xml(
     worksheet: &Worksheet,
     parsed_formulas: &[Node],
     dimension: &str,
     is_sheet_selected: bool,
+    dxfs: &[FilterDxf],
 ) -> String {
     let mut sheet_data_str: Vec<String> = vec![];
     let mut cols_str: Vec<String> = vec![];
@@ -74,8 +96,9 @@ pub(crate) fn get_worksheet_xml(
             Some(s) => format!(" style=\"{s}\""),
             None => "".to_string(),
         };
+        let outline = get_outline_attributes(col.hidden, col.outline_level, col.collapsed);
         cols_str.push(format!(
-            "<col min=\"{min}\" max=\"{max}\" width=\"{width}\" customWidth=\"{custom_width}\"{column_style}/>"
+            "<col min=\"{min}\" max=\"{max}\" width=\"{width}\" customWidth=\"{custom_width}\"{column_style}{outline}/>"
         ));
     }
 
@@ -93,7 +116,16 @@ pub(crate) fn get_worksheet_xml(
         row_style_dict.insert(row.r, row.clone());
     }
 
-    for (row_index, row_data) in worksheet.sheet_data.iter().sorted_by_key(|x| x.0) {
+    // Rows with properties but without cells are also exported
+    let empty_row = HashMap::new();
+    let row_indices = worksheet
+        .sheet_data
+        .keys()
+        .chain(row_style_dict.keys())
+        .unique()
+        .sorted();
+    for row_index in row_indices {
+        let row_data = worksheet.sheet_data.get(row_index).unwrap_or(&empty_row);
         let mut row_data_str: Vec<String> = vec![];
         for (column_index, cell) in row_data.iter().sorted_by_key(|x| x.0) {
             let column_name = number_to_column(*column_index).unwrap();
@@ -224,18 +256,18 @@ pub(crate) fn get_worksheet_xml(
         }
         let row_style_str = match row_style_dict.get(row_index) {
             Some(row_style) => {
-                let hidden_str = if row_style.hidden {
-                    r#" hidden="1""#
-                } else {
-                    ""
-                };
+                let outline = get_outline_attributes(
+                    row_style.hidden,
+                    row_style.outline_level,
+                    row_style.collapsed,
+                );
                 format!(
                     r#" s="{}" ht="{}" customHeight="{}" customFormat="{}"{}"#,
                     row_style.s,
                     row_style.height,
                     i32::from(row_style.custom_height),
                     i32::from(row_style.custom_format),
-                    hidden_str,
+                    outline,
                 )
             }
             None => "".to_string(),
@@ -247,6 +279,14 @@ pub(crate) fn get_worksheet_xml(
     }
     let sheet_data = sheet_data_str.join("");
 
+    let sheet_protection = get_sheet_protection_xml(&worksheet.protection);
+    let opaque_elements = get_sheet_elements_xml(worksheet);
+
+    let auto_filter = match &worksheet.auto_filter {
+        Some(auto_filter) => get_auto_filter_xml(auto_filter, dxfs),
+        None => "".to_string(),
+    };
+
     for merge_cell_ref in &worksheet.merge_cells {
         merged_cells_str.push(format!("<mergeCell ref=\"{merge_cell_ref}\"/>"))
     }
@@ -301,6 +341,47 @@ pub(crate) fn get_worksheet_xml(
         "".to_string()
     };
 
+    // <sheetPr><outlinePr summaryBelow="0"/><pageSetUpPr fitToPage="1"/></sheetPr>
+    let mut sheet_pr = String::new();
+    if !worksheet.summary_below || !worksheet.summary_right {
+        sheet_pr.push_str(&format!(
+            "<outlinePr summaryBelow=\"{}\" summaryRight=\"{}\"/>",
+            i32::from(worksheet.summary_below),
+            i32::from(worksheet.summary_right)
+        ));
+    }
+    if worksheet.page_setup.fit_to_page {
+        sheet_pr.push_str("<pageSetUpPr fitToPage=\"1\"/>");
+    }
+    let sheet_pr = if sheet_pr.is_empty() {
+        sheet_pr
+    } else {
+        format!("<sheetPr>{sheet_pr}</sheetPr>")
+    };
+
+    let page_setup = get_page_setup_xml(&worksheet.page_setup);
+
+    // Excel needs the maximum outline levels to display the outline
+    let outline_level_row = worksheet
+        .rows
+        .iter()
+        .map(|r| r.outline_level)
+        .max()
+        .unwrap_or(0);
+    let outline_level_col = worksheet
+        .cols
+        .iter()
+        .map(|c| c.outline_level)
+        .max()
+        .unwrap_or(0);
+    let sheet_format_pr = if outline_level_row > 0 || outline_level_col > 0 {
+        format!(
+            "<sheetFormatPr defaultRowHeight=\"14.5\" outlineLevelRow=\"{outline_level_row}\" outlineLevelCol=\"{outline_level_col}\"/>"
+        )
+    } else {
+        "".to_string()
+    };
+
     let frozen_rows = worksheet.frozen_rows;
     let frozen_columns = worksheet.frozen_columns;
 
@@ -344,17 +425,23 @@ pub(crate) fn get_worksheet_xml(
     format!(
         "{XML_DECLARATION}\
 <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+  {sheet_pr}\
   <dimension ref=\"{dimension}\"/>\
   <sheetViews>\
     <sheetView workbookViewId=\"0\"{show_grid_lines}{tab_selected}>\
         {pane}\
     </sheetView>\
   </sheetViews>\
+  {sheet_format_pr}\
   {cols}\
   <sheetData>\
   {sheet_data}\
   </sheetData>\
+  {sheet_protection}\
+  {auto_filter}\
   {merge_cells_section}\
+  {page_setup}\
+  {opaque_elements}\
 </worksheet>"
     )
 }
@@ -0,0 +1,149 @@
+use ironcalc_base::types::{
+    AutoFilter, ColumnFilter, CustomFilter, DynamicFilterType, FilterColumn, FilterOperator,
+};
+use roxmltree::Node;
+
+use crate::error::XlsxError;
+
+use super::{
+    styles::DxfColors,
+    util::{get_attribute, get_bool, get_bool_false},
+};
+
+// <autoFilter ref="A1:D6">
+//     <filterColumn colId="0">
+//         <filters blank="1"><filter val="Apples"/></filters>
+//     </filterColumn>
+//     <filterColumn colId="1">
+//         <customFilters and="1">
+//             <customFilter operator="greaterThan" val="20"/>
+//         </customFilters>
+//     </filterColumn>
+//     <filterColumn colId="2"><top10 val="10"/></filterColumn>
+//     <filterColumn colId="3"><dynamicFilter type="today"/></filterColumn>
+//     <filterColumn colId="4"><colorFilter dxfId="0"/></filterColumn>
+// </autoFilter>
+
+fn get_operator(operator: &str) -> Result<FilterOperator, XlsxError> {
+    match operator {
+        "equal" => Ok(FilterOperator::Equal),
+        "lessThan" => Ok(FilterOperator::LessThan),
+        "lessThanOrEqual" => Ok(FilterOperator::LessThanOrEqual),
+        "notEqual" => Ok(FilterOperator::NotEqual),
+        "greaterThanOrEqual" => Ok(FilterOperator::GreaterThanOrEqual),
+        "greaterThan" => Ok(FilterOperator::GreaterThan),
+        _ => Err(XlsxError::Xml(format!(
+            "Invalid filter operator: {operator}"
+        ))),
+    }
+}
+
+fn get_dynamic_filter_type(filter_type: &str) -> Option<DynamicFilterType> {
+    let filter_type = match filter_type {
+        "aboveAverage" => DynamicFilterType::AboveAverage,
+        "belowAverage" => DynamicFilterType::BelowAverage,
+        "tomorrow" => DynamicFilterType::Tomorrow,
+        "today" => DynamicFilterType::Today,
+        "yesterday" => DynamicFilterType::Yesterday,
+        "nextWeek" => DynamicFilterType::NextWeek,
+        "thisWeek" => DynamicFilterType::ThisWeek,
+        "lastWeek" => DynamicFilterType::LastWeek,
+        "nextMonth" => DynamicFilterType::NextMonth,
+        "thisMonth" => DynamicFilterType::ThisMonth,
+        "lastMonth" => DynamicFilterType::LastMonth,
+        "nextQuarter" => DynamicFilterType::NextQuarter,
+        "thisQuarter" => DynamicFilterType::ThisQuarter,
+        "lastQuarter" => DynamicFilterType::LastQuarter,
+        "nextYear" => DynamicFilterType::NextYear,
+        "thisYear" => DynamicFilterType::ThisYear,
+        "lastYear" => DynamicFilterType::LastYear,
+        "yearToDate" => DynamicFilterType::YearToDate,
+        _ => {
+            // Q1-Q4 and M1-M12
+            if let Some(quarter) = filter_type.strip_prefix('Q') {
+                DynamicFilterType::Quarter(quarter.parse::<u32>().ok()?)
+            } else if let Some(month) = filter_type.strip_prefix('M') {
+                DynamicFilterType::Month(month.parse::<u32>().ok()?)
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(filter_type)
+}
+
+fn load_column_filter(node: Node, dxfs: &[DxfColors]) -> Result<Option<ColumnFilter>, XlsxError> {
+    let filter = match node.tag_name().name() {
+        "filters" => {
+            let values = node
+                .children()
+                .filter(|n| n.has_tag_name("filter"))
+                .map(|n| get_attribute(&n, "val").map(|v| v.to_string()))
+                .collect::<Result<Vec<String>, XlsxError>>()?;
+            ColumnFilter::Values {
+                values,
+                blank: get_bool_false(node, "blank"),
+            }
+        }
+        "customFilters" => {
+            let mut filters = Vec::new();
+            for custom_filter in node.children().filter(|n| n.has_tag_name("customFilter")) {
+                let operator =
+                    get_operator(custom_filter.attribute("operator").unwrap_or("equal"))?;
+                let value = custom_filter.attribute("val").unwrap_or("").to_string();
+                filters.push(CustomFilter { operator, value });
+            }
+            ColumnFilter::Custom {
+                and: get_bool_false(node, "and"),
+                filters,
+            }
+        }
+        "top10" => ColumnFilter::Top10 {
+            top: get_bool(node, "top"),
+            percent: get_bool_false(node, "percent"),
+            value: get_attribute(&node, "val")?.parse::<f64>()?,
+        },
+        "dynamicFilter" => match get_dynamic_filter_type(get_attribute(&node, "type")?) {
+            Some(filter_type) => ColumnFilter::Dynamic { filter_type },
+            None => return Ok(None),
+        },
+        "colorFilter" => {
+            let cell_color = get_bool(node, "cellColor");
+            let dxf = node
+                .attribute("dxfId")
+                .and_then(|id| id.parse::<usize>().ok())
+                .and_then(|id| dxfs.get(id));
+            let color = match dxf {
+                Some(dxf) if cell_color => dxf.fill.clone(),
+                Some(dxf) => dxf.font.clone(),
+                None => None,
+            };
+            match color {
+                Some(color) => ColumnFilter::Color { cell_color, color },
+                None => return Ok(None),
+            }
+        }
+        // Not supported: iconFilter
+        _ => return Ok(None),
+    };
+    Ok(Some(filter))
+}
+
+/// Reads an `<autoFilter>` element of a worksheet or a table
+pub(super) fn load_auto_filter(node: Node, dxfs: &[DxfColors]) -> Result<AutoFilter, XlsxError> {
+    let reference = get_attribute(&node, "ref")?.to_string();
+    let mut filter_columns = Vec::new();
+    for filter_column in node.children().filter(|n| n.has_tag_name("filterColumn")) {
+        let col_id = get_attribute(&filter_column, "colId")?.parse::<u32>()?;
+        for child in filter_column.children().filter(|n| n.is_element()) {
+            if let Some(filter) = load_column_filter(child, dxfs)? {
+                filter_columns.push(FilterColumn { col_id, filter });
+                break;
+            }
+        }
+    }
+    Ok(AutoFilter {
+        reference,
+        filter_columns,
+    })
+}
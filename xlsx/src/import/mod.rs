@@ -1,8 +1,12 @@
-mod colors;
+mod auto_filter;
 mod metadata;
+mod opaque_parts;
+mod page_setup;
+mod protection;
 mod shared_strings;
 mod styles;
 mod tables;
+mod theme;
 mod util;
 mod workbook;
 mod worksheets;
@@ -25,7 +29,9 @@ use crate::error::XlsxError;
 use shared_strings::read_shared_strings;
 
 use metadata::load_metadata;
-use styles::load_styles;
+use opaque_parts::load_opaque_parts;
+use styles::{load_dxf_colors, load_styles};
+use theme::load_theme;
 use util::get_attribute;
 use workbook::load_workbook;
 use worksheets::{load_sheets, Relationship};
@@ -66,14 +72,18 @@ fn load_xlsx_from_reader<R: Read + std::io::Seek>(
     let workbook = load_workbook(&mut archive)?;
     let rels = load_relationships(&mut archive)?;
     let mut tables = HashMap::new();
-    let (worksheets, selected_sheet) = load_sheets(
+    let theme = load_theme(&mut archive, &rels)?;
+    let styles = load_styles(&mut archive)?;
+    let dxfs = load_dxf_colors(&mut archive)?;
+    let (mut worksheets, selected_sheet) = load_sheets(
         &mut archive,
         &rels,
         &workbook,
         &mut tables,
         &mut shared_strings,
+        &dxfs,
     )?;
-    let styles = load_styles(&mut archive)?;
+    let opaque_parts = load_opaque_parts(&mut archive, &rels, &workbook, &mut worksheets)?;
     let metadata = match load_metadata(&mut archive) {
         Ok(metadata) => metadata,
         Err(_) => {
@@ -98,7 +108,8 @@ fn load_xlsx_from_reader<R: Read + std::io::Seek>(
         },
     );
     Ok(Workbook {
-        shared_strings,
+        shared_strings: shared_strings.strings,
+        rich_text: shared_strings.rich_text,
         defined_names: workbook.defined_names,
         worksheets,
         styles,
@@ -106,10 +117,15 @@ fn load_xlsx_from_reader<R: Read + std::io::Seek>(
         settings: WorkbookSettings {
             tz: tz.to_string(),
             locale: locale.to_string(),
+            date_system: workbook.date_system,
+            custom_lists: vec![],
         },
         metadata,
         tables,
         views,
+        protection: workbook.protection,
+        opaque_parts,
+        theme,
     })
 }
 
@@ -0,0 +1,405 @@
+//! Parts of the xlsx package that IronCalc does not understand (charts, images, pivot tables,
+//! VBA projects, custom XML,...) are kept as is, so that saving the workbook does not lose them.
+//!
+//! We walk the relationship graph starting from the package, the workbook and the worksheets.
+//! Relationships to parts we load into the model are skipped, everything reachable from the
+//! other relationships is kept together with the relationships files of those parts.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::Read,
+};
+
+use ironcalc_base::types::{
+    OpaqueElement, OpaquePart, OpaqueParts, OpaqueRelationship, OpaqueSheetParts, Worksheet,
+};
+use roxmltree::Node;
+
+use crate::error::XlsxError;
+
+use super::{
+    util::get_attribute,
+    worksheets::{Relationship, WorkbookXML},
+};
+
+const MAIN_NAMESPACE: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const RELATIONSHIPS_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+// The last segment of the relationship types we load (or write) ourselves
+const PACKAGE_TYPES: [&str; 3] = ["officeDocument", "core-properties", "extended-properties"];
+const WORKBOOK_TYPES: [&str; 6] = [
+    "worksheet",
+    "styles",
+    "sharedStrings",
+    "calcChain",
+    "sheetMetadata",
+    "theme",
+];
+const SHEET_TYPES: [&str; 5] = [
+    "table",
+    "comments",
+    "vmlDrawing",
+    "hyperlink",
+    "printerSettings",
+];
+
+// Elements of the workbook and the worksheets that point to opaque relationships.
+// Chart sheets are kept as `sheet` elements of the `sheets` list.
+const WORKBOOK_ELEMENTS: [&str; 2] = ["externalReferences", "pivotCaches"];
+const SHEET_ELEMENTS: [&str; 6] = [
+    "drawing",
+    "legacyDrawingHF",
+    "drawingHF",
+    "picture",
+    "oleObjects",
+    "controls",
+];
+
+struct ContentTypes {
+    defaults: HashMap<String, String>,
+    overrides: HashMap<String, String>,
+}
+
+impl ContentTypes {
+    fn get(&self, path: &str) -> Option<String> {
+        if let Some(content_type) = self.overrides.get(&path.to_lowercase()) {
+            return Some(content_type.clone());
+        }
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        self.defaults.get(&extension).cloned()
+    }
+}
+
+fn load_content_types<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+) -> Result<ContentTypes, XlsxError> {
+    let mut file = archive.by_name("[Content_Types].xml")?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    let doc = roxmltree::Document::parse(&text)?;
+    let mut defaults = HashMap::new();
+    let mut overrides = HashMap::new();
+    for node in doc.root_element().children() {
+        if node.has_tag_name("Default") {
+            defaults.insert(
+                get_attribute(&node, "Extension")?.to_lowercase(),
+                get_attribute(&node, "ContentType")?.to_string(),
+            );
+        } else if node.has_tag_name("Override") {
+            let part_name = get_attribute(&node, "PartName")?;
+            overrides.insert(
+                part_name.trim_start_matches('/').to_lowercase(),
+                get_attribute(&node, "ContentType")?.to_string(),
+            );
+        }
+    }
+    Ok(ContentTypes {
+        defaults,
+        overrides,
+    })
+}
+
+fn read_file<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+    path: &str,
+) -> Result<Option<Vec<u8>>, XlsxError> {
+    let mut file = match archive.by_name(path) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(Some(data))
+}
+
+// xl/drawings/drawing1.xml -> xl/drawings/_rels/drawing1.xml.rels
+fn get_rels_path(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((folder, name)) => format!("{folder}/_rels/{name}.rels"),
+        None => format!("_rels/{path}.rels"),
+    }
+}
+
+fn get_folder(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(folder, _)| folder)
+}
+
+// Resolves a relationship target relative to the folder of the source part.
+// ("xl/worksheets", "../drawings/drawing1.xml") -> "xl/drawings/drawing1.xml"
+pub(super) fn resolve_target(folder: &str, target: &str) -> String {
+    let (mut segments, target) = match target.strip_prefix('/') {
+        Some(target) => (vec![], target),
+        None => (
+            folder.split('/').filter(|s| !s.is_empty()).collect(),
+            target,
+        ),
+    };
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn is_external(relationship: &OpaqueRelationship) -> bool {
+    relationship.target_mode.as_deref() == Some("External")
+}
+
+fn is_known_type(rel_type: &str, known_types: &[&str]) -> bool {
+    rel_type
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| known_types.contains(&name))
+}
+
+fn parse_relationships(data: &[u8]) -> Result<Vec<OpaqueRelationship>, XlsxError> {
+    let text = String::from_utf8_lossy(data);
+    let doc = roxmltree::Document::parse(&text)?;
+    let mut relationships = Vec::new();
+    for node in doc.root_element().children() {
+        if node.has_tag_name("Relationship") {
+            relationships.push(OpaqueRelationship {
+                id: get_attribute(&node, "Id")?.to_string(),
+                rel_type: get_attribute(&node, "Type")?.to_string(),
+                target: get_attribute(&node, "Target")?.to_string(),
+                target_mode: node.attribute("TargetMode").map(|s| s.to_string()),
+            });
+        }
+    }
+    Ok(relationships)
+}
+
+// Returns the relationships of the part with the unknown types and the ones in `kept_ids`
+fn load_opaque_relationships<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+    path: &str,
+    known_types: &[&str],
+    kept_ids: &[String],
+) -> Result<Vec<OpaqueRelationship>, XlsxError> {
+    let data = match read_file(archive, &get_rels_path(path))? {
+        Some(data) => data,
+        None => return Ok(vec![]),
+    };
+    Ok(parse_relationships(&data)?
+        .into_iter()
+        .filter(|relationship| {
+            !is_known_type(&relationship.rel_type, known_types)
+                || kept_ids.contains(&relationship.id)
+        })
+        .collect())
+}
+
+// The pictures of the headers and footers are vmlDrawing relationships, like the comments.
+// We write the comments ourselves but those pictures are kept.
+fn get_header_footer_drawing_ids(root: Node) -> Vec<String> {
+    root.children()
+        .filter(|node| node.has_tag_name((MAIN_NAMESPACE, "legacyDrawingHF")))
+        .filter_map(|node| node.attribute((RELATIONSHIPS_NAMESPACE, "id")))
+        .map(|id| id.to_string())
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Serializes the element using the prefix `r` for the relationships namespace.
+// Returns None if the element uses any other namespace.
+fn write_element(node: Node, ids: &mut Vec<String>) -> Option<String> {
+    if node.tag_name().namespace() != Some(MAIN_NAMESPACE) {
+        return None;
+    }
+    let name = node.tag_name().name();
+    let mut xml = format!("<{name}");
+    for attribute in node.attributes() {
+        let attribute_name = match attribute.namespace() {
+            None => attribute.name().to_string(),
+            Some(RELATIONSHIPS_NAMESPACE) => {
+                ids.push(attribute.value().to_string());
+                format!("r:{}", attribute.name())
+            }
+            Some(_) => return None,
+        };
+        xml.push_str(&format!(
+            " {attribute_name}=\"{}\"",
+            escape(attribute.value())
+        ));
+    }
+    xml.push('>');
+    for child in node.children() {
+        if child.is_element() {
+            xml.push_str(&write_element(child, ids)?);
+        } else if child.is_text() {
+            xml.push_str(&escape(child.text().unwrap_or("")));
+        }
+    }
+    xml.push_str(&format!("</{name}>"));
+    Some(xml)
+}
+
+// Returns the elements with the given names that only point to the kept relationships
+fn load_opaque_elements(
+    root: Node,
+    names: &[&str],
+    relationships: &[OpaqueRelationship],
+) -> Vec<OpaqueElement> {
+    let mut elements = Vec::new();
+    for node in root.children() {
+        let name = node.tag_name().name();
+        if !node.is_element() || !names.contains(&name) {
+            continue;
+        }
+        let mut ids = Vec::new();
+        if let Some(xml) = write_element(node, &mut ids) {
+            if ids
+                .iter()
+                .all(|id| relationships.iter().any(|r| &r.id == id))
+            {
+                elements.push(OpaqueElement {
+                    name: name.to_string(),
+                    xml,
+                });
+            }
+        }
+    }
+    elements
+}
+
+fn read_text<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+    path: &str,
+) -> Result<String, XlsxError> {
+    let mut file = archive.by_name(path)?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Loads all the parts of the package we do not understand. The relationships and elements of
+/// each worksheet are stored in the worksheet.
+pub(super) fn load_opaque_parts<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+    rels: &HashMap<String, Relationship>,
+    workbook: &WorkbookXML,
+    worksheets: &mut [Worksheet],
+) -> Result<OpaqueParts, XlsxError> {
+    let content_types = load_content_types(archive)?;
+
+    // Parts we write ourselves
+    let mut visited: HashSet<String> = HashSet::from([
+        "xl/workbook.xml".to_string(),
+        "xl/styles.xml".to_string(),
+        "xl/sharedStrings.xml".to_string(),
+        "docProps/app.xml".to_string(),
+        "docProps/core.xml".to_string(),
+    ]);
+    let mut queue = VecDeque::new();
+
+    let package_relationships = load_opaque_relationships(archive, "", &PACKAGE_TYPES, &[])?;
+    for relationship in package_relationships.iter().filter(|r| !is_external(r)) {
+        queue.push_back(resolve_target("", &relationship.target));
+    }
+
+    let workbook_path = "xl/workbook.xml";
+    let workbook_relationships =
+        load_opaque_relationships(archive, workbook_path, &WORKBOOK_TYPES, &[])?;
+    for relationship in workbook_relationships.iter().filter(|r| !is_external(r)) {
+        queue.push_back(resolve_target("xl", &relationship.target));
+    }
+    let text = read_text(archive, workbook_path)?;
+    let doc = roxmltree::Document::parse(&text)?;
+    let mut workbook_elements = load_opaque_elements(
+        doc.root_element(),
+        &WORKBOOK_ELEMENTS,
+        &workbook_relationships,
+    );
+    // The worksheets point to relationships we load, so only the chart sheets are kept
+    if let Some(sheets) = doc
+        .root_element()
+        .children()
+        .find(|node| node.has_tag_name((MAIN_NAMESPACE, "sheets")))
+    {
+        workbook_elements.extend(load_opaque_elements(
+            sheets,
+            &["sheet"],
+            &workbook_relationships,
+        ));
+    }
+
+    // The worksheets are loaded in the same order as in the workbook
+    let sheet_paths = workbook
+        .worksheets
+        .iter()
+        .filter_map(|sheet| rels.get(&sheet.id))
+        .filter(|rel| rel.rel_type.ends_with("worksheet"))
+        .map(|rel| resolve_target("xl", &rel.target));
+    for (worksheet, path) in worksheets.iter_mut().zip(sheet_paths) {
+        visited.insert(path.clone());
+        let text = read_text(archive, &path)?;
+        let doc = roxmltree::Document::parse(&text)?;
+        let header_footer_ids = get_header_footer_drawing_ids(doc.root_element());
+        let relationships =
+            load_opaque_relationships(archive, &path, &SHEET_TYPES, &header_footer_ids)?;
+        if relationships.is_empty() {
+            continue;
+        }
+        let folder = get_folder(&path);
+        for relationship in relationships.iter().filter(|r| !is_external(r)) {
+            queue.push_back(resolve_target(folder, &relationship.target));
+        }
+        let elements = load_opaque_elements(doc.root_element(), &SHEET_ELEMENTS, &relationships);
+        worksheet.opaque_parts = OpaqueSheetParts {
+            relationships,
+            elements,
+        };
+    }
+
+    // Keep all the parts reachable from the opaque relationships
+    let mut parts = Vec::new();
+    while let Some(path) = queue.pop_front() {
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        let data = match read_file(archive, &path)? {
+            Some(data) => data,
+            None => continue,
+        };
+        parts.push(OpaquePart {
+            content_type: content_types.get(&path),
+            path: path.clone(),
+            data,
+        });
+        let rels_path = get_rels_path(&path);
+        if let Some(data) = read_file(archive, &rels_path)? {
+            let folder = get_folder(&path);
+            for relationship in parse_relationships(&data)? {
+                if !is_external(&relationship) {
+                    queue.push_back(resolve_target(folder, &relationship.target));
+                }
+            }
+            parts.push(OpaquePart {
+                content_type: content_types.get(&rels_path),
+                path: rels_path,
+                data,
+            });
+        }
+    }
+
+    Ok(OpaqueParts {
+        workbook_content_type: content_types.get(workbook_path),
+        parts,
+        package_relationships,
+        workbook_relationships,
+        workbook_elements,
+    })
+}
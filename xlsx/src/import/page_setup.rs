@@ -0,0 +1,112 @@
+use ironcalc_base::types::{HeaderFooter, PageMargins, PageOrientation, PageSetup};
+use roxmltree::Node;
+
+use crate::error::XlsxError;
+
+use super::util::{get_attribute, get_bool_false};
+
+// <sheetPr><pageSetUpPr fitToPage="1"/></sheetPr>
+// ...
+// <printOptions gridLines="1" headings="1" horizontalCentered="1"/>
+// <pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/>
+// <pageSetup paperSize="9" scale="80" fitToHeight="0" orientation="landscape" r:id="rId1"/>
+// <headerFooter differentFirst="1">
+//     <oddHeader>&amp;CBoard pack</oddHeader>
+//     <oddFooter>&amp;RPage &amp;P of &amp;N</oddFooter>
+// </headerFooter>
+// <rowBreaks count="1" manualBreakCount="1"><brk id="40" max="16383" man="1"/></rowBreaks>
+// <colBreaks count="1" manualBreakCount="1"><brk id="8" max="1048575" man="1"/></colBreaks>
+
+fn get_child<'a, 'input>(node: Node<'a, 'input>, tag_name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag_name))
+}
+
+fn get_u32(node: Node, attribute: &str, default: u32) -> u32 {
+    node.attribute(attribute)
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(default)
+}
+
+fn get_f64(node: Node, attribute: &str, default: f64) -> f64 {
+    node.attribute(attribute)
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(default)
+}
+
+fn load_breaks(node: Option<Node>) -> Result<Vec<i32>, XlsxError> {
+    let mut breaks = Vec::new();
+    if let Some(node) = node {
+        for brk in node.children().filter(|n| n.has_tag_name("brk")) {
+            // We only keep manual page breaks
+            if get_bool_false(brk, "man") {
+                breaks.push(get_attribute(&brk, "id")?.parse::<i32>()?);
+            }
+        }
+    }
+    Ok(breaks)
+}
+
+pub(super) fn load_page_setup(ws: Node) -> Result<PageSetup, XlsxError> {
+    let mut page_setup = PageSetup::default();
+
+    if let Some(node) = get_child(ws, "sheetPr").and_then(|n| get_child(n, "pageSetUpPr")) {
+        page_setup.fit_to_page = get_bool_false(node, "fitToPage");
+    }
+
+    if let Some(node) = get_child(ws, "printOptions") {
+        page_setup.print_grid_lines = get_bool_false(node, "gridLines");
+        page_setup.print_headings = get_bool_false(node, "headings");
+        page_setup.horizontal_centered = get_bool_false(node, "horizontalCentered");
+        page_setup.vertical_centered = get_bool_false(node, "verticalCentered");
+    }
+
+    if let Some(node) = get_child(ws, "pageMargins") {
+        let default = PageMargins::default();
+        page_setup.margins = PageMargins {
+            left: get_f64(node, "left", default.left),
+            right: get_f64(node, "right", default.right),
+            top: get_f64(node, "top", default.top),
+            bottom: get_f64(node, "bottom", default.bottom),
+            header: get_f64(node, "header", default.header),
+            footer: get_f64(node, "footer", default.footer),
+        };
+    }
+
+    if let Some(node) = get_child(ws, "pageSetup") {
+        page_setup.paper_size = get_u32(node, "paperSize", 1);
+        page_setup.scale = get_u32(node, "scale", 100);
+        page_setup.fit_to_width = get_u32(node, "fitToWidth", 1);
+        page_setup.fit_to_height = get_u32(node, "fitToHeight", 1);
+        page_setup.orientation = match node.attribute("orientation") {
+            Some("landscape") => PageOrientation::Landscape,
+            _ => PageOrientation::Portrait,
+        };
+        if get_bool_false(node, "useFirstPageNumber") {
+            page_setup.first_page_number = Some(get_u32(node, "firstPageNumber", 1));
+        }
+    }
+
+    if let Some(node) = get_child(ws, "headerFooter") {
+        let text = |tag_name: &str| {
+            get_child(node, tag_name)
+                .and_then(|n| n.text())
+                .unwrap_or_default()
+                .to_string()
+        };
+        page_setup.header_footer = HeaderFooter {
+            odd_header: text("oddHeader"),
+            odd_footer: text("oddFooter"),
+            even_header: text("evenHeader"),
+            even_footer: text("evenFooter"),
+            first_header: text("firstHeader"),
+            first_footer: text("firstFooter"),
+            different_odd_even: get_bool_false(node, "differentOddEven"),
+            different_first: get_bool_false(node, "differentFirst"),
+        };
+    }
+
+    page_setup.row_breaks = load_breaks(get_child(ws, "rowBreaks"))?;
+    page_setup.column_breaks = load_breaks(get_child(ws, "colBreaks"))?;
+
+    Ok(page_setup)
+}
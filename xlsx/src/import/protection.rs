@@ -0,0 +1,78 @@
+use ironcalc_base::types::{ProtectionPassword, SheetProtection, WorkbookProtection};
+use roxmltree::Node;
+
+use super::util::{get_bool, get_bool_false};
+
+// <sheetProtection algorithmName="SHA-512" hashValue="..." saltValue="..." spinCount="100000"
+//     sheet="1" objects="1" scenarios="1" formatColumns="0" autoFilter="0"/>
+// <workbookProtection workbookAlgorithmName="SHA-512" workbookHashValue="..."
+//     workbookSaltValue="..." workbookSpinCount="100000" lockStructure="1"/>
+// NB: The attributes of the sheet protection are true if the operation is *not* allowed.
+
+// `prefix` is "" for sheets and "workbook" for workbooks
+fn load_password(node: Node, prefix: &str) -> Option<ProtectionPassword> {
+    let attribute = |name: &str| {
+        if prefix.is_empty() {
+            node.attribute(name)
+        } else {
+            // i.e. workbookHashValue
+            let mut chars = name.chars();
+            let first = chars.next().map(|c| c.to_ascii_uppercase());
+            node.attribute(format!("{prefix}{}{}", first?, chars.as_str()).as_str())
+        }
+    };
+    if let (Some(algorithm_name), Some(hash_value), Some(salt_value)) = (
+        attribute("algorithmName"),
+        attribute("hashValue"),
+        attribute("saltValue"),
+    ) {
+        let spin_count = attribute("spinCount")
+            .and_then(|s| s.parse::<u32>().ok())
+            .unwrap_or(0);
+        return Some(ProtectionPassword::Hashed {
+            algorithm_name: algorithm_name.to_string(),
+            hash_value: hash_value.to_string(),
+            salt_value: salt_value.to_string(),
+            spin_count,
+        });
+    }
+    attribute("password").map(|hash| ProtectionPassword::Legacy(hash.to_string()))
+}
+
+pub(super) fn load_sheet_protection(ws: Node) -> Option<SheetProtection> {
+    let node = ws.children().find(|n| n.has_tag_name("sheetProtection"))?;
+    if !get_bool_false(node, "sheet") {
+        return None;
+    }
+    Some(SheetProtection {
+        password: load_password(node, ""),
+        select_locked_cells: !get_bool_false(node, "selectLockedCells"),
+        select_unlocked_cells: !get_bool_false(node, "selectUnlockedCells"),
+        format_cells: !get_bool(node, "formatCells"),
+        format_columns: !get_bool(node, "formatColumns"),
+        format_rows: !get_bool(node, "formatRows"),
+        insert_columns: !get_bool(node, "insertColumns"),
+        insert_rows: !get_bool(node, "insertRows"),
+        insert_hyperlinks: !get_bool(node, "insertHyperlinks"),
+        delete_columns: !get_bool(node, "deleteColumns"),
+        delete_rows: !get_bool(node, "deleteRows"),
+        sort: !get_bool(node, "sort"),
+        auto_filter: !get_bool(node, "autoFilter"),
+        pivot_tables: !get_bool(node, "pivotTables"),
+        objects: !get_bool_false(node, "objects"),
+        scenarios: !get_bool_false(node, "scenarios"),
+    })
+}
+
+pub(super) fn load_workbook_protection(node: Node) -> Option<WorkbookProtection> {
+    let lock_structure = get_bool_false(node, "lockStructure");
+    let lock_windows = get_bool_false(node, "lockWindows");
+    if !lock_structure && !lock_windows {
+        return None;
+    }
+    Some(WorkbookProtection {
+        password: load_password(node, "workbook"),
+        lock_structure,
+        lock_windows,
+    })
+}
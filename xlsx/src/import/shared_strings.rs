@@ -1,39 +1,151 @@
-use std::io::Read;
+use std::{collections::HashMap, io::Read};
 
+use ironcalc_base::types::{FontVerticalAlign, RunFont, TextRun};
 use roxmltree::Node;
 
 use crate::error::XlsxError;
 
-/// Reads the list of shared strings in an Excel workbook
-/// Note than in IronCalc we lose _internal_ styling of a string
+use super::util::{get_color, get_underline};
+
+/// The shared strings of a workbook and the formatted runs of those that are rich text
+#[derive(Default)]
+pub(crate) struct SharedStrings {
+    pub(crate) strings: Vec<String>,
+    pub(crate) rich_text: HashMap<u32, Vec<TextRun>>,
+}
+
+impl SharedStrings {
+    /// Returns the index of a plain text string, adding it if needed
+    pub(crate) fn get_or_insert(&mut self, text: &str) -> i32 {
+        let rich_text = &self.rich_text;
+        match self
+            .strings
+            .iter()
+            .enumerate()
+            .position(|(index, s)| s == text && !rich_text.contains_key(&(index as u32)))
+        {
+            Some(index) => index as i32,
+            None => {
+                self.strings.push(text.to_string());
+                self.strings.len() as i32 - 1
+            }
+        }
+    }
+
+    /// Returns the index of a string item (plain or rich text), adding it if needed
+    pub(crate) fn insert_item(&mut self, item: StringItem) -> i32 {
+        match item.runs {
+            Some(runs) => {
+                let index = self.strings.len() as u32;
+                self.strings.push(item.text);
+                self.rich_text.insert(index, runs);
+                index as i32
+            }
+            None => self.get_or_insert(&item.text),
+        }
+    }
+}
+
+/// The text of a string item (`<si>` or `<is>`) and its runs if it is rich text
+pub(crate) struct StringItem {
+    pub(crate) text: String,
+    pub(crate) runs: Option<Vec<TextRun>>,
+}
+
+/// Reads the list of shared strings in an Excel workbook, including the formatting of
+/// rich text strings.
 /// See Section 18.4
 pub(crate) fn read_shared_strings<R: Read + std::io::Seek>(
     archive: &mut zip::read::ZipArchive<R>,
-) -> Result<Vec<String>, XlsxError> {
+) -> Result<SharedStrings, XlsxError> {
     match archive.by_name("xl/sharedStrings.xml") {
         Ok(mut file) => {
             let mut text = String::new();
             file.read_to_string(&mut text)?;
             read_shared_strings_from_string(&text)
         }
-        Err(_e) => Ok(Vec::new()),
+        Err(_e) => Ok(SharedStrings::default()),
     }
 }
 
-fn read_shared_strings_from_string(text: &str) -> Result<Vec<String>, XlsxError> {
+fn read_shared_strings_from_string(text: &str) -> Result<SharedStrings, XlsxError> {
     let doc = roxmltree::Document::parse(text)?;
-    let mut shared_strings = Vec::new();
+    let mut shared_strings = SharedStrings::default();
     let nodes: Vec<Node> = doc.descendants().filter(|n| n.has_tag_name("si")).collect();
     for node in nodes {
+        // NB: we keep every item in its place, the cells refer to them by index
+        let item = read_string_item(node)?;
+        if let Some(runs) = item.runs {
+            let index = shared_strings.strings.len() as u32;
+            shared_strings.rich_text.insert(index, runs);
+        }
+        shared_strings.strings.push(item.text);
+    }
+    Ok(shared_strings)
+}
+
+/// Reads a string item (18.4.8 si or 18.3.1.53 is).
+/// Phonetic runs (`rPh`) are ignored.
+pub(crate) fn read_string_item(node: Node) -> Result<StringItem, XlsxError> {
+    let run_nodes: Vec<Node> = node.children().filter(|n| n.has_tag_name("r")).collect();
+    if run_nodes.is_empty() {
         let text = node
-            .descendants()
+            .children()
             .filter(|n| n.has_tag_name("t"))
-            .map(|n| n.text().unwrap_or("").to_string())
-            .collect::<Vec<String>>()
-            .join("");
-        shared_strings.push(text);
+            .map(|n| n.text().unwrap_or(""))
+            .collect::<String>();
+        return Ok(StringItem { text, runs: None });
     }
-    Ok(shared_strings)
+    let mut runs = Vec::new();
+    for run_node in run_nodes {
+        let text = run_node
+            .children()
+            .filter(|n| n.has_tag_name("t"))
+            .map(|n| n.text().unwrap_or(""))
+            .collect::<String>();
+        let font = match run_node.children().find(|n| n.has_tag_name("rPr")) {
+            Some(properties) => Some(read_run_font(properties)?),
+            None => None,
+        };
+        runs.push(TextRun { text, font });
+    }
+    let text = runs.iter().map(|run| run.text.as_str()).collect();
+    Ok(StringItem {
+        text,
+        runs: Some(runs),
+    })
+}
+
+// 18.4.7 rPr (Run Properties)
+fn read_run_font(node: Node) -> Result<RunFont, XlsxError> {
+    let mut font = RunFont::default();
+    for feature in node.children() {
+        let val = feature.attribute("val");
+        match feature.tag_name().name() {
+            // Boolean properties are true unless val is "0" or "false"
+            "b" => font.b = Some(!matches!(val, Some("0") | Some("false"))),
+            "i" => font.i = Some(!matches!(val, Some("0") | Some("false"))),
+            "strike" => font.strike = Some(!matches!(val, Some("0") | Some("false"))),
+            "u" => font.underline = Some(get_underline(feature)),
+            "sz" => {
+                font.sz = val
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .map(|v| v.round() as i32)
+            }
+            "color" => font.color = get_color(feature)?,
+            "rFont" => font.name = val.map(|v| v.to_string()),
+            "vertAlign" => {
+                font.vert_align = match val {
+                    Some("superscript") => Some(FontVerticalAlign::Superscript),
+                    Some("subscript") => Some(FontVerticalAlign::Subscript),
+                    Some("baseline") => Some(FontVerticalAlign::Baseline),
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(font)
 }
 
 #[cfg(test)]
@@ -70,12 +182,29 @@ mod tests {
 </sst>"#;
         let shared_strings = read_shared_strings_from_string(xml_string.trim()).unwrap();
         assert_eq!(
-            shared_strings,
+            shared_strings.strings,
             [
                 "A string".to_string(),
                 "A second String".to_string(),
                 "Hello World".to_string()
             ]
         );
+        let runs = shared_strings.rich_text.get(&2).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].font, None);
+        assert_eq!(
+            runs[1],
+            TextRun {
+                text: " World".to_string(),
+                font: Some(RunFont {
+                    b: Some(true),
+                    sz: Some(11),
+                    color: Some("#FF0000".to_string()),
+                    name: Some("Calibri".to_string()),
+                    ..Default::default()
+                }),
+            }
+        );
+        assert_eq!(shared_strings.rich_text.len(), 1);
     }
 }
@@ -1,14 +1,17 @@
 use std::{collections::HashMap, io::Read};
 
 use ironcalc_base::types::{
-    Alignment, Border, BorderItem, BorderStyle, CellStyleXfs, CellStyles, CellXfs, Fill, Font,
-    FontScheme, HorizontalAlignment, NumFmt, Styles, VerticalAlignment,
+    Alignment, Border, BorderItem, BorderStyle, CellProtection, CellStyleXfs, CellStyles, CellXfs,
+    Color, Fill, Font, FontScheme, FontUnderline, FontVerticalAlign, GradientFill, GradientStop,
+    GradientType, HorizontalAlignment, NumFmt, ReadingOrder, Styles, VerticalAlignment,
 };
 use roxmltree::Node;
 
 use crate::error::XlsxError;
 
-use super::util::{get_attribute, get_bool, get_bool_false, get_color, get_number};
+use super::util::{
+    get_attribute, get_bool, get_bool_false, get_color, get_number, get_style_color, get_underline,
+};
 
 fn get_border(node: Node, name: &str) -> Result<Option<BorderItem>, XlsxError> {
     let style;
@@ -40,7 +43,7 @@ fn get_border(node: Node, name: &str) -> Result<Option<BorderItem>, XlsxError> {
             .filter(|n| n.has_tag_name("color"))
             .collect::<Vec<Node>>();
         if color_node.len() == 1 {
-            color = get_color(color_node[0])?;
+            color = get_style_color(color_node[0])?;
         } else {
             color = None;
         }
@@ -50,6 +53,113 @@ fn get_border(node: Node, name: &str) -> Result<Option<BorderItem>, XlsxError> {
     Ok(Some(BorderItem { style, color }))
 }
 
+// 18.8.1 alignment (Alignment)
+fn load_alignment(xfs: Node) -> Option<Alignment> {
+    let alignment_nodes = xfs
+        .children()
+        .filter(|n| n.has_tag_name("alignment"))
+        .collect::<Vec<Node>>();
+    if alignment_nodes.len() == 1 {
+        let alignment_node = alignment_nodes[0];
+        let wrap_text = get_bool_false(alignment_node, "wrapText");
+
+        let horizontal = match alignment_node.attribute("horizontal") {
+            Some("center") => HorizontalAlignment::Center,
+            Some("centerContinuous") => HorizontalAlignment::CenterContinuous,
+            Some("distributed") => HorizontalAlignment::Distributed,
+            Some("fill") => HorizontalAlignment::Fill,
+            Some("general") => HorizontalAlignment::General,
+            Some("justify") => HorizontalAlignment::Justify,
+            Some("left") => HorizontalAlignment::Left,
+            Some("right") => HorizontalAlignment::Right,
+            // TODO: Should we fail in this case or set the alignment to default?
+            Some(_) => HorizontalAlignment::default(),
+            None => HorizontalAlignment::default(),
+        };
+
+        let vertical = match alignment_node.attribute("vertical") {
+            Some("bottom") => VerticalAlignment::Bottom,
+            Some("center") => VerticalAlignment::Center,
+            Some("distributed") => VerticalAlignment::Distributed,
+            Some("justify") => VerticalAlignment::Justify,
+            Some("top") => VerticalAlignment::Top,
+            // TODO: Should we fail in this case or set the alignment to default?
+            Some(_) => VerticalAlignment::default(),
+            None => VerticalAlignment::default(),
+        };
+
+        let reading_order = match alignment_node.attribute("readingOrder") {
+            Some("1") => ReadingOrder::LeftToRight,
+            Some("2") => ReadingOrder::RightToLeft,
+            _ => ReadingOrder::Context,
+        };
+
+        Some(Alignment {
+            horizontal,
+            vertical,
+            wrap_text,
+            text_rotation: get_number(alignment_node, "textRotation"),
+            indent: get_number(alignment_node, "indent"),
+            shrink_to_fit: get_bool_false(alignment_node, "shrinkToFit"),
+            justify_last_line: get_bool_false(alignment_node, "justifyLastLine"),
+            reading_order,
+        })
+    } else {
+        None
+    }
+}
+
+// Gradients are compared for equality, so we never keep NaN or infinite values
+fn get_float(node: Node, name: &str) -> f64 {
+    node.attribute(name)
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite())
+        .unwrap_or(0.0)
+}
+
+// 18.8.24 gradientFill
+// <gradientFill degree="90">
+//   <stop position="0"><color theme="0"/></stop>
+//   <stop position="1"><color theme="4"/></stop>
+// </gradientFill>
+fn load_gradient_fill(node: Node) -> Result<GradientFill, XlsxError> {
+    let gradient_type = match node.attribute("type") {
+        Some("path") => GradientType::Path,
+        _ => GradientType::Linear,
+    };
+    let mut stops = Vec::new();
+    for stop in node.children().filter(|n| n.has_tag_name("stop")) {
+        let color = match stop.children().find(|n| n.has_tag_name("color")) {
+            Some(color_node) => get_style_color(color_node)?,
+            None => None,
+        };
+        stops.push(GradientStop {
+            position: get_float(stop, "position"),
+            color,
+        });
+    }
+    Ok(GradientFill {
+        gradient_type,
+        degree: get_float(node, "degree"),
+        left: get_float(node, "left"),
+        right: get_float(node, "right"),
+        top: get_float(node, "top"),
+        bottom: get_float(node, "bottom"),
+        stops,
+    })
+}
+
+fn load_protection(xfs: Node) -> Option<CellProtection> {
+    // <protection locked="0" hidden="1"/>
+    xfs.children()
+        .find(|n| n.has_tag_name("protection"))
+        .map(|node| CellProtection {
+            locked: get_bool(node, "locked"),
+            hidden: get_bool_false(node, "hidden"),
+        })
+        .filter(|protection| *protection != CellProtection::default())
+}
+
 pub(super) fn load_styles<R: Read + std::io::Seek>(
     archive: &mut zip::read::ZipArchive<R>,
 ) -> Result<Styles, XlsxError> {
@@ -86,15 +196,17 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
     for font in font_nodes.children() {
         let mut sz = 11;
         let mut name = "Calibri".to_string();
-        // NOTE: In Excel you can have simple underline or double underline
-        // In IronCalc convert double underline to simple
-        // This in excel is u with a value of "double"
-        let mut u = false;
+        // NOTE: The kind of underline is the value of u, i.e. <u val="double"/>
+        let mut underline = FontUnderline::None;
         let mut b = false;
         let mut i = false;
         let mut strike = false;
+        let mut vert_align = FontVerticalAlign::Baseline;
+        let mut outline = false;
+        let mut shadow = false;
+        let mut condense = false;
         // Default color is black
-        let mut color = Some("#000000".to_string());
+        let mut color = Some(Color::Rgb("#000000".to_string()));
         let mut family = 2;
         let mut scheme = FontScheme::default();
         for feature in font.children() {
@@ -107,11 +219,21 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
                         .unwrap_or(11);
                 }
                 "color" => {
-                    color = get_color(feature)?;
+                    color = get_style_color(feature)?;
                 }
                 "u" => {
-                    u = true;
+                    underline = get_underline(feature);
                 }
+                "vertAlign" => {
+                    vert_align = match feature.attribute("val") {
+                        Some("superscript") => FontVerticalAlign::Superscript,
+                        Some("subscript") => FontVerticalAlign::Subscript,
+                        _ => FontVerticalAlign::Baseline,
+                    };
+                }
+                "outline" => outline = get_bool(feature, "val"),
+                "shadow" => shadow = get_bool(feature, "val"),
+                "condense" => condense = get_bool(feature, "val"),
                 "b" => {
                     b = true;
                 }
@@ -148,7 +270,7 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
         }
         fonts.push(Font {
             strike,
-            u,
+            underline,
             b,
             i,
             sz,
@@ -156,6 +278,10 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
             name,
             family,
             scheme,
+            vert_align,
+            outline,
+            shadow,
+            condense,
         });
     }
 
@@ -165,35 +291,39 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
         .filter(|n| n.has_tag_name("fills"))
         .collect::<Vec<Node>>()[0];
     for fill in fill_nodes.children() {
+        if let Some(gradient_fill) = fill.children().find(|n| n.has_tag_name("gradientFill")) {
+            fills.push(Fill {
+                gradient: Some(load_gradient_fill(gradient_fill)?),
+                ..Default::default()
+            });
+            continue;
+        }
         let pattern_fill = fill
             .children()
             .filter(|n| n.has_tag_name("patternFill"))
             .collect::<Vec<Node>>();
         if pattern_fill.len() != 1 {
             // safety belt
-            // Some fills do not have a patternFill, but they have gradientFill
-            fills.push(Fill {
-                pattern_type: "solid".to_string(),
-                fg_color: None,
-                bg_color: None,
-            });
+            fills.push(Fill::default());
             continue;
         }
         let pattern_fill = pattern_fill[0];
 
+        // TODO: Should we fail if the pattern is unknown?
         let pattern_type = pattern_fill
             .attribute("patternType")
             .unwrap_or("none")
-            .to_string();
+            .parse()
+            .unwrap_or_default();
         let mut fg_color = None;
         let mut bg_color = None;
         for feature in pattern_fill.children() {
             match feature.tag_name().name() {
                 "fgColor" => {
-                    fg_color = get_color(feature)?;
+                    fg_color = get_style_color(feature)?;
                 }
                 "bgColor" => {
-                    bg_color = get_color(feature)?;
+                    bg_color = get_style_color(feature)?;
                 }
                 _ => {
                     println!("Unexpected pattern");
@@ -205,6 +335,7 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
             pattern_type,
             fg_color,
             bg_color,
+            gradient: None,
         })
     }
 
@@ -260,6 +391,8 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
             apply_protection,
             apply_font,
             apply_fill,
+            alignment: load_alignment(xfs),
+            protection: load_protection(xfs),
         });
     }
 
@@ -272,7 +405,11 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
     for cell_style in cell_style_nodes.children() {
         let name = get_attribute(&cell_style, "name")?.to_string();
         let xf_id = get_number(cell_style, "xfId");
-        let builtin_id = get_number(cell_style, "builtinId");
+        // Custom styles do not have a builtinId
+        let builtin_id = cell_style
+            .attribute("builtinId")
+            .and_then(|id| id.parse::<i32>().ok())
+            .unwrap_or(-1);
         style_names.insert(xf_id, name.clone());
         cell_styles.push(CellStyles {
             name,
@@ -303,47 +440,8 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
         // TODO: Pivot Tables
         // let pivotButton = get_bool(xfs, "pivotButton");
 
-        let alignment_nodes = xfs
-            .children()
-            .filter(|n| n.has_tag_name("alignment"))
-            .collect::<Vec<Node>>();
-        let alignment = if alignment_nodes.len() == 1 {
-            let alignment_node = alignment_nodes[0];
-            let wrap_text = get_bool_false(alignment_node, "wrapText");
-
-            let horizontal = match alignment_node.attribute("horizontal") {
-                Some("center") => HorizontalAlignment::Center,
-                Some("centerContinuous") => HorizontalAlignment::CenterContinuous,
-                Some("distributed") => HorizontalAlignment::Distributed,
-                Some("fill") => HorizontalAlignment::Fill,
-                Some("general") => HorizontalAlignment::General,
-                Some("justify") => HorizontalAlignment::Justify,
-                Some("left") => HorizontalAlignment::Left,
-                Some("right") => HorizontalAlignment::Right,
-                // TODO: Should we fail in this case or set the alignment to default?
-                Some(_) => HorizontalAlignment::default(),
-                None => HorizontalAlignment::default(),
-            };
-
-            let vertical = match alignment_node.attribute("vertical") {
-                Some("bottom") => VerticalAlignment::Bottom,
-                Some("center") => VerticalAlignment::Center,
-                Some("distributed") => VerticalAlignment::Distributed,
-                Some("justify") => VerticalAlignment::Justify,
-                Some("top") => VerticalAlignment::Top,
-                // TODO: Should we fail in this case or set the alignment to default?
-                Some(_) => VerticalAlignment::default(),
-                None => VerticalAlignment::default(),
-            };
-
-            Some(Alignment {
-                horizontal,
-                vertical,
-                wrap_text,
-            })
-        } else {
-            None
-        };
+        let alignment = load_alignment(xfs);
+        let protection = load_protection(xfs);
 
         cell_xfs.push(CellXfs {
             xf_id,
@@ -359,11 +457,11 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
             apply_fill,
             quote_prefix,
             alignment,
+            protection,
         });
     }
 
     // TODO
-    // let mut dxfs = Vec::new();
     // let mut tableStyles = Vec::new();
     // let mut colors = Vec::new();
     // <colors>
@@ -385,3 +483,45 @@ pub(super) fn load_styles<R: Read + std::io::Seek>(
         cell_styles,
     })
 }
+
+/// The colors of a differential format (dxf). IronCalc does not support dxfs, but they are used
+/// to define the color of color filters.
+pub(crate) struct DxfColors {
+    pub(crate) fill: Option<String>,
+    pub(crate) font: Option<String>,
+}
+
+// <dxfs count="2">
+//     <dxf><fill><patternFill><bgColor rgb="FFFFFF00"/></patternFill></fill></dxf>
+//     <dxf><font><color rgb="FFFF0000"/></font></dxf>
+// </dxfs>
+pub(super) fn load_dxf_colors<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+) -> Result<Vec<DxfColors>, XlsxError> {
+    let mut file = archive.by_name("xl/styles.xml")?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    let doc = roxmltree::Document::parse(&text)?;
+    let mut dxfs = Vec::new();
+    let dxf_nodes = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("dxfs"))
+        .flat_map(|n| n.children().filter(|c| c.has_tag_name("dxf")));
+    for dxf in dxf_nodes {
+        let mut fill = None;
+        let mut font = None;
+        for node in dxf.descendants() {
+            match node.tag_name().name() {
+                // NB: In a dxf the color of a solid fill is the background color
+                "bgColor" => fill = get_color(node)?,
+                "fgColor" if fill.is_none() => fill = get_color(node)?,
+                "color" if node.parent().is_some_and(|p| p.has_tag_name("font")) => {
+                    font = get_color(node)?
+                }
+                _ => {}
+            }
+        }
+        dxfs.push(DxfColors { fill, font });
+    }
+    Ok(dxfs)
+}
@@ -5,7 +5,11 @@ use roxmltree::Node;
 
 use crate::error::XlsxError;
 
-use super::util::{get_bool, get_bool_false};
+use super::{
+    auto_filter::load_auto_filter,
+    styles::DxfColors,
+    util::{get_bool, get_bool_false},
+};
 
 // <table name="Table" displayName="Table" totalsRowCount ref="A1:D6">
 //   <autoFilter ref="A1:D6">
@@ -25,6 +29,7 @@ pub(crate) fn load_table<R: Read + std::io::Seek>(
     archive: &mut zip::read::ZipArchive<R>,
     path: &str,
     sheet_name: &str,
+    dxfs: &[DxfColors],
 ) -> Result<Table, XlsxError> {
     let mut file = archive.by_name(path)?;
     let mut text = String::new();
@@ -103,18 +108,15 @@ pub(crate) fn load_table<R: Read + std::io::Seek>(
     // TODO: Conformant implementations should panic if header_row_dxf_id or data_dxf_id are out of bounds.
 
     // Note that filters are non dynamic
-    // The only thing important for us is whether or not it has filters
-    let auto_filter = table
-        .descendants()
-        .filter(|n| n.has_tag_name("autoFilter"))
-        .collect::<Vec<Node>>();
-
-    let has_filters = if let Some(filter) = auto_filter.first() {
-        filter.children().count() > 0
-    } else {
-        false
+    let auto_filter = match table.children().find(|n| n.has_tag_name("autoFilter")) {
+        Some(node) => Some(load_auto_filter(node, dxfs)?),
+        None => None,
     };
 
+    let has_filters = auto_filter
+        .as_ref()
+        .is_some_and(|f| !f.filter_columns.is_empty());
+
     // tableColumn
     let table_column = table
         .descendants()
@@ -204,6 +206,7 @@ pub(crate) fn load_table<R: Read + std::io::Seek>(
         columns,
         style_info,
         has_filters,
+        auto_filter,
         sheet_name: sheet_name.to_string(),
     })
 }
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, io::Read};
+
+use ironcalc_base::types::Theme;
+use roxmltree::Node;
+
+use crate::error::XlsxError;
+
+use super::{opaque_parts::resolve_target, worksheets::Relationship};
+
+// The colours of the scheme in the order used by the `theme` attribute of colours
+const COLOR_NAMES: [&str; 12] = [
+    "lt1", "dk1", "lt2", "dk2", "accent1", "accent2", "accent3", "accent4", "accent5", "accent6",
+    "hlink", "folHlink",
+];
+
+fn get_scheme_color(node: Node) -> Option<String> {
+    node.children().find_map(|child| {
+        let value = match child.tag_name().name() {
+            "srgbClr" => child.attribute("val"),
+            "sysClr" => child.attribute("lastClr"),
+            _ => None,
+        }?;
+        if value.len() == 6 {
+            Some(format!("#{}", value.to_uppercase()))
+        } else {
+            None
+        }
+    })
+}
+
+fn get_latin_typeface(node: Node) -> Option<String> {
+    node.children()
+        .find(|n| n.has_tag_name("latin"))
+        .and_then(|n| n.attribute("typeface"))
+        .map(|typeface| typeface.to_string())
+}
+
+/// Loads the theme of the workbook (20.1.6.9 theme). If there is none we use the Office theme.
+pub(super) fn load_theme<R: Read + std::io::Seek>(
+    archive: &mut zip::read::ZipArchive<R>,
+    rels: &HashMap<String, Relationship>,
+) -> Result<Theme, XlsxError> {
+    let mut theme = Theme::default();
+    let target = match rels.values().find(|rel| rel.rel_type.ends_with("/theme")) {
+        Some(rel) => resolve_target("xl", &rel.target),
+        None => return Ok(theme),
+    };
+    let mut file = archive.by_name(&target)?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    let doc = roxmltree::Document::parse(&text)?;
+    let root = doc.root_element();
+    if let Some(name) = root.attribute("name") {
+        theme.name = name.to_string();
+    }
+    for node in root.descendants() {
+        match node.tag_name().name() {
+            "clrScheme" => {
+                for child in node.children().filter(|n| n.is_element()) {
+                    let name = child.tag_name().name();
+                    if let Some(index) = COLOR_NAMES.iter().position(|&n| n == name) {
+                        if let Some(color) = get_scheme_color(child) {
+                            theme.colors[index] = color;
+                        }
+                    }
+                }
+            }
+            "majorFont" => {
+                if let Some(typeface) = get_latin_typeface(node) {
+                    theme.major_font = typeface;
+                }
+            }
+            "minorFont" => {
+                if let Some(typeface) = get_latin_typeface(node) {
+                    theme.minor_font = typeface;
+                }
+            }
+            _ => {}
+        }
+    }
+    drop(doc);
+    theme.xml = Some(text);
+    Ok(theme)
+}
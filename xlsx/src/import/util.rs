@@ -1,12 +1,10 @@
 #![allow(clippy::unwrap_used)]
 
-use colors::{get_indexed_color, get_themed_color};
+use ironcalc_base::types::{Color, FontUnderline, Theme};
 use roxmltree::{ExpandedName, Node};
 
 use crate::error::XlsxError;
 
-use super::colors;
-
 pub(crate) fn get_number(node: Node, s: &str) -> i32 {
     node.attribute(s).unwrap_or("0").parse::<i32>().unwrap_or(0)
 }
@@ -36,7 +34,13 @@ pub(super) fn get_value_or_default(node: &Node, tag_name: &str, default: &str) -
     }
 }
 
+/// Returns the RGB value of a colour. Theme colours are resolved against the default theme.
 pub(super) fn get_color(node: Node) -> Result<Option<String>, XlsxError> {
+    Ok(get_style_color(node)?.map(|color| color.to_rgb(&Theme::default())))
+}
+
+/// Returns a colour as it is defined in the workbook
+pub(super) fn get_style_color(node: Node) -> Result<Option<Color>, XlsxError> {
     // 18.3.1.15 color (Data Bar Color)
     if node.has_attribute("rgb") {
         let mut val = node.attribute("rgb").unwrap().to_string();
@@ -44,21 +48,17 @@ pub(super) fn get_color(node: Node) -> Result<Option<String>, XlsxError> {
         if val.len() == 8 {
             val = format!("#{}", &val[2..8]);
         }
-        Ok(Some(val))
+        Ok(Some(Color::Rgb(val)))
     } else if node.has_attribute("indexed") {
-        let index = node.attribute("indexed").unwrap().parse::<i32>()?;
-        let rgb = get_indexed_color(index);
-        Ok(Some(rgb))
-    // Color::Indexed(val)
+        let indexed = node.attribute("indexed").unwrap().parse::<i32>()?;
+        Ok(Some(Color::Indexed { indexed }))
     } else if node.has_attribute("theme") {
         let theme = node.attribute("theme").unwrap().parse::<i32>()?;
         let tint = match node.attribute("tint") {
             Some(t) => t.parse::<f64>().unwrap_or(0.0),
             None => 0.0,
         };
-        let rgb = get_themed_color(theme, tint);
-        Ok(Some(rgb))
-    // Color::Theme { theme, tint }
+        Ok(Some(Color::theme(theme, tint)))
     } else if node.has_attribute("auto") {
         // TODO: Is this correct?
         // A boolean value indicating the color is automatic and system color dependent.
@@ -78,3 +78,14 @@ pub(super) fn get_bool_false(node: Node, s: &str) -> bool {
     // defaults to false
     matches!(node.attribute(s), Some("1"))
 }
+
+// 18.18.85 ST_UnderlineValues, a missing val is a single underline
+pub(super) fn get_underline(node: Node) -> FontUnderline {
+    match node.attribute("val") {
+        Some("double") => FontUnderline::Double,
+        Some("singleAccounting") => FontUnderline::SingleAccounting,
+        Some("doubleAccounting") => FontUnderline::DoubleAccounting,
+        Some("none") => FontUnderline::None,
+        _ => FontUnderline::Single,
+    }
+}
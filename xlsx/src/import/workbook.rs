@@ -1,11 +1,12 @@
 use std::io::Read;
 
-use ironcalc_base::types::{DefinedName, SheetState};
+use ironcalc_base::types::{DateSystem, DefinedName, SheetState};
 use roxmltree::Node;
 
 use crate::error::XlsxError;
 
 use super::{
+    protection::load_workbook_protection,
     util::get_attribute,
     worksheets::{Sheet, WorkbookXML},
 };
@@ -71,9 +72,26 @@ pub(super) fn load_workbook<R: Read + std::io::Seek>(
             sheet_id,
         })
     }
+    let protection = doc
+        .descendants()
+        .find(|n| n.has_tag_name("workbookProtection"))
+        .and_then(load_workbook_protection);
+    // <workbookPr date1904="1"/>
+    let is_date1904 = doc
+        .descendants()
+        .find(|n| n.has_tag_name("workbookPr"))
+        .and_then(|n| n.attribute("date1904"))
+        .is_some_and(|value| value == "1" || value == "true");
+    let date_system = if is_date1904 {
+        DateSystem::Excel1904
+    } else {
+        DateSystem::Excel1900
+    };
     // read the relationships file
     Ok(WorkbookXML {
         worksheets: sheets,
         defined_names,
+        protection,
+        date_system,
     })
 }
@@ -13,8 +13,8 @@ use ironcalc_base::{
         utils::{column_to_number, parse_reference_a1},
     },
     types::{
-        Cell, Col, Comment, DefinedName, Row, SheetData, SheetState, Table, Worksheet,
-        WorksheetView,
+        Cell, Col, Comment, DateSystem, DefinedName, MergedRange, Row, SheetData, SheetState,
+        Table, WorkbookProtection, Worksheet, WorksheetView,
     },
 };
 use roxmltree::Node;
@@ -23,8 +23,13 @@ use thiserror::Error;
 use crate::error::XlsxError;
 
 use super::{
+    auto_filter::load_auto_filter,
+    page_setup::load_page_setup,
+    protection::load_sheet_protection,
+    shared_strings::{read_string_item, SharedStrings, StringItem},
+    styles::DxfColors,
     tables::load_table,
-    util::{get_attribute, get_color, get_number},
+    util::{get_attribute, get_bool, get_bool_false, get_color, get_number},
 };
 
 pub(crate) struct Sheet {
@@ -37,6 +42,8 @@ pub(crate) struct Sheet {
 pub(crate) struct WorkbookXML {
     pub(crate) worksheets: Vec<Sheet>,
     pub(crate) defined_names: Vec<DefinedName>,
+    pub(crate) protection: Option<WorkbookProtection>,
+    pub(crate) date_system: DateSystem,
 }
 
 pub(crate) struct Relationship {
@@ -143,6 +150,8 @@ fn load_columns(ws: Node) -> Result<Vec<Col>, XlsxError> {
     //     <col min="5" max="5" width="38.26953125" customWidth="1"/>
     //     <col min="6" max="6" width="9.1796875" style="1"/>
     //     <col min="8" max="8" width="4" customWidth="1"/>
+    //     <col min="9" max="10" width="8.7" hidden="1" outlineLevel="1"/>
+    //     <col min="11" max="11" width="8.7" collapsed="1"/>
     // </cols>
     let mut cols = Vec::new();
     let columns = ws
@@ -161,19 +170,33 @@ fn load_columns(ws: Node) -> Result<Vec<Col>, XlsxError> {
             let style = col
                 .attribute("style")
                 .map(|s| s.parse::<i32>().unwrap_or(0));
+            let hidden = get_bool_false(col, "hidden");
+            let outline_level = get_outline_level(col);
+            let collapsed = get_bool_false(col, "collapsed");
             cols.push(Col {
                 min,
                 max,
                 width,
                 custom_width,
                 style,
+                hidden,
+                outline_level,
+                collapsed,
             })
         }
     }
     Ok(cols)
 }
 
-fn load_merge_cells(ws: Node) -> Result<Vec<String>, XlsxError> {
+// Outline levels go from 0 to 7
+fn get_outline_level(node: Node) -> u8 {
+    node.attribute("outlineLevel")
+        .and_then(|s| s.parse::<u8>().ok())
+        .unwrap_or(0)
+        .min(7)
+}
+
+fn load_merge_cells(ws: Node) -> Result<Vec<MergedRange>, XlsxError> {
     // 18.3.1.55 Merge Cells
     // <mergeCells count="1">
     //    <mergeCell ref="K7:L10"/>
@@ -185,8 +208,8 @@ fn load_merge_cells(ws: Node) -> Result<Vec<String>, XlsxError> {
         .collect::<Vec<Node>>();
     if merge_cells_nodes.len() == 1 {
         for merge_cell in merge_cells_nodes[0].children() {
-            let reference = get_attribute(&merge_cell, "ref")?.to_string();
-            merge_cells.push(reference);
+            let reference = get_attribute(&merge_cell, "ref")?;
+            merge_cells.push(MergedRange::parse(reference).map_err(XlsxError::Xml)?);
         }
     }
     Ok(merge_cells)
@@ -213,6 +236,25 @@ fn load_sheet_color(ws: Node) -> Result<Option<String>, XlsxError> {
     Ok(color)
 }
 
+// Returns (summary_below, summary_right)
+fn load_outline_properties(ws: Node) -> (bool, bool) {
+    // <sheetPr>
+    //     <outlinePr summaryBelow="0" summaryRight="0"/>
+    // </sheetPr>
+    let outline_pr = ws
+        .children()
+        .filter(|n| n.has_tag_name("sheetPr"))
+        .flat_map(|n| n.children())
+        .find(|n| n.has_tag_name("outlinePr"));
+    match outline_pr {
+        Some(node) => (
+            get_bool(node, "summaryBelow"),
+            get_bool(node, "summaryRight"),
+        ),
+        None => (true, true),
+    }
+}
+
 fn load_comments<R: Read + std::io::Seek>(
     archive: &mut zip::read::ZipArchive<R>,
     path: &str,
@@ -334,8 +376,8 @@ fn get_cell_from_excel(
     formula_index: i32,
     sheet_name: &str,
     cell_ref: &str,
-    shared_strings: &mut Vec<String>,
-    rich_text_inline: Option<String>,
+    shared_strings: &mut SharedStrings,
+    rich_text_inline: Option<StringItem>,
 ) -> Cell {
     // Possible cell types:
     // 18.18.11 ST_CellType (Cell Type)
@@ -378,14 +420,7 @@ fn get_cell_from_excel(
                 s: cell_style,
             },
             "str" => {
-                let s = cell_value.unwrap_or("");
-                let si = if let Some(i) = shared_strings.iter().position(|r| r == s) {
-                    i
-                } else {
-                    shared_strings.push(s.to_string());
-                    shared_strings.len() - 1
-                } as i32;
-
+                let si = shared_strings.get_or_insert(cell_value.unwrap_or(""));
                 Cell::SharedString { si, s: cell_style }
             }
             "d" => {
@@ -397,14 +432,10 @@ fn get_cell_from_excel(
                 }
             }
             "inlineStr" => {
-                let s = rich_text_inline.unwrap_or_default();
-                let si = if let Some(i) = shared_strings.iter().position(|r| r == &s) {
-                    i
-                } else {
-                    shared_strings.push(s.to_string());
-                    shared_strings.len() - 1
-                } as i32;
-
+                let si = match rich_text_inline {
+                    Some(item) => shared_strings.insert_item(item),
+                    None => shared_strings.get_or_insert(""),
+                };
                 Cell::SharedString { si, s: cell_style }
             }
             "empty" => Cell::EmptyCell { s: cell_style },
@@ -486,7 +517,7 @@ fn get_cell_from_excel(
                 // NB: This is untested, I don't know of any engine that uses inline strings in formulas
                 Cell::CellFormulaString {
                     f: formula_index,
-                    v: rich_text_inline.unwrap_or("".to_string()),
+                    v: rich_text_inline.map(|item| item.text).unwrap_or_default(),
                     s: cell_style,
                 }
             }
@@ -512,6 +543,7 @@ fn load_sheet_rels<R: Read + std::io::Seek>(
     path: &str,
     tables: &mut HashMap<String, Table>,
     sheet_name: &str,
+    dxfs: &[DxfColors],
 ) -> Result<Vec<Comment>, XlsxError> {
     // ...xl/worksheets/sheet6.xml -> xl/worksheets/_rels/sheet6.xml.rels
     let mut comments = Vec::new();
@@ -552,7 +584,7 @@ fn load_sheet_rels<R: Read + std::io::Seek>(
                 target
             };
 
-            let table = load_table(archive, &path, sheet_name)?;
+            let table = load_table(archive, &path, sheet_name, dxfs)?;
             tables.insert(table.name.clone(), table);
         }
     }
@@ -694,14 +726,16 @@ pub(super) struct SheetSettings {
     pub comments: Vec<Comment>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn load_sheet<R: Read + std::io::Seek>(
     archive: &mut zip::read::ZipArchive<R>,
     path: &str,
     settings: SheetSettings,
     worksheets: &[String],
     tables: &HashMap<String, Table>,
-    shared_strings: &mut Vec<String>,
+    shared_strings: &mut SharedStrings,
     defined_names: Vec<DefinedNameS>,
+    dxfs: &[DxfColors],
 ) -> Result<(Worksheet, bool), XlsxError> {
     let sheet_name = &settings.name;
     let sheet_id = settings.id;
@@ -723,6 +757,7 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
 
     let cols = load_columns(ws)?;
     let color = load_sheet_color(ws)?;
+    let (summary_below, summary_right) = load_outline_properties(ws);
 
     // sheetData
     // <row r="1" spans="1:15" x14ac:dyDescent="0.35">
@@ -774,8 +809,17 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
         };
         let custom_format = matches!(row.attribute("customFormat"), Some("1"));
         let hidden = matches!(row.attribute("hidden"), Some("1"));
-
-        if custom_height || custom_format || row_style != 0 || has_height_attribute || hidden {
+        let outline_level = get_outline_level(row);
+        let collapsed = get_bool_false(row, "collapsed");
+
+        if custom_height
+            || custom_format
+            || row_style != 0
+            || has_height_attribute
+            || hidden
+            || outline_level != 0
+            || collapsed
+        {
             rows.push(Row {
                 r: row_index,
                 height,
@@ -783,11 +827,13 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
                 custom_height,
                 custom_format,
                 hidden,
+                outline_level,
+                collapsed,
             });
         }
 
         // Unused attributes:
-        // * thickBot, thickTop, ph, collapsed, outlineLevel
+        // * thickBot, thickTop, ph
 
         let mut data_row = HashMap::new();
 
@@ -823,19 +869,9 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
             //     <t>Hello, World!</t>
             //   </is>
             // </c>
-            let cell_rich_text_nodes: Vec<Node> =
-                cell.children().filter(|n| n.has_tag_name("is")).collect();
-            let cell_rich_text = if cell_rich_text_nodes.is_empty() {
-                None
-            } else {
-                let texts: Vec<String> = cell_rich_text_nodes[0]
-                    .descendants()
-                    .filter(|n| n.has_tag_name("t"))
-                    .filter_map(|n| n.text())
-                    .map(|s| s.to_string())
-                    .collect();
-
-                Some(texts.join(""))
+            let cell_rich_text = match cell.children().find(|n| n.has_tag_name("is")) {
+                Some(node) => Some(read_string_item(node)?),
+                None => None,
             };
 
             let cell_metadata = cell.attribute("cm");
@@ -1003,6 +1039,11 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
 
     let merge_cells = load_merge_cells(ws)?;
 
+    let auto_filter = match ws.children().find(|n| n.has_tag_name("autoFilter")) {
+        Some(node) => Some(load_auto_filter(node, dxfs)?),
+        None => None,
+    };
+
     // Conditional Formatting
     // <conditionalFormatting sqref="B1:B9">
     //     <cfRule type="colorScale" priority="1">
@@ -1014,8 +1055,9 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
     //         </colorScale>
     //     </cfRule>
     // </conditionalFormatting>
-    // pageSetup
-    // <pageSetup orientation="portrait" r:id="rId1"/>
+
+    let page_setup = load_page_setup(ws)?;
+    let protection = load_sheet_protection(ws);
 
     let mut views = HashMap::new();
     views.insert(
@@ -1046,6 +1088,12 @@ pub(super) fn load_sheet<R: Read + std::io::Seek>(
             frozen_columns: sheet_view.frozen_columns,
             show_grid_lines: sheet_view.show_grid_lines,
             views,
+            auto_filter,
+            summary_below,
+            summary_right,
+            page_setup,
+            protection,
+            opaque_parts: Default::default(),
         },
         sheet_view.is_selected,
     ))
@@ -1056,7 +1104,8 @@ pub(super) fn load_sheets<R: Read + std::io::Seek>(
     rels: &HashMap<String, Relationship>,
     workbook: &WorkbookXML,
     tables: &mut HashMap<String, Table>,
-    shared_strings: &mut Vec<String>,
+    shared_strings: &mut SharedStrings,
+    dxfs: &[DxfColors],
 ) -> Result<(Vec<Worksheet>, u32), XlsxError> {
     // load comments and tables
     let mut comments = HashMap::new();
@@ -1071,7 +1120,7 @@ pub(super) fn load_sheets<R: Read + std::io::Seek>(
             };
             comments.insert(
                 &sheet.id,
-                load_sheet_rels(archive, &path, tables, &sheet.name)?,
+                load_sheet_rels(archive, &path, tables, &sheet.name, dxfs)?,
             );
         }
     }
@@ -1113,6 +1162,7 @@ pub(super) fn load_sheets<R: Read + std::io::Seek>(
                 tables,
                 shared_strings,
                 defined_names.clone(),
+                dxfs,
             )?;
             if is_selected {
                 selected_sheet = sheet_index;
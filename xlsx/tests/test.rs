@@ -164,17 +164,17 @@ fn test_model_has_correct_styles(model: &Model) {
     let style_a1 = model.get_style_for_cell(0, 1, 1).unwrap();
     assert!(style_a1.font.b);
     assert!(!style_a1.font.i);
-    assert!(!style_a1.font.u);
+    assert!(!style_a1.font.is_underlined());
 
     // B1 is Italics
     let style_b1 = model.get_style_for_cell(0, 1, 2).unwrap();
     assert!(style_b1.font.i);
     assert!(!style_b1.font.b);
-    assert!(!style_b1.font.u);
+    assert!(!style_b1.font.is_underlined());
 
     // C1 Underlined
     let style_c1 = model.get_style_for_cell(0, 1, 3).unwrap();
-    assert!(style_c1.font.u);
+    assert!(style_c1.font.is_underlined());
     assert!(!style_c1.font.b);
     assert!(!style_c1.font.i);
 
@@ -182,13 +182,13 @@ fn test_model_has_correct_styles(model: &Model) {
     let style_d1 = model.get_style_for_cell(0, 1, 4).unwrap();
     assert!(style_d1.font.b);
     assert!(style_d1.font.i);
-    assert!(!style_d1.font.u);
+    assert!(!style_d1.font.is_underlined());
 
     // E1 Bold, italics and underlined
     let style_e1 = model.get_style_for_cell(0, 1, 5).unwrap();
     assert!(style_e1.font.b);
     assert!(style_e1.font.i);
-    assert!(style_e1.font.u);
+    assert!(style_e1.font.is_underlined());
     assert!(!style_e1.font.strike);
 
     // F1 strikethrough
@@ -197,7 +197,7 @@ fn test_model_has_correct_styles(model: &Model) {
 
     // G1 Double underlined just get simple underlined
     let style_g1 = model.get_style_for_cell(0, 1, 7).unwrap();
-    assert!(style_g1.font.u);
+    assert!(style_g1.font.is_underlined());
 
     let height_row_3 = model.workbook.worksheet(0).unwrap().row_height(3).unwrap();
     assert_eq!(height_row_3, 136.0);
@@ -639,3 +639,173 @@ fn test_pyopenxl_example() {
     let b1 = model.get_formatted_cell_value(0, 1, 2).unwrap();
     assert_eq!(b1, "It is what it is");
 }
+
+#[test]
+fn test_save_to_xlsx_keeps_unknown_parts() {
+    let mut model = load_from_xlsx("tests/example.xlsx", "en", "UTC", "en").unwrap();
+    model.set_user_input(0, 1, 1, "Edited".to_string()).unwrap();
+    model.evaluate();
+    let temp_file_name = "temp_file_example_unknown_parts.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+
+    let read_file = |archive: &mut zip::ZipArchive<fs::File>, name: &str| {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    };
+    let mut archive = zip::ZipArchive::new(fs::File::open(temp_file_name).unwrap()).unwrap();
+    for name in [
+        "xl/theme/theme1.xml",
+        "xl/drawings/drawing2.xml",
+        "xl/drawings/_rels/drawing2.xml.rels",
+        "xl/charts/chart2.xml",
+        "xl/charts/style2.xml",
+        "xl/pivotTables/pivotTable1.xml",
+        "xl/pivotCache/pivotCacheDefinition1.xml",
+        "xl/pivotCache/pivotCacheRecords1.xml",
+    ] {
+        assert!(archive.by_name(name).is_ok(), "Missing {name}");
+    }
+    let content_types = read_file(&mut archive, "[Content_Types].xml");
+    assert!(content_types.contains(r#"<Override PartName="/xl/charts/chart2.xml" ContentType="application/vnd.openxmlformats-officedocument.drawingml.chart+xml"/>"#));
+    // The relationships of the workbook are renumbered after the 10 sheets, the styles, the
+    // shared strings and the theme
+    let workbook_rels = read_file(&mut archive, "xl/_rels/workbook.xml.rels");
+    assert!(workbook_rels.contains(r#"<Relationship Id="rId14" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/pivotCacheDefinition" Target="pivotCache/pivotCacheDefinition1.xml"/>"#));
+    let workbook_xml = read_file(&mut archive, "xl/workbook.xml");
+    assert!(workbook_xml.contains(
+        r#"<pivotCaches><pivotCache cacheId="2" r:id="rId14"></pivotCache></pivotCaches>"#
+    ));
+    assert!(workbook_xml.contains(r#"<sheet name="Chart1" sheetId="12" r:id="rId15"></sheet>"#));
+    assert!(archive.by_name("xl/chartsheets/sheet1.xml").is_ok());
+    drop(archive);
+
+    // Saving the workbook again keeps the same parts
+    let model2 = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+    assert_eq!(model2.get_formatted_cell_value(0, 1, 1).unwrap(), "Edited");
+    let parts = &model.workbook.opaque_parts;
+    let parts2 = &model2.workbook.opaque_parts;
+    assert_eq!(parts.parts, parts2.parts);
+    // The pivot caches and the chart sheet
+    assert_eq!(parts.workbook_elements.len(), 2);
+    assert_eq!(parts2.workbook_elements.len(), 2);
+    for (worksheet, worksheet2) in model
+        .workbook
+        .worksheets
+        .iter()
+        .zip(&model2.workbook.worksheets)
+    {
+        assert_eq!(worksheet.opaque_parts, worksheet2.opaque_parts);
+    }
+    let drawings: Vec<&str> = model2
+        .workbook
+        .worksheets
+        .iter()
+        .flat_map(|worksheet| &worksheet.opaque_parts.elements)
+        .map(|element| element.xml.as_str())
+        .collect();
+    assert_eq!(drawings, vec![r#"<drawing r:id="rId1"></drawing>"#]);
+}
+
+#[test]
+fn test_save_to_xlsx_duplicated_sheet_with_drawing() {
+    let mut model = load_from_xlsx("tests/example.xlsx", "en", "UTC", "en").unwrap();
+    let sheet = model
+        .workbook
+        .worksheets
+        .iter()
+        .position(|worksheet| !worksheet.opaque_parts.elements.is_empty())
+        .unwrap() as u32;
+    let index = model.duplicate_sheet(sheet, "Copy").unwrap();
+    let temp_file_name = "temp_file_duplicated_sheet_with_drawing.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+
+    // Only the original sheet points to the drawing
+    let mut archive = zip::ZipArchive::new(fs::File::open(temp_file_name).unwrap()).unwrap();
+    let mut drawing_relationships = 0;
+    for name in archive.file_names().map(str::to_string).collect::<Vec<_>>() {
+        if name.starts_with("xl/worksheets/_rels/") {
+            let mut text = String::new();
+            archive
+                .by_name(&name)
+                .unwrap()
+                .read_to_string(&mut text)
+                .unwrap();
+            drawing_relationships += text.matches("../drawings/drawing").count();
+        }
+    }
+    drop(archive);
+    assert_eq!(drawing_relationships, 1);
+
+    let model2 = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+    let worksheets = &model2.workbook.worksheets;
+    assert_eq!(
+        worksheets[sheet as usize].opaque_parts,
+        model.workbook.worksheets[sheet as usize].opaque_parts
+    );
+    assert_eq!(worksheets[index as usize].get_name(), "Copy");
+    assert!(worksheets[index as usize].opaque_parts.elements.is_empty());
+}
+
+#[test]
+fn test_save_to_xlsx_keeps_chart_sheets_and_header_pictures() {
+    let mut model = load_from_xlsx("tests/chartsheet.xlsx", "en", "UTC", "en").unwrap();
+    assert_eq!(model.workbook.get_worksheet_names(), ["Data"]);
+    // The new sheet takes the sheetId of the chart sheet, the chart sheet is renumbered
+    model.add_sheet("Extra").unwrap();
+    let temp_file_name = "temp_file_chartsheet.xlsx";
+    save_to_xlsx(&model, temp_file_name).unwrap();
+
+    let read_file = |archive: &mut zip::ZipArchive<fs::File>, name: &str| {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    };
+    let mut archive = zip::ZipArchive::new(fs::File::open(temp_file_name).unwrap()).unwrap();
+    for name in [
+        "xl/chartsheets/sheet1.xml",
+        "xl/chartsheets/_rels/sheet1.xml.rels",
+        "xl/drawings/drawing1.xml",
+        "xl/charts/chart1.xml",
+        "xl/drawings/vmlDrawingHF1.vml",
+        "xl/drawings/_rels/vmlDrawingHF1.vml.rels",
+        "xl/media/image1.png",
+    ] {
+        assert!(archive.by_name(name).is_ok(), "Missing {name}");
+    }
+    // Two worksheets, the styles and the shared strings go first
+    let workbook_rels = read_file(&mut archive, "xl/_rels/workbook.xml.rels");
+    assert!(workbook_rels.contains(r#"<Relationship Id="rId6" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chartsheet" Target="chartsheets/sheet1.xml"/>"#));
+    let workbook_xml = read_file(&mut archive, "xl/workbook.xml");
+    assert!(workbook_xml.contains(r#"<sheet name="Extra" sheetId="2" r:id="rId2"/><sheet name="Chart1" sheetId="3" r:id="rId6"></sheet></sheets>"#));
+    let content_types = read_file(&mut archive, "[Content_Types].xml");
+    assert!(content_types.contains(r#"<Override PartName="/xl/chartsheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.chartsheet+xml"/>"#));
+    let sheet_rels = read_file(&mut archive, "xl/worksheets/_rels/sheet1.xml.rels");
+    assert!(sheet_rels.contains(r#"Target="../drawings/vmlDrawingHF1.vml""#));
+    let sheet_xml = read_file(&mut archive, "xl/worksheets/sheet1.xml");
+    assert!(sheet_xml.contains(r#"<legacyDrawingHF r:id="rId1"></legacyDrawingHF>"#));
+    drop(archive);
+
+    // Loading and saving again keeps everything
+    let model2 = load_from_xlsx(temp_file_name, "en", "UTC", "en").unwrap();
+    fs::remove_file(temp_file_name).unwrap();
+    assert_eq!(model2.workbook.get_worksheet_names(), ["Data", "Extra"]);
+    let parts = &model.workbook.opaque_parts;
+    let parts2 = &model2.workbook.opaque_parts;
+    assert_eq!(parts.parts, parts2.parts);
+    assert_eq!(parts2.workbook_elements.len(), 1);
+    assert_eq!(
+        model.workbook.worksheets[0].opaque_parts,
+        model2.workbook.worksheets[0].opaque_parts
+    );
+}